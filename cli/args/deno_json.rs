@@ -1,12 +1,27 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::collections::HashMap;
 use std::collections::HashSet;
+use std::ops::Range;
+use std::path::Path;
+use std::path::PathBuf;
 
+use deno_config::deno_json::ConfigFile;
+use deno_config::deno_json::IgnoredCompilerOptions;
 use deno_config::deno_json::TsConfigForEmit;
+use deno_config::fs::DenoConfigFs;
+use deno_config::fs::RealDenoConfigFs;
+use deno_config::workspace::WorkspaceDirectory;
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
 use deno_core::serde_json;
+use deno_core::url::Url;
 use deno_semver::jsr::JsrDepPackageReq;
 use deno_semver::jsr::JsrPackageReqReference;
 use deno_semver::npm::NpmPackageReqReference;
+use indexmap::IndexMap;
+use serde::Deserialize;
 
 #[cfg(test)] // happens to only be used by the tests at the moment
 pub struct DenoConfigFsAdapter<'a>(
@@ -64,6 +79,108 @@ impl<'a> deno_config::fs::DenoConfigFs for DenoConfigFsAdapter<'a> {
   }
 }
 
+/// The options of the `"fmt"` configuration that are handled by the CLI,
+/// which `deno_config` would reject as unknown fields.
+const CLI_FMT_OPTIONS: &[&str] = &["sortImports"];
+
+/// Reads config files from the real file system for `deno_config`, hiding
+/// the options of their `"fmt"` configuration that only the CLI knows about.
+pub struct CliDenoConfigFs;
+
+impl DenoConfigFs for CliDenoConfigFs {
+  fn read_to_string_lossy(
+    &self,
+    path: &Path,
+  ) -> Result<String, std::io::Error> {
+    RealDenoConfigFs
+      .read_to_string_lossy(path)
+      .map(strip_cli_fmt_options)
+  }
+
+  fn stat_sync(
+    &self,
+    path: &Path,
+  ) -> Result<deno_config::fs::FsMetadata, std::io::Error> {
+    RealDenoConfigFs.stat_sync(path)
+  }
+
+  fn read_dir(
+    &self,
+    path: &Path,
+  ) -> Result<Vec<deno_config::fs::FsDirEntry>, std::io::Error> {
+    RealDenoConfigFs.read_dir(path)
+  }
+}
+
+/// Blanks out the options of the `"fmt"` configuration of a config file's
+/// text that are handled by the CLI, keeping the lines of everything else.
+pub fn strip_cli_fmt_options(text: String) -> String {
+  if !CLI_FMT_OPTIONS.iter().any(|name| text.contains(name)) {
+    return text;
+  }
+  let ranges = cli_fmt_option_ranges(&text);
+  if ranges.is_empty() {
+    return text;
+  }
+  let mut stripped = String::with_capacity(text.len());
+  let mut last_end = 0;
+  for range in ranges {
+    stripped.push_str(&text[last_end..range.start]);
+    stripped.extend(text[range.clone()].chars().map(|c| {
+      if matches!(c, '\r' | '\n') {
+        c
+      } else {
+        ' '
+      }
+    }));
+    last_end = range.end;
+  }
+  stripped.push_str(&text[last_end..]);
+  stripped
+}
+
+/// The ranges of the properties of the `"fmt"` configuration that are
+/// handled by the CLI, including the comma following them.
+fn cli_fmt_option_ranges(text: &str) -> Vec<Range<usize>> {
+  use jsonc_parser::ast::Value;
+
+  fn end_of_separator(text: &str, end: usize) -> usize {
+    let mut pos = end;
+    loop {
+      let rest = &text[pos..];
+      let trimmed = rest.trim_start();
+      pos += rest.len() - trimmed.len();
+      if trimmed.starts_with("//") {
+        pos += trimmed.find('\n').unwrap_or(trimmed.len());
+      } else if let Some(comment) = trimmed.strip_prefix("/*") {
+        pos += comment.find("*/").map(|i| i + 4).unwrap_or(trimmed.len());
+      } else if trimmed.starts_with(',') {
+        return pos + 1;
+      } else {
+        return end;
+      }
+    }
+  }
+
+  let Ok(ast) =
+    jsonc_parser::parse_to_ast(text, &Default::default(), &Default::default())
+  else {
+    return Vec::new();
+  };
+  let Some(Value::Object(root)) = &ast.value else {
+    return Vec::new();
+  };
+  let Some(fmt) = root.get_object("fmt") else {
+    return Vec::new();
+  };
+  fmt
+    .properties
+    .iter()
+    .filter(|prop| CLI_FMT_OPTIONS.contains(&prop.name.as_str()))
+    .map(|prop| prop.range.start..end_of_separator(text, prop.range.end))
+    .collect()
+}
+
 pub fn deno_json_deps(
   config: &deno_config::deno_json::ConfigFile,
 ) -> HashSet<JsrDepPackageReq> {
@@ -108,7 +225,11 @@ fn values_to_set<'a>(
 }
 
 pub fn check_warn_tsconfig(ts_config: &TsConfigForEmit) {
-  if let Some(ignored_options) = &ts_config.maybe_ignored_options {
+  if let Some(ignored_options) = ts_config
+    .maybe_ignored_options
+    .as_ref()
+    .and_then(without_resolver_compiler_options)
+  {
     log::warn!("{}", ignored_options);
   }
   let serde_json::Value::Object(obj) = &ts_config.ts_config.0 else {
@@ -121,3 +242,517 @@ pub fn check_warn_tsconfig(ts_config: &TsConfigForEmit) {
     );
   }
 }
+
+/// Reads a top level property of the config file that `deno_config`
+/// doesn't know about, which it would otherwise silently discard.
+pub fn read_config_file_property(
+  config_file: &ConfigFile,
+  name: &str,
+) -> Result<Option<serde_json::Value>, AnyError> {
+  let Ok(path) = config_file.specifier.to_file_path() else {
+    return Ok(None);
+  };
+  let text = std::fs::read_to_string(&path)
+    .with_context(|| format!("Failed reading {}", path.display()))?;
+  let value = jsonc_parser::parse_to_serde_value(&text, &Default::default())
+    .with_context(|| format!("Failed parsing {}", path.display()))?;
+  Ok(match value {
+    Some(serde_json::Value::Object(mut obj)) => obj.remove(name),
+    _ => None,
+  })
+}
+
+/// The `"plugins"` section of the config file.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PluginsConfig {
+  /// Specifiers of dprint Wasm plugins used by `deno fmt`.
+  pub fmt: Vec<String>,
+  /// Specifiers of JavaScript or TypeScript modules providing custom rules
+  /// used by `deno lint` and the language server.
+  pub lint: Vec<String>,
+  /// Specifiers of JavaScript or TypeScript modules extracting the scripts
+  /// embedded in framework files, like `.vue` components, for the language
+  /// server.
+  pub lsp: Vec<String>,
+}
+
+pub fn read_plugins_config(
+  config_file: &ConfigFile,
+) -> Result<PluginsConfig, AnyError> {
+  let Some(value) = read_config_file_property(config_file, "plugins")? else {
+    return Ok(PluginsConfig::default());
+  };
+  serde_json::from_value(value).with_context(|| {
+    format!(
+      "Invalid \"plugins\" configuration in {}",
+      config_file.specifier
+    )
+  })
+}
+
+/// The severity of a lint rule in the `"lintSeverity"` property of the config
+/// file.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum LintRuleSeverity {
+  /// The diagnostics of the rule are reported as warnings, which don't fail
+  /// `deno lint` unless there are more than `--max-warnings`.
+  Warn,
+  #[default]
+  Error,
+}
+
+/// Reads the `"lintSeverity"` property of the config file, mapping rule
+/// codes to their severity. Rules that aren't listed are errors.
+pub fn read_lint_severity_config(
+  config_file: &ConfigFile,
+) -> Result<HashMap<String, LintRuleSeverity>, AnyError> {
+  let Some(value) = read_config_file_property(config_file, "lintSeverity")?
+  else {
+    return Ok(HashMap::new());
+  };
+  serde_json::from_value(value).with_context(|| {
+    format!(
+      "Invalid \"lintSeverity\" configuration in {}. Expected an object mapping rule names to \"warn\" or \"error\".",
+      config_file.specifier
+    )
+  })
+}
+
+/// Reads the `"registry"` property of the config file, the URL of the JSR
+/// compatible registry that `deno publish` uploads packages to.
+pub fn read_registry_config(
+  config_file: &ConfigFile,
+) -> Result<Option<Url>, AnyError> {
+  let Some(value) = read_config_file_property(config_file, "registry")? else {
+    return Ok(None);
+  };
+  let invalid_registry = || {
+    format!(
+      "Invalid \"registry\" configuration in {}. Expected an http or https URL.",
+      config_file.specifier
+    )
+  };
+  let serde_json::Value::String(url) = value else {
+    bail!("{}", invalid_registry());
+  };
+  // ensure there is a trailing slash for the directory
+  let url = Url::parse(&format!("{}/", url.trim_end_matches('/')))
+    .with_context(invalid_registry)?;
+  if !matches!(url.scheme(), "http" | "https") {
+    bail!("{}", invalid_registry());
+  }
+  Ok(Some(url))
+}
+
+/// Reads the `"envFile"` property of the config file, one or more environment
+/// files loaded when `--env-file` isn't passed, relative to the config file.
+pub fn read_env_file_config(
+  config_file: &ConfigFile,
+) -> Result<Vec<PathBuf>, AnyError> {
+  #[derive(Deserialize)]
+  #[serde(untagged)]
+  enum EnvFileConfig {
+    Single(String),
+    Multiple(Vec<String>),
+  }
+
+  let Some(value) = read_config_file_property(config_file, "envFile")? else {
+    return Ok(Vec::new());
+  };
+  let env_files = match serde_json::from_value(value).with_context(|| {
+    format!(
+      "Invalid \"envFile\" configuration in {}. Expected a path or an array of paths.",
+      config_file.specifier
+    )
+  })? {
+    EnvFileConfig::Single(env_file) => vec![env_file],
+    EnvFileConfig::Multiple(env_files) => env_files,
+  };
+  let Ok(config_path) = config_file.specifier.to_file_path() else {
+    return Ok(Vec::new());
+  };
+  let config_dir = config_path.parent().unwrap();
+  Ok(
+    env_files
+      .into_iter()
+      .map(|env_file| config_dir.join(env_file))
+      .collect(),
+  )
+}
+
+/// The `"check"` section of the config file.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct CheckConfig {
+  /// Whether type errors in npm packages are reported.
+  pub npm: bool,
+  /// npm packages whose type errors aren't reported.
+  pub npm_skip: Vec<String>,
+}
+
+pub fn read_check_config(
+  config_file: &ConfigFile,
+) -> Result<CheckConfig, AnyError> {
+  let Some(value) = read_config_file_property(config_file, "check")? else {
+    return Ok(CheckConfig::default());
+  };
+  serde_json::from_value(value).with_context(|| {
+    format!(
+      "Invalid \"check\" configuration in {}",
+      config_file.specifier
+    )
+  })
+}
+
+/// Reads the `"sortImports"` option of the `"fmt"` configuration of the
+/// config file of a workspace directory, or else of the root config file.
+/// `deno_config` never sees it, see [`CliDenoConfigFs`].
+pub fn read_fmt_sort_imports_config(
+  dir: &WorkspaceDirectory,
+) -> Result<Option<bool>, AnyError> {
+  let config_files = dir
+    .maybe_deno_json()
+    .into_iter()
+    .chain(dir.workspace.root_deno_json());
+  for config_file in config_files {
+    let Some(fmt) = read_config_file_property(config_file, "fmt")? else {
+      continue;
+    };
+    match fmt.get("sortImports") {
+      Some(serde_json::Value::Bool(sort_imports)) => {
+        return Ok(Some(*sort_imports))
+      }
+      Some(_) => bail!(
+        "Invalid \"fmt.sortImports\" configuration in {}. Expected a boolean.",
+        config_file.specifier
+      ),
+      None => {}
+    }
+  }
+  Ok(None)
+}
+
+/// The `"importMaps"` of the config file, which are composed with its own
+/// import map.
+pub fn read_import_maps_config(
+  config_file: &ConfigFile,
+) -> Result<Vec<Url>, AnyError> {
+  let Some(value) = read_config_file_property(config_file, "importMaps")?
+  else {
+    return Ok(Vec::new());
+  };
+  let invalid_import_maps = || {
+    format!(
+      "Invalid \"importMaps\" configuration in {}. Expected an array of import map paths or URLs.",
+      config_file.specifier
+    )
+  };
+  let import_maps: Vec<String> =
+    serde_json::from_value(value).with_context(invalid_import_maps)?;
+  import_maps
+    .iter()
+    .map(|import_map| {
+      config_file
+        .specifier
+        .join(import_map)
+        .with_context(|| format!("Bad URL (\"{import_map}\") for import map."))
+    })
+    .collect()
+}
+
+/// The schemes that are handled by Deno itself, which can't have a handler in
+/// the `"schemes"` property of the config file.
+const BUILTIN_SCHEMES: &[&str] = &[
+  "blob", "data", "file", "http", "https", "jsr", "node", "npm",
+];
+
+/// The handler of a custom URL scheme in the `"schemes"` property of the
+/// config file. Other handlers, like ones running a program, can only be
+/// registered by embedders.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SchemeHandlerConfig {
+  /// A URL template that the specifiers are redirected to, like
+  /// `"https://modules.corp.example/{host}/{path}"`.
+  pub url: String,
+}
+
+/// Reads the `"schemes"` property of the config file, mapping custom URL
+/// schemes like `s3` or `corp` to the handler that fetches their modules.
+pub fn read_schemes_config(
+  config_file: &ConfigFile,
+) -> Result<IndexMap<String, SchemeHandlerConfig>, AnyError> {
+  let Some(value) = read_config_file_property(config_file, "schemes")? else {
+    return Ok(IndexMap::new());
+  };
+  let schemes: IndexMap<String, SchemeHandlerConfig> =
+    serde_json::from_value(value).with_context(|| {
+      format!(
+        "Invalid \"schemes\" configuration in {}. Expected an object mapping schemes to a \"url\" template.",
+        config_file.specifier
+      )
+    })?;
+  for scheme in schemes.keys() {
+    let is_valid_scheme = scheme.starts_with(|c: char| c.is_ascii_lowercase())
+      && scheme.chars().all(|c| {
+        c.is_ascii_lowercase()
+          || c.is_ascii_digit()
+          || matches!(c, '+' | '-' | '.')
+      });
+    if !is_valid_scheme {
+      bail!(
+        "Invalid scheme \"{}\" in the \"schemes\" configuration of {}. Expected a lowercase URL scheme without the colon.",
+        scheme,
+        config_file.specifier
+      );
+    }
+    if BUILTIN_SCHEMES.contains(&scheme.as_str()) {
+      bail!(
+        "The \"{}\" scheme is handled by Deno and can't be configured in the \"schemes\" configuration of {}.",
+        scheme,
+        config_file.specifier
+      );
+    }
+  }
+  Ok(schemes)
+}
+
+/// The `"compile"` section of the config file.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompileConfig {
+  /// Additional modules, files and directories to include in the executable
+  /// produced by `deno compile`, relative to the config file.
+  pub include: Vec<String>,
+}
+
+pub fn read_compile_config(
+  config_file: &ConfigFile,
+) -> Result<CompileConfig, AnyError> {
+  let Some(value) = read_config_file_property(config_file, "compile")? else {
+    return Ok(CompileConfig::default());
+  };
+  serde_json::from_value(value).with_context(|| {
+    format!(
+      "Invalid \"compile\" configuration in {}",
+      config_file.specifier
+    )
+  })
+}
+
+/// The `"coverage"` section of the config file.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct CoverageConfig {
+  /// Files, directories or globs of the modules included in coverage
+  /// reports, relative to the config file.
+  pub include: Vec<String>,
+  /// Files, directories or globs of the modules excluded from coverage
+  /// reports, relative to the config file.
+  pub exclude: Vec<String>,
+}
+
+pub fn read_coverage_config(
+  config_file: &ConfigFile,
+) -> Result<CoverageConfig, AnyError> {
+  let Some(value) = read_config_file_property(config_file, "coverage")? else {
+    return Ok(CoverageConfig::default());
+  };
+  serde_json::from_value(value).with_context(|| {
+    format!(
+      "Invalid \"coverage\" configuration in {}",
+      config_file.specifier
+    )
+  })
+}
+
+/// A task of the `"tasks"` section of the config file, which is either a
+/// command or an object that declares the tasks to run before it:
+///
+/// ```jsonc
+/// {
+///   "tasks": {
+///     "build": {
+///       "command": "deno run -A build.ts",
+///       "dependsOn": ["check", "lint"]
+///     }
+///   }
+/// }
+/// ```
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum TaskConfig {
+  Command(String),
+  #[serde(rename_all = "camelCase")]
+  Object {
+    command: String,
+    #[serde(default)]
+    depends_on: Vec<String>,
+  },
+}
+
+impl TaskConfig {
+  pub fn command(&self) -> &str {
+    match self {
+      TaskConfig::Command(command) => command,
+      TaskConfig::Object { command, .. } => command,
+    }
+  }
+
+  pub fn depends_on(&self) -> &[String] {
+    match self {
+      TaskConfig::Command(_) => &[],
+      TaskConfig::Object { depends_on, .. } => depends_on,
+    }
+  }
+}
+
+/// Reads the `"tasks"` section of the config file when a task uses the
+/// object form, which `deno_config` fails to parse. Returns `None` when all
+/// the tasks are commands.
+pub fn read_tasks_config(
+  config_file: &ConfigFile,
+) -> Result<Option<IndexMap<String, TaskConfig>>, AnyError> {
+  let Some(value) = read_config_file_property(config_file, "tasks")? else {
+    return Ok(None);
+  };
+  let has_object_task = value
+    .as_object()
+    .is_some_and(|tasks| tasks.values().any(|task| task.is_object()));
+  if !has_object_task {
+    return Ok(None);
+  }
+  serde_json::from_value(value).map(Some).with_context(|| {
+    format!(
+      "Invalid \"tasks\" configuration in {}",
+      config_file.specifier
+    )
+  })
+}
+
+/// The `"baseUrl"` and `"paths"` compiler options, which Deno doesn't pass
+/// to tsc, but honors when resolving the bare specifiers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TsConfigPathsConfig {
+  /// The `"baseUrl"` resolved from the directory of the config file.
+  pub base_url: Option<PathBuf>,
+  /// The directory that the substitutions of the `"paths"` are relative to,
+  /// which is the `"baseUrl"` when set and otherwise the directory of the
+  /// config file.
+  pub paths_dir: PathBuf,
+  /// The patterns of the `"paths"`, which have at most one `*` wildcard,
+  /// mapped to their substitutions in order of preference.
+  pub paths: IndexMap<String, Vec<String>>,
+}
+
+/// Compiler options that are reported as ignored by `deno_config`, but are
+/// honored by the resolver.
+const RESOLVER_COMPILER_OPTIONS: [&str; 2] = ["baseUrl", "paths"];
+
+/// Removes the compiler options that the resolver honors from the ones that
+/// are reported as ignored.
+pub fn without_resolver_compiler_options(
+  ignored_options: &IgnoredCompilerOptions,
+) -> Option<IgnoredCompilerOptions> {
+  let items = ignored_options
+    .items
+    .iter()
+    .filter(|item| !RESOLVER_COMPILER_OPTIONS.contains(&item.as_str()))
+    .cloned()
+    .collect::<Vec<_>>();
+  if items.is_empty() {
+    return None;
+  }
+  Some(IgnoredCompilerOptions {
+    items,
+    maybe_specifier: ignored_options.maybe_specifier.clone(),
+  })
+}
+
+/// Reads the `"baseUrl"` and `"paths"` of the `"compilerOptions"` of the
+/// config file. Returns `None` when neither is set.
+pub fn read_tsconfig_paths_config(
+  config_file: &ConfigFile,
+) -> Result<Option<TsConfigPathsConfig>, AnyError> {
+  #[derive(Default, Deserialize)]
+  #[serde(default, rename_all = "camelCase")]
+  struct PathsCompilerOptions {
+    base_url: Option<String>,
+    paths: Option<IndexMap<String, Vec<String>>>,
+  }
+
+  let Some(compiler_options) = &config_file.json.compiler_options else {
+    return Ok(None);
+  };
+  let options: PathsCompilerOptions =
+    serde_json::from_value(compiler_options.clone()).with_context(|| {
+      format!(
+        "Invalid \"baseUrl\" or \"paths\" compiler option in {}. Expected a string and an object mapping patterns to arrays of paths.",
+        config_file.specifier
+      )
+    })?;
+  if options.base_url.is_none() && options.paths.is_none() {
+    return Ok(None);
+  }
+  let Ok(config_dir) = config_file.specifier.join(".") else {
+    return Ok(None);
+  };
+  let Ok(config_dir) = config_dir.to_file_path() else {
+    return Ok(None);
+  };
+  let paths = options.paths.unwrap_or_default();
+  for (pattern, substitutions) in &paths {
+    for value in std::iter::once(pattern).chain(substitutions) {
+      if value.matches('*').count() > 1 {
+        bail!(
+          "Invalid \"paths\" compiler option in {}. \"{}\" can have at most one '*' character.",
+          config_file.specifier,
+          value
+        );
+      }
+    }
+  }
+  let base_url = options
+    .base_url
+    .map(|base_url| deno_core::normalize_path(config_dir.join(base_url)));
+  Ok(Some(TsConfigPathsConfig {
+    paths_dir: base_url.clone().unwrap_or(config_dir),
+    base_url,
+    paths,
+  }))
+}
+
+#[cfg(test)]
+mod test {
+  use deno_core::serde_json::json;
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  #[test]
+  fn test_strip_cli_fmt_options() {
+    let text = r#"{
+  "fmt": {
+    "sortImports": true, // sorted
+    "lineWidth": 100,
+    /* last */ "sortImports": false
+  },
+  "sortImports": true
+}"#;
+    let stripped = strip_cli_fmt_options(text.to_string());
+    assert_eq!(stripped.lines().count(), text.lines().count());
+    let value =
+      jsonc_parser::parse_to_serde_value(&stripped, &Default::default())
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+      value,
+      json!({ "fmt": { "lineWidth": 100 }, "sortImports": true })
+    );
+
+    let text = r#"{ "fmt": { "lineWidth": 100 } }"#;
+    assert_eq!(strip_cli_fmt_options(text.to_string()), text);
+  }
+}
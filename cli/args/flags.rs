@@ -10,6 +10,7 @@ use std::num::NonZeroUsize;
 use std::path::Path;
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
 
 use clap::builder::styling::AnsiColor;
 use clap::builder::FalseyValueParser;
@@ -23,7 +24,9 @@ use clap::Command;
 use clap::ValueHint;
 use color_print::cstr;
 use deno_config::deno_json::NodeModulesDirMode;
+use deno_config::glob::is_glob_pattern;
 use deno_config::glob::FilePatterns;
+use deno_config::glob::GlobPattern;
 use deno_config::glob::PathOrPatternSet;
 use deno_core::anyhow::bail;
 use deno_core::anyhow::Context;
@@ -41,6 +44,7 @@ use serde::Deserialize;
 use serde::Serialize;
 
 use crate::args::resolve_no_prompt;
+use crate::args::LintReporterKind;
 use crate::util::fs::canonicalize_path;
 
 use super::flags_net;
@@ -97,6 +101,8 @@ pub struct RemoveFlags {
 pub struct BenchFlags {
   pub files: FileFlags,
   pub filter: Option<String>,
+  pub tags: Option<String>,
+  pub skip_tags: Option<String>,
   pub json: bool,
   pub no_run: bool,
   pub watch: Option<WatchFlags>,
@@ -105,6 +111,10 @@ pub struct BenchFlags {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CacheFlags {
   pub files: Vec<String>,
+  /// Write the cached dependencies of the files to this bundle.
+  pub export: Option<String>,
+  /// Populate the cache from this bundle before caching the files.
+  pub import: Option<String>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -112,6 +122,26 @@ pub struct CheckFlags {
   pub files: Vec<String>,
   pub doc: bool,
   pub doc_only: bool,
+  /// Report type errors in npm packages.
+  pub npm: bool,
+  /// npm packages whose type errors aren't reported.
+  pub npm_skip: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BundlePlatform {
+  #[default]
+  Deno,
+  Browser,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BundleFlags {
+  pub entrypoints: Vec<String>,
+  pub output: Option<String>,
+  pub outdir: Option<String>,
+  pub external: Vec<String>,
+  pub platform: BundlePlatform,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -123,6 +153,10 @@ pub struct CompileFlags {
   pub no_terminal: bool,
   pub icon: Option<String>,
   pub include: Vec<String>,
+  pub windows_version_info: Vec<(String, String)>,
+  pub sign_command: Option<String>,
+  pub compress: bool,
+  pub strip: bool,
 }
 
 impl CompileFlags {
@@ -135,8 +169,21 @@ impl CompileFlags {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct CompletionsFlags {
-  pub buf: Box<[u8]>,
+pub enum CompletionsFlags {
+  /// A completion script for a shell.
+  Static(Box<[u8]>),
+  /// The values that the completion scripts ask for at the prompt.
+  Dynamic(DynamicCompletionsKind),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DynamicCompletionsKind {
+  /// Names of the tasks in the config files, for `deno task`.
+  Tasks,
+  /// Names of the tests in the test modules, for `deno test --filter`.
+  Tests,
+  /// Names of the globally installed executables, for `deno uninstall`.
+  Installed,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Default)]
@@ -205,6 +252,7 @@ pub struct FmtFlags {
   pub single_quote: Option<bool>,
   pub prose_wrap: Option<String>,
   pub no_semicolons: Option<bool>,
+  pub sort_imports: Option<bool>,
   pub watch: Option<WatchFlags>,
   pub unstable_css: bool,
   pub unstable_html: bool,
@@ -224,12 +272,15 @@ pub struct InitFlags {
   pub dir: Option<String>,
   pub lib: bool,
   pub serve: bool,
+  pub template: Option<String>,
+  pub ci: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct InfoFlags {
   pub json: bool,
   pub file: Option<String>,
+  pub why: Option<String>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -267,20 +318,29 @@ pub struct JSONReferenceFlags {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct JupyterFlags {
   pub install: bool,
+  pub name: Option<String>,
+  pub display: Option<String>,
   pub kernel: bool,
   pub conn_file: Option<String>,
 }
 
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LspFlags {
+  pub profile: Option<String>,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct UninstallFlagsGlobal {
   pub name: String,
   pub root: Option<String>,
+  pub clean_cache: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum UninstallKind {
   Local(RemoveFlags),
   Global(UninstallFlagsGlobal),
+  ListGlobal { root: Option<String> },
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -293,11 +353,14 @@ pub struct LintFlags {
   pub files: FileFlags,
   pub rules: bool,
   pub fix: bool,
+  pub fix_dry_run: bool,
   pub maybe_rules_tags: Option<Vec<String>>,
   pub maybe_rules_include: Option<Vec<String>>,
   pub maybe_rules_exclude: Option<Vec<String>>,
   pub json: bool,
   pub compact: bool,
+  pub output: Option<LintReporterKind>,
+  pub max_warnings: Option<usize>,
   pub watch: Option<WatchFlags>,
 }
 
@@ -364,6 +427,7 @@ pub struct WatchFlags {
   pub hmr: bool,
   pub no_clear_screen: bool,
   pub exclude: Vec<String>,
+  pub debounce_ms: Option<u64>,
 }
 
 #[derive(Clone, Default, Debug, Eq, PartialEq)]
@@ -372,6 +436,7 @@ pub struct WatchFlagsWithPaths {
   pub paths: Vec<String>,
   pub no_clear_screen: bool,
   pub exclude: Vec<String>,
+  pub debounce_ms: Option<u64>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -400,6 +465,8 @@ pub struct TestFlags {
   pub files: FileFlags,
   pub permit_no_files: bool,
   pub filter: Option<String>,
+  pub tags: Option<String>,
+  pub skip_tags: Option<String>,
   pub shuffle: Option<u64>,
   pub concurrent_jobs: Option<NonZeroUsize>,
   pub trace_leaks: bool,
@@ -418,6 +485,7 @@ pub struct UpgradeFlags {
   pub version: Option<String>,
   pub output: Option<String>,
   pub version_or_hash_or_channel: Option<String>,
+  pub channel: Option<String>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -439,7 +507,7 @@ pub enum DenoSubcommand {
   Add(AddFlags),
   Remove(RemoveFlags),
   Bench(BenchFlags),
-  Bundle,
+  Bundle(BundleFlags),
   Cache(CacheFlags),
   Check(CheckFlags),
   Clean,
@@ -455,7 +523,7 @@ pub enum DenoSubcommand {
   JSONReference(JSONReferenceFlags),
   Jupyter(JupyterFlags),
   Uninstall(UninstallFlags),
-  Lsp,
+  Lsp(LspFlags),
   Lint(LintFlags),
   Repl(ReplFlags),
   Run(RunFlags),
@@ -482,7 +550,7 @@ impl DenoSubcommand {
         | Self::Jupyter(_)
         | Self::Repl(_)
         | Self::Bench(_)
-        | Self::Lsp
+        | Self::Lsp(_)
     )
   }
 }
@@ -559,6 +627,15 @@ pub enum PackagesAllowedScripts {
   None,
 }
 
+fn parse_version_info_entry(s: &str) -> Result<(String, String), AnyError> {
+  match s.split_once('=') {
+    Some((key, value)) if !key.is_empty() => {
+      Ok((key.to_string(), value.to_string()))
+    }
+    _ => bail!("Invalid version info entry '{}'. Expected KEY=VALUE", s),
+  }
+}
+
 fn parse_packages_allowed_scripts(s: &str) -> Result<String, AnyError> {
   if !s.starts_with("npm:") {
     bail!("Invalid package for --allow-scripts: '{}'. An 'npm:' specifier is required", s);
@@ -596,16 +673,21 @@ pub struct Flags {
   pub type_check_mode: TypeCheckMode,
   pub config_flag: ConfigFlag,
   pub node_modules_dir: Option<NodeModulesDirMode>,
+  /// Custom conditions of the resolution of the exports and imports of
+  /// package.json, like the `--conditions` flag of Node.
+  pub node_conditions: Vec<String>,
   pub vendor: Option<bool>,
   pub enable_op_summary_metrics: bool,
   pub enable_testing_features: bool,
   pub ext: Option<String>,
   pub ignore: Vec<String>,
   pub import_map_path: Option<String>,
-  pub env_file: Option<String>,
+  pub env_file: Option<Vec<String>>,
   pub inspect_brk: Option<SocketAddr>,
   pub inspect_wait: Option<SocketAddr>,
   pub inspect: Option<SocketAddr>,
+  pub cpu_prof: Option<ProfileFlags>,
+  pub heap_prof: Option<ProfileFlags>,
   pub location: Option<Url>,
   pub lock: Option<String>,
   pub log_level: Option<Level>,
@@ -623,6 +705,18 @@ pub struct Flags {
   pub allow_scripts: PackagesAllowedScripts,
 }
 
+/// Options of `--cpu-prof` or `--heap-prof`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ProfileFlags {
+  /// Directory the profiles are written to, the current directory by default.
+  pub dir: Option<String>,
+  /// File name of the profile, generated by default.
+  pub name: Option<String>,
+  /// Sampling interval, in microseconds for CPU profiles and in bytes for
+  /// heap profiles.
+  pub interval: Option<u64>,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Default, Serialize, Deserialize)]
 pub struct PermissionFlags {
   pub allow_all: bool,
@@ -995,50 +1089,36 @@ impl Flags {
     self.permissions.allow_ffi = None;
   }
 
+  /// The `--watch-exclude` and `--watch-debounce` values of the subcommand
+  /// when it runs in watch mode.
+  fn watch_exclude_and_debounce(&self) -> Option<(&[String], Option<u64>)> {
+    match &self.subcommand {
+      DenoSubcommand::Run(RunFlags {
+        watch: Some(watch), ..
+      })
+      | DenoSubcommand::Serve(ServeFlags {
+        watch: Some(watch), ..
+      })
+      | DenoSubcommand::Test(TestFlags {
+        watch: Some(watch), ..
+      }) => Some((&watch.exclude, watch.debounce_ms)),
+      DenoSubcommand::Bench(BenchFlags {
+        watch: Some(watch), ..
+      })
+      | DenoSubcommand::Lint(LintFlags {
+        watch: Some(watch), ..
+      })
+      | DenoSubcommand::Fmt(FmtFlags {
+        watch: Some(watch), ..
+      }) => Some((&watch.exclude, watch.debounce_ms)),
+      _ => None,
+    }
+  }
+
   pub fn resolve_watch_exclude_set(
     &self,
   ) -> Result<PathOrPatternSet, AnyError> {
-    if let DenoSubcommand::Run(RunFlags {
-      watch:
-        Some(WatchFlagsWithPaths {
-          exclude: excluded_paths,
-          ..
-        }),
-      ..
-    })
-    | DenoSubcommand::Bench(BenchFlags {
-      watch:
-        Some(WatchFlags {
-          exclude: excluded_paths,
-          ..
-        }),
-      ..
-    })
-    | DenoSubcommand::Test(TestFlags {
-      watch:
-        Some(WatchFlagsWithPaths {
-          exclude: excluded_paths,
-          ..
-        }),
-      ..
-    })
-    | DenoSubcommand::Lint(LintFlags {
-      watch:
-        Some(WatchFlags {
-          exclude: excluded_paths,
-          ..
-        }),
-      ..
-    })
-    | DenoSubcommand::Fmt(FmtFlags {
-      watch:
-        Some(WatchFlags {
-          exclude: excluded_paths,
-          ..
-        }),
-      ..
-    }) = &self.subcommand
-    {
+    if let Some((excluded_paths, _)) = self.watch_exclude_and_debounce() {
       let cwd = std::env::current_dir()?;
       PathOrPatternSet::from_exclude_relative_path_or_patterns(
         &cwd,
@@ -1049,6 +1129,41 @@ impl Flags {
       Ok(PathOrPatternSet::default())
     }
   }
+
+  /// How long the file watcher waits for file changes to settle before
+  /// restarting, when set with `--watch-debounce`.
+  pub fn watch_debounce(&self) -> Option<Duration> {
+    self
+      .watch_exclude_and_debounce()
+      .and_then(|(_, debounce_ms)| debounce_ms)
+      .map(Duration::from_millis)
+  }
+
+  /// The globs passed to `--watch=<FILES>`. Changes to files matching them
+  /// restart the process, no matter if they're part of the module graph.
+  pub fn resolve_watch_patterns(&self) -> Result<Vec<GlobPattern>, AnyError> {
+    let paths = match &self.subcommand {
+      DenoSubcommand::Run(RunFlags {
+        watch: Some(watch), ..
+      })
+      | DenoSubcommand::Serve(ServeFlags {
+        watch: Some(watch), ..
+      })
+      | DenoSubcommand::Test(TestFlags {
+        watch: Some(watch), ..
+      }) => &watch.paths,
+      _ => return Ok(Vec::new()),
+    };
+    let cwd = std::env::current_dir()?;
+    paths
+      .iter()
+      .filter(|path| is_glob_pattern(path))
+      .map(|path| {
+        GlobPattern::from_relative(&cwd, path)
+          .with_context(|| format!("Failed resolving watch pattern '{path}'."))
+      })
+      .collect()
+  }
 }
 
 static ENV_VARIABLES_HELP: &str = cstr!(
@@ -1240,7 +1355,7 @@ pub fn flags_from_vec(args: Vec<OsString>) -> clap::error::Result<Flags> {
       "add" => add_parse(&mut flags, &mut m),
       "remove" => remove_parse(&mut flags, &mut m),
       "bench" => bench_parse(&mut flags, &mut m)?,
-      "bundle" => bundle_parse(&mut flags, &mut m),
+      "bundle" => bundle_parse(&mut flags, &mut m)?,
       "cache" => cache_parse(&mut flags, &mut m)?,
       "check" => check_parse(&mut flags, &mut m)?,
       "clean" => clean_parse(&mut flags, &mut m),
@@ -1611,6 +1726,20 @@ If you specify a directory instead of a file, the path is expanded to all contai
           "Run benchmarks with this string or regexp pattern in the bench name",
         ),
       )
+      .arg(
+        Arg::new("tags")
+          .long("tags")
+          .require_equals(true)
+          .value_name("EXPR")
+          .help("Only run benchmarks whose tags match this expression, e.g. 'slow & !windows'"),
+      )
+      .arg(
+        Arg::new("skip-tags")
+          .long("skip-tags")
+          .require_equals(true)
+          .value_name("EXPR")
+          .help("Skip benchmarks whose tags match this expression"),
+      )
       .arg(
         Arg::new("files")
           .help("List of file names to run")
@@ -1625,6 +1754,7 @@ If you specify a directory instead of a file, the path is expanded to all contai
       )
       .arg(watch_arg(false))
       .arg(watch_exclude_arg())
+      .arg(watch_debounce_arg())
       .arg(no_clear_screen_arg())
       .arg(script_arg().last(true))
       .arg(env_file_arg())
@@ -1633,10 +1763,73 @@ If you specify a directory instead of a file, the path is expanded to all contai
 }
 
 fn bundle_subcommand() -> Command {
-  command("bundle",  "⚠️ `deno bundle` was removed in Deno 2.
+  command(
+    "bundle",
+    cstr!("Bundle modules and their dependencies into JavaScript files for the browser or Deno.
 
-See the Deno 1.x to 2.x Migration Guide for migration instructions: https://docs.deno.com/runtime/manual/advanced/migrate_deprecations", UnstableArgsConfig::ResolutionOnly)
-    .hide(true)
+Print the bundle of a module to standard output:
+  <p(245)>deno bundle main.ts</>
+
+Write the bundle to a file:
+  <p(245)>deno bundle -o dist/main.js main.ts</>
+
+With several entrypoints or dynamic imports, a bundle is written to the output directory for each
+entrypoint and for each dynamically imported module, so the code is only loaded when it is used.
+Modules used by several of those bundles are written once to a common bundle that they import:
+  <p(245)>deno bundle --outdir=dist main.ts worker.ts</>
+
+Unused code is removed from the bundles. npm and node specifiers aren't bundled and are imported at runtime.
+With <c>--platform=deno</> (the default) they're written as resolved by the import map, while with
+<c>--platform=browser</> they're kept as written in the source for an import map of the page to resolve.
+Other specifiers are kept as well when listed with <c>--external</>, which supports a trailing <c>*</> wildcard:
+  <p(245)>deno bundle --platform=browser --external=https://esm.sh/* main.ts</>
+
+The modules are type-checked before bundling unless <c>--no-check</> is passed."),
+    UnstableArgsConfig::ResolutionOnly,
+  )
+  .defer(|cmd| {
+    compile_args(cmd)
+      .arg(check_arg(true))
+      .arg(
+        Arg::new("output")
+          .long("output")
+          .short('o')
+          .value_name("FILE")
+          .help("Write the bundle to this file instead of standard output")
+          .value_hint(ValueHint::FilePath),
+      )
+      .arg(
+        Arg::new("outdir")
+          .long("outdir")
+          .value_name("DIR")
+          .help("Write the bundles to this directory, which is required when more than one bundle is created")
+          .conflicts_with("output")
+          .value_hint(ValueHint::DirPath),
+      )
+      .arg(
+        Arg::new("external")
+          .long("external")
+          .value_name("SPECIFIER")
+          .require_equals(true)
+          .action(ArgAction::Append)
+          .value_delimiter(',')
+          .help("Specifiers to leave as imports in the bundles rather than bundling them"),
+      )
+      .arg(
+        Arg::new("platform")
+          .long("platform")
+          .require_equals(true)
+          .value_parser(["deno", "browser"])
+          .default_value("deno")
+          .help("The platform the bundles are loaded on"),
+      )
+      .arg(
+        Arg::new("entrypoints")
+          .num_args(1..)
+          .required_unless_present("help")
+          .value_hint(ValueHint::FilePath),
+      )
+  })
 }
 
 fn cache_subcommand() -> Command {
@@ -1649,6 +1842,10 @@ Download and compile a module with all of its static dependencies and save them
 
 Future runs of this module will trigger no downloads or compilation unless --reload is specified
 
+Pack the remote and npm dependencies into a bundle that another machine can import to run with --cached-only without network access:
+  <p(245)>deno cache --export deps.tar main.ts</>
+  <p(245)>deno cache --import deps.tar</>
+
 <y>Read more:</> <c>https://docs.deno.com/go/cache</>"),
     UnstableArgsConfig::ResolutionOnly,
 )
@@ -1659,7 +1856,23 @@ Future runs of this module will trigger no downloads or compilation unless --rel
       .arg(
         Arg::new("file")
           .num_args(1..)
-          .required_unless_present("help")
+          .required_unless_present_any(["help", "import"])
+          .value_hint(ValueHint::FilePath),
+      )
+      .arg(
+        Arg::new("export")
+          .long("export")
+          .value_name("FILE")
+          .help("Write the remote and npm dependencies of the files to a bundle")
+          .requires("file")
+          .conflicts_with("import")
+          .value_hint(ValueHint::FilePath),
+      )
+      .arg(
+        Arg::new("import")
+          .long("import")
+          .value_name("FILE")
+          .help("Populate the cache with the dependencies of a bundle")
           .value_hint(ValueHint::FilePath),
       )
       .arg(frozen_lockfile_arg())
@@ -1681,6 +1894,13 @@ fn check_subcommand() -> Command {
 
   <p(245)>deno check jsr:@std/http/file-server</>
 
+Type errors in npm packages are not reported unless --npm or --all is specified. Use --npm-skip to ignore
+the errors of some packages:
+
+  <p(245)>deno check --npm --npm-skip=some-untyped-package main.ts</>
+
+These can also be set in the <c>check</> section of the config file with the <c>npm</> and <c>npmSkip</> fields.
+
 Unless --reload is specified, this command will not re-download already cached dependencies
 
 <y>Read more:</> <c>https://docs.deno.com/go/check</>"),
@@ -1704,6 +1924,21 @@ Unless --reload is specified, this command will not re-download already cached d
             .conflicts_with("no-remote")
             .hide(true)
         )
+        .arg(
+          Arg::new("npm")
+            .long("npm")
+            .help("Type-check npm packages as well as local modules")
+            .action(ArgAction::SetTrue)
+        )
+        .arg(
+          Arg::new("npm-skip")
+            .long("npm-skip")
+            .help("Don't report type errors in these npm packages. Implies --npm")
+            .value_name("PACKAGES")
+            .require_equals(true)
+            .use_value_delimiter(true)
+            .action(ArgAction::Append)
+        )
         .arg(
           Arg::new("doc")
             .long("doc")
@@ -1737,6 +1972,18 @@ fn compile_subcommand() -> Command {
 
 Any flags specified which affect runtime behavior will be applied to the resulting binary.
 
+Embed static files and directories, which are packed into the executable's virtual file system:
+  <p(245)>deno compile --include ./templates --include ./public main.ts</>
+
+Shrink the executable by compressing its contents and leaving out source maps:
+  <p(245)>deno compile --compress --strip main.ts</>
+
+Set the version resource of a Windows executable and sign it once it's built:
+  <p(245)>deno compile --target x86_64-pc-windows-msvc --windows-version-info=ProductName=MyApp --sign-command='signtool sign /fd SHA256 /a' main.ts</>
+
+The executable caches the compiled V8 code of its modules in <c>$DENO_DIR</> when it first runs, so later runs
+don't parse and compile them again. The modules are still evaluated on every start. Opt out with <c>--no-code-cache</>.
+
 Cross-compiling to different target architectures is supported using the <c>--target</> flag.
 On the first invocation with deno will download the proper binary and cache it in <c>$DENO_DIR</>.
 
@@ -1751,13 +1998,14 @@ On the first invocation with deno will download the proper binary and cache it i
         Arg::new("include")
           .long("include")
           .help(
-            cstr!("Includes an additional module in the compiled executable's module graph.
+            cstr!("Includes an additional module or file/directory in the compiled executable.
   <p(245)>Use this flag if a dynamically imported module or a web worker main module
-  fails to load in the executable. This flag can be passed multiple times,
-  to include multiple additional modules.</>",
+  fails to load in the executable, or to embed files like templates and web assets
+  that the program reads with <c>Deno.readFile</>. This flag can be passed multiple
+  times, to include multiple additional modules and files.</>",
           ))
           .action(ArgAction::Append)
-          .value_hint(ValueHint::FilePath)
+          .value_hint(ValueHint::AnyPath)
           .help_heading(COMPILE_HEADING),
       )
       .arg(
@@ -1789,6 +2037,22 @@ On the first invocation with deno will download the proper binary and cache it i
           .action(ArgAction::SetTrue)
           .help_heading(COMPILE_HEADING),
       )
+      .arg(
+        Arg::new("compress")
+          .long("compress")
+          .help(cstr!("Compress the embedded modules and files with zstd.
+  <p(245)>This makes the executable smaller, at the cost of decompressing them on startup</>"))
+          .action(ArgAction::SetTrue)
+          .help_heading(COMPILE_HEADING),
+      )
+      .arg(
+        Arg::new("strip")
+          .long("strip")
+          .help(cstr!("Don't embed source maps in the executable.
+  <p(245)>Stack traces then point to the emitted JavaScript rather than the original source</>"))
+          .action(ArgAction::SetTrue)
+          .help_heading(COMPILE_HEADING),
+      )
       .arg(
         Arg::new("icon")
           .long("icon")
@@ -1796,8 +2060,34 @@ On the first invocation with deno will download the proper binary and cache it i
           .value_parser(value_parser!(String))
           .help_heading(COMPILE_HEADING),
       )
+      .arg(
+        Arg::new("windows-version-info")
+          .long("windows-version-info")
+          .help(cstr!("Set a version resource string of the executable on Windows, e.g. <c>ProductName=MyApp</>.
+  <p(245)>Numeric <c>FileVersion</> and <c>ProductVersion</> values are also used as the file's version.
+  This flag can be passed multiple times, to set multiple strings.</>"))
+          .value_name("KEY=VALUE")
+          .require_equals(true)
+          .action(ArgAction::Append)
+          .value_parser(parse_version_info_entry)
+          .help_heading(COMPILE_HEADING),
+      )
+      .arg(
+        Arg::new("sign-command")
+          .long("sign-command")
+          .help(cstr!("A command that signs the executable, run with the executable's path as its last argument.
+  <p(245)>The executable is only written to the output path once signing succeeds,
+  e.g. <c>--sign-command='signtool sign /fd SHA256 /a'</> or <c>--sign-command='codesign --force -s MyIdentity'</></>"))
+          .value_name("COMMAND")
+          .require_equals(true)
+          .value_parser(value_parser!(String))
+          .help_heading(COMPILE_HEADING),
+      )
       .arg(executable_ext_arg())
       .arg(env_file_arg())
+      .arg(no_code_cache_arg().help(
+        "Don't cache the V8 code of the executable's modules in DENO_DIR on its first run",
+      ))
       .arg(
         script_arg()
           .required_unless_present("help")
@@ -1818,11 +2108,22 @@ fn completions_subcommand() -> Command {
     UnstableArgsConfig::None,
   )
   .defer(|cmd| {
-    cmd.disable_help_subcommand(true).arg(
-      Arg::new("shell")
-        .value_parser(["bash", "fish", "powershell", "zsh", "fig"])
-        .required_unless_present("help"),
-    )
+    cmd
+      .disable_help_subcommand(true)
+      .arg(
+        Arg::new("shell")
+          .value_parser(["bash", "fish", "powershell", "zsh", "fig"])
+          .required_unless_present_any(["help", "dynamic"]),
+      )
+      // called by the bash, fish and zsh completion scripts
+      .arg(
+        Arg::new("dynamic")
+          .long("dynamic")
+          .require_equals(true)
+          .value_parser(["tasks", "tests", "installed"])
+          .conflicts_with("shell")
+          .hide(true),
+      )
   })
 }
 
@@ -1930,9 +2231,15 @@ Output documentation to standard output:
 Output documentation in HTML format:
     <p(245)>deno doc --html --name=\"My library\" ./path/to/module.ts</>
 
-Lint a module for documentation diagnostics:
+The generated site is self-contained and searchable, so it can be published as is, e.g. to GitHub Pages:
+    <p(245)>deno doc --html --output=docs --strip-trailing-html ./path/to/module.ts</>
+
+Lint a module for documentation diagnostics, like exported symbols missing JSDoc or broken <c>{@link}</> references:
     <p(245)>deno doc --lint ./path/to/module.ts</>
 
+Type-check the code examples in the documentation:
+    <p(245)>deno check --doc-only ./path/to/module.ts</>
+
 Target a specific symbol:
     <p(245)>deno doc ./path/to/module.ts MyClass.someField</>
 
@@ -2099,6 +2406,8 @@ Supported file types are:
 Supported file types which are behind corresponding unstable flags (see formatting options):
   <p(245)>HTML, CSS, SCSS, SASS, LESS, YAML, Svelte, Vue, Astro and Angular</>
 
+Other file types can be formatted with dprint Wasm plugins listed under `plugins.fmt` in the config file.
+
 Format stdin and write to stdout:
   <p(245)>cat file.ts | deno fmt -</>
 
@@ -2151,6 +2460,7 @@ Ignore formatting a file by adding an ignore comment at the top of the file:
       )
       .arg(watch_arg(false))
       .arg(watch_exclude_arg())
+      .arg(watch_debounce_arg())
       .arg(no_clear_screen_arg())
       .arg(
         Arg::new("use-tabs")
@@ -2213,6 +2523,18 @@ Ignore formatting a file by adding an ignore comment at the top of the file:
           )
           .help_heading(FMT_HEADING),
       )
+      .arg(
+        Arg::new("sort-imports")
+          .long("sort-imports")
+          .num_args(0..=1)
+          .value_parser(value_parser!(bool))
+          .default_missing_value("true")
+          .require_equals(true)
+          .help(
+            cstr!("Sort, group and deduplicate import declarations <p(245)>[default: false]</>"),
+          )
+          .help_heading(FMT_HEADING),
+      )
       .arg(
         Arg::new("unstable-css")
           .long("unstable-css")
@@ -2249,25 +2571,58 @@ Ignore formatting a file by adding an ignore comment at the top of the file:
 }
 
 fn init_subcommand() -> Command {
-  command("init", "Initialize a new project", UnstableArgsConfig::None).defer(
-    |cmd| {
-      cmd
-        .arg(Arg::new("dir").value_hint(ValueHint::DirPath))
-        .arg(
-          Arg::new("lib")
-            .long("lib")
-            .help("Generate an example library project")
-            .action(ArgAction::SetTrue),
-        )
-        .arg(
-          Arg::new("serve")
-            .long("serve")
-            .help("Generate an example project for `deno serve`")
-            .conflicts_with("lib")
-            .action(ArgAction::SetTrue),
-        )
-    },
+  command(
+    "init",
+    cstr!("Initialize a new project.
+
+  <p(245)>deno init</>
+  <p(245)>deno init --lib my_lib</>
+
+Other kinds of projects are generated with <c>--template</>:
+  <p(245)>deno init --template=serve</>    <p(245)># a web server for `deno serve`</>
+  <p(245)>deno init --template=lib</>      <p(245)># a library to publish to JSR</>
+  <p(245)>deno init --template=cli</>      <p(245)># a command line program</>
+  <p(245)>deno init --template=workspace</> <p(245)># a workspace with several packages</>
+
+A template can also be fetched from a URL or a path. It's a JSON file with a <c>files</>
+object mapping the relative paths of the files to create to their contents:
+  <p(245)>deno init --template=https://example.com/template.json</>
+
+Add <c>--ci</> to also generate a GitHub Actions workflow."),
+    UnstableArgsConfig::None,
   )
+  .defer(|cmd| {
+    cmd
+      .arg(Arg::new("dir").value_hint(ValueHint::DirPath))
+      .arg(
+        Arg::new("lib")
+          .long("lib")
+          .help("Generate an example library project")
+          .action(ArgAction::SetTrue),
+      )
+      .arg(
+        Arg::new("serve")
+          .long("serve")
+          .help("Generate an example project for `deno serve`")
+          .conflicts_with("lib")
+          .action(ArgAction::SetTrue),
+      )
+      .arg(
+        Arg::new("template")
+          .long("template")
+          .require_equals(true)
+          .value_name("NAME_OR_URL")
+          .help("Generate the project from a template: serve, lib, cli, workspace, or a URL or path to a template file")
+          .conflicts_with_all(["lib", "serve"])
+          .value_hint(ValueHint::Url),
+      )
+      .arg(
+        Arg::new("ci")
+          .long("ci")
+          .help("Generate a GitHub Actions workflow that lints and tests the project")
+          .action(ArgAction::SetTrue),
+      )
+  })
 }
 
 fn info_subcommand() -> Command {
@@ -2283,6 +2638,13 @@ The following information is shown:
   emit: Local path of compiled source code (TypeScript only)
   dependencies: Dependency tree of the source file
 
+With <c>--json</>, each module also lists its emitted size and the total size of
+everything it depends on, and npm packages resolved at several versions are
+listed under <c>duplicateNpmPackages</>.
+
+Show every import chain from the entrypoint to a module or npm package:
+  <p(245)>deno info --why npm:chalk main.ts</>
+
 <y>Read more:</> <c>https://docs.deno.com/go/info</>"),
           UnstableArgsConfig::ResolutionOnly
     )
@@ -2311,6 +2673,14 @@ The following information is shown:
           .long("json")
           .help("UNSTABLE: Outputs the information in JSON format")
           .action(ArgAction::SetTrue),
+      )
+      .arg(
+        Arg::new("why")
+          .long("why")
+          .value_name("SPECIFIER")
+          .requires("file")
+          .conflicts_with("json")
+          .help("Show every import chain from the entrypoint to a module or npm package, like './util.ts', 'npm:chalk' or 'npm:chalk@5.3.0'"),
       ))
 }
 
@@ -2354,7 +2724,11 @@ The installation root is determined, in order of precedence:
   - <p(245)>DENO_INSTALL_ROOT</> environment variable
   - <p(245)>$HOME/.deno</>
 
-These must be added to the path manually if required."), UnstableArgsConfig::ResolutionAndRuntime)
+These must be added to the path manually if required.
+
+The executable runs with a copy of the lockfile populated while installing (or the one provided
+with <c>--lock</>), so it keeps using the same module versions. Use <c>--frozen</> to make it error
+instead of updating the lockfile."), UnstableArgsConfig::ResolutionAndRuntime)
     .visible_alias("i")
     .defer(|cmd| {
       permission_args(runtime_args(cmd, false, true), Some("global"))
@@ -2414,7 +2788,17 @@ fn json_reference_subcommand() -> Command {
 }
 
 fn jupyter_subcommand() -> Command {
-  command("jupyter", "Deno kernel for Jupyter notebooks", UnstableArgsConfig::ResolutionAndRuntime)
+  command("jupyter",
+    cstr!("Deno kernel for Jupyter notebooks.
+
+Install the kernelspec, so that notebooks can use the Deno kernel:
+  <p(245)>deno jupyter --install</>
+
+Install several kernels, for example one per project, with a different name and display name:
+  <p(245)>deno jupyter --install --name=deno-project --display='Deno (project)'</>
+
+Running <c>deno jupyter</> without arguments shows whether the kernel is installed."),
+    UnstableArgsConfig::ResolutionAndRuntime)
     .arg(
       Arg::new("install")
         .long("install")
@@ -2422,6 +2806,22 @@ fn jupyter_subcommand() -> Command {
         .conflicts_with("kernel")
         .action(ArgAction::SetTrue)
     )
+    .arg(
+      Arg::new("name")
+        .long("name")
+        .short('n')
+        .help("Set the name of the kernel, 'deno' by default")
+        .value_parser(value_parser!(String))
+        .conflicts_with("kernel")
+    )
+    .arg(
+      Arg::new("display")
+        .long("display")
+        .short('d')
+        .help("Set the name of the kernel displayed in notebooks, 'Deno' by default")
+        .value_parser(value_parser!(String))
+        .requires("install")
+    )
     .arg(
       Arg::new("kernel")
         .long("kernel")
@@ -2449,6 +2849,12 @@ fn uninstall_subcommand() -> Command {
 To change the installation root, use <c>--root</> flag:
   <p(245)>deno uninstall --global --root /usr/local serve</>
 
+To list the executables in the installation root, use <c>--list</> flag:
+  <p(245)>deno uninstall --global --list</>
+
+To also remove the executable's dependencies that were cached when it was installed, use <c>--clean-cache</> flag:
+  <p(245)>deno uninstall --global --clean-cache file_server</>
+
 The installation root is determined, in order of precedence:
   - <p(245)>--root</> option
   - <p(245)>DENO_INSTALL_ROOT</> environment variable
@@ -2457,7 +2863,10 @@ The installation root is determined, in order of precedence:
   )
   .defer(|cmd| {
     cmd
-      .arg(Arg::new("name-or-package").required_unless_present("help"))
+      .arg(
+        Arg::new("name-or-package")
+          .required_unless_present_any(["help", "list"]),
+      )
       .arg(
         Arg::new("root")
           .long("root")
@@ -2465,6 +2874,22 @@ The installation root is determined, in order of precedence:
           .requires("global")
           .value_hint(ValueHint::DirPath),
       )
+      .arg(
+        Arg::new("list")
+          .long("list")
+          .help("List the installed executables")
+          .requires("global")
+          .conflicts_with("name-or-package")
+          .action(ArgAction::SetTrue),
+      )
+      .arg(
+        Arg::new("clean-cache")
+          .long("clean-cache")
+          .help("Remove the executable's cached dependencies")
+          .requires("global")
+          .requires("name-or-package")
+          .action(ArgAction::SetTrue),
+      )
       .arg(
         Arg::new("global")
           .long("global")
@@ -2483,13 +2908,23 @@ The installation root is determined, in order of precedence:
 }
 
 fn lsp_subcommand() -> Command {
-  Command::new("lsp").about(
-    "The 'deno lsp' subcommand provides a way for code editors and IDEs to interact with Deno
+  Command::new("lsp")
+    .about(
+      "The 'deno lsp' subcommand provides a way for code editors and IDEs to interact with Deno
 using the Language Server Protocol. Usually humans do not use this subcommand directly.
 For example, 'deno lsp' can provide IDEs with go-to-definition support and automatic code formatting.
 
 How to connect various editors and IDEs to 'deno lsp': https://docs.deno.com/go/lsp",
-  )
+    )
+    .arg(
+      Arg::new("profile")
+        .long("profile")
+        .value_name("FILE")
+        .help("Write request timings, tsc round trips and cache hit rates to this file on shutdown")
+        .require_equals(true)
+        .value_parser(value_parser!(String))
+        .value_hint(ValueHint::FilePath),
+    )
 }
 
 fn lint_subcommand() -> Command {
@@ -2503,6 +2938,9 @@ fn lint_subcommand() -> Command {
 Print result as JSON:
   <p(245)>deno lint --json</>
 
+Print result as SARIF, e.g. for uploading to GitHub code scanning:
+  <p(245)>deno lint --output=sarif > results.sarif</>
+
 Read from stdin:
   <p(245)>cat file.ts | deno lint -</>
   <p(245)>cat file.ts | deno lint --json -</>
@@ -2510,6 +2948,9 @@ Read from stdin:
 List available rules:
   <p(245)>deno lint --rules</>
 
+Preview the fixes <c>--fix</> would apply:
+  <p(245)>deno lint --fix-dry-run</>
+
 To ignore specific diagnostics, you can write an ignore comment on the preceding line with a rule name (or multiple):
   <p(245)>// deno-lint-ignore no-explicit-any</>
   <p(245)>// deno-lint-ignore require-await no-empty</>
@@ -2517,6 +2958,11 @@ To ignore specific diagnostics, you can write an ignore comment on the preceding
 To ignore linting on an entire file, you can add an ignore comment at the top of the file:
   <p(245)>// deno-lint-ignore-file</>
 
+Custom rules can be added with plugin modules listed under `plugins.lint` in the config file.
+
+Rules set to 'warn' under `lintSeverity` in the config file are reported as warnings, which only fail the lint when there are more than <c>--max-warnings</>:
+  <p(245)>deno lint --max-warnings=10</>
+
 <y>Read more:</> <c>https://docs.deno.com/go/lint</>
 "),
     UnstableArgsConfig::ResolutionOnly,
@@ -2530,6 +2976,14 @@ To ignore linting on an entire file, you can add an ignore comment at the top of
           .action(ArgAction::SetTrue)
           .help_heading(LINT_HEADING),
       )
+      .arg(
+        Arg::new("fix-dry-run")
+          .long("fix-dry-run")
+          .help("Print the changes --fix would make as a diff without writing them")
+          .conflicts_with("fix")
+          .action(ArgAction::SetTrue)
+          .help_heading(LINT_HEADING),
+      )
       .arg(
             Arg::new("ext")
                 .long("ext")
@@ -2604,6 +3058,25 @@ To ignore linting on an entire file, you can add an ignore comment at the top of
           .conflicts_with("json")
           .help_heading(LINT_HEADING),
       )
+      .arg(
+        Arg::new("output")
+          .long("output")
+          .require_equals(true)
+          .value_name("FORMAT")
+          .help("Output lint result in the given format")
+          .value_parser(["pretty", "json", "compact", "sarif"])
+          .conflicts_with_all(["json", "compact"])
+          .help_heading(LINT_HEADING),
+      )
+      .arg(
+        Arg::new("max-warnings")
+          .long("max-warnings")
+          .require_equals(true)
+          .value_name("N")
+          .help("Fail if more than N warnings are reported. Warnings don't fail the lint otherwise")
+          .value_parser(value_parser!(usize))
+          .help_heading(LINT_HEADING),
+      )
       .arg(
         Arg::new("files")
           .num_args(1..)
@@ -2612,6 +3085,7 @@ To ignore linting on an entire file, you can add an ignore comment at the top of
       )
       .arg(watch_arg(false))
       .arg(watch_exclude_arg())
+      .arg(watch_debounce_arg())
       .arg(no_clear_screen_arg())
   })
 }
@@ -2626,7 +3100,7 @@ fn repl_subcommand() -> Command {
           .num_args(1..)
           .action(ArgAction::Append)
           .require_equals(true)
-          .help("Evaluates the provided file(s) as scripts when the REPL starts. Accepts file paths and URLs")
+          .help("Evaluates the provided file(s) as scripts when the REPL starts. Accepts file paths and URLs. Scripts can customize the prompt, commands and context through the `repl` global")
           .value_hint(ValueHint::AnyPath),
       )
       .arg(
@@ -2655,6 +3129,7 @@ fn run_args(command: Command, top_level: bool) -> Command {
     .arg(watch_arg(true))
     .arg(hmr_arg(true))
     .arg(watch_exclude_arg())
+    .arg(watch_debounce_arg())
     .arg(no_clear_screen_arg())
     .arg(executable_ext_arg())
     .arg(if top_level {
@@ -2664,6 +3139,7 @@ fn run_args(command: Command, top_level: bool) -> Command {
     })
     .arg(env_file_arg())
     .arg(no_code_cache_arg())
+    .args(profiling_args())
 }
 
 fn run_subcommand() -> Command {
@@ -2708,6 +3184,11 @@ Start a server defined in server.ts:
 Start a server defined in server.ts, watching for changes and running on port 5050:
   <p(245)>deno serve --watch --port 5050 server.ts</>
 
+When a file changes, the server stops accepting connections and lets the requests in flight finish before restarting.
+
+Start a server on every CPU core:
+  <p(245)>deno serve --parallel server.ts</>
+
 <y>Read more:</> <c>https://docs.deno.com/go/serve</>"), UnstableArgsConfig::ResolutionAndRuntime), true, true)
     .arg(
       Arg::new("port")
@@ -2728,6 +3209,7 @@ Start a server defined in server.ts, watching for changes and running on port 50
     .arg(watch_arg(true))
     .arg(hmr_arg(true))
     .arg(watch_exclude_arg())
+    .arg(watch_debounce_arg())
     .arg(no_clear_screen_arg())
     .arg(executable_ext_arg())
     .arg(
@@ -2747,7 +3229,11 @@ fn task_subcommand() -> Command {
   <p(245)>deno task build</>
 
 List all available tasks:
-  <p(245)>deno task</>"
+  <p(245)>deno task</>
+
+Tasks defined as an object can list the tasks that need to run before them,
+which run in parallel when they don't depend on each other:
+  <p(245)>{ command: <c>deno run -A build.ts</>, dependsOn: [<c>check</>, <c>lint</>] }</>"
     ),
     UnstableArgsConfig::ResolutionAndRuntime,
   )
@@ -2782,6 +3268,7 @@ or <c>**/__tests__/**</>:
     )
     .defer(|cmd|
       runtime_args(cmd, true, true)
+      .args(profiling_args())
       .arg(check_arg(true))
       .arg(
         Arg::new("ignore")
@@ -2837,6 +3324,22 @@ or <c>**/__tests__/**</>:
           .help("Run tests with this string or regexp pattern in the test name")
           .help_heading(TEST_HEADING),
       )
+      .arg(
+        Arg::new("tags")
+          .long("tags")
+          .require_equals(true)
+          .value_name("EXPR")
+          .help("Only run tests whose tags match this expression, e.g. 'slow & !windows'")
+          .help_heading(TEST_HEADING),
+      )
+      .arg(
+        Arg::new("skip-tags")
+          .long("skip-tags")
+          .require_equals(true)
+          .value_name("EXPR")
+          .help("Skip tests whose tags match this expression")
+          .help_heading(TEST_HEADING),
+      )
       .arg(
         Arg::new("shuffle")
           .long("shuffle")
@@ -2884,6 +3387,7 @@ or <c>**/__tests__/**</>:
           .conflicts_with("coverage"),
       )
       .arg(watch_exclude_arg())
+      .arg(watch_debounce_arg())
       .arg(no_clear_screen_arg())
       .arg(script_arg().last(true))
       .arg(
@@ -2963,6 +3467,14 @@ different location, use the <c>--output</> flag:
     UnstableArgsConfig::None,
   )
   .hide(cfg!(not(feature = "upgrade")))
+  .after_help(cstr!("<y>Environment variables:</>
+  <g>DENO_UPGRADE_URL</>         Release server to upgrade from instead of dl.deno.land, or the one built
+                           into this executable. It provides <p(245)>release-latest.txt</> and
+                           <p(245)>release/v<<VERSION>>/<<ARCHIVE>></>, and <p(245)><<CHANNEL>>-latest.txt</> and
+                           <p(245)><<CHANNEL>>/v<<VERSION>>/<<ARCHIVE>></> for custom channels
+  <g>DENO_UPGRADE_PUBLIC_KEY</>  Base64 encoded Ed25519 public key, instead of the one built into this
+                           executable. Archives downloaded from a release server must have a matching
+                           base64 encoded signature published at <p(245)><<ARCHIVE URL>>.sig</>"))
   .defer(|cmd| {
     cmd
       .arg(
@@ -3013,6 +3525,15 @@ different location, use the <c>--output</> flag:
           // NOTE(bartlomieju): pre-v1.46 compat
           .hide(true),
       )
+      .arg(
+        Arg::new("channel")
+          .long("channel")
+          .require_equals(true)
+          .value_name("NAME")
+          .help(cstr!("Release channel to upgrade to. Channels other than <p(245)>stable, rc, lts and canary</> require <c>DENO_UPGRADE_URL</>"))
+          .conflicts_with_all(["canary", "release-candidate"])
+          .help_heading(UPGRADE_HEADING),
+      )
       .arg(
         Arg::new("version-or-hash-or-channel")
           .help(cstr!("Version <p(245)>(v1.46.0)</>, channel <p(245)>(rc, canary)</> or commit hash <p(245)>(9bc2dd29ad6ba334fd57a20114e367d3c04763d4)</>"))
@@ -3036,7 +3557,10 @@ See the Deno 1.x to 2.x Migration Guide for migration instructions: https://docs
 }
 
 fn publish_subcommand() -> Command {
-  command("publish", "Publish the current working directory's package or workspace", UnstableArgsConfig::ResolutionOnly)
+  command("publish", cstr!("Publish the current working directory's package or workspace to JSR.
+
+To publish to another JSR compatible registry, set the <c>registry</> field of the root config file to its URL,
+for example <c>https://jsr.example.com/</>."), UnstableArgsConfig::ResolutionOnly)
     .defer(|cmd| {
       cmd
       .arg(
@@ -3090,6 +3614,7 @@ fn compile_args_without_check_args(app: Command) -> Command {
     .arg(no_remote_arg())
     .arg(no_npm_arg())
     .arg(node_modules_dir_arg())
+    .arg(node_conditions_arg())
     .arg(vendor_arg())
     .arg(config_arg())
     .arg(no_config_arg())
@@ -3523,7 +4048,7 @@ fn inspect_args(app: Command) -> Command {
         .value_name("HOST_AND_PORT")
         .default_missing_value("127.0.0.1:9229")
         .help(
-          "Activate inspector on host:port and wait for debugger to connect before running user code",
+          "Activate inspector on host:port and wait for debugger to connect before running user code, including the code of workers",
         )
         .num_args(0..=1)
         .require_equals(true)
@@ -3532,6 +4057,61 @@ fn inspect_args(app: Command) -> Command {
     )
 }
 
+fn profiling_args() -> [Arg; 8] {
+  [
+    Arg::new("cpu-prof")
+      .long("cpu-prof")
+      .help("Start the V8 CPU profiler on startup and write the profile to a .cpuprofile file on exit")
+      .action(ArgAction::SetTrue)
+      .help_heading(DEBUGGING_HEADING),
+    Arg::new("cpu-prof-dir")
+      .long("cpu-prof-dir")
+      .value_name("DIR")
+      .help("Directory the CPU profiles are written to [default: the current directory]")
+      .requires("cpu-prof")
+      .value_hint(ValueHint::DirPath)
+      .help_heading(DEBUGGING_HEADING),
+    Arg::new("cpu-prof-name")
+      .long("cpu-prof-name")
+      .value_name("NAME")
+      .help("File name of the CPU profile")
+      .requires("cpu-prof")
+      .help_heading(DEBUGGING_HEADING),
+    Arg::new("cpu-prof-interval")
+      .long("cpu-prof-interval")
+      .value_name("MICROSECONDS")
+      .help("Sampling interval of the CPU profiler in microseconds [default: 1000]")
+      .requires("cpu-prof")
+      .value_parser(value_parser!(u64).range(1..))
+      .help_heading(DEBUGGING_HEADING),
+    Arg::new("heap-prof")
+      .long("heap-prof")
+      .help("Start the V8 sampling heap profiler on startup and write the profile to a .heapprofile file on exit")
+      .action(ArgAction::SetTrue)
+      .help_heading(DEBUGGING_HEADING),
+    Arg::new("heap-prof-dir")
+      .long("heap-prof-dir")
+      .value_name("DIR")
+      .help("Directory the heap profiles are written to [default: the current directory]")
+      .requires("heap-prof")
+      .value_hint(ValueHint::DirPath)
+      .help_heading(DEBUGGING_HEADING),
+    Arg::new("heap-prof-name")
+      .long("heap-prof-name")
+      .value_name("NAME")
+      .help("File name of the heap profile")
+      .requires("heap-prof")
+      .help_heading(DEBUGGING_HEADING),
+    Arg::new("heap-prof-interval")
+      .long("heap-prof-interval")
+      .value_name("BYTES")
+      .help("Average sampling interval of the heap profiler in bytes [default: 524288]")
+      .requires("heap-prof")
+      .value_parser(value_parser!(u64).range(1..))
+      .help_heading(DEBUGGING_HEADING),
+  ]
+}
+
 fn import_map_arg() -> Arg {
   Arg::new("import-map")
     .long("import-map")
@@ -3551,14 +4131,18 @@ fn env_file_arg() -> Arg {
     .alias("env")
     .value_name("FILE")
     .help(cstr!(
-      "Load environment variables from local file
-  <p(245)>Only the first environment variable with a given key is used.
-  Existing process environment variables are not overwritten.</>"
+      "Load environment variables from local file. Can be repeated
+  <p(245)>Only the first environment variable with a given key in a file is used,
+  and variables in later files take precedence over the ones in earlier files.
+  Existing process environment variables are not overwritten.
+  Reading them still requires --allow-env.
+  Defaults to the envFile field of the config file.</>"
     ))
     .value_hint(ValueHint::FilePath)
     .default_missing_value(".env")
     .require_equals(true)
     .num_args(0..=1)
+    .action(ArgAction::Append)
 }
 
 fn reload_arg() -> Arg {
@@ -3605,7 +4189,7 @@ fn frozen_lockfile_arg() -> Arg {
     .num_args(0..=1)
     .require_equals(true)
     .default_missing_value("true")
-    .help("Error out if lockfile is out of date")
+    .help("Error out if lockfile is out of date or misses a dependency, which is never downloaded")
     .help_heading(DEPENDENCY_MANAGEMENT_HEADING)
 }
 
@@ -3699,7 +4283,7 @@ fn hmr_arg(takes_files: bool) -> Arg {
         cstr!(
         "Watch for file changes and restart process automatically.
   <p(245)>Local files from entry point module graph are watched by default.
-  Additional paths might be watched by passing them as arguments to this flag.</>"),
+  Additional paths or globs might be watched by passing them as arguments to this flag.</>"),
       )
       .value_hint(ValueHint::AnyPath)
   } else {
@@ -3725,7 +4309,7 @@ fn watch_arg(takes_files: bool) -> Arg {
         cstr!(
         "Watch for file changes and restart process automatically.
   <p(245)>Local files from entry point module graph are watched by default.
-  Additional paths might be watched by passing them as arguments to this flag.</>"),
+  Additional paths or globs might be watched by passing them as arguments to this flag.</>"),
       )
       .value_hint(ValueHint::AnyPath)
   } else {
@@ -3752,6 +4336,16 @@ fn no_code_cache_arg() -> Arg {
     .action(ArgAction::SetTrue)
 }
 
+fn watch_debounce_arg() -> Arg {
+  Arg::new("watch-debounce")
+    .long("watch-debounce")
+    .help("Wait for the given number of milliseconds without file changes before restarting. Defaults to 200")
+    .value_name("MS")
+    .require_equals(true)
+    .value_parser(value_parser!(u64))
+    .help_heading(FILE_WATCHING_HEADING)
+}
+
 fn watch_exclude_arg() -> Arg {
   Arg::new("watch-exclude")
     .long("watch-exclude")
@@ -3878,6 +4472,17 @@ fn node_modules_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   }
 }
 
+fn node_conditions_arg() -> Arg {
+  Arg::new("conditions")
+    .long("conditions")
+    .value_name("CONDITIONS")
+    .require_equals(true)
+    .use_value_delimiter(true)
+    .action(ArgAction::Append)
+    .help("Custom conditions for resolving the exports and imports of npm packages, along with \"deno\", \"node\" and \"import\"")
+    .help_heading(DEPENDENCY_MANAGEMENT_HEADING)
+}
+
 fn node_modules_dir_arg() -> Arg {
   fn parse_node_modules_dir_mode(
     s: &str,
@@ -4089,6 +4694,8 @@ fn bench_parse(
   };
 
   let filter = matches.remove_one::<String>("filter");
+  let tags = matches.remove_one::<String>("tags");
+  let skip_tags = matches.remove_one::<String>("skip-tags");
 
   if matches.contains_id("script_arg") {
     flags
@@ -4107,6 +4714,8 @@ fn bench_parse(
   flags.subcommand = DenoSubcommand::Bench(BenchFlags {
     files: FileFlags { include, ignore },
     filter,
+    tags,
+    skip_tags,
     json,
     no_run,
     watch: watch_arg_parse(matches)?,
@@ -4115,8 +4724,29 @@ fn bench_parse(
   Ok(())
 }
 
-fn bundle_parse(flags: &mut Flags, _matches: &mut ArgMatches) {
-  flags.subcommand = DenoSubcommand::Bundle;
+fn bundle_parse(
+  flags: &mut Flags,
+  matches: &mut ArgMatches,
+) -> clap::error::Result<()> {
+  flags.type_check_mode = TypeCheckMode::Local;
+  compile_args_parse(flags, matches)?;
+  unstable_args_parse(flags, matches, UnstableArgsConfig::ResolutionOnly);
+  let entrypoints = matches.remove_many::<String>("entrypoints").unwrap();
+  let platform = match matches.remove_one::<String>("platform").as_deref() {
+    Some("browser") => BundlePlatform::Browser,
+    _ => BundlePlatform::Deno,
+  };
+  flags.subcommand = DenoSubcommand::Bundle(BundleFlags {
+    entrypoints: entrypoints.collect(),
+    output: matches.remove_one::<String>("output"),
+    outdir: matches.remove_one::<String>("outdir"),
+    external: matches
+      .remove_many::<String>("external")
+      .map(|external| external.collect())
+      .unwrap_or_default(),
+    platform,
+  });
+  Ok(())
 }
 
 fn cache_parse(
@@ -4127,8 +4757,17 @@ fn cache_parse(
   unstable_args_parse(flags, matches, UnstableArgsConfig::ResolutionOnly);
   frozen_lockfile_arg_parse(flags, matches);
   allow_scripts_arg_parse(flags, matches)?;
-  let files = matches.remove_many::<String>("file").unwrap().collect();
-  flags.subcommand = DenoSubcommand::Cache(CacheFlags { files });
+  let files = matches
+    .remove_many::<String>("file")
+    .map(|f| f.collect())
+    .unwrap_or_default();
+  let export = matches.remove_one::<String>("export");
+  let import = matches.remove_one::<String>("import");
+  flags.subcommand = DenoSubcommand::Cache(CacheFlags {
+    files,
+    export,
+    import,
+  });
   Ok(())
 }
 
@@ -4143,10 +4782,16 @@ fn check_parse(
   if matches.get_flag("all") || matches.get_flag("remote") {
     flags.type_check_mode = TypeCheckMode::All;
   }
+  let npm_skip = matches
+    .remove_many::<String>("npm-skip")
+    .map(|packages| packages.collect::<Vec<_>>())
+    .unwrap_or_default();
   flags.subcommand = DenoSubcommand::Check(CheckFlags {
     files,
     doc: matches.get_flag("doc"),
     doc_only: matches.get_flag("doc-only"),
+    npm: matches.get_flag("npm") || !npm_skip.is_empty(),
+    npm_skip,
   });
   Ok(())
 }
@@ -4168,7 +4813,15 @@ fn compile_parse(
   let output = matches.remove_one::<String>("output");
   let target = matches.remove_one::<String>("target");
   let icon = matches.remove_one::<String>("icon");
+  let windows_version_info = matches
+    .remove_many::<(String, String)>("windows-version-info")
+    .map(|entries| entries.collect())
+    .unwrap_or_default();
+  let sign_command = matches.remove_one::<String>("sign-command");
   let no_terminal = matches.get_flag("no-terminal");
+  flags.code_cache_enabled = !matches.get_flag("no-code-cache");
+  let compress = matches.get_flag("compress");
+  let strip = matches.get_flag("strip");
   let include = match matches.remove_many::<String>("include") {
     Some(f) => f.collect(),
     None => vec![],
@@ -4183,6 +4836,10 @@ fn compile_parse(
     no_terminal,
     icon,
     include,
+    windows_version_info,
+    sign_command,
+    compress,
+    strip,
   });
 
   Ok(())
@@ -4200,21 +4857,133 @@ fn completions_parse(
   use clap_complete::shells::Zsh;
   use clap_complete_fig::Fig;
 
+  if let Some(kind) = matches.remove_one::<String>("dynamic") {
+    let kind = match kind.as_str() {
+      "tasks" => DynamicCompletionsKind::Tasks,
+      "tests" => DynamicCompletionsKind::Tests,
+      "installed" => DynamicCompletionsKind::Installed,
+      _ => unreachable!(),
+    };
+    flags.subcommand =
+      DenoSubcommand::Completions(CompletionsFlags::Dynamic(kind));
+    return;
+  }
+
   let mut buf: Vec<u8> = vec![];
   let name = "deno";
 
   match matches.get_one::<String>("shell").unwrap().as_str() {
-    "bash" => generate(Bash, &mut app, name, &mut buf),
-    "fish" => generate(Fish, &mut app, name, &mut buf),
+    "bash" => {
+      generate(Bash, &mut app, name, &mut buf);
+      buf.extend_from_slice(BASH_DYNAMIC_COMPLETIONS.as_bytes());
+    }
+    "fish" => {
+      generate(Fish, &mut app, name, &mut buf);
+      buf.extend_from_slice(FISH_DYNAMIC_COMPLETIONS.as_bytes());
+    }
     "powershell" => generate(PowerShell, &mut app, name, &mut buf),
-    "zsh" => generate(Zsh, &mut app, name, &mut buf),
+    "zsh" => {
+      generate(Zsh, &mut app, name, &mut buf);
+      buf = add_zsh_dynamic_completions(&String::from_utf8(buf).unwrap())
+        .into_bytes();
+    }
     "fig" => generate(Fig, &mut app, name, &mut buf),
     _ => unreachable!(),
   }
 
-  flags.subcommand = DenoSubcommand::Completions(CompletionsFlags {
-    buf: buf.into_boxed_slice(),
-  });
+  flags.subcommand = DenoSubcommand::Completions(CompletionsFlags::Static(
+    buf.into_boxed_slice(),
+  ));
+}
+
+// The completion scripts call back into `deno completions --dynamic=<KIND>`
+// for values that depend on the current directory or the installed
+// executables, before falling back to the completions generated by clap.
+
+static BASH_DYNAMIC_COMPLETIONS: &str = r#"
+_deno_dynamic() {
+    _deno "$@"
+    [[ ${COMP_CWORD} -ge 2 ]] || return
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    local prev="${COMP_WORDS[COMP_CWORD-1]}"
+    local kind=""
+    case "${COMP_WORDS[1]}" in
+        task)
+            [[ ${COMP_CWORD} -eq 2 && "${cur}" != -* ]] && kind="tasks"
+            ;;
+        uninstall)
+            [[ "${cur}" != -* ]] && kind="installed"
+            ;;
+        test)
+            if [[ "${prev}" == "--filter" ]]; then
+                kind="tests"
+            elif [[ "${prev}" == "=" && "${COMP_WORDS[COMP_CWORD-2]}" == "--filter" ]]; then
+                kind="tests"
+            fi
+            ;;
+    esac
+    if [[ -n "${kind}" ]]; then
+        [[ "${cur}" == "=" ]] && cur=""
+        local IFS=$'
+'
+        COMPREPLY=( $(compgen -W "$(deno completions --dynamic=${kind} 2>/dev/null)" -- "${cur}") )
+    fi
+}
+
+if [[ "${BASH_VERSINFO[0]}" -eq 4 && "${BASH_VERSINFO[1]}" -ge 4 || "${BASH_VERSINFO[0]}" -gt 4 ]]; then
+    complete -F _deno_dynamic -o nosort -o bashdefault -o default deno
+else
+    complete -F _deno_dynamic -o bashdefault -o default deno
+fi
+"#;
+
+static FISH_DYNAMIC_COMPLETIONS: &str = r#"
+complete -c deno -n "__fish_seen_subcommand_from task; and test (count (commandline -opc)) -eq 2" -f -a "(deno completions --dynamic=tasks 2>/dev/null)"
+complete -c deno -n "__fish_seen_subcommand_from test" -l filter -r -f -a "(deno completions --dynamic=tests 2>/dev/null)"
+complete -c deno -n "__fish_seen_subcommand_from uninstall" -f -a "(deno completions --dynamic=installed 2>/dev/null)"
+"#;
+
+static ZSH_DYNAMIC_COMPLETIONS: &str = r#"
+_deno() {
+    local kind
+    case "${words[2]}" in
+        task)
+            (( CURRENT == 3 )) && [[ "${words[CURRENT]}" != -* ]] && kind=tasks
+            ;;
+        uninstall)
+            (( CURRENT > 2 )) && [[ "${words[CURRENT]}" != -* ]] && kind=installed
+            ;;
+        test)
+            if [[ "${words[CURRENT]}" == --filter=* ]]; then
+                compset -P '--filter='
+                kind=tests
+            elif [[ "${words[CURRENT-1]}" == --filter ]]; then
+                kind=tests
+            fi
+            ;;
+    esac
+    if [[ -n "$kind" ]]; then
+        local -a candidates
+        candidates=(${(f)"$(deno completions --dynamic=$kind 2>/dev/null)"})
+        if (( ${#candidates} )); then
+            compadd -a candidates
+            return
+        fi
+    fi
+    _deno_static "$@"
+}
+
+"#;
+
+/// Renames the completion function generated by clap, so that the one
+/// with the dynamic completions is called by zsh instead.
+fn add_zsh_dynamic_completions(script: &str) -> String {
+  const FUNCTION: &str = "\n_deno() {\n";
+  const FOOTER: &str = "\nif [ \"$funcstack[1]\" = \"_deno\" ]; then";
+  debug_assert!(script.contains(FUNCTION) && script.contains(FOOTER));
+  script
+    .replacen(FUNCTION, "\n_deno_static() {\n", 1)
+    .replacen(FOOTER, &format!("{ZSH_DYNAMIC_COMPLETIONS}{FOOTER}"), 1)
 }
 
 fn coverage_parse(
@@ -4373,6 +5142,7 @@ fn fmt_parse(
   let single_quote = matches.remove_one::<bool>("single-quote");
   let prose_wrap = matches.remove_one::<String>("prose-wrap");
   let no_semicolons = matches.remove_one::<bool>("no-semicolons");
+  let sort_imports = matches.remove_one::<bool>("sort-imports");
   let unstable_css = matches.get_flag("unstable-css");
   let unstable_html = matches.get_flag("unstable-html");
   let unstable_component = matches.get_flag("unstable-component");
@@ -4387,6 +5157,7 @@ fn fmt_parse(
     single_quote,
     prose_wrap,
     no_semicolons,
+    sort_imports,
     watch: watch_arg_parse(matches)?,
     unstable_css,
     unstable_html,
@@ -4401,6 +5172,8 @@ fn init_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     dir: matches.remove_one::<String>("dir"),
     lib: matches.get_flag("lib"),
     serve: matches.get_flag("serve"),
+    template: matches.remove_one::<String>("template"),
+    ci: matches.get_flag("ci"),
   });
 }
 
@@ -4423,6 +5196,7 @@ fn info_parse(
   flags.subcommand = DenoSubcommand::Info(InfoFlags {
     file: matches.remove_one::<String>("file"),
     json,
+    why: matches.remove_one::<String>("why"),
   });
 
   Ok(())
@@ -4559,20 +5333,37 @@ fn jupyter_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   let conn_file = matches.remove_one::<String>("conn");
   let kernel = matches.get_flag("kernel");
   let install = matches.get_flag("install");
+  let name = matches.remove_one::<String>("name");
+  let display = matches.remove_one::<String>("display");
 
   flags.subcommand = DenoSubcommand::Jupyter(JupyterFlags {
     install,
+    name,
+    display,
     kernel,
     conn_file,
   });
 }
 
 fn uninstall_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  if matches.get_flag("list") {
+    let root = matches.remove_one::<String>("root");
+    flags.subcommand = DenoSubcommand::Uninstall(UninstallFlags {
+      kind: UninstallKind::ListGlobal { root },
+    });
+    return;
+  }
+
   let name = matches.remove_one::<String>("name-or-package").unwrap();
 
   let kind = if matches.get_flag("global") {
     let root = matches.remove_one::<String>("root");
-    UninstallKind::Global(UninstallFlagsGlobal { name, root })
+    let clean_cache = matches.get_flag("clean-cache");
+    UninstallKind::Global(UninstallFlagsGlobal {
+      name,
+      root,
+      clean_cache,
+    })
   } else {
     let packages: Vec<_> = vec![name]
       .into_iter()
@@ -4588,8 +5379,9 @@ fn uninstall_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   flags.subcommand = DenoSubcommand::Uninstall(UninstallFlags { kind });
 }
 
-fn lsp_parse(flags: &mut Flags, _matches: &mut ArgMatches) {
-  flags.subcommand = DenoSubcommand::Lsp;
+fn lsp_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  let profile = matches.remove_one::<String>("profile");
+  flags.subcommand = DenoSubcommand::Lsp(LspFlags { profile });
 }
 
 fn lint_parse(
@@ -4611,6 +5403,7 @@ fn lint_parse(
     None => vec![],
   };
   let fix = matches.get_flag("fix");
+  let fix_dry_run = matches.get_flag("fix-dry-run");
   let rules = matches.get_flag("rules");
   let maybe_rules_tags = matches
     .remove_many::<String>("rules-tags")
@@ -4626,6 +5419,10 @@ fn lint_parse(
 
   let json = matches.get_flag("json");
   let compact = matches.get_flag("compact");
+  let output = matches
+    .remove_one::<String>("output")
+    .map(|output| LintReporterKind::from_name(&output).unwrap());
+  let max_warnings = matches.remove_one::<usize>("max-warnings");
 
   flags.subcommand = DenoSubcommand::Lint(LintFlags {
     files: FileFlags {
@@ -4633,12 +5430,15 @@ fn lint_parse(
       ignore,
     },
     fix,
+    fix_dry_run,
     rules,
     maybe_rules_tags,
     maybe_rules_include,
     maybe_rules_exclude,
     json,
     compact,
+    output,
+    max_warnings,
     watch: watch_arg_parse(matches)?,
   });
   Ok(())
@@ -4683,6 +5483,7 @@ fn run_parse(
 ) -> clap::error::Result<()> {
   runtime_args_parse(flags, matches, true, true)?;
   ext_arg_parse(flags, matches);
+  profiling_args_parse(flags, matches);
 
   flags.code_cache_enabled = !matches.get_flag("no-code-cache");
 
@@ -4821,6 +5622,7 @@ fn test_parse(
   flags.type_check_mode = TypeCheckMode::Local;
   runtime_args_parse(flags, matches, true, true)?;
   ext_arg_parse(flags, matches);
+  profiling_args_parse(flags, matches);
 
   // NOTE: `deno test` always uses `--no-prompt`, tests shouldn't ever do
   // interactive prompts, unless done by user code
@@ -4839,6 +5641,8 @@ fn test_parse(
   #[allow(clippy::print_stderr)]
   let permit_no_files = matches.get_flag("permit-no-files");
   let filter = matches.remove_one::<String>("filter");
+  let tags = matches.remove_one::<String>("tags");
+  let skip_tags = matches.remove_one::<String>("skip-tags");
   let clean = matches.get_flag("clean");
 
   let fail_fast = if matches.contains_id("fail-fast") {
@@ -4902,6 +5706,8 @@ fn test_parse(
     fail_fast,
     files: FileFlags { include, ignore },
     filter,
+    tags,
+    skip_tags,
     shuffle,
     permit_no_files,
     concurrent_jobs,
@@ -4930,6 +5736,7 @@ fn upgrade_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   let output = matches.remove_one::<String>("output");
   let version_or_hash_or_channel =
     matches.remove_one::<String>("version-or-hash-or-channel");
+  let channel = matches.remove_one::<String>("channel");
   flags.subcommand = DenoSubcommand::Upgrade(UpgradeFlags {
     dry_run,
     force,
@@ -4938,6 +5745,7 @@ fn upgrade_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     version,
     output,
     version_or_hash_or_channel,
+    channel,
   });
 }
 
@@ -4979,6 +5787,7 @@ fn compile_args_without_check_parse(
   no_remote_arg_parse(flags, matches);
   no_npm_arg_parse(flags, matches);
   node_modules_and_vendor_dir_arg_parse(flags, matches);
+  node_conditions_arg_parse(flags, matches);
   config_args_parse(flags, matches);
   reload_arg_parse(flags, matches)?;
   lock_args_parse(flags, matches);
@@ -5188,6 +5997,21 @@ fn runtime_args_parse(
   Ok(())
 }
 
+fn profiling_args_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  let mut profile_parse = |name: &str| {
+    if !matches.get_flag(name) {
+      return None;
+    }
+    Some(ProfileFlags {
+      dir: matches.remove_one::<String>(&format!("{name}-dir")),
+      name: matches.remove_one::<String>(&format!("{name}-name")),
+      interval: matches.remove_one::<u64>(&format!("{name}-interval")),
+    })
+  };
+  flags.cpu_prof = profile_parse("cpu-prof");
+  flags.heap_prof = profile_parse("heap-prof");
+}
+
 fn inspect_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   flags.inspect = matches.remove_one::<SocketAddr>("inspect");
   flags.inspect_brk = matches.remove_one::<SocketAddr>("inspect-brk");
@@ -5199,7 +6023,9 @@ fn import_map_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
 }
 
 fn env_file_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  flags.env_file = matches.remove_one::<String>("env-file");
+  flags.env_file = matches
+    .remove_many::<String>("env-file")
+    .map(|env_files| env_files.collect());
 }
 
 fn reload_arg_parse(
@@ -5254,6 +6080,12 @@ fn frozen_lockfile_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   }
 }
 
+fn node_conditions_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  if let Some(conditions) = matches.remove_many::<String>("conditions") {
+    flags.node_conditions = conditions.collect();
+  }
+}
+
 fn ext_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   flags.ext = matches.remove_one::<String>("ext");
 }
@@ -5385,6 +6217,7 @@ fn watch_arg_parse(
         })
         .transpose()?
         .unwrap_or_default(),
+      debounce_ms: matches.remove_one::<u64>("watch-debounce"),
     }))
   } else {
     Ok(None)
@@ -5409,6 +6242,7 @@ fn watch_arg_parse_with_paths(
         })
         .transpose()?
         .unwrap_or_default(),
+      debounce_ms: matches.remove_one::<u64>("watch-debounce"),
     }));
   }
 
@@ -5430,6 +6264,7 @@ fn watch_arg_parse_with_paths(
             })
             .transpose()?
             .unwrap_or_default(),
+          debounce_ms: matches.remove_one::<u64>("watch-debounce"),
         })
       })
       .transpose();
@@ -5532,6 +6367,7 @@ mod tests {
           version: None,
           output: None,
           version_or_hash_or_channel: None,
+          channel: None,
         }),
         ..Flags::default()
       }
@@ -5552,10 +6388,36 @@ mod tests {
           version: None,
           output: Some(String::from("example.txt")),
           version_or_hash_or_channel: None,
+          channel: None,
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn upgrade_with_channel_flag() {
+    let r = flags_from_vec(svec!["deno", "upgrade", "--channel=nightly"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Upgrade(UpgradeFlags {
+          force: false,
+          dry_run: false,
+          canary: false,
+          release_candidate: false,
+          version: None,
+          output: None,
+          version_or_hash_or_channel: None,
+          channel: Some("nightly".to_string()),
         }),
         ..Flags::default()
       }
     );
+
+    let r =
+      flags_from_vec(svec!["deno", "upgrade", "--channel=nightly", "--canary"]);
+    assert!(r.is_err());
   }
 
   #[test]
@@ -5603,6 +6465,7 @@ mod tests {
             paths: vec![],
             no_clear_screen: false,
             exclude: vec![],
+            debounce_ms: None,
           }),
           bare: false,
         }),
@@ -5628,6 +6491,7 @@ mod tests {
             paths: vec![],
             no_clear_screen: true,
             exclude: vec![],
+            debounce_ms: None,
           }),
           bare: true,
         }),
@@ -5654,6 +6518,7 @@ mod tests {
             paths: vec![],
             no_clear_screen: true,
             exclude: vec![],
+            debounce_ms: None,
           }),
           bare: false,
         }),
@@ -5680,6 +6545,7 @@ mod tests {
             paths: vec![],
             no_clear_screen: true,
             exclude: vec![],
+            debounce_ms: None,
           }),
           bare: false,
         }),
@@ -5706,6 +6572,7 @@ mod tests {
             paths: vec![String::from("foo.txt")],
             no_clear_screen: true,
             exclude: vec![],
+            debounce_ms: None,
           }),
           bare: false,
         }),
@@ -5733,6 +6600,7 @@ mod tests {
             paths: vec![String::from("file1"), String::from("file2")],
             no_clear_screen: false,
             exclude: vec![],
+            debounce_ms: None,
           }),
           bare: true,
         }),
@@ -5763,6 +6631,38 @@ mod tests {
             paths: vec![],
             no_clear_screen: true,
             exclude: vec![],
+            debounce_ms: None,
+          }),
+          bare: false,
+        }),
+        code_cache_enabled: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn run_watch_with_debounce() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--watch=src/**/*.css",
+      "--watch-debounce=50",
+      "script.ts"
+    ]);
+
+    let flags = r.unwrap();
+    assert_eq!(
+      flags,
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+          watch: Some(WatchFlagsWithPaths {
+            hmr: false,
+            paths: vec![String::from("src/**/*.css")],
+            no_clear_screen: false,
+            exclude: vec![],
+            debounce_ms: Some(50),
           }),
           bare: false,
         }),
@@ -5770,6 +6670,16 @@ mod tests {
         ..Flags::default()
       }
     );
+    assert_eq!(flags.watch_debounce(), Some(Duration::from_millis(50)));
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--watch",
+      "--watch-debounce=soon",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
   }
 
   #[test]
@@ -5792,6 +6702,7 @@ mod tests {
             paths: vec![],
             no_clear_screen: false,
             exclude: vec![String::from("foo")],
+            debounce_ms: None,
           }),
           bare: true,
         }),
@@ -5818,6 +6729,7 @@ mod tests {
             paths: vec![String::from("foo")],
             no_clear_screen: false,
             exclude: vec![String::from("bar")],
+            debounce_ms: None,
           }),
           bare: false,
         }),
@@ -5845,6 +6757,7 @@ mod tests {
             paths: vec![],
             no_clear_screen: false,
             exclude: vec![String::from("foo"), String::from("bar")],
+            debounce_ms: None,
           }),
           bare: false,
         }),
@@ -5871,6 +6784,7 @@ mod tests {
             paths: vec![String::from("foo"), String::from("bar")],
             no_clear_screen: false,
             exclude: vec![String::from("baz"), String::from("qux"),],
+            debounce_ms: None,
           }),
           bare: true,
         }),
@@ -6268,6 +7182,7 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          sort_imports: None,
           unstable_css: false,
           unstable_html: false,
           unstable_component: false,
@@ -6294,6 +7209,7 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          sort_imports: None,
           unstable_css: false,
           unstable_html: false,
           unstable_component: false,
@@ -6320,6 +7236,7 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          sort_imports: None,
           unstable_css: false,
           unstable_html: false,
           unstable_component: false,
@@ -6346,6 +7263,7 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          sort_imports: None,
           unstable_css: false,
           unstable_html: false,
           unstable_component: false,
@@ -6381,6 +7299,7 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          sort_imports: None,
           unstable_css: true,
           unstable_html: true,
           unstable_component: true,
@@ -6389,6 +7308,7 @@ mod tests {
             hmr: false,
             no_clear_screen: true,
             exclude: vec![],
+            debounce_ms: None,
           })
         }),
         ..Flags::default()
@@ -6418,6 +7338,7 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          sort_imports: None,
           unstable_css: false,
           unstable_html: false,
           unstable_component: false,
@@ -6444,6 +7365,7 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          sort_imports: None,
           unstable_css: false,
           unstable_html: false,
           unstable_component: false,
@@ -6478,6 +7400,7 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          sort_imports: None,
           unstable_css: false,
           unstable_html: false,
           unstable_component: false,
@@ -6501,6 +7424,7 @@ mod tests {
       "--prose-wrap",
       "never",
       "--no-semicolons",
+      "--sort-imports",
     ]);
     assert_eq!(
       r.unwrap(),
@@ -6517,6 +7441,7 @@ mod tests {
           single_quote: Some(true),
           prose_wrap: Some("never".to_string()),
           no_semicolons: Some(true),
+          sort_imports: Some(true),
           unstable_css: false,
           unstable_html: false,
           unstable_component: false,
@@ -6534,6 +7459,7 @@ mod tests {
       "--use-tabs=false",
       "--single-quote=false",
       "--no-semicolons=false",
+      "--sort-imports=false",
     ]);
     assert_eq!(
       r.unwrap(),
@@ -6550,6 +7476,7 @@ mod tests {
           single_quote: Some(false),
           prose_wrap: None,
           no_semicolons: Some(false),
+          sort_imports: Some(false),
           unstable_css: false,
           unstable_html: false,
           unstable_component: false,
@@ -6561,6 +7488,29 @@ mod tests {
     );
   }
 
+  #[test]
+  fn lsp() {
+    let r = flags_from_vec(svec!["deno", "lsp"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Lsp(LspFlags { profile: None }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "lsp", "--profile=lsp_profile.json"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Lsp(LspFlags {
+          profile: Some(String::from("lsp_profile.json")),
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn lint() {
     let r = flags_from_vec(svec!["deno", "lint", "script_1.ts", "script_2.ts"]);
@@ -6573,12 +7523,15 @@ mod tests {
             ignore: vec![],
           },
           fix: false,
+          fix_dry_run: false,
           rules: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
           maybe_rules_exclude: None,
           json: false,
           compact: false,
+          output: None,
+          max_warnings: None,
           watch: Default::default(),
         }),
         ..Flags::default()
@@ -6601,12 +7554,15 @@ mod tests {
             ignore: vec![],
           },
           fix: false,
+          fix_dry_run: false,
           rules: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
           maybe_rules_exclude: None,
           json: false,
           compact: false,
+          output: None,
+          max_warnings: None,
           watch: Some(Default::default()),
         }),
         ..Flags::default()
@@ -6630,16 +7586,20 @@ mod tests {
             ignore: vec![],
           },
           fix: false,
+          fix_dry_run: false,
           rules: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
           maybe_rules_exclude: None,
           json: false,
           compact: false,
+          output: None,
+          max_warnings: None,
           watch: Some(WatchFlags {
             hmr: false,
             no_clear_screen: true,
             exclude: vec![],
+            debounce_ms: None,
           }),
         }),
         ..Flags::default()
@@ -6661,18 +7621,105 @@ mod tests {
             ignore: vec!["script_1.ts".to_string(), "script_2.ts".to_string()],
           },
           fix: true,
+          fix_dry_run: false,
+          rules: false,
+          maybe_rules_tags: None,
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: false,
+          compact: false,
+          output: None,
+          max_warnings: None,
+          watch: Default::default(),
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "lint", "--fix-dry-run"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          fix: false,
+          fix_dry_run: true,
+          rules: false,
+          maybe_rules_tags: None,
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: false,
+          compact: false,
+          output: None,
+          max_warnings: None,
+          watch: Default::default(),
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "lint", "--fix", "--fix-dry-run"]);
+    assert!(r.is_err());
+
+    let r = flags_from_vec(svec!["deno", "lint", "--output=sarif"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          fix: false,
+          fix_dry_run: false,
           rules: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
           maybe_rules_exclude: None,
           json: false,
           compact: false,
+          output: Some(LintReporterKind::Sarif),
+          max_warnings: None,
+          watch: Default::default(),
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "lint", "--json", "--output=sarif"]);
+    assert!(r.is_err());
+
+    let r = flags_from_vec(svec!["deno", "lint", "--max-warnings=10"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          fix: false,
+          fix_dry_run: false,
+          rules: false,
+          maybe_rules_tags: None,
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: false,
+          compact: false,
+          output: None,
+          max_warnings: Some(10),
           watch: Default::default(),
         }),
         ..Flags::default()
       }
     );
 
+    let r = flags_from_vec(svec!["deno", "lint", "--max-warnings=-1"]);
+    assert!(r.is_err());
+
     let r = flags_from_vec(svec!["deno", "lint", "--rules"]);
     assert_eq!(
       r.unwrap(),
@@ -6683,12 +7730,15 @@ mod tests {
             ignore: vec![],
           },
           fix: false,
+          fix_dry_run: false,
           rules: true,
           maybe_rules_tags: None,
           maybe_rules_include: None,
           maybe_rules_exclude: None,
           json: false,
           compact: false,
+          output: None,
+          max_warnings: None,
           watch: Default::default(),
         }),
         ..Flags::default()
@@ -6710,12 +7760,15 @@ mod tests {
             ignore: vec![],
           },
           fix: false,
+          fix_dry_run: false,
           rules: true,
           maybe_rules_tags: Some(svec!["recommended"]),
           maybe_rules_include: None,
           maybe_rules_exclude: None,
           json: false,
           compact: false,
+          output: None,
+          max_warnings: None,
           watch: Default::default(),
         }),
         ..Flags::default()
@@ -6738,12 +7791,15 @@ mod tests {
             ignore: vec![],
           },
           fix: false,
+          fix_dry_run: false,
           rules: false,
           maybe_rules_tags: Some(svec![""]),
           maybe_rules_include: Some(svec!["ban-untagged-todo", "no-undef"]),
           maybe_rules_exclude: Some(svec!["no-const-assign"]),
           json: false,
           compact: false,
+          output: None,
+          max_warnings: None,
           watch: Default::default(),
         }),
         ..Flags::default()
@@ -6760,12 +7816,15 @@ mod tests {
             ignore: vec![],
           },
           fix: false,
+          fix_dry_run: false,
           rules: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
           maybe_rules_exclude: None,
           json: true,
           compact: false,
+          output: None,
+          max_warnings: None,
           watch: Default::default(),
         }),
         ..Flags::default()
@@ -6789,12 +7848,15 @@ mod tests {
             ignore: vec![],
           },
           fix: false,
+          fix_dry_run: false,
           rules: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
           maybe_rules_exclude: None,
           json: true,
           compact: false,
+          output: None,
+          max_warnings: None,
           watch: Default::default(),
         }),
         config_flag: ConfigFlag::Path("Deno.jsonc".to_string()),
@@ -6819,12 +7881,15 @@ mod tests {
             ignore: vec![],
           },
           fix: false,
+          fix_dry_run: false,
           rules: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
           maybe_rules_exclude: None,
           json: false,
           compact: true,
+          output: None,
+          max_warnings: None,
           watch: Default::default(),
         }),
         config_flag: ConfigFlag::Path("Deno.jsonc".to_string()),
@@ -6845,6 +7910,56 @@ mod tests {
     );
   }
 
+  #[test]
+  fn bundle() {
+    let r = flags_from_vec(svec!["deno", "bundle", "main.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          entrypoints: svec!["main.ts"],
+          output: None,
+          outdir: None,
+          external: vec![],
+          platform: BundlePlatform::Deno,
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "bundle",
+      "--no-check",
+      "--outdir",
+      "dist",
+      "--platform=browser",
+      "--external=https://esm.sh/*,npm:*",
+      "main.ts",
+      "worker.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          entrypoints: svec!["main.ts", "worker.ts"],
+          output: None,
+          outdir: Some(String::from("dist")),
+          external: svec!["https://esm.sh/*", "npm:*"],
+          platform: BundlePlatform::Browser,
+        }),
+        type_check_mode: TypeCheckMode::None,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno", "bundle", "-o", "out.js", "--outdir", "dist", "main.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn cache() {
     let r = flags_from_vec(svec!["deno", "cache", "script.ts"]);
@@ -6853,12 +7968,62 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Cache(CacheFlags {
           files: svec!["script.ts"],
+          export: None,
+          import: None,
         }),
         ..Flags::default()
       }
     );
   }
 
+  #[test]
+  fn cache_export_import() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "cache",
+      "--export",
+      "deps.tar",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Cache(CacheFlags {
+          files: svec!["script.ts"],
+          export: Some("deps.tar".to_string()),
+          import: None,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "cache", "--import", "deps.tar"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Cache(CacheFlags {
+          files: vec![],
+          export: None,
+          import: Some("deps.tar".to_string()),
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "cache", "--export", "deps.tar"]);
+    assert!(r.is_err());
+    let r = flags_from_vec(svec![
+      "deno",
+      "cache",
+      "--export",
+      "a.tar",
+      "--import",
+      "b.tar",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn check() {
     let r = flags_from_vec(svec!["deno", "check", "script.ts"]);
@@ -6869,6 +8034,8 @@ mod tests {
           files: svec!["script.ts"],
           doc: false,
           doc_only: false,
+          npm: false,
+          npm_skip: vec![],
         }),
         type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
@@ -6883,6 +8050,8 @@ mod tests {
           files: svec!["script.ts"],
           doc: true,
           doc_only: false,
+          npm: false,
+          npm_skip: vec![],
         }),
         type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
@@ -6897,6 +8066,46 @@ mod tests {
           files: svec!["markdown.md"],
           doc: false,
           doc_only: true,
+          npm: false,
+          npm_skip: vec![],
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "check", "--npm", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Check(CheckFlags {
+          files: svec!["script.ts"],
+          doc: false,
+          doc_only: false,
+          npm: true,
+          npm_skip: vec![],
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "check",
+      "--npm-skip=chalk,@types/node",
+      "--npm-skip=preact",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Check(CheckFlags {
+          files: svec!["script.ts"],
+          doc: false,
+          doc_only: false,
+          npm: true,
+          npm_skip: svec!["chalk", "@types/node", "preact"],
         }),
         type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
@@ -6925,6 +8134,8 @@ mod tests {
             files: svec!["script.ts"],
             doc: false,
             doc_only: false,
+            npm: false,
+            npm_skip: vec![],
           }),
           type_check_mode: TypeCheckMode::All,
           ..Flags::default()
@@ -6954,6 +8165,7 @@ mod tests {
         subcommand: DenoSubcommand::Info(InfoFlags {
           json: false,
           file: Some("script.ts".to_string()),
+          why: None,
         }),
         ..Flags::default()
       }
@@ -6966,6 +8178,7 @@ mod tests {
         subcommand: DenoSubcommand::Info(InfoFlags {
           json: false,
           file: Some("script.ts".to_string()),
+          why: None,
         }),
         reload: true,
         ..Flags::default()
@@ -6979,18 +8192,37 @@ mod tests {
         subcommand: DenoSubcommand::Info(InfoFlags {
           json: true,
           file: Some("script.ts".to_string()),
+          why: None,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r =
+      flags_from_vec(svec!["deno", "info", "--why", "npm:chalk", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: false,
+          file: Some("script.ts".to_string()),
+          why: Some("npm:chalk".to_string()),
         }),
         ..Flags::default()
       }
     );
 
+    let r = flags_from_vec(svec!["deno", "info", "--why", "npm:chalk"]);
+    assert!(r.is_err());
+
     let r = flags_from_vec(svec!["deno", "info"]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Info(InfoFlags {
           json: false,
-          file: None
+          file: None,
+          why: None,
         }),
         ..Flags::default()
       }
@@ -7002,7 +8234,8 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Info(InfoFlags {
           json: true,
-          file: None
+          file: None,
+          why: None,
         }),
         ..Flags::default()
       }
@@ -7021,7 +8254,8 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Info(InfoFlags {
           json: false,
-          file: None
+          file: None,
+          why: None,
         }),
         config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
         no_npm: true,
@@ -7138,7 +8372,7 @@ mod tests {
           allow_all: true,
           ..Default::default()
         },
-        env_file: Some(".example.env".to_owned()),
+        env_file: Some(vec![".example.env".to_owned()]),
         ..Flags::default()
       }
     );
@@ -7232,7 +8466,7 @@ mod tests {
           allow_all: true,
           ..Default::default()
         },
-        env_file: Some(".example.env".to_owned()),
+        env_file: Some(vec![".example.env".to_owned()]),
         unsafely_ignore_certificate_errors: Some(vec![]),
         ..Flags::default()
       }
@@ -7821,6 +9055,7 @@ mod tests {
         subcommand: DenoSubcommand::Info(InfoFlags {
           file: Some("script.ts".to_string()),
           json: false,
+          why: None,
         }),
         import_map_path: Some("import_map.json".to_owned()),
         ..Flags::default()
@@ -7841,6 +9076,8 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Cache(CacheFlags {
           files: svec!["script.ts"],
+          export: None,
+          import: None,
         }),
         import_map_path: Some("import_map.json".to_owned()),
         ..Flags::default()
@@ -7882,7 +9119,7 @@ mod tests {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        env_file: Some(".env".to_owned()),
+        env_file: Some(vec![".env".to_owned()]),
         code_cache_enabled: true,
         ..Flags::default()
       }
@@ -7898,7 +9135,7 @@ mod tests {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        env_file: Some(".env".to_owned()),
+        env_file: Some(vec![".env".to_owned()]),
         code_cache_enabled: true,
         ..Flags::default()
       }
@@ -7931,7 +9168,7 @@ mod tests {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        env_file: Some(".another_env".to_owned()),
+        env_file: Some(vec![".another_env".to_owned()]),
         code_cache_enabled: true,
         ..Flags::default()
       }
@@ -7952,7 +9189,29 @@ mod tests {
         subcommand: DenoSubcommand::Run(RunFlags::new_default(
           "script.ts".to_string(),
         )),
-        env_file: Some(".another_env".to_owned()),
+        env_file: Some(vec![".another_env".to_owned()]),
+        code_cache_enabled: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn run_multiple_env_file_defined() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--env-file",
+      "--env-file=.two_env",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        env_file: Some(vec![".env".to_owned(), ".two_env".to_owned()]),
         code_cache_enabled: true,
         ..Flags::default()
       }
@@ -7968,6 +9227,8 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Cache(CacheFlags {
           files: svec!["script.ts", "script_two.ts"],
+          export: None,
+          import: None,
         }),
         ..Flags::default()
       }
@@ -8095,7 +9356,7 @@ mod tests {
           allow_read: Some(vec![]),
           ..Default::default()
         },
-        env_file: Some(".example.env".to_owned()),
+        env_file: Some(vec![".example.env".to_owned()]),
         ..Flags::default()
       }
     );
@@ -8141,6 +9402,7 @@ mod tests {
           kind: UninstallKind::Global(UninstallFlagsGlobal {
             name: "file_server".to_string(),
             root: None,
+            clean_cache: false,
           }),
         }),
         ..Flags::default()
@@ -8162,11 +9424,56 @@ mod tests {
           kind: UninstallKind::Global(UninstallFlagsGlobal {
             name: "file_server".to_string(),
             root: Some("/user/foo/bar".to_string()),
+            clean_cache: false,
+          }),
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "uninstall",
+      "-g",
+      "--clean-cache",
+      "file_server"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Uninstall(UninstallFlags {
+          kind: UninstallKind::Global(UninstallFlagsGlobal {
+            name: "file_server".to_string(),
+            root: None,
+            clean_cache: true,
           }),
         }),
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "uninstall",
+      "-g",
+      "--list",
+      "--root",
+      "/user/foo/bar"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Uninstall(UninstallFlags {
+          kind: UninstallKind::ListGlobal {
+            root: Some("/user/foo/bar".to_string()),
+          },
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "uninstall", "--list"]);
+    assert!(r.is_err());
   }
 
   #[test]
@@ -8215,11 +9522,45 @@ mod tests {
     let r = flags_from_vec(svec!["deno", "completions", "zsh"]).unwrap();
 
     match r.subcommand {
-      DenoSubcommand::Completions(CompletionsFlags { buf }) => {
+      DenoSubcommand::Completions(CompletionsFlags::Static(buf)) => {
         assert!(!buf.is_empty())
       }
       _ => unreachable!(),
     }
+
+    let r = flags_from_vec(svec!["deno", "completions", "bash"]).unwrap();
+    match r.subcommand {
+      DenoSubcommand::Completions(CompletionsFlags::Static(buf)) => {
+        let script = String::from_utf8(buf.into_vec()).unwrap();
+        assert!(script.contains("complete -F _deno_dynamic"));
+      }
+      _ => unreachable!(),
+    }
+
+    let r = flags_from_vec(svec!["deno", "completions", "zsh"]).unwrap();
+    match r.subcommand {
+      DenoSubcommand::Completions(CompletionsFlags::Static(buf)) => {
+        let script = String::from_utf8(buf.into_vec()).unwrap();
+        assert!(script.contains("\n_deno_static() {\n"));
+        assert!(script.contains("_deno_static \"$@\""));
+      }
+      _ => unreachable!(),
+    }
+  }
+
+  #[test]
+  fn completions_dynamic() {
+    let r = flags_from_vec(svec!["deno", "completions", "--dynamic=tasks"]);
+    assert_eq!(
+      r.unwrap().subcommand,
+      DenoSubcommand::Completions(CompletionsFlags::Dynamic(
+        DynamicCompletionsKind::Tasks
+      ))
+    );
+
+    let r =
+      flags_from_vec(svec!["deno", "completions", "bash", "--dynamic=tasks"]);
+    assert!(r.is_err());
   }
 
   #[test]
@@ -8498,6 +9839,28 @@ mod tests {
     );
   }
 
+  #[test]
+  fn node_conditions_flag() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--conditions=development,worker",
+      "--conditions=custom",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "script.ts".to_string(),
+        )),
+        node_conditions: svec!["development", "worker", "custom"],
+        code_cache_enabled: true,
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn vendor_flag() {
     let r = flags_from_vec(svec!["deno", "run", "--vendor", "script.ts"]);
@@ -8706,6 +10069,8 @@ mod tests {
           doc: false,
           fail_fast: None,
           filter: Some("- foo".to_string()),
+          tags: None,
+          skip_tags: None,
           permit_no_files: true,
           files: FileFlags {
             include: vec!["dir1/".to_string(), "dir2/".to_string()],
@@ -8790,6 +10155,8 @@ mod tests {
           doc: false,
           fail_fast: Some(NonZeroUsize::new(3).unwrap()),
           filter: None,
+          tags: None,
+          skip_tags: None,
           permit_no_files: false,
           shuffle: None,
           files: FileFlags {
@@ -8833,6 +10200,8 @@ mod tests {
           doc: false,
           fail_fast: None,
           filter: None,
+          tags: None,
+          skip_tags: None,
           permit_no_files: false,
           shuffle: None,
           files: FileFlags {
@@ -8970,6 +10339,8 @@ mod tests {
           doc: false,
           fail_fast: None,
           filter: None,
+          tags: None,
+          skip_tags: None,
           permit_no_files: false,
           shuffle: Some(1),
           files: FileFlags {
@@ -9006,6 +10377,8 @@ mod tests {
           doc: false,
           fail_fast: None,
           filter: None,
+          tags: None,
+          skip_tags: None,
           permit_no_files: false,
           shuffle: None,
           files: FileFlags {
@@ -9041,6 +10414,8 @@ mod tests {
           doc: false,
           fail_fast: None,
           filter: None,
+          tags: None,
+          skip_tags: None,
           permit_no_files: false,
           shuffle: None,
           files: FileFlags {
@@ -9078,6 +10453,8 @@ mod tests {
           doc: false,
           fail_fast: None,
           filter: None,
+          tags: None,
+          skip_tags: None,
           permit_no_files: false,
           shuffle: None,
           files: FileFlags {
@@ -9092,6 +10469,7 @@ mod tests {
             hmr: false,
             no_clear_screen: true,
             exclude: vec![],
+            debounce_ms: None,
             paths: vec![],
           }),
           reporter: Default::default(),
@@ -9108,6 +10486,32 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_tags() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "test",
+      "--tags=slow & !windows",
+      "--skip-tags=flaky"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test(TestFlags {
+          tags: Some("slow & !windows".to_string()),
+          skip_tags: Some("flaky".to_string()),
+          ..TestFlags::default()
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        permissions: PermissionFlags {
+          no_prompt: true,
+          ..Default::default()
+        },
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn test_watch_with_paths() {
     let r = flags_from_vec(svec!("deno", "test", "--watch=foo"));
@@ -9122,6 +10526,7 @@ mod tests {
             paths: vec![String::from("foo")],
             no_clear_screen: false,
             exclude: vec![],
+            debounce_ms: None,
           }),
           ..TestFlags::default()
         }),
@@ -9146,6 +10551,7 @@ mod tests {
             paths: vec![String::from("foo"), String::from("bar")],
             no_clear_screen: false,
             exclude: vec![],
+            debounce_ms: None,
           }),
           ..TestFlags::default()
         }),
@@ -9174,6 +10580,7 @@ mod tests {
             paths: vec![],
             no_clear_screen: false,
             exclude: vec![String::from("foo")],
+            debounce_ms: None,
           }),
           ..TestFlags::default()
         }),
@@ -9202,6 +10609,7 @@ mod tests {
             paths: vec![String::from("foo")],
             no_clear_screen: false,
             exclude: vec![String::from("bar")],
+            debounce_ms: None,
           }),
           ..TestFlags::default()
         }),
@@ -9231,6 +10639,7 @@ mod tests {
             paths: vec![],
             no_clear_screen: false,
             exclude: vec![String::from("foo"), String::from("bar")],
+            debounce_ms: None,
           }),
           ..TestFlags::default()
         }),
@@ -9260,6 +10669,7 @@ mod tests {
             paths: vec![String::from("foo"), String::from("bar")],
             no_clear_screen: false,
             exclude: vec![String::from("baz"), String::from("qux"),],
+            debounce_ms: None,
           }),
           ..TestFlags::default()
         }),
@@ -9327,6 +10737,7 @@ mod tests {
           version: None,
           output: None,
           version_or_hash_or_channel: None,
+          channel: None,
         }),
         ca_data: Some(CaData::File("example.crt".to_owned())),
         ..Flags::default()
@@ -9348,6 +10759,7 @@ mod tests {
           version: None,
           output: None,
           version_or_hash_or_channel: None,
+          channel: None,
         }),
         ..Flags::default()
       }
@@ -9375,6 +10787,8 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Cache(CacheFlags {
           files: svec!["script.ts", "script_two.ts"],
+          export: None,
+          import: None,
         }),
         ca_data: Some(CaData::File("example.crt".to_owned())),
         ..Flags::default()
@@ -9397,6 +10811,7 @@ mod tests {
         subcommand: DenoSubcommand::Info(InfoFlags {
           json: false,
           file: Some("https://example.com".to_string()),
+          why: None,
         }),
         ca_data: Some(CaData::File("example.crt".to_owned())),
         ..Flags::default()
@@ -9706,6 +11121,70 @@ mod tests {
     );
   }
 
+  #[test]
+  fn profiling() {
+    let r = flags_from_vec(svec!["deno", "run", "--cpu-prof", "foo.js"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "foo.js".to_string(),
+        )),
+        cpu_prof: Some(ProfileFlags::default()),
+        code_cache_enabled: true,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--cpu-prof",
+      "--cpu-prof-dir=profiles",
+      "--cpu-prof-name=main.cpuprofile",
+      "--cpu-prof-interval=100",
+      "--heap-prof",
+      "--heap-prof-interval=1024",
+      "foo.js"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags::new_default(
+          "foo.js".to_string(),
+        )),
+        cpu_prof: Some(ProfileFlags {
+          dir: Some("profiles".to_string()),
+          name: Some("main.cpuprofile".to_string()),
+          interval: Some(100),
+        }),
+        heap_prof: Some(ProfileFlags {
+          dir: None,
+          name: None,
+          interval: Some(1024),
+        }),
+        code_cache_enabled: true,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "test", "--heap-prof"]);
+    assert_eq!(r.unwrap().heap_prof, Some(ProfileFlags::default()));
+
+    // the options require the profiler to be enabled
+    let r =
+      flags_from_vec(svec!["deno", "run", "--cpu-prof-dir=profiles", "foo.js"]);
+    assert!(r.is_err());
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--cpu-prof",
+      "--cpu-prof-interval=0",
+      "foo.js"
+    ]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn compile() {
     let r = flags_from_vec(svec![
@@ -9724,9 +11203,14 @@ mod tests {
           target: None,
           no_terminal: false,
           icon: None,
-          include: vec![]
+          include: vec![],
+          windows_version_info: vec![],
+          sign_command: None,
+          compress: false,
+          strip: false,
         }),
         type_check_mode: TypeCheckMode::Local,
+        code_cache_enabled: true,
         ..Flags::default()
       }
     );
@@ -9735,7 +11219,7 @@ mod tests {
   #[test]
   fn compile_with_flags() {
     #[rustfmt::skip]
-    let r = flags_from_vec(svec!["deno", "compile", "--import-map", "import_map.json", "--no-remote", "--config", "tsconfig.json", "--no-check", "--unsafely-ignore-certificate-errors", "--reload", "--lock", "lock.json", "--cert", "example.crt", "--cached-only", "--location", "https:foo", "--allow-read", "--allow-net", "--v8-flags=--help", "--seed", "1", "--no-terminal", "--icon", "favicon.ico", "--output", "colors", "--env=.example.env", "https://examples.deno.land/color-logging.ts", "foo", "bar", "-p", "8080"]);
+    let r = flags_from_vec(svec!["deno", "compile", "--import-map", "import_map.json", "--no-remote", "--config", "tsconfig.json", "--no-check", "--unsafely-ignore-certificate-errors", "--reload", "--lock", "lock.json", "--cert", "example.crt", "--cached-only", "--location", "https:foo", "--allow-read", "--allow-net", "--v8-flags=--help", "--seed", "1", "--no-terminal", "--no-code-cache", "--icon", "favicon.ico", "--output", "colors", "--env=.example.env", "https://examples.deno.land/color-logging.ts", "foo", "bar", "-p", "8080"]);
     assert_eq!(
       r.unwrap(),
       Flags {
@@ -9747,7 +11231,11 @@ mod tests {
           target: None,
           no_terminal: true,
           icon: Some(String::from("favicon.ico")),
-          include: vec![]
+          include: vec![],
+          windows_version_info: vec![],
+          sign_command: None,
+          compress: false,
+          strip: false,
         }),
         import_map_path: Some("import_map.json".to_string()),
         no_remote: true,
@@ -9766,7 +11254,85 @@ mod tests {
         unsafely_ignore_certificate_errors: Some(vec![]),
         v8_flags: svec!["--help", "--random-seed=1"],
         seed: Some(1),
-        env_file: Some(".example.env".to_owned()),
+        env_file: Some(vec![".example.env".to_owned()]),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn compile_with_windows_version_info_and_sign_command() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "compile",
+      "--target",
+      "x86_64-pc-windows-msvc",
+      "--windows-version-info=ProductName=My App",
+      "--windows-version-info=FileVersion=1.2.3",
+      "--sign-command=signtool sign /a",
+      "main.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Compile(CompileFlags {
+          source_file: "main.ts".to_string(),
+          output: None,
+          args: vec![],
+          target: Some("x86_64-pc-windows-msvc".to_string()),
+          no_terminal: false,
+          icon: None,
+          include: vec![],
+          windows_version_info: vec![
+            ("ProductName".to_string(), "My App".to_string()),
+            ("FileVersion".to_string(), "1.2.3".to_string()),
+          ],
+          sign_command: Some("signtool sign /a".to_string()),
+          compress: false,
+          strip: false,
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        code_cache_enabled: true,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "compile",
+      "--windows-version-info=ProductName",
+      "main.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn compile_with_compress_and_strip() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "compile",
+      "--compress",
+      "--strip",
+      "main.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Compile(CompileFlags {
+          source_file: "main.ts".to_string(),
+          output: None,
+          args: vec![],
+          target: None,
+          no_terminal: false,
+          icon: None,
+          include: vec![],
+          windows_version_info: vec![],
+          sign_command: None,
+          compress: true,
+          strip: true,
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        code_cache_enabled: true,
         ..Flags::default()
       }
     );
@@ -10147,6 +11713,8 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Bench(BenchFlags {
           filter: Some("- foo".to_string()),
+          tags: None,
+          skip_tags: None,
           json: true,
           no_run: true,
           files: FileFlags {
@@ -10178,6 +11746,8 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Bench(BenchFlags {
           filter: None,
+          tags: None,
+          skip_tags: None,
           json: false,
           no_run: false,
           files: FileFlags {
@@ -10285,6 +11855,8 @@ mod tests {
           dir: None,
           lib: false,
           serve: false,
+          template: None,
+          ci: false,
         }),
         ..Flags::default()
       }
@@ -10298,6 +11870,8 @@ mod tests {
           dir: Some(String::from("foo")),
           lib: false,
           serve: false,
+          template: None,
+          ci: false,
         }),
         ..Flags::default()
       }
@@ -10311,6 +11885,8 @@ mod tests {
           dir: None,
           lib: false,
           serve: false,
+          template: None,
+          ci: false,
         }),
         log_level: Some(Level::Error),
         ..Flags::default()
@@ -10325,6 +11901,8 @@ mod tests {
           dir: None,
           lib: true,
           serve: false,
+          template: None,
+          ci: false,
         }),
         ..Flags::default()
       }
@@ -10338,6 +11916,8 @@ mod tests {
           dir: None,
           lib: false,
           serve: true,
+          template: None,
+          ci: false,
         }),
         ..Flags::default()
       }
@@ -10351,10 +11931,36 @@ mod tests {
           dir: Some(String::from("foo")),
           lib: true,
           serve: false,
+          template: None,
+          ci: false,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "init",
+      "--template=workspace",
+      "--ci",
+      "foo"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Init(InitFlags {
+          dir: Some(String::from("foo")),
+          lib: false,
+          serve: false,
+          template: Some(String::from("workspace")),
+          ci: true,
         }),
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec(svec!["deno", "init", "--template=cli", "--lib"]);
+    assert!(r.is_err());
   }
 
   #[test]
@@ -10365,6 +11971,8 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Jupyter(JupyterFlags {
           install: false,
+          name: None,
+          display: None,
           kernel: false,
           conn_file: None,
         }),
@@ -10378,6 +11986,8 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Jupyter(JupyterFlags {
           install: true,
+          name: None,
+          display: None,
           kernel: false,
           conn_file: None,
         }),
@@ -10397,6 +12007,8 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Jupyter(JupyterFlags {
           install: false,
+          name: None,
+          display: None,
           kernel: true,
           conn_file: Some(String::from("path/to/conn/file")),
         }),
@@ -10416,6 +12028,42 @@ mod tests {
     r.unwrap_err();
     let r = flags_from_vec(svec!["deno", "jupyter", "--install", "--kernel",]);
     r.unwrap_err();
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "jupyter",
+      "--install",
+      "--name",
+      "deno-project",
+      "--display",
+      "Deno (project)"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Jupyter(JupyterFlags {
+          install: true,
+          name: Some(String::from("deno-project")),
+          display: Some(String::from("Deno (project)")),
+          kernel: false,
+          conn_file: None,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "jupyter", "--display", "Deno"]);
+    r.unwrap_err();
+    let r = flags_from_vec(svec![
+      "deno",
+      "jupyter",
+      "--kernel",
+      "--name",
+      "deno-project",
+      "--conn",
+      "path/to/conn/file"
+    ]);
+    r.unwrap_err();
   }
 
   #[test]
@@ -10582,6 +12230,8 @@ mod tests {
             Flags {
               subcommand: DenoSubcommand::Cache(CacheFlags {
                 files: svec!["script.ts"],
+                export: None,
+                import: None,
               }),
               allow_scripts: value,
               ..Flags::default()
@@ -10733,6 +12383,8 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Jupyter(JupyterFlags {
           install: false,
+          name: None,
+          display: None,
           kernel: false,
           conn_file: None,
         }),
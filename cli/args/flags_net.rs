@@ -31,6 +31,8 @@ pub fn validator(host_and_port: &str) -> Result<String, String> {
   if Url::parse(&format!("internal://{host_and_port}")).is_ok()
     || host_and_port.parse::<IpAddr>().is_ok()
     || host_and_port.parse::<BarePort>().is_ok()
+    || (host_and_port.starts_with("vsock:")
+      && NetDescriptor::parse(host_and_port).is_ok())
   {
     Ok(host_and_port.to_string())
   } else {
@@ -177,6 +179,16 @@ mod tests {
     assert_eq!(actual, expected);
   }
 
+  #[test]
+  fn parse_net_args_vsock() {
+    let entries = svec!["vsock:3", "vsock:3:5000"];
+    let expected = svec!["vsock:3", "vsock:3:5000"];
+    let actual = parse(entries).unwrap();
+    assert_eq!(actual, expected);
+    assert!(super::validator("vsock:3:5000").is_ok());
+    assert!(parse(svec!["vsock:host"]).is_err());
+  }
+
   #[test]
   fn parse_net_args_ipv6_error1() {
     let entries = svec![":::"];
@@ -1,9 +1,17 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::ops::Range;
+
+use deno_config::deno_json::ConfigFile;
+use deno_config::workspace::SpecifiedImportMap;
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
 use deno_core::serde_json;
 use deno_core::url::Url;
+use indexmap::IndexMap;
 
+use crate::args::deno_json::read_import_maps_config;
 use crate::file_fetcher::FileFetcher;
 
 pub async fn resolve_import_map_value_from_specifier(
@@ -22,3 +30,501 @@ pub async fn resolve_import_map_value_from_specifier(
     Ok(serde_json::from_str(&file.source)?)
   }
 }
+
+/// Composes the import maps in the `"importMaps"` of the config file with its
+/// own import map, if it has any.
+pub async fn resolve_composed_import_map(
+  config_file: &ConfigFile,
+  file_fetcher: &FileFetcher,
+) -> Result<Option<SpecifiedImportMap>, AnyError> {
+  let specifiers = read_import_maps_config(config_file)?;
+  if specifiers.is_empty() {
+    return Ok(None);
+  }
+  let mut import_maps = Vec::with_capacity(specifiers.len() + 1);
+  for specifier in specifiers {
+    let value =
+      resolve_import_map_value_from_specifier(&specifier, file_fetcher)
+        .await
+        .with_context(|| {
+          format!("Unable to load '{}' import map", specifier)
+        })?;
+    import_maps.push((specifier, value));
+  }
+  if let Some(import_map) = &config_file.json.import_map {
+    let specifier = config_file.specifier.join(import_map)?;
+    let value =
+      resolve_import_map_value_from_specifier(&specifier, file_fetcher)
+        .await
+        .with_context(|| {
+          format!("Unable to load '{}' import map", specifier)
+        })?;
+    import_maps.push((specifier, value));
+  } else {
+    let mut value = serde_json::Map::new();
+    if let Some(imports) = &config_file.json.imports {
+      value.insert("imports".to_string(), imports.clone());
+    }
+    if let Some(scopes) = &config_file.json.scopes {
+      value.insert("scopes".to_string(), scopes.clone());
+    }
+    import_maps.push((
+      config_file.specifier.clone(),
+      serde_json::Value::Object(value),
+    ));
+  }
+  Ok(Some(SpecifiedImportMap {
+    base_url: config_file.specifier.clone(),
+    value: compose_import_maps(import_maps)?,
+  }))
+}
+
+/// Composes import maps into one, where the entries of the later import maps
+/// take precedence over the same entries of the earlier ones. The entries of
+/// the `"scopes"` are composed per scope.
+///
+/// The relative specifiers are resolved against the URL of their import map,
+/// as the composed import map has a single base URL.
+pub fn compose_import_maps(
+  import_maps: Vec<(Url, serde_json::Value)>,
+) -> Result<serde_json::Value, AnyError> {
+  fn is_relative(specifier: &str) -> bool {
+    specifier.starts_with("./")
+      || specifier.starts_with("../")
+      || specifier.starts_with('/')
+  }
+
+  fn resolve_entries(
+    base_url: &Url,
+    value: Option<&serde_json::Value>,
+    property: &str,
+  ) -> Result<serde_json::Map<String, serde_json::Value>, AnyError> {
+    let entries = match value {
+      Some(serde_json::Value::Object(entries)) => entries,
+      Some(_) => bail!("\"{}\" must be an object.", property),
+      None => return Ok(Default::default()),
+    };
+    let mut resolved = serde_json::Map::with_capacity(entries.len());
+    for (key, value) in entries {
+      let key = if is_relative(key) {
+        base_url.join(key)?.to_string()
+      } else {
+        key.clone()
+      };
+      let value = match value {
+        serde_json::Value::String(address) if is_relative(address) => {
+          serde_json::Value::String(base_url.join(address)?.to_string())
+        }
+        value => value.clone(),
+      };
+      resolved.insert(key, value);
+    }
+    Ok(resolved)
+  }
+
+  let mut imports = serde_json::Map::new();
+  let mut scopes: IndexMap<String, serde_json::Map<String, serde_json::Value>> =
+    IndexMap::new();
+  for (base_url, value) in import_maps {
+    let serde_json::Value::Object(value) = value else {
+      bail!("Import map '{}' must be an object.", base_url);
+    };
+    let in_import_map = || format!("Invalid import map '{}'", base_url);
+    imports.extend(
+      resolve_entries(&base_url, value.get("imports"), "imports")
+        .with_context(in_import_map)?,
+    );
+    let import_map_scopes =
+      resolve_entries(&base_url, value.get("scopes"), "scopes")
+        .with_context(in_import_map)?;
+    for (scope, entries) in import_map_scopes {
+      let entries = resolve_entries(&base_url, Some(&entries), &scope)
+        .with_context(in_import_map)?;
+      // the scopes are URLs resolved against the base URL, even without a
+      // leading "./"
+      let scope = match Url::parse(&scope) {
+        Ok(url) => url.to_string(),
+        Err(_) => base_url.join(&scope)?.to_string(),
+      };
+      scopes.entry(scope).or_default().extend(entries);
+    }
+  }
+
+  let mut composed = serde_json::Map::new();
+  composed.insert("imports".to_string(), serde_json::Value::Object(imports));
+  if !scopes.is_empty() {
+    let scopes = scopes
+      .into_iter()
+      .map(|(scope, entries)| (scope, serde_json::Value::Object(entries)))
+      .collect();
+    composed.insert("scopes".to_string(), serde_json::Value::Object(scopes));
+  }
+  Ok(serde_json::Value::Object(composed))
+}
+
+/// An entry of the `"imports"` or of one of the `"scopes"` of an import map,
+/// as written in an import map or config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportMapEntryText {
+  pub scope: Option<String>,
+  pub key: String,
+  /// The byte range of the property.
+  pub range: Range<usize>,
+  /// The byte range to delete to remove the property, including the comma
+  /// separating it from a sibling.
+  pub removal_range: Range<usize>,
+}
+
+/// An import map entry with its scope and key resolved against the location
+/// of the import map.
+struct ResolvedImportMapEntry {
+  scope: Option<Url>,
+  key: String,
+  is_url_like: bool,
+}
+
+impl ResolvedImportMapEntry {
+  fn resolve(base: &Url, entry: &ImportMapEntryText) -> Option<Self> {
+    let scope = match &entry.scope {
+      Some(scope) => Some(base.join(scope).ok()?),
+      None => None,
+    };
+    let is_url_like = entry.key.starts_with('/')
+      || entry.key.starts_with("./")
+      || entry.key.starts_with("../")
+      || Url::parse(&entry.key).is_ok();
+    let key = if is_url_like {
+      base.join(&entry.key).ok()?.to_string()
+    } else {
+      entry.key.clone()
+    };
+    Some(Self {
+      scope,
+      key,
+      is_url_like,
+    })
+  }
+
+  /// If an import of `specifier` from `referrer`, which resolves to
+  /// `maybe_url` as a URL, may be resolved through this entry.
+  fn matches(
+    &self,
+    referrer: &Url,
+    specifier: &str,
+    maybe_url: Option<&Url>,
+  ) -> bool {
+    if let Some(scope) = &self.scope {
+      let in_scope = if scope.as_str().ends_with('/') {
+        referrer.as_str().starts_with(scope.as_str())
+      } else {
+        referrer == scope
+      };
+      if !in_scope {
+        return false;
+      }
+    }
+    let specifier = if self.is_url_like {
+      let Some(url) = maybe_url else {
+        return false;
+      };
+      url.as_str()
+    } else {
+      specifier
+    };
+    if self.key.ends_with('/') {
+      specifier.starts_with(&self.key)
+    } else {
+      // bare keys of config files also map their sub paths
+      specifier == self.key
+        || (!self.is_url_like
+          && specifier
+            .strip_prefix(&self.key)
+            .is_some_and(|rest| rest.starts_with('/')))
+    }
+  }
+}
+
+/// Collects the entries of the `"imports"` and `"scopes"` of the import map
+/// or config file text, in the order they're written.
+pub fn parse_import_map_entries(text: &str) -> Vec<ImportMapEntryText> {
+  use jsonc_parser::ast::Object;
+  use jsonc_parser::ast::Value;
+
+  fn collect_entries(
+    object: &Object,
+    scope: Option<&str>,
+    entries: &mut Vec<ImportMapEntryText>,
+  ) {
+    let properties = &object.properties;
+    for (i, prop) in properties.iter().enumerate() {
+      let removal_range = if let Some(next) = properties.get(i + 1) {
+        prop.range.start..next.range.start
+      } else if let Some(previous) = i.checked_sub(1).map(|i| &properties[i]) {
+        previous.range.end..prop.range.end
+      } else {
+        prop.range.start..prop.range.end
+      };
+      entries.push(ImportMapEntryText {
+        scope: scope.map(|s| s.to_string()),
+        key: prop.name.as_str().to_string(),
+        range: prop.range.start..prop.range.end,
+        removal_range,
+      });
+    }
+  }
+
+  let Ok(ast) =
+    jsonc_parser::parse_to_ast(text, &Default::default(), &Default::default())
+  else {
+    return Vec::new();
+  };
+  let Some(Value::Object(root)) = &ast.value else {
+    return Vec::new();
+  };
+  let mut entries = Vec::new();
+  for prop in &root.properties {
+    match (prop.name.as_str(), &prop.value) {
+      ("imports", Value::Object(imports)) => {
+        collect_entries(imports, None, &mut entries);
+      }
+      ("scopes", Value::Object(scopes)) => {
+        for scope_prop in &scopes.properties {
+          if let Value::Object(scope) = &scope_prop.value {
+            collect_entries(
+              scope,
+              Some(scope_prop.name.as_str()),
+              &mut entries,
+            );
+          }
+        }
+      }
+      _ => {}
+    }
+  }
+  entries
+}
+
+/// Finds the entries which are ignored because an entry with the same key
+/// follows them in the same object.
+pub fn find_duplicate_import_map_entries(
+  entries: &[ImportMapEntryText],
+) -> Vec<&ImportMapEntryText> {
+  entries
+    .iter()
+    .enumerate()
+    .filter(|(i, entry)| {
+      entries[i + 1..]
+        .iter()
+        .any(|e| e.scope == entry.scope && e.key == entry.key)
+    })
+    .map(|(_, entry)| entry)
+    .collect()
+}
+
+/// Finds the entries of an import map located at `base` which none of the
+/// `(referrer, specifier)` imports may be resolved through. Duplicate entries
+/// are left to [`find_duplicate_import_map_entries`].
+pub fn find_unused_import_map_entries<'a>(
+  base: &Url,
+  entries: &'a [ImportMapEntryText],
+  imports: &[(Url, String)],
+) -> Vec<&'a ImportMapEntryText> {
+  let duplicates = find_duplicate_import_map_entries(entries);
+  let imports = imports
+    .iter()
+    .map(|(referrer, specifier)| {
+      (referrer, specifier.as_str(), referrer.join(specifier).ok())
+    })
+    .collect::<Vec<_>>();
+  entries
+    .iter()
+    .filter(|entry| !duplicates.contains(entry))
+    .filter(|entry| {
+      let Some(resolved) = ResolvedImportMapEntry::resolve(base, entry) else {
+        return false;
+      };
+      !imports.iter().any(|(referrer, specifier, maybe_url)| {
+        resolved.matches(referrer, specifier, maybe_url.as_ref())
+      })
+    })
+    .collect()
+}
+
+/// The specifiers a config file imports itself, such as the
+/// `"jsxImportSource"` and `"types"` compiler options.
+pub fn config_file_imports(config_file: &ConfigFile) -> Vec<(Url, String)> {
+  let mut imports = Vec::new();
+  if let Ok(Some(jsx_config)) = config_file.to_maybe_jsx_import_source_config()
+  {
+    for specifier in [
+      jsx_config.maybe_specifier_text(),
+      jsx_config.maybe_types_specifier_text(),
+    ]
+    .into_iter()
+    .flatten()
+    {
+      imports.push((jsx_config.base_url.clone(), specifier));
+    }
+  }
+  if let Ok(types) = config_file.to_compiler_option_types() {
+    for (referrer, specifiers) in types {
+      for specifier in specifiers {
+        imports.push((referrer.clone(), specifier));
+      }
+    }
+  }
+  imports
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn keys(entries: Vec<&ImportMapEntryText>) -> Vec<&str> {
+    entries.into_iter().map(|e| e.key.as_str()).collect()
+  }
+
+  #[test]
+  fn test_parse_import_map_entries() {
+    let text = r#"{
+  "imports": {
+    "a": "./a.ts",
+    "b": "./b.ts"
+  },
+  "scopes": { "./vendor/": { "c": "./c.ts" } }
+}"#;
+    let entries = parse_import_map_entries(text);
+    assert_eq!(
+      entries
+        .iter()
+        .map(|e| (
+          e.scope.as_deref(),
+          e.key.as_str(),
+          &text[e.range.clone()],
+          &text[e.removal_range.clone()]
+        ))
+        .collect::<Vec<_>>(),
+      vec![
+        (None, "a", r#""a": "./a.ts""#, "\"a\": \"./a.ts\",\n    "),
+        (None, "b", r#""b": "./b.ts""#, ",\n    \"b\": \"./b.ts\""),
+        (
+          Some("./vendor/"),
+          "c",
+          r#""c": "./c.ts""#,
+          r#""c": "./c.ts""#
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_find_duplicate_import_map_entries() {
+    let entries = parse_import_map_entries(
+      r#"{
+  "imports": { "a": "./a.ts", "b": "./b.ts", "a": "./a2.ts" },
+  "scopes": { "./vendor/": { "b": "./b.ts" } }
+}"#,
+    );
+    let duplicates = find_duplicate_import_map_entries(&entries);
+    assert_eq!(duplicates, vec![&entries[0]]);
+  }
+
+  #[test]
+  fn test_find_unused_import_map_entries() {
+    let base = Url::parse("file:///project/deno.json").unwrap();
+    let entries = parse_import_map_entries(
+      r#"{
+  "imports": {
+    "@std/path": "jsr:@std/path@1",
+    "@std/fs": "jsr:@std/fs@1",
+    "chalk": "npm:chalk@5",
+    "utils/": "./utils/",
+    "./polyfill.ts": "./polyfill_deno.ts",
+    "unused": "./unused.ts"
+  },
+  "scopes": {
+    "./vendor/": { "preact": "npm:preact@10" },
+    "./other/": { "chalk": "npm:chalk@4" }
+  }
+}"#,
+    );
+    let main = Url::parse("file:///project/main.ts").unwrap();
+    let vendored = Url::parse("file:///project/vendor/mod.ts").unwrap();
+    let imports = vec![
+      (main.clone(), "@std/path/posix".to_string()),
+      (main.clone(), "chalk".to_string()),
+      (main.clone(), "utils/mod.ts".to_string()),
+      (main.clone(), "./polyfill.ts".to_string()),
+      (main.clone(), "@std/fsx".to_string()),
+      (vendored, "preact".to_string()),
+    ];
+    let unused = find_unused_import_map_entries(&base, &entries, &imports);
+    assert_eq!(keys(unused), vec!["@std/fs", "unused", "chalk"]);
+  }
+
+  #[test]
+  fn test_compose_import_maps() {
+    let org_url =
+      Url::parse("https://example.com/org/import_map.json").unwrap();
+    let local_url = Url::parse("file:///project/deno.json").unwrap();
+    let composed = compose_import_maps(vec![
+      (
+        org_url,
+        serde_json::json!({
+          "imports": {
+            "@org/utils": "./utils/mod.ts",
+            "@org/log": "./log/mod.ts",
+            "./shim.ts": "/shims/shim.ts"
+          },
+          "scopes": {
+            "vendor/": { "a": "./vendor/a.ts" }
+          }
+        }),
+      ),
+      (
+        local_url,
+        serde_json::json!({
+          "imports": {
+            "@org/log": "./log.ts",
+            "@std/path": "jsr:@std/path@1"
+          },
+          "scopes": {
+            "https://example.com/org/vendor/": { "b": "./b.ts" }
+          }
+        }),
+      ),
+    ])
+    .unwrap();
+    assert_eq!(
+      composed,
+      serde_json::json!({
+        "imports": {
+          "@org/utils": "https://example.com/org/utils/mod.ts",
+          "@org/log": "file:///project/log.ts",
+          "https://example.com/org/shim.ts": "https://example.com/shims/shim.ts",
+          "@std/path": "jsr:@std/path@1"
+        },
+        "scopes": {
+          "https://example.com/org/vendor/": {
+            "a": "https://example.com/org/vendor/a.ts",
+            "b": "file:///project/b.ts"
+          }
+        }
+      })
+    );
+  }
+
+  #[test]
+  fn test_compose_import_maps_invalid() {
+    let url = Url::parse("file:///project/import_map.json").unwrap();
+    let err = compose_import_maps(vec![(
+      url,
+      serde_json::json!({ "imports": ["./a.ts"] }),
+    )])
+    .unwrap_err();
+    assert_eq!(
+      format!("{:#}", err),
+      "Invalid import map 'file:///project/import_map.json': \"imports\" must be an object."
+    );
+  }
+}
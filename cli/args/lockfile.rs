@@ -1,36 +1,60 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::collections::BTreeMap;
 use std::collections::HashSet;
 use std::path::PathBuf;
 
 use deno_config::deno_json::ConfigFile;
+use deno_config::workspace::NpmPackageConfig;
 use deno_config::workspace::Workspace;
+use deno_core::anyhow::bail;
 use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
 use deno_core::parking_lot::Mutex;
 use deno_core::parking_lot::MutexGuard;
+use deno_core::serde_json;
+use deno_core::ModuleSpecifier;
 use deno_lockfile::WorkspaceMemberConfig;
+use deno_npm::NpmPackageId;
 use deno_package_json::PackageJsonDepValue;
 use deno_runtime::deno_node::PackageJson;
 use deno_semver::jsr::JsrDepPackageReq;
+use deno_semver::package::PackageKind;
+use deno_semver::package::PackageNv;
+use deno_semver::Version;
 
 use crate::cache;
+use crate::colors;
 use crate::util::fs::atomic_write_file_with_retries;
 use crate::Flags;
 
+use crate::args::jsr_url;
 use crate::args::DenoSubcommand;
 use crate::args::InstallFlags;
 use crate::args::InstallKind;
+use crate::args::InstallNpmPatchedPkg;
+
+use super::node_lockfile::import_node_lockfile;
+use super::node_lockfile::PackageJsonDep;
 
 use deno_lockfile::Lockfile;
 
 #[derive(Debug)]
 pub struct CliLockfile {
   lockfile: Mutex<Lockfile>,
+  npm_patch_hashes: Mutex<NpmPatchHashes>,
   pub filename: PathBuf,
   pub frozen: bool,
 }
 
+/// The checksums of the patches applied to npm packages, which are stored as
+/// the `"patchHash"` of the packages in the lockfile, like pnpm does.
+#[derive(Debug, Default)]
+struct NpmPatchHashes {
+  by_nv: BTreeMap<PackageNv, String>,
+  has_changed: bool,
+}
+
 pub struct Guard<'a, T> {
   guard: MutexGuard<'a, T>,
 }
@@ -54,6 +78,7 @@ impl CliLockfile {
     let filename = lockfile.filename.clone();
     Self {
       lockfile: Mutex::new(lockfile),
+      npm_patch_hashes: Default::default(),
       filename,
       frozen,
     }
@@ -73,6 +98,46 @@ impl CliLockfile {
     self.lockfile.lock().set_workspace_config(options);
   }
 
+  /// Records the checksums of the patches applied to the npm packages in the
+  /// lockfile. Errors when the lockfile is frozen and has a different patch
+  /// for one of them.
+  pub fn set_npm_patches(
+    &self,
+    patched_pkgs: &[InstallNpmPatchedPkg],
+  ) -> Result<(), AnyError> {
+    let lockfile = self.lockfile.lock();
+    let locked_nvs = lockfile
+      .content
+      .packages
+      .npm
+      .keys()
+      .filter_map(|id| NpmPackageId::from_serialized(id).ok())
+      .map(|id| id.nv)
+      .collect::<HashSet<_>>();
+    let by_nv = patched_pkgs
+      .iter()
+      .filter(|pkg| locked_nvs.contains(&pkg.nv))
+      .map(|pkg| (pkg.nv.clone(), pkg.checksum.clone()))
+      .collect::<BTreeMap<_, _>>();
+    let mut patch_hashes = self.npm_patch_hashes.lock();
+    if patch_hashes.by_nv == by_nv {
+      return Ok(());
+    }
+    if self.frozen {
+      let nv = by_nv
+        .keys()
+        .chain(patch_hashes.by_nv.keys())
+        .find(|nv| by_nv.get(nv) != patch_hashes.by_nv.get(nv))
+        .unwrap();
+      bail!(
+        "The patch of npm package '{nv}' doesn't match the lockfile. Run `deno install --frozen=false`, or rerun with `--frozen=false` to update it."
+      );
+    }
+    patch_hashes.by_nv = by_nv;
+    patch_hashes.has_changed = true;
+    Ok(())
+  }
+
   pub fn overwrite(&self) -> bool {
     self.lockfile.lock().overwrite
   }
@@ -80,18 +145,23 @@ impl CliLockfile {
   pub fn write_if_changed(&self) -> Result<(), AnyError> {
     self.error_if_changed()?;
     let mut lockfile = self.lockfile.lock();
-    let Some(bytes) = lockfile.resolve_write_bytes() else {
-      return Ok(()); // nothing to do
+    let mut patch_hashes = self.npm_patch_hashes.lock();
+    let text = match lockfile.resolve_write_bytes() {
+      Some(bytes) => String::from_utf8(bytes)?,
+      None if patch_hashes.has_changed => lockfile.as_json_string(),
+      None => return Ok(()), // nothing to do
     };
+    let text = add_npm_patch_hashes(text, &patch_hashes.by_nv);
     // do an atomic write to reduce the chance of multiple deno
     // processes corrupting the file
     atomic_write_file_with_retries(
       &lockfile.filename,
-      bytes,
+      text.as_bytes(),
       cache::CACHE_PERM,
     )
     .context("Failed writing lockfile.")?;
     lockfile.has_content_changed = false;
+    patch_hashes.has_changed = false;
     Ok(())
   }
 
@@ -101,6 +171,7 @@ impl CliLockfile {
   ) -> Result<Option<CliLockfile>, AnyError> {
     fn pkg_json_deps(
       maybe_pkg_json: Option<&PackageJson>,
+      workspace_npm_pkgs: &[NpmPackageConfig],
     ) -> HashSet<JsrDepPackageReq> {
       let Some(pkg_json) = maybe_pkg_json else {
         return Default::default();
@@ -111,7 +182,12 @@ impl CliLockfile {
         .filter_map(|dep| dep.as_ref().ok())
         .filter_map(|dep| match dep {
           PackageJsonDepValue::Req(req) => {
-            Some(JsrDepPackageReq::npm(req.clone()))
+            // workspace members are linked rather than resolved from the
+            // registry, so they don't belong in the lockfile
+            let is_workspace_pkg = workspace_npm_pkgs.iter().any(|pkg| {
+              pkg.matches_req(req) && pkg.pkg_json.path != pkg_json.path
+            });
+            (!is_workspace_pkg).then(|| JsrDepPackageReq::npm(req.clone()))
           }
           PackageJsonDepValue::Workspace(_) => None,
         })
@@ -120,28 +196,44 @@ impl CliLockfile {
 
     fn deno_json_deps(
       maybe_deno_json: Option<&ConfigFile>,
+      workspace_npm_pkgs: &[NpmPackageConfig],
     ) -> HashSet<JsrDepPackageReq> {
       maybe_deno_json
         .map(|c| {
           crate::args::deno_json::deno_json_deps(c)
             .into_iter()
+            .filter(|dep| {
+              dep.kind != PackageKind::Npm
+                || !workspace_npm_pkgs
+                  .iter()
+                  .any(|pkg| pkg.matches_req(&dep.req))
+            })
             .collect()
         })
         .unwrap_or_default()
     }
 
-    if flags.no_lock
-      || matches!(
-        flags.subcommand,
-        DenoSubcommand::Install(InstallFlags {
-          kind: InstallKind::Global(..),
-          ..
-        }) | DenoSubcommand::Uninstall(_)
-      )
+    if flags.no_lock || matches!(flags.subcommand, DenoSubcommand::Uninstall(_))
     {
       return Ok(None);
     }
 
+    // global installs never use the lockfile of the current project, only
+    // the one provided with --lock that is then copied next to the shim
+    if matches!(
+      flags.subcommand,
+      DenoSubcommand::Install(InstallFlags {
+        kind: InstallKind::Global(..),
+        ..
+      })
+    ) {
+      let Some(lock) = &flags.lock else {
+        return Ok(None);
+      };
+      let frozen = flags.frozen_lockfile.unwrap_or(false);
+      return Self::read_from_path(PathBuf::from(lock), frozen).map(Some);
+    }
+
     let filename = match flags.lock {
       Some(ref lock) => PathBuf::from(lock),
       None => match workspace.resolve_lockfile_path()? {
@@ -160,14 +252,22 @@ impl CliLockfile {
         .unwrap_or(false)
     });
 
+    let is_new_lockfile = !filename.exists();
     let lockfile = Self::read_from_path(filename, frozen)?;
 
     // initialize the lockfile with the workspace's configuration
     let root_url = workspace.root_dir();
+    let workspace_npm_pkgs = workspace.npm_packages();
     let config = deno_lockfile::WorkspaceConfig {
       root: WorkspaceMemberConfig {
-        package_json_deps: pkg_json_deps(root_folder.pkg_json.as_deref()),
-        dependencies: deno_json_deps(root_folder.deno_json.as_deref()),
+        package_json_deps: pkg_json_deps(
+          root_folder.pkg_json.as_deref(),
+          &workspace_npm_pkgs,
+        ),
+        dependencies: deno_json_deps(
+          root_folder.deno_json.as_deref(),
+          &workspace_npm_pkgs,
+        ),
       },
       members: workspace
         .config_folders()
@@ -187,8 +287,14 @@ impl CliLockfile {
             },
             {
               let config = WorkspaceMemberConfig {
-                package_json_deps: pkg_json_deps(folder.pkg_json.as_deref()),
-                dependencies: deno_json_deps(folder.deno_json.as_deref()),
+                package_json_deps: pkg_json_deps(
+                  folder.pkg_json.as_deref(),
+                  &workspace_npm_pkgs,
+                ),
+                dependencies: deno_json_deps(
+                  folder.deno_json.as_deref(),
+                  &workspace_npm_pkgs,
+                ),
               };
               if config.package_json_deps.is_empty()
                 && config.dependencies.is_empty()
@@ -208,22 +314,111 @@ impl CliLockfile {
       config,
     });
 
+    if is_new_lockfile && !flags.no_npm {
+      lockfile.import_node_lockfile(workspace, &workspace_npm_pkgs);
+    }
+
     Ok(Some(lockfile))
   }
 
+  /// Seeds the npm packages of a new lockfile with the ones of the
+  /// `package-lock.json` or `yarn.lock` of the workspace, if there is one.
+  fn import_node_lockfile(
+    &self,
+    workspace: &Workspace,
+    workspace_npm_pkgs: &[NpmPackageConfig],
+  ) {
+    let root_url = workspace.root_dir();
+    let Ok(root_dir) = root_url.to_file_path() else {
+      return;
+    };
+    let mut deps = Vec::new();
+    for (folder_url, folder) in workspace.config_folders() {
+      let Some(pkg_json) = &folder.pkg_json else {
+        continue;
+      };
+      let Some(dir) = root_url.make_relative(folder_url) else {
+        continue;
+      };
+      let dir = dir.trim_end_matches('/');
+      for (name, dep) in pkg_json.resolve_local_package_json_deps().iter() {
+        let Ok(PackageJsonDepValue::Req(req)) = dep else {
+          continue;
+        };
+        let is_workspace_pkg = workspace_npm_pkgs.iter().any(|pkg| {
+          pkg.matches_req(req) && pkg.pkg_json.path != pkg_json.path
+        });
+        if is_workspace_pkg {
+          continue;
+        }
+        let Some(version_req_text) =
+          [&pkg_json.dependencies, &pkg_json.dev_dependencies]
+            .into_iter()
+            .flatten()
+            .find_map(|deps| deps.get(name))
+        else {
+          continue;
+        };
+        deps.push(PackageJsonDep {
+          dir: dir.to_string(),
+          name: name.clone(),
+          version_req_text: version_req_text.clone(),
+          req: req.clone(),
+        });
+      }
+    }
+    if deps.is_empty() {
+      return;
+    }
+
+    match import_node_lockfile(&root_dir, &deps) {
+      Ok(Some(imported)) => {
+        let mut lockfile = self.lockfile.lock();
+        for (req, version) in imported.specifiers {
+          lockfile
+            .insert_package_specifier(JsrDepPackageReq::npm(req), version);
+        }
+        for package in imported.packages {
+          lockfile.insert_npm_package(package);
+        }
+        log::info!(
+          "{} npm package versions from {}",
+          colors::green("Imported"),
+          imported.path.display()
+        );
+        if !imported.skipped.is_empty() {
+          log::warn!(
+            "{} Skipped importing npm packages with peer dependencies, or depending on such packages, from {}: {}",
+            colors::yellow("Warning"),
+            imported.path.display(),
+            imported.skipped.join(", ")
+          );
+        }
+      }
+      Ok(None) => {}
+      Err(err) => {
+        log::warn!("{} {:#}", colors::yellow("Warning"), err);
+      }
+    }
+  }
+
   pub fn read_from_path(
     file_path: PathBuf,
     frozen: bool,
   ) -> Result<CliLockfile, AnyError> {
     match std::fs::read_to_string(&file_path) {
-      Ok(text) => Ok(CliLockfile::new(
-        Lockfile::new(deno_lockfile::NewLockfileOptions {
-          file_path,
-          content: &text,
-          overwrite: false,
-        })?,
-        frozen,
-      )),
+      Ok(text) => {
+        let lockfile = CliLockfile::new(
+          Lockfile::new(deno_lockfile::NewLockfileOptions {
+            file_path,
+            content: &text,
+            overwrite: false,
+          })?,
+          frozen,
+        );
+        lockfile.npm_patch_hashes.lock().by_nv = read_npm_patch_hashes(&text);
+        Ok(lockfile)
+      }
       Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(
         CliLockfile::new(Lockfile::new_empty(file_path, false), frozen),
       ),
@@ -252,4 +447,179 @@ impl CliLockfile {
       Ok(())
     }
   }
+
+  /// Errors when the lockfile is frozen and doesn't have the remote module,
+  /// so that a module that isn't locked is never fetched.
+  pub fn error_if_remote_not_locked(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Result<(), AnyError> {
+    if !self.frozen || !matches!(specifier.scheme(), "http" | "https") {
+      return Ok(());
+    }
+    let lockfile = self.lockfile.lock();
+    let missing_item = match specifier.as_str().strip_prefix(jsr_url().as_str())
+    {
+      // the files of jsr packages are verified against the manifests of
+      // their versions, so only the metadata is checked
+      Some(path) => {
+        if let Some(name) = path.strip_suffix("/meta.json") {
+          // the versions of a package are only loaded to resolve a
+          // requirement that isn't in the lockfile
+          Some(format!("The jsr package \"{name}\""))
+        } else if let Some((name, version)) = path
+          .strip_suffix("_meta.json")
+          .and_then(|path| path.rsplit_once('/'))
+        {
+          let is_locked =
+            Version::parse_standard(version).is_ok_and(|version| {
+              lockfile.content.packages.jsr.contains_key(&PackageNv {
+                name: name.to_string(),
+                version,
+              })
+            });
+          (!is_locked).then(|| format!("The jsr package \"{name}@{version}\""))
+        } else {
+          None
+        }
+      }
+      None => {
+        let is_locked = lockfile.remote().contains_key(specifier.as_str())
+          || lockfile.content.redirects.contains_key(specifier.as_str());
+        (!is_locked).then(|| format!("The module \"{specifier}\""))
+      }
+    };
+    match missing_item {
+      Some(item) => Err(Self::not_locked_error(&item)),
+      None => Ok(()),
+    }
+  }
+
+  /// Gets the error of a module or npm package that isn't in the frozen
+  /// lockfile.
+  pub fn not_locked_error(item: &str) -> AnyError {
+    deno_core::anyhow::anyhow!(
+      "{item} is not in the lockfile, which is frozen. Run `deno install --frozen=false`, or rerun with `--frozen=false` to add it."
+    )
+  }
+}
+
+/// Reads the `"patchHash"` of the npm packages in the lockfile, which
+/// `deno_lockfile` doesn't keep.
+fn read_npm_patch_hashes(text: &str) -> BTreeMap<PackageNv, String> {
+  let Ok(value) = serde_json::from_str::<serde_json::Value>(text) else {
+    return Default::default();
+  };
+  let Some(packages) = value.get("npm").and_then(|npm| npm.as_object()) else {
+    return Default::default();
+  };
+  packages
+    .iter()
+    .filter_map(|(id, info)| {
+      let patch_hash = info.get("patchHash")?.as_str()?;
+      let id = NpmPackageId::from_serialized(id).ok()?;
+      Some((id.nv, patch_hash.to_string()))
+    })
+    .collect()
+}
+
+/// Adds the `"patchHash"` of the patched npm packages to their entries in the
+/// lockfile, after the integrity that every entry starts with.
+fn add_npm_patch_hashes(
+  text: String,
+  by_nv: &BTreeMap<PackageNv, String>,
+) -> String {
+  if by_nv.is_empty() {
+    return text;
+  }
+  let mut output = String::with_capacity(text.len());
+  let mut in_npm = false;
+  let mut patch_hash = None;
+  for line in text.split_inclusive('\n') {
+    if line == "  \"npm\": {\n" {
+      in_npm = true;
+    } else if in_npm && line.starts_with("  }") {
+      in_npm = false;
+    } else if in_npm && line.starts_with("    \"") {
+      patch_hash = line
+        .trim()
+        .strip_suffix(": {")
+        .and_then(|id| serde_json::from_str::<String>(id).ok())
+        .and_then(|id| NpmPackageId::from_serialized(&id).ok())
+        .and_then(|id| by_nv.get(&id.nv));
+    } else if let Some(hash) =
+      patch_hash.filter(|_| in_npm && line.starts_with("      \"integrity\": "))
+    {
+      let has_more_fields = line.ends_with(",\n");
+      output.push_str(line.trim_end_matches([',', '\n']));
+      output.push_str(",\n      \"patchHash\": ");
+      output.push_str(&serde_json::to_string(hash).unwrap());
+      output.push_str(if has_more_fields { ",\n" } else { "\n" });
+      continue;
+    }
+    output.push_str(line);
+  }
+  output
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn npm_patch_hashes_round_trip() {
+    let text = r#"{
+  "version": "4",
+  "npm": {
+    "@denotest/a@1.0.0": {
+      "integrity": "sha512-a"
+    },
+    "@denotest/b@1.0.0_@denotest+a@1.0.0": {
+      "integrity": "sha512-b",
+      "dependencies": [
+        "@denotest/a"
+      ]
+    },
+    "@denotest/c@1.0.0": {
+      "integrity": "sha512-c"
+    }
+  }
+}
+"#;
+    let by_nv = BTreeMap::from([
+      (
+        PackageNv::from_str("@denotest/a@1.0.0").unwrap(),
+        "1".to_string(),
+      ),
+      (
+        PackageNv::from_str("@denotest/b@1.0.0").unwrap(),
+        "2".to_string(),
+      ),
+    ]);
+    let patched = add_npm_patch_hashes(text.to_string(), &by_nv);
+    assert_eq!(
+      patched,
+      r#"{
+  "version": "4",
+  "npm": {
+    "@denotest/a@1.0.0": {
+      "integrity": "sha512-a",
+      "patchHash": "1"
+    },
+    "@denotest/b@1.0.0_@denotest+a@1.0.0": {
+      "integrity": "sha512-b",
+      "patchHash": "2",
+      "dependencies": [
+        "@denotest/a"
+      ]
+    },
+    "@denotest/c@1.0.0": {
+      "integrity": "sha512-c"
+    }
+  }
+}
+"#
+    );
+    assert_eq!(read_npm_patch_hashes(&patched), by_nv);
+  }
 }
@@ -3,12 +3,14 @@
 pub mod deno_json;
 mod flags;
 mod flags_net;
-mod import_map;
+pub mod import_map;
 mod lockfile;
+mod node_lockfile;
 mod package_json;
 
 use deno_ast::SourceMapOption;
 use deno_config::deno_json::NodeModulesDirMode;
+use deno_config::glob::is_glob_pattern;
 use deno_config::workspace::CreateResolverOptions;
 use deno_config::workspace::FolderConfigs;
 use deno_config::workspace::PackageJsonDepResolution;
@@ -28,7 +30,9 @@ use deno_npm::npm_rc::ResolvedNpmRc;
 use deno_npm::resolution::ValidSerializedNpmResolutionSnapshot;
 use deno_npm::NpmSystemInfo;
 use deno_semver::npm::NpmPackageReqReference;
+use import_map::resolve_composed_import_map;
 use import_map::resolve_import_map_value_from_specifier;
+use indexmap::IndexMap;
 
 pub use deno_config::deno_json::BenchConfig;
 pub use deno_config::deno_json::ConfigFile;
@@ -44,6 +48,7 @@ pub use deno_config::glob::FilePatterns;
 pub use deno_json::check_warn_tsconfig;
 pub use flags::*;
 pub use lockfile::CliLockfile;
+pub use package_json::InstallNpmPatchedPkg;
 pub use package_json::NpmInstallDepsProvider;
 
 use deno_ast::ModuleSpecifier;
@@ -60,7 +65,7 @@ use deno_runtime::deno_tls::rustls_pemfile;
 use deno_runtime::deno_tls::webpki_roots;
 use deno_runtime::inspector_server::InspectorServer;
 use deno_terminal::colors;
-use dotenvy::from_filename;
+use dotenvy::from_path_iter;
 use once_cell::sync::Lazy;
 use serde::Deserialize;
 use serde::Serialize;
@@ -251,6 +256,8 @@ impl CacheSetting {
 
 pub struct WorkspaceBenchOptions {
   pub filter: Option<String>,
+  pub tags: Option<String>,
+  pub skip_tags: Option<String>,
   pub json: bool,
   pub no_run: bool,
 }
@@ -259,6 +266,8 @@ impl WorkspaceBenchOptions {
   pub fn resolve(bench_flags: &BenchFlags) -> Self {
     Self {
       filter: bench_flags.filter.clone(),
+      tags: bench_flags.tags.clone(),
+      skip_tags: bench_flags.skip_tags.clone(),
       json: bench_flags.json,
       no_run: bench_flags.no_run,
     }
@@ -290,6 +299,8 @@ pub struct UnstableFmtOptions {
 #[derive(Clone, Debug)]
 pub struct FmtOptions {
   pub options: FmtOptionsConfig,
+  /// Whether the imports at the top of modules are sorted and grouped.
+  pub sort_imports: bool,
   pub unstable: UnstableFmtOptions,
   pub files: FilePatterns,
 }
@@ -304,6 +315,7 @@ impl FmtOptions {
   pub fn new_with_base(base: PathBuf) -> Self {
     Self {
       options: FmtOptionsConfig::default(),
+      sort_imports: false,
       unstable: Default::default(),
       files: FilePatterns::new_with_base(base),
     }
@@ -311,11 +323,13 @@ impl FmtOptions {
 
   pub fn resolve(
     fmt_config: FmtConfig,
+    sort_imports: Option<bool>,
     unstable: UnstableFmtOptions,
     fmt_flags: &FmtFlags,
   ) -> Self {
     Self {
       options: resolve_fmt_options(fmt_flags, fmt_config.options),
+      sort_imports: fmt_flags.sort_imports.or(sort_imports).unwrap_or(false),
       unstable: UnstableFmtOptions {
         css: unstable.css || fmt_flags.unstable_css,
         html: unstable.html || fmt_flags.unstable_html,
@@ -371,6 +385,8 @@ pub struct WorkspaceTestOptions {
   pub fail_fast: Option<NonZeroUsize>,
   pub permit_no_files: bool,
   pub filter: Option<String>,
+  pub tags: Option<String>,
+  pub skip_tags: Option<String>,
   pub shuffle: Option<u64>,
   pub concurrent_jobs: NonZeroUsize,
   pub trace_leaks: bool,
@@ -389,6 +405,8 @@ impl WorkspaceTestOptions {
       doc: test_flags.doc,
       fail_fast: test_flags.fail_fast,
       filter: test_flags.filter.clone(),
+      tags: test_flags.tags.clone(),
+      skip_tags: test_flags.skip_tags.clone(),
       no_run: test_flags.no_run,
       shuffle: test_flags.shuffle,
       trace_leaks: test_flags.trace_leaks,
@@ -413,17 +431,35 @@ impl TestOptions {
   }
 }
 
-#[derive(Clone, Copy, Default, Debug)]
+#[derive(Clone, Copy, Default, Debug, Eq, PartialEq)]
 pub enum LintReporterKind {
   #[default]
   Pretty,
   Json,
   Compact,
+  /// SARIF 2.1.0, as read by code scanning dashboards.
+  Sarif,
+}
+
+impl LintReporterKind {
+  /// Parses the name of a reporter, as given to `--output` or in the
+  /// `lint.report` option of the config file.
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "pretty" => Some(Self::Pretty),
+      "json" => Some(Self::Json),
+      "compact" => Some(Self::Compact),
+      "sarif" => Some(Self::Sarif),
+      _ => None,
+    }
+  }
 }
 
 #[derive(Clone, Debug)]
 pub struct WorkspaceLintOptions {
   pub reporter_kind: LintReporterKind,
+  /// The number of warnings above which the lint fails.
+  pub max_warnings: Option<usize>,
 }
 
 impl WorkspaceLintOptions {
@@ -431,7 +467,9 @@ impl WorkspaceLintOptions {
     lint_config: &WorkspaceLintConfig,
     lint_flags: &LintFlags,
   ) -> Result<Self, AnyError> {
-    let mut maybe_reporter_kind = if lint_flags.json {
+    let mut maybe_reporter_kind = if lint_flags.output.is_some() {
+      lint_flags.output
+    } else if lint_flags.json {
       Some(LintReporterKind::Json)
     } else if lint_flags.compact {
       Some(LintReporterKind::Compact)
@@ -442,17 +480,16 @@ impl WorkspaceLintOptions {
     if maybe_reporter_kind.is_none() {
       // Flag not set, so try to get lint reporter from the config file.
       maybe_reporter_kind = match lint_config.report.as_deref() {
-        Some("json") => Some(LintReporterKind::Json),
-        Some("compact") => Some(LintReporterKind::Compact),
-        Some("pretty") => Some(LintReporterKind::Pretty),
-        Some(_) => {
-          bail!("Invalid lint report type in config file")
-        }
+        Some(report) => match LintReporterKind::from_name(report) {
+          Some(kind) => Some(kind),
+          None => bail!("Invalid lint report type in config file"),
+        },
         None => None,
       }
     }
     Ok(Self {
       reporter_kind: maybe_reporter_kind.unwrap_or_default(),
+      max_warnings: lint_flags.max_warnings,
     })
   }
 }
@@ -462,6 +499,7 @@ pub struct LintOptions {
   pub rules: LintRulesConfig,
   pub files: FilePatterns,
   pub fix: bool,
+  pub fix_dry_run: bool,
 }
 
 impl Default for LintOptions {
@@ -476,6 +514,7 @@ impl LintOptions {
       rules: Default::default(),
       files: FilePatterns::new_with_base(base),
       fix: false,
+      fix_dry_run: false,
     }
   }
 
@@ -489,6 +528,7 @@ impl LintOptions {
         lint_flags.maybe_rules_exclude.clone(),
       ),
       fix: lint_flags.fix,
+      fix_dry_run: lint_flags.fix_dry_run,
     }
   }
 }
@@ -521,7 +561,7 @@ fn resolve_lint_rules_options(
 
 pub fn discover_npmrc_from_workspace(
   workspace: &Workspace,
-) -> Result<(Arc<ResolvedNpmRc>, Option<PathBuf>), AnyError> {
+) -> Result<(Arc<ResolvedNpmRc>, NpmRcFetchOptions, Vec<PathBuf>), AnyError> {
   let root_folder = workspace.root_folder_configs();
   discover_npmrc(
     root_folder.pkg_json.as_ref().map(|p| p.path.clone()),
@@ -535,15 +575,58 @@ pub fn discover_npmrc_from_workspace(
   )
 }
 
-/// Discover `.npmrc` file - currently we only support it next to `package.json`
-/// or next to `deno.json`.
+/// The `.npmrc` options that affect how the npm registries are fetched from,
+/// which aren't part of the `ResolvedNpmRc`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NpmRcFetchOptions {
+  /// `always-auth` - send the auth of the package's registry when fetching
+  /// a tarball hosted at a URL that has no auth configured.
+  pub always_auth: bool,
+  /// `strict-ssl` - verify the TLS certificates of the registries.
+  pub strict_ssl: bool,
+}
+
+impl Default for NpmRcFetchOptions {
+  fn default() -> Self {
+    Self {
+      always_auth: false,
+      strict_ssl: true,
+    }
+  }
+}
+
+impl NpmRcFetchOptions {
+  fn parse(source: &str) -> Self {
+    let mut options = Self::default();
+    for line in source.lines() {
+      let line = line.trim();
+      if line.starts_with([';', '#']) {
+        continue;
+      }
+      let Some((key, value)) = line.split_once('=') else {
+        continue;
+      };
+      let value = value.trim().trim_matches(['"', '\'']);
+      match key.trim() {
+        "always-auth" => options.always_auth = value == "true",
+        "strict-ssl" => options.strict_ssl = value != "false",
+        _ => {}
+      }
+    }
+    options
+  }
+}
+
+/// Discover the `.npmrc` files - the one next to `package.json` or, if there
+/// is none, next to `deno.json`, and the one in the user's home directory.
 ///
-/// In the future we will need to support it in user directory or global directory
+/// The options of the project's file take precedence over the user's file,
 /// as per https://docs.npmjs.com/cli/v10/configuring-npm/npmrc#files.
+/// The paths of all the files that were read are returned.
 fn discover_npmrc(
   maybe_package_json_path: Option<PathBuf>,
   maybe_deno_json_path: Option<PathBuf>,
-) -> Result<(Arc<ResolvedNpmRc>, Option<PathBuf>), AnyError> {
+) -> Result<(Arc<ResolvedNpmRc>, NpmRcFetchOptions, Vec<PathBuf>), AnyError> {
   const NPMRC_NAME: &str = ".npmrc";
 
   fn get_env_var(var_name: &str) -> Option<String> {
@@ -572,45 +655,37 @@ fn discover_npmrc(
   }
 
   fn try_to_parse_npmrc(
-    source: String,
+    source: &str,
     path: &Path,
-  ) -> Result<Arc<ResolvedNpmRc>, AnyError> {
-    let npmrc = NpmRc::parse(&source, &get_env_var).with_context(|| {
+  ) -> Result<(Arc<ResolvedNpmRc>, NpmRcFetchOptions), AnyError> {
+    let npmrc = NpmRc::parse(source, &get_env_var).with_context(|| {
       format!("Failed to parse .npmrc at {}", path.display())
     })?;
     let resolved = npmrc
       .as_resolved(npm_registry_url())
       .context("Failed to resolve .npmrc options")?;
-    Ok(Arc::new(resolved))
-  }
-
-  // 1. Try `.npmrc` next to `package.json`
-  if let Some(package_json_path) = maybe_package_json_path {
-    if let Some(package_json_dir) = package_json_path.parent() {
-      if let Some((source, path)) = try_to_read_npmrc(package_json_dir)? {
-        return try_to_parse_npmrc(source, &path).map(|r| (r, Some(path)));
-      }
-    }
-  }
-
-  // 2. Try `.npmrc` next to `deno.json(c)`
-  if let Some(deno_json_path) = maybe_deno_json_path {
-    if let Some(deno_json_dir) = deno_json_path.parent() {
-      if let Some((source, path)) = try_to_read_npmrc(deno_json_dir)? {
-        return try_to_parse_npmrc(source, &path).map(|r| (r, Some(path)));
+    Ok((Arc::new(resolved), NpmRcFetchOptions::parse(source)))
+  }
+
+  // 1. Try `.npmrc` next to `package.json`, then next to `deno.json(c)`
+  let mut maybe_project_npmrc = None;
+  for config_path in [maybe_package_json_path, maybe_deno_json_path]
+    .into_iter()
+    .flatten()
+  {
+    if let Some(config_dir) = config_path.parent() {
+      if let Some(npmrc) = try_to_read_npmrc(config_dir)? {
+        maybe_project_npmrc = Some(npmrc);
+        break;
       }
     }
   }
 
-  // TODO(bartlomieju): update to read both files - one in the project root and one and
-  // home dir and then merge them.
-  // 3. Try `.npmrc` in the user's home directory
+  // 2. Try `.npmrc` in the user's home directory
+  let mut maybe_user_npmrc = None;
   if let Some(home_dir) = cache::home_dir() {
     match try_to_read_npmrc(&home_dir) {
-      Ok(Some((source, path))) => {
-        return try_to_parse_npmrc(source, &path).map(|r| (r, Some(path)));
-      }
-      Ok(None) => {}
+      Ok(maybe_npmrc) => maybe_user_npmrc = maybe_npmrc,
       Err(err) if err.source.kind() == std::io::ErrorKind::PermissionDenied => {
         log::debug!(
           "Skipping .npmrc in home directory due to permission denied error. {:#}",
@@ -623,8 +698,24 @@ fn discover_npmrc(
     }
   }
 
-  log::debug!("No .npmrc file found");
-  Ok((create_default_npmrc(), None))
+  match (maybe_project_npmrc, maybe_user_npmrc) {
+    (Some((project_source, project_path)), Some((user_source, user_path))) => {
+      // surface errors with the path of the file that has them
+      try_to_parse_npmrc(&user_source, &user_path)?;
+      try_to_parse_npmrc(&project_source, &project_path)?;
+      // the later keys override the earlier ones
+      let source = format!("{}\n{}", user_source, project_source);
+      try_to_parse_npmrc(&source, &project_path)
+        .map(|(r, o)| (r, o, vec![user_path, project_path]))
+    }
+    (Some((source, path)), None) | (None, Some((source, path))) => {
+      try_to_parse_npmrc(&source, &path).map(|(r, o)| (r, o, vec![path]))
+    }
+    (None, None) => {
+      log::debug!("No .npmrc file found");
+      Ok((create_default_npmrc(), Default::default(), Vec::new()))
+    }
+  }
 }
 
 pub fn create_default_npmrc() -> Arc<ResolvedNpmRc> {
@@ -777,7 +868,9 @@ pub struct CliOptions {
   initial_cwd: PathBuf,
   maybe_node_modules_folder: Option<PathBuf>,
   npmrc: Arc<ResolvedNpmRc>,
+  npmrc_fetch_options: NpmRcFetchOptions,
   maybe_lockfile: Option<Arc<CliLockfile>>,
+  env_files: Vec<PathBuf>,
   overrides: CliOptionOverrides,
   pub start_dir: Arc<WorkspaceDirectory>,
   pub deno_dir_provider: Arc<DenoDirProvider>,
@@ -789,6 +882,7 @@ impl CliOptions {
     initial_cwd: PathBuf,
     maybe_lockfile: Option<Arc<CliLockfile>>,
     npmrc: Arc<ResolvedNpmRc>,
+    npmrc_fetch_options: NpmRcFetchOptions,
     start_dir: Arc<WorkspaceDirectory>,
     force_global_cache: bool,
   ) -> Result<Self, AnyError> {
@@ -822,14 +916,17 @@ impl CliOptions {
     )
     .with_context(|| "Resolving node_modules folder.")?;
 
-    load_env_variables_from_env_file(flags.env_file.as_ref());
+    let env_files = resolve_env_files(&flags, &start_dir)?;
+    load_env_variables_from_env_files(&env_files);
 
     Ok(Self {
       flags,
       initial_cwd,
       maybe_lockfile,
       npmrc,
+      npmrc_fetch_options,
       maybe_node_modules_folder,
+      env_files,
       overrides: Default::default(),
       start_dir,
       deno_dir_provider,
@@ -863,7 +960,7 @@ impl CliOptions {
         log::debug!("package.json auto-discovery is disabled");
       }
       WorkspaceDiscoverOptions {
-        fs: Default::default(), // use real fs
+        fs: &deno_json::CliDenoConfigFs,
         deno_json_cache: None,
         pkg_json_cache: Some(&node_resolver::PackageJsonThreadLocalCache),
         workspace_cache: None,
@@ -908,7 +1005,8 @@ impl CliOptions {
       log::warn!("{} {}", colors::yellow("Warning"), diagnostic);
     }
 
-    let (npmrc, _) = discover_npmrc_from_workspace(&start_dir.workspace)?;
+    let (npmrc, npmrc_fetch_options, _) =
+      discover_npmrc_from_workspace(&start_dir.workspace)?;
 
     let maybe_lock_file = CliLockfile::discover(&flags, &start_dir.workspace)?;
 
@@ -919,6 +1017,7 @@ impl CliOptions {
       initial_cwd,
       maybe_lock_file.map(Arc::new),
       npmrc,
+      npmrc_fetch_options,
       Arc::new(start_dir),
       false,
     )
@@ -1068,7 +1167,12 @@ impl CliOptions {
             value,
           })
         }
-        None => None,
+        None => match self.workspace().root_deno_json() {
+          Some(config_file) => {
+            resolve_composed_import_map(config_file, file_fetcher).await?
+          }
+          None => None,
+        },
       }
     };
     Ok(
@@ -1121,8 +1225,10 @@ impl CliOptions {
     }
   }
 
-  pub fn env_file_name(&self) -> Option<&String> {
-    self.flags.env_file.as_ref()
+  /// The environment files passed with `--env-file` or declared in the
+  /// config file, in the order they're loaded.
+  pub fn env_files(&self) -> &[PathBuf] {
+    &self.env_files
   }
 
   pub fn resolve_main_module(&self) -> Result<ModuleSpecifier, AnyError> {
@@ -1280,6 +1386,10 @@ impl CliOptions {
     &self.npmrc
   }
 
+  pub fn npmrc_fetch_options(&self) -> &NpmRcFetchOptions {
+    &self.npmrc_fetch_options
+  }
+
   pub fn resolve_fmt_options_for_members(
     &self,
     fmt_flags: &FmtFlags,
@@ -1292,7 +1402,9 @@ impl CliOptions {
     let unstable = self.resolve_config_unstable_fmt_options();
     let mut result = Vec::with_capacity(member_configs.len());
     for (ctx, config) in member_configs {
-      let options = FmtOptions::resolve(config, unstable.clone(), fmt_flags);
+      let sort_imports = deno_json::read_fmt_sort_imports_config(&ctx)?;
+      let options =
+        FmtOptions::resolve(config, sort_imports, unstable.clone(), fmt_flags);
       result.push((ctx, options));
     }
     Ok(result)
@@ -1467,6 +1579,14 @@ impl CliOptions {
       || self.flags.inspect_wait.is_some()
   }
 
+  pub fn cpu_prof(&self) -> Option<&ProfileFlags> {
+    self.flags.cpu_prof.as_ref()
+  }
+
+  pub fn heap_prof(&self) -> Option<&ProfileFlags> {
+    self.flags.heap_prof.as_ref()
+  }
+
   pub fn inspect_brk(&self) -> Option<SocketAddr> {
     self.flags.inspect_brk
   }
@@ -1498,6 +1618,10 @@ impl CliOptions {
     self.flags.no_npm
   }
 
+  pub fn node_conditions(&self) -> &[String] {
+    &self.flags.node_conditions
+  }
+
   pub fn permission_flags(&self) -> &PermissionFlags {
     &self.flags.permissions
   }
@@ -1648,9 +1772,23 @@ impl CliOptions {
     if let DenoSubcommand::Run(RunFlags {
       watch: Some(WatchFlagsWithPaths { paths, .. }),
       ..
+    })
+    | DenoSubcommand::Serve(ServeFlags {
+      watch: Some(WatchFlagsWithPaths { paths, .. }),
+      ..
+    })
+    | DenoSubcommand::Test(TestFlags {
+      watch: Some(WatchFlagsWithPaths { paths, .. }),
+      ..
     }) = &self.flags.subcommand
     {
-      full_paths.extend(paths.iter().map(|path| self.initial_cwd.join(path)));
+      // globs are watched by the file watcher itself
+      full_paths.extend(
+        paths
+          .iter()
+          .filter(|path| !is_glob_pattern(path))
+          .map(|path| self.initial_cwd.join(path)),
+      );
     }
 
     if let Ok(Some(import_map_path)) = self
@@ -1869,21 +2007,69 @@ pub fn config_to_deno_graph_workspace_member(
   })
 }
 
-fn load_env_variables_from_env_file(filename: Option<&String>) {
-  let Some(env_file_name) = filename else {
-    return;
-  };
-  match from_filename(env_file_name) {
-    Ok(_) => (),
-    Err(error) => {
+/// Resolves the environment files passed with `--env-file`, falling back to
+/// the `"envFile"` property of the config file for the subcommands that
+/// accept the flag.
+fn resolve_env_files(
+  flags: &Flags,
+  start_dir: &WorkspaceDirectory,
+) -> Result<Vec<PathBuf>, AnyError> {
+  if let Some(env_files) = &flags.env_file {
+    return Ok(env_files.iter().map(PathBuf::from).collect());
+  }
+  let accepts_env_file = matches!(
+    flags.subcommand,
+    DenoSubcommand::Bench(_)
+      | DenoSubcommand::Eval(_)
+      | DenoSubcommand::Repl(_)
+      | DenoSubcommand::Run(_)
+      | DenoSubcommand::Serve(_)
+      | DenoSubcommand::Test(_)
+  );
+  if !accepts_env_file {
+    return Ok(Vec::new());
+  }
+  match start_dir
+    .maybe_deno_json()
+    .or_else(|| start_dir.workspace.root_deno_json())
+  {
+    Some(config_file) => deno_json::read_env_file_config(config_file),
+    None => Ok(Vec::new()),
+  }
+}
+
+/// Loads the environment files, where the variables of a later file take
+/// precedence over the ones of an earlier file. Variables that are already
+/// set in the environment of the process are never overwritten.
+fn load_env_variables_from_env_files(env_files: &[PathBuf]) {
+  let mut env_vars = IndexMap::new();
+  for env_file in env_files {
+    let env_file_name = env_file.display();
+    let mut file_env_vars = IndexMap::new();
+    let result = from_path_iter(env_file).and_then(|iter| {
+      for item in iter {
+        let (key, value) = item?;
+        // like dotenv, the first definition of a variable in a file wins
+        file_env_vars.entry(key).or_insert(value);
+      }
+      Ok(())
+    });
+    // the variables parsed before an error are still loaded
+    env_vars.extend(file_env_vars);
+    if let Err(error) = result {
       match error {
           dotenvy::Error::LineParse(line, index)=> log::info!("{} Parsing failed within the specified environment file: {} at index: {} of the value: {}",colors::yellow("Warning"), env_file_name, index, line),
-          dotenvy::Error::Io(_)=> log::info!("{} The `--env-file` flag was used, but the environment file specified '{}' was not found.",colors::yellow("Warning"),env_file_name),
+          dotenvy::Error::Io(_)=> log::info!("{} The environment file specified '{}' was not found.",colors::yellow("Warning"),env_file_name),
           dotenvy::Error::EnvVar(_)=> log::info!("{} One or more of the environment variables isn't present or not unicode within the specified environment file: {}",colors::yellow("Warning"),env_file_name),
           _ => log::info!("{} Unknown failure occurred with the specified environment file: {}", colors::yellow("Warning"), env_file_name),
         }
     }
   }
+  for (key, value) in env_vars {
+    if std::env::var_os(&key).is_none() {
+      std::env::set_var(key, value);
+    }
+  }
 }
 
 #[cfg(test)]
@@ -1976,4 +2162,25 @@ mod test {
     let reg_api_url = jsr_api_url();
     assert!(reg_api_url.as_str().ends_with('/'));
   }
+
+  #[test]
+  fn npmrc_fetch_options() {
+    assert_eq!(NpmRcFetchOptions::parse(""), NpmRcFetchOptions::default());
+    assert_eq!(
+      NpmRcFetchOptions::parse(
+        "registry=https://example.com/\nalways-auth = true\nstrict-ssl=false\n"
+      ),
+      NpmRcFetchOptions {
+        always_auth: true,
+        strict_ssl: false,
+      }
+    );
+    // comments and scoped keys are ignored
+    assert_eq!(
+      NpmRcFetchOptions::parse(
+        "; strict-ssl=false\n//example.com/:always-auth=true\n"
+      ),
+      NpmRcFetchOptions::default()
+    );
+  }
 }
@@ -0,0 +1,801 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Conversion of the lockfiles of npm (`package-lock.json`) and Yarn v1
+//! (`yarn.lock`) to the npm packages of the Deno lockfile, which seeds a new
+//! Deno lockfile so the npm packages keep the versions they were installed
+//! with by those package managers.
+
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_lockfile::NpmPackageDependencyLockfileInfo;
+use deno_lockfile::NpmPackageLockfileInfo;
+use deno_semver::package::PackageReq;
+use deno_semver::Version;
+use serde::Deserialize;
+
+/// A dependency of a package.json in the workspace.
+#[derive(Debug, Clone)]
+pub struct PackageJsonDep {
+  /// The directory of the package.json relative to the root directory of the
+  /// workspace, which is empty for the root directory.
+  pub dir: String,
+  /// The key of the dependency in the package.json.
+  pub name: String,
+  /// The value of the dependency in the package.json.
+  pub version_req_text: String,
+  pub req: PackageReq,
+}
+
+pub struct ImportedNodeLockfile {
+  pub path: PathBuf,
+  /// The versions that the dependencies of the package.json files resolve to.
+  pub specifiers: Vec<(PackageReq, String)>,
+  pub packages: Vec<NpmPackageLockfileInfo>,
+  /// The ids of the packages that have peer dependencies or depend on such
+  /// packages, which aren't imported.
+  pub skipped: Vec<String>,
+}
+
+/// Converts the `package-lock.json` or else the `yarn.lock` in the root
+/// directory, if there is one.
+pub fn import_node_lockfile(
+  root_dir: &Path,
+  deps: &[PackageJsonDep],
+) -> Result<Option<ImportedNodeLockfile>, AnyError> {
+  let package_lock_path = root_dir.join("package-lock.json");
+  let yarn_lock_path = root_dir.join("yarn.lock");
+  let (path, result) = if let Some(text) = read_if_exists(&package_lock_path)? {
+    let result = import_package_lock(&text, deps);
+    (package_lock_path, result)
+  } else if let Some(text) = read_if_exists(&yarn_lock_path)? {
+    // yarn.lock doesn't record peer dependencies, so they're read from the
+    // installed packages
+    let has_peer_deps = |name: &str, version: &str| {
+      let path = root_dir
+        .join("node_modules")
+        .join(name)
+        .join("package.json");
+      let Ok(Some(text)) = read_if_exists(&path) else {
+        return false;
+      };
+      let Ok(pkg_json) = serde_json::from_str::<serde_json::Value>(&text)
+      else {
+        return false;
+      };
+      pkg_json["version"] == version
+        && pkg_json["peerDependencies"]
+          .as_object()
+          .is_some_and(|deps| !deps.is_empty())
+    };
+    let result = import_yarn_lock(&text, deps, &has_peer_deps);
+    (yarn_lock_path, result)
+  } else {
+    return Ok(None);
+  };
+  let mut imported =
+    result.with_context(|| format!("Failed importing '{}'", path.display()))?;
+  imported.path = path;
+  Ok(Some(imported))
+}
+
+fn read_if_exists(path: &Path) -> Result<Option<String>, AnyError> {
+  match std::fs::read_to_string(path) {
+    Ok(text) => Ok(Some(text)),
+    Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+    Err(err) => {
+      Err(err).with_context(|| format!("Failed reading '{}'", path.display()))
+    }
+  }
+}
+
+fn version_matching_req(req: &PackageReq, version: &str) -> Option<String> {
+  let version = Version::parse_from_npm(version).ok()?;
+  req
+    .version_req
+    .matches(&version)
+    .then(|| version.to_string())
+}
+
+fn id_version(id: &str) -> Option<Version> {
+  let (_, version) = id.rsplit_once('@')?;
+  Version::parse_from_npm(version).ok()
+}
+
+/// A package of a node lockfile, identified by `name@version`.
+struct LockedPackage {
+  integrity: String,
+  has_peer_deps: bool,
+  /// The ids of the dependencies, by the names they're imported with.
+  dependencies: BTreeMap<String, String>,
+}
+
+/// Adds a package that might be installed in multiple places already. Deno
+/// resolves the dependencies of a package once, so where the places have
+/// different versions of a dependency the highest one is kept.
+fn add_locked_package(
+  packages: &mut BTreeMap<String, LockedPackage>,
+  id: String,
+  package: LockedPackage,
+) {
+  let existing = match packages.entry(id) {
+    Entry::Vacant(entry) => {
+      entry.insert(package);
+      return;
+    }
+    Entry::Occupied(entry) => entry.into_mut(),
+  };
+  existing.has_peer_deps |= package.has_peer_deps;
+  for (name, id) in package.dependencies {
+    match existing.dependencies.entry(name) {
+      Entry::Vacant(entry) => {
+        entry.insert(id);
+      }
+      Entry::Occupied(mut entry) => {
+        if id_version(&id) > id_version(entry.get()) {
+          entry.insert(id);
+        }
+      }
+    }
+  }
+}
+
+/// The packages that can't be imported, because they have peer dependencies
+/// or depend on such packages. Deno identifies the copies of these packages
+/// by the versions their peer dependencies resolve to, which node lockfiles
+/// don't record.
+fn skipped_packages(
+  packages: &BTreeMap<String, LockedPackage>,
+) -> BTreeSet<String> {
+  let mut skipped = packages
+    .iter()
+    .filter(|(_, package)| package.has_peer_deps)
+    .map(|(id, _)| id.clone())
+    .collect::<BTreeSet<_>>();
+  loop {
+    let dependents = packages
+      .iter()
+      .filter(|(id, package)| {
+        !skipped.contains(*id)
+          && package.dependencies.values().any(|id| skipped.contains(id))
+      })
+      .map(|(id, _)| id.clone())
+      .collect::<Vec<_>>();
+    if dependents.is_empty() {
+      return skipped;
+    }
+    skipped.extend(dependents);
+  }
+}
+
+fn imported_node_lockfile(
+  packages: BTreeMap<String, LockedPackage>,
+  skipped: BTreeSet<String>,
+  specifiers: Vec<(PackageReq, String)>,
+) -> ImportedNodeLockfile {
+  let packages = packages
+    .into_iter()
+    .filter(|(id, _)| !skipped.contains(id))
+    .map(|(id, package)| NpmPackageLockfileInfo {
+      serialized_id: id,
+      integrity: package.integrity,
+      dependencies: package
+        .dependencies
+        .into_iter()
+        .map(|(name, id)| NpmPackageDependencyLockfileInfo { name, id })
+        .collect(),
+    })
+    .collect();
+  ImportedNodeLockfile {
+    path: PathBuf::new(),
+    specifiers,
+    packages,
+    skipped: skipped.into_iter().collect(),
+  }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PackageLock {
+  lockfile_version: u32,
+  #[serde(default)]
+  packages: BTreeMap<String, PackageLockPackage>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PackageLockPackage {
+  name: Option<String>,
+  version: Option<String>,
+  integrity: Option<String>,
+  #[serde(default)]
+  link: bool,
+  #[serde(default)]
+  dependencies: BTreeMap<String, String>,
+  #[serde(default)]
+  optional_dependencies: BTreeMap<String, String>,
+  #[serde(default)]
+  peer_dependencies: BTreeMap<String, String>,
+}
+
+/// Converts a `package-lock.json` of `"lockfileVersion"` 2 or 3, whose
+/// `"packages"` are keyed by their path in the `node_modules` directories.
+fn import_package_lock(
+  text: &str,
+  deps: &[PackageJsonDep],
+) -> Result<ImportedNodeLockfile, AnyError> {
+  let lock: PackageLock = serde_json::from_str(text)?;
+  if lock.lockfile_version < 2 {
+    bail!(
+      "Unsupported lockfileVersion {}. Run `npm install` with npm 7 or later to upgrade it.",
+      lock.lockfile_version
+    );
+  }
+
+  // resolves the package the same way as node, from the closest
+  // node_modules directory of the path and then of its ancestors
+  let find_package = |from_path: &str, name: &str| {
+    let mut dir = from_path;
+    loop {
+      let path = if dir.is_empty() {
+        format!("node_modules/{}", name)
+      } else {
+        format!("{}/node_modules/{}", dir, name)
+      };
+      if let Some(entry) = lock.packages.get_key_value(&path) {
+        return Some(entry);
+      }
+      if dir.is_empty() {
+        return None;
+      }
+      dir = match dir.rfind("node_modules/") {
+        Some(index) if index > 0 => &dir[..index - 1],
+        _ => "",
+      };
+    }
+  };
+  let package_id = |path: &str, package: &PackageLockPackage| {
+    let name = match &package.name {
+      Some(name) => name.as_str(),
+      None => &path[path.rfind("node_modules/")? + "node_modules/".len()..],
+    };
+    Some(format!("{}@{}", name, package.version.as_ref()?))
+  };
+
+  let mut packages = BTreeMap::new();
+  for (path, package) in &lock.packages {
+    // skip the workspace members and the links to them
+    if !path.contains("node_modules/") || package.link {
+      continue;
+    }
+    let Some(id) = package_id(path, package) else {
+      bail!("Missing the version of '{}'.", path);
+    };
+    let Some(integrity) = &package.integrity else {
+      bail!(
+        "Missing the integrity of '{}', which is only supported for packages of npm registries.",
+        path
+      );
+    };
+    let dependencies = package
+      .dependencies
+      .keys()
+      .chain(package.optional_dependencies.keys())
+      .filter_map(|name| {
+        // optional dependencies might not be installed
+        let (dep_path, dep) = find_package(path, name)?;
+        if dep.link {
+          return None;
+        }
+        Some((name.clone(), package_id(dep_path, dep)?))
+      })
+      .collect();
+    add_locked_package(
+      &mut packages,
+      id,
+      LockedPackage {
+        integrity: integrity.clone(),
+        has_peer_deps: !package.peer_dependencies.is_empty(),
+        dependencies,
+      },
+    );
+  }
+  let skipped = skipped_packages(&packages);
+
+  let specifiers = deps
+    .iter()
+    .filter_map(|dep| {
+      let (path, package) = find_package(&dep.dir, &dep.name)?;
+      if skipped.contains(&package_id(path, package)?) {
+        return None;
+      }
+      let version = package.version.as_ref()?;
+      Some((dep.req.clone(), version_matching_req(&dep.req, version)?))
+    })
+    .collect();
+
+  Ok(imported_node_lockfile(packages, skipped, specifiers))
+}
+
+#[derive(Debug, Default)]
+struct YarnLockEntry {
+  name: String,
+  version: Option<String>,
+  integrity: Option<String>,
+  dependencies: Vec<(String, String)>,
+}
+
+/// Converts a `yarn.lock` of Yarn v1, whose entries are keyed by the
+/// dependencies that resolve to them, like `"name@^1.0.0", "name@^1.1.0":`.
+/// Whether a package has peer dependencies is asked from `has_peer_deps`
+/// with its name and version, since these lockfiles don't record them.
+fn import_yarn_lock(
+  text: &str,
+  deps: &[PackageJsonDep],
+  has_peer_deps: &dyn Fn(&str, &str) -> bool,
+) -> Result<ImportedNodeLockfile, AnyError> {
+  if text.lines().any(|line| line.starts_with("__metadata:")) {
+    bail!("Lockfiles of Yarn 2 or later are not supported.");
+  }
+
+  fn unquote(text: &str) -> &str {
+    text.trim().trim_matches('"')
+  }
+
+  // splits `name@range`, where the name might be scoped
+  fn split_key(key: &str) -> Option<(&str, &str)> {
+    let index = key.get(1..)?.find('@')? + 1;
+    Some((&key[..index], &key[index + 1..]))
+  }
+
+  // the name of the package that a dependency resolves to, which differs
+  // for aliases like `alias@npm:name@^1.0.0`
+  fn package_name<'a>(name: &'a str, range: &'a str) -> &'a str {
+    match range.strip_prefix("npm:").and_then(split_key) {
+      Some((name, _)) => name,
+      None => name,
+    }
+  }
+
+  let mut entries: Vec<YarnLockEntry> = Vec::new();
+  let mut entries_by_key = HashMap::new();
+  let mut in_dependencies = false;
+  for (line_index, line) in text.lines().enumerate() {
+    let trimmed = line.trim();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+      continue;
+    }
+    let indent = line.len() - line.trim_start().len();
+    if indent == 0 {
+      let Some(keys) = trimmed.strip_suffix(':') else {
+        bail!("Unexpected line {}: {}", line_index + 1, line);
+      };
+      let mut entry = YarnLockEntry::default();
+      for key in keys.split(", ").map(unquote) {
+        let Some((name, range)) = split_key(key) else {
+          bail!("Unexpected entry on line {}: {}", line_index + 1, key);
+        };
+        entry.name = package_name(name, range).to_string();
+        entries_by_key.insert(key.to_string(), entries.len());
+      }
+      entries.push(entry);
+      in_dependencies = false;
+      continue;
+    }
+    let Some(entry) = entries.last_mut() else {
+      bail!("Unexpected line {}: {}", line_index + 1, line);
+    };
+    if indent <= 2 {
+      in_dependencies =
+        matches!(trimmed, "dependencies:" | "optionalDependencies:");
+      if let Some((key, value)) = trimmed.split_once(' ') {
+        match key {
+          "version" => entry.version = Some(unquote(value).to_string()),
+          "integrity" => entry.integrity = Some(unquote(value).to_string()),
+          _ => {}
+        }
+      }
+    } else if in_dependencies {
+      if let Some((name, range)) = trimmed.split_once(' ') {
+        entry
+          .dependencies
+          .push((unquote(name).to_string(), unquote(range).to_string()));
+      }
+    }
+  }
+
+  let package_id = |entry: &YarnLockEntry| {
+    Some(format!("{}@{}", entry.name, entry.version.as_ref()?))
+  };
+  let mut packages = BTreeMap::new();
+  for entry in &entries {
+    let Some(version) = &entry.version else {
+      bail!("Missing the version of '{}'.", entry.name);
+    };
+    let id = format!("{}@{}", entry.name, version);
+    let Some(integrity) = &entry.integrity else {
+      bail!(
+        "Missing the integrity of '{}', which is only supported for packages of npm registries.",
+        id
+      );
+    };
+    let dependencies = entry
+      .dependencies
+      .iter()
+      .filter_map(|(name, range)| {
+        // optional dependencies might not be in the lockfile
+        let dep = &entries[*entries_by_key.get(&format!("{name}@{range}"))?];
+        Some((name.clone(), package_id(dep)?))
+      })
+      .collect();
+    add_locked_package(
+      &mut packages,
+      id,
+      LockedPackage {
+        integrity: integrity.clone(),
+        has_peer_deps: has_peer_deps(&entry.name, version),
+        dependencies,
+      },
+    );
+  }
+  let skipped = skipped_packages(&packages);
+
+  let specifiers = deps
+    .iter()
+    .filter_map(|dep| {
+      let key = format!("{}@{}", dep.name, dep.version_req_text);
+      let entry = &entries[*entries_by_key.get(&key)?];
+      if skipped.contains(&package_id(entry)?) {
+        return None;
+      }
+      let version = entry.version.as_ref()?;
+      Some((dep.req.clone(), version_matching_req(&dep.req, version)?))
+    })
+    .collect();
+
+  Ok(imported_node_lockfile(packages, skipped, specifiers))
+}
+
+#[cfg(test)]
+mod test {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  fn dep(dir: &str, name: &str, version_req_text: &str) -> PackageJsonDep {
+    let req = match version_req_text.strip_prefix("npm:") {
+      Some(req) => req.to_string(),
+      None => format!("{}@{}", name, version_req_text),
+    };
+    PackageJsonDep {
+      dir: dir.to_string(),
+      name: name.to_string(),
+      version_req_text: version_req_text.to_string(),
+      req: PackageReq::from_str(&req).unwrap(),
+    }
+  }
+
+  fn specifier(req: &str, version: &str) -> (PackageReq, String) {
+    (PackageReq::from_str(req).unwrap(), version.to_string())
+  }
+
+  type PackageSummary = (String, String, Vec<(String, String)>);
+
+  fn package(
+    id: &str,
+    integrity: &str,
+    dependencies: &[(&str, &str)],
+  ) -> PackageSummary {
+    (
+      id.to_string(),
+      integrity.to_string(),
+      dependencies
+        .iter()
+        .map(|(name, id)| (name.to_string(), id.to_string()))
+        .collect(),
+    )
+  }
+
+  fn packages(imported: &ImportedNodeLockfile) -> Vec<PackageSummary> {
+    imported
+      .packages
+      .iter()
+      .map(|package| {
+        (
+          package.serialized_id.clone(),
+          package.integrity.clone(),
+          package
+            .dependencies
+            .iter()
+            .map(|dep| (dep.name.clone(), dep.id.clone()))
+            .collect(),
+        )
+      })
+      .collect()
+  }
+
+  #[test]
+  fn imports_package_lock() {
+    let text = r#"{
+      "name": "project",
+      "lockfileVersion": 3,
+      "packages": {
+        "": {
+          "name": "project",
+          "workspaces": ["member"],
+          "dependencies": { "a": "^1.0.0", "alias": "npm:c@^2.0.0" }
+        },
+        "member": {
+          "name": "member",
+          "dependencies": { "b": "^1.0.0" }
+        },
+        "node_modules/member": {
+          "resolved": "member",
+          "link": true
+        },
+        "node_modules/a": {
+          "version": "1.2.0",
+          "integrity": "sha512-a",
+          "dependencies": { "b": "^2.0.0", "member": "*" },
+          "optionalDependencies": { "missing": "^1.0.0" }
+        },
+        "node_modules/a/node_modules/b": {
+          "version": "2.0.0",
+          "integrity": "sha512-b2"
+        },
+        "node_modules/alias": {
+          "name": "c",
+          "version": "2.1.0",
+          "integrity": "sha512-c",
+          "dependencies": { "b": "^1.0.0" }
+        },
+        "node_modules/b": {
+          "version": "1.0.1",
+          "integrity": "sha512-b1"
+        }
+      }
+    }"#;
+    let deps = [
+      dep("", "a", "^1.0.0"),
+      dep("", "alias", "npm:c@^2.0.0"),
+      dep("member", "b", "^1.0.0"),
+      dep("member", "d", "^1.0.0"),
+    ];
+    let imported = import_package_lock(text, &deps).unwrap();
+    assert_eq!(
+      imported.specifiers,
+      vec![
+        specifier("a@^1.0.0", "1.2.0"),
+        specifier("c@^2.0.0", "2.1.0"),
+        specifier("b@^1.0.0", "1.0.1"),
+      ]
+    );
+    assert_eq!(
+      packages(&imported),
+      vec![
+        package("a@1.2.0", "sha512-a", &[("b", "b@2.0.0")]),
+        package("b@1.0.1", "sha512-b1", &[]),
+        package("b@2.0.0", "sha512-b2", &[]),
+        package("c@2.1.0", "sha512-c", &[("b", "b@1.0.1")]),
+      ]
+    );
+  }
+
+  #[test]
+  fn skips_packages_with_peer_deps_in_package_lock() {
+    let text = r#"{
+      "lockfileVersion": 3,
+      "packages": {
+        "node_modules/a": {
+          "version": "1.0.0",
+          "integrity": "sha512-a",
+          "dependencies": { "plugin": "^1.0.0" }
+        },
+        "node_modules/plugin": {
+          "version": "1.0.0",
+          "integrity": "sha512-plugin",
+          "peerDependencies": { "host": "^2.0.0" }
+        },
+        "node_modules/host": {
+          "version": "2.0.0",
+          "integrity": "sha512-host"
+        }
+      }
+    }"#;
+    let deps = [dep("", "a", "^1.0.0"), dep("", "host", "^2.0.0")];
+    let imported = import_package_lock(text, &deps).unwrap();
+    assert_eq!(imported.specifiers, vec![specifier("host@^2.0.0", "2.0.0")]);
+    assert_eq!(
+      packages(&imported),
+      vec![package("host@2.0.0", "sha512-host", &[])]
+    );
+    assert_eq!(imported.skipped, vec!["a@1.0.0", "plugin@1.0.0"]);
+  }
+
+  #[test]
+  fn merges_package_in_multiple_places_in_package_lock() {
+    let text = r#"{
+      "lockfileVersion": 3,
+      "packages": {
+        "node_modules/a": {
+          "version": "1.0.0",
+          "integrity": "sha512-a1",
+          "dependencies": { "b": "^1.0.0", "c": "^1.0.0" }
+        },
+        "node_modules/b": {
+          "version": "1.0.0",
+          "integrity": "sha512-b"
+        },
+        "node_modules/c": {
+          "version": "1.0.0",
+          "integrity": "sha512-c1"
+        },
+        "node_modules/d": {
+          "version": "1.0.0",
+          "integrity": "sha512-d",
+          "dependencies": { "a": "^2.0.0" }
+        },
+        "node_modules/d/node_modules/a": {
+          "version": "2.0.0",
+          "integrity": "sha512-a2",
+          "dependencies": { "c": "^1.0.0" }
+        },
+        "node_modules/d/node_modules/a/node_modules/c": {
+          "version": "1.1.0",
+          "integrity": "sha512-c2"
+        },
+        "node_modules/e": {
+          "version": "1.0.0",
+          "integrity": "sha512-e",
+          "dependencies": { "a": "^2.0.0" }
+        },
+        "node_modules/e/node_modules/a": {
+          "version": "2.0.0",
+          "integrity": "sha512-a2",
+          "dependencies": { "c": "^1.0.0" }
+        }
+      }
+    }"#;
+    let imported = import_package_lock(text, &[]).unwrap();
+    assert_eq!(
+      packages(&imported),
+      vec![
+        package(
+          "a@1.0.0",
+          "sha512-a1",
+          &[("b", "b@1.0.0"), ("c", "c@1.0.0")]
+        ),
+        // the copy of a@2.0.0 in e uses the c@1.0.0 in the root instead
+        package("a@2.0.0", "sha512-a2", &[("c", "c@1.1.0")]),
+        package("b@1.0.0", "sha512-b", &[]),
+        package("c@1.0.0", "sha512-c1", &[]),
+        package("c@1.1.0", "sha512-c2", &[]),
+        package("d@1.0.0", "sha512-d", &[("a", "a@2.0.0")]),
+        package("e@1.0.0", "sha512-e", &[("a", "a@2.0.0")]),
+      ]
+    );
+    assert!(imported.skipped.is_empty());
+  }
+
+  #[test]
+  fn errors_for_unsupported_package_lock() {
+    let err = import_package_lock(r#"{ "lockfileVersion": 1 }"#, &[])
+      .err()
+      .unwrap();
+    assert_eq!(
+      err.to_string(),
+      "Unsupported lockfileVersion 1. Run `npm install` with npm 7 or later to upgrade it."
+    );
+    let text = r#"{
+      "lockfileVersion": 3,
+      "packages": {
+        "node_modules/a": { "version": "1.0.0", "resolved": "git+ssh://host/a.git" }
+      }
+    }"#;
+    let err = import_package_lock(text, &[]).err().unwrap();
+    assert_eq!(
+      err.to_string(),
+      "Missing the integrity of 'node_modules/a', which is only supported for packages of npm registries."
+    );
+  }
+
+  #[test]
+  fn imports_yarn_lock() {
+    let text = r#"# THIS IS AN AUTOGENERATED FILE. DO NOT EDIT THIS FILE DIRECTLY.
+# yarn lockfile v1
+
+
+"@scope/a@^1.0.0", "@scope/a@^1.1.0":
+  version "1.2.0"
+  resolved "https://registry.yarnpkg.com/@scope/a/-/a-1.2.0.tgz#abc"
+  integrity sha512-a
+  dependencies:
+    b "^2.0.0"
+  optionalDependencies:
+    missing "^1.0.0"
+
+alias@npm:c@^2.0.0:
+  version "2.1.0"
+  resolved "https://registry.yarnpkg.com/c/-/c-2.1.0.tgz#def"
+  integrity sha512-c
+  dependencies:
+    "@scope/a" "^1.1.0"
+
+b@^2.0.0:
+  version "2.0.0"
+  resolved "https://registry.yarnpkg.com/b/-/b-2.0.0.tgz#ghi"
+  integrity sha512-b
+"#;
+    let deps = [
+      dep("", "@scope/a", "^1.0.0"),
+      dep("", "alias", "npm:c@^2.0.0"),
+      dep("", "b", "^3.0.0"),
+    ];
+    let imported = import_yarn_lock(text, &deps, &|_, _| false).unwrap();
+    assert_eq!(
+      imported.specifiers,
+      vec![
+        specifier("@scope/a@^1.0.0", "1.2.0"),
+        specifier("c@^2.0.0", "2.1.0"),
+      ]
+    );
+    assert_eq!(
+      packages(&imported),
+      vec![
+        package("@scope/a@1.2.0", "sha512-a", &[("b", "b@2.0.0")]),
+        package("b@2.0.0", "sha512-b", &[]),
+        package("c@2.1.0", "sha512-c", &[("@scope/a", "@scope/a@1.2.0")]),
+      ]
+    );
+  }
+
+  #[test]
+  fn skips_packages_with_peer_deps_in_yarn_lock() {
+    let text = r#"# yarn lockfile v1
+
+
+a@^1.0.0:
+  version "1.0.0"
+  integrity sha512-a
+  dependencies:
+    plugin "^1.0.0"
+
+host@^2.0.0:
+  version "2.0.0"
+  integrity sha512-host
+
+plugin@^1.0.0:
+  version "1.0.0"
+  integrity sha512-plugin
+"#;
+    let deps = [dep("", "a", "^1.0.0"), dep("", "host", "^2.0.0")];
+    let imported = import_yarn_lock(text, &deps, &|name, version| {
+      name == "plugin" && version == "1.0.0"
+    })
+    .unwrap();
+    assert_eq!(imported.specifiers, vec![specifier("host@^2.0.0", "2.0.0")]);
+    assert_eq!(
+      packages(&imported),
+      vec![package("host@2.0.0", "sha512-host", &[])]
+    );
+    assert_eq!(imported.skipped, vec!["a@1.0.0", "plugin@1.0.0"]);
+  }
+
+  #[test]
+  fn errors_for_yarn_berry_lock() {
+    let text = "__metadata:\n  version: 6\n";
+    let err = import_yarn_lock(text, &[], &|_, _| false).err().unwrap();
+    assert_eq!(
+      err.to_string(),
+      "Lockfiles of Yarn 2 or later are not supported."
+    );
+  }
+}
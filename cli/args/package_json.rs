@@ -1,14 +1,21 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::collections::BTreeMap;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 
 use deno_config::workspace::Workspace;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
 use deno_core::serde_json;
 use deno_package_json::PackageJsonDepValue;
 use deno_semver::npm::NpmPackageReqReference;
+use deno_semver::package::PackageNv;
 use deno_semver::package::PackageReq;
 
+use crate::util::checksum;
+
 #[derive(Debug)]
 pub struct InstallNpmRemotePkg {
   pub alias: Option<String>,
@@ -22,10 +29,23 @@ pub struct InstallNpmWorkspacePkg {
   pub target_dir: PathBuf,
 }
 
+/// An npm package whose files are changed by a patch when it's set up in
+/// the `node_modules` directory.
+#[derive(Debug)]
+pub struct InstallNpmPatchedPkg {
+  pub nv: PackageNv,
+  pub patch_path: PathBuf,
+  /// The contents of the patch file.
+  pub patch: String,
+  /// The SHA-256 checksum of the patch file.
+  pub checksum: String,
+}
+
 #[derive(Debug, Default)]
 pub struct NpmInstallDepsProvider {
   remote_pkgs: Vec<InstallNpmRemotePkg>,
   workspace_pkgs: Vec<InstallNpmWorkspacePkg>,
+  patched_pkgs: Vec<InstallNpmPatchedPkg>,
 }
 
 impl NpmInstallDepsProvider {
@@ -33,7 +53,7 @@ impl NpmInstallDepsProvider {
     Self::default()
   }
 
-  pub fn from_workspace(workspace: &Arc<Workspace>) -> Self {
+  pub fn from_workspace(workspace: &Arc<Workspace>) -> Result<Self, AnyError> {
     // todo(dsherret): estimate capacity?
     let mut workspace_pkgs = Vec::new();
     let mut remote_pkgs = Vec::new();
@@ -128,10 +148,11 @@ impl NpmInstallDepsProvider {
 
     remote_pkgs.shrink_to_fit();
     workspace_pkgs.shrink_to_fit();
-    Self {
+    Ok(Self {
       remote_pkgs,
       workspace_pkgs,
-    }
+      patched_pkgs: resolve_patched_pkgs(workspace)?,
+    })
   }
 
   pub fn remote_pkgs(&self) -> &Vec<InstallNpmRemotePkg> {
@@ -141,4 +162,102 @@ impl NpmInstallDepsProvider {
   pub fn workspace_pkgs(&self) -> &Vec<InstallNpmWorkspacePkg> {
     &self.workspace_pkgs
   }
+
+  pub fn patched_pkgs(&self) -> &Vec<InstallNpmPatchedPkg> {
+    &self.patched_pkgs
+  }
+
+  pub fn patched_pkg(&self, nv: &PackageNv) -> Option<&InstallNpmPatchedPkg> {
+    self.patched_pkgs.iter().find(|pkg| pkg.nv == *nv)
+  }
+}
+
+/// Resolves the `"patchedDependencies"` of the workspace root, which map
+/// exact npm package versions to patch files relative to the config file:
+///
+/// ```jsonc
+/// {
+///   "patchedDependencies": {
+///     "is-number@7.0.0": "patches/is-number@7.0.0.patch"
+///   }
+/// }
+/// ```
+///
+/// They're read from the `deno.json` and the `package.json`, where the
+/// `"pnpm"` section of the latter is also understood. Entries of the
+/// `deno.json` take precedence.
+fn resolve_patched_pkgs(
+  workspace: &Workspace,
+) -> Result<Vec<InstallNpmPatchedPkg>, AnyError> {
+  let root_folder = workspace.root_folder_configs();
+  let mut patches = BTreeMap::new();
+  if let Some(pkg_json) = &root_folder.pkg_json {
+    let text = std::fs::read_to_string(&pkg_json.path)
+      .with_context(|| format!("Failed reading {}", pkg_json.path.display()))?;
+    let value: serde_json::Value = serde_json::from_str(&text)
+      .with_context(|| format!("Failed parsing {}", pkg_json.path.display()))?;
+    let value = value
+      .get("patchedDependencies")
+      .or_else(|| value.pointer("/pnpm/patchedDependencies"))
+      .cloned();
+    if let Some(value) = value {
+      patches.extend(parse_patched_dependencies(value, &pkg_json.path)?);
+    }
+  }
+  if let Some(deno_json) = &root_folder.deno_json {
+    let value = crate::args::deno_json::read_config_file_property(
+      deno_json,
+      "patchedDependencies",
+    )?;
+    if let (Some(value), Ok(path)) = (value, deno_json.specifier.to_file_path())
+    {
+      patches.extend(parse_patched_dependencies(value, &path)?);
+    }
+  }
+
+  patches
+    .into_iter()
+    .map(|(nv, patch_path)| {
+      let patch = std::fs::read_to_string(&patch_path).with_context(|| {
+        format!(
+          "Failed reading the patch of npm package '{}' at {}",
+          nv,
+          patch_path.display()
+        )
+      })?;
+      Ok(InstallNpmPatchedPkg {
+        checksum: checksum::gen(&[patch.as_bytes()]),
+        nv,
+        patch_path,
+        patch,
+      })
+    })
+    .collect()
+}
+
+fn parse_patched_dependencies(
+  value: serde_json::Value,
+  config_path: &Path,
+) -> Result<Vec<(PackageNv, PathBuf)>, AnyError> {
+  let entries: BTreeMap<String, String> = serde_json::from_value(value)
+    .with_context(|| {
+      format!(
+        "Invalid \"patchedDependencies\" in {}. Expected an object mapping packages to patch files.",
+        config_path.display()
+      )
+    })?;
+  let config_dir = config_path.parent().unwrap();
+  entries
+    .into_iter()
+    .map(|(key, patch_path)| {
+      let nv = PackageNv::from_str(&key).with_context(|| {
+        format!(
+          "Invalid \"patchedDependencies\" entry '{}' in {}. Expected a package name and exact version, like 'is-number@7.0.0'.",
+          key,
+          config_path.display()
+        )
+      })?;
+      Ok((nv, config_dir.join(patch_path)))
+    })
+    .collect()
 }
@@ -432,6 +432,14 @@ fn main() {
   }
   println!("cargo:rerun-if-env-changed=DENO_CANARY");
 
+  // the release server and public key of the builds of a fork
+  for name in ["DENO_RELEASE_URL", "DENO_RELEASE_PUBLIC_KEY"] {
+    if let Ok(value) = env::var(name) {
+      println!("cargo:rustc-env={name}={value}");
+    }
+    println!("cargo:rerun-if-env-changed={name}");
+  }
+
   println!("cargo:rustc-env=GIT_COMMIT_HASH={}", git_commit_hash());
   println!("cargo:rerun-if-env-changed=GIT_COMMIT_HASH");
   println!(
@@ -131,6 +131,11 @@ impl DenoDir {
     self.root.join("deps")
   }
 
+  /// Path to the cache folder of the modules of custom URL schemes.
+  pub fn schemes_folder_path(&self) -> PathBuf {
+    self.root.join("schemes")
+  }
+
   /// Path to the origin data cache folder.
   pub fn origin_data_folder_path(&self) -> PathBuf {
     // TODO(@crowlKats): change to origin_data for 2.0
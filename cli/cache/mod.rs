@@ -1,6 +1,7 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 use crate::args::CacheSetting;
+use crate::args::CliLockfile;
 use crate::errors::get_error_class_name;
 use crate::file_fetcher::FetchNoFollowOptions;
 use crate::file_fetcher::FetchOptions;
@@ -112,6 +113,7 @@ pub struct FetchCacher {
   global_http_cache: Arc<GlobalHttpCache>,
   npm_resolver: Arc<dyn CliNpmResolver>,
   module_info_cache: Arc<ModuleInfoCache>,
+  maybe_lockfile: Option<Arc<CliLockfile>>,
   permissions: FetchPermissionsOption,
   cache_info_enabled: bool,
 }
@@ -123,6 +125,7 @@ impl FetchCacher {
     global_http_cache: Arc<GlobalHttpCache>,
     npm_resolver: Arc<dyn CliNpmResolver>,
     module_info_cache: Arc<ModuleInfoCache>,
+    maybe_lockfile: Option<Arc<CliLockfile>>,
     permissions: FetchPermissionsOption,
   ) -> Self {
     Self {
@@ -131,6 +134,7 @@ impl FetchCacher {
       global_http_cache,
       npm_resolver,
       module_info_cache,
+      maybe_lockfile,
       permissions,
       cache_info_enabled: false,
     }
@@ -208,6 +212,12 @@ impl Loader for FetchCacher {
       }
     }
 
+    if let Some(lockfile) = &self.maybe_lockfile {
+      if let Err(err) = lockfile.error_if_remote_not_locked(specifier) {
+        return Box::pin(futures::future::ready(Err(err)));
+      }
+    }
+
     let file_fetcher = self.file_fetcher.clone();
     let file_header_overrides = self.file_header_overrides.clone();
     let permissions = self.permissions.clone();
@@ -1,12 +1,14 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 use crate::args::check_warn_tsconfig;
+use crate::args::deno_json::read_tsconfig_paths_config;
 use crate::args::get_root_cert_store;
 use crate::args::CaData;
 use crate::args::CliOptions;
 use crate::args::DenoSubcommand;
 use crate::args::Flags;
 use crate::args::NpmInstallDepsProvider;
+use crate::args::ProfileFlags;
 use crate::args::StorageKeyResolver;
 use crate::args::TsConfigType;
 use crate::cache::Caches;
@@ -43,6 +45,8 @@ use crate::resolver::CliGraphResolverOptions;
 use crate::resolver::CliNodeResolver;
 use crate::resolver::NpmModuleLoader;
 use crate::resolver::SloppyImportsResolver;
+use crate::resolver::TsConfigPathsResolver;
+use crate::scheme_handlers::SchemeHandlers;
 use crate::standalone::DenoCompileBinaryWriter;
 use crate::tools::check::TypeChecker;
 use crate::tools::coverage::CoverageCollector;
@@ -52,6 +56,8 @@ use crate::util::file_watcher::WatcherCommunicator;
 use crate::util::fs::canonicalize_path_maybe_not_exists;
 use crate::util::progress_bar::ProgressBar;
 use crate::util::progress_bar::ProgressBarStyle;
+use crate::util::v8::profiler::ProfileOptions;
+use crate::util::v8::profiler::ProfilerOptions;
 use crate::worker::CliMainWorkerFactory;
 use crate::worker::CliMainWorkerOptions;
 use std::path::PathBuf;
@@ -186,6 +192,7 @@ struct CliFactoryServices {
   npm_resolver: Deferred<Arc<dyn CliNpmResolver>>,
   permission_desc_parser: Deferred<Arc<RuntimePermissionDescriptorParser>>,
   sloppy_imports_resolver: Deferred<Option<Arc<SloppyImportsResolver>>>,
+  tsconfig_paths_resolver: Deferred<Option<Arc<TsConfigPathsResolver>>>,
   text_only_progress_bar: Deferred<ProgressBar>,
   type_checker: Deferred<Arc<TypeChecker>>,
   cjs_resolutions: Deferred<Arc<CjsResolutionStore>>,
@@ -333,14 +340,31 @@ impl CliFactory {
   pub fn file_fetcher(&self) -> Result<&Arc<FileFetcher>, AnyError> {
     self.services.file_fetcher.get_or_try_init(|| {
       let cli_options = self.cli_options()?;
-      Ok(Arc::new(FileFetcher::new(
+      let mut file_fetcher = FileFetcher::new(
         self.http_cache()?.clone(),
         cli_options.cache_setting(),
         !cli_options.no_remote(),
         self.http_client_provider().clone(),
         self.blob_store().clone(),
         Some(self.text_only_progress_bar().clone()),
-      )))
+      );
+      let mut scheme_handlers = SchemeHandlers::new(
+        self.deno_dir()?.schemes_folder_path(),
+        cli_options.maybe_lockfile().cloned(),
+      );
+      let start_dir = &cli_options.start_dir;
+      // the handlers of a workspace member take precedence over the ones of
+      // the workspace root
+      if let Some(root_deno_json) = start_dir.workspace.root_deno_json() {
+        scheme_handlers.register_from_config(root_deno_json)?;
+      }
+      if let Some(deno_json) = start_dir.maybe_deno_json() {
+        scheme_handlers.register_from_config(deno_json)?;
+      }
+      if !scheme_handlers.is_empty() {
+        file_fetcher.set_scheme_handlers(Arc::new(scheme_handlers));
+      }
+      Ok(Arc::new(file_fetcher))
     })
   }
 
@@ -391,9 +415,10 @@ impl CliFactory {
             cache_setting: cli_options.cache_setting(),
             text_only_progress_bar: self.text_only_progress_bar().clone(),
             maybe_node_modules_path: cli_options.node_modules_dir_path().cloned(),
-            npm_install_deps_provider: Arc::new(NpmInstallDepsProvider::from_workspace(cli_options.workspace())),
+            npm_install_deps_provider: Arc::new(NpmInstallDepsProvider::from_workspace(cli_options.workspace())?),
             npm_system_info: cli_options.npm_system_info(),
             npmrc: cli_options.npmrc().clone(),
+            npmrc_fetch_options: cli_options.npmrc_fetch_options().clone(),
             lifecycle_scripts: cli_options.lifecycle_scripts_config(),
           })
         }).await
@@ -418,6 +443,27 @@ impl CliFactory {
       .map(|maybe| maybe.as_ref())
   }
 
+  pub fn tsconfig_paths_resolver(
+    &self,
+  ) -> Result<Option<&Arc<TsConfigPathsResolver>>, AnyError> {
+    self
+      .services
+      .tsconfig_paths_resolver
+      .get_or_try_init(|| {
+        let start_dir = &self.cli_options()?.start_dir;
+        let Some(config_file) = start_dir
+          .maybe_deno_json()
+          .or_else(|| start_dir.workspace.root_deno_json())
+        else {
+          return Ok(None);
+        };
+        Ok(read_tsconfig_paths_config(config_file)?.map(|config| {
+          Arc::new(TsConfigPathsResolver::new(config, self.fs().clone()))
+        }))
+      })
+      .map(|maybe| maybe.as_ref())
+  }
+
   pub async fn workspace_resolver(
     &self,
   ) -> Result<&Arc<WorkspaceResolver>, AnyError> {
@@ -462,6 +508,7 @@ impl CliFactory {
           let cli_options = self.cli_options()?;
           Ok(Arc::new(CliGraphResolver::new(CliGraphResolverOptions {
             sloppy_imports_resolver: self.sloppy_imports_resolver()?.cloned(),
+            tsconfig_paths_resolver: self.tsconfig_paths_resolver()?.cloned(),
             node_resolver: Some(self.cli_node_resolver().await?.clone()),
             npm_resolver: if cli_options.no_npm() {
               None
@@ -555,6 +602,7 @@ impl CliFactory {
           Ok(Arc::new(NodeResolver::new(
             DenoFsNodeResolverEnv::new(self.fs().clone()),
             self.npm_resolver().await?.clone().into_npm_resolver(),
+            self.cli_options()?.node_conditions().to_vec(),
           )))
         }
         .boxed_local(),
@@ -848,6 +896,20 @@ impl CliFactory {
         None
       };
 
+    let initial_cwd = cli_options.initial_cwd();
+    let profile_options = |profile_flags: &ProfileFlags| ProfileOptions {
+      dir: match &profile_flags.dir {
+        Some(dir) => initial_cwd.join(dir),
+        None => initial_cwd.to_path_buf(),
+      },
+      name: profile_flags.name.clone(),
+      interval: profile_flags.interval,
+    };
+    let profiler = ProfilerOptions {
+      cpu: cli_options.cpu_prof().map(profile_options),
+      heap: cli_options.heap_prof().map(profile_options),
+    };
+
     Ok(CliMainWorkerOptions {
       argv: cli_options.argv().clone(),
       // This optimization is only available for "run" subcommand
@@ -878,6 +940,7 @@ impl CliFactory {
         .clone(),
       create_hmr_runner,
       create_coverage_collector,
+      profiler,
       node_ipc: cli_options.node_ipc_fd(),
       serve_port: cli_options.serve_port(),
       serve_host: cli_options.serve_host(),
@@ -4,10 +4,15 @@ use crate::args::CacheSetting;
 use crate::auth_tokens::AuthTokens;
 use crate::cache::HttpCache;
 use crate::colors;
+use crate::http_util::download_retry_delay;
 use crate::http_util::CacheSemantics;
 use crate::http_util::FetchOnceArgs;
 use crate::http_util::FetchOnceResult;
 use crate::http_util::HttpClientProvider;
+use crate::http_util::DOWNLOAD_RETRIES;
+use crate::scheme_handlers::SchemeHandler;
+use crate::scheme_handlers::SchemeHandlerResponse;
+use crate::scheme_handlers::SchemeHandlers;
 use crate::util::progress_bar::ProgressBar;
 
 use deno_ast::MediaType;
@@ -225,6 +230,7 @@ pub struct FileFetcher {
   blob_store: Arc<BlobStore>,
   download_log_level: log::Level,
   progress_bar: Option<ProgressBar>,
+  scheme_handlers: Option<Arc<SchemeHandlers>>,
 }
 
 impl FileFetcher {
@@ -246,6 +252,7 @@ impl FileFetcher {
       blob_store,
       download_log_level: log::Level::Info,
       progress_bar,
+      scheme_handlers: None,
     }
   }
 
@@ -258,6 +265,11 @@ impl FileFetcher {
     self.download_log_level = level;
   }
 
+  /// Sets the handlers of the modules of custom URL schemes.
+  pub fn set_scheme_handlers(&mut self, scheme_handlers: Arc<SchemeHandlers>) {
+    self.scheme_handlers = Some(scheme_handlers);
+  }
+
   /// Fetch cached remote file.
   ///
   /// This is a recursive operation if source file has redirections.
@@ -433,15 +445,21 @@ impl FileFetcher {
     let maybe_auth_token = self.auth_tokens.get(specifier);
 
     async fn handle_request_or_server_error(
-      retried: &mut bool,
+      retries: &mut u32,
       specifier: &Url,
       err_str: String,
     ) -> Result<(), AnyError> {
-      // Retry once, and bail otherwise.
-      if !*retried {
-        *retried = true;
-        log::debug!("Import '{}' failed: {}. Retrying...", specifier, err_str);
-        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+      // Retry with a backoff, and bail otherwise.
+      if *retries < DOWNLOAD_RETRIES {
+        let delay = download_retry_delay(*retries);
+        *retries += 1;
+        log::debug!(
+          "Import '{}' failed: {}. Retrying in {}ms...",
+          specifier,
+          err_str,
+          delay.as_millis()
+        );
+        tokio::time::sleep(delay).await;
         Ok(())
       } else {
         Err(generic_error(format!(
@@ -451,7 +469,7 @@ impl FileFetcher {
       }
     }
 
-    let mut retried = false; // retry intermittent failures
+    let mut retries = 0; // retry intermittent failures
     let result = loop {
       let result = match self
         .http_client_provider
@@ -487,12 +505,12 @@ impl FileFetcher {
           }))
         }
         FetchOnceResult::RequestError(err) => {
-          handle_request_or_server_error(&mut retried, specifier, err).await?;
+          handle_request_or_server_error(&mut retries, specifier, err).await?;
           continue;
         }
         FetchOnceResult::ServerError(status) => {
           handle_request_or_server_error(
-            &mut retried,
+            &mut retries,
             specifier,
             status.to_string(),
           )
@@ -507,6 +525,81 @@ impl FileFetcher {
     result
   }
 
+  /// Fetches a module of a custom URL scheme with its handler, caching the
+  /// source that it returns.
+  async fn fetch_scheme_handler_no_follow(
+    &self,
+    specifier: &ModuleSpecifier,
+    scheme_handler: &Arc<dyn SchemeHandler>,
+    cache_setting: &CacheSetting,
+    maybe_checksum: Option<&LoaderChecksum>,
+  ) -> Result<FileOrRedirect, AnyError> {
+    debug!(
+      "FileFetcher::fetch_scheme_handler_no_follow - specifier: {}",
+      specifier
+    );
+    let scheme_handlers = self.scheme_handlers.as_ref().unwrap();
+
+    let use_cache = match cache_setting {
+      // the sources of custom schemes don't have any cache headers
+      CacheSetting::RespectHeaders => true,
+      cache_setting => self.should_use_cache(specifier, cache_setting),
+    };
+    let maybe_cached_source = if use_cache {
+      scheme_handlers.get_cached(specifier)
+    } else {
+      None
+    };
+    let source = match maybe_cached_source {
+      Some(source) => source,
+      None => {
+        let is_redirect_only = scheme_handler.is_redirect_only();
+        if *cache_setting == CacheSetting::Only && !is_redirect_only {
+          return Err(custom_error(
+            "NotCached",
+            format!(
+              "Specifier not found in cache: \"{specifier}\", --cached-only is specified."
+            ),
+          ));
+        }
+        let maybe_progress_guard = if is_redirect_only {
+          None
+        } else if let Some(pb) = self.progress_bar.as_ref() {
+          Some(pb.update(specifier.as_str()))
+        } else {
+          log::log!(
+            self.download_log_level,
+            "{} {}",
+            colors::green("Download"),
+            specifier
+          );
+          None
+        };
+        let response = scheme_handler.fetch(specifier).await?;
+        drop(maybe_progress_guard);
+        match response {
+          SchemeHandlerResponse::Redirect(redirect_specifier) => {
+            return Ok(FileOrRedirect::Redirect(redirect_specifier));
+          }
+          SchemeHandlerResponse::Module(source) => {
+            scheme_handlers.set_cached(specifier, &source)?;
+            source
+          }
+        }
+      }
+    };
+
+    if let Some(checksum) = &maybe_checksum {
+      checksum.check_source(&source)?;
+    }
+    scheme_handlers.check_lockfile_checksum(specifier, &source)?;
+    Ok(FileOrRedirect::File(File {
+      specifier: specifier.clone(),
+      maybe_headers: None,
+      source: Arc::from(source),
+    }))
+  }
+
   /// Returns if the cache should be used for a given specifier.
   fn should_use_cache(
     &self,
@@ -642,7 +735,14 @@ impl FileFetcher {
       "FileFetcher::fetch_no_follow_with_options - specifier: {}",
       specifier
     );
-    let scheme = get_validated_scheme(specifier)?;
+    let maybe_scheme_handler = self
+      .scheme_handlers
+      .as_ref()
+      .and_then(|scheme_handlers| scheme_handlers.get(specifier.scheme()));
+    let scheme = match maybe_scheme_handler {
+      Some(_) => specifier.scheme().to_string(),
+      None => get_validated_scheme(specifier)?,
+    };
     match options.permissions {
       FetchPermissionsOptionRef::AllowAll => {
         // allow
@@ -664,6 +764,17 @@ impl FileFetcher {
         .fetch_blob_url(specifier)
         .await
         .map(FileOrRedirect::File)
+    } else if let Some(scheme_handler) = maybe_scheme_handler
+      .filter(|handler| self.allow_remote || handler.is_redirect_only())
+    {
+      self
+        .fetch_scheme_handler_no_follow(
+          specifier,
+          scheme_handler,
+          options.maybe_cache_setting.unwrap_or(&self.cache_setting),
+          maybe_checksum,
+        )
+        .await
     } else if !self.allow_remote {
       Err(custom_error(
         "NoRemote",
@@ -867,6 +978,46 @@ mod tests {
     assert_eq!(result_file, file);
   }
 
+  #[tokio::test]
+  async fn test_fetch_embedder_scheme_handler() {
+    #[derive(Debug, Default)]
+    struct CountingSchemeHandler(std::sync::atomic::AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl SchemeHandler for CountingSchemeHandler {
+      async fn fetch(
+        &self,
+        _specifier: &ModuleSpecifier,
+      ) -> Result<SchemeHandlerResponse, AnyError> {
+        self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Ok(SchemeHandlerResponse::Module(
+          b"export const a = 1;".to_vec(),
+        ))
+      }
+    }
+
+    let (mut file_fetcher, temp_dir) = setup(CacheSetting::Use, None);
+    let handler = Arc::new(CountingSchemeHandler::default());
+    let mut scheme_handlers =
+      SchemeHandlers::new(temp_dir.path().join("schemes").to_path_buf(), None);
+    scheme_handlers.register("corp", handler.clone());
+    file_fetcher.set_scheme_handlers(Arc::new(scheme_handlers));
+
+    let specifier = resolve_url("corp://std/mod.ts").unwrap();
+    for _ in 0..2 {
+      let file = file_fetcher
+        .fetch_bypass_permissions(&specifier)
+        .await
+        .unwrap()
+        .into_text_decoded()
+        .unwrap();
+      assert_eq!(&*file.source, "export const a = 1;");
+      assert_eq!(file.media_type, MediaType::TypeScript);
+    }
+    // the second fetch is served from the cache
+    assert_eq!(handler.0.load(std::sync::atomic::Ordering::SeqCst), 1);
+  }
+
   #[tokio::test]
   async fn test_fetch_data_url() {
     let (file_fetcher, _) = setup(CacheSetting::Use, None);
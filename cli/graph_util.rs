@@ -683,6 +683,7 @@ impl ModuleGraphBuilder {
       self.global_http_cache.clone(),
       self.npm_resolver.clone(),
       self.module_info_cache.clone(),
+      self.lockfile.clone(),
       permissions,
     )
   }
@@ -19,13 +19,20 @@ use deno_runtime::deno_fetch;
 use deno_runtime::deno_fetch::create_http_client;
 use deno_runtime::deno_fetch::CreateHttpClientOptions;
 use deno_runtime::deno_tls::RootCertStoreProvider;
+use http::header::HeaderMap;
 use http::header::HeaderName;
 use http::header::HeaderValue;
 use http::header::ACCEPT;
+use http::header::ACCEPT_RANGES;
 use http::header::AUTHORIZATION;
 use http::header::CONTENT_LENGTH;
+use http::header::CONTENT_RANGE;
+use http::header::ETAG;
 use http::header::IF_NONE_MATCH;
+use http::header::IF_RANGE;
+use http::header::LAST_MODIFIED;
 use http::header::LOCATION;
+use http::header::RANGE;
 use http::StatusCode;
 use http_body_util::BodyExt;
 
@@ -35,6 +42,7 @@ use std::thread::ThreadId;
 use std::time::Duration;
 use std::time::SystemTime;
 use thiserror::Error;
+use tokio::sync::Semaphore;
 
 // TODO(ry) HTTP headers are not unique key, value pairs. There may be more than
 // one header line with the same key. This should be changed to something like
@@ -207,6 +215,20 @@ pub struct FetchOnceArgs<'a> {
   pub maybe_progress_guard: Option<&'a UpdateGuard>,
 }
 
+/// The maximum number of downloads that are in flight at the same time,
+/// across all the clients of a provider.
+const MAX_CONCURRENT_DOWNLOADS: usize = 16;
+
+/// The number of times a download that failed because of the connection or
+/// the server is retried.
+pub const DOWNLOAD_RETRIES: u32 = 3;
+
+/// The delay before retrying a failed download, which doubles with every
+/// retry.
+pub fn download_retry_delay(retry: u32) -> Duration {
+  Duration::from_millis(100 * 2u64.pow(retry))
+}
+
 pub struct HttpClientProvider {
   options: CreateHttpClientOptions,
   root_cert_store_provider: Option<Arc<dyn RootCertStoreProvider>>,
@@ -214,6 +236,7 @@ pub struct HttpClientProvider {
   // so we store these Clients keyed by thread id
   // https://github.com/seanmonstar/reqwest/issues/1148#issuecomment-910868788
   clients_by_thread_id: Mutex<HashMap<ThreadId, deno_fetch::Client>>,
+  download_permits: Arc<Semaphore>,
 }
 
 impl std::fmt::Debug for HttpClientProvider {
@@ -236,6 +259,35 @@ impl HttpClientProvider {
       },
       root_cert_store_provider,
       clients_by_thread_id: Default::default(),
+      download_permits: Arc::new(Semaphore::new(MAX_CONCURRENT_DOWNLOADS)),
+    }
+  }
+
+  /// Creates a provider of clients with the same options, which additionally
+  /// don't verify the TLS certificates of the provided hosts.
+  pub fn with_unsafely_ignored_certificate_errors(
+    &self,
+    hosts: Vec<String>,
+  ) -> Self {
+    let unsafely_ignore_certificate_errors = match &self
+      .options
+      .unsafely_ignore_certificate_errors
+    {
+      // an empty list already ignores the errors of all hosts
+      Some(allowlist) if allowlist.is_empty() => Some(Vec::new()),
+      Some(allowlist) => Some(allowlist.iter().cloned().chain(hosts).collect()),
+      None if hosts.is_empty() => None,
+      None => Some(hosts),
+    };
+    Self {
+      options: CreateHttpClientOptions {
+        unsafely_ignore_certificate_errors,
+        ..self.options.clone()
+      },
+      root_cert_store_provider: self.root_cert_store_provider.clone(),
+      clients_by_thread_id: Default::default(),
+      // share the limit of concurrent downloads
+      download_permits: self.download_permits.clone(),
     }
   }
 
@@ -245,7 +297,10 @@ impl HttpClientProvider {
     let mut clients = self.clients_by_thread_id.lock();
     let entry = clients.entry(thread_id);
     match entry {
-      Entry::Occupied(entry) => Ok(HttpClient::new(entry.get().clone())),
+      Entry::Occupied(entry) => Ok(HttpClient::new(
+        entry.get().clone(),
+        self.download_permits.clone(),
+      )),
       Entry::Vacant(entry) => {
         let client = create_http_client(
           version::DENO_VERSION_INFO.user_agent,
@@ -258,7 +313,7 @@ impl HttpClientProvider {
           },
         )?;
         entry.insert(client.clone());
-        Ok(HttpClient::new(client))
+        Ok(HttpClient::new(client, self.download_permits.clone()))
       }
     }
   }
@@ -291,9 +346,25 @@ pub enum DownloadError {
   BadResponse(#[from] BadResponseError),
 }
 
+impl DownloadError {
+  /// Whether the download failed because of the connection or the server, so
+  /// it might succeed when retried.
+  fn is_retryable(&self) -> bool {
+    match self {
+      DownloadError::Fetch(_) => true,
+      DownloadError::BadResponse(err) => {
+        err.status_code.is_server_error()
+          || err.status_code == StatusCode::TOO_MANY_REQUESTS
+      }
+      _ => false,
+    }
+  }
+}
+
 #[derive(Debug)]
 pub struct HttpClient {
   client: deno_fetch::Client,
+  download_permits: Arc<Semaphore>,
   // don't allow sending this across threads because then
   // it might be shared accidentally across tokio runtimes
   // which will cause issues
@@ -304,9 +375,10 @@ pub struct HttpClient {
 impl HttpClient {
   // DO NOT make this public. You should always be creating one of these from
   // the HttpClientProvider
-  fn new(client: deno_fetch::Client) -> Self {
+  fn new(client: deno_fetch::Client, download_permits: Arc<Semaphore>) -> Self {
     Self {
       client,
+      download_permits,
       _unsend_marker: deno_core::unsync::UnsendMarker::default(),
     }
   }
@@ -387,6 +459,7 @@ impl HttpClient {
       let accepts_val = HeaderValue::from_str(&accept)?;
       request.headers_mut().insert(ACCEPT, accepts_val);
     }
+    let _permit = self.download_permits.acquire().await.unwrap();
     let response = match self.client.clone().send(request).await {
       Ok(resp) => resp,
       Err(err) => {
@@ -486,7 +559,7 @@ impl HttpClient {
     url: Url,
     maybe_header: Option<(HeaderName, HeaderValue)>,
   ) -> Result<Url, AnyError> {
-    let (_, url) = self.get_redirected_response(url, maybe_header).await?;
+    let (_, url, _) = self.get_redirected_response(url, maybe_header).await?;
     Ok(url)
   }
 
@@ -496,7 +569,39 @@ impl HttpClient {
     maybe_header: Option<(HeaderName, HeaderValue)>,
     progress_guard: Option<&UpdateGuard>,
   ) -> Result<Option<Vec<u8>>, DownloadError> {
-    let (response, _) = self.get_redirected_response(url, maybe_header).await?;
+    let mut retries = 0;
+    loop {
+      let result = self
+        .download_once(url.clone(), maybe_header.clone(), progress_guard)
+        .await;
+      match result {
+        Err(err) if retries < DOWNLOAD_RETRIES && err.is_retryable() => {
+          let delay = download_retry_delay(retries);
+          log::debug!(
+            "Download of '{}' failed: {}. Retrying in {}ms...",
+            url,
+            err,
+            delay.as_millis()
+          );
+          tokio::time::sleep(delay).await;
+          retries += 1;
+        }
+        result => return result,
+      }
+    }
+  }
+
+  async fn download_once(
+    &self,
+    url: Url,
+    maybe_header: Option<(HeaderName, HeaderValue)>,
+    progress_guard: Option<&UpdateGuard>,
+  ) -> Result<Option<Vec<u8>>, DownloadError> {
+    let _permit = self.download_permits.acquire().await.unwrap();
+    // the header is dropped when redirected to another origin, so the
+    // download is resumed with the header of the final request
+    let (response, url, maybe_header) =
+      self.get_redirected_response(url, maybe_header).await?;
 
     if response.status() == 404 {
       return Ok(None);
@@ -511,17 +616,94 @@ impl HttpClient {
       }));
     }
 
-    get_response_body_with_progress(response, progress_guard)
+    self
+      .get_response_body_resumable(response, url, maybe_header, progress_guard)
       .await
       .map(Some)
-      .map_err(DownloadError::Fetch)
   }
 
+  /// Gets the body of the response. When the connection is interrupted, the
+  /// download is resumed where it stopped with range requests, provided that
+  /// the server supports them, so large tarballs aren't downloaded again from
+  /// the start.
+  async fn get_response_body_resumable(
+    &self,
+    mut response: http::Response<deno_fetch::ResBody>,
+    url: Url,
+    maybe_header: Option<(HeaderName, HeaderValue)>,
+    progress_guard: Option<&UpdateGuard>,
+  ) -> Result<Vec<u8>, DownloadError> {
+    let mut data = Vec::new();
+    let mut resumes = 0;
+    let mut maybe_validator = resume_validator(response.headers());
+    loop {
+      let err = match read_response_body_with_progress(
+        response,
+        &mut data,
+        progress_guard,
+      )
+      .await
+      {
+        Ok(()) => return Ok(data),
+        Err(err) => err,
+      };
+      let Some(validator) = maybe_validator.clone() else {
+        return Err(DownloadError::Fetch(err));
+      };
+      if data.is_empty() || resumes >= DOWNLOAD_RETRIES {
+        return Err(DownloadError::Fetch(err));
+      }
+      resumes += 1;
+      log::debug!(
+        "Download of '{}' was interrupted after {} bytes: {}. Resuming...",
+        url,
+        data.len(),
+        err
+      );
+      let mut req = self.get(url.clone())?.build();
+      if let Some((header_name, header_value)) = maybe_header.as_ref() {
+        req.headers_mut().append(header_name, header_value.clone());
+      }
+      req.headers_mut().insert(
+        RANGE,
+        HeaderValue::from_str(&format!("bytes={}-", data.len()))?,
+      );
+      req.headers_mut().insert(IF_RANGE, validator);
+      response = self
+        .client
+        .clone()
+        .send(req)
+        .await
+        .map_err(|e| DownloadError::Fetch(e.into()))?;
+      match response.status() {
+        StatusCode::PARTIAL_CONTENT
+          if content_range_start(response.headers())
+            == Some(data.len() as u64) => {}
+        // the resource changed in the meantime, so it's sent in full
+        StatusCode::OK => {
+          data.clear();
+          maybe_validator = resume_validator(response.headers());
+        }
+        _ => return Err(DownloadError::Fetch(err)),
+      }
+    }
+  }
+
+  /// Follows the redirects of the request, returning the final response and
+  /// URL along with the header that was sent with the final request, which
+  /// is dropped once redirected to another origin.
   async fn get_redirected_response(
     &self,
     mut url: Url,
     mut maybe_header: Option<(HeaderName, HeaderValue)>,
-  ) -> Result<(http::Response<deno_fetch::ResBody>, Url), DownloadError> {
+  ) -> Result<
+    (
+      http::Response<deno_fetch::ResBody>,
+      Url,
+      Option<(HeaderName, HeaderValue)>,
+    ),
+    DownloadError,
+  > {
     let mut req = self.get(url.clone())?.build();
     if let Some((header_name, header_value)) = maybe_header.as_ref() {
       req.headers_mut().append(header_name, header_value.clone());
@@ -557,12 +739,12 @@ impl HttpClient {
           response = new_response;
           url = new_url;
         } else {
-          return Ok((new_response, new_url));
+          return Ok((new_response, new_url, maybe_header));
         }
       }
       Err(DownloadError::TooManyRedirects)
     } else {
-      Ok((response, url))
+      Ok((response, url, maybe_header))
     }
   }
 }
@@ -571,32 +753,73 @@ async fn get_response_body_with_progress(
   response: http::Response<deno_fetch::ResBody>,
   progress_guard: Option<&UpdateGuard>,
 ) -> Result<Vec<u8>, AnyError> {
+  if progress_guard.is_none() {
+    let bytes = response.collect().await?.to_bytes();
+    return Ok(bytes.into());
+  }
+  let mut data = Vec::new();
+  read_response_body_with_progress(response, &mut data, progress_guard).await?;
+  Ok(data)
+}
+
+/// Appends the body of the response to the data, which holds the start of
+/// the body when the download was resumed. The data that was received is
+/// kept when reading the body fails.
+async fn read_response_body_with_progress(
+  response: http::Response<deno_fetch::ResBody>,
+  data: &mut Vec<u8>,
+  progress_guard: Option<&UpdateGuard>,
+) -> Result<(), AnyError> {
   use http_body::Body as _;
-  if let Some(progress_guard) = progress_guard {
-    let mut total_size = response.body().size_hint().exact();
-    if total_size.is_none() {
-      total_size = response
-        .headers()
-        .get(CONTENT_LENGTH)
-        .and_then(|val| val.to_str().ok())
-        .and_then(|s| s.parse::<u64>().ok());
+  let mut remaining_size = response.body().size_hint().exact();
+  if remaining_size.is_none() {
+    remaining_size = response
+      .headers()
+      .get(CONTENT_LENGTH)
+      .and_then(|val| val.to_str().ok())
+      .and_then(|s| s.parse::<u64>().ok());
+  }
+  if let Some(remaining_size) = remaining_size {
+    data.reserve(remaining_size as usize);
+    if let Some(progress_guard) = progress_guard {
+      progress_guard.set_total_size(data.len() as u64 + remaining_size);
     }
-    if let Some(total_size) = total_size {
-      progress_guard.set_total_size(total_size);
-      let mut current_size = 0;
-      let mut data = Vec::with_capacity(total_size as usize);
-      let mut stream = response.into_body().into_data_stream();
-      while let Some(item) = stream.next().await {
-        let bytes = item?;
-        current_size += bytes.len() as u64;
-        progress_guard.set_position(current_size);
-        data.extend(bytes.into_iter());
-      }
-      return Ok(data);
+  }
+  let mut stream = response.into_body().into_data_stream();
+  while let Some(item) = stream.next().await {
+    let bytes = item?;
+    data.extend_from_slice(&bytes);
+    if let Some(progress_guard) = progress_guard {
+      progress_guard.set_position(data.len() as u64);
     }
   }
-  let bytes = response.collect().await?.to_bytes();
-  Ok(bytes.into())
+  Ok(())
+}
+
+/// Gets the validator for the `If-Range` header of a request that resumes
+/// the download of the response, when the server supports range requests.
+fn resume_validator(headers: &HeaderMap) -> Option<HeaderValue> {
+  let accepts_ranges = headers
+    .get(ACCEPT_RANGES)
+    .and_then(|value| value.to_str().ok())
+    .is_some_and(|value| value.trim().eq_ignore_ascii_case("bytes"));
+  if !accepts_ranges {
+    return None;
+  }
+  headers
+    .get(ETAG)
+    // weak etags can't be used for range requests
+    .filter(|etag| !etag.as_bytes().starts_with(b"W/"))
+    .or_else(|| headers.get(LAST_MODIFIED))
+    .cloned()
+}
+
+/// Gets the position of the first byte of a partial response, from a
+/// `Content-Range` header like `bytes 1000-1999/2000`.
+fn content_range_start(headers: &HeaderMap) -> Option<u64> {
+  let content_range = headers.get(CONTENT_RANGE)?.to_str().ok()?;
+  let (start, _) = content_range.strip_prefix("bytes ")?.split_once('-')?;
+  start.trim().parse().ok()
 }
 
 /// Construct the next uri based on base uri and location header fragment
@@ -764,10 +987,192 @@ mod test {
     assert_eq!(new_uri.path(), "/z");
   }
 
+  #[test]
+  fn test_resume_validator() {
+    let headers = |entries: &[(&'static str, &'static str)]| {
+      let mut headers = HeaderMap::new();
+      for (name, value) in entries {
+        headers.insert(*name, HeaderValue::from_static(value));
+      }
+      headers
+    };
+    assert_eq!(resume_validator(&headers(&[("etag", "\"abc\"")])), None);
+    assert_eq!(
+      resume_validator(&headers(&[
+        ("accept-ranges", "bytes"),
+        ("etag", "\"abc\""),
+        ("last-modified", "Wed, 21 Oct 2015 07:28:00 GMT"),
+      ])),
+      Some(HeaderValue::from_static("\"abc\""))
+    );
+    assert_eq!(
+      resume_validator(&headers(&[
+        ("accept-ranges", "bytes"),
+        ("etag", "W/\"abc\""),
+        ("last-modified", "Wed, 21 Oct 2015 07:28:00 GMT"),
+      ])),
+      Some(HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"))
+    );
+    assert_eq!(
+      resume_validator(&headers(&[
+        ("accept-ranges", "none"),
+        ("etag", "\"abc\""),
+      ])),
+      None
+    );
+  }
+
+  #[test]
+  fn test_content_range_start() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(content_range_start(&headers), None);
+    headers.insert(
+      CONTENT_RANGE,
+      HeaderValue::from_static("bytes 1000-1999/2000"),
+    );
+    assert_eq!(content_range_start(&headers), Some(1000));
+    headers.insert(CONTENT_RANGE, HeaderValue::from_static("bytes */2000"));
+    assert_eq!(content_range_start(&headers), None);
+  }
+
+  #[test]
+  fn test_download_retry_delay() {
+    assert_eq!(download_retry_delay(0), Duration::from_millis(100));
+    assert_eq!(download_retry_delay(1), Duration::from_millis(200));
+    assert_eq!(download_retry_delay(2), Duration::from_millis(400));
+  }
+
+  /// Reads the head of a request, which is all that the test servers need.
+  async fn read_request_head(stream: &mut tokio::net::TcpStream) -> String {
+    use tokio::io::AsyncReadExt;
+    let mut head = Vec::new();
+    let mut buf = [0; 1024];
+    while !head.ends_with(b"\r\n\r\n") {
+      let n = stream.read(&mut buf).await.unwrap();
+      if n == 0 {
+        break;
+      }
+      head.extend_from_slice(&buf[..n]);
+    }
+    String::from_utf8(head).unwrap().to_ascii_lowercase()
+  }
+
+  /// Serves "helloworld" with range request support, but drops the
+  /// connection after "hello" when the body isn't requested with a range.
+  async fn serve_interrupted_download(
+    listener: tokio::net::TcpListener,
+    requests: Arc<Mutex<Vec<String>>>,
+  ) {
+    use tokio::io::AsyncWriteExt;
+    loop {
+      let (mut stream, _) = listener.accept().await.unwrap();
+      let head = read_request_head(&mut stream).await;
+      let response = if head.contains("\r\nrange: bytes=5-\r\n") {
+        concat!(
+          "HTTP/1.1 206 Partial Content\r\n",
+          "content-range: bytes 5-9/10\r\n",
+          "content-length: 5\r\n",
+          "connection: close\r\n\r\n",
+          "world",
+        )
+      } else {
+        concat!(
+          "HTTP/1.1 200 OK\r\n",
+          "accept-ranges: bytes\r\n",
+          "etag: \"v1\"\r\n",
+          "content-length: 10\r\n",
+          "connection: close\r\n\r\n",
+          "hello",
+        )
+      };
+      requests.lock().push(head);
+      stream.write_all(response.as_bytes()).await.unwrap();
+      stream.shutdown().await.ok();
+    }
+  }
+
+  #[tokio::test]
+  async fn test_download_resume_after_redirect() {
+    use tokio::io::AsyncWriteExt;
+    let download_listener =
+      tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let download_port = download_listener.local_addr().unwrap().port();
+    let download_requests = Arc::new(Mutex::new(Vec::new()));
+    let download_server = tokio::spawn(serve_interrupted_download(
+      download_listener,
+      download_requests.clone(),
+    ));
+    // redirects to the download server, which is another origin
+    let redirect_listener =
+      tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let redirect_port = redirect_listener.local_addr().unwrap().port();
+    let redirect_server = tokio::spawn(async move {
+      loop {
+        let (mut stream, _) = redirect_listener.accept().await.unwrap();
+        read_request_head(&mut stream).await;
+        let response = format!(
+          concat!(
+            "HTTP/1.1 302 Found\r\n",
+            "location: http://127.0.0.1:{}/pkg.tgz\r\n",
+            "content-length: 0\r\n",
+            "connection: close\r\n\r\n",
+          ),
+          download_port
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+        stream.shutdown().await.ok();
+      }
+    });
+
+    let client = create_test_client();
+    let auth_header = Some((
+      AUTHORIZATION,
+      HeaderValue::from_static("Bearer registry-token"),
+    ));
+
+    // the download is resumed on the same origin with the header
+    let url =
+      Url::parse(&format!("http://127.0.0.1:{download_port}/pkg.tgz")).unwrap();
+    let bytes = client
+      .download_inner(url, auth_header.clone(), None)
+      .await
+      .unwrap();
+    assert_eq!(bytes.as_deref(), Some(b"helloworld".as_slice()));
+    {
+      let requests = std::mem::take(&mut *download_requests.lock());
+      assert_eq!(requests.len(), 2);
+      assert!(requests[0].contains("\r\nauthorization: bearer"));
+      assert!(requests[1].contains("\r\nrange: bytes=5-\r\n"));
+      assert!(requests[1].contains("\r\nif-range: \"v1\"\r\n"));
+      assert!(requests[1].contains("\r\nauthorization: bearer"));
+    }
+
+    // the header isn't sent to the other origin, neither when redirected nor
+    // when the download is resumed
+    let url =
+      Url::parse(&format!("http://127.0.0.1:{redirect_port}/pkg.tgz")).unwrap();
+    let bytes = client.download_inner(url, auth_header, None).await.unwrap();
+    assert_eq!(bytes.as_deref(), Some(b"helloworld".as_slice()));
+    let requests = std::mem::take(&mut *download_requests.lock());
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1].contains("\r\nrange: bytes=5-\r\n"));
+    for request in requests {
+      assert!(!request.contains("authorization"), "{request}");
+    }
+
+    download_server.abort();
+    redirect_server.abort();
+  }
+
+  fn test_download_permits() -> Arc<Semaphore> {
+    Arc::new(Semaphore::new(MAX_CONCURRENT_DOWNLOADS))
+  }
+
   fn create_test_client() -> HttpClient {
     HttpClient::new(
       create_http_client("test_client", CreateHttpClientOptions::default())
         .unwrap(),
+      test_download_permits(),
     )
   }
 
@@ -958,6 +1363,7 @@ mod test {
         },
       )
       .unwrap(),
+      test_download_permits(),
     );
     let result = client
       .fetch_no_follow(FetchOnceArgs {
@@ -1004,6 +1410,7 @@ mod test {
           CreateHttpClientOptions::default(),
         )
         .unwrap(),
+        test_download_permits(),
       );
 
       let result = client
@@ -1066,6 +1473,7 @@ mod test {
         },
       )
       .unwrap(),
+      test_download_permits(),
     );
 
     let result = client
@@ -1120,6 +1528,7 @@ mod test {
         },
       )
       .unwrap(),
+      test_download_permits(),
     );
     let result = client
       .fetch_no_follow(FetchOnceArgs {
@@ -1161,6 +1570,7 @@ mod test {
         },
       )
       .unwrap(),
+      test_download_permits(),
     );
     let result = client
       .fetch_no_follow(FetchOnceArgs {
@@ -1217,6 +1627,7 @@ mod test {
         },
       )
       .unwrap(),
+      test_download_permits(),
     );
     let result = client
       .fetch_no_follow(FetchOnceArgs {
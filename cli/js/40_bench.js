@@ -6,6 +6,7 @@ import {
   escapeName,
   pledgePermissions,
   restorePermissions,
+  validateTags,
 } from "ext:cli/40_test_common.js";
 import { Console } from "ext:deno_console/01_console.js";
 import { setExitHandler } from "ext:runtime/30_os.js";
@@ -63,6 +64,7 @@ function bench(
       only: false,
       sanitizeExit: true,
       permissions: null,
+      tags: [],
       warmup: true,
     };
     if (cachedOrigin == undefined) {
@@ -77,6 +79,7 @@ function bench(
       warmupBenchDesc.ignore,
       warmupBenchDesc.only,
       warmupBenchDesc.warmup,
+      warmupBenchDesc.tags,
       registerBenchIdRetBufU8,
     );
     warmupBenchDesc.id = registerBenchIdRetBufU8[0];
@@ -90,6 +93,7 @@ function bench(
     only: false,
     sanitizeExit: true,
     permissions: null,
+    tags: [],
   };
 
   if (typeof nameOrFnOrOptions === "string") {
@@ -167,6 +171,7 @@ function bench(
   benchDesc.fn = wrapBenchmark(benchDesc);
   benchDesc.warmup = false;
   benchDesc.name = escapeName(benchDesc.name);
+  benchDesc.tags = validateTags(benchDesc.tags);
   if (cachedOrigin == undefined) {
     cachedOrigin = op_bench_get_origin();
   }
@@ -178,6 +183,7 @@ function bench(
     benchDesc.ignore,
     benchDesc.only,
     false,
+    benchDesc.tags,
     registerBenchIdRetBufU8,
   );
   benchDesc.id = registerBenchIdRetBufU8[0];
@@ -1,7 +1,11 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 import { core, primordials } from "ext:core/mod.js";
-import { escapeName, withPermissions } from "ext:cli/40_test_common.js";
+import {
+  escapeName,
+  validateTags,
+  withPermissions,
+} from "ext:cli/40_test_common.js";
 
 // TODO(mmastrac): We cannot import these from "ext:core/ops" yet
 const {
@@ -45,6 +49,7 @@ const DenoNs = globalThis.Deno;
  *   sanitizeResources: boolean,
  *   sanitizeExit: boolean,
  *   permissions: PermissionOptions,
+ *   tags: string[],
  * }} TestDescription
  *
  * @typedef {{
@@ -85,6 +90,7 @@ const DenoNs = globalThis.Deno;
  *   only: boolean.
  *   sanitizeExit: boolean,
  *   permissions: PermissionOptions,
+ *   tags: string[],
  * }} BenchDescription
  */
 
@@ -228,6 +234,7 @@ function testInner(
     sanitizeResources: true,
     sanitizeExit: true,
     permissions: null,
+    tags: [],
   };
 
   if (typeof nameOrFnOrOptions === "string") {
@@ -312,6 +319,7 @@ function testInner(
   testDesc.location = core.currentUserCallSite();
   testDesc.fn = wrapTest(testDesc);
   testDesc.name = escapeName(testDesc.name);
+  testDesc.tags = validateTags(testDesc.tags);
 
   op_register_test(
     testDesc.fn,
@@ -320,6 +328,7 @@ function testInner(
     testDesc.only,
     testDesc.sanitizeOps,
     testDesc.sanitizeResources,
+    testDesc.tags,
     testDesc.location.fileName,
     testDesc.location.lineNumber,
     testDesc.location.columnNumber,
@@ -3,8 +3,11 @@ import { core, primordials } from "ext:core/mod.js";
 import { serializePermissions } from "ext:runtime/10_permissions.js";
 const ops = core.ops;
 const {
+  ArrayIsArray,
+  RegExpPrototypeTest,
   StringPrototypeReplaceAll,
   SafeArrayIterator,
+  TypeError,
 } = primordials;
 
 const ESCAPE_ASCII_CHARS = [
@@ -37,6 +40,27 @@ export function escapeName(name) {
   return name;
 }
 
+// Tags can't contain the operators of the `--tags` expressions.
+const TAG_REGEX = /^[\p{L}\p{N}\-_.:/@]+$/u;
+
+/**
+ * @param {unknown} tags
+ * @returns {string[]}
+ */
+export function validateTags(tags) {
+  if (!ArrayIsArray(tags)) {
+    throw new TypeError("The 'tags' option must be an array of strings");
+  }
+  for (const tag of new SafeArrayIterator(tags)) {
+    if (typeof tag !== "string" || !RegExpPrototypeTest(TAG_REGEX, tag)) {
+      throw new TypeError(
+        `Invalid tag '${tag}': tags can only contain letters, numbers, '-', '_', '.', ':', '/' and '@'`,
+      );
+    }
+  }
+  return tags;
+}
+
 export function pledgePermissions(permissions) {
   return ops.op_pledge_test_permissions(
     serializePermissions(permissions),
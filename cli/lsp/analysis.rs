@@ -8,8 +8,10 @@ use super::resolver::LspResolver;
 use super::tsc;
 use super::urls::url_to_uri;
 
+use crate::args::deno_json::LintRuleSeverity;
 use crate::args::jsr_url;
 use crate::tools::lint::CliLinter;
+use crate::tools::lint::LintRuleSeverities;
 use deno_lint::diagnostic::LintDiagnosticRange;
 
 use deno_ast::SourceRange;
@@ -23,6 +25,7 @@ use deno_core::serde::Serialize;
 use deno_core::serde_json;
 use deno_core::serde_json::json;
 use deno_core::ModuleSpecifier;
+use deno_package_json::PackageJsonDepValue;
 use deno_runtime::deno_node::PathClean;
 use deno_runtime::fs_util::specifier_to_file_path;
 use deno_semver::jsr::JsrPackageNvReference;
@@ -115,7 +118,12 @@ pub struct Reference {
 }
 
 impl Reference {
-  pub fn to_diagnostic(&self) -> lsp::Diagnostic {
+  /// Lint diagnostics are warnings, unless their rule is set to "error" in
+  /// the `"lintSeverity"` property of the config file.
+  pub fn to_diagnostic(
+    &self,
+    severities: &LintRuleSeverities,
+  ) -> lsp::Diagnostic {
     match &self.category {
       Category::Lint {
         message,
@@ -124,7 +132,12 @@ impl Reference {
         quick_fixes,
       } => lsp::Diagnostic {
         range: self.range,
-        severity: Some(lsp::DiagnosticSeverity::WARNING),
+        severity: Some(match severities.get_configured(code) {
+          Some(LintRuleSeverity::Error) => lsp::DiagnosticSeverity::ERROR,
+          Some(LintRuleSeverity::Warn) | None => {
+            lsp::DiagnosticSeverity::WARNING
+          }
+        }),
         code: Some(lsp::NumberOrString::String(code.to_string())),
         code_description: None,
         source: Some(DiagnosticSource::Lint.as_lsp_source().to_string()),
@@ -367,6 +380,16 @@ impl<'a> TsResponseImportMapper<'a> {
               }
             }
 
+            // check if any pkg reqs match a dependency in the package.json
+            if let Some(alias) =
+              self.find_package_json_dep_alias(&pkg_reqs, referrer)
+            {
+              return Some(match sub_path.as_deref() {
+                Some(path) => format!("{}/{}", alias, path),
+                None => alias,
+              });
+            }
+
             // if not found in the import map, return the first pkg req
             if let Some(pkg_req) = pkg_reqs.first() {
               return Some(concat_npm_specifier(
@@ -390,6 +413,29 @@ impl<'a> TsResponseImportMapper<'a> {
     None
   }
 
+  /// Finds the name a package is depended on by in the package.json closest
+  /// to the referrer, so it can be imported with a bare specifier.
+  fn find_package_json_dep_alias(
+    &self,
+    pkg_reqs: &[PackageReq],
+    referrer: &ModuleSpecifier,
+  ) -> Option<String> {
+    let package_json = self
+      .resolver
+      .get_closest_package_json(referrer)
+      .ok()
+      .flatten()?;
+    package_json
+      .resolve_local_package_json_deps()
+      .into_iter()
+      .find_map(|(alias, dep)| match dep {
+        Ok(PackageJsonDepValue::Req(req)) if pkg_reqs.contains(&req) => {
+          Some(alias)
+        }
+        _ => None,
+      })
+  }
+
   fn resolve_package_path(
     &self,
     specifier: &ModuleSpecifier,
@@ -710,6 +756,12 @@ pub struct CodeActionData {
   pub fix_id: String,
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganizeImportsCodeActionData {
+  pub specifier: ModuleSpecifier,
+}
+
 #[derive(Debug, Clone)]
 enum CodeActionKind {
   Deno(lsp::CodeAction),
@@ -1246,9 +1298,26 @@ mod tests {
     ];
 
     for (input, expected) in test_cases.iter() {
-      let actual = input.to_diagnostic();
+      let actual = input.to_diagnostic(&Default::default());
       assert_eq!(&actual, expected);
     }
+
+    // the severity of the rules listed in the config file is used
+    let severities = LintRuleSeverities(HashMap::from([
+      ("code1".to_string(), LintRuleSeverity::Error),
+      ("code2".to_string(), LintRuleSeverity::Warn),
+    ]));
+    let actual = test_cases
+      .iter()
+      .map(|(input, _)| input.to_diagnostic(&severities).severity)
+      .collect::<Vec<_>>();
+    assert_eq!(
+      actual,
+      vec![
+        Some(lsp::DiagnosticSeverity::ERROR),
+        Some(lsp::DiagnosticSeverity::WARNING)
+      ]
+    );
   }
 
   #[test]
@@ -20,8 +20,11 @@ fn code_action_capabilities(
     .and_then(|it| it.code_action.as_ref())
     .and_then(|it| it.code_action_literal_support.as_ref())
     .map(|_| {
-      let mut code_action_kinds =
-        vec![CodeActionKind::QUICKFIX, CodeActionKind::REFACTOR];
+      let mut code_action_kinds = vec![
+        CodeActionKind::QUICKFIX,
+        CodeActionKind::REFACTOR,
+        CodeActionKind::SOURCE_ORGANIZE_IMPORTS,
+      ];
       code_action_kinds.extend(
         ALL_KNOWN_REFACTOR_ACTION_KINDS
           .iter()
@@ -37,6 +40,37 @@ fn code_action_capabilities(
     .unwrap_or(CodeActionProviderCapability::Simple(true))
 }
 
+/// Pull diagnostics are only provided to clients which opt into them with the
+/// `pullDiagnostics` experimental capability, since the server stops
+/// publishing diagnostics for them.
+pub fn pull_diagnostics_negotiated(
+  client_capabilities: &ClientCapabilities,
+) -> bool {
+  let supports_pull = client_capabilities
+    .text_document
+    .as_ref()
+    .is_some_and(|it| it.diagnostic.is_some());
+  let opted_in = client_capabilities
+    .experimental
+    .as_ref()
+    .and_then(|it| it.get("pullDiagnostics")?.as_bool())
+    .unwrap_or(false);
+  supports_pull && opted_in
+}
+
+fn diagnostic_capabilities(
+  client_capabilities: &ClientCapabilities,
+) -> Option<DiagnosticServerCapabilities> {
+  pull_diagnostics_negotiated(client_capabilities).then(|| {
+    DiagnosticServerCapabilities::Options(DiagnosticOptions {
+      identifier: Some("deno".to_string()),
+      inter_file_dependencies: true,
+      workspace_diagnostics: true,
+      work_done_progress_options: Default::default(),
+    })
+  })
+}
+
 pub fn server_capabilities(
   client_capabilities: &ClientCapabilities,
 ) -> ServerCapabilities {
@@ -131,7 +165,7 @@ pub fn server_capabilities(
         SemanticTokensOptions {
           legend: get_legend(),
           range: Some(true),
-          full: Some(SemanticTokensFullOptions::Bool(true)),
+          full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
           ..Default::default()
         },
       ),
@@ -143,7 +177,9 @@ pub fn server_capabilities(
       }),
       file_operations: None,
     }),
-    linked_editing_range_provider: None,
+    linked_editing_range_provider: Some(
+      LinkedEditingRangeServerCapabilities::Simple(true),
+    ),
     moniker_provider: None,
     experimental: Some(json!({
       "denoConfigTasks": true,
@@ -151,8 +187,7 @@ pub fn server_capabilities(
     })),
     inlay_hint_provider: Some(OneOf::Left(true)),
     position_encoding: None,
-    // TODO(nayeemrmn): Support pull-based diagnostics.
-    diagnostic_provider: None,
+    diagnostic_provider: diagnostic_capabilities(client_capabilities),
     inline_value_provider: None,
     inline_completion_provider: None,
     notebook_document_sync: None,
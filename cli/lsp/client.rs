@@ -12,6 +12,7 @@ use lsp_types::Uri;
 use tower_lsp::lsp_types as lsp;
 use tower_lsp::lsp_types::ConfigurationItem;
 
+use crate::lsp::logging::lsp_warn;
 use crate::lsp::repl::get_repl_workspace_settings;
 
 use super::config::WorkspaceSettings;
@@ -116,6 +117,17 @@ impl Client {
     });
   }
 
+  /// Asks the client to pull the diagnostics of all documents again.
+  pub fn send_diagnostic_refresh_request(&self) {
+    // do on a task in case the caller currently is in the lsp lock
+    let client = self.0.clone();
+    spawn(async move {
+      if let Err(err) = client.workspace_diagnostic_refresh().await {
+        lsp_warn!("Failed to refresh diagnostics: {:#}", err);
+      }
+    });
+  }
+
   pub fn show_message(
     &self,
     message_type: lsp::MessageType,
@@ -150,6 +162,21 @@ impl OutsideLockClient {
   ) -> Result<Vec<WorkspaceSettings>, AnyError> {
     self.0.workspace_configuration(scopes).await
   }
+
+  pub async fn create_work_done_progress(
+    &self,
+    token: lsp::ProgressToken,
+  ) -> Result<(), AnyError> {
+    self.0.create_work_done_progress(token).await
+  }
+
+  pub async fn send_progress(
+    &self,
+    token: lsp::ProgressToken,
+    progress: lsp::WorkDoneProgress,
+  ) {
+    self.0.send_progress(token, progress).await
+  }
 }
 
 #[async_trait]
@@ -181,11 +208,21 @@ trait ClientTrait: Send + Sync {
     &self,
     scopes: Vec<Option<lsp::Uri>>,
   ) -> Result<Vec<WorkspaceSettings>, AnyError>;
+  async fn workspace_diagnostic_refresh(&self) -> Result<(), AnyError>;
   async fn show_message(&self, message_type: lsp::MessageType, text: String);
   async fn register_capability(
     &self,
     registrations: Vec<lsp::Registration>,
   ) -> Result<(), AnyError>;
+  async fn create_work_done_progress(
+    &self,
+    token: lsp::ProgressToken,
+  ) -> Result<(), AnyError>;
+  async fn send_progress(
+    &self,
+    token: lsp::ProgressToken,
+    progress: lsp::WorkDoneProgress,
+  );
 }
 
 #[derive(Clone)]
@@ -319,6 +356,14 @@ impl ClientTrait for TowerClient {
     }
   }
 
+  async fn workspace_diagnostic_refresh(&self) -> Result<(), AnyError> {
+    self
+      .0
+      .workspace_diagnostic_refresh()
+      .await
+      .map_err(|err| anyhow!("{}", err))
+  }
+
   async fn show_message(
     &self,
     message_type: lsp::MessageType,
@@ -337,6 +382,33 @@ impl ClientTrait for TowerClient {
       .await
       .map_err(|err| anyhow!("{}", err))
   }
+
+  async fn create_work_done_progress(
+    &self,
+    token: lsp::ProgressToken,
+  ) -> Result<(), AnyError> {
+    self
+      .0
+      .send_request::<lsp::request::WorkDoneProgressCreate>(
+        lsp::WorkDoneProgressCreateParams { token },
+      )
+      .await
+      .map_err(|err| anyhow!("{}", err))
+  }
+
+  async fn send_progress(
+    &self,
+    token: lsp::ProgressToken,
+    progress: lsp::WorkDoneProgress,
+  ) {
+    self
+      .0
+      .send_notification::<lsp::notification::Progress>(lsp::ProgressParams {
+        token,
+        value: lsp::ProgressParamsValue::WorkDone(progress),
+      })
+      .await
+  }
 }
 
 #[derive(Clone)]
@@ -385,6 +457,10 @@ impl ClientTrait for ReplClient {
     Ok(vec![get_repl_workspace_settings(); scopes.len()])
   }
 
+  async fn workspace_diagnostic_refresh(&self) -> Result<(), AnyError> {
+    Ok(())
+  }
+
   async fn show_message(
     &self,
     _message_type: lsp::MessageType,
@@ -398,4 +474,18 @@ impl ClientTrait for ReplClient {
   ) -> Result<(), AnyError> {
     Ok(())
   }
+
+  async fn create_work_done_progress(
+    &self,
+    _token: lsp::ProgressToken,
+  ) -> Result<(), AnyError> {
+    Ok(())
+  }
+
+  async fn send_progress(
+    &self,
+    _token: lsp::ProgressToken,
+    _progress: lsp::WorkDoneProgress,
+  ) {
+  }
 }
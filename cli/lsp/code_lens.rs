@@ -84,6 +84,7 @@ impl DenoTestCollector {
   ) {
     let options = json!({
       "inspect": inspect,
+      "filter": exact_test_filter(name.as_ref()),
     });
     self.code_lenses.push(lsp::CodeLens {
       range,
@@ -153,6 +154,12 @@ impl DenoTestCollector {
   }
 }
 
+/// A `deno test --filter` value which only matches the test with exactly this
+/// name, where the name itself would be matched as a substring.
+fn exact_test_filter(name: &str) -> String {
+  format!("/^{}$/", regex::escape(name))
+}
+
 impl Visit for DenoTestCollector {
   fn visit_call_expr(&mut self, node: &ast::CallExpr) {
     if let ast::Callee::Expr(callee_expr) = &node.callee {
@@ -478,7 +485,10 @@ pub fn collect_tsc(
       }
       match i.kind {
         tsc::ScriptElementKind::FunctionElement => {
-          if code_lens_settings.references_all_functions {
+          if code_lens_settings.references_all_functions
+            || (code_lens_settings.references_exported_functions
+              && EXPORT_MODIFIER.is_match(&i.kind_modifiers))
+          {
             code_lenses.push(i.to_code_lens(
               line_index.clone(),
               specifier,
@@ -551,6 +561,12 @@ mod tests {
 
   use super::*;
 
+  #[test]
+  fn test_exact_test_filter() {
+    assert_eq!(exact_test_filter("test a"), "/^test a$/");
+    assert_eq!(exact_test_filter("a (b) [c]"), r"/^a \(b\) \[c\]$/");
+  }
+
   #[test]
   fn test_deno_test_collector() {
     let specifier = resolve_url("https://deno.land/x/mod.ts").unwrap();
@@ -604,6 +620,7 @@ mod tests {
               json!("test a"),
               json!({
                 "inspect": false,
+                "filter": "/^test a$/",
               }),
             ])
           }),
@@ -628,6 +645,7 @@ mod tests {
               json!("test a"),
               json!({
                 "inspect": true,
+                "filter": "/^test a$/",
               }),
             ])
           }),
@@ -652,6 +670,7 @@ mod tests {
               json!("useFnName"),
               json!({
                 "inspect": false,
+                "filter": "/^useFnName$/",
               }),
             ])
           }),
@@ -676,6 +695,7 @@ mod tests {
               json!("useFnName"),
               json!({
                 "inspect": true,
+                "filter": "/^useFnName$/",
               }),
             ])
           }),
@@ -700,6 +720,7 @@ mod tests {
               json!("test b"),
               json!({
                 "inspect": false,
+                "filter": "/^test b$/",
               }),
             ])
           }),
@@ -724,6 +745,7 @@ mod tests {
               json!("test b"),
               json!({
                 "inspect": true,
+                "filter": "/^test b$/",
               }),
             ])
           }),
@@ -748,6 +770,7 @@ mod tests {
               json!("test ignore"),
               json!({
                 "inspect": false,
+                "filter": "/^test ignore$/",
               }),
             ]),
           }),
@@ -772,6 +795,7 @@ mod tests {
               json!("test ignore"),
               json!({
                 "inspect": true,
+                "filter": "/^test ignore$/",
               }),
             ]),
           }),
@@ -796,6 +820,7 @@ mod tests {
               json!("test only"),
               json!({
                 "inspect": false,
+                "filter": "/^test only$/",
               }),
             ]),
           }),
@@ -820,6 +845,7 @@ mod tests {
               json!("test only"),
               json!({
                 "inspect": true,
+                "filter": "/^test only$/",
               }),
             ]),
           }),
@@ -844,6 +870,7 @@ mod tests {
               json!("test template literal name"),
               json!({
                 "inspect": false,
+                "filter": "/^test template literal name$/",
               }),
             ]),
           }),
@@ -868,6 +895,7 @@ mod tests {
               json!("test template literal name"),
               json!({
                 "inspect": true,
+                "filter": "/^test template literal name$/",
               }),
             ]),
           }),
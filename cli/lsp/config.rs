@@ -50,19 +50,30 @@ use std::sync::Arc;
 use tower_lsp::lsp_types as lsp;
 
 use super::logging::lsp_log;
+use crate::args::deno_json::read_fmt_sort_imports_config;
+use crate::args::deno_json::read_import_maps_config;
+use crate::args::deno_json::read_tsconfig_paths_config;
+use crate::args::deno_json::without_resolver_compiler_options;
+use crate::args::deno_json::CliDenoConfigFs;
 use crate::args::discover_npmrc_from_workspace;
 use crate::args::has_flag_env_var;
+use crate::args::import_map::resolve_composed_import_map;
 use crate::args::CliLockfile;
 use crate::args::ConfigFile;
 use crate::args::LintFlags;
 use crate::args::LintOptions;
+use crate::args::NpmRcFetchOptions;
 use crate::cache::FastInsecureHasher;
 use crate::file_fetcher::FileFetcher;
 use crate::lsp::logging::lsp_warn;
+use crate::lsp::plugins::LspPlugins;
 use crate::resolver::SloppyImportsResolver;
+use crate::resolver::TsConfigPathsResolver;
 use crate::tools::lint::CliLinter;
 use crate::tools::lint::CliLinterOptions;
+use crate::tools::lint::LintPlugins;
 use crate::tools::lint::LintRuleProvider;
+use crate::tools::lint::LintRuleSeverities;
 use crate::util::fs::canonicalize_path_maybe_not_exists;
 
 pub const SETTINGS_SECTION: &str = "deno";
@@ -132,6 +143,10 @@ pub struct CodeLensSettings {
   /// an impact, the `references` flag needs to be `true`.
   #[serde(default)]
   pub references_all_functions: bool,
+  /// Flag for providing reference code lens on exported functions.  For this
+  /// to have an impact, the `references` flag needs to be `true`.
+  #[serde(default = "is_true")]
+  pub references_exported_functions: bool,
   /// Flag for providing test code lens on `Deno.test` statements.  There is
   /// also the `test_args` setting, but this is not used by the server.
   #[serde(default = "is_true")]
@@ -144,6 +159,7 @@ impl Default for CodeLensSettings {
       implementations: false,
       references: false,
       references_all_functions: false,
+      references_exported_functions: true,
       test: true,
     }
   }
@@ -850,14 +866,16 @@ impl Settings {
     let Ok(path) = specifier_to_file_path(specifier) else {
       return (&self.unscoped, self.first_folder.as_ref());
     };
+    // Each workspace folder is configured independently. Folders which the
+    // client didn't provide settings for use the unscoped settings, but paths
+    // in those are still relative to the folder the specifier is in.
     for (folder_uri, settings) in self.by_workspace_folder.iter().rev() {
-      if let Some(settings) = settings {
-        let Ok(folder_path) = specifier_to_file_path(folder_uri) else {
-          continue;
-        };
-        if path.starts_with(folder_path) {
-          return (settings, Some(folder_uri));
-        }
+      let Ok(folder_path) = specifier_to_file_path(folder_uri) else {
+        continue;
+      };
+      if path.starts_with(folder_path) {
+        let settings = settings.as_deref().unwrap_or(&self.unscoped);
+        return (settings, Some(folder_uri));
       }
     }
     (&self.unscoped, self.first_folder.as_ref())
@@ -1052,6 +1070,11 @@ impl Config {
     self.client_capabilities.workspace.is_some()
   }
 
+  pub fn work_done_progress_capable(&self) -> bool {
+    (|| self.client_capabilities.window.as_ref()?.work_done_progress)()
+      .unwrap_or(false)
+  }
+
   pub fn workspace_configuration_capable(&self) -> bool {
     (|| self.client_capabilities.workspace.as_ref()?.configuration)()
       .unwrap_or(false)
@@ -1077,6 +1100,26 @@ impl Config {
     .unwrap_or(false)
   }
 
+  pub fn type_hierarchy_capable(&self) -> bool {
+    (|| {
+      let text_document = self.client_capabilities.text_document.as_ref()?;
+      text_document.type_hierarchy.as_ref()?.dynamic_registration
+    })()
+    .unwrap_or(false)
+  }
+
+  pub fn pull_diagnostics_capable(&self) -> bool {
+    super::capabilities::pull_diagnostics_negotiated(&self.client_capabilities)
+  }
+
+  pub fn diagnostic_refresh_capable(&self) -> bool {
+    (|| {
+      let workspace = self.client_capabilities.workspace.as_ref()?;
+      workspace.diagnostic.as_ref()?.refresh_support
+    })()
+    .unwrap_or(false)
+  }
+
   pub fn line_folding_only_capable(&self) -> bool {
     (|| {
       let text_document = self.client_capabilities.text_document.as_ref()?;
@@ -1102,6 +1145,24 @@ impl Config {
     .unwrap_or(false)
   }
 
+  /// Whether the client implements the `deno.client.rename` command, which
+  /// starts renaming the symbol at a position. Clients list the commands they
+  /// implement in the `commands.commands` experimental capability, which is
+  /// the shape that rust-analyzer uses for the same purpose.
+  pub fn rename_command_capable(&self) -> bool {
+    (|| {
+      let experimental = self.client_capabilities.experimental.as_ref()?;
+      let commands = experimental.get("commands")?.get("commands")?;
+      Some(
+        commands
+          .as_array()?
+          .iter()
+          .any(|command| command == "deno.client.rename"),
+      )
+    })()
+    .unwrap_or(false)
+  }
+
   pub fn testing_api_capable(&self) -> bool {
     (|| {
       let experimental = self.client_capabilities.experimental.as_ref()?;
@@ -1146,7 +1207,13 @@ impl LspTsConfig {
   pub fn new(config_file: Option<&ConfigFile>) -> Self {
     let mut ts_config = Self::default();
     match ts_config.inner.merge_tsconfig_from_config_file(config_file) {
-      Ok(Some(ignored_options)) => lsp_warn!("{}", ignored_options),
+      Ok(Some(ignored_options)) => {
+        if let Some(ignored_options) =
+          without_resolver_compiler_options(&ignored_options)
+        {
+          lsp_warn!("{}", ignored_options)
+        }
+      }
       Err(err) => lsp_warn!("{}", err),
       _ => {}
     }
@@ -1170,18 +1237,23 @@ pub struct ConfigData {
   pub canonicalized_scope: Option<Arc<ModuleSpecifier>>,
   pub member_dir: Arc<WorkspaceDirectory>,
   pub fmt_config: Arc<FmtConfig>,
+  pub fmt_sort_imports: bool,
   pub lint_config: Arc<LintConfig>,
   pub test_config: Arc<TestConfig>,
   pub exclude_files: Arc<PathOrPatternSet>,
   pub linter: Arc<CliLinter>,
+  pub lint_severities: Arc<LintRuleSeverities>,
+  pub lsp_plugins: Option<Arc<LspPlugins>>,
   pub ts_config: Arc<LspTsConfig>,
   pub byonm: bool,
   pub node_modules_dir: Option<PathBuf>,
   pub vendor_dir: Option<PathBuf>,
   pub lockfile: Option<Arc<CliLockfile>>,
   pub npmrc: Option<Arc<ResolvedNpmRc>>,
+  pub npmrc_fetch_options: NpmRcFetchOptions,
   pub resolver: Arc<WorkspaceResolver>,
   pub sloppy_imports_resolver: Option<Arc<SloppyImportsResolver>>,
+  pub tsconfig_paths_resolver: Option<Arc<TsConfigPathsResolver>>,
   pub import_map_from_settings: Option<ModuleSpecifier>,
   watched_files: HashMap<ModuleSpecifier, ConfigWatchedFileType>,
 }
@@ -1338,21 +1410,22 @@ impl ConfigData {
     }
 
     // todo(dsherret): cache this so we don't load this so many times
-    let npmrc = discover_npmrc_from_workspace(&member_dir.workspace)
-      .inspect(|(_, path)| {
-        if let Some(path) = path {
-          lsp_log!("  Resolved .npmrc: \"{}\"", path.display());
-
-          if let Ok(specifier) = ModuleSpecifier::from_file_path(path) {
-            add_watched_file(specifier, ConfigWatchedFileType::NpmRc);
+    let (npmrc, npmrc_fetch_options) =
+      discover_npmrc_from_workspace(&member_dir.workspace)
+        .inspect(|(_, _, paths)| {
+          for path in paths {
+            lsp_log!("  Resolved .npmrc: \"{}\"", path.display());
+
+            if let Ok(specifier) = ModuleSpecifier::from_file_path(path) {
+              add_watched_file(specifier, ConfigWatchedFileType::NpmRc);
+            }
           }
-        }
-      })
-      .inspect_err(|err| {
-        lsp_warn!("  Couldn't read .npmrc for \"{scope}\": {err}");
-      })
-      .map(|(r, _)| r)
-      .ok();
+        })
+        .inspect_err(|err| {
+          lsp_warn!("  Couldn't read .npmrc for \"{scope}\": {err}");
+        })
+        .map(|(r, o, _)| (Some(r), o))
+        .unwrap_or_default();
     let default_file_pattern_base =
       scope.to_file_path().unwrap_or_else(|_| PathBuf::from("/"));
     let fmt_config = Arc::new(
@@ -1366,6 +1439,13 @@ impl ConfigData {
           FmtConfig::new_with_base(default_file_pattern_base.clone())
         }),
     );
+    let fmt_sort_imports = read_fmt_sort_imports_config(&member_dir)
+      .inspect_err(|err| {
+        lsp_warn!("  Couldn't read formatter configuration: {:#}", err)
+      })
+      .ok()
+      .flatten()
+      .unwrap_or(false);
     let lint_config = Arc::new(
       member_dir
         .to_lint_config(FilePatterns::new_with_base(member_dir.dir_path()))
@@ -1378,6 +1458,14 @@ impl ConfigData {
         }),
     );
 
+    let lint_severities = Arc::new(
+      LintRuleSeverities::resolve(&member_dir)
+        .inspect_err(|err| {
+          lsp_warn!("  Couldn't read lint severity configuration: {:#}", err)
+        })
+        .unwrap_or_default(),
+    );
+
     let test_config = Arc::new(
       member_dir
         .to_test_config(FilePatterns::new_with_base(member_dir.dir_path()))
@@ -1538,6 +1626,30 @@ impl ConfigData {
             None
           }
         }
+      } else if let (Some(config_file), Some(file_fetcher)) =
+        (member_dir.workspace.root_deno_json(), file_fetcher)
+      {
+        for import_map_url in
+          read_import_maps_config(config_file).unwrap_or_default()
+        {
+          add_watched_file(import_map_url, ConfigWatchedFileType::ImportMap);
+        }
+        // spawn due to the lsp's `Send` requirement
+        let result = deno_core::unsync::spawn({
+          let config_file = config_file.clone();
+          let file_fetcher = file_fetcher.clone();
+          async move {
+            resolve_composed_import_map(&config_file, &file_fetcher).await
+          }
+        })
+        .await
+        .unwrap();
+        result
+          .inspect_err(|err| {
+            lsp_warn!("  Couldn't compose the import maps: {:#}", err);
+          })
+          .ok()
+          .flatten()
       } else {
         None
       }
@@ -1605,11 +1717,68 @@ impl ConfigData {
         deno_runtime::deno_fs::RealFs,
       )))
     });
+    let tsconfig_paths_config = member_dir
+      .maybe_deno_json()
+      .or_else(|| member_dir.workspace.root_deno_json())
+      .and_then(|config_file| {
+        read_tsconfig_paths_config(config_file)
+          .inspect_err(|err| {
+            lsp_warn!("  Couldn't read \"paths\" compiler option: {}", err)
+          })
+          .ok()
+          .flatten()
+      });
+    let tsconfig_paths_resolver = tsconfig_paths_config.map(|config| {
+      Arc::new(TsConfigPathsResolver::new_without_stat_cache(
+        config,
+        Arc::new(deno_runtime::deno_fs::RealFs),
+      ))
+    });
     let resolver = Arc::new(resolver);
     let lint_rule_provider = LintRuleProvider::new(
       sloppy_imports_resolver.clone(),
       Some(resolver.clone()),
     );
+    let lint_plugins = if let Some(file_fetcher) = file_fetcher {
+      // spawn due to the lsp's `Send` requirement
+      deno_core::unsync::spawn({
+        let config_file = member_dir.workspace.root_deno_json().cloned();
+        let file_fetcher = file_fetcher.clone();
+        async move {
+          LintPlugins::resolve(config_file.as_deref(), &file_fetcher).await
+        }
+      })
+      .await
+      .map_err(AnyError::from)
+      .and_then(|result| result)
+      .inspect_err(|err| {
+        lsp_warn!("  Failed to load lint plugins: {:#}", err);
+      })
+      .ok()
+      .flatten()
+    } else {
+      None
+    };
+    let lsp_plugins = if let Some(file_fetcher) = file_fetcher {
+      // spawn due to the lsp's `Send` requirement
+      deno_core::unsync::spawn({
+        let config_file = member_dir.workspace.root_deno_json().cloned();
+        let file_fetcher = file_fetcher.clone();
+        async move {
+          LspPlugins::resolve(config_file.as_deref(), &file_fetcher).await
+        }
+      })
+      .await
+      .map_err(AnyError::from)
+      .and_then(|result| result)
+      .inspect_err(|err| {
+        lsp_warn!("  Failed to load language server plugins: {:#}", err);
+      })
+      .ok()
+      .flatten()
+    } else {
+      None
+    };
     let linter = Arc::new(CliLinter::new(CliLinterOptions {
       configured_rules: lint_rule_provider.resolve_lint_rules(
         LintOptions::resolve((*lint_config).clone(), &LintFlags::default())
@@ -1617,7 +1786,9 @@ impl ConfigData {
         member_dir.maybe_deno_json().map(|c| c.as_ref()),
       ),
       fix: false,
+      fix_dry_run: false,
       deno_lint_config,
+      plugins: lint_plugins,
     }));
 
     ConfigData {
@@ -1626,10 +1797,14 @@ impl ConfigData {
       member_dir,
       resolver,
       sloppy_imports_resolver,
+      tsconfig_paths_resolver,
       fmt_config,
+      fmt_sort_imports,
       lint_config,
       test_config,
       linter,
+      lint_severities,
+      lsp_plugins,
       exclude_files,
       ts_config: Arc::new(ts_config),
       byonm,
@@ -1637,6 +1812,7 @@ impl ConfigData {
       vendor_dir,
       lockfile,
       npmrc,
+      npmrc_fetch_options,
       import_map_from_settings,
       watched_files,
     }
@@ -1742,6 +1918,23 @@ impl ConfigTree {
     None
   }
 
+  /// The deno.json and import map files which modules of the scopes may be
+  /// resolved through.
+  pub fn import_map_files(&self) -> IndexSet<&ModuleSpecifier> {
+    self
+      .scopes
+      .values()
+      .flat_map(|data| data.watched_files.iter())
+      .filter(|(_, typ)| {
+        matches!(
+          typ,
+          ConfigWatchedFileType::DenoJson | ConfigWatchedFileType::ImportMap
+        )
+      })
+      .map(|(specifier, _)| specifier)
+      .collect()
+  }
+
   pub fn is_watched_file(&self, specifier: &ModuleSpecifier) -> bool {
     let path = specifier.path();
     if path.ends_with("/deno.json")
@@ -1760,6 +1953,39 @@ impl ConfigTree {
       .any(|data| data.watched_files.contains_key(specifier))
   }
 
+  /// The directories containing the modules which may resolve differently
+  /// after the given watched files changed.
+  pub fn watched_file_change_dirs<'a>(
+    &self,
+    specifiers: impl IntoIterator<Item = &'a ModuleSpecifier>,
+  ) -> Vec<ModuleSpecifier> {
+    specifiers
+      .into_iter()
+      .filter(|s| self.is_watched_file(s))
+      .flat_map(|specifier| {
+        let dir = match specifier.as_str().find("/node_modules/") {
+          Some(index) => {
+            ModuleSpecifier::parse(&specifier.as_str()[..index + 1]).ok()
+          }
+          None => specifier.join(".").ok(),
+        };
+        // workspace members can resolve each other's exports
+        let scope_dirs = self
+          .scopes
+          .values()
+          .filter(|data| data.watched_files.contains_key(specifier))
+          .flat_map(|data| {
+            [
+              data.scope.as_ref().clone(),
+              data.member_dir.workspace.root_dir().as_ref().clone(),
+            ]
+          })
+          .collect::<Vec<_>>();
+        dir.into_iter().chain(scope_dirs)
+      })
+      .collect()
+  }
+
   pub async fn refresh(
     &mut self,
     settings: &Settings,
@@ -2060,7 +2286,7 @@ impl DenoConfigFs for CachedDenoConfigFs {
       .0
       .lock()
       .read_to_string_calls
-      .get(path, |path| RealDenoConfigFs.read_to_string_lossy(path))
+      .get(path, |path| CliDenoConfigFs.read_to_string_lossy(path))
   }
 
   fn read_dir(
@@ -2150,6 +2376,32 @@ mod tests {
     assert!(!config.specifier_enabled(&root_uri.join("mod3.ts").unwrap()));
   }
 
+  #[test]
+  fn test_config_specifier_enabled_multi_root() {
+    let root_a = resolve_url("file:///a/").unwrap();
+    let root_b = resolve_url("file:///b/").unwrap();
+    let root_c = resolve_url("file:///c/").unwrap();
+    let mut config =
+      Config::new_with_roots(vec![root_a.clone(), root_b.clone(), root_c]);
+    config.set_workspace_settings(
+      serde_json::from_value(json!({ "enablePaths": ["src"] })).unwrap(),
+      vec![(
+        root_b.clone(),
+        serde_json::from_value(json!({ "enable": true })).unwrap(),
+      )],
+    );
+    assert!(config.specifier_enabled(&root_a.join("src/mod.ts").unwrap()));
+    assert!(!config.specifier_enabled(&root_a.join("mod.ts").unwrap()));
+    assert!(config.specifier_enabled(&root_b.join("mod.ts").unwrap()));
+    // the unscoped `enablePaths` are relative to each folder
+    assert!(
+      config.specifier_enabled(&resolve_url("file:///c/src/mod.ts").unwrap())
+    );
+    assert!(
+      !config.specifier_enabled(&resolve_url("file:///c/mod.ts").unwrap())
+    );
+  }
+
   #[test]
   fn test_set_workspace_settings_defaults() {
     let mut config = Config::default();
@@ -2172,6 +2424,7 @@ mod tests {
           implementations: false,
           references: false,
           references_all_functions: false,
+          references_exported_functions: true,
           test: true,
         },
         internal_debug: false,
@@ -2357,6 +2610,32 @@ mod tests {
     assert!(config.specifier_enabled(&root_uri));
   }
 
+  #[tokio::test]
+  async fn config_watched_file_change_dirs() {
+    let root_uri = root_dir();
+    let mut config = Config::new_with_roots(vec![root_uri.clone()]);
+    config
+      .tree
+      .inject_config_file(
+        ConfigFile::new(
+          "{}",
+          root_uri.join("deno.json").unwrap(),
+          &ConfigParseOptions::default(),
+        )
+        .unwrap(),
+      )
+      .await;
+
+    let deno_json = root_uri.join("deno.json").unwrap();
+    let dirs = config.tree.watched_file_change_dirs([&deno_json]);
+    assert!(!dirs.is_empty());
+    assert!(dirs.iter().all(|dir| dir == &root_uri));
+
+    // files which aren't watched don't affect any module
+    let module = root_uri.join("mod.ts").unwrap();
+    assert!(config.tree.watched_file_change_dirs([&module]).is_empty());
+  }
+
   // Regression test for https://github.com/denoland/vscode_deno/issues/917.
   #[test]
   fn config_specifier_enabled_matches_by_path_component() {
@@ -0,0 +1,846 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Completions, hovers and diagnostics for deno.json(c) and import map files,
+//! based on their JSON schemas.
+
+use std::ops::Range;
+
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_core::serde_json::Value;
+use deno_core::url::Url;
+use deno_core::ModuleSpecifier;
+use jsonc_parser::ast;
+use jsonc_parser::common::Ranged;
+use jsonc_parser::tokens::Token;
+use jsonc_parser::Scanner;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use text_size::TextSize;
+use tower_lsp::lsp_types as lsp;
+
+use super::config::ConfigWatchedFileType;
+use super::text::LineIndex;
+use crate::tools::lint::LintRuleProvider;
+
+static CONFIG_FILE_SCHEMA: Lazy<Value> = Lazy::new(|| {
+  serde_json::from_str(include_str!("../schemas/config-file.v1.json")).unwrap()
+});
+
+static IMPORT_MAP_SCHEMA: Lazy<Value> = Lazy::new(|| {
+  let properties = &CONFIG_FILE_SCHEMA["properties"];
+  json!({
+    "type": "object",
+    "properties": {
+      "imports": properties["imports"],
+      "scopes": properties["scopes"],
+    },
+  })
+});
+
+fn schema_for_file_type(
+  file_type: ConfigWatchedFileType,
+) -> Option<&'static Value> {
+  match file_type {
+    ConfigWatchedFileType::DenoJson => Some(&CONFIG_FILE_SCHEMA),
+    ConfigWatchedFileType::ImportMap => Some(&IMPORT_MAP_SCHEMA),
+    ConfigWatchedFileType::Lockfile
+    | ConfigWatchedFileType::NpmRc
+    | ConfigWatchedFileType::PackageJson => None,
+  }
+}
+
+/// A segment of the path from the root value of a JSON document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum JsonPathSegment {
+  Key(String),
+  Item,
+}
+
+impl JsonPathSegment {
+  fn is_key(&self, key: &str) -> bool {
+    matches!(self, Self::Key(k) if k == key)
+  }
+}
+
+#[derive(Debug)]
+enum JsonFrame {
+  Object {
+    key: Option<String>,
+    after_colon: bool,
+    keys: Vec<String>,
+  },
+  Array,
+}
+
+/// Where a position is in a JSON document. The path leads to the object for
+/// property names and to the value itself otherwise.
+#[derive(Debug)]
+struct JsonPosition {
+  path: Vec<JsonPathSegment>,
+  is_key: bool,
+  /// The string literal the position is in and its byte range, including the
+  /// quotes.
+  string: Option<(String, Range<usize>)>,
+  /// The names of the properties preceding the position in its object.
+  sibling_keys: Vec<String>,
+}
+
+/// Finds where the byte `offset` is in the JSON text. This only scans the
+/// text up to the offset, so that it works while the document is incomplete.
+fn find_json_position(text: &str, offset: usize) -> Option<JsonPosition> {
+  let mut scanner = Scanner::new(text);
+  let mut frames = Vec::new();
+  let mut string = None;
+  loop {
+    let token = match scanner.scan() {
+      Ok(Some(token)) => token,
+      Ok(None) => break,
+      Err(_) => {
+        // most likely a string which is still being typed
+        let start = scanner.token_start();
+        let prefix = text.get(start..offset).unwrap_or_default();
+        if start < offset && prefix.starts_with('"') && !prefix.contains('\n') {
+          string = Some((prefix[1..].to_string(), start..offset));
+        }
+        break;
+      }
+    };
+    let range = scanner.token_start()..scanner.token_end();
+    if range.start >= offset {
+      break;
+    }
+    match token {
+      Token::String(value) if offset < range.end => {
+        let literal = &text[range.clone()];
+        string = match literal.find('\n') {
+          // an unterminated string followed by other lines
+          Some(index) => {
+            let end = range.start + index;
+            if offset > end {
+              return None;
+            }
+            Some((text[range.start + 1..end].to_string(), range.start..end))
+          }
+          None => Some((value.to_string(), range)),
+        };
+        break;
+      }
+      Token::CommentLine(_) | Token::CommentBlock(_) => {
+        if offset <= range.end {
+          return None;
+        }
+      }
+      Token::OpenBrace => frames.push(JsonFrame::Object {
+        key: None,
+        after_colon: false,
+        keys: Vec::new(),
+      }),
+      Token::OpenBracket => frames.push(JsonFrame::Array),
+      Token::CloseBrace | Token::CloseBracket => {
+        frames.pop();
+      }
+      Token::Colon => {
+        if let Some(JsonFrame::Object { after_colon, .. }) = frames.last_mut() {
+          *after_colon = true;
+        }
+      }
+      Token::Comma => {
+        if let Some(JsonFrame::Object {
+          key, after_colon, ..
+        }) = frames.last_mut()
+        {
+          *key = None;
+          *after_colon = false;
+        }
+      }
+      Token::String(value) => {
+        if let Some(JsonFrame::Object {
+          key,
+          after_colon: false,
+          keys,
+        }) = frames.last_mut()
+        {
+          *key = Some(value.to_string());
+          keys.push(value.to_string());
+        }
+      }
+      Token::Word(_) | Token::Boolean(_) | Token::Number(_) | Token::Null => {}
+    }
+  }
+
+  let (last, rest) = frames.split_last()?;
+  let mut path = Vec::with_capacity(frames.len());
+  for frame in rest {
+    match frame {
+      JsonFrame::Object {
+        key: Some(key),
+        after_colon: true,
+        ..
+      } => path.push(JsonPathSegment::Key(key.clone())),
+      JsonFrame::Array => path.push(JsonPathSegment::Item),
+      JsonFrame::Object { .. } => return None,
+    }
+  }
+  let mut sibling_keys = Vec::new();
+  let is_key = match last {
+    JsonFrame::Object {
+      after_colon: false,
+      keys,
+      ..
+    } => {
+      sibling_keys.clone_from(keys);
+      true
+    }
+    JsonFrame::Object {
+      key: Some(key),
+      after_colon: true,
+      ..
+    } => {
+      path.push(JsonPathSegment::Key(key.clone()));
+      false
+    }
+    JsonFrame::Object { key: None, .. } => return None,
+    JsonFrame::Array => {
+      path.push(JsonPathSegment::Item);
+      false
+    }
+  };
+  Some(JsonPosition {
+    path,
+    is_key,
+    string,
+    sibling_keys,
+  })
+}
+
+/// Pushes the schema, or its alternatives if it has any.
+fn expand_schema<'a>(schema: &'a Value, schemas: &mut Vec<&'a Value>) {
+  let alternatives = schema.get("oneOf").or_else(|| schema.get("anyOf"));
+  match alternatives.and_then(|a| a.as_array()) {
+    Some(alternatives) => {
+      for alternative in alternatives {
+        expand_schema(alternative, schemas);
+      }
+    }
+    None => schemas.push(schema),
+  }
+}
+
+fn property_schema<'a>(schema: &'a Value, name: &str) -> Option<&'a Value> {
+  if let Some(property) = schema.get("properties").and_then(|p| p.get(name)) {
+    return Some(property);
+  }
+  if let Some(patterns) =
+    schema.get("patternProperties").and_then(|p| p.as_object())
+  {
+    for (pattern, property) in patterns {
+      if Regex::new(pattern).is_ok_and(|r| r.is_match(name)) {
+        return Some(property);
+      }
+    }
+  }
+  schema.get("additionalProperties").filter(|a| a.is_object())
+}
+
+fn schemas_at_path<'a>(
+  schema: &'a Value,
+  path: &[JsonPathSegment],
+) -> Vec<&'a Value> {
+  let mut schemas = Vec::new();
+  expand_schema(schema, &mut schemas);
+  for segment in path {
+    let mut next = Vec::new();
+    for schema in schemas {
+      let child = match segment {
+        JsonPathSegment::Key(key) => property_schema(schema, key),
+        JsonPathSegment::Item => schema.get("items"),
+      };
+      if let Some(child) = child {
+        expand_schema(child, &mut next);
+      }
+    }
+    schemas = next;
+  }
+  schemas
+}
+
+fn schema_description(schema: &Value) -> Option<&str> {
+  schema
+    .get("markdownDescription")
+    .or_else(|| schema.get("description"))
+    .and_then(|d| d.as_str())
+}
+
+fn schema_types(schema: &Value) -> Vec<&str> {
+  match schema.get("type") {
+    Some(Value::String(typ)) => vec![typ.as_str()],
+    Some(Value::Array(types)) => {
+      types.iter().filter_map(|t| t.as_str()).collect()
+    }
+    _ => Vec::new(),
+  }
+}
+
+fn to_lsp_position(
+  text: &str,
+  line_index: &LineIndex,
+  offset: usize,
+) -> lsp::Position {
+  let utf16_offset = text[..offset].encode_utf16().count() as u32;
+  line_index.position_tsc(TextSize::from(utf16_offset))
+}
+
+fn to_lsp_range(
+  text: &str,
+  line_index: &LineIndex,
+  range: &Range<usize>,
+) -> lsp::Range {
+  lsp::Range {
+    start: to_lsp_position(text, line_index, range.start),
+    end: to_lsp_position(text, line_index, range.end),
+  }
+}
+
+fn markdown_documentation(value: &str) -> lsp::Documentation {
+  lsp::Documentation::MarkupContent(lsp::MarkupContent {
+    kind: lsp::MarkupKind::Markdown,
+    value: value.to_string(),
+  })
+}
+
+/// The names of the tasks defined in the config file.
+fn task_names(text: &str) -> Vec<String> {
+  let Ok(ast) =
+    jsonc_parser::parse_to_ast(text, &Default::default(), &Default::default())
+  else {
+    return Vec::new();
+  };
+  let Some(ast::Value::Object(root)) = &ast.value else {
+    return Vec::new();
+  };
+  let Some(tasks) = root.get_object("tasks") else {
+    return Vec::new();
+  };
+  tasks
+    .properties
+    .iter()
+    .map(|p| p.name.as_str().to_string())
+    .collect()
+}
+
+fn lint_rule_names() -> Vec<&'static str> {
+  let mut names = LintRuleProvider::new(None, None)
+    .resolve_lint_rules(Default::default(), None)
+    .all_rule_codes
+    .into_iter()
+    .collect::<Vec<_>>();
+  names.sort_unstable();
+  names
+}
+
+fn is_bare_specifier(specifier: &str) -> bool {
+  !(specifier.starts_with("./")
+    || specifier.starts_with("../")
+    || specifier.starts_with('/')
+    || Url::parse(specifier).is_ok())
+}
+
+/// Returns the completions at `position` in a deno.json or import map file.
+/// The `(referrer, specifier)` imports of the workspace are only requested for
+/// completing the keys of the `"imports"` and `"scopes"`, which are offered
+/// for the bare specifiers which are imported but not mapped yet.
+pub fn get_completions(
+  specifier: &ModuleSpecifier,
+  file_type: ConfigWatchedFileType,
+  text: &str,
+  line_index: &LineIndex,
+  position: lsp::Position,
+  get_workspace_imports: impl FnOnce() -> Vec<(ModuleSpecifier, String)>,
+) -> Option<lsp::CompletionResponse> {
+  let schema = schema_for_file_type(file_type)?;
+  let offset = usize::from(line_index.offset(position).ok()?);
+  let json_position = find_json_position(text, offset)?;
+  let range = match &json_position.string {
+    Some((_, range)) => range.clone(),
+    None => offset..offset,
+  };
+  let range = to_lsp_range(text, line_index, &range);
+  let to_item = |label: &str,
+                 new_text: String,
+                 kind: lsp::CompletionItemKind,
+                 schema: Option<&Value>| {
+    let deprecated =
+      schema.and_then(|s| s.get("deprecated")) == Some(&Value::Bool(true));
+    lsp::CompletionItem {
+      label: label.to_string(),
+      kind: Some(kind),
+      documentation: schema
+        .and_then(schema_description)
+        .map(markdown_documentation),
+      tags: deprecated.then(|| vec![lsp::CompletionItemTag::DEPRECATED]),
+      filter_text: Some(new_text.clone()),
+      text_edit: Some(lsp::CompletionTextEdit::Edit(lsp::TextEdit {
+        range,
+        new_text,
+      })),
+      ..Default::default()
+    }
+  };
+  let quoted = |value: &str| serde_json::to_string(value).unwrap();
+
+  let path = &json_position.path;
+  let mut items = Vec::<lsp::CompletionItem>::new();
+  if json_position.is_key {
+    let is_import_map_keys = matches!(path.as_slice(), [s] if s.is_key("imports"))
+      || matches!(path.as_slice(), [s, JsonPathSegment::Key(_)] if s.is_key("scopes"));
+    if is_import_map_keys {
+      let mut bare_specifiers = get_workspace_imports()
+        .into_iter()
+        .filter(|(referrer, _)| {
+          specifier
+            .join(".")
+            .is_ok_and(|dir| referrer.as_str().starts_with(dir.as_str()))
+        })
+        .map(|(_, specifier)| specifier)
+        .filter(|s| is_bare_specifier(s))
+        .collect::<Vec<_>>();
+      bare_specifiers.sort();
+      bare_specifiers.dedup();
+      for bare_specifier in bare_specifiers {
+        if json_position.sibling_keys.contains(&bare_specifier) {
+          continue;
+        }
+        items.push(to_item(
+          &bare_specifier,
+          quoted(&bare_specifier),
+          lsp::CompletionItemKind::MODULE,
+          None,
+        ));
+      }
+    }
+    for schema in schemas_at_path(schema, path) {
+      let Some(properties) =
+        schema.get("properties").and_then(|p| p.as_object())
+      else {
+        continue;
+      };
+      for (name, property) in properties {
+        if json_position.sibling_keys.contains(name)
+          || items.iter().any(|i| &i.label == name)
+        {
+          continue;
+        }
+        items.push(to_item(
+          name,
+          quoted(name),
+          lsp::CompletionItemKind::PROPERTY,
+          Some(property),
+        ));
+      }
+    }
+  } else {
+    let mut push_value = |label: &str, new_text: String, schema: &Value| {
+      if !items.iter().any(|i| i.label == label) {
+        items.push(to_item(
+          label,
+          new_text,
+          lsp::CompletionItemKind::VALUE,
+          Some(schema),
+        ));
+      }
+    };
+    match path.as_slice() {
+      [tasks, JsonPathSegment::Key(task), depends_on, JsonPathSegment::Item]
+        if tasks.is_key("tasks") && depends_on.is_key("dependsOn") =>
+      {
+        for name in task_names(text) {
+          if &name != task {
+            push_value(&name, quoted(&name), &json!({}));
+          }
+        }
+      }
+      [lint, rules, JsonPathSegment::Key(list), JsonPathSegment::Item]
+        if lint.is_key("lint")
+          && rules.is_key("rules")
+          && (list == "include" || list == "exclude") =>
+      {
+        for name in lint_rule_names() {
+          push_value(name, quoted(name), &json!({}));
+        }
+      }
+      _ => {}
+    }
+    for schema in schemas_at_path(schema, path) {
+      let enum_values = schema.get("enum").and_then(|e| e.as_array());
+      let examples = schema.get("examples").and_then(|e| e.as_array());
+      for value in enum_values.into_iter().chain(examples).flatten() {
+        match value {
+          Value::String(value) => push_value(value, quoted(value), schema),
+          Value::Bool(_) | Value::Number(_) => {
+            push_value(&value.to_string(), value.to_string(), schema)
+          }
+          _ => {}
+        }
+      }
+      if schema_types(schema).contains(&"boolean") {
+        push_value("true", "true".to_string(), schema);
+        push_value("false", "false".to_string(), schema);
+      }
+    }
+  }
+  if items.is_empty() {
+    return None;
+  }
+  Some(lsp::CompletionResponse::List(lsp::CompletionList {
+    is_incomplete: false,
+    items,
+  }))
+}
+
+/// Returns the description of the property name at `position` in a deno.json
+/// or import map file.
+pub fn get_hover(
+  file_type: ConfigWatchedFileType,
+  text: &str,
+  line_index: &LineIndex,
+  position: lsp::Position,
+) -> Option<lsp::Hover> {
+  let schema = schema_for_file_type(file_type)?;
+  let offset = usize::from(line_index.offset(position).ok()?);
+  let mut json_position = find_json_position(text, offset)?;
+  let (key, range) = json_position.string.take()?;
+  if !json_position.is_key {
+    return None;
+  }
+  json_position.path.push(JsonPathSegment::Key(key));
+  let descriptions = schemas_at_path(schema, &json_position.path)
+    .into_iter()
+    .filter_map(schema_description)
+    .collect::<Vec<_>>();
+  if descriptions.is_empty() {
+    return None;
+  }
+  Some(lsp::Hover {
+    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+      kind: lsp::MarkupKind::Markdown,
+      value: descriptions.join("\n\n---\n\n"),
+    }),
+    range: Some(to_lsp_range(text, line_index, &range)),
+  })
+}
+
+fn value_matches_type(value: &ast::Value, typ: &str) -> bool {
+  match (value, typ) {
+    (ast::Value::StringLit(_), "string")
+    | (ast::Value::BooleanLit(_), "boolean")
+    | (ast::Value::NumberLit(_), "number")
+    | (ast::Value::Object(_), "object")
+    | (ast::Value::Array(_), "array")
+    | (ast::Value::NullKeyword(_), "null") => true,
+    (ast::Value::NumberLit(number), "integer") => {
+      number.value.parse::<i64>().is_ok()
+    }
+    _ => false,
+  }
+}
+
+fn scalar_value(value: &ast::Value) -> Option<Value> {
+  match value {
+    ast::Value::StringLit(string) => {
+      Some(Value::String(string.value.to_string()))
+    }
+    ast::Value::BooleanLit(boolean) => Some(Value::Bool(boolean.value)),
+    ast::Value::NumberLit(number) => serde_json::from_str(number.value).ok(),
+    ast::Value::NullKeyword(_) => Some(Value::Null),
+    _ => None,
+  }
+}
+
+fn validate_value(
+  value: &ast::Value,
+  schema: &Value,
+  errors: &mut Vec<(Range<usize>, String)>,
+) {
+  let range = value.range().start..value.range().end;
+  let alternatives = schema.get("oneOf").or_else(|| schema.get("anyOf"));
+  if let Some(alternatives) = alternatives.and_then(|a| a.as_array()) {
+    let mut alternative_errors = None;
+    for alternative in alternatives {
+      let mut errors = Vec::new();
+      validate_value(value, alternative, &mut errors);
+      if errors.is_empty() {
+        return;
+      }
+      // prefer reporting the errors within a value of the right type
+      let is_nested = errors.iter().all(|(r, _)| r != &range);
+      if alternative_errors.is_none() || is_nested {
+        alternative_errors = Some(errors);
+      }
+    }
+    errors.extend(alternative_errors.unwrap_or_default());
+    return;
+  }
+  let types = schema_types(schema);
+  if !types.is_empty() && !types.iter().any(|t| value_matches_type(value, t)) {
+    let expected = types
+      .iter()
+      .map(|t| format!("\"{t}\""))
+      .collect::<Vec<_>>()
+      .join(" | ");
+    errors.push((range, format!("Incorrect type. Expected {expected}.")));
+    return;
+  }
+  if let Some(enum_values) = schema.get("enum").and_then(|e| e.as_array()) {
+    if !scalar_value(value).is_some_and(|v| enum_values.contains(&v)) {
+      let expected = enum_values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+      errors.push((
+        range,
+        format!("Value is not accepted. Valid values: {expected}."),
+      ));
+      return;
+    }
+  }
+  match value {
+    ast::Value::Object(object) => {
+      for prop in &object.properties {
+        let name = prop.name.as_str();
+        match property_schema(schema, name) {
+          Some(property) => validate_value(&prop.value, property, errors),
+          None => {
+            if schema.get("additionalProperties") == Some(&Value::Bool(false)) {
+              let name_range = prop.name.range();
+              errors.push((
+                name_range.start..name_range.end,
+                format!("Property \"{name}\" is not allowed."),
+              ));
+            }
+          }
+        }
+      }
+    }
+    ast::Value::Array(array) => {
+      if let Some(items) = schema.get("items") {
+        for element in &array.elements {
+          validate_value(element, items, errors);
+        }
+      }
+    }
+    _ => {}
+  }
+}
+
+/// Validates a deno.json or import map file against its schema, returning the
+/// byte ranges and messages of the errors. Syntax errors aren't reported
+/// here.
+pub fn validate(
+  file_type: ConfigWatchedFileType,
+  text: &str,
+) -> Vec<(Range<usize>, String)> {
+  let Some(schema) = schema_for_file_type(file_type) else {
+    return Vec::new();
+  };
+  let Ok(ast) =
+    jsonc_parser::parse_to_ast(text, &Default::default(), &Default::default())
+  else {
+    return Vec::new();
+  };
+  let mut errors = Vec::new();
+  if let Some(value) = &ast.value {
+    validate_value(value, schema, &mut errors);
+  }
+  errors
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn completion_labels(
+    file_type: ConfigWatchedFileType,
+    text_with_cursor: &str,
+  ) -> Vec<String> {
+    let offset = text_with_cursor.find('|').unwrap();
+    let text = text_with_cursor.replacen('|', "", 1);
+    let line_index = LineIndex::new(&text);
+    let position = to_lsp_position(&text, &line_index, offset);
+    let specifier = ModuleSpecifier::parse("file:///a/deno.json").unwrap();
+    let response = get_completions(
+      &specifier,
+      file_type,
+      &text,
+      &line_index,
+      position,
+      || {
+        vec![
+          (
+            ModuleSpecifier::parse("file:///a/mod.ts").unwrap(),
+            "chalk".to_string(),
+          ),
+          (
+            ModuleSpecifier::parse("file:///a/mod.ts").unwrap(),
+            "./b.ts".to_string(),
+          ),
+          (
+            ModuleSpecifier::parse("file:///other/mod.ts").unwrap(),
+            "other".to_string(),
+          ),
+        ]
+      },
+    );
+    match response {
+      Some(lsp::CompletionResponse::List(list)) => {
+        list.items.into_iter().map(|i| i.label).collect()
+      }
+      _ => Vec::new(),
+    }
+  }
+
+  #[test]
+  fn test_find_json_position() {
+    let text = r#"{ "fmt": { "lineWidth": 80, "pro" }, "lint": { "rules": { "exclude": ["no-"] } } }"#;
+    let position =
+      find_json_position(text, text.find("pro").unwrap() + 3).unwrap();
+    assert_eq!(position.path, vec![JsonPathSegment::Key("fmt".to_string())]);
+    assert!(position.is_key);
+    assert_eq!(position.sibling_keys, vec!["lineWidth".to_string()]);
+    assert_eq!(position.string.unwrap().0, "pro");
+
+    let position =
+      find_json_position(text, text.find("no-").unwrap() + 3).unwrap();
+    assert_eq!(
+      position.path,
+      vec![
+        JsonPathSegment::Key("lint".to_string()),
+        JsonPathSegment::Key("rules".to_string()),
+        JsonPathSegment::Key("exclude".to_string()),
+        JsonPathSegment::Item,
+      ]
+    );
+    assert!(!position.is_key);
+
+    // incomplete documents
+    let text = "{\n  \"compilerOptions\": {\n    \"str\n  }\n}";
+    let position =
+      find_json_position(text, text.find("str").unwrap() + 3).unwrap();
+    assert!(position.is_key);
+    assert_eq!(
+      position.path,
+      vec![JsonPathSegment::Key("compilerOptions".to_string())]
+    );
+    assert_eq!(position.string.unwrap().0, "str");
+    assert!(find_json_position("{ // \"a\"", 7).is_none());
+  }
+
+  #[test]
+  fn test_get_completions() {
+    let labels = completion_labels(
+      ConfigWatchedFileType::DenoJson,
+      r#"{ "fmt": { "useTabs": true, "|" } }"#,
+    );
+    assert!(labels.contains(&"lineWidth".to_string()));
+    assert!(!labels.contains(&"useTabs".to_string()));
+
+    let labels = completion_labels(
+      ConfigWatchedFileType::DenoJson,
+      r#"{ "fmt": { "proseWrap": "|" } }"#,
+    );
+    assert_eq!(labels, vec!["always", "never", "preserve"]);
+
+    let labels = completion_labels(
+      ConfigWatchedFileType::DenoJson,
+      r#"{ "fmt": { "useTabs": | } }"#,
+    );
+    assert_eq!(labels, vec!["true", "false"]);
+
+    let labels = completion_labels(
+      ConfigWatchedFileType::DenoJson,
+      r#"{ "tasks": { "build": "deno run build.ts", "dev": { "command": "deno run dev.ts", "dependsOn": ["|"] } } }"#,
+    );
+    assert_eq!(labels, vec!["build"]);
+
+    let labels = completion_labels(
+      ConfigWatchedFileType::DenoJson,
+      r#"{ "lint": { "rules": { "exclude": ["|"] } } }"#,
+    );
+    assert!(labels.contains(&"no-explicit-any".to_string()));
+
+    let labels = completion_labels(
+      ConfigWatchedFileType::ImportMap,
+      r#"{ "imports": { "|" } }"#,
+    );
+    assert_eq!(labels, vec!["chalk"]);
+
+    let labels =
+      completion_labels(ConfigWatchedFileType::ImportMap, r#"{ "|" }"#);
+    assert_eq!(labels, vec!["imports", "scopes"]);
+  }
+
+  #[test]
+  fn test_get_hover() {
+    let text = r#"{ "compilerOptions": { "strict": true } }"#;
+    let line_index = LineIndex::new(text);
+    let offset = text.find("strict").unwrap() + 1;
+    let hover = get_hover(
+      ConfigWatchedFileType::DenoJson,
+      text,
+      &line_index,
+      to_lsp_position(text, &line_index, offset),
+    )
+    .unwrap();
+    let lsp::HoverContents::Markup(contents) = hover.contents else {
+      unreachable!();
+    };
+    assert!(contents
+      .value
+      .starts_with("Enable all strict type checking"));
+    assert_eq!(
+      hover.range,
+      Some(lsp::Range {
+        start: lsp::Position {
+          line: 0,
+          character: 23,
+        },
+        end: lsp::Position {
+          line: 0,
+          character: 31,
+        },
+      })
+    );
+  }
+
+  #[test]
+  fn test_validate() {
+    let text = r#"{
+  "compilerOptions": { "strict": "yes", "unknownOption": true },
+  "fmt": { "proseWrap": "sometimes", "lineWidth": 80 },
+  "nodeModulesDir": "auto",
+  "lock": false,
+  "tasks": { "dev": { "command": "deno run dev.ts", "dependsOn": [1] } }
+}"#;
+    let errors = validate(ConfigWatchedFileType::DenoJson, text)
+      .into_iter()
+      .map(|(range, message)| (&text[range], message))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      errors,
+      vec![
+        ("\"yes\"", "Incorrect type. Expected \"boolean\".".to_string()),
+        (
+          "\"unknownOption\"",
+          "Property \"unknownOption\" is not allowed.".to_string()
+        ),
+        (
+          "\"sometimes\"",
+          "Value is not accepted. Valid values: \"always\", \"never\", \"preserve\"."
+            .to_string()
+        ),
+        ("1", "Incorrect type. Expected \"string\".".to_string()),
+      ]
+    );
+  }
+}
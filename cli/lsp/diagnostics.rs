@@ -3,6 +3,7 @@
 use super::analysis;
 use super::client::Client;
 use super::config::Config;
+use super::config_file;
 use super::documents;
 use super::documents::Document;
 use super::documents::Documents;
@@ -16,6 +17,11 @@ use super::urls::uri_parse_unencoded;
 use super::urls::url_to_uri;
 use super::urls::LspUrlMap;
 
+use crate::args::import_map::config_file_imports;
+use crate::args::import_map::find_duplicate_import_map_entries;
+use crate::args::import_map::find_unused_import_map_entries;
+use crate::args::import_map::parse_import_map_entries;
+use crate::cache::FastInsecureHasher;
 use crate::graph_util;
 use crate::graph_util::enhanced_resolution_error_message;
 use crate::lsp::lsp_custom::DiagnosticBatchNotificationParams;
@@ -24,6 +30,7 @@ use crate::resolver::SloppyImportsResolver;
 use crate::tools::lint::CliLinter;
 use crate::tools::lint::CliLinterOptions;
 use crate::tools::lint::LintRuleProvider;
+use crate::tools::lint::LintRuleSeverities;
 use crate::util::path::to_percent_decoded_str;
 
 use deno_ast::MediaType;
@@ -252,6 +259,14 @@ impl TsDiagnosticsStore {
     self.0.lock().clear();
   }
 
+  fn extend(&self, diagnostics: &DiagnosticVec) {
+    self.0.lock().extend(
+      diagnostics
+        .iter()
+        .map(|record| (record.specifier.clone(), record.versioned.clone())),
+    );
+  }
+
   fn update(&self, diagnostics: &DiagnosticVec) {
     let mut stored_ts_diagnostics = self.0.lock();
     *stored_ts_diagnostics = diagnostics
@@ -388,6 +403,8 @@ pub struct DiagnosticsServer {
   ts_server: Arc<TsServer>,
   batch_counter: DiagnosticBatchCounter,
   state: Arc<DiagnosticsState>,
+  pull_mode: bool,
+  refresh_capable: bool,
 }
 
 impl DiagnosticsServer {
@@ -405,9 +422,18 @@ impl DiagnosticsServer {
       ts_server,
       batch_counter: Default::default(),
       state,
+      pull_mode: false,
+      refresh_capable: false,
     }
   }
 
+  /// Stop publishing diagnostics, for clients which request them with
+  /// `textDocument/diagnostic` and `workspace/diagnostic` instead.
+  pub fn set_pull_mode(&mut self, refresh_capable: bool) {
+    self.pull_mode = true;
+    self.refresh_capable = refresh_capable;
+  }
+
   pub fn get_ts_diagnostics(
     &self,
     specifier: &ModuleSpecifier,
@@ -425,10 +451,19 @@ impl DiagnosticsServer {
     if let Some(tx) = &self.channel {
       let _ = tx.send(ChannelMessage::Clear);
     }
+    if self.pull_mode && self.refresh_capable {
+      // the client pulls the diagnostics of documents when they change, but
+      // needs to be told about project wide changes
+      self.client.send_diagnostic_refresh_request();
+    }
   }
 
   #[allow(unused_must_use)]
   pub fn start(&mut self) {
+    if self.pull_mode {
+      // nothing is published, so there's nothing to generate in the background
+      return;
+    }
     let (tx, mut rx) = mpsc::unbounded_channel::<ChannelMessage>();
     self.channel = Some(tx);
     let client = self.client.clone();
@@ -666,6 +701,9 @@ impl DiagnosticsServer {
     &self,
     message: DiagnosticServerUpdateMessage,
   ) -> Result<(), AnyError> {
+    if self.pull_mode {
+      return Ok(());
+    }
     // todo(dsherret): instead of queuing up messages, it would be better to
     // instead only store the latest message (ex. maybe using a
     // tokio::sync::watch::channel)
@@ -679,6 +717,121 @@ impl DiagnosticsServer {
       Err(anyhow!("diagnostics server not started"))
     }
   }
+
+  /// Generates the diagnostics of all sources for the given open documents,
+  /// when they're pulled by the client.
+  pub async fn pull(
+    &self,
+    snapshot: Arc<StateSnapshot>,
+    specifiers: Vec<ModuleSpecifier>,
+  ) -> Result<Vec<PulledDiagnostics>, AnyError> {
+    let config = snapshot.config.clone();
+    let documents = specifiers
+      .iter()
+      .filter_map(|s| snapshot.documents.get(s))
+      .filter(|d| d.is_open())
+      .collect::<Vec<_>>();
+    let diagnosable_specifiers = documents
+      .iter()
+      .filter(|d| d.is_diagnosable())
+      .map(|d| d.specifier().clone())
+      .collect::<Vec<_>>();
+    let ts_diagnostics = if diagnosable_specifiers.is_empty() {
+      Vec::new()
+    } else {
+      generate_specifiers_ts_diagnostics(
+        snapshot.clone(),
+        &config,
+        &self.ts_server,
+        diagnosable_specifiers,
+        Default::default(),
+      )
+      .await?
+    };
+    self.ts_diagnostics.extend(&ts_diagnostics);
+    let mut ts_diagnostics = ts_diagnostics
+      .into_iter()
+      .map(|r| (r.specifier, r.versioned.diagnostics))
+      .collect::<HashMap<_, _>>();
+    let import_map_files = config.tree.import_map_files();
+    let mut import_map_diagnostics = if documents
+      .iter()
+      .any(|d| import_map_files.contains(d.specifier()))
+    {
+      generate_import_map_diagnostics(&snapshot, &config)
+        .into_iter()
+        .map(|r| (r.specifier, r.versioned.diagnostics))
+        .collect::<HashMap<_, _>>()
+    } else {
+      Default::default()
+    };
+    let mut pulled = Vec::with_capacity(documents.len());
+    for document in documents {
+      let specifier = document.specifier();
+      let mut diagnostics = Vec::new();
+      if document.is_diagnosable() {
+        diagnostics.extend(generate_document_deno_diagnostics(
+          &snapshot, &config, &document,
+        ));
+        diagnostics.extend(
+          maybe_generate_document_lint_diagnostics(
+            &snapshot, &config, &document,
+          )
+          .unwrap_or_default(),
+        );
+        diagnostics
+          .extend(ts_diagnostics.remove(specifier).unwrap_or_default());
+      }
+      diagnostics
+        .extend(import_map_diagnostics.remove(specifier).unwrap_or_default());
+      let version = document.maybe_lsp_version();
+      self.state.update(specifier, version, &diagnostics);
+      pulled.push(PulledDiagnostics {
+        specifier: specifier.clone(),
+        version,
+        diagnostics,
+      });
+    }
+    Ok(pulled)
+  }
+}
+
+/// The diagnostics of a document which were pulled by the client.
+#[derive(Debug)]
+pub struct PulledDiagnostics {
+  pub specifier: ModuleSpecifier,
+  pub version: Option<i32>,
+  pub diagnostics: Vec<lsp::Diagnostic>,
+}
+
+impl PulledDiagnostics {
+  /// Identifies the diagnostics, so an unchanged report can be sent when the
+  /// client already has them.
+  fn result_id(&self) -> String {
+    let mut hasher = FastInsecureHasher::new_without_deno_version();
+    hasher.write_hashable(self.version);
+    hasher.write_str(&serde_json::to_string(&self.diagnostics).unwrap());
+    hasher.finish().to_string()
+  }
+
+  pub fn into_report(
+    self,
+    previous_result_id: Option<&str>,
+  ) -> lsp::DocumentDiagnosticReportKind {
+    let result_id = self.result_id();
+    if previous_result_id == Some(result_id.as_str()) {
+      lsp::DocumentDiagnosticReportKind::Unchanged(
+        lsp::UnchangedDocumentDiagnosticReport { result_id },
+      )
+    } else {
+      lsp::DocumentDiagnosticReportKind::Full(
+        lsp::FullDocumentDiagnosticReport {
+          result_id: Some(result_id),
+          items: self.diagnostics,
+        },
+      )
+    }
+  }
 }
 
 impl<'a> From<&'a crate::tsc::DiagnosticCategory> for lsp::DiagnosticSeverity {
@@ -795,69 +948,92 @@ fn generate_lint_diagnostics(
   let documents = snapshot
     .documents
     .documents(DocumentsFilter::OpenDiagnosable);
-  let config_data_by_scope = config.tree.data_by_scope();
   let mut diagnostics_vec = Vec::new();
   for document in documents {
-    let specifier = document.specifier();
-    if specifier.scheme() != "file" {
-      continue;
-    }
-    if !config.specifier_enabled(specifier) {
-      continue;
-    }
-    let settings = config.workspace_settings_for_specifier(specifier);
-    if !settings.lint {
-      continue;
-    }
     // exit early if cancelled
     if token.is_cancelled() {
       break;
     }
-    // ignore any npm package files
-    if snapshot.resolver.in_node_modules(specifier) {
+    let Some(diagnostics) =
+      maybe_generate_document_lint_diagnostics(snapshot, config, &document)
+    else {
       continue;
-    }
-    let version = document.maybe_lsp_version();
-    let (lint_config, linter) = config
-      .tree
-      .scope_for_specifier(specifier)
-      .and_then(|s| config_data_by_scope.get(s))
-      .map(|d| (d.lint_config.clone(), d.linter.clone()))
-      .unwrap_or_else(|| {
-        (
-          Arc::new(LintConfig::new_with_base(PathBuf::from("/"))),
-          Arc::new(CliLinter::new(CliLinterOptions {
-            configured_rules: {
-              let lint_rule_provider = LintRuleProvider::new(None, None);
-              lint_rule_provider.resolve_lint_rules(Default::default(), None)
-            },
-            fix: false,
-            deno_lint_config: deno_lint::linter::LintConfig {
-              default_jsx_factory: None,
-              default_jsx_fragment_factory: None,
-            },
-          })),
-        )
-      });
+    };
     diagnostics_vec.push(DiagnosticRecord {
-      specifier: specifier.clone(),
+      specifier: document.specifier().clone(),
       versioned: VersionedDiagnostics {
-        version,
-        diagnostics: generate_document_lint_diagnostics(
-          &document,
-          &lint_config,
-          &linter,
-        ),
+        version: document.maybe_lsp_version(),
+        diagnostics,
       },
     });
   }
   diagnostics_vec
 }
 
+/// Returns `None` when the document is not linted.
+fn maybe_generate_document_lint_diagnostics(
+  snapshot: &language_server::StateSnapshot,
+  config: &Config,
+  document: &Document,
+) -> Option<Vec<lsp::Diagnostic>> {
+  let specifier = document.specifier();
+  if specifier.scheme() != "file" {
+    return None;
+  }
+  if !config.specifier_enabled(specifier) {
+    return None;
+  }
+  let settings = config.workspace_settings_for_specifier(specifier);
+  if !settings.lint {
+    return None;
+  }
+  // ignore any npm package files
+  if snapshot.resolver.in_node_modules(specifier) {
+    return None;
+  }
+  let (lint_config, linter, lint_severities) = config
+    .tree
+    .scope_for_specifier(specifier)
+    .and_then(|s| config.tree.data_by_scope().get(s))
+    .map(|d| {
+      (
+        d.lint_config.clone(),
+        d.linter.clone(),
+        d.lint_severities.clone(),
+      )
+    })
+    .unwrap_or_else(|| {
+      (
+        Arc::new(LintConfig::new_with_base(PathBuf::from("/"))),
+        Arc::new(CliLinter::new(CliLinterOptions {
+          configured_rules: {
+            let lint_rule_provider = LintRuleProvider::new(None, None);
+            lint_rule_provider.resolve_lint_rules(Default::default(), None)
+          },
+          fix: false,
+          fix_dry_run: false,
+          deno_lint_config: deno_lint::linter::LintConfig {
+            default_jsx_factory: None,
+            default_jsx_fragment_factory: None,
+          },
+          plugins: None,
+        })),
+        Default::default(),
+      )
+    });
+  Some(generate_document_lint_diagnostics(
+    document,
+    &lint_config,
+    &linter,
+    &lint_severities,
+  ))
+}
+
 fn generate_document_lint_diagnostics(
   document: &Document,
   lint_config: &LintConfig,
   linter: &CliLinter,
+  lint_severities: &LintRuleSeverities,
 ) -> Vec<lsp::Diagnostic> {
   if !lint_config.files.matches_specifier(document.specifier()) {
     return Vec::new();
@@ -869,7 +1045,7 @@ fn generate_document_lint_diagnostics(
       {
         references
           .into_iter()
-          .map(|r| r.to_diagnostic())
+          .map(|r| r.to_diagnostic(lint_severities))
           .collect::<Vec<_>>()
       } else {
         Vec::new()
@@ -889,12 +1065,26 @@ async fn generate_ts_diagnostics(
   ts_server: &tsc::TsServer,
   token: CancellationToken,
 ) -> Result<DiagnosticVec, AnyError> {
-  let mut diagnostics_vec = Vec::new();
   let specifiers = snapshot
     .documents
     .documents(DocumentsFilter::OpenDiagnosable)
     .into_iter()
-    .map(|d| d.specifier().clone());
+    .map(|d| d.specifier().clone())
+    .collect();
+  generate_specifiers_ts_diagnostics(
+    snapshot, config, ts_server, specifiers, token,
+  )
+  .await
+}
+
+async fn generate_specifiers_ts_diagnostics(
+  snapshot: Arc<language_server::StateSnapshot>,
+  config: &Config,
+  ts_server: &tsc::TsServer,
+  specifiers: Vec<ModuleSpecifier>,
+  token: CancellationToken,
+) -> Result<DiagnosticVec, AnyError> {
+  let mut diagnostics_vec = Vec::new();
   let (enabled_specifiers, disabled_specifiers) = specifiers
     .into_iter()
     .partition::<Vec<_>, _>(|s| config.specifier_enabled(s));
@@ -976,6 +1166,13 @@ struct DiagnosticDataImportMapRemap {
   pub to: String,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DiagnosticDataImportMapEntry {
+  pub key: String,
+  pub removal_range: lsp::Range,
+}
+
 /// An enum which represents diagnostic errors which originate from Deno itself.
 pub enum DenoDiagnostic {
   /// A `x-deno-warning` is associated with the specifier and should be displayed
@@ -1008,6 +1205,20 @@ pub enum DenoDiagnostic {
   InvalidNodeSpecifier(ModuleSpecifier),
   /// Bare specifier is used for `node:` specifier
   BareNodeSpecifier(String),
+  /// An import map entry which none of the modules of the workspace may be
+  /// resolved through.
+  UnusedImportMapEntry {
+    key: String,
+    removal_range: lsp::Range,
+  },
+  /// An import map entry which is ignored because an entry with the same key
+  /// follows it.
+  DuplicateImportMapEntry {
+    key: String,
+    removal_range: lsp::Range,
+  },
+  /// A value of a config or import map file which doesn't match its schema.
+  InvalidConfigValue(String),
 }
 
 impl DenoDiagnostic {
@@ -1046,6 +1257,9 @@ impl DenoDiagnostic {
       }
       Self::InvalidNodeSpecifier(_) => "resolver-error",
       Self::BareNodeSpecifier(_) => "import-node-prefix-missing",
+      Self::UnusedImportMapEntry { .. } => "unused-import-map-entry",
+      Self::DuplicateImportMapEntry { .. } => "duplicate-import-map-entry",
+      Self::InvalidConfigValue(_) => "invalid-config-value",
     }
   }
 
@@ -1201,6 +1415,35 @@ impl DenoDiagnostic {
             ..Default::default()
           }
         }
+        "unused-import-map-entry" | "duplicate-import-map-entry" => {
+          let data = diagnostic
+            .data
+            .clone()
+            .ok_or_else(|| anyhow!("Diagnostic is missing data"))?;
+          let DiagnosticDataImportMapEntry { key, removal_range } =
+            serde_json::from_value(data)?;
+          let title = if code == "unused-import-map-entry" {
+            format!("Remove unused entry \"{key}\".")
+          } else {
+            format!("Remove duplicate entry \"{key}\".")
+          };
+          lsp::CodeAction {
+            title,
+            kind: Some(lsp::CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diagnostic.clone()]),
+            edit: Some(lsp::WorkspaceEdit {
+              changes: Some(HashMap::from([(
+                url_to_uri(specifier)?,
+                vec![lsp::TextEdit {
+                  new_text: "".to_string(),
+                  range: removal_range,
+                }],
+              )])),
+              ..Default::default()
+            }),
+            ..Default::default()
+          }
+        }
         _ => {
           return Err(anyhow!(
             "Unsupported diagnostic code (\"{}\") provided.",
@@ -1225,7 +1468,9 @@ impl DenoDiagnostic {
         | "not-installed-npm"
         | "no-attribute-type"
         | "redirect"
-        | "import-node-prefix-missing" => true,
+        | "import-node-prefix-missing"
+        | "unused-import-map-entry"
+        | "duplicate-import-map-entry" => true,
         "no-local" => diagnostic.data.is_some(),
         _ => false,
       }
@@ -1295,13 +1540,19 @@ impl DenoDiagnostic {
       )},
       Self::InvalidNodeSpecifier(specifier) => (lsp::DiagnosticSeverity::ERROR, format!("Unknown Node built-in module: {}", specifier.path()), None),
       Self::BareNodeSpecifier(specifier) => (lsp::DiagnosticSeverity::WARNING, format!("\"{}\" is resolved to \"node:{}\". If you want to use a built-in Node module, add a \"node:\" prefix.", specifier, specifier), Some(json!({ "specifier": specifier }))),
+      Self::UnusedImportMapEntry { key, removal_range } => (lsp::DiagnosticSeverity::HINT, format!("The import map entry \"{key}\" is not used by any module of the workspace."), Some(json!({ "key": key, "removalRange": removal_range }))),
+      Self::DuplicateImportMapEntry { key, removal_range } => (lsp::DiagnosticSeverity::WARNING, format!("The import map entry \"{key}\" is ignored because it is defined again later."), Some(json!({ "key": key, "removalRange": removal_range }))),
+      Self::InvalidConfigValue(message) => (lsp::DiagnosticSeverity::WARNING, message.to_string(), None),
     };
+    let tags = matches!(self, Self::UnusedImportMapEntry { .. })
+      .then(|| vec![lsp::DiagnosticTag::UNNECESSARY]);
     lsp::Diagnostic {
       range: *range,
       severity: Some(severity),
       code: Some(lsp::NumberOrString::String(self.code().to_string())),
       source: Some(DiagnosticSource::Deno.as_lsp_source().to_string()),
       message,
+      tags,
       data,
       ..Default::default()
     }
@@ -1594,19 +1845,105 @@ fn generate_deno_diagnostics(
     if token.is_cancelled() {
       break;
     }
+    diagnostics_vec.push(DiagnosticRecord {
+      specifier: document.specifier().clone(),
+      versioned: VersionedDiagnostics {
+        version: document.maybe_lsp_version(),
+        diagnostics: generate_document_deno_diagnostics(
+          snapshot, config, &document,
+        ),
+      },
+    });
+  }
+
+  if !token.is_cancelled() {
+    diagnostics_vec.extend(generate_import_map_diagnostics(snapshot, config));
+  }
+
+  diagnostics_vec
+}
+
+fn generate_document_deno_diagnostics(
+  snapshot: &language_server::StateSnapshot,
+  config: &Config,
+  document: &Document,
+) -> Vec<lsp::Diagnostic> {
+  let mut diagnostics = Vec::new();
+  if config.specifier_enabled(document.specifier()) {
+    for (dependency_key, dependency) in document.dependencies() {
+      diagnose_dependency(
+        &mut diagnostics,
+        snapshot,
+        document,
+        dependency_key,
+        dependency,
+      );
+    }
+  }
+  diagnostics
+}
+
+/// Generate diagnostics for the entries of open deno.json and import map
+/// files which no module of the workspace is resolved through, or which are
+/// ignored because they're defined again later.
+fn generate_import_map_diagnostics(
+  snapshot: &language_server::StateSnapshot,
+  config: &Config,
+) -> DiagnosticVec {
+  let mut diagnostics_vec = Vec::new();
+  let mut workspace_imports = None;
+  for specifier in config.tree.import_map_files() {
+    let Some(document) = snapshot.documents.get(specifier) else {
+      continue;
+    };
+    if !document.is_open() || !config.specifier_enabled(specifier) {
+      continue;
+    }
+    let text = document.content();
+    let line_index = document.line_index();
+    let to_lsp_range = |range: &std::ops::Range<usize>| {
+      let to_position = |offset: usize| {
+        let utf16_offset = text[..offset].encode_utf16().count() as u32;
+        line_index.position_tsc(utf16_offset.into())
+      };
+      lsp::Range {
+        start: to_position(range.start),
+        end: to_position(range.end),
+      }
+    };
+    let entries = parse_import_map_entries(text);
+    let workspace_imports = workspace_imports
+      .get_or_insert_with(|| collect_workspace_imports(snapshot, config));
     let mut diagnostics = Vec::new();
-    let specifier = document.specifier();
-    if config.specifier_enabled(specifier) {
-      for (dependency_key, dependency) in document.dependencies() {
-        diagnose_dependency(
-          &mut diagnostics,
-          snapshot,
-          &document,
-          dependency_key,
-          dependency,
+    if let Some((_, file_type)) = config.tree.watched_file_type(specifier) {
+      for (range, message) in config_file::validate(file_type, text) {
+        diagnostics.push(
+          DenoDiagnostic::InvalidConfigValue(message)
+            .to_lsp_diagnostic(&to_lsp_range(&range)),
         );
       }
     }
+    for entry in find_duplicate_import_map_entries(&entries) {
+      diagnostics.push(
+        DenoDiagnostic::DuplicateImportMapEntry {
+          key: entry.key.clone(),
+          removal_range: to_lsp_range(&entry.removal_range),
+        }
+        .to_lsp_diagnostic(&to_lsp_range(&entry.range)),
+      );
+    }
+    for entry in
+      find_unused_import_map_entries(specifier, &entries, workspace_imports)
+    {
+      diagnostics.push(
+        DenoDiagnostic::UnusedImportMapEntry {
+          key: entry.key.clone(),
+          removal_range: to_lsp_range(&entry.removal_range),
+        }
+        .to_lsp_diagnostic(&to_lsp_range(&entry.range)),
+      );
+    }
+    diagnostics.sort_by_key(|d| (d.range.start.line, d.range.start.character));
     diagnostics_vec.push(DiagnosticRecord {
       specifier: specifier.clone(),
       versioned: VersionedDiagnostics {
@@ -1615,10 +1952,66 @@ fn generate_deno_diagnostics(
       },
     });
   }
-
   diagnostics_vec
 }
 
+/// The `(referrer, specifier)` imports of the local modules of the workspace
+/// and of its config files. The modules are found by following the module
+/// graph from the loaded documents and the exports of the config files, so
+/// the ones which aren't loaded, like beyond the document preload limit, are
+/// included as long as they're reachable.
+pub fn collect_workspace_imports(
+  snapshot: &language_server::StateSnapshot,
+  config: &Config,
+) -> Vec<(ModuleSpecifier, String)> {
+  let mut imports = Vec::new();
+  let mut pending = snapshot
+    .documents
+    .documents(DocumentsFilter::AllDiagnosable)
+    .iter()
+    .map(|d| (d.specifier().clone(), d.file_referrer().cloned()))
+    .collect::<Vec<_>>();
+  for config_data in config.tree.data_by_scope().values() {
+    let config_files = config_data
+      .maybe_deno_json()
+      .into_iter()
+      .chain(config_data.member_dir.workspace.root_deno_json());
+    for config_file in config_files {
+      imports.extend(config_file_imports(config_file));
+      let Ok(exports) = config_file.to_exports_config() else {
+        continue;
+      };
+      for key in exports.clone().into_map().into_keys() {
+        if let Ok(Some(specifier)) = exports.get_resolved(&key) {
+          pending.push((specifier, None));
+        }
+      }
+    }
+  }
+  let mut visited = HashSet::new();
+  while let Some((specifier, file_referrer)) = pending.pop() {
+    if specifier.scheme() != "file" || !visited.insert(specifier.clone()) {
+      continue;
+    }
+    let Some(document) = snapshot
+      .documents
+      .get_or_load(&specifier, file_referrer.as_ref())
+    else {
+      continue;
+    };
+    for (dependency_key, dependency) in document.dependencies() {
+      imports.push((specifier.clone(), dependency_key.clone()));
+      let resolved = [&dependency.maybe_code, &dependency.maybe_type]
+        .into_iter()
+        .filter_map(|resolution| resolution.maybe_specifier());
+      for specifier in resolved {
+        pending.push((specifier.clone(), document.file_referrer().cloned()));
+      }
+    }
+  }
+  imports
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
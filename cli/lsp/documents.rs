@@ -3,6 +3,8 @@
 use super::cache::calculate_fs_version;
 use super::cache::LspCache;
 use super::config::Config;
+use super::performance::CacheStats;
+use super::plugins::EmbeddedDocument;
 use super::resolver::LspResolver;
 use super::testing::TestCollector;
 use super::testing::TestModule;
@@ -182,7 +184,7 @@ impl AssetOrDocument {
   pub fn text(&self) -> Arc<str> {
     match self {
       AssetOrDocument::Asset(a) => a.text(),
-      AssetOrDocument::Document(d) => d.text.clone(),
+      AssetOrDocument::Document(d) => d.script_text().clone(),
     }
   }
 
@@ -301,6 +303,9 @@ pub struct Document {
   line_index: Arc<LineIndex>,
   maybe_headers: Option<HashMap<String, String>>,
   maybe_language_id: Option<LanguageId>,
+  /// The virtual document of a framework file handled by a language server
+  /// plugin.
+  maybe_embedded: Option<Arc<EmbeddedDocument>>,
   /// This is cached in a mutex so `workspace/symbol` and
   /// `textDocument/codeLens` requests don't require a write lock.
   maybe_navigation_tree: Mutex<Option<Arc<tsc::NavigationTree>>>,
@@ -374,6 +379,7 @@ impl Document {
       maybe_types_dependency,
       line_index,
       maybe_language_id,
+      maybe_embedded: None,
       maybe_headers,
       maybe_navigation_tree: Mutex::new(None),
       maybe_test_module_fut,
@@ -395,19 +401,30 @@ impl Document {
     resolver: Arc<LspResolver>,
     config: Arc<Config>,
   ) -> Arc<Self> {
-    let media_type = resolve_media_type(
-      &self.specifier,
-      self.maybe_headers.as_ref(),
-      self.maybe_language_id,
-      &resolver,
-    );
+    // the virtual document of a framework file is extracted again by the
+    // language server once the config is updated
+    let maybe_embedded = self.maybe_embedded.clone();
+    let media_type = maybe_embedded
+      .as_ref()
+      .map(|embedded| embedded.media_type)
+      .unwrap_or_else(|| {
+        resolve_media_type(
+          &self.specifier,
+          self.maybe_headers.as_ref(),
+          self.maybe_language_id,
+          &resolver,
+        )
+      });
     let dependencies;
     let maybe_types_dependency;
     let maybe_parsed_source;
     let maybe_test_module_fut;
     if media_type != self.media_type {
-      let parsed_source_result =
-        parse_source(self.specifier.clone(), self.text.clone(), media_type);
+      let parsed_source_result = parse_source(
+        self.specifier.clone(),
+        self.script_text().clone(),
+        media_type,
+      );
       let maybe_module = analyze_module(
         self.specifier.clone(),
         &parsed_source_result,
@@ -473,6 +490,7 @@ impl Document {
       line_index: self.line_index.clone(),
       maybe_headers: self.maybe_headers.clone(),
       maybe_language_id: self.maybe_language_id,
+      maybe_embedded,
       maybe_test_module_fut,
       media_type,
       open_data: self.open_data.as_ref().map(|d| DocumentOpenData {
@@ -488,6 +506,42 @@ impl Document {
     })
   }
 
+  /// Like `with_new_config()`, but keeps the resolved dependencies. Only valid
+  /// when the config change doesn't affect how this document resolves.
+  fn with_new_resolver_only(
+    &self,
+    resolver: Arc<LspResolver>,
+    config: Arc<Config>,
+  ) -> Arc<Self> {
+    let maybe_test_module_fut = self
+      .maybe_test_module_fut
+      .clone()
+      .filter(|_| config.specifier_enabled_for_test(&self.specifier));
+    Arc::new(Self {
+      config,
+      dependencies: self.dependencies.clone(),
+      file_referrer: self.file_referrer.clone(),
+      maybe_types_dependency: self.maybe_types_dependency.clone(),
+      maybe_navigation_tree: Mutex::new(self.maybe_navigation_tree()),
+      maybe_fs_version: self.maybe_fs_version.clone(),
+      line_index: self.line_index.clone(),
+      maybe_headers: self.maybe_headers.clone(),
+      maybe_language_id: self.maybe_language_id,
+      maybe_embedded: self.maybe_embedded.clone(),
+      maybe_test_module_fut,
+      media_type: self.media_type,
+      open_data: self.open_data.as_ref().map(|d| DocumentOpenData {
+        lsp_version: d.lsp_version,
+        maybe_parsed_source: d.maybe_parsed_source.clone(),
+        maybe_semantic_tokens: d.maybe_semantic_tokens.clone(),
+      }),
+      resolver,
+      specifier: self.specifier.clone(),
+      text: self.text.clone(),
+      text_info_cell: self.text_info_cell.clone(),
+    })
+  }
+
   fn with_change(
     &self,
     version: i32,
@@ -510,7 +564,18 @@ impl Document {
       }
     }
     let text: Arc<str> = content.into();
-    let media_type = self.media_type;
+    // the virtual document of a framework file is extracted again by the
+    // language server after the change, until then the file isn't analyzed
+    let media_type = if self.maybe_embedded.is_some() {
+      resolve_media_type(
+        &self.specifier,
+        self.maybe_headers.as_ref(),
+        self.maybe_language_id,
+        &self.resolver,
+      )
+    } else {
+      self.media_type
+    };
     let (maybe_parsed_source, maybe_module) = if self
       .maybe_language_id
       .as_ref()
@@ -550,6 +615,7 @@ impl Document {
       file_referrer: self.file_referrer.clone(),
       maybe_fs_version: self.maybe_fs_version.clone(),
       maybe_language_id: self.maybe_language_id,
+      maybe_embedded: None,
       dependencies,
       maybe_types_dependency,
       text,
@@ -568,6 +634,74 @@ impl Document {
     }))
   }
 
+  /// Sets the virtual document of a framework file, which is extracted by
+  /// the language server plugins after the document is opened or changed.
+  fn with_embedded(
+    &self,
+    maybe_embedded: Option<Arc<EmbeddedDocument>>,
+  ) -> Arc<Self> {
+    let media_type = maybe_embedded
+      .as_ref()
+      .map(|embedded| embedded.media_type)
+      .unwrap_or_else(|| {
+        resolve_media_type(
+          &self.specifier,
+          self.maybe_headers.as_ref(),
+          self.maybe_language_id,
+          &self.resolver,
+        )
+      });
+    let (maybe_parsed_source, maybe_module) =
+      if media_type_is_diagnosable(media_type) {
+        parse_and_analyze_module(
+          self.specifier.clone(),
+          maybe_embedded
+            .as_ref()
+            .map(|embedded| embedded.text.clone())
+            .unwrap_or_else(|| self.text.clone()),
+          self.maybe_headers.as_ref(),
+          media_type,
+          self.file_referrer.as_ref(),
+          &self.resolver,
+        )
+      } else {
+        (None, None)
+      };
+    let maybe_module = maybe_module.and_then(Result::ok);
+    let dependencies = maybe_module
+      .as_ref()
+      .map(|m| Arc::new(m.dependencies.clone()))
+      .unwrap_or_default();
+    let maybe_types_dependency = maybe_module
+      .as_ref()
+      .and_then(|m| Some(Arc::new(m.maybe_types_dependency.clone()?)));
+    let maybe_test_module_fut =
+      get_maybe_test_module_fut(maybe_parsed_source.as_ref(), &self.config);
+    Arc::new(Self {
+      config: self.config.clone(),
+      specifier: self.specifier.clone(),
+      file_referrer: self.file_referrer.clone(),
+      maybe_fs_version: self.maybe_fs_version.clone(),
+      maybe_language_id: self.maybe_language_id,
+      maybe_embedded,
+      dependencies,
+      maybe_types_dependency,
+      text: self.text.clone(),
+      text_info_cell: once_cell::sync::OnceCell::new(),
+      line_index: self.line_index.clone(),
+      maybe_headers: self.maybe_headers.clone(),
+      maybe_navigation_tree: Mutex::new(None),
+      maybe_test_module_fut,
+      media_type,
+      open_data: self.open_data.as_ref().map(|d| DocumentOpenData {
+        lsp_version: d.lsp_version,
+        maybe_parsed_source,
+        maybe_semantic_tokens: Default::default(),
+      }),
+      resolver: self.resolver.clone(),
+    })
+  }
+
   pub fn closed(&self, cache: &Arc<LspCache>) -> Arc<Self> {
     Arc::new(Self {
       config: self.config.clone(),
@@ -579,6 +713,7 @@ impl Document {
         self.file_referrer.as_ref(),
       ),
       maybe_language_id: self.maybe_language_id,
+      maybe_embedded: self.maybe_embedded.clone(),
       dependencies: self.dependencies.clone(),
       maybe_types_dependency: self.maybe_types_dependency.clone(),
       text: self.text.clone(),
@@ -606,6 +741,7 @@ impl Document {
         self.file_referrer.as_ref(),
       ),
       maybe_language_id: self.maybe_language_id,
+      maybe_embedded: self.maybe_embedded.clone(),
       dependencies: self.dependencies.clone(),
       maybe_types_dependency: self.maybe_types_dependency.clone(),
       text: self.text.clone(),
@@ -641,6 +777,24 @@ impl Document {
     &self.text
   }
 
+  /// The text analyzed as a module, which is the virtual document of the
+  /// embedded scripts for framework files handled by a language server plugin.
+  pub fn script_text(&self) -> &Arc<str> {
+    self
+      .maybe_embedded
+      .as_ref()
+      .map(|embedded| &embedded.text)
+      .unwrap_or(&self.text)
+  }
+
+  pub fn is_embedded(&self) -> bool {
+    self.maybe_embedded.is_some()
+  }
+
+  pub fn maybe_embedded(&self) -> Option<&Arc<EmbeddedDocument>> {
+    self.maybe_embedded.as_ref()
+  }
+
   pub fn text_info(&self) -> &SourceTextInfo {
     // try to get the text info from the parsed source and if
     // not then create one in the cell
@@ -651,7 +805,7 @@ impl Document {
       .unwrap_or_else(|| {
         self
           .text_info_cell
-          .get_or_init(|| SourceTextInfo::new(self.text.clone()))
+          .get_or_init(|| SourceTextInfo::new(self.script_text().clone()))
       })
   }
 
@@ -803,6 +957,11 @@ pub fn to_lsp_range(range: &deno_graph::Range) -> lsp::Range {
 struct FileSystemDocuments {
   docs: DashMap<ModuleSpecifier, Arc<Document>>,
   dirty: AtomicBool,
+  /// The documents which were added, updated or removed since the last call to
+  /// `take_dirty_specifiers()`.
+  dirty_specifiers: Mutex<HashSet<ModuleSpecifier>>,
+  /// Whether lookups could reuse the cached document or had to refresh it.
+  cache_stats: Mutex<CacheStats>,
 }
 
 impl FileSystemDocuments {
@@ -830,6 +989,7 @@ impl FileSystemDocuments {
         }
       }
     };
+    self.cache_stats.lock().record(!dirty);
     if dirty {
       // attempt to update the file on the file system
       self.refresh_document(specifier, resolver, config, cache, file_referrer)
@@ -909,7 +1069,7 @@ impl FileSystemDocuments {
       )
     };
     self.docs.insert(specifier.clone(), doc.clone());
-    self.set_dirty(true);
+    self.dirty_specifiers.lock().insert(specifier.clone());
     Some(doc)
   }
 
@@ -917,7 +1077,13 @@ impl FileSystemDocuments {
     &self,
     specifier: &ModuleSpecifier,
   ) -> Option<Arc<Document>> {
-    Some(self.docs.remove(specifier)?.1)
+    let (specifier, doc) = self.docs.remove(specifier)?;
+    self.dirty_specifiers.lock().insert(specifier);
+    Some(doc)
+  }
+
+  fn take_dirty_specifiers(&self) -> HashSet<ModuleSpecifier> {
+    std::mem::take(&mut *self.dirty_specifiers.lock())
   }
 
   /// Sets the dirty flag to the provided value and returns the previous value.
@@ -926,6 +1092,45 @@ impl FileSystemDocuments {
   }
 }
 
+/// The npm package requirements of a single document, cached so that only the
+/// documents which changed need to be visited again.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct DocumentNpmInfo {
+  scope: Option<ModuleSpecifier>,
+  reqs: BTreeSet<PackageReq>,
+  has_node_specifier: bool,
+}
+
+impl DocumentNpmInfo {
+  fn new(doc: &Document) -> Self {
+    let mut info = Self {
+      scope: doc.scope().cloned(),
+      ..Default::default()
+    };
+    for dependency in doc.dependencies().values() {
+      if let Some(dep) = dependency.get_code() {
+        if dep.scheme() == "node" {
+          info.has_node_specifier = true;
+        }
+        if let Ok(reference) = NpmPackageReqReference::from_specifier(dep) {
+          info.reqs.insert(reference.into_inner().req);
+        }
+      }
+      if let Some(dep) = dependency.get_type() {
+        if let Ok(reference) = NpmPackageReqReference::from_specifier(dep) {
+          info.reqs.insert(reference.into_inner().req);
+        }
+      }
+    }
+    if let Some(dep) = doc.maybe_types_dependency().maybe_specifier() {
+      if let Ok(reference) = NpmPackageReqReference::from_specifier(dep) {
+        info.reqs.insert(reference.into_inner().req);
+      }
+    }
+    info
+  }
+}
+
 /// Specify the documents to include on a `documents.documents(...)` call.
 #[derive(Debug, Clone, Copy)]
 pub enum DocumentsFilter {
@@ -943,8 +1148,11 @@ pub struct Documents {
   cache: Arc<LspCache>,
   config: Arc<Config>,
   /// A flag that indicates that stated data is potentially invalid and needs to
-  /// be recalculated before being considered valid.
+  /// be recalculated for all documents before being considered valid.
   dirty: bool,
+  /// The open documents which changed since the npm package requirements were
+  /// last calculated.
+  dirty_specifiers: HashSet<ModuleSpecifier>,
   /// A map of documents that are "open" in the language server.
   open_docs: HashMap<ModuleSpecifier, Arc<Document>>,
   /// Documents stored on the file system.
@@ -952,6 +1160,9 @@ pub struct Documents {
   /// A resolver that takes into account currently loaded import map and JSX
   /// settings.
   resolver: Arc<LspResolver>,
+  /// The npm package requirements found in the npm specifiers of each
+  /// document.
+  npm_info_by_specifier: Arc<HashMap<ModuleSpecifier, Arc<DocumentNpmInfo>>>,
   /// The npm package requirements found in npm specifiers.
   npm_reqs_by_scope:
     Arc<BTreeMap<Option<ModuleSpecifier>, BTreeSet<PackageReq>>>,
@@ -989,13 +1200,17 @@ impl Documents {
     );
 
     self.file_system_docs.remove_document(&specifier);
-    self.file_system_docs.set_dirty(true);
 
+    self.dirty_specifiers.insert(specifier.clone());
     self.open_docs.insert(specifier, document.clone());
-    self.dirty = true;
     document
   }
 
+  /// The hits and misses of the cache of documents read from the file system.
+  pub fn file_system_cache_stats(&self) -> CacheStats {
+    *self.file_system_docs.cache_stats.lock()
+  }
+
   /// Apply language server content changes to an open document.
   pub fn change(
     &mut self,
@@ -1015,12 +1230,29 @@ impl Documents {
           format!("The specifier \"{specifier}\" was not found."),
         ))
       })?;
-    self.dirty = true;
+    self.dirty_specifiers.insert(specifier.clone());
     let doc = doc.with_change(version, changes)?;
     self.open_docs.insert(doc.specifier().clone(), doc.clone());
     Ok(doc)
   }
 
+  /// Sets the virtual document of an open framework file, extracted by the
+  /// language server plugins of its scope.
+  pub fn set_embedded(
+    &mut self,
+    specifier: &ModuleSpecifier,
+    maybe_embedded: Option<Arc<EmbeddedDocument>>,
+  ) -> Option<Arc<Document>> {
+    let doc = self.open_docs.get(specifier)?;
+    if doc.maybe_embedded() == maybe_embedded.as_ref() {
+      return None;
+    }
+    let doc = doc.with_embedded(maybe_embedded);
+    self.dirty_specifiers.insert(specifier.clone());
+    self.open_docs.insert(specifier.clone(), doc.clone());
+    Some(doc)
+  }
+
   pub fn save(&mut self, specifier: &ModuleSpecifier) {
     let doc = self
       .open_docs
@@ -1030,7 +1262,7 @@ impl Documents {
     let Some(doc) = doc else {
       return;
     };
-    self.dirty = true;
+    self.dirty_specifiers.insert(specifier.clone());
     let doc = doc.saved(&self.cache);
     self.open_docs.insert(doc.specifier().clone(), doc.clone());
   }
@@ -1046,7 +1278,7 @@ impl Documents {
         .docs
         .insert(specifier.clone(), document);
 
-      self.dirty = true;
+      self.dirty_specifiers.insert(specifier.clone());
     }
   }
 
@@ -1318,6 +1550,27 @@ impl Documents {
     cache: &LspCache,
     workspace_files: &IndexSet<ModuleSpecifier>,
   ) {
+    self.update_config_in_dirs(config, resolver, cache, workspace_files, None)
+  }
+
+  /// Like `update_config()`, but only the dependencies of the documents in
+  /// `changed_dirs` are resolved again when it's provided. The others only
+  /// take on the new resolver.
+  pub fn update_config_in_dirs(
+    &mut self,
+    config: &Config,
+    resolver: &Arc<LspResolver>,
+    cache: &LspCache,
+    workspace_files: &IndexSet<ModuleSpecifier>,
+    changed_dirs: Option<&[ModuleSpecifier]>,
+  ) {
+    let is_affected = |specifier: &ModuleSpecifier| {
+      changed_dirs.map_or(true, |dirs| {
+        dirs
+          .iter()
+          .any(|dir| specifier.as_str().starts_with(dir.as_str()))
+      })
+    };
     self.config = Arc::new(config.clone());
     self.cache = Arc::new(cache.clone());
     self.resolver = resolver.clone();
@@ -1340,14 +1593,21 @@ impl Documents {
         if !config.specifier_enabled(doc.specifier()) {
           continue;
         }
-        *doc = doc.with_new_config(self.resolver.clone(), self.config.clone());
+        *doc = if is_affected(doc.specifier()) {
+          doc.with_new_config(self.resolver.clone(), self.config.clone())
+        } else {
+          doc.with_new_resolver_only(self.resolver.clone(), self.config.clone())
+        };
       }
       for mut doc in self.file_system_docs.docs.iter_mut() {
         if !config.specifier_enabled(doc.specifier()) {
           continue;
         }
-        *doc.value_mut() =
-          doc.with_new_config(self.resolver.clone(), self.config.clone());
+        *doc.value_mut() = if is_affected(doc.specifier()) {
+          doc.with_new_config(self.resolver.clone(), self.config.clone())
+        } else {
+          doc.with_new_resolver_only(self.resolver.clone(), self.config.clone())
+        };
       }
       self.open_docs = open_docs;
       let mut preload_count = 0;
@@ -1371,50 +1631,72 @@ impl Documents {
           );
         }
       }
-      fs_docs.set_dirty(true);
     }
     self.dirty = true;
   }
 
-  /// Iterate through the documents, building a map where the key is a unique
-  /// document and the value is a set of specifiers that depend on that
-  /// document.
+  /// Collect the npm package requirements of the documents. Only the documents
+  /// which changed since the last calculation are visited, unless everything
+  /// was invalidated by a config change or a released document.
   fn calculate_npm_reqs_if_dirty(&mut self) {
-    let mut npm_reqs_by_scope: BTreeMap<_, BTreeSet<_>> = Default::default();
-    let mut scopes_with_specifier = HashSet::new();
+    let dirty_specifiers = std::mem::take(&mut self.dirty_specifiers)
+      .into_iter()
+      .chain(self.file_system_docs.take_dirty_specifiers())
+      .collect::<HashSet<_>>();
     let is_fs_docs_dirty = self.file_system_docs.set_dirty(false);
-    if !is_fs_docs_dirty && !self.dirty {
-      return;
-    }
-    let mut visit_doc = |doc: &Arc<Document>| {
-      let scope = doc.scope();
-      let reqs = npm_reqs_by_scope.entry(scope.cloned()).or_default();
-      for dependency in doc.dependencies().values() {
-        if let Some(dep) = dependency.get_code() {
-          if dep.scheme() == "node" {
-            scopes_with_specifier.insert(scope.cloned());
-          }
-          if let Ok(reference) = NpmPackageReqReference::from_specifier(dep) {
-            reqs.insert(reference.into_inner().req);
-          }
+    if self.dirty || is_fs_docs_dirty {
+      let mut npm_info_by_specifier = HashMap::new();
+      for entry in self.file_system_docs.docs.iter() {
+        npm_info_by_specifier.insert(
+          entry.key().clone(),
+          Arc::new(DocumentNpmInfo::new(entry.value())),
+        );
+      }
+      for (specifier, doc) in &self.open_docs {
+        npm_info_by_specifier
+          .insert(specifier.clone(), Arc::new(DocumentNpmInfo::new(doc)));
+      }
+      self.npm_info_by_specifier = Arc::new(npm_info_by_specifier);
+    } else {
+      let mut changed = false;
+      for specifier in dirty_specifiers {
+        let doc = self.open_docs.get(&specifier).cloned().or_else(|| {
+          self
+            .file_system_docs
+            .docs
+            .get(&specifier)
+            .map(|e| e.value().clone())
+        });
+        let info = doc.map(|doc| DocumentNpmInfo::new(&doc));
+        let old_info = self.npm_info_by_specifier.get(&specifier);
+        if old_info.map(|i| i.as_ref()) == info.as_ref() {
+          continue;
         }
-        if let Some(dep) = dependency.get_type() {
-          if let Ok(reference) = NpmPackageReqReference::from_specifier(dep) {
-            reqs.insert(reference.into_inner().req);
+        let npm_info_by_specifier =
+          Arc::make_mut(&mut self.npm_info_by_specifier);
+        match info {
+          Some(info) => {
+            npm_info_by_specifier.insert(specifier, Arc::new(info));
+          }
+          None => {
+            npm_info_by_specifier.remove(&specifier);
           }
         }
+        changed = true;
       }
-      if let Some(dep) = doc.maybe_types_dependency().maybe_specifier() {
-        if let Ok(reference) = NpmPackageReqReference::from_specifier(dep) {
-          reqs.insert(reference.into_inner().req);
-        }
+      if !changed {
+        return;
       }
-    };
-    for entry in self.file_system_docs.docs.iter() {
-      visit_doc(entry.value())
     }
-    for doc in self.open_docs.values() {
-      visit_doc(doc);
+
+    let mut npm_reqs_by_scope: BTreeMap<_, BTreeSet<_>> = Default::default();
+    let mut scopes_with_specifier = HashSet::new();
+    for info in self.npm_info_by_specifier.values() {
+      let reqs = npm_reqs_by_scope.entry(info.scope.clone()).or_default();
+      reqs.extend(info.reqs.iter().cloned());
+      if info.has_node_specifier {
+        scopes_with_specifier.insert(info.scope.clone());
+      }
     }
 
     // fill the reqs from the lockfile
@@ -1694,6 +1976,49 @@ console.log(b, "hello deno");
     );
   }
 
+  #[tokio::test]
+  async fn test_documents_npm_reqs_incremental() {
+    let (mut documents, _, _) = setup().await;
+    let specifier = ModuleSpecifier::parse("file:///a.ts").unwrap();
+    documents.open(
+      specifier.clone(),
+      1,
+      LanguageId::TypeScript,
+      "console.log(1);\n".into(),
+      None,
+    );
+    let npm_reqs = documents.npm_reqs_by_scope();
+    assert!(npm_reqs.values().all(|reqs| reqs.is_empty()));
+
+    let insert = |line: u32, text: &str| lsp::TextDocumentContentChangeEvent {
+      range: Some(lsp::Range {
+        start: lsp::Position { line, character: 0 },
+        end: lsp::Position { line, character: 0 },
+      }),
+      range_length: None,
+      text: text.to_string(),
+    };
+    documents
+      .change(&specifier, 2, vec![insert(0, "import \"npm:chalk@5\";\n")])
+      .unwrap();
+    let npm_reqs = documents.npm_reqs_by_scope();
+    assert_eq!(
+      npm_reqs.get(&None).unwrap().iter().collect::<Vec<_>>(),
+      vec![&PackageReq::from_str("chalk@5").unwrap()],
+    );
+
+    // edits which don't touch the npm imports keep the same reqs
+    documents
+      .change(&specifier, 3, vec![insert(2, "console.log(2);\n")])
+      .unwrap();
+    assert!(Arc::ptr_eq(&npm_reqs, &documents.npm_reqs_by_scope()));
+
+    documents.close(&specifier);
+    documents.release(&specifier);
+    let npm_reqs = documents.npm_reqs_by_scope();
+    assert!(npm_reqs.values().all(|reqs| reqs.is_empty()));
+  }
+
   #[tokio::test]
   async fn test_documents_ensure_no_duplicates() {
     // it should never happen that a user of this API causes this to happen,
@@ -1830,4 +2155,105 @@ console.log(b, "hello deno");
       );
     }
   }
+
+  #[tokio::test]
+  async fn test_documents_update_config_in_dirs() {
+    let (mut documents, cache, temp_dir) = setup().await;
+    temp_dir.create_dir_all("a");
+    temp_dir.create_dir_all("b");
+    temp_dir.write("file2.ts", "");
+    temp_dir.write("file3.ts", "");
+    let file2_specifier = temp_dir.url().join("file2.ts").unwrap();
+    let file3_specifier = temp_dir.url().join("file3.ts").unwrap();
+    let a_specifier = temp_dir.url().join("a/mod.ts").unwrap();
+    let b_specifier = temp_dir.url().join("b/mod.ts").unwrap();
+
+    let mut config = Config::new_with_roots([temp_dir.url()]);
+    let workspace_settings =
+      serde_json::from_str(r#"{ "enable": true }"#).unwrap();
+    config.set_workspace_settings(workspace_settings, vec![]);
+    let workspace_files = Default::default();
+    let import_map = |target: &str| {
+      ConfigFile::new(
+        &json!({ "imports": { "test": target } }).to_string(),
+        temp_dir.url().join("deno.json").unwrap(),
+        &ConfigParseOptions::default(),
+      )
+      .unwrap()
+    };
+    let resolved = |documents: &Documents, specifier: &ModuleSpecifier| {
+      documents
+        .get(specifier)
+        .unwrap()
+        .dependencies()
+        .get("test")
+        .unwrap()
+        .maybe_code
+        .maybe_specifier()
+        .cloned()
+    };
+
+    config
+      .tree
+      .inject_config_file(import_map("./file2.ts"))
+      .await;
+    let resolver =
+      Arc::new(LspResolver::from_config(&config, &cache, None).await);
+    documents.update_config(&config, &resolver, &cache, &workspace_files);
+    for specifier in [&a_specifier, &b_specifier] {
+      documents.open(
+        specifier.clone(),
+        1,
+        LanguageId::TypeScript,
+        "import {} from 'test';".into(),
+        None,
+      );
+    }
+
+    // only the documents in the changed directories are resolved again
+    config
+      .tree
+      .inject_config_file(import_map("./file3.ts"))
+      .await;
+    let resolver =
+      Arc::new(LspResolver::from_config(&config, &cache, None).await);
+    documents.update_config_in_dirs(
+      &config,
+      &resolver,
+      &cache,
+      &workspace_files,
+      Some(&[temp_dir.url().join("a/").unwrap()]),
+    );
+    assert_eq!(
+      resolved(&documents, &a_specifier),
+      Some(file3_specifier.clone())
+    );
+    assert_eq!(resolved(&documents, &b_specifier), Some(file2_specifier));
+
+    // and all of them without any directories
+    documents.update_config(&config, &resolver, &cache, &workspace_files);
+    assert_eq!(
+      resolved(&documents, &a_specifier),
+      Some(file3_specifier.clone())
+    );
+    assert_eq!(resolved(&documents, &b_specifier), Some(file3_specifier));
+  }
+
+  #[tokio::test]
+  async fn test_documents_release_invalidates_npm_reqs() {
+    let (mut documents, _, temp_dir) = setup().await;
+    let file_path = temp_dir.path().join("file.ts");
+    let specifier = temp_dir.url().join("file.ts").unwrap();
+    fs::write(&file_path, "import \"npm:chalk@5\";\n").unwrap();
+    documents.get_or_load(&specifier, None).unwrap();
+    let npm_reqs = documents.npm_reqs_by_scope();
+    assert_eq!(
+      npm_reqs.get(&None).unwrap().iter().collect::<Vec<_>>(),
+      vec![&PackageReq::from_str("chalk@5").unwrap()],
+    );
+
+    documents.release(&specifier);
+    let npm_reqs = documents.npm_reqs_by_scope();
+    assert!(npm_reqs.values().all(|reqs| reqs.is_empty()));
+  }
 }
@@ -0,0 +1,225 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Edits to the config files of a workspace when files are renamed or moved.
+//! These complement the edits to import specifiers in modules, which are
+//! provided by tsc.
+
+use deno_core::serde_json;
+use deno_core::ModuleSpecifier;
+use jsonc_parser::ast::Object;
+use jsonc_parser::ast::StringLit;
+use jsonc_parser::ast::Value;
+use text_size::TextSize;
+use tower_lsp::lsp_types as lsp;
+
+use super::text::LineIndex;
+use crate::util::path::relative_specifier;
+
+/// A file or directory which is renamed from `old` to `new`.
+#[derive(Debug, Clone)]
+pub struct FileRename {
+  pub old: ModuleSpecifier,
+  pub new: ModuleSpecifier,
+  pub is_dir: bool,
+}
+
+impl FileRename {
+  fn rename(&self, specifier: &ModuleSpecifier) -> Option<ModuleSpecifier> {
+    if !self.is_dir {
+      return (specifier == &self.old).then(|| self.new.clone());
+    }
+    let old_dir = dir_specifier(&self.old);
+    let rest = specifier.as_str().strip_prefix(old_dir.as_str())?;
+    dir_specifier(&self.new).join(rest).ok()
+  }
+}
+
+fn dir_specifier(specifier: &ModuleSpecifier) -> ModuleSpecifier {
+  let mut specifier = specifier.clone();
+  if !specifier.path().ends_with('/') {
+    specifier.set_path(&format!("{}/", specifier.path()));
+  }
+  specifier
+}
+
+/// Returns the edits to the `imports`, `scopes`, `exports` and `importMap`
+/// entries of a deno.json or import map file which refer to renamed files.
+pub fn get_config_file_rename_edits(
+  specifier: &ModuleSpecifier,
+  text: &str,
+  renames: &[FileRename],
+) -> Vec<lsp::TextEdit> {
+  let Ok(ast) =
+    jsonc_parser::parse_to_ast(text, &Default::default(), &Default::default())
+  else {
+    return Vec::new();
+  };
+  let Some(Value::Object(root)) = &ast.value else {
+    return Vec::new();
+  };
+  let mut literals = Vec::new();
+  if let Some(imports) = root.get_object("imports") {
+    collect_string_values(imports, &mut literals);
+  }
+  if let Some(scopes) = root.get_object("scopes") {
+    for prop in &scopes.properties {
+      if let Value::Object(scope) = &prop.value {
+        collect_string_values(scope, &mut literals);
+      }
+    }
+  }
+  match root.get("exports").map(|p| &p.value) {
+    Some(Value::StringLit(lit)) => literals.push(lit),
+    Some(Value::Object(exports)) => {
+      collect_string_values(exports, &mut literals)
+    }
+    _ => {}
+  }
+  if let Some(lit) = root.get_string("importMap") {
+    literals.push(lit);
+  }
+
+  let line_index = LineIndex::new(text);
+  let to_position = |offset: usize| {
+    let utf16_offset = text[..offset].encode_utf16().count() as u32;
+    line_index.position_tsc(TextSize::from(utf16_offset))
+  };
+  literals
+    .into_iter()
+    .filter_map(|lit| {
+      let new_value = renamed_value(specifier, &lit.value, renames)?;
+      Some(lsp::TextEdit {
+        range: lsp::Range {
+          start: to_position(lit.range.start),
+          end: to_position(lit.range.end),
+        },
+        new_text: serde_json::to_string(&new_value).ok()?,
+      })
+    })
+    .collect()
+}
+
+fn collect_string_values<'a>(
+  object: &'a Object<'a>,
+  literals: &mut Vec<&'a StringLit<'a>>,
+) {
+  for prop in &object.properties {
+    if let Value::StringLit(lit) = &prop.value {
+      literals.push(lit);
+    }
+  }
+}
+
+/// Only relative paths and file urls are rewritten, keeping their form.
+fn renamed_value(
+  base: &ModuleSpecifier,
+  value: &str,
+  renames: &[FileRename],
+) -> Option<String> {
+  let is_relative = value.starts_with("./") || value.starts_with("../");
+  if !is_relative && !value.starts_with("file:") {
+    return None;
+  }
+  let specifier = base.join(value).ok()?;
+  let new_specifier = renames.iter().find_map(|r| r.rename(&specifier))?;
+  if is_relative {
+    relative_specifier(base, &new_specifier)
+  } else {
+    Some(new_specifier.to_string())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn rename(old: &str, new: &str, is_dir: bool) -> FileRename {
+    FileRename {
+      old: ModuleSpecifier::parse(old).unwrap(),
+      new: ModuleSpecifier::parse(new).unwrap(),
+      is_dir,
+    }
+  }
+
+  fn apply_edits(text: &str, edits: Vec<lsp::TextEdit>) -> String {
+    let line_index = LineIndex::new(text);
+    let mut text = text.to_string();
+    for edit in edits.into_iter().rev() {
+      let range = line_index.get_text_range(edit.range).unwrap();
+      text.replace_range(
+        usize::from(range.start())..usize::from(range.end()),
+        &edit.new_text,
+      );
+    }
+    text
+  }
+
+  #[test]
+  fn test_get_config_file_rename_edits_file() {
+    let specifier = ModuleSpecifier::parse("file:///a/deno.json").unwrap();
+    let text = r#"{
+  // comment
+  "imports": {
+    "@std/assert": "jsr:@std/assert@1",
+    "util": "./src/util.ts",
+    "other": "./src/other.ts"
+  },
+  "scopes": {
+    "./vendor/": { "util": "file:///a/src/util.ts" }
+  },
+  "exports": "./src/util.ts"
+}
+"#;
+    let edits = get_config_file_rename_edits(
+      &specifier,
+      text,
+      &[rename(
+        "file:///a/src/util.ts",
+        "file:///a/lib/ütil.ts",
+        false,
+      )],
+    );
+    assert_eq!(
+      apply_edits(text, edits),
+      r#"{
+  // comment
+  "imports": {
+    "@std/assert": "jsr:@std/assert@1",
+    "util": "./lib/ütil.ts",
+    "other": "./src/other.ts"
+  },
+  "scopes": {
+    "./vendor/": { "util": "file:///a/lib/%C3%BCtil.ts" }
+  },
+  "exports": "./lib/ütil.ts"
+}
+"#
+    );
+  }
+
+  #[test]
+  fn test_get_config_file_rename_edits_dir() {
+    let specifier = ModuleSpecifier::parse("file:///a/deno.json").unwrap();
+    let text = r#"{
+  "importMap": "./maps/import_map.json",
+  "imports": { "src/": "./src/", "mod": "./src/mod.ts", "x": "./srcx.ts" },
+  "exports": { ".": "./src/mod.ts", "./other": "./other.ts" }
+}"#;
+    let edits = get_config_file_rename_edits(
+      &specifier,
+      text,
+      &[
+        rename("file:///a/src", "file:///a/lib", true),
+        rename("file:///a/maps", "file:///a/config/maps", true),
+      ],
+    );
+    assert_eq!(
+      apply_edits(text, edits),
+      r#"{
+  "importMap": "./config/maps/import_map.json",
+  "imports": { "src/": "./lib/", "mod": "./lib/mod.ts", "x": "./srcx.ts" },
+  "exports": { ".": "./lib/mod.ts", "./other": "./other.ts" }
+}"#
+    );
+  }
+}
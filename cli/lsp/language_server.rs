@@ -19,6 +19,7 @@ use deno_runtime::deno_tls::rustls::RootCertStore;
 use deno_runtime::deno_tls::RootCertStoreProvider;
 use deno_runtime::fs_util::specifier_to_file_path;
 use deno_semver::jsr::JsrPackageReqReference;
+use deno_semver::package::PackageReq;
 use indexmap::Equivalent;
 use indexmap::IndexSet;
 use log::error;
@@ -34,6 +35,7 @@ use std::fmt::Write as _;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc::unbounded_channel;
 use tokio::sync::mpsc::UnboundedReceiver;
 use tokio::sync::mpsc::UnboundedSender;
@@ -41,11 +43,13 @@ use tower_lsp::jsonrpc::Error as LspError;
 use tower_lsp::jsonrpc::Result as LspResult;
 use tower_lsp::lsp_types::request::*;
 use tower_lsp::lsp_types::*;
+use uuid::Uuid;
 
 use super::analysis::fix_ts_import_changes;
 use super::analysis::ts_changes_to_edit;
 use super::analysis::CodeActionCollection;
 use super::analysis::CodeActionData;
+use super::analysis::OrganizeImportsCodeActionData;
 use super::analysis::TsResponseImportMapper;
 use super::cache::LspCache;
 use super::capabilities;
@@ -56,6 +60,7 @@ use super::config::Config;
 use super::config::UpdateImportsOnFileMoveEnabled;
 use super::config::WorkspaceSettings;
 use super::config::SETTINGS_SECTION;
+use super::config_file;
 use super::diagnostics;
 use super::diagnostics::DiagnosticDataSpecifier;
 use super::diagnostics::DiagnosticServerUpdateMessage;
@@ -67,6 +72,8 @@ use super::documents::Document;
 use super::documents::Documents;
 use super::documents::DocumentsFilter;
 use super::documents::LanguageId;
+use super::file_rename;
+use super::file_rename::FileRename;
 use super::jsr::CliJsrSearchApi;
 use super::logging::lsp_log;
 use super::logging::lsp_warn;
@@ -74,10 +81,12 @@ use super::lsp_custom;
 use super::lsp_custom::TaskDefinition;
 use super::npm::CliNpmSearchApi;
 use super::parent_process_checker;
+use super::performance::CacheStats;
 use super::performance::Performance;
 use super::refactor;
 use super::registries::ModuleRegistry;
 use super::resolver::LspResolver;
+use super::semantic_tokens::SemanticTokensResults;
 use super::testing;
 use super::text;
 use super::tsc;
@@ -90,6 +99,8 @@ use super::urls;
 use super::urls::uri_to_url;
 use super::urls::url_to_uri;
 use crate::args::create_default_npmrc;
+use crate::args::deno_json::CliDenoConfigFs;
+use crate::args::deno_json::TaskConfig;
 use crate::args::get_root_cert_store;
 use crate::args::has_flag_env_var;
 use crate::args::CaData;
@@ -107,6 +118,7 @@ use crate::lsp::tsc::file_text_changes_to_workspace_edit;
 use crate::lsp::urls::LspUrlKind;
 use crate::tools::fmt::format_file;
 use crate::tools::fmt::format_parsed_source;
+use crate::tools::fmt::sort_imports;
 use crate::tools::upgrade::check_for_upgrades_for_lsp;
 use crate::tools::upgrade::upgrade_check_enabled;
 use crate::util::fs::remove_dir_all_if_exists;
@@ -114,6 +126,11 @@ use crate::util::path::is_importable_ext;
 use crate::util::path::to_percent_decoded_str;
 use crate::util::sync::AsyncFlag;
 
+/// How long caching runs before a server initiated progress is shown for it,
+/// so caching modules that are already cached, like on every save, doesn't
+/// create one.
+const CACHE_PROGRESS_DELAY: Duration = Duration::from_millis(500);
+
 struct LspRootCertStoreProvider(RootCertStore);
 
 impl RootCertStoreProvider for LspRootCertStoreProvider {
@@ -135,6 +152,8 @@ pub struct LanguageServer {
   init_flag: AsyncFlag,
   performance: Arc<Performance>,
   shutdown_flag: AsyncFlag,
+  /// The file to write the profile to on shutdown, from `deno lsp --profile`.
+  maybe_profile_path: Option<PathBuf>,
 }
 
 /// Snapshot of the state used by TSC.
@@ -185,6 +204,8 @@ impl LanguageServerTaskQueue {
   }
 }
 
+type NpmReqsByScope = BTreeMap<Option<ModuleSpecifier>, BTreeSet<PackageReq>>;
+
 #[derive(Debug)]
 pub struct Inner {
   /// Cached versions of "fixed" assets that can either be inlined in Rust or
@@ -212,6 +233,10 @@ pub struct Inner {
   /// A collection of measurements which instrument that performance of the LSP.
   performance: Arc<Performance>,
   resolver: Arc<LspResolver>,
+  /// The npm package requirements last set on the resolver, so they're only
+  /// set again when they change.
+  resolver_npm_reqs: Option<(Arc<LspResolver>, Arc<NpmReqsByScope>)>,
+  semantic_tokens_results: SemanticTokensResults,
   task_queue: LanguageServerTaskQueue,
   /// A memoized version of fixable diagnostic codes retrieved from TypeScript.
   ts_fixable_diagnostics: Vec<String>,
@@ -226,7 +251,11 @@ pub struct Inner {
 }
 
 impl LanguageServer {
-  pub fn new(client: Client, shutdown_flag: AsyncFlag) -> Self {
+  pub fn new(
+    client: Client,
+    shutdown_flag: AsyncFlag,
+    maybe_profile_path: Option<PathBuf>,
+  ) -> Self {
     let performance = Arc::new(Performance::default());
     Self {
       client: client.clone(),
@@ -237,16 +266,21 @@ impl LanguageServer {
       init_flag: Default::default(),
       performance,
       shutdown_flag,
+      maybe_profile_path,
     }
   }
 
   /// Similar to `deno install --entrypoint` on the command line, where modules will be cached
   /// in the Deno cache, including any of their dependencies.
+  ///
+  /// The progress is reported with `work_done_token` when the client provided
+  /// one, otherwise with a server initiated token if the client supports it.
   pub async fn cache(
     &self,
     specifiers: Vec<ModuleSpecifier>,
     referrer: ModuleSpecifier,
     force_global_cache: bool,
+    work_done_token: Option<ProgressToken>,
   ) -> LspResult<Option<Value>> {
     async fn create_graph_for_caching(
       cli_options: CliOptions,
@@ -300,11 +334,18 @@ impl LanguageServer {
     let mark = self
       .performance
       .mark_with_args("lsp.cache", (&specifiers, &referrer));
-    let prepare_cache_result = self.inner.write().await.prepare_cache(
-      specifiers,
-      referrer,
-      force_global_cache,
-    );
+    let progress_message = match specifiers.as_slice() {
+      [] => referrer.to_string(),
+      [specifier] => specifier.to_string(),
+      specifiers => format!("{} modules", specifiers.len()),
+    };
+    let (prepare_cache_result, work_done_progress_capable) = {
+      let mut inner = self.inner.write().await;
+      (
+        inner.prepare_cache(specifiers, referrer, force_global_cache),
+        inner.config.work_done_progress_capable(),
+      )
+    };
 
     match prepare_cache_result {
       Ok(result) => {
@@ -312,11 +353,63 @@ impl LanguageServer {
         let cli_options = result.cli_options;
         let roots = result.roots;
         let open_docs = result.open_docs;
-        let handle = spawn(async move {
+        let client = self.client.when_outside_lsp_lock();
+        let mut handle = spawn(async move {
           create_graph_for_caching(cli_options, roots, open_docs).await
         });
-
-        if let Err(err) = handle.await.unwrap() {
+        let mut cache_result = None;
+        let progress_token = match work_done_token {
+          Some(token) => Some(token),
+          None if work_done_progress_capable => {
+            match tokio::time::timeout(CACHE_PROGRESS_DELAY, &mut handle).await
+            {
+              Ok(result) => {
+                cache_result = Some(result.unwrap());
+                None
+              }
+              Err(_) => {
+                let token = ProgressToken::String(format!(
+                  "deno/cache/{}",
+                  Uuid::new_v4()
+                ));
+                match client.create_work_done_progress(token.clone()).await {
+                  Ok(()) => Some(token),
+                  Err(err) => {
+                    lsp_warn!("Unable to create progress: {:#}", err);
+                    None
+                  }
+                }
+              }
+            }
+          }
+          None => None,
+        };
+        if let Some(token) = &progress_token {
+          client
+            .send_progress(
+              token.clone(),
+              WorkDoneProgress::Begin(WorkDoneProgressBegin {
+                title: "Caching dependencies".to_string(),
+                cancellable: Some(false),
+                message: Some(progress_message),
+                percentage: None,
+              }),
+            )
+            .await;
+        }
+        let cache_result = match cache_result {
+          Some(cache_result) => cache_result,
+          None => handle.await.unwrap(),
+        };
+        if let Some(token) = progress_token {
+          client
+            .send_progress(
+              token,
+              WorkDoneProgress::End(WorkDoneProgressEnd { message: None }),
+            )
+            .await;
+        }
+        if let Err(err) = cache_result {
           lsp_warn!("Error caching: {:#}", err);
           self.client.show_message(MessageType::WARNING, err);
         }
@@ -361,6 +454,13 @@ impl LanguageServer {
     Ok(Some(self.inner.read().await.get_performance()))
   }
 
+  pub async fn profile_request(&self) -> LspResult<Option<Value>> {
+    if !self.init_flag.is_raised() {
+      self.init_flag.wait_raised().await;
+    }
+    Ok(Some(self.inner.read().await.get_profile().await))
+  }
+
   pub async fn task_definitions(&self) -> LspResult<Vec<TaskDefinition>> {
     if !self.init_flag.is_raised() {
       self.init_flag.wait_raised().await;
@@ -499,6 +599,8 @@ impl Inner {
       npm_search_api,
       performance,
       resolver: Default::default(),
+      resolver_npm_reqs: None,
+      semantic_tokens_results: Default::default(),
       ts_fixable_diagnostics: Default::default(),
       ts_server,
       url_map: Default::default(),
@@ -596,6 +698,19 @@ impl Inner {
     }
   }
 
+  /// Like `Config::specifier_enabled()`, but dependency modules which don't
+  /// belong to the workspace (remote modules and npm packages in the global
+  /// cache) follow the settings of the workspace file that referred to them.
+  fn specifier_enabled_for_dependency(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> bool {
+    match self.documents.get_file_referrer(specifier) {
+      Some(file_referrer) => self.config.specifier_enabled(&file_referrer),
+      None => self.config.specifier_enabled(specifier),
+    }
+  }
+
   pub fn snapshot(&self) -> Arc<StateSnapshot> {
     Arc::new(StateSnapshot {
       project_version: self.project_version,
@@ -762,6 +877,11 @@ impl Inner {
       self.config.set_client_capabilities(params.capabilities);
     }
 
+    if self.config.pull_diagnostics_capable() {
+      self
+        .diagnostics_server
+        .set_pull_mode(self.config.diagnostic_refresh_capable());
+    }
     self.diagnostics_server.start();
     if let Err(e) = self
       .ts_server
@@ -988,7 +1108,9 @@ impl Inner {
               };
               specifier
             };
-            if let Err(err) = ls.cache(vec![specifier], referrer, false).await {
+            if let Err(err) =
+              ls.cache(vec![specifier], referrer, false, None).await
+            {
               lsp_warn!("{:#}", err);
             }
           });
@@ -1009,13 +1131,30 @@ impl Inner {
     );
   }
 
-  async fn refresh_documents_config(&mut self) {
-    self.documents.update_config(
+  /// Updates the documents for a new config. When `changed_dirs` is provided,
+  /// only the documents in those directories are resolved again.
+  async fn refresh_documents_config(
+    &mut self,
+    changed_dirs: Option<&[ModuleSpecifier]>,
+  ) {
+    self.documents.update_config_in_dirs(
       &self.config,
       &self.resolver,
       &self.cache,
       &self.workspace_files,
+      changed_dirs,
     );
+    // the plugins of the scopes may have changed
+    let open_specifiers = self
+      .documents
+      .documents(DocumentsFilter::All)
+      .into_iter()
+      .filter(|d| d.is_open())
+      .map(|d| d.specifier().clone())
+      .collect::<Vec<_>>();
+    for specifier in open_specifiers {
+      self.refresh_embedded_document(&specifier).await;
+    }
 
     // refresh the npm specifiers because it might have discovered
     // a @types/node package and now's a good time to do that anyway
@@ -1024,6 +1163,38 @@ impl Inner {
     self.project_changed([], true);
   }
 
+  /// Extracts the virtual document of an open framework file with the
+  /// language server plugins of its scope, returning the updated document
+  /// when it changed. The plugins run on their own thread and time out, so a
+  /// hanging plugin doesn't block the language server.
+  async fn refresh_embedded_document(
+    &mut self,
+    specifier: &ModuleSpecifier,
+  ) -> Option<Arc<Document>> {
+    let document = self.documents.get(specifier)?;
+    let maybe_plugins = self
+      .config
+      .tree
+      .data_for_specifier(specifier)
+      .and_then(|data| data.lsp_plugins.clone())
+      .filter(|plugins| plugins.handles(specifier));
+    let Some(plugins) = maybe_plugins else {
+      return self.documents.set_embedded(specifier, None);
+    };
+    let maybe_embedded = match plugins
+      .extract(specifier, document.content())
+      .await
+    {
+      Some(Ok(embedded)) => Some(Arc::new(embedded)),
+      Some(Err(err)) => {
+        lsp_warn!("Failed to extract the scripts of \"{specifier}\": {err:#}");
+        None
+      }
+      None => None,
+    };
+    self.documents.set_embedded(specifier, maybe_embedded)
+  }
+
   async fn did_open(&mut self, params: DidOpenTextDocumentParams) {
     let mark = self.performance.mark_with_args("lsp.did_open", &params);
     let Some(scheme) = params.text_document.uri.scheme() else {
@@ -1056,13 +1227,16 @@ impl Inner {
     let specifier = self
       .url_map
       .uri_to_specifier(&params.text_document.uri, LspUrlKind::File);
-    let document = self.documents.open(
+    let mut document = self.documents.open(
       specifier.clone(),
       params.text_document.version,
       params.text_document.language_id.parse().unwrap(),
       params.text_document.text.into(),
       file_referrer,
     );
+    if let Some(embedded) = self.refresh_embedded_document(&specifier).await {
+      document = embedded;
+    }
     if document.is_diagnosable() {
       self.project_changed([(document.specifier(), ChangeKind::Opened)], false);
       self.refresh_npm_specifiers().await;
@@ -1083,7 +1257,11 @@ impl Inner {
       params.text_document.version,
       params.content_changes,
     ) {
-      Ok(document) => {
+      Ok(mut document) => {
+        if let Some(embedded) = self.refresh_embedded_document(&specifier).await
+        {
+          document = embedded;
+        }
         if document.is_diagnosable() {
           let old_scopes_with_node_specifier =
             self.documents.scopes_with_node_specifier().clone();
@@ -1131,7 +1309,8 @@ impl Inner {
     }
     self.task_queue.queue_task(Box::new(|ls: LanguageServer| {
       spawn(async move {
-        if let Err(err) = ls.cache(vec![], specifier.clone(), false).await {
+        if let Err(err) = ls.cache(vec![], specifier.clone(), false, None).await
+        {
           lsp_warn!("Failed to cache \"{}\" on save: {:#}", &specifier, err);
         }
       });
@@ -1140,6 +1319,13 @@ impl Inner {
 
   async fn refresh_npm_specifiers(&mut self) {
     let package_reqs = self.documents.npm_reqs_by_scope();
+    if let Some((resolver, reqs)) = &self.resolver_npm_reqs {
+      if Arc::ptr_eq(resolver, &self.resolver) && reqs == &package_reqs {
+        return;
+      }
+    }
+    self.resolver_npm_reqs =
+      Some((self.resolver.clone(), package_reqs.clone()));
     let resolver = self.resolver.clone();
     // spawn due to the lsp's `Send` requirement
     spawn(async move { resolver.set_npm_reqs(&package_reqs).await })
@@ -1162,6 +1348,7 @@ impl Inner {
       .url_map
       .uri_to_specifier(&params.text_document.uri, LspUrlKind::File);
     self.diagnostics_state.clear(&specifier);
+    self.semantic_tokens_results.remove(&specifier);
     if self.is_diagnosable(&specifier) {
       self.refresh_npm_specifiers().await;
       self.diagnostics_server.invalidate(&[specifier.clone()]);
@@ -1170,6 +1357,9 @@ impl Inner {
     }
     self.documents.close(&specifier);
     self.project_changed([(&specifier, ChangeKind::Closed)], false);
+    if self.config.tree.import_map_files().contains(&specifier) {
+      self.send_diagnostics_update();
+    }
     self.performance.measure(mark);
   }
 
@@ -1197,7 +1387,7 @@ impl Inner {
     self.refresh_config_tree().await;
     self.update_cache();
     self.refresh_resolver().await;
-    self.refresh_documents_config().await;
+    self.refresh_documents_config(None).await;
     self.diagnostics_server.invalidate_all();
     self.send_diagnostics_update();
     self.send_testing_update();
@@ -1242,12 +1432,22 @@ impl Inner {
           })
         })
       }));
+      let mut changed_dirs = self
+        .config
+        .tree
+        .watched_file_change_dirs(changes.iter().map(|(s, _)| s));
       self.workspace_files_hash = 0;
       self.refresh_workspace_files();
       self.refresh_config_tree().await;
+      changed_dirs.extend(
+        self
+          .config
+          .tree
+          .watched_file_change_dirs(changes.iter().map(|(s, _)| s)),
+      );
       self.update_cache();
       self.refresh_resolver().await;
-      self.refresh_documents_config().await;
+      self.refresh_documents_config(Some(&changed_dirs)).await;
       self.project_changed(
         changes.iter().map(|(s, _)| (s, ChangeKind::Modified)),
         false,
@@ -1379,11 +1579,9 @@ impl Inner {
         .clone();
       fmt_options.use_tabs = Some(!params.options.insert_spaces);
       fmt_options.indent_width = Some(params.options.tab_size as u8);
-      let maybe_workspace = self
-        .config
-        .tree
-        .data_for_specifier(&specifier)
-        .map(|d| &d.member_dir.workspace);
+      let maybe_config_data = self.config.tree.data_for_specifier(&specifier);
+      let maybe_workspace = maybe_config_data.map(|d| &d.member_dir.workspace);
+      let sort_imports = maybe_config_data.is_some_and(|d| d.fmt_sort_imports);
       let unstable_options = UnstableFmtOptions {
         css: maybe_workspace
           .map(|w| w.has_unstable("fmt-css"))
@@ -1400,10 +1598,24 @@ impl Inner {
       };
       let document = document.clone();
       move || {
-        let format_result = match document.maybe_parsed_source() {
-          Some(Ok(parsed_source)) => {
+        // the parsed source of a framework file is the virtual document of
+        // its scripts, so the file itself is formatted instead
+        let maybe_parsed_source = document
+          .maybe_parsed_source()
+          .filter(|_| !document.is_embedded());
+        let format_result = match maybe_parsed_source {
+          Some(Ok(parsed_source)) if !sort_imports => {
             format_parsed_source(parsed_source, &fmt_options)
           }
+          // sorting imports requires reparsing the sorted text
+          Some(Ok(_)) => format_file(
+            &file_path,
+            document.content(),
+            &fmt_options,
+            sort_imports,
+            &unstable_options,
+            None,
+          ),
           Some(Err(err)) => Err(anyhow!("{:#}", err)),
           None => {
             // the file path is only used to determine what formatter should
@@ -1419,6 +1631,7 @@ impl Inner {
               &file_path,
               document.content(),
               &fmt_options,
+              sort_imports,
               &unstable_options,
               None,
             )
@@ -1458,6 +1671,24 @@ impl Inner {
       &params.text_document_position_params.text_document.uri,
       LspUrlKind::File,
     );
+    if let Some((_, file_type)) = self.config.tree.watched_file_type(&specifier)
+    {
+      if !self.config.specifier_enabled(&specifier) {
+        return Ok(None);
+      }
+      let Some(document) = self.documents.get(&specifier) else {
+        return Ok(None);
+      };
+      let mark = self.performance.mark_with_args("lsp.hover", &params);
+      let hover = config_file::get_hover(
+        file_type,
+        document.content(),
+        &document.line_index(),
+        params.text_document_position_params.position,
+      );
+      self.performance.measure(mark);
+      return Ok(hover);
+    }
     if !self.is_diagnosable(&specifier)
       || !self.config.specifier_enabled(&specifier)
     {
@@ -1588,6 +1819,11 @@ impl Inner {
     let specifier = self
       .url_map
       .uri_to_specifier(&params.text_document.uri, LspUrlKind::File);
+    if self.config.tree.import_map_files().contains(&specifier)
+      && self.config.specifier_enabled(&specifier)
+    {
+      return self.import_map_code_action(&specifier, &params);
+    }
     if !self.is_diagnosable(&specifier)
       || !self.config.specifier_enabled(&specifier)
     {
@@ -1769,6 +2005,26 @@ impl Inner {
         .map(CodeActionOrCommand::CodeAction),
     );
 
+    // Source
+    let organize_imports_requested =
+      params.context.only.as_ref().is_some_and(|only| {
+        only.iter().any(|kind| {
+          CodeActionKind::SOURCE_ORGANIZE_IMPORTS
+            .as_str()
+            .starts_with(kind.as_str())
+        })
+      });
+    if organize_imports_requested {
+      all_actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+        title: "Organize Imports".to_string(),
+        kind: Some(CodeActionKind::SOURCE_ORGANIZE_IMPORTS),
+        data: Some(json!(OrganizeImportsCodeActionData {
+          specifier: specifier.clone(),
+        })),
+        ..Default::default()
+      }));
+    }
+
     let code_action_disabled_capable =
       self.config.code_action_disabled_capable();
     let actions: Vec<CodeActionOrCommand> = all_actions.into_iter().filter(|ca| {
@@ -1785,6 +2041,30 @@ impl Inner {
     Ok(response)
   }
 
+  /// Quick fixes for the diagnostics of the entries of a deno.json or import
+  /// map file.
+  fn import_map_code_action(
+    &self,
+    specifier: &ModuleSpecifier,
+    params: &CodeActionParams,
+  ) -> LspResult<Option<CodeActionResponse>> {
+    let mut code_actions = CodeActionCollection::default();
+    for diagnostic in &params.context.diagnostics {
+      if diagnostic.source.as_deref() == Some("deno")
+        && diagnostics::DenoDiagnostic::is_fixable(diagnostic)
+      {
+        code_actions
+          .add_deno_fix_action(specifier, diagnostic)
+          .map_err(|err| {
+            error!("{:#}", err);
+            LspError::internal_error()
+          })?;
+      }
+    }
+    let actions = code_actions.get_response();
+    Ok((!actions.is_empty()).then_some(actions))
+  }
+
   async fn code_action_resolve(
     &self,
     params: CodeAction,
@@ -1898,6 +2178,30 @@ impl Inner {
         })?
       }
       code_action.edit = refactor_edit_info.to_workspace_edit(self)?;
+      if self.config.rename_command_capable() {
+        code_action.command = refactor_edit_info
+          .rename_position(self)
+          .and_then(|(specifier, position)| {
+            Some(Command {
+              title: "Rename".to_string(),
+              command: "deno.client.rename".to_string(),
+              arguments: Some(vec![
+                json!(url_to_uri(&specifier).ok()?),
+                json!(position),
+              ]),
+            })
+          });
+      }
+      code_action
+    } else if kind == CodeActionKind::SOURCE_ORGANIZE_IMPORTS {
+      let action_data: OrganizeImportsCodeActionData = from_value(data)
+        .map_err(|err| {
+          error!("Unable to decode code action data: {:#}", err);
+          LspError::invalid_params("The CodeAction's data is invalid.")
+        })?;
+      let mut code_action = params;
+      code_action.edit =
+        self.organize_imports_edit(&action_data.specifier).await?;
       code_action
     } else {
       // The code action doesn't need to be resolved
@@ -1908,6 +2212,78 @@ impl Inner {
     Ok(result)
   }
 
+  /// Removes unused and merges duplicate imports with tsc, then groups and
+  /// sorts the import declarations the same way `deno fmt` does so that
+  /// formatting the result afterwards doesn't move them again.
+  async fn organize_imports_edit(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> LspResult<Option<WorkspaceEdit>> {
+    let asset_or_doc = self.get_asset_or_document(specifier)?;
+    let line_index = asset_or_doc.line_index();
+    let changes = self
+      .ts_server
+      .organize_imports(
+        self.snapshot(),
+        specifier.clone(),
+        (&self.config.tree.fmt_config_for_specifier(specifier).options).into(),
+        tsc::UserPreferences {
+          organize_imports_ignore_case: Some(true),
+          ..tsc::UserPreferences::from_config_for_specifier(
+            &self.config,
+            specifier,
+          )
+        },
+        asset_or_doc.scope().cloned(),
+      )
+      .await?;
+    let mut text_changes = changes
+      .into_iter()
+      .filter(|changes| changes.file_name == specifier.as_str())
+      .flat_map(|changes| changes.text_changes)
+      .collect::<Vec<_>>();
+    text_changes.sort_by_key(|change| std::cmp::Reverse(change.span.start));
+    let original_text = asset_or_doc.text();
+    let mut text = original_text.to_string();
+    for change in text_changes {
+      let range = line_index
+        .get_text_range(change.span.to_range(line_index.clone()))
+        .map_err(|err| {
+          error!("Unable to apply organize imports change: {:#}", err);
+          LspError::internal_error()
+        })?;
+      text.replace_range(
+        usize::from(range.start())..usize::from(range.end()),
+        &change.new_text,
+      );
+    }
+    let ext = asset_or_doc.media_type().as_ts_extension();
+    if let Some(sorted_text) = sort_imports(&text, &ext[1..]) {
+      text = sorted_text;
+    }
+    let edits = text::get_edits(&original_text, &text, &line_index);
+    if edits.is_empty() {
+      return Ok(None);
+    }
+    let uri = self
+      .url_map
+      .specifier_to_uri(specifier, asset_or_doc.file_referrer())
+      .map_err(|err| {
+        error!("{:#}", err);
+        LspError::internal_error()
+      })?;
+    Ok(Some(WorkspaceEdit {
+      document_changes: Some(DocumentChanges::Edits(vec![TextDocumentEdit {
+        text_document: OptionalVersionedTextDocumentIdentifier {
+          uri,
+          version: asset_or_doc.document_lsp_version(),
+        },
+        edits: edits.into_iter().map(OneOf::Left).collect(),
+      }])),
+      ..Default::default()
+    }))
+  }
+
   pub fn get_ts_response_import_mapper(
     &self,
     file_referrer: &ModuleSpecifier,
@@ -2013,6 +2389,118 @@ impl Inner {
     result
   }
 
+  async fn diagnostic(
+    &self,
+    params: DocumentDiagnosticParams,
+  ) -> LspResult<DocumentDiagnosticReportResult> {
+    let specifier = self
+      .url_map
+      .uri_to_specifier(&params.text_document.uri, LspUrlKind::File);
+    let mark = self.performance.mark_with_args("lsp.diagnostic", &params);
+    let pulled = self
+      .diagnostics_server
+      .pull(self.snapshot(), vec![specifier])
+      .await
+      .map_err(|err| {
+        error!("Unable to get diagnostics: {:#}", err);
+        LspError::internal_error()
+      })?;
+    let report = match pulled.into_iter().next() {
+      Some(pulled) => {
+        match pulled.into_report(params.previous_result_id.as_deref()) {
+          DocumentDiagnosticReportKind::Full(report) => {
+            DocumentDiagnosticReport::Full(
+              RelatedFullDocumentDiagnosticReport {
+                related_documents: None,
+                full_document_diagnostic_report: report,
+              },
+            )
+          }
+          DocumentDiagnosticReportKind::Unchanged(report) => {
+            DocumentDiagnosticReport::Unchanged(
+              RelatedUnchangedDocumentDiagnosticReport {
+                related_documents: None,
+                unchanged_document_diagnostic_report: report,
+              },
+            )
+          }
+        }
+      }
+      None => DocumentDiagnosticReport::Full(Default::default()),
+    };
+    self.performance.measure(mark);
+    Ok(DocumentDiagnosticReportResult::Report(report))
+  }
+
+  /// The diagnostics of all open documents, including deno.json and import
+  /// map files.
+  async fn workspace_diagnostic(
+    &self,
+    params: WorkspaceDiagnosticParams,
+  ) -> LspResult<WorkspaceDiagnosticReportResult> {
+    let mark = self
+      .performance
+      .mark_with_args("lsp.workspace_diagnostic", &params);
+    let import_map_files = self.config.tree.import_map_files();
+    let specifiers = self
+      .documents
+      .documents(DocumentsFilter::OpenDiagnosable)
+      .into_iter()
+      .map(|d| d.specifier().clone())
+      .chain(import_map_files.into_iter().cloned())
+      .collect::<IndexSet<_>>();
+    let previous_result_ids = params
+      .previous_result_ids
+      .into_iter()
+      .map(|r| (r.uri, r.value))
+      .collect::<HashMap<_, _>>();
+    let pulled = self
+      .diagnostics_server
+      .pull(self.snapshot(), specifiers.into_iter().collect())
+      .await
+      .map_err(|err| {
+        error!("Unable to get diagnostics: {:#}", err);
+        LspError::internal_error()
+      })?;
+    let mut items = Vec::with_capacity(pulled.len());
+    for pulled in pulled {
+      let file_referrer = self.documents.get_file_referrer(&pulled.specifier);
+      let Ok(uri) = self
+        .url_map
+        .specifier_to_uri(&pulled.specifier, file_referrer.as_deref())
+      else {
+        continue;
+      };
+      let version = pulled.version.map(|v| v as i64);
+      let previous_result_id =
+        previous_result_ids.get(&uri).map(|id| id.as_str());
+      items.push(match pulled.into_report(previous_result_id) {
+        DocumentDiagnosticReportKind::Full(report) => {
+          WorkspaceDocumentDiagnosticReport::Full(
+            WorkspaceFullDocumentDiagnosticReport {
+              uri,
+              version,
+              full_document_diagnostic_report: report,
+            },
+          )
+        }
+        DocumentDiagnosticReportKind::Unchanged(report) => {
+          WorkspaceDocumentDiagnosticReport::Unchanged(
+            WorkspaceUnchangedDocumentDiagnosticReport {
+              uri,
+              version,
+              unchanged_document_diagnostic_report: report,
+            },
+          )
+        }
+      });
+    }
+    self.performance.measure(mark);
+    Ok(WorkspaceDiagnosticReportResult::Report(
+      WorkspaceDiagnosticReport { items },
+    ))
+  }
+
   async fn document_highlight(
     &self,
     params: DocumentHighlightParams,
@@ -2206,6 +2694,28 @@ impl Inner {
       &params.text_document_position.text_document.uri,
       LspUrlKind::File,
     );
+    if let Some((_, file_type)) = self.config.tree.watched_file_type(&specifier)
+    {
+      if !self.config.specifier_enabled(&specifier) {
+        return Ok(None);
+      }
+      let Some(document) = self.documents.get(&specifier) else {
+        return Ok(None);
+      };
+      let mark = self.performance.mark_with_args("lsp.completion", &params);
+      let response = config_file::get_completions(
+        &specifier,
+        file_type,
+        document.content(),
+        &document.line_index(),
+        params.text_document_position.position,
+        || {
+          diagnostics::collect_workspace_imports(&self.snapshot(), &self.config)
+        },
+      );
+      self.performance.measure(mark);
+      return Ok(response);
+    }
     let language_settings =
       self.config.language_settings_for_specifier(&specifier);
     if !self.is_diagnosable(&specifier)
@@ -2483,6 +2993,40 @@ impl Inner {
     Ok(response)
   }
 
+  async fn linked_editing_range(
+    &self,
+    params: LinkedEditingRangeParams,
+  ) -> LspResult<Option<LinkedEditingRanges>> {
+    let specifier = self.url_map.uri_to_specifier(
+      &params.text_document_position_params.text_document.uri,
+      LspUrlKind::File,
+    );
+    if !self.is_diagnosable(&specifier)
+      || !self.config.specifier_enabled(&specifier)
+    {
+      return Ok(None);
+    }
+
+    let mark = self
+      .performance
+      .mark_with_args("lsp.linked_editing_range", &params);
+    let asset_or_doc = self.get_asset_or_document(&specifier)?;
+    let line_index = asset_or_doc.line_index();
+    let maybe_linked_editing_info = self
+      .ts_server
+      .get_linked_editing_range(
+        self.snapshot(),
+        specifier,
+        line_index.offset_tsc(params.text_document_position_params.position)?,
+        asset_or_doc.scope().cloned(),
+      )
+      .await?;
+    let response = maybe_linked_editing_info
+      .map(|info| info.to_linked_editing_ranges(line_index));
+    self.performance.measure(mark);
+    Ok(response)
+  }
+
   async fn incoming_calls(
     &self,
     params: CallHierarchyIncomingCallsParams,
@@ -2491,7 +3035,7 @@ impl Inner {
       .url_map
       .uri_to_specifier(&params.item.uri, LspUrlKind::File);
     if !self.is_diagnosable(&specifier)
-      || !self.config.specifier_enabled(&specifier)
+      || !self.specifier_enabled_for_dependency(&specifier)
     {
       return Ok(None);
     }
@@ -2540,7 +3084,7 @@ impl Inner {
       .url_map
       .uri_to_specifier(&params.item.uri, LspUrlKind::File);
     if !self.is_diagnosable(&specifier)
-      || !self.config.specifier_enabled(&specifier)
+      || !self.specifier_enabled_for_dependency(&specifier)
     {
       return Ok(None);
     }
@@ -2588,7 +3132,7 @@ impl Inner {
       LspUrlKind::File,
     );
     if !self.is_diagnosable(&specifier)
-      || !self.config.specifier_enabled(&specifier)
+      || !self.specifier_enabled_for_dependency(&specifier)
     {
       return Ok(None);
     }
@@ -2643,6 +3187,119 @@ impl Inner {
     Ok(response)
   }
 
+  async fn prepare_type_hierarchy(
+    &self,
+    params: TypeHierarchyPrepareParams,
+  ) -> LspResult<Option<Vec<TypeHierarchyItem>>> {
+    let specifier = self.url_map.uri_to_specifier(
+      &params.text_document_position_params.text_document.uri,
+      LspUrlKind::File,
+    );
+    if !self.is_diagnosable(&specifier)
+      || !self.specifier_enabled_for_dependency(&specifier)
+    {
+      return Ok(None);
+    }
+
+    let mark = self
+      .performance
+      .mark_with_args("lsp.prepare_type_hierarchy", &params);
+    let asset_or_doc = self.get_asset_or_document(&specifier)?;
+    let line_index = asset_or_doc.line_index();
+
+    let maybe_items = self
+      .ts_server
+      .prepare_type_hierarchy(
+        self.snapshot(),
+        specifier,
+        line_index.offset_tsc(params.text_document_position_params.position)?,
+        asset_or_doc.scope().cloned(),
+      )
+      .await?;
+
+    let response = maybe_items.map(|items| {
+      items
+        .iter()
+        .filter_map(|item| item.try_resolve_type_hierarchy_item(self))
+        .collect()
+    });
+    self.performance.measure(mark);
+    Ok(response)
+  }
+
+  async fn supertypes(
+    &self,
+    params: TypeHierarchySupertypesParams,
+  ) -> LspResult<Option<Vec<TypeHierarchyItem>>> {
+    let specifier = self
+      .url_map
+      .uri_to_specifier(&params.item.uri, LspUrlKind::File);
+    if !self.is_diagnosable(&specifier)
+      || !self.specifier_enabled_for_dependency(&specifier)
+    {
+      return Ok(None);
+    }
+
+    let mark = self.performance.mark_with_args("lsp.supertypes", &params);
+    let asset_or_doc = self.get_asset_or_document(&specifier)?;
+    let line_index = asset_or_doc.line_index();
+
+    let items = self
+      .ts_server
+      .provide_type_hierarchy_supertypes(
+        self.snapshot(),
+        specifier,
+        line_index.offset_tsc(params.item.selection_range.start)?,
+        asset_or_doc.scope().cloned(),
+      )
+      .await?;
+
+    let resolved_items = items
+      .iter()
+      .filter_map(|item| item.try_resolve_type_hierarchy_item(self))
+      .collect();
+    self.performance.measure(mark);
+    Ok(Some(resolved_items))
+  }
+
+  async fn subtypes(
+    &self,
+    params: TypeHierarchySubtypesParams,
+  ) -> LspResult<Option<Vec<TypeHierarchyItem>>> {
+    let specifier = self
+      .url_map
+      .uri_to_specifier(&params.item.uri, LspUrlKind::File);
+    if !self.is_diagnosable(&specifier)
+      || !self.specifier_enabled_for_dependency(&specifier)
+    {
+      return Ok(None);
+    }
+
+    let mark = self.performance.mark_with_args("lsp.subtypes", &params);
+    let asset_or_doc = self.get_asset_or_document(&specifier)?;
+    let line_index = asset_or_doc.line_index();
+
+    let items: Vec<tsc::TypeHierarchyItem> = self
+      .ts_server
+      .provide_type_hierarchy_subtypes(
+        self.snapshot(),
+        specifier,
+        line_index.offset_tsc(params.item.selection_range.start)?,
+      )
+      .await
+      .map_err(|err| {
+        lsp_warn!("{:#}", err);
+        LspError::internal_error()
+      })?;
+
+    let resolved_items = items
+      .iter()
+      .filter_map(|item| item.try_resolve_type_hierarchy_item(self))
+      .collect();
+    self.performance.measure(mark);
+    Ok(Some(resolved_items))
+  }
+
   async fn rename(
     &self,
     params: RenameParams,
@@ -2742,15 +3399,73 @@ impl Inner {
     let mark = self
       .performance
       .mark_with_args("lsp.semantic_tokens_full", &params);
-    let asset_or_doc = self.get_asset_or_document(&specifier)?;
+    let semantic_tokens = self.get_semantic_tokens_full(&specifier).await?;
+    let response = if !semantic_tokens.data.is_empty() {
+      Some(SemanticTokensResult::Tokens(
+        self
+          .semantic_tokens_results
+          .insert(specifier, semantic_tokens),
+      ))
+    } else {
+      None
+    };
+    self.performance.measure(mark);
+    Ok(response)
+  }
+
+  async fn semantic_tokens_full_delta(
+    &self,
+    params: SemanticTokensDeltaParams,
+  ) -> LspResult<Option<SemanticTokensFullDeltaResult>> {
+    let specifier = self
+      .url_map
+      .uri_to_specifier(&params.text_document.uri, LspUrlKind::File);
+    if !self.is_diagnosable(&specifier) {
+      return Ok(None);
+    }
+
+    let mark = self
+      .performance
+      .mark_with_args("lsp.semantic_tokens_full_delta", &params);
+    let semantic_tokens = self.get_semantic_tokens_full(&specifier).await?;
+    let maybe_previous_tokens = self
+      .semantic_tokens_results
+      .get(&specifier, &params.previous_result_id);
+    let response = match maybe_previous_tokens {
+      Some(previous_tokens) => {
+        let semantic_tokens = self
+          .semantic_tokens_results
+          .insert(specifier, semantic_tokens);
+        Some(SemanticTokensFullDeltaResult::TokensDelta(
+          SemanticTokensDelta {
+            edits: super::semantic_tokens::tokens_delta(
+              &previous_tokens.data,
+              &semantic_tokens.data,
+            ),
+            result_id: semantic_tokens.result_id,
+          },
+        ))
+      }
+      None if !semantic_tokens.data.is_empty() => {
+        Some(SemanticTokensFullDeltaResult::Tokens(
+          self
+            .semantic_tokens_results
+            .insert(specifier, semantic_tokens),
+        ))
+      }
+      None => None,
+    };
+    self.performance.measure(mark);
+    Ok(response)
+  }
+
+  async fn get_semantic_tokens_full(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> LspResult<SemanticTokens> {
+    let asset_or_doc = self.get_asset_or_document(specifier)?;
     if let Some(tokens) = asset_or_doc.maybe_semantic_tokens() {
-      let response = if !tokens.data.is_empty() {
-        Some(SemanticTokensResult::Tokens(tokens.clone()))
-      } else {
-        None
-      };
-      self.performance.measure(mark);
-      return Ok(response);
+      return Ok(tokens);
     }
 
     let line_index = asset_or_doc.line_index();
@@ -2759,7 +3474,7 @@ impl Inner {
       .ts_server
       .get_encoded_semantic_classifications(
         self.snapshot(),
-        specifier,
+        specifier.clone(),
         0..line_index.text_content_length_utf16().into(),
         asset_or_doc.scope().cloned(),
       )
@@ -2771,14 +3486,7 @@ impl Inner {
     if let Some(doc) = asset_or_doc.document() {
       doc.cache_semantic_tokens_full(semantic_tokens.clone());
     }
-
-    let response = if !semantic_tokens.data.is_empty() {
-      Some(SemanticTokensResult::Tokens(semantic_tokens))
-    } else {
-      None
-    };
-    self.performance.measure(mark);
-    Ok(response)
+    Ok(semantic_tokens)
   }
 
   async fn semantic_tokens_range(
@@ -2889,11 +3597,16 @@ impl Inner {
     params: RenameFilesParams,
   ) -> LspResult<Option<WorkspaceEdit>> {
     let mut changes = vec![];
+    let mut renames = vec![];
     for rename in params.files {
       let old_specifier = self.url_map.uri_to_specifier(
         &Uri::from_str(&rename.old_uri).unwrap(),
         LspUrlKind::File,
       );
+      let new_specifier = self.url_map.uri_to_specifier(
+        &Uri::from_str(&rename.new_uri).unwrap(),
+        LspUrlKind::File,
+      );
       let options = self
         .config
         .language_settings_for_specifier(&old_specifier)
@@ -2904,6 +3617,13 @@ impl Inner {
       if options.enabled == UpdateImportsOnFileMoveEnabled::Never {
         continue;
       }
+      renames.push(FileRename {
+        old: old_specifier.clone(),
+        new: new_specifier.clone(),
+        is_dir: specifier_to_file_path(&old_specifier)
+          .map(|p| p.is_dir())
+          .unwrap_or(false),
+      });
       let format_code_settings = (&self
         .config
         .tree
@@ -2916,10 +3636,7 @@ impl Inner {
           .get_edits_for_file_rename(
             self.snapshot(),
             old_specifier,
-            self.url_map.uri_to_specifier(
-              &Uri::from_str(&rename.new_uri).unwrap(),
-              LspUrlKind::File,
-            ),
+            new_specifier,
             format_code_settings,
             tsc::UserPreferences {
               allow_text_changes_in_new_files: Some(true),
@@ -2933,7 +3650,71 @@ impl Inner {
           })?,
       );
     }
-    file_text_changes_to_workspace_edit(&changes, self)
+    let mut workspace_edit =
+      file_text_changes_to_workspace_edit(&changes, self)?;
+    if renames.is_empty() {
+      return Ok(workspace_edit);
+    }
+    let config_edits = self.config_file_rename_edits(&renames);
+    if !config_edits.is_empty() {
+      let workspace_edit =
+        workspace_edit.get_or_insert_with(|| WorkspaceEdit {
+          document_changes: Some(DocumentChanges::Operations(vec![])),
+          ..Default::default()
+        });
+      if let Some(DocumentChanges::Operations(ops)) =
+        &mut workspace_edit.document_changes
+      {
+        ops.extend(config_edits.into_iter().map(DocumentChangeOperation::Edit));
+      }
+    }
+    Ok(workspace_edit)
+  }
+
+  /// Edits to the deno.json and import map files of the workspace for entries
+  /// which refer to renamed files.
+  fn config_file_rename_edits(
+    &self,
+    renames: &[FileRename],
+  ) -> Vec<TextDocumentEdit> {
+    let mut config_specifiers = IndexSet::new();
+    for config_data in self.config.tree.data_by_scope().values() {
+      if let Some(deno_json) = config_data.maybe_deno_json() {
+        config_specifiers.insert(deno_json.specifier.clone());
+      }
+      if let Ok(Some(import_map_specifier)) =
+        config_data.member_dir.workspace.to_import_map_specifier()
+      {
+        config_specifiers.insert(import_map_specifier);
+      }
+    }
+    config_specifiers
+      .into_iter()
+      .filter_map(|specifier| {
+        let (text, version) = match self.documents.get(&specifier) {
+          Some(document) if document.is_open() => {
+            (document.content().to_string(), document.maybe_lsp_version())
+          }
+          _ => (
+            std::fs::read_to_string(specifier_to_file_path(&specifier).ok()?)
+              .ok()?,
+            None,
+          ),
+        };
+        let edits =
+          file_rename::get_config_file_rename_edits(&specifier, &text, renames);
+        if edits.is_empty() {
+          return None;
+        }
+        Some(TextDocumentEdit {
+          text_document: OptionalVersionedTextDocumentIdentifier {
+            uri: url_to_uri(&specifier).ok()?,
+            version,
+          },
+          edits: edits.into_iter().map(OneOf::Left).collect(),
+        })
+      })
+      .collect()
   }
 
   async fn symbol(
@@ -3039,7 +3820,12 @@ impl tower_lsp::LanguageServer for LanguageServer {
         serde_json::from_value(json!(params.arguments))
           .map_err(|err| LspError::invalid_params(err.to_string()))?;
       self
-        .cache(specifiers, referrer, options.force_global_cache)
+        .cache(
+          specifiers,
+          referrer,
+          options.force_global_cache,
+          params.work_done_progress_params.work_done_token,
+        )
         .await
     } else if params.command == "deno.reloadImportRegistries" {
       self.inner.write().await.reload_import_registries().await
@@ -3103,6 +3889,19 @@ impl tower_lsp::LanguageServer for LanguageServer {
   }
 
   async fn shutdown(&self) -> LspResult<()> {
+    if let Some(profile_path) = &self.maybe_profile_path {
+      let profile = self.inner.read().await.get_profile().await;
+      let result = serde_json::to_vec_pretty(&profile)
+        .map_err(AnyError::from)
+        .and_then(|bytes| Ok(std::fs::write(profile_path, bytes)?));
+      if let Err(err) = result {
+        lsp_warn!(
+          "Unable to write the profile to \"{}\": {:#}",
+          profile_path.display(),
+          err
+        );
+      }
+    }
     self.shutdown_flag.raise();
     Ok(())
   }
@@ -3269,6 +4068,26 @@ impl tower_lsp::LanguageServer for LanguageServer {
     self.inner.read().await.code_lens_resolve(params).await
   }
 
+  async fn diagnostic(
+    &self,
+    params: DocumentDiagnosticParams,
+  ) -> LspResult<DocumentDiagnosticReportResult> {
+    if !self.init_flag.is_raised() {
+      self.init_flag.wait_raised().await;
+    }
+    self.inner.read().await.diagnostic(params).await
+  }
+
+  async fn workspace_diagnostic(
+    &self,
+    params: WorkspaceDiagnosticParams,
+  ) -> LspResult<WorkspaceDiagnosticReportResult> {
+    if !self.init_flag.is_raised() {
+      self.init_flag.wait_raised().await;
+    }
+    self.inner.read().await.workspace_diagnostic(params).await
+  }
+
   async fn document_highlight(
     &self,
     params: DocumentHighlightParams,
@@ -3349,6 +4168,16 @@ impl tower_lsp::LanguageServer for LanguageServer {
     self.inner.read().await.folding_range(params).await
   }
 
+  async fn linked_editing_range(
+    &self,
+    params: LinkedEditingRangeParams,
+  ) -> LspResult<Option<LinkedEditingRanges>> {
+    if !self.init_flag.is_raised() {
+      self.init_flag.wait_raised().await;
+    }
+    self.inner.read().await.linked_editing_range(params).await
+  }
+
   async fn incoming_calls(
     &self,
     params: CallHierarchyIncomingCallsParams,
@@ -3379,6 +4208,36 @@ impl tower_lsp::LanguageServer for LanguageServer {
     self.inner.read().await.prepare_call_hierarchy(params).await
   }
 
+  async fn prepare_type_hierarchy(
+    &self,
+    params: TypeHierarchyPrepareParams,
+  ) -> LspResult<Option<Vec<TypeHierarchyItem>>> {
+    if !self.init_flag.is_raised() {
+      self.init_flag.wait_raised().await;
+    }
+    self.inner.read().await.prepare_type_hierarchy(params).await
+  }
+
+  async fn supertypes(
+    &self,
+    params: TypeHierarchySupertypesParams,
+  ) -> LspResult<Option<Vec<TypeHierarchyItem>>> {
+    if !self.init_flag.is_raised() {
+      self.init_flag.wait_raised().await;
+    }
+    self.inner.read().await.supertypes(params).await
+  }
+
+  async fn subtypes(
+    &self,
+    params: TypeHierarchySubtypesParams,
+  ) -> LspResult<Option<Vec<TypeHierarchyItem>>> {
+    if !self.init_flag.is_raised() {
+      self.init_flag.wait_raised().await;
+    }
+    self.inner.read().await.subtypes(params).await
+  }
+
   async fn rename(
     &self,
     params: RenameParams,
@@ -3409,6 +4268,21 @@ impl tower_lsp::LanguageServer for LanguageServer {
     self.inner.read().await.semantic_tokens_full(params).await
   }
 
+  async fn semantic_tokens_full_delta(
+    &self,
+    params: SemanticTokensDeltaParams,
+  ) -> LspResult<Option<SemanticTokensFullDeltaResult>> {
+    if !self.init_flag.is_raised() {
+      self.init_flag.wait_raised().await;
+    }
+    self
+      .inner
+      .read()
+      .await
+      .semantic_tokens_full_delta(params)
+      .await
+  }
+
   async fn semantic_tokens_range(
     &self,
     params: SemanticTokensRangeParams,
@@ -3459,7 +4333,7 @@ struct PrepareCacheResult {
 // These are implementations of custom commands supported by the LSP
 impl Inner {
   async fn initialized(&mut self) -> Vec<Registration> {
-    let mut registrations = Vec::with_capacity(2);
+    let mut registrations = Vec::with_capacity(3);
     init_log_file(self.config.log_file());
     self.update_debug_flag();
     self.update_global_cache().await;
@@ -3467,7 +4341,7 @@ impl Inner {
     self.refresh_config_tree().await;
     self.update_cache();
     self.refresh_resolver().await;
-    self.refresh_documents_config().await;
+    self.refresh_documents_config(None).await;
 
     if self.config.did_change_watched_files_capable() {
       // we are going to watch all the JSON files in the workspace, and the
@@ -3505,6 +4379,23 @@ impl Inner {
       });
     }
 
+    if self.config.type_hierarchy_capable() {
+      // `ServerCapabilities` has no field for type hierarchies in the version
+      // of `lsp-types` we use, so it can only be registered dynamically.
+      let options = TypeHierarchyRegistrationOptions {
+        text_document_registration_options: TextDocumentRegistrationOptions {
+          document_selector: None,
+        },
+        type_hierarchy_options: Default::default(),
+        static_registration_options: Default::default(),
+      };
+      registrations.push(Registration {
+        id: "textDocument/prepareTypeHierarchy".to_string(),
+        method: "textDocument/prepareTypeHierarchy".to_string(),
+        register_options: Some(serde_json::to_value(options).unwrap()),
+      });
+    }
+
     if self.config.testing_api_capable() {
       let test_server = testing::TestServer::new(
         self.client.clone(),
@@ -3591,7 +4482,7 @@ impl Inner {
           initial_cwd.clone()
         ]),
         &WorkspaceDiscoverOptions {
-          fs: Default::default(), // use real fs,
+          fs: &CliDenoConfigFs,
           deno_json_cache: None,
           pkg_json_cache: None,
           workspace_cache: None,
@@ -3630,6 +4521,9 @@ impl Inner {
       config_data
         .and_then(|d| d.npmrc.clone())
         .unwrap_or_else(create_default_npmrc),
+      config_data
+        .map(|d| d.npmrc_fetch_options.clone())
+        .unwrap_or_default(),
       workspace,
       force_global_cache,
     )?;
@@ -3684,7 +4578,7 @@ impl Inner {
     self.refresh_workspace_files();
     self.refresh_config_tree().await;
     self.refresh_resolver().await;
-    self.refresh_documents_config().await;
+    self.refresh_documents_config(None).await;
     self.diagnostics_server.invalidate_all();
     self.send_diagnostics_update();
     self.send_testing_update();
@@ -3695,6 +4589,47 @@ impl Inner {
     json!({ "averages": averages })
   }
 
+  /// The per request timings, tsc round trips and cache hit rates since the
+  /// start of the server, to attach to reports about its performance.
+  async fn get_profile(&self) -> Value {
+    // Taken before asking tsc, so that the profile doesn't count itself.
+    let totals = self.performance.totals();
+    let totals_with_prefix = |prefix: &str| {
+      totals
+        .iter()
+        .filter(|t| t.name.starts_with(prefix))
+        .collect::<Vec<_>>()
+    };
+    let tsc_requests = totals_with_prefix("tsc.request.");
+    let tsc_ops = totals_with_prefix("tsc.op.");
+    let cache_json = |stats: CacheStats| {
+      json!({
+        "hits": stats.hits,
+        "misses": stats.misses,
+        "hitRate": stats.hit_rate(),
+      })
+    };
+    let source_file_cache_stats = self
+      .ts_server
+      .get_source_file_cache_stats(self.snapshot())
+      .await
+      .ok();
+    json!({
+      "uptime": self.performance.uptime().as_millis() as u64,
+      "requests": totals_with_prefix("lsp."),
+      "tsc": {
+        "roundTrips": tsc_requests.iter().map(|t| t.count).sum::<u32>(),
+        "hostCallbacks": tsc_ops.iter().map(|t| t.count).sum::<u32>(),
+        "requests": tsc_requests,
+      },
+      "caches": {
+        "fileSystemDocuments":
+          cache_json(self.documents.file_system_cache_stats()),
+        "tscSourceFiles": source_file_cache_stats.map(cache_json),
+      },
+    })
+  }
+
   async fn test_run_request(
     &self,
     params: Option<Value>,
@@ -3734,12 +4669,13 @@ impl Inner {
     for config_file in self.config.tree.config_files() {
       if let Some(tasks) = json!(&config_file.json.tasks).as_object() {
         for (name, value) in tasks {
-          let Some(command) = value.as_str() else {
+          let Ok(task) = serde_json::from_value::<TaskConfig>(value.clone())
+          else {
             continue;
           };
           result.push(TaskDefinition {
             name: name.clone(),
-            command: command.to_string(),
+            command: task.command().to_string(),
             source_uri: url_to_uri(&config_file.specifier)
               .map_err(|_| LspError::internal_error())?,
           });
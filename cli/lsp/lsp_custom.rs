@@ -5,6 +5,7 @@ use deno_core::serde::Serialize;
 use tower_lsp::lsp_types as lsp;
 
 pub const PERFORMANCE_REQUEST: &str = "deno/performance";
+pub const PROFILE_REQUEST: &str = "deno/profile";
 pub const TASK_REQUEST: &str = "deno/taskDefinitions";
 pub const VIRTUAL_TEXT_DOCUMENT: &str = "deno/virtualTextDocument";
 pub const LATEST_DIAGNOSTIC_BATCH_INDEX: &str =
@@ -2,9 +2,11 @@
 
 use deno_core::error::AnyError;
 use deno_core::unsync::spawn;
+use std::path::PathBuf;
 use tower_lsp::LspService;
 use tower_lsp::Server;
 
+use crate::args::LspFlags;
 use crate::lsp::language_server::LanguageServer;
 use crate::util::sync::AsyncFlag;
 pub use repl::ReplCompletionItem;
@@ -19,8 +21,10 @@ mod client;
 mod code_lens;
 mod completions;
 mod config;
+mod config_file;
 mod diagnostics;
 mod documents;
+mod file_rename;
 mod jsr;
 pub mod language_server;
 mod logging;
@@ -29,6 +33,7 @@ mod npm;
 mod parent_process_checker;
 mod path_to_regex;
 mod performance;
+mod plugins;
 mod refactor;
 mod registries;
 mod repl;
@@ -40,7 +45,7 @@ mod text;
 mod tsc;
 mod urls;
 
-pub async fn start() -> Result<(), AnyError> {
+pub async fn start(lsp_flags: LspFlags) -> Result<(), AnyError> {
   let stdin = tokio::io::stdin();
   let stdout = tokio::io::stdout();
 
@@ -49,12 +54,14 @@ pub async fn start() -> Result<(), AnyError> {
     language_server::LanguageServer::new(
       client::Client::from_tower(client),
       shutdown_flag.clone(),
+      lsp_flags.profile.as_ref().map(PathBuf::from),
     )
   })
   .custom_method(
     lsp_custom::PERFORMANCE_REQUEST,
     LanguageServer::performance_request,
   )
+  .custom_method(lsp_custom::PROFILE_REQUEST, LanguageServer::profile_request)
   .custom_method(lsp_custom::TASK_REQUEST, LanguageServer::task_definitions)
   // TODO(nayeemrmn): Rename this to `deno/taskDefinitions` in vscode_deno and
   // remove this alias.
@@ -34,6 +34,43 @@ impl Ord for PerformanceAverage {
   }
 }
 
+/// The number of measurements and their total duration for a name, since the
+/// start of the language server.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PerformanceTotal {
+  pub name: String,
+  pub count: u32,
+  pub total_duration: f64,
+  pub average_duration: f64,
+}
+
+/// The hits and misses of a cache.
+#[derive(
+  Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq, Eq,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+  pub hits: u32,
+  pub misses: u32,
+}
+
+impl CacheStats {
+  pub fn record(&mut self, hit: bool) {
+    if hit {
+      self.hits += 1;
+    } else {
+      self.misses += 1;
+    }
+  }
+
+  /// The ratio of lookups which were hits, or `None` if there weren't any.
+  pub fn hit_rate(&self) -> Option<f64> {
+    let lookups = self.hits + self.misses;
+    (lookups > 0).then(|| self.hits as f64 / lookups as f64)
+  }
+}
+
 /// A structure which serves as a start of a measurement span.
 #[derive(Debug)]
 pub struct PerformanceMark {
@@ -92,6 +129,7 @@ struct PerformanceInner {
   measurements_by_type: HashMap<String, (/* count */ u32, /* duration */ f64)>,
   max_size: usize,
   measures: VecDeque<PerformanceMeasure>,
+  start: Instant,
 }
 
 impl PerformanceInner {
@@ -127,6 +165,7 @@ impl Default for PerformanceInner {
       measurements_by_type: Default::default(),
       max_size: 3_000,
       measures: Default::default(),
+      start: Instant::now(),
     }
   }
 }
@@ -194,6 +233,35 @@ impl Performance {
       .collect::<Vec<_>>()
   }
 
+  /// Return the count, total and average duration of each measurement since
+  /// the start, sorted by name. Unlike `averages()`, these include the
+  /// measurements which rolled off.
+  pub fn totals(&self) -> Vec<PerformanceTotal> {
+    let mut totals = self
+      .0
+      .lock()
+      .measurements_by_type
+      .iter()
+      .map(|(name, (count, duration))| PerformanceTotal {
+        name: name.to_string(),
+        count: *count,
+        total_duration: *duration,
+        average_duration: if *count > 0 {
+          *duration / *count as f64
+        } else {
+          0.0
+        },
+      })
+      .collect::<Vec<_>>();
+    totals.sort_by(|a, b| a.name.cmp(&b.name));
+    totals
+  }
+
+  /// The time elapsed since the structure was created.
+  pub fn uptime(&self) -> Duration {
+    self.0.lock().start.elapsed()
+  }
+
   pub fn averages_as_f64(&self) -> Vec<(String, u32, f64)> {
     let mut averages: HashMap<String, Vec<Duration>> = HashMap::new();
     for measure in self.0.lock().measures.iter() {
@@ -317,6 +385,39 @@ mod tests {
     assert!(performance.average("c").is_none());
   }
 
+  #[test]
+  fn test_totals() {
+    let performance = Performance::default();
+    let mark1 = performance.mark("b");
+    let mark2 = performance.mark("a");
+    let mark3 = performance.mark("b");
+    performance.measure(mark1);
+    performance.measure(mark2);
+    performance.measure(mark3);
+    let totals = performance.totals();
+    assert_eq!(
+      totals
+        .iter()
+        .map(|t| (t.name.as_str(), t.count))
+        .collect::<Vec<_>>(),
+      vec![("a", 1), ("b", 2)]
+    );
+    let b = &totals[1];
+    assert_eq!(b.average_duration, b.total_duration / 2.0);
+  }
+
+  #[test]
+  fn test_cache_stats() {
+    let mut stats = CacheStats::default();
+    assert_eq!(stats.hit_rate(), None);
+    stats.record(true);
+    stats.record(true);
+    stats.record(true);
+    stats.record(false);
+    assert_eq!(stats, CacheStats { hits: 3, misses: 1 });
+    assert_eq!(stats.hit_rate(), Some(0.75));
+  }
+
   #[test]
   fn test_averages() {
     let performance = Performance::default();
@@ -0,0 +1,50 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+// Host side of language server plugins. This script is executed in a bare
+// isolate without any Deno APIs before the plugin modules are evaluated.
+
+((globalThis) => {
+  const core = globalThis.Deno.core;
+  const plugins = [];
+
+  function print(isErr, args) {
+    core.print(args.map((arg) => String(arg)).join(" ") + "\n", isErr);
+  }
+
+  globalThis.console = {
+    log: (...args) => print(false, args),
+    info: (...args) => print(false, args),
+    warn: (...args) => print(true, args),
+    error: (...args) => print(true, args),
+  };
+
+  globalThis.registerLspPlugins = (entries) => {
+    for (const [specifier, plugin] of entries) {
+      if (
+        typeof plugin?.name !== "string" ||
+        !Array.isArray(plugin.extensions) ||
+        typeof plugin.extract !== "function"
+      ) {
+        throw new Error(
+          `Language server plugin ${specifier} must default export an object with "name", "extensions" and "extract" properties.`,
+        );
+      }
+      plugins.push(plugin);
+    }
+  };
+
+  globalThis.getLspPluginInfos = () =>
+    plugins.map(({ name, extensions }) => ({
+      name,
+      extensions: extensions.map((ext) => String(ext)),
+    }));
+
+  globalThis.extractEmbeddedScripts = (index, specifier, source) => {
+    const scripts = plugins[index].extract(source, specifier) ?? [];
+    return scripts.map(({ start, end, lang }) => ({
+      start,
+      end,
+      lang: lang == null ? null : String(lang),
+    }));
+  };
+})(globalThis);
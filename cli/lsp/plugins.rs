@@ -0,0 +1,473 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Language server plugins for framework files, declared in the config file:
+//!
+//! ```jsonc
+//! {
+//!   "plugins": {
+//!     "lsp": ["./vue_plugin.ts"]
+//!   }
+//! }
+//! ```
+//!
+//! A plugin module default exports the file extensions it handles and an
+//! `extract` function returning the ranges of the scripts embedded in a file,
+//! as UTF-16 offsets like JavaScript string indices:
+//!
+//! ```js
+//! export default {
+//!   name: "vue",
+//!   extensions: [".vue"],
+//!   extract(source, specifier) {
+//!     const match = /(<script[^>]*>)([\s\S]*?)<\/script>/.exec(source);
+//!     if (match == null) {
+//!       return [];
+//!     }
+//!     const start = match.index + match[1].length;
+//!     return [{ start, end: start + match[2].length, lang: "ts" }];
+//!   },
+//! };
+//! ```
+//!
+//! The language server analyzes a virtual document of such a file in which
+//! everything outside of the embedded scripts is replaced by whitespace. Line
+//! breaks and both the UTF-8 and UTF-16 lengths of every character are
+//! preserved, so each position and offset of the virtual document maps to the
+//! same one of the file and requests inside the script blocks don't need to
+//! be translated.
+//!
+//! Like lint plugins, the plugins are evaluated in a bare `JsRuntime` on a
+//! dedicated thread that has no access to any Deno APIs. The scripts of the
+//! open files are extracted asynchronously with a timeout, so a plugin that
+//! hangs doesn't freeze the language server.
+
+use std::rc::Rc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use deno_ast::MediaType;
+use deno_ast::ModuleSpecifier;
+use deno_config::deno_json::ConfigFile;
+use deno_core::anyhow::anyhow;
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::located_script_name;
+use deno_core::serde_json;
+use deno_core::serde_v8;
+use deno_core::v8;
+use deno_core::JsRuntime;
+use deno_core::RuntimeOptions;
+use deno_core::StaticModuleLoader;
+use deno_runtime::tokio_util::create_basic_runtime;
+use serde::Deserialize;
+use tokio::sync::oneshot;
+
+use crate::args::deno_json::read_plugins_config;
+use crate::file_fetcher::FileFetcher;
+use crate::tools::lint::transpile_plugin;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PluginInfo {
+  name: String,
+  extensions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EmbeddedScript {
+  start: u32,
+  end: u32,
+  lang: Option<String>,
+}
+
+/// How long extracting the scripts of a file may take before the language
+/// server gives up on it.
+const EXTRACT_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct ExtractJob {
+  plugin_index: usize,
+  specifier: String,
+  source: Arc<str>,
+  sender: oneshot::Sender<Result<Vec<EmbeddedScript>, AnyError>>,
+}
+
+/// The virtual document of the scripts embedded in a framework file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedDocument {
+  pub text: Arc<str>,
+  pub media_type: MediaType,
+}
+
+fn call_into_runtime<T: serde::de::DeserializeOwned>(
+  runtime: &mut JsRuntime,
+  source: String,
+) -> Result<T, AnyError> {
+  let global = runtime.execute_script(located_script_name!(), source)?;
+  let scope = &mut runtime.handle_scope();
+  let local = v8::Local::new(scope, global);
+  Ok(serde_v8::from_v8::<T>(scope, local)?)
+}
+
+/// Loads the plugin modules into a new runtime and returns their info.
+async fn init_runtime(
+  runtime: &mut JsRuntime,
+  plugin_specifiers: &[ModuleSpecifier],
+) -> Result<Vec<PluginInfo>, AnyError> {
+  runtime.execute_script(
+    "ext:cli/lsp_plugin_host.js",
+    deno_core::ascii_str_include!("plugin_host.js"),
+  )?;
+  let mut main_code = String::new();
+  let mut registrations = Vec::with_capacity(plugin_specifiers.len());
+  for (i, specifier) in plugin_specifiers.iter().enumerate() {
+    let specifier = serde_json::to_string(specifier.as_str())?;
+    main_code.push_str(&format!("import plugin{i} from {specifier};\n"));
+    registrations.push(format!("[{specifier}, plugin{i}]"));
+  }
+  main_code.push_str(&format!(
+    "globalThis.registerLspPlugins([{}]);\n",
+    registrations.join(", ")
+  ));
+  let main_specifier = ModuleSpecifier::parse("ext:cli/lsp_plugins.js")?;
+  let mod_id = runtime
+    .load_main_es_module_from_code(&main_specifier, main_code)
+    .await?;
+  let evaluation = runtime.mod_evaluate(mod_id);
+  runtime.run_event_loop(Default::default()).await?;
+  evaluation.await?;
+  call_into_runtime(runtime, "globalThis.getLspPluginInfos()".to_string())
+}
+
+/// Runs the plugins on the current thread until the language server drops
+/// the sender of the jobs.
+fn run_plugins(
+  modules: Vec<(ModuleSpecifier, Arc<str>)>,
+  init_sender: oneshot::Sender<Result<Vec<PluginInfo>, AnyError>>,
+  receiver: mpsc::Receiver<ExtractJob>,
+  pending_jobs: &AtomicUsize,
+) {
+  let plugin_specifiers =
+    modules.iter().map(|(s, _)| s.clone()).collect::<Vec<_>>();
+  let module_loader = StaticModuleLoader::new(
+    modules
+      .into_iter()
+      .map(|(specifier, code)| (specifier, code.to_string())),
+  );
+  let tokio_runtime = create_basic_runtime();
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    module_loader: Some(Rc::new(module_loader)),
+    ..Default::default()
+  });
+  let init_result =
+    tokio_runtime.block_on(init_runtime(&mut runtime, &plugin_specifiers));
+  let is_err = init_result.is_err();
+  if init_sender.send(init_result).is_err() || is_err {
+    return;
+  }
+  while let Ok(job) = receiver.recv() {
+    let result = serde_json::to_string(&job.specifier)
+      .and_then(|specifier| {
+        serde_json::to_string(&*job.source).map(|source| (specifier, source))
+      })
+      .map_err(AnyError::from)
+      .and_then(|(specifier, source)| {
+        call_into_runtime(
+          &mut runtime,
+          format!(
+            "globalThis.extractEmbeddedScripts({}, {specifier}, {source})",
+            job.plugin_index
+          ),
+        )
+      });
+    pending_jobs.fetch_sub(1, Ordering::SeqCst);
+    let _ = job.sender.send(result);
+  }
+}
+
+/// The language server plugins declared in a config file.
+pub struct LspPlugins {
+  infos: Vec<PluginInfo>,
+  sender: mpsc::Sender<ExtractJob>,
+  /// The number of jobs sent to the plugin thread that haven't finished.
+  pending_jobs: Arc<AtomicUsize>,
+  /// Set when a job timed out. Until the plugin thread catches up, the
+  /// following jobs fail right away instead of waiting on it again.
+  timed_out: AtomicBool,
+}
+
+impl std::fmt::Debug for LspPlugins {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("LspPlugins")
+      .field("infos", &self.infos)
+      .finish()
+  }
+}
+
+impl LspPlugins {
+  /// Loads the language server plugins declared in the provided config file,
+  /// returning `None` when there aren't any.
+  pub async fn resolve(
+    maybe_config_file: Option<&ConfigFile>,
+    file_fetcher: &FileFetcher,
+  ) -> Result<Option<Arc<Self>>, AnyError> {
+    let Some(config_file) = maybe_config_file else {
+      return Ok(None);
+    };
+    let plugins_config = read_plugins_config(config_file)?;
+    if plugins_config.lsp.is_empty() {
+      return Ok(None);
+    }
+    let mut modules = Vec::with_capacity(plugins_config.lsp.len());
+    for specifier in plugins_config.lsp {
+      let specifier =
+        config_file.specifier.join(&specifier).with_context(|| {
+          format!("Invalid language server plugin specifier '{specifier}'")
+        })?;
+      let file = file_fetcher
+        .fetch_bypass_permissions(&specifier)
+        .await
+        .and_then(|file| file.into_text_decoded())
+        .with_context(|| {
+          format!("Failed to load language server plugin {specifier}")
+        })?;
+      let code = transpile_plugin(&specifier, file.media_type, file.source)
+        .with_context(|| {
+          format!("Failed to load language server plugin {specifier}")
+        })?;
+      modules.push((specifier, code));
+    }
+    Self::start(modules)
+      .await
+      .map(|plugins| Some(Arc::new(plugins)))
+  }
+
+  async fn start(
+    modules: Vec<(ModuleSpecifier, Arc<str>)>,
+  ) -> Result<Self, AnyError> {
+    let (init_sender, init_receiver) = oneshot::channel();
+    let (sender, receiver) = mpsc::channel::<ExtractJob>();
+    let pending_jobs = Arc::new(AtomicUsize::new(0));
+    std::thread::spawn({
+      let pending_jobs = pending_jobs.clone();
+      move || run_plugins(modules, init_sender, receiver, &pending_jobs)
+    });
+    let infos = init_receiver
+      .await
+      .context("Language server plugin thread exited unexpectedly.")?
+      .context("Failed to initialize language server plugins")?;
+    Ok(Self {
+      infos,
+      sender,
+      pending_jobs,
+      timed_out: AtomicBool::new(false),
+    })
+  }
+
+  fn plugin_index(&self, specifier: &ModuleSpecifier) -> Option<usize> {
+    let path = specifier.path().to_lowercase();
+    self.infos.iter().position(|info| {
+      info.extensions.iter().any(|ext| {
+        let ext = ext.trim_start_matches('.').to_lowercase();
+        path
+          .strip_suffix(&ext)
+          .is_some_and(|rest| rest.ends_with('.'))
+      })
+    })
+  }
+
+  /// Whether a plugin handles the extension of the file.
+  pub fn handles(&self, specifier: &ModuleSpecifier) -> bool {
+    self.plugin_index(specifier).is_some()
+  }
+
+  /// Extracts the virtual document of a file with the first plugin that
+  /// handles its extension, returning `None` when no plugin matches it.
+  pub async fn extract(
+    &self,
+    specifier: &ModuleSpecifier,
+    text: &Arc<str>,
+  ) -> Option<Result<EmbeddedDocument, AnyError>> {
+    let plugin_index = self.plugin_index(specifier)?;
+    let result = self
+      .extract_scripts(plugin_index, specifier, text)
+      .await
+      .map(|scripts| EmbeddedDocument {
+        text: embedded_text(text, &scripts).into(),
+        media_type: media_type_from_lang(
+          scripts.first().and_then(|s| s.lang.as_deref()),
+        ),
+      });
+    Some(result)
+  }
+
+  async fn extract_scripts(
+    &self,
+    plugin_index: usize,
+    specifier: &ModuleSpecifier,
+    text: &Arc<str>,
+  ) -> Result<Vec<EmbeddedScript>, AnyError> {
+    let plugin_name = &self.infos[plugin_index].name;
+    if self.timed_out.load(Ordering::SeqCst) {
+      if self.pending_jobs.load(Ordering::SeqCst) > 0 {
+        bail!("Language server plugin {plugin_name} is not responding.");
+      }
+      self.timed_out.store(false, Ordering::SeqCst);
+    }
+    let (sender, receiver) = oneshot::channel();
+    self.pending_jobs.fetch_add(1, Ordering::SeqCst);
+    let job = ExtractJob {
+      plugin_index,
+      specifier: specifier.to_string(),
+      source: text.clone(),
+      sender,
+    };
+    if self.sender.send(job).is_err() {
+      self.pending_jobs.fetch_sub(1, Ordering::SeqCst);
+      bail!("Language server plugin {plugin_name} exited.");
+    }
+    match tokio::time::timeout(EXTRACT_TIMEOUT, receiver).await {
+      Ok(result) => result
+        .map_err(|_| anyhow!("Language server plugin {plugin_name} exited."))?,
+      Err(_) => {
+        self.timed_out.store(true, Ordering::SeqCst);
+        bail!(
+          "Language server plugin {plugin_name} timed out after {} seconds.",
+          EXTRACT_TIMEOUT.as_secs()
+        );
+      }
+    }
+  }
+}
+
+fn media_type_from_lang(lang: Option<&str>) -> MediaType {
+  match lang {
+    Some("js" | "javascript") => MediaType::JavaScript,
+    Some("jsx") => MediaType::Jsx,
+    Some("tsx") => MediaType::Tsx,
+    _ => MediaType::TypeScript,
+  }
+}
+
+/// Replaces everything outside of the scripts with whitespace, keeping line
+/// breaks and both the UTF-8 and UTF-16 lengths of each character.
+fn embedded_text(text: &str, scripts: &[EmbeddedScript]) -> String {
+  let mut embedded = String::with_capacity(text.len());
+  let mut offset = 0;
+  for c in text.chars() {
+    let is_script = scripts
+      .iter()
+      .any(|script| script.start <= offset && offset < script.end);
+    if is_script || c == '\n' || c == '\r' {
+      embedded.push(c);
+    } else {
+      embedded.push_str(whitespace_of_same_length(c));
+    }
+    offset += c.len_utf16() as u32;
+  }
+  embedded
+}
+
+/// Gets whitespace that JavaScript skips with the same UTF-8 and UTF-16
+/// lengths as the character.
+fn whitespace_of_same_length(c: char) -> &'static str {
+  match c.len_utf8() {
+    1 => " ",
+    // no-break space
+    2 => "\u{a0}",
+    // en space
+    3 => "\u{2002}",
+    // characters outside of the BMP are two UTF-16 code units
+    _ => "\u{a0}\u{a0}",
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[tokio::test]
+  async fn test_extract_timeout() {
+    let specifier = ModuleSpecifier::parse("file:///plugin.js").unwrap();
+    let code = r#"export default {
+      name: "hang",
+      extensions: [".vue"],
+      extract(source) {
+        while (source === "hang") {}
+        return [{ start: 0, end: source.length, lang: "js" }];
+      },
+    };"#;
+    let plugins = LspPlugins::start(vec![(specifier, code.into())])
+      .await
+      .unwrap();
+    let app = ModuleSpecifier::parse("file:///App.vue").unwrap();
+    let other = ModuleSpecifier::parse("file:///main.ts").unwrap();
+    assert!(plugins.handles(&app));
+    assert!(!plugins.handles(&other));
+    assert!(plugins.extract(&other, &"a".into()).await.is_none());
+    let embedded = plugins.extract(&app, &"a".into()).await.unwrap().unwrap();
+    assert_eq!(embedded.text.as_ref(), "a");
+    assert_eq!(embedded.media_type, MediaType::JavaScript);
+
+    let err = plugins
+      .extract(&app, &"hang".into())
+      .await
+      .unwrap()
+      .unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "Language server plugin hang timed out after 5 seconds."
+    );
+    // the plugin isn't waited on again while it's hanging
+    let err = plugins
+      .extract(&app, &"a".into())
+      .await
+      .unwrap()
+      .unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "Language server plugin hang is not responding."
+    );
+  }
+
+  #[test]
+  fn test_embedded_text() {
+    let text = "<template>\r\n  <p>ü 😀</p>\n</template>\n<script lang=\"ts\">\nconst a: string = \"😀\";\n</script>\n";
+    let utf16_offset =
+      |s: &str| text[..text.find(s).unwrap()].encode_utf16().count() as u32;
+    let embedded = embedded_text(
+      text,
+      &[EmbeddedScript {
+        start: utf16_offset("\nconst"),
+        end: utf16_offset("</script>"),
+        lang: Some("ts".to_string()),
+      }],
+    );
+    assert_eq!(
+      embedded,
+      format!(
+        "{}\r\n{}\u{a0}{}\u{a0}\u{a0}{}\n{}\n{}\nconst a: string = \"😀\";\n{}\n",
+        " ".repeat(10),
+        " ".repeat(5),
+        " ".repeat(1),
+        " ".repeat(4),
+        " ".repeat(11),
+        " ".repeat(18),
+        " ".repeat(9),
+      )
+    );
+    assert_eq!(embedded.len(), text.len());
+    assert_eq!(embedded.encode_utf16().count(), text.encode_utf16().count());
+    for (c, expected) in [('a', 1), ('ü', 2), ('€', 3), ('😀', 4)] {
+      let whitespace = whitespace_of_same_length(c);
+      assert_eq!(whitespace.len(), expected);
+      assert_eq!(whitespace.encode_utf16().count(), c.len_utf16());
+      assert!(whitespace.chars().all(char::is_whitespace));
+    }
+  }
+}
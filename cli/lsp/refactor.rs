@@ -130,6 +130,22 @@ pub static REWRITE_PROPERTY_GENERATEACCESSORS: Lazy<RefactorCodeActionKind> =
     }),
   });
 
+pub static INLINE_VARIABLE: Lazy<RefactorCodeActionKind> =
+  Lazy::new(|| RefactorCodeActionKind {
+    kind: [lsp::CodeActionKind::REFACTOR_INLINE.as_str(), "variable"]
+      .join(".")
+      .into(),
+    matches_callback: Box::new(|tag: &str| tag.starts_with("Inline variable")),
+  });
+
+pub static INLINE_FUNCTION: Lazy<RefactorCodeActionKind> =
+  Lazy::new(|| RefactorCodeActionKind {
+    kind: [lsp::CodeActionKind::REFACTOR_INLINE.as_str(), "function"]
+      .join(".")
+      .into(),
+    matches_callback: Box::new(|tag: &str| tag.starts_with("Inline function")),
+  });
+
 pub static ALL_KNOWN_REFACTOR_ACTION_KINDS: Lazy<
   Vec<&'static RefactorCodeActionKind>,
 > = Lazy::new(|| {
@@ -144,6 +160,8 @@ pub static ALL_KNOWN_REFACTOR_ACTION_KINDS: Lazy<
     &REWRITE_ARROW_BRACES,
     &REWRITE_PARAMETERS_TO_DESTRUCTURED,
     &REWRITE_PROPERTY_GENERATEACCESSORS,
+    &INLINE_VARIABLE,
+    &INLINE_FUNCTION,
   ]
 });
 
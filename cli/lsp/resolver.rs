@@ -478,6 +478,9 @@ async fn create_npm_resolver(
       npmrc: config_data
         .and_then(|d| d.npmrc.clone())
         .unwrap_or_else(create_default_npmrc),
+      npmrc_fetch_options: config_data
+        .map(|d| d.npmrc_fetch_options.clone())
+        .unwrap_or_default(),
       npm_system_info: NpmSystemInfo::default(),
       lifecycle_scripts: Default::default(),
     })
@@ -500,6 +503,7 @@ fn create_node_resolver(
   let node_resolver_inner = Arc::new(NodeResolver::new(
     deno_runtime::deno_node::DenoFsNodeResolverEnv::new(fs.clone()),
     npm_resolver.clone().into_npm_resolver(),
+    Vec::new(),
   ));
   Some(Arc::new(CliNodeResolver::new(
     CJS_RESOLUTIONS.clone(),
@@ -538,6 +542,8 @@ fn create_graph_resolver(
       .is_some_and(|workspace| workspace.has_unstable("bare-node-builtins")),
     sloppy_imports_resolver: config_data
       .and_then(|d| d.sloppy_imports_resolver.clone()),
+    tsconfig_paths_resolver: config_data
+      .and_then(|d| d.tsconfig_paths_resolver.clone()),
   }))
 }
 
@@ -5,13 +5,19 @@
 // and https://github.com/microsoft/vscode/blob/main/src/vs/workbench/api/common/extHostTypes.ts
 // for the SemanticTokensBuilder implementation.
 
+use deno_core::parking_lot::Mutex;
+use deno_core::ModuleSpecifier;
+use std::collections::HashMap;
 use std::ops::Index;
 use std::ops::IndexMut;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use tower_lsp::lsp_types as lsp;
 use tower_lsp::lsp_types::SemanticToken;
 use tower_lsp::lsp_types::SemanticTokenModifier;
 use tower_lsp::lsp_types::SemanticTokenType;
 use tower_lsp::lsp_types::SemanticTokens;
+use tower_lsp::lsp_types::SemanticTokensEdit;
 use tower_lsp::lsp_types::SemanticTokensLegend;
 
 pub const MODIFIER_MASK: u32 = 255;
@@ -296,6 +302,69 @@ pub fn tokens_within_range(
   }
 }
 
+/// Computes the edits which turn the `old` tokens into the `new` tokens, which
+/// is a single edit of what's between their common prefix and suffix.
+pub fn tokens_delta(
+  old: &[SemanticToken],
+  new: &[SemanticToken],
+) -> Vec<SemanticTokensEdit> {
+  let prefix_len = old.iter().zip(new).take_while(|(o, n)| o == n).count();
+  if prefix_len == old.len() && prefix_len == new.len() {
+    return Vec::new();
+  }
+  let suffix_len = old[prefix_len..]
+    .iter()
+    .rev()
+    .zip(new[prefix_len..].iter().rev())
+    .take_while(|(o, n)| o == n)
+    .count();
+  let old_end = old.len() - suffix_len;
+  let new_end = new.len() - suffix_len;
+  // the edit positions are indexes into the flattened token integers
+  vec![SemanticTokensEdit {
+    start: (prefix_len * 5) as u32,
+    delete_count: ((old_end - prefix_len) * 5) as u32,
+    data: Some(new[prefix_len..new_end].to_vec()),
+  }]
+}
+
+/// The tokens last sent to the client for each document, which the edits of
+/// `textDocument/semanticTokens/full/delta` responses are relative to.
+#[derive(Debug, Default)]
+pub struct SemanticTokensResults {
+  last_result_id: AtomicUsize,
+  tokens: Mutex<HashMap<ModuleSpecifier, SemanticTokens>>,
+}
+
+impl SemanticTokensResults {
+  /// Stores the tokens with a new result id and returns them.
+  pub fn insert(
+    &self,
+    specifier: ModuleSpecifier,
+    mut tokens: SemanticTokens,
+  ) -> SemanticTokens {
+    let result_id = self.last_result_id.fetch_add(1, Ordering::Relaxed) + 1;
+    tokens.result_id = Some(result_id.to_string());
+    self.tokens.lock().insert(specifier, tokens.clone());
+    tokens
+  }
+
+  /// Returns the stored tokens of the document, if they have the result id.
+  pub fn get(
+    &self,
+    specifier: &ModuleSpecifier,
+    result_id: &str,
+  ) -> Option<SemanticTokens> {
+    let tokens = self.tokens.lock();
+    let tokens = tokens.get(specifier)?;
+    (tokens.result_id.as_deref() == Some(result_id)).then(|| tokens.clone())
+  }
+
+  pub fn remove(&self, specifier: &ModuleSpecifier) {
+    self.tokens.lock().remove(specifier);
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -499,6 +568,43 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_tokens_delta() {
+    let token = |delta_line, length| SemanticToken {
+      delta_line,
+      delta_start: 0,
+      length,
+      token_type: 0,
+      token_modifiers_bitset: 0,
+    };
+    let old = vec![token(1, 1), token(1, 2), token(1, 3), token(1, 4)];
+    assert_eq!(tokens_delta(&old, &old), vec![]);
+    assert_eq!(
+      tokens_delta(&old, &[token(1, 1), token(2, 5), token(1, 4)]),
+      vec![SemanticTokensEdit {
+        start: 5,
+        delete_count: 10,
+        data: Some(vec![token(2, 5)]),
+      }]
+    );
+    assert_eq!(
+      tokens_delta(&old, &[token(1, 1), token(1, 2)]),
+      vec![SemanticTokensEdit {
+        start: 10,
+        delete_count: 10,
+        data: Some(vec![]),
+      }]
+    );
+    assert_eq!(
+      tokens_delta(&[], &[token(1, 1)]),
+      vec![SemanticTokensEdit {
+        start: 0,
+        delete_count: 0,
+        data: Some(vec![token(1, 1)]),
+      }]
+    );
+  }
+
   #[test]
   fn test_tokens_within_range_empty() {
     let mut builder = SemanticTokensBuilder::new();
@@ -291,6 +291,8 @@ impl TestRun {
         exclude: lsp_filter
           .map(|f| f.exclude.values().map(|t| t.name.clone()).collect())
           .unwrap_or_default(),
+        tags: None,
+        skip_tags: None,
       };
       let token = self.token.clone();
 
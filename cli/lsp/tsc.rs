@@ -9,6 +9,7 @@ use super::documents::Document;
 use super::documents::DocumentsFilter;
 use super::language_server;
 use super::language_server::StateSnapshot;
+use super::performance::CacheStats;
 use super::performance::Performance;
 use super::performance::PerformanceMark;
 use super::refactor::RefactorCodeActionData;
@@ -600,6 +601,17 @@ impl TsServer {
     })
   }
 
+  pub async fn get_source_file_cache_stats(
+    &self,
+    snapshot: Arc<StateSnapshot>,
+  ) -> Result<CacheStats, LspError> {
+    let req = TscRequest::GetSourceFileCacheStats;
+    self.request(snapshot, req, None).await.map_err(|err| {
+      log::error!("Unable to get source file cache stats: {}", err);
+      LspError::internal_error()
+    })
+  }
+
   pub async fn get_quick_info(
     &self,
     snapshot: Arc<StateSnapshot>,
@@ -717,6 +729,38 @@ impl TsServer {
       })
   }
 
+  pub async fn organize_imports(
+    &self,
+    snapshot: Arc<StateSnapshot>,
+    specifier: ModuleSpecifier,
+    format_code_settings: FormatCodeSettings,
+    preferences: UserPreferences,
+    scope: Option<ModuleSpecifier>,
+  ) -> Result<Vec<FileTextChanges>, LspError> {
+    let req = TscRequest::OrganizeImports(Box::new((
+      OrganizeImportsArgs {
+        r#type: "file",
+        file_name: self.specifier_map.denormalize(&specifier),
+        mode: "All",
+      },
+      format_code_settings,
+      preferences,
+    )));
+    self
+      .request::<Vec<FileTextChanges>>(snapshot, req, scope)
+      .await
+      .and_then(|mut changes| {
+        for changes in &mut changes {
+          changes.normalize(&self.specifier_map)?;
+        }
+        Ok(changes)
+      })
+      .map_err(|err| {
+        log::error!("Unable to organize imports from TypeScript: {}", err);
+        LspError::internal_error()
+      })
+  }
+
   #[allow(clippy::too_many_arguments)]
   pub async fn get_edits_for_refactor(
     &self,
@@ -1091,6 +1135,101 @@ impl TsServer {
       })
   }
 
+  pub async fn prepare_type_hierarchy(
+    &self,
+    snapshot: Arc<StateSnapshot>,
+    specifier: ModuleSpecifier,
+    position: u32,
+    scope: Option<ModuleSpecifier>,
+  ) -> Result<Option<Vec<TypeHierarchyItem>>, LspError> {
+    let req = TscRequest::PrepareTypeHierarchy((
+      self.specifier_map.denormalize(&specifier),
+      position,
+    ));
+    self
+      .request::<Option<Vec<TypeHierarchyItem>>>(snapshot, req, scope)
+      .await
+      .and_then(|mut items| {
+        for item in items.iter_mut().flatten() {
+          item.normalize(&self.specifier_map)?;
+        }
+        Ok(items)
+      })
+      .map_err(|err| {
+        log::error!("Failed to request to tsserver {}", err);
+        LspError::invalid_request()
+      })
+  }
+
+  pub async fn provide_type_hierarchy_supertypes(
+    &self,
+    snapshot: Arc<StateSnapshot>,
+    specifier: ModuleSpecifier,
+    position: u32,
+    scope: Option<ModuleSpecifier>,
+  ) -> Result<Vec<TypeHierarchyItem>, LspError> {
+    let req = TscRequest::ProvideTypeHierarchySupertypes((
+      self.specifier_map.denormalize(&specifier),
+      position,
+    ));
+    self
+      .request::<Vec<TypeHierarchyItem>>(snapshot, req, scope)
+      .await
+      .and_then(|mut items| {
+        for item in &mut items {
+          item.normalize(&self.specifier_map)?;
+        }
+        Ok(items)
+      })
+      .map_err(|err| {
+        log::error!("Failed to request to tsserver {}", err);
+        LspError::invalid_request()
+      })
+  }
+
+  pub async fn provide_type_hierarchy_subtypes(
+    &self,
+    snapshot: Arc<StateSnapshot>,
+    specifier: ModuleSpecifier,
+    position: u32,
+  ) -> Result<Vec<TypeHierarchyItem>, AnyError> {
+    let req = TscRequest::ProvideTypeHierarchySubtypes((
+      self.specifier_map.denormalize(&specifier),
+      position,
+    ));
+    let mut results = FuturesOrdered::new();
+    for scope in snapshot
+      .config
+      .tree
+      .data_by_scope()
+      .keys()
+      .map(Some)
+      .chain(std::iter::once(None))
+    {
+      results.push_back(self.request::<Vec<TypeHierarchyItem>>(
+        snapshot.clone(),
+        req.clone(),
+        scope.cloned(),
+      ));
+    }
+    let mut all_items = IndexSet::new();
+    while let Some(items) = results.next().await {
+      let mut items = items
+        .inspect_err(|err| {
+          let err = err.to_string();
+          if !err.contains("Could not find source file") {
+            lsp_warn!("Unable to get subtypes from TypeScript: {err}");
+          }
+        })
+        .unwrap_or_default();
+      for item in &mut items {
+        item.normalize(&self.specifier_map)?;
+      }
+      all_items.extend(items)
+    }
+    Ok(all_items.into_iter().collect())
+  }
+
   pub async fn find_rename_locations(
     &self,
     snapshot: Arc<StateSnapshot>,
@@ -1160,6 +1299,23 @@ impl TsServer {
     })
   }
 
+  pub async fn get_linked_editing_range(
+    &self,
+    snapshot: Arc<StateSnapshot>,
+    specifier: ModuleSpecifier,
+    position: u32,
+    scope: Option<ModuleSpecifier>,
+  ) -> Result<Option<LinkedEditingInfo>, LspError> {
+    let req = TscRequest::GetLinkedEditingRangeAtPosition((
+      self.specifier_map.denormalize(&specifier),
+      position,
+    ));
+    self.request(snapshot, req, scope).await.map_err(|err| {
+      log::error!("Failed to request to tsserver {}", err);
+      LspError::invalid_request()
+    })
+  }
+
   pub async fn get_encoded_semantic_classifications(
     &self,
     snapshot: Arc<StateSnapshot>,
@@ -2943,6 +3099,8 @@ pub fn file_text_changes_to_workspace_edit(
 pub struct RefactorEditInfo {
   pub edits: Vec<FileTextChanges>,
   #[serde(skip_serializing_if = "Option::is_none")]
+  pub rename_filename: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
   pub rename_location: Option<u32>,
 }
 
@@ -2954,9 +3112,42 @@ impl RefactorEditInfo {
     for changes in &mut self.edits {
       changes.normalize(specifier_map)?;
     }
+    if let Some(rename_filename) = &mut self.rename_filename {
+      *rename_filename =
+        specifier_map.normalize(&*rename_filename)?.to_string();
+    }
     Ok(())
   }
 
+  /// The position of the name which the refactor introduced (ex. of an
+  /// extracted function or constant) once the edits are applied, so it can be
+  /// renamed right away.
+  pub fn rename_position(
+    &self,
+    language_server: &language_server::Inner,
+  ) -> Option<(ModuleSpecifier, lsp::Position)> {
+    let rename_location = self.rename_location?;
+    let specifier = resolve_url(self.rename_filename.as_ref()?).ok()?;
+    let asset_or_doc =
+      language_server.get_asset_or_document(&specifier).ok()?;
+    let mut text = asset_or_doc.text().encode_utf16().collect::<Vec<_>>();
+    let mut text_changes = self
+      .edits
+      .iter()
+      .filter(|c| c.file_name == specifier.as_str())
+      .flat_map(|c| &c.text_changes)
+      .collect::<Vec<_>>();
+    // apply the changes from the end so the spans of the others stay valid
+    text_changes.sort_by_key(|c| std::cmp::Reverse(c.span.start));
+    for change in text_changes {
+      let start = (change.span.start as usize).min(text.len());
+      let end = (start + change.span.length as usize).min(text.len());
+      text.splice(start..end, change.new_text.encode_utf16());
+    }
+    let line_index = LineIndex::new(&String::from_utf16_lossy(&text));
+    Some((specifier, line_index.position_tsc(rename_location.into())))
+  }
+
   pub fn to_workspace_edit(
     &self,
     language_server: &language_server::Inner,
@@ -3198,6 +3389,16 @@ impl CallHierarchyItem {
     let name = if use_file_name {
       if let Some(file_path) = maybe_file_path.as_ref() {
         file_path.file_name().unwrap().to_string_lossy().to_string()
+      } else if let Some(file_name) = target_specifier
+        .path_segments()
+        .and_then(|mut s| s.next_back())
+        .filter(|s| {
+          !s.is_empty() && matches!(target_specifier.scheme(), "http" | "https")
+        })
+      {
+        // remote modules are named like local ones, with the rest of the url
+        // as the detail
+        file_name.to_string()
       } else {
         uri.as_str().to_string()
       }
@@ -3217,6 +3418,11 @@ impl CallHierarchyItem {
         } else {
           parent_dir.to_string_lossy().to_string()
         }
+      } else if matches!(target_specifier.scheme(), "http" | "https") {
+        target_specifier
+          .join("./")
+          .map(|dir| dir.to_string())
+          .unwrap_or_default()
       } else {
         String::new()
       }
@@ -3332,6 +3538,57 @@ impl CallHierarchyOutgoingCall {
   }
 }
 
+#[derive(Debug, Eq, PartialEq, Hash, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeHierarchyItem {
+  name: String,
+  kind: ScriptElementKind,
+  kind_modifiers: String,
+  file: String,
+  span: TextSpan,
+  selection_span: TextSpan,
+}
+
+impl TypeHierarchyItem {
+  fn normalize(
+    &mut self,
+    specifier_map: &TscSpecifierMap,
+  ) -> Result<(), AnyError> {
+    self.file = specifier_map.normalize(&self.file)?.to_string();
+    Ok(())
+  }
+
+  pub fn try_resolve_type_hierarchy_item(
+    &self,
+    language_server: &language_server::Inner,
+  ) -> Option<lsp::TypeHierarchyItem> {
+    let target_specifier = resolve_url(&self.file).ok()?;
+    let target_asset_or_doc =
+      language_server.get_maybe_asset_or_document(&target_specifier)?;
+    let line_index = target_asset_or_doc.line_index();
+    let file_referrer = language_server
+      .documents
+      .get_file_referrer(&target_specifier);
+    let uri = language_server
+      .url_map
+      .specifier_to_uri(&target_specifier, file_referrer.as_deref())
+      .ok()?;
+    let tags = parse_kind_modifier(&self.kind_modifiers)
+      .contains("deprecated")
+      .then_some(lsp::SymbolTag::DEPRECATED);
+    Some(lsp::TypeHierarchyItem {
+      name: self.name.clone(),
+      kind: self.kind.clone().into(),
+      tags,
+      detail: None,
+      uri,
+      range: self.span.to_range(line_index.clone()),
+      selection_range: self.selection_span.to_range(line_index),
+      data: None,
+    })
+  }
+}
+
 /// Used to convert completion code actions into a command and additional text
 /// edits to pass in the completion item.
 fn parse_code_actions(
@@ -4134,6 +4391,30 @@ impl SelectionRange {
   }
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkedEditingInfo {
+  ranges: Vec<TextSpan>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  word_pattern: Option<String>,
+}
+
+impl LinkedEditingInfo {
+  pub fn to_linked_editing_ranges(
+    &self,
+    line_index: Arc<LineIndex>,
+  ) -> lsp::LinkedEditingRanges {
+    lsp::LinkedEditingRanges {
+      ranges: self
+        .ranges
+        .iter()
+        .map(|span| span.to_range(line_index.clone()))
+        .collect(),
+      word_pattern: self.word_pattern.clone(),
+    }
+  }
+}
+
 #[derive(Debug, Default)]
 pub struct TscSpecifierMap {
   normalized_specifiers: DashMap<String, ModuleSpecifier>,
@@ -4892,6 +5173,8 @@ pub struct UserPreferences {
   pub allow_rename_of_import_path: Option<bool>,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub auto_import_file_exclude_patterns: Option<Vec<String>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub organize_imports_ignore_case: Option<bool>,
 }
 
 impl UserPreferences {
@@ -5115,6 +5398,14 @@ pub struct CombinedCodeFixScope {
   file_name: String,
 }
 
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganizeImportsArgs {
+  r#type: &'static str,
+  file_name: String,
+  mode: &'static str,
+}
+
 #[derive(Serialize, Clone, Copy)]
 pub struct JsNull;
 
@@ -5122,6 +5413,7 @@ pub struct JsNull;
 pub enum TscRequest {
   GetDiagnostics((Vec<String>, usize)),
   GetAssets,
+  GetSourceFileCacheStats,
 
   CleanupSemanticCache,
   // https://github.com/denoland/deno/blob/v1.37.1/cli/tsc/dts/typescript.d.ts#L6230
@@ -5172,6 +5464,10 @@ pub enum TscRequest {
       Option<UserPreferences>,
     )>,
   ),
+  // https://github.com/denoland/deno/blob/v1.37.1/cli/tsc/dts/typescript.d.ts#L6277
+  OrganizeImports(
+    Box<(OrganizeImportsArgs, FormatCodeSettings, UserPreferences)>,
+  ),
   // https://github.com/denoland/deno/blob/v1.37.1/cli/tsc/dts/typescript.d.ts#L6281
   GetEditsForFileRename(
     Box<(String, String, FormatCodeSettings, UserPreferences)>,
@@ -5214,10 +5510,16 @@ pub enum TscRequest {
   ProvideCallHierarchyOutgoingCalls((String, u32)),
   // https://github.com/denoland/deno/blob/v1.37.1/cli/tsc/dts/typescript.d.ts#L6236
   PrepareCallHierarchy((String, u32)),
+  // not part of the TypeScript API, implemented in 99_main_compiler.js
+  PrepareTypeHierarchy((String, u32)),
+  ProvideTypeHierarchySupertypes((String, u32)),
+  ProvideTypeHierarchySubtypes((String, u32)),
   // https://github.com/denoland/deno/blob/v1.37.1/cli/tsc/dts/typescript.d.ts#L6221
   FindRenameLocations((String, u32, bool, bool, bool)),
   // https://github.com/denoland/deno/blob/v1.37.1/cli/tsc/dts/typescript.d.ts#L6224
   GetSmartSelectionRange((String, u32)),
+  // https://github.com/denoland/deno/blob/v2.0.0-rc.3/cli/tsc/dts/typescript.d.ts#L10173
+  GetLinkedEditingRangeAtPosition((String, u32)),
   // https://github.com/denoland/deno/blob/v1.37.1/cli/tsc/dts/typescript.d.ts#L6183
   GetEncodedSemanticClassifications((String, TextSpan, &'static str)),
   // https://github.com/denoland/deno/blob/v1.37.1/cli/tsc/dts/typescript.d.ts#L6217
@@ -5268,6 +5570,9 @@ impl TscRequest {
       TscRequest::GetEditsForFileRename(args) => {
         ("getEditsForFileRename", Some(serde_v8::to_v8(scope, args)?))
       }
+      TscRequest::OrganizeImports(args) => {
+        ("organizeImports", Some(serde_v8::to_v8(scope, args)?))
+      }
       TscRequest::GetDocumentHighlights(args) => {
         ("getDocumentHighlights", Some(serde_v8::to_v8(scope, args)?))
       }
@@ -5305,6 +5610,17 @@ impl TscRequest {
       TscRequest::PrepareCallHierarchy(args) => {
         ("prepareCallHierarchy", Some(serde_v8::to_v8(scope, args)?))
       }
+      TscRequest::PrepareTypeHierarchy(args) => {
+        ("$prepareTypeHierarchy", Some(serde_v8::to_v8(scope, args)?))
+      }
+      TscRequest::ProvideTypeHierarchySupertypes(args) => (
+        "$provideTypeHierarchySupertypes",
+        Some(serde_v8::to_v8(scope, args)?),
+      ),
+      TscRequest::ProvideTypeHierarchySubtypes(args) => (
+        "$provideTypeHierarchySubtypes",
+        Some(serde_v8::to_v8(scope, args)?),
+      ),
       TscRequest::FindRenameLocations(args) => {
         ("findRenameLocations", Some(serde_v8::to_v8(scope, args)?))
       }
@@ -5312,6 +5628,10 @@ impl TscRequest {
         "getSmartSelectionRange",
         Some(serde_v8::to_v8(scope, args)?),
       ),
+      TscRequest::GetLinkedEditingRangeAtPosition(args) => (
+        "getLinkedEditingRangeAtPosition",
+        Some(serde_v8::to_v8(scope, args)?),
+      ),
       TscRequest::GetEncodedSemanticClassifications(args) => (
         "getEncodedSemanticClassifications",
         Some(serde_v8::to_v8(scope, args)?),
@@ -5327,6 +5647,7 @@ impl TscRequest {
       }
       TscRequest::CleanupSemanticCache => ("cleanupSemanticCache", None),
       TscRequest::GetAssets => ("$getAssets", None),
+      TscRequest::GetSourceFileCacheStats => ("$getSourceFileCacheStats", None),
     };
 
     Ok(args)
@@ -5345,6 +5666,7 @@ impl TscRequest {
       TscRequest::GetCombinedCodeFix(_) => "getCombinedCodeFix",
       TscRequest::GetEditsForRefactor(_) => "getEditsForRefactor",
       TscRequest::GetEditsForFileRename(_) => "getEditsForFileRename",
+      TscRequest::OrganizeImports(_) => "organizeImports",
       TscRequest::GetDocumentHighlights(_) => "getDocumentHighlights",
       TscRequest::GetDefinitionAndBoundSpan(_) => "getDefinitionAndBoundSpan",
       TscRequest::GetTypeDefinitionAtPosition(_) => {
@@ -5363,8 +5685,18 @@ impl TscRequest {
         "provideCallHierarchyOutgoingCalls"
       }
       TscRequest::PrepareCallHierarchy(_) => "prepareCallHierarchy",
+      TscRequest::PrepareTypeHierarchy(_) => "$prepareTypeHierarchy",
+      TscRequest::ProvideTypeHierarchySupertypes(_) => {
+        "$provideTypeHierarchySupertypes"
+      }
+      TscRequest::ProvideTypeHierarchySubtypes(_) => {
+        "$provideTypeHierarchySubtypes"
+      }
       TscRequest::FindRenameLocations(_) => "findRenameLocations",
       TscRequest::GetSmartSelectionRange(_) => "getSmartSelectionRange",
+      TscRequest::GetLinkedEditingRangeAtPosition(_) => {
+        "getLinkedEditingRangeAtPosition"
+      }
       TscRequest::GetEncodedSemanticClassifications(_) => {
         "getEncodedSemanticClassifications"
       }
@@ -5372,6 +5704,7 @@ impl TscRequest {
       TscRequest::GetNavigateToItems(_) => "getNavigateToItems",
       TscRequest::ProvideInlayHints(_) => "provideInlayHints",
       TscRequest::GetAssets => "$getAssets",
+      TscRequest::GetSourceFileCacheStats => "$getSourceFileCacheStats",
     }
   }
 }
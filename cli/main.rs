@@ -20,6 +20,7 @@ mod node;
 mod npm;
 mod ops;
 mod resolver;
+mod scheme_handlers;
 mod shared;
 mod standalone;
 mod task_runner;
@@ -30,6 +31,7 @@ mod version;
 mod worker;
 
 use crate::args::flags_from_vec;
+use crate::args::CompletionsFlags;
 use crate::args::DenoSubcommand;
 use crate::args::Flags;
 use crate::util::display;
@@ -110,7 +112,9 @@ async fn run_subcommand(flags: Arc<Flags>) -> Result<i32, AnyError> {
         tools::bench::run_benchmarks(flags, bench_flags).await
       }
     }),
-    DenoSubcommand::Bundle => exit_with_message("⚠️ `deno bundle` was removed in Deno 2.\n\nSee the Deno 1.x to 2.x Migration Guide for migration instructions: https://docs.deno.com/runtime/manual/advanced/migrate_deprecations", 1),
+    DenoSubcommand::Bundle(bundle_flags) => spawn_subcommand(async {
+      tools::bundle::bundle(flags, bundle_flags).await
+    }),
     DenoSubcommand::Doc(doc_flags) => {
       spawn_subcommand(async { tools::doc::doc(flags, doc_flags).await })
     }
@@ -118,7 +122,7 @@ async fn run_subcommand(flags: Arc<Flags>) -> Result<i32, AnyError> {
       tools::run::eval_command(flags, eval_flags).await
     }),
     DenoSubcommand::Cache(cache_flags) => spawn_subcommand(async move {
-      tools::installer::install_from_entrypoints(flags, &cache_flags.files).await
+      tools::cache::cache(flags, cache_flags).await
     }),
     DenoSubcommand::Check(check_flags) => spawn_subcommand(async move {
       tools::check::check(flags, check_flags).await
@@ -139,9 +143,7 @@ async fn run_subcommand(flags: Arc<Flags>) -> Result<i32, AnyError> {
     }
     DenoSubcommand::Init(init_flags) => {
       spawn_subcommand(async {
-        // make compiler happy since init_project is sync
-        tokio::task::yield_now().await;
-        tools::init::init_project(init_flags)
+        tools::init::init_project(flags, init_flags).await
       })
     }
     DenoSubcommand::Info(info_flags) => {
@@ -159,7 +161,9 @@ async fn run_subcommand(flags: Arc<Flags>) -> Result<i32, AnyError> {
     DenoSubcommand::Uninstall(uninstall_flags) => spawn_subcommand(async {
       tools::installer::uninstall(flags, uninstall_flags).await
     }),
-    DenoSubcommand::Lsp => spawn_subcommand(async { lsp::start().await }),
+    DenoSubcommand::Lsp(lsp_flags) => {
+      spawn_subcommand(async { lsp::start(lsp_flags).await })
+    }
     DenoSubcommand::Lint(lint_flags) => spawn_subcommand(async {
       if lint_flags.rules {
         tools::lint::print_rules_list(
@@ -255,7 +259,14 @@ async fn run_subcommand(flags: Arc<Flags>) -> Result<i32, AnyError> {
     }
     DenoSubcommand::Completions(completions_flags) => {
       spawn_subcommand(async move {
-        display::write_to_stdout_ignore_sigpipe(&completions_flags.buf)
+        match completions_flags {
+          CompletionsFlags::Static(buf) => {
+            display::write_to_stdout_ignore_sigpipe(&buf).map_err(AnyError::from)
+          }
+          CompletionsFlags::Dynamic(kind) => {
+            tools::completions::print_dynamic_completions(flags, kind).await
+          }
+        }
       })
     }
     DenoSubcommand::Types => spawn_subcommand(async move {
@@ -467,7 +478,7 @@ fn resolve_flags_and_init(
   let default_v8_flags = match flags.subcommand {
     // Using same default as VSCode:
     // https://github.com/microsoft/vscode/blob/48d4ba271686e8072fc6674137415bc80d936bc7/extensions/typescript-language-features/src/configuration/configuration.ts#L213-L214
-    DenoSubcommand::Lsp => vec!["--max-old-space-size=3072".to_string()],
+    DenoSubcommand::Lsp(_) => vec!["--max-old-space-size=3072".to_string()],
     _ => {
       // TODO(bartlomieju): I think this can be removed as it's handled by `deno_core`
       // and its settings.
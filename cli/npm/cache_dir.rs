@@ -66,6 +66,11 @@ impl NpmCacheDir {
     &self.root_dir_url
   }
 
+  pub fn content_store_dir(&self) -> PathBuf {
+    // registry folders are named after hosts, which can't have underscores
+    self.root_dir.join("_content")
+  }
+
   pub fn package_folder_for_id(
     &self,
     folder_id: &NpmPackageCacheFolderId,
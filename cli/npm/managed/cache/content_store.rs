@@ -0,0 +1,338 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! A store of the files of the packages in the global npm cache, addressed by
+//! the checksum of their contents.
+//!
+//! The files of the package folders are hardlinks to the files in the store,
+//! so the files that several packages or versions of a package have in common
+//! take up disk space once. The `node_modules` directories hardlink to the
+//! package folders, so they share the files of the store as well.
+//!
+//! Since a file is shared by every package that has it, the files of the store
+//! are made read-only, so that editing one in place fails instead of silently
+//! changing the other packages. Files that no package links to anymore are
+//! removed from the store from time to time.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+
+use crate::util::checksum;
+use crate::util::path::get_atomic_file_path;
+
+/// Replaces the files in the directory with hardlinks to the files with the
+/// same contents in the store, adding the files that aren't in the store yet.
+pub fn link_dir_to_content_store(
+  dir: &Path,
+  store_dir: &Path,
+) -> Result<(), AnyError> {
+  let read_dir =
+    fs::read_dir(dir).with_context(|| format!("Reading {}", dir.display()))?;
+  for entry in read_dir {
+    let entry = entry?;
+    let file_type = entry.file_type()?;
+    let path = entry.path();
+    if file_type.is_dir() {
+      link_dir_to_content_store(&path, store_dir)?;
+    } else if file_type.is_file() {
+      link_file_to_content_store(&path, store_dir)
+        .with_context(|| format!("Linking {}", path.display()))?;
+    }
+  }
+  Ok(())
+}
+
+fn link_file_to_content_store(
+  path: &Path,
+  store_dir: &Path,
+) -> Result<(), AnyError> {
+  let data = fs::read(path)?;
+  let store_path = content_store_path(store_dir, path, &data)?;
+
+  if !is_intact(&store_path, &data) {
+    fs::create_dir_all(store_path.parent().unwrap())?;
+    set_readonly(path)?;
+    let temp_path = get_atomic_file_path(&store_path);
+    fs::hard_link(path, &temp_path)?;
+    if let Err(err) = fs::rename(&temp_path, &store_path) {
+      let _ = fs::remove_file(&temp_path);
+      return Err(err.into());
+    }
+    return Ok(());
+  }
+
+  // replace the file with a hardlink to the store, which is done via a
+  // rename so the file is never missing
+  let temp_path = get_atomic_file_path(path);
+  match fs::hard_link(&store_path, &temp_path) {
+    Ok(()) => {}
+    // another process removed the file in the meantime
+    Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+    Err(err) => return Err(err.into()),
+  }
+  if let Err(err) = fs::rename(&temp_path, path) {
+    let _ = fs::remove_file(&temp_path);
+    return Err(err.into());
+  }
+  Ok(())
+}
+
+/// Whether the file in the store still has the contents it's addressed by.
+///
+/// Files are made read-only when they're added to the store, so one that is
+/// writable again might have been modified through one of its hardlinks, and
+/// is replaced rather than shared.
+#[cfg(unix)]
+fn is_intact(store_path: &Path, _data: &[u8]) -> bool {
+  fs::metadata(store_path)
+    .is_ok_and(|metadata| metadata.permissions().readonly())
+}
+
+/// Whether the file in the store still has the contents it's addressed by.
+///
+/// Read-only files get in the way of deleting a `node_modules` directory on
+/// Windows, so the files of the store are compared instead.
+#[cfg(not(unix))]
+fn is_intact(store_path: &Path, data: &[u8]) -> bool {
+  fs::read(store_path).is_ok_and(|store_data| store_data == data)
+}
+
+#[cfg(unix)]
+fn set_readonly(path: &Path) -> Result<(), AnyError> {
+  use std::os::unix::fs::PermissionsExt;
+  let mut permissions = fs::metadata(path)?.permissions();
+  permissions.set_mode(permissions.mode() & !0o222);
+  fs::set_permissions(path, permissions)?;
+  Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_readonly(_path: &Path) -> Result<(), AnyError> {
+  Ok(())
+}
+
+/// How often the files that are no longer linked to are removed from the store.
+const GC_INTERVAL: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Removes the files of the store that no package links to anymore, unless
+/// that was already done within the last day.
+pub fn maybe_collect_content_store_garbage(
+  store_dir: &Path,
+) -> Result<(), AnyError> {
+  let marker_path = store_dir.join(".last_gc");
+  let is_due = match fs::metadata(&marker_path) {
+    Ok(metadata) => metadata
+      .modified()?
+      .elapsed()
+      .map_or(true, |elapsed| elapsed >= GC_INTERVAL),
+    Err(err) if err.kind() == ErrorKind::NotFound => true,
+    Err(err) => return Err(err.into()),
+  };
+  if !is_due || !store_dir.exists() {
+    return Ok(());
+  }
+  // mark the collection as done first, so concurrent processes skip it
+  fs::write(&marker_path, "")?;
+  collect_content_store_garbage(store_dir)
+}
+
+/// Removes the files of the store that no package links to anymore, which are
+/// the ones with a single hardlink left.
+#[cfg(unix)]
+fn collect_content_store_garbage(store_dir: &Path) -> Result<(), AnyError> {
+  use std::os::unix::fs::MetadataExt;
+  for entry in fs::read_dir(store_dir)? {
+    let entry = entry?;
+    if !entry.file_type()?.is_dir() {
+      continue;
+    }
+    for entry in fs::read_dir(entry.path())? {
+      let entry = entry?;
+      let metadata = entry.metadata()?;
+      if metadata.is_file() && metadata.nlink() == 1 {
+        match fs::remove_file(entry.path()) {
+          Ok(()) => {}
+          Err(err) if err.kind() == ErrorKind::NotFound => {}
+          Err(err) => return Err(err.into()),
+        }
+      }
+    }
+  }
+  Ok(())
+}
+
+/// The number of hardlinks of a file isn't available on Windows, so the
+/// store isn't collected there.
+#[cfg(not(unix))]
+fn collect_content_store_garbage(_store_dir: &Path) -> Result<(), AnyError> {
+  Ok(())
+}
+
+fn content_store_path(
+  store_dir: &Path,
+  path: &Path,
+  data: &[u8],
+) -> Result<PathBuf, AnyError> {
+  let mut name = checksum::gen(&[data]);
+  // hardlinks share their permissions, so executable files are stored
+  // separately from the other files
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::PermissionsExt;
+    if fs::metadata(path)?.permissions().mode() & 0o111 != 0 {
+      name.push_str("-exec");
+    }
+  }
+  #[cfg(not(unix))]
+  let _ = path;
+  Ok(store_dir.join(&name[..2]).join(&name[2..]))
+}
+
+#[cfg(test)]
+mod test {
+  use test_util::TempDir;
+
+  use super::*;
+
+  #[test]
+  fn links_files_with_the_same_contents() {
+    let temp_dir = TempDir::new();
+    let store_dir = temp_dir.path().join("store");
+    temp_dir.create_dir_all("a/lib");
+    temp_dir.create_dir_all("b");
+    temp_dir.write("a/lib/index.js", "module.exports = 1;");
+    temp_dir.write("a/README.md", "# a");
+    temp_dir.write("b/index.js", "module.exports = 1;");
+    link_dir_to_content_store(
+      temp_dir.path().join("a").as_path(),
+      store_dir.as_path(),
+    )
+    .unwrap();
+    link_dir_to_content_store(
+      temp_dir.path().join("b").as_path(),
+      store_dir.as_path(),
+    )
+    .unwrap();
+
+    assert_eq!(temp_dir.read_to_string("b/index.js"), "module.exports = 1;");
+    assert_eq!(temp_dir.read_to_string("a/README.md"), "# a");
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::MetadataExt;
+      let ino = |path: &str| {
+        temp_dir
+          .path()
+          .join(path)
+          .as_path()
+          .metadata()
+          .unwrap()
+          .ino()
+      };
+      assert_eq!(ino("a/lib/index.js"), ino("b/index.js"));
+      assert_ne!(ino("a/lib/index.js"), ino("a/README.md"));
+    }
+  }
+
+  #[test]
+  fn replaces_modified_files_in_the_store() {
+    let temp_dir = TempDir::new();
+    let store_dir = temp_dir.path().join("store");
+    temp_dir.create_dir_all("a");
+    temp_dir.create_dir_all("b");
+    temp_dir.write("a/index.js", "module.exports = 1;");
+    link_dir_to_content_store(
+      temp_dir.path().join("a").as_path(),
+      store_dir.as_path(),
+    )
+    .unwrap();
+    // modify the file in the store through its hardlink, keeping its size,
+    // which first requires making it writable again
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::PermissionsExt;
+      let path = temp_dir.path().join("a/index.js");
+      let path = path.as_path();
+      let mut permissions = path.metadata().unwrap().permissions();
+      assert!(permissions.readonly());
+      permissions.set_mode(permissions.mode() | 0o200);
+      fs::set_permissions(path, permissions).unwrap();
+    }
+    temp_dir.write("a/index.js", "module.exports = 2;");
+
+    temp_dir.write("b/index.js", "module.exports = 1;");
+    link_dir_to_content_store(
+      temp_dir.path().join("b").as_path(),
+      store_dir.as_path(),
+    )
+    .unwrap();
+    assert_eq!(temp_dir.read_to_string("b/index.js"), "module.exports = 1;");
+
+    // the store now holds the original contents again
+    temp_dir.create_dir_all("c");
+    temp_dir.write("c/index.js", "module.exports = 1;");
+    link_dir_to_content_store(
+      temp_dir.path().join("c").as_path(),
+      store_dir.as_path(),
+    )
+    .unwrap();
+    assert_eq!(temp_dir.read_to_string("c/index.js"), "module.exports = 1;");
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::MetadataExt;
+      let ino = |path: &str| {
+        temp_dir
+          .path()
+          .join(path)
+          .as_path()
+          .metadata()
+          .unwrap()
+          .ino()
+      };
+      assert_eq!(ino("b/index.js"), ino("c/index.js"));
+      assert_ne!(ino("a/index.js"), ino("c/index.js"));
+    }
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn collects_files_no_longer_linked_to() {
+    let temp_dir = TempDir::new();
+    let store_dir = temp_dir.path().join("store");
+    temp_dir.create_dir_all("a");
+    temp_dir.create_dir_all("b");
+    temp_dir.write("a/index.js", "module.exports = 1;");
+    temp_dir.write("b/index.js", "module.exports = 2;");
+    for dir in ["a", "b"] {
+      link_dir_to_content_store(
+        temp_dir.path().join(dir).as_path(),
+        store_dir.as_path(),
+      )
+      .unwrap();
+    }
+    let store_files = || {
+      walkdir::WalkDir::new(store_dir.as_path())
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+          entry.file_type().is_file() && entry.file_name() != ".last_gc"
+        })
+        .count()
+    };
+    assert_eq!(store_files(), 2);
+
+    temp_dir.remove_dir_all("b");
+    maybe_collect_content_store_garbage(store_dir.as_path()).unwrap();
+    assert_eq!(store_files(), 1);
+    assert_eq!(temp_dir.read_to_string("a/index.js"), "module.exports = 1;");
+
+    // not collected again within a day
+    temp_dir.remove_dir_all("a");
+    maybe_collect_content_store_garbage(store_dir.as_path()).unwrap();
+    assert_eq!(store_files(), 1);
+  }
+}
@@ -25,6 +25,7 @@ use crate::npm::NpmCacheDir;
 use crate::util::fs::atomic_write_file_with_retries;
 use crate::util::fs::hard_link_dir_recursive;
 
+mod content_store;
 mod registry_info;
 mod tarball;
 mod tarball_extract;
@@ -139,6 +140,12 @@ impl NpmCache {
     self.cache_dir.root_dir().to_owned()
   }
 
+  /// The folder of the store that the files of the package folders are
+  /// hardlinked to.
+  pub fn content_store_folder(&self) -> PathBuf {
+    self.cache_dir.content_store_dir()
+  }
+
   pub fn resolve_package_folder_id_from_specifier(
     &self,
     specifier: &ModuleSpecifier,
@@ -173,7 +180,10 @@ impl NpmCache {
     Ok(())
   }
 
-  fn get_registry_package_info_file_cache_path(&self, name: &str) -> PathBuf {
+  pub fn get_registry_package_info_file_cache_path(
+    &self,
+    name: &str,
+  ) -> PathBuf {
     let name_folder_path = self.package_name_folder(name);
     name_folder_path.join("registry.json")
   }
@@ -54,6 +54,7 @@ pub struct TarballCache {
   fs: Arc<dyn FileSystem>,
   http_client_provider: Arc<HttpClientProvider>,
   npmrc: Arc<ResolvedNpmRc>,
+  always_auth: bool,
   progress_bar: ProgressBar,
   memory_cache: Mutex<HashMap<PackageNv, MemoryCacheItem>>,
 }
@@ -64,6 +65,7 @@ impl TarballCache {
     fs: Arc<dyn FileSystem>,
     http_client_provider: Arc<HttpClientProvider>,
     npmrc: Arc<ResolvedNpmRc>,
+    always_auth: bool,
     progress_bar: ProgressBar,
   ) -> Self {
     Self {
@@ -71,6 +73,7 @@ impl TarballCache {
       fs,
       http_client_provider,
       npmrc,
+      always_auth,
       progress_bar,
       memory_cache: Default::default(),
     }
@@ -165,8 +168,15 @@ impl TarballCache {
       // IMPORTANT: npm registries may specify tarball URLs at different URLS than the
       // registry, so we MUST get the auth for the tarball URL and not the registry URL.
       let tarball_uri = Url::parse(&dist.tarball)?;
+      // With `always-auth`, the auth of the package's registry is sent when
+      // there's none for the tarball URL, but only to the registry's own
+      // origin so the token never leaks to a foreign host.
       let maybe_registry_config =
-        tarball_cache.npmrc.tarball_config(&tarball_uri);
+        tarball_cache.npmrc.tarball_config(&tarball_uri).or_else(|| {
+          (tarball_cache.always_auth
+            && tarball_uri.origin() == registry_url.origin())
+          .then(|| tarball_cache.npmrc.get_registry_config(&package_nv.name))
+        });
       let maybe_auth_header = maybe_registry_config.and_then(|c| maybe_auth_header_for_npm_registry(c).ok()?);
 
       let guard = tarball_cache.progress_bar.update(&dist.tarball);
@@ -213,6 +223,7 @@ impl TarballCache {
           };
           let dist = dist.clone();
           let package_nv = package_nv.clone();
+          let content_store_dir = tarball_cache.cache.content_store_folder();
           deno_core::unsync::spawn_blocking(move || {
             verify_and_extract_tarball(
               &package_nv,
@@ -220,6 +231,7 @@ impl TarballCache {
               &dist,
               &package_folder,
               extraction_mode,
+              &content_store_dir,
             )
           })
           .await?
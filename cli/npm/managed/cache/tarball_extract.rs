@@ -20,6 +20,9 @@ use tar::EntryType;
 
 use crate::util::path::get_atomic_dir_path;
 
+use super::content_store::link_dir_to_content_store;
+use super::content_store::maybe_collect_content_store_garbage;
+
 #[derive(Debug, Copy, Clone)]
 pub enum TarballExtractionMode {
   /// Overwrites the destination directory without deleting any files.
@@ -38,19 +41,40 @@ pub fn verify_and_extract_tarball(
   dist_info: &NpmPackageVersionDistInfo,
   output_folder: &Path,
   extraction_mode: TarballExtractionMode,
+  content_store_dir: &Path,
 ) -> Result<(), AnyError> {
   verify_tarball_integrity(package_nv, data, &dist_info.integrity())?;
 
   match extraction_mode {
-    TarballExtractionMode::Overwrite => extract_tarball(data, output_folder),
+    TarballExtractionMode::Overwrite => {
+      extract_tarball(data, output_folder)?;
+      link_to_content_store(output_folder, content_store_dir);
+    }
     TarballExtractionMode::SiblingTempDir => {
       let temp_dir = get_atomic_dir_path(output_folder);
       extract_tarball(data, &temp_dir)?;
+      link_to_content_store(&temp_dir, content_store_dir);
       rename_with_retries(&temp_dir, output_folder)
         .map_err(AnyError::from)
-        .context("Failed moving extracted tarball to final destination.")
+        .context("Failed moving extracted tarball to final destination.")?;
     }
   }
+  if let Err(err) = maybe_collect_content_store_garbage(content_store_dir) {
+    log::debug!("Failed collecting the npm content store: {:#}", err);
+  }
+  Ok(())
+}
+
+fn link_to_content_store(package_folder: &Path, content_store_dir: &Path) {
+  if let Err(err) = link_dir_to_content_store(package_folder, content_store_dir)
+  {
+    // the store only saves disk space, so the extracted files are kept
+    log::debug!(
+      "Failed linking {} to the npm content store: {:#}",
+      package_folder.display(),
+      err
+    );
+  }
 }
 
 fn rename_with_retries(
@@ -1,5 +1,6 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -7,6 +8,7 @@ use std::sync::Arc;
 use cache::RegistryInfoDownloader;
 use cache::TarballCache;
 use deno_ast::ModuleSpecifier;
+use deno_core::anyhow::bail;
 use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
 use deno_core::serde_json;
@@ -19,6 +21,7 @@ use deno_npm::resolution::ValidSerializedNpmResolutionSnapshot;
 use deno_npm::NpmPackageId;
 use deno_npm::NpmResolutionPackage;
 use deno_npm::NpmSystemInfo;
+use deno_runtime::colors;
 use deno_runtime::deno_fs::FileSystem;
 use deno_runtime::deno_node::NodePermissions;
 use deno_runtime::deno_node::NodeRequireResolver;
@@ -35,6 +38,7 @@ use crate::args::LifecycleScriptsConfig;
 use crate::args::NpmInstallDepsProvider;
 use crate::args::NpmProcessState;
 use crate::args::NpmProcessStateKind;
+use crate::args::NpmRcFetchOptions;
 use crate::cache::FastInsecureHasher;
 use crate::http_util::HttpClientProvider;
 use crate::util::fs::canonicalize_path_maybe_not_exists_with_fs;
@@ -74,12 +78,14 @@ pub struct CliNpmResolverManagedCreateOptions {
   pub npm_system_info: NpmSystemInfo,
   pub npm_install_deps_provider: Arc<NpmInstallDepsProvider>,
   pub npmrc: Arc<ResolvedNpmRc>,
+  pub npmrc_fetch_options: NpmRcFetchOptions,
   pub lifecycle_scripts: LifecycleScriptsConfig,
 }
 
 pub async fn create_managed_npm_resolver_for_lsp(
-  options: CliNpmResolverManagedCreateOptions,
+  mut options: CliNpmResolverManagedCreateOptions,
 ) -> Arc<dyn CliNpmResolver> {
+  options.http_client_provider = resolve_npm_http_client_provider(&options);
   let npm_cache = create_cache(&options);
   let npm_api = create_api(&options, npm_cache.clone());
   // spawn due to the lsp's `Send` requirement
@@ -98,6 +104,7 @@ pub async fn create_managed_npm_resolver_for_lsp(
       npm_api,
       npm_cache,
       options.npmrc,
+      options.npmrc_fetch_options.always_auth,
       options.npm_install_deps_provider,
       options.text_only_progress_bar,
       options.maybe_node_modules_path,
@@ -111,8 +118,9 @@ pub async fn create_managed_npm_resolver_for_lsp(
 }
 
 pub async fn create_managed_npm_resolver(
-  options: CliNpmResolverManagedCreateOptions,
+  mut options: CliNpmResolverManagedCreateOptions,
 ) -> Result<Arc<dyn CliNpmResolver>, AnyError> {
+  options.http_client_provider = resolve_npm_http_client_provider(&options);
   let npm_cache = create_cache(&options);
   let npm_api = create_api(&options, npm_cache.clone());
   let snapshot = resolve_snapshot(&npm_api, options.snapshot).await?;
@@ -123,6 +131,7 @@ pub async fn create_managed_npm_resolver(
     npm_api,
     npm_cache,
     options.npmrc,
+    options.npmrc_fetch_options.always_auth,
     options.npm_install_deps_provider,
     options.text_only_progress_bar,
     options.maybe_node_modules_path,
@@ -140,6 +149,7 @@ fn create_inner(
   npm_api: Arc<CliNpmRegistryApi>,
   npm_cache: Arc<NpmCache>,
   npm_rc: Arc<ResolvedNpmRc>,
+  always_auth: bool,
   npm_install_deps_provider: Arc<NpmInstallDepsProvider>,
   text_only_progress_bar: crate::util::progress_bar::ProgressBar,
   node_modules_dir_path: Option<PathBuf>,
@@ -157,6 +167,7 @@ fn create_inner(
     fs.clone(),
     http_client_provider.clone(),
     npm_rc.clone(),
+    always_auth,
     text_only_progress_bar.clone(),
   ));
   let fs_resolver = create_npm_fs_resolver(
@@ -185,6 +196,33 @@ fn create_inner(
   ))
 }
 
+/// Creates the provider of the HTTP clients for the npm registries, which
+/// don't verify the TLS certificates of the registries with `strict-ssl=false`
+/// in the `.npmrc`.
+fn resolve_npm_http_client_provider(
+  options: &CliNpmResolverManagedCreateOptions,
+) -> Arc<HttpClientProvider> {
+  if options.npmrc_fetch_options.strict_ssl {
+    return options.http_client_provider.clone();
+  }
+  let hosts = options
+    .npmrc
+    .get_all_known_registries_urls()
+    .iter()
+    .filter_map(|url| url.host_str().map(|host| host.to_string()))
+    .collect::<Vec<_>>();
+  log::warn!(
+    "{} TLS certificate validation is disabled for the npm registries due to \"strict-ssl=false\" in .npmrc: {}",
+    colors::yellow("Warning"),
+    hosts.join(", ")
+  );
+  Arc::new(
+    options
+      .http_client_provider
+      .with_unsafely_ignored_certificate_errors(hosts),
+  )
+}
+
 fn create_cache(options: &CliNpmResolverManagedCreateOptions) -> Arc<NpmCache> {
   Arc::new(NpmCache::new(
     NpmCacheDir::new(
@@ -448,6 +486,15 @@ impl ManagedCliNpmResolver {
   }
 
   pub async fn cache_packages(&self) -> Result<(), AnyError> {
+    let patched_pkgs = self.npm_install_deps_provider.patched_pkgs();
+    if !patched_pkgs.is_empty() && self.root_node_modules_path().is_none() {
+      bail!(
+        "\"patchedDependencies\" are only applied with a node_modules directory. Set \"nodeModulesDir\": \"auto\" in the config file."
+      );
+    }
+    if let Some(lockfile) = &self.maybe_lockfile {
+      lockfile.set_npm_patches(patched_pkgs)?;
+    }
     self.fs_resolver.cache_packages().await
   }
 
@@ -518,6 +565,27 @@ impl ManagedCliNpmResolver {
   pub fn global_cache_root_folder(&self) -> PathBuf {
     self.npm_cache.root_folder()
   }
+
+  /// Gets the folders of the packages of the resolution in the global cache
+  /// along with the files of their registry information, which is everything
+  /// that's needed to install the packages without the network.
+  pub fn global_cache_paths(&self) -> Vec<PathBuf> {
+    let snapshot = self.resolution.snapshot();
+    let mut paths = Vec::new();
+    let mut seen_names = HashSet::new();
+    for package in snapshot.all_packages_for_every_system() {
+      let nv = &package.id.nv;
+      if seen_names.insert(nv.name.as_str()) {
+        paths.push(
+          self
+            .npm_cache
+            .get_registry_package_info_file_cache_path(&nv.name),
+        );
+      }
+      paths.push(self.npm_cache.package_folder_for_nv(nv));
+    }
+    paths
+  }
 }
 
 fn npm_process_state(
@@ -1,13 +1,16 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::sync::Arc;
 
 use deno_core::error::AnyError;
+use deno_core::parking_lot::Mutex;
 use deno_lockfile::NpmPackageDependencyLockfileInfo;
 use deno_lockfile::NpmPackageLockfileInfo;
 use deno_npm::registry::NpmRegistryApi;
+use deno_npm::registry::NpmRegistryPackageInfoLoadError;
 use deno_npm::resolution::NpmPackagesPartitioned;
 use deno_npm::resolution::NpmResolutionError;
 use deno_npm::resolution::NpmResolutionSnapshot;
@@ -50,6 +53,12 @@ pub struct AddPkgReqsResult {
 pub struct NpmResolution {
   api: Arc<CliNpmRegistryApi>,
   snapshot: SyncReadAsyncWriteLock<NpmResolutionSnapshot>,
+  /// The snapshot before the package requirements of `add_package_reqs` were
+  /// added, which is the one of the lockfile or of `set_package_reqs`.
+  base_snapshot: Mutex<NpmResolutionSnapshot>,
+  /// The package requirements added to the base snapshot, which are resolved
+  /// again together with the ones of every later call.
+  added_reqs: Mutex<BTreeSet<PackageReq>>,
   maybe_lockfile: Option<Arc<CliLockfile>>,
 }
 
@@ -80,7 +89,9 @@ impl NpmResolution {
   ) -> Self {
     Self {
       api,
-      snapshot: SyncReadAsyncWriteLock::new(initial_snapshot),
+      snapshot: SyncReadAsyncWriteLock::new(initial_snapshot.clone()),
+      base_snapshot: Mutex::new(initial_snapshot),
+      added_reqs: Default::default(),
       maybe_lockfile,
     }
   }
@@ -91,18 +102,58 @@ impl NpmResolution {
   ) -> AddPkgReqsResult {
     // only allow one thread in here at a time
     let snapshot_lock = self.snapshot.acquire().await;
+
+    // The requirements are resolved on the base snapshot together with the
+    // ones that were added before, so the versions picked for the peer
+    // dependencies are the same as if they were all found at once, instead of
+    // depending on which of them were found first, like the dynamic imports.
+    let reqs_set = package_reqs.iter().collect::<HashSet<_>>();
+    let previous_reqs = self
+      .added_reqs
+      .lock()
+      .iter()
+      .filter(|req| !reqs_set.contains(req))
+      .cloned()
+      .collect::<Vec<_>>();
+    let all_reqs = previous_reqs
+      .iter()
+      .chain(package_reqs)
+      .cloned()
+      .collect::<Vec<_>>();
+    let current_snapshot = snapshot_lock.read().clone();
+    let is_up_to_date = package_reqs
+      .iter()
+      .all(|req| current_snapshot.package_reqs().contains_key(req));
     let result = add_package_reqs_to_snapshot(
       &self.api,
-      package_reqs,
+      &all_reqs,
       self.maybe_lockfile.clone(),
-      || snapshot_lock.read().clone(),
+      || {
+        if is_up_to_date {
+          current_snapshot.clone()
+        } else {
+          self.base_snapshot.lock().clone()
+        }
+      },
     )
     .await;
 
     AddPkgReqsResult {
-      results: result.results,
+      results: result
+        .results
+        .into_iter()
+        .skip(previous_reqs.len())
+        .collect(),
       dependencies_result: match result.dep_graph_result {
         Ok(snapshot) => {
+          if let Some(lockfile) = &self.maybe_lockfile {
+            remove_replaced_lockfile_packages(
+              lockfile,
+              &current_snapshot,
+              &snapshot,
+            );
+          }
+          self.added_reqs.lock().extend(package_reqs.iter().cloned());
           *snapshot_lock.write() = snapshot;
           Ok(())
         }
@@ -140,6 +191,8 @@ impl NpmResolution {
     .await
     .into_result()?;
 
+    *self.base_snapshot.lock() = snapshot.clone();
+    self.added_reqs.lock().clear();
     *snapshot_lock.write() = snapshot;
 
     Ok(())
@@ -279,12 +332,43 @@ async fn add_package_reqs_to_snapshot(
       dep_graph_result: Ok(snapshot),
     };
   }
+  // the resolution of a frozen lockfile comes from the lockfile, so the
+  // packages that aren't in it are errors instead of being resolved from the
+  // registry
+  if maybe_lockfile
+    .as_ref()
+    .is_some_and(|lockfile| lockfile.frozen)
+  {
+    let not_locked_error = |req: &PackageReq| {
+      NpmResolutionError::Registry(NpmRegistryPackageInfoLoadError::LoadError(
+        Arc::new(CliLockfile::not_locked_error(&format!(
+          "The npm package \"npm:{req}\""
+        ))),
+      ))
+    };
+    let missing_req = package_reqs
+      .iter()
+      .find(|req| !snapshot.package_reqs().contains_key(*req))
+      .unwrap();
+    return deno_npm::resolution::AddPkgReqsResult {
+      results: package_reqs
+        .iter()
+        .map(|req| match snapshot.package_reqs().get(req) {
+          Some(nv) => Ok(nv.clone()),
+          None => Err(not_locked_error(req)),
+        })
+        .collect(),
+      dep_graph_result: Err(not_locked_error(missing_req)),
+    };
+  }
   log::debug!(
     /* this string is used in tests */
     "Running npm resolution."
   );
   let pending_resolver = get_npm_pending_resolver(api);
-  let result = pending_resolver.add_pkg_reqs(snapshot, package_reqs).await;
+  let result =
+    add_pkg_reqs_in_stable_order(&pending_resolver, snapshot, package_reqs)
+      .await;
   api.clear_memory_cache();
   let result = match &result.dep_graph_result {
     Err(NpmResolutionError::Resolution(err)) if api.mark_force_reload() => {
@@ -293,7 +377,9 @@ async fn add_package_reqs_to_snapshot(
 
       // try again
       let snapshot = get_new_snapshot();
-      let result = pending_resolver.add_pkg_reqs(snapshot, package_reqs).await;
+      let result =
+        add_pkg_reqs_in_stable_order(&pending_resolver, snapshot, package_reqs)
+          .await;
       api.clear_memory_cache();
       result
     }
@@ -309,6 +395,34 @@ async fn add_package_reqs_to_snapshot(
   result
 }
 
+/// Resolves the package requirements in a sorted order, so that the versions
+/// that are picked for the peer dependencies, which end up in the ids of the
+/// packages in the lockfile, don't depend on the order that the requirements
+/// were found in, like the order that the modules happened to load in.
+async fn add_pkg_reqs_in_stable_order(
+  pending_resolver: &NpmResolutionSnapshotPendingResolver<CliNpmRegistryApi>,
+  snapshot: NpmResolutionSnapshot,
+  package_reqs: &[PackageReq],
+) -> deno_npm::resolution::AddPkgReqsResult {
+  let mut order = (0..package_reqs.len()).collect::<Vec<_>>();
+  order.sort_by(|a, b| package_reqs[*a].cmp(&package_reqs[*b]));
+  let sorted_reqs = order
+    .iter()
+    .map(|index| package_reqs[*index].clone())
+    .collect::<Vec<_>>();
+  let result = pending_resolver.add_pkg_reqs(snapshot, &sorted_reqs).await;
+
+  // the results correspond to the provided requirements
+  let mut results = package_reqs.iter().map(|_| None).collect::<Vec<_>>();
+  for (index, req_result) in order.into_iter().zip(result.results) {
+    results[index] = Some(req_result);
+  }
+  deno_npm::resolution::AddPkgReqsResult {
+    results: results.into_iter().map(Option::unwrap).collect(),
+    dep_graph_result: result.dep_graph_result,
+  }
+}
+
 fn get_npm_pending_resolver(
   api: &CliNpmRegistryApi,
 ) -> NpmResolutionSnapshotPendingResolver<CliNpmRegistryApi> {
@@ -341,6 +455,25 @@ fn populate_lockfile_from_snapshot(
   }
 }
 
+/// Removes the packages from the lockfile that were resolved differently when
+/// the package requirements were resolved again.
+fn remove_replaced_lockfile_packages(
+  lockfile: &CliLockfile,
+  old_snapshot: &NpmResolutionSnapshot,
+  new_snapshot: &NpmResolutionSnapshot,
+) {
+  let mut lockfile = lockfile.lock();
+  for package in old_snapshot.all_packages_for_every_system() {
+    if new_snapshot.package_from_id(&package.id).is_none() {
+      lockfile
+        .content
+        .packages
+        .npm
+        .remove(&package.id.as_serialized());
+    }
+  }
+}
+
 fn npm_package_to_lockfile_info(
   pkg: &NpmResolutionPackage,
 ) -> NpmPackageLockfileInfo {
@@ -3,6 +3,7 @@
 //! Code for local node_modules resolution.
 
 mod bin_entries;
+mod prebuilt_addons;
 
 use std::borrow::Cow;
 use std::cell::RefCell;
@@ -17,6 +18,7 @@ use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
 
+use crate::args::InstallNpmPatchedPkg;
 use crate::args::LifecycleScriptsConfig;
 use crate::args::PackagesAllowedScripts;
 use crate::colors;
@@ -37,6 +39,7 @@ use deno_npm::NpmSystemInfo;
 use deno_runtime::deno_fs;
 use deno_runtime::deno_node::NodePermissions;
 use deno_semver::package::PackageNv;
+use deno_semver::package::PackageReq;
 use node_resolver::errors::PackageFolderResolveError;
 use node_resolver::errors::PackageFolderResolveIoError;
 use node_resolver::errors::PackageNotFoundError;
@@ -53,6 +56,7 @@ use crate::util::fs::canonicalize_path_maybe_not_exists_with_fs;
 use crate::util::fs::clone_dir_recursive;
 use crate::util::fs::symlink_dir;
 use crate::util::fs::LaxSingleProcessFsFlag;
+use crate::util::patch::apply_patch_to_dir;
 use crate::util::progress_bar::ProgressBar;
 use crate::util::progress_bar::ProgressMessagePrompt;
 
@@ -380,15 +384,34 @@ fn can_run_scripts(
 ) -> bool {
   match allow_scripts {
     PackagesAllowedScripts::All => true,
-    // TODO: make this more correct
     PackagesAllowedScripts::Some(allow_list) => allow_list.iter().any(|s| {
       let s = s.strip_prefix("npm:").unwrap_or(s);
-      s == package_nv.name || s == package_nv.to_string()
+      if s == package_nv.name {
+        return true;
+      }
+      // a version requirement, like `npm:esbuild@^0.23`
+      match PackageReq::from_str(s) {
+        Ok(req) => {
+          req.name == package_nv.name
+            && req.version_req.matches(&package_nv.version)
+        }
+        Err(_) => false,
+      }
     }),
     PackagesAllowedScripts::None => false,
   }
 }
 
+/// Whether the package folder was set up with the patch that's currently
+/// configured for the package, or without a patch if there's none.
+fn is_patch_up_to_date(
+  folder_path: &Path,
+  patched_pkg: Option<&InstallNpmPatchedPkg>,
+) -> bool {
+  let applied_checksum = fs::read_to_string(folder_path.join(".patched")).ok();
+  applied_checksum.as_deref() == patched_pkg.map(|pkg| pkg.checksum.as_str())
+}
+
 // npm defaults to running `node-gyp rebuild` if there is a `binding.gyp` file
 // but it always fails if the package excludes the `binding.gyp` file when they publish.
 // (for example, `fsevents` hits this)
@@ -410,6 +433,21 @@ fn has_lifecycle_scripts(
     || package.scripts.contains_key("postinstall")
 }
 
+/// Whether the lifecycle scripts of the package only download or compile a
+/// native addon, while the package ships a prebuilt one for this platform
+/// that it loads by itself. The package then works without running them.
+fn only_builds_prebuilt_addon(
+  package: &NpmResolutionPackage,
+  package_path: &Path,
+  system_info: &NpmSystemInfo,
+) -> bool {
+  ["preinstall", "install", "postinstall"]
+    .iter()
+    .filter_map(|script_name| package.scripts.get(*script_name))
+    .all(|script| prebuilt_addons::is_addon_install_script(script))
+    && prebuilt_addons::find_prebuilt_addon(package_path, system_info).is_some()
+}
+
 /// Creates a pnpm style folder structure.
 #[allow(clippy::too_many_arguments)]
 async fn sync_resolution_with_fs(
@@ -461,6 +499,7 @@ async fn sync_resolution_with_fs(
   let mut newest_packages_by_name: HashMap<&String, &NpmResolutionPackage> =
     HashMap::with_capacity(package_partitions.packages.len());
   let bin_entries = Rc::new(RefCell::new(bin_entries::BinEntries::new()));
+  let mut packages_maybe_with_scripts = Vec::new();
   let mut packages_with_scripts = Vec::with_capacity(2);
   let mut packages_with_scripts_not_run = Vec::new();
   let packages_with_deprecation_warnings = Arc::new(Mutex::new(Vec::new()));
@@ -479,10 +518,12 @@ async fn sync_resolution_with_fs(
       get_package_folder_id_folder_name(&package.get_package_cache_folder_id());
     let folder_path = deno_local_registry_dir.join(&package_folder_name);
     let initialized_file = folder_path.join(".initialized");
+    let patched_pkg = npm_install_deps_provider.patched_pkg(&package.id.nv);
     if !cache
       .cache_setting()
       .should_use_for_npm_package(&package.id.nv.name)
       || !initialized_file.exists()
+      || !is_patch_up_to_date(&folder_path, patched_pkg)
     {
       // cache bust the dep from the dep setup cache so the symlinks
       // are forced to be recreated
@@ -504,11 +545,40 @@ async fn sync_resolution_with_fs(
         let package_path =
           join_package_name(&sub_node_modules, &package.id.nv.name);
         let cache_folder = cache.package_folder_for_nv(&package.id.nv);
+        let patch = patched_pkg.map(|pkg| {
+          (
+            pkg.patch.clone(),
+            pkg.checksum.clone(),
+            pkg.patch_path.clone(),
+          )
+        });
+        let package_nv = package.id.nv.clone();
 
         deno_core::unsync::spawn_blocking({
           let package_path = package_path.clone();
           move || {
+            let patched_file = folder_path.join(".patched");
+            if patched_file.exists() {
+              // start over from the original files of the package, as the
+              // previous patch may have added files
+              if package_path.exists() {
+                fs::remove_dir_all(&package_path).with_context(|| {
+                  format!("Removing {}", package_path.display())
+                })?;
+              }
+              fs::remove_file(&patched_file)?;
+            }
             clone_dir_recursive(&cache_folder, &package_path)?;
+            if let Some((patch, checksum, patch_path)) = patch {
+              apply_patch_to_dir(&package_path, &patch).with_context(|| {
+                format!(
+                  "Failed applying {} to npm package '{}'.",
+                  patch_path.display(),
+                  package_nv
+                )
+              })?;
+              fs::write(&patched_file, checksum)?;
+            }
             // write out a file that indicates this folder has been initialized
             fs::write(initialized_file, "")?;
 
@@ -535,6 +605,18 @@ async fn sync_resolution_with_fs(
       });
     }
 
+    if !package.scripts.is_empty() {
+      packages_maybe_with_scripts.push((package, folder_path));
+    }
+  }
+
+  while let Some(result) = cache_futures.next().await {
+    result?; // surface the first error
+  }
+
+  // the scripts are checked once the package folders are set up, as
+  // whether they're needed depends on the files of the package
+  for (package, folder_path) in packages_maybe_with_scripts {
     let sub_node_modules = folder_path.join("node_modules");
     let package_path =
       join_package_name(&sub_node_modules, &package.id.nv.name);
@@ -549,16 +631,15 @@ async fn sync_resolution_with_fs(
             scripts_run,
           ));
         }
-      } else if !scripts_run.exists() && !has_warned.exists() {
+      } else if !scripts_run.exists()
+        && !has_warned.exists()
+        && !only_builds_prebuilt_addon(package, &package_path, system_info)
+      {
         packages_with_scripts_not_run.push((has_warned, package.id.nv.clone()));
       }
     }
   }
 
-  while let Some(result) = cache_futures.next().await {
-    result?; // surface the first error
-  }
-
   // 2. Create any "copy" packages, which are used for peer dependencies
   for package in &package_partitions.copy_packages {
     let package_cache_folder_id = package.get_package_cache_folder_id();
@@ -835,6 +916,8 @@ async fn sync_resolution_with_fs(
               init_cwd,
               argv: &[],
               root_node_modules_dir: Some(root_node_modules_dir_path),
+              stdio: None,
+              cancellation_token: None,
             })
             .await?;
           if exit_code != 0 {
@@ -1204,6 +1287,26 @@ mod test {
     }
   }
 
+  #[test]
+  fn test_can_run_scripts() {
+    let nv = PackageNv::from_str("esbuild@0.23.1").unwrap();
+    let allowed = |list: &[&str]| {
+      can_run_scripts(
+        &PackagesAllowedScripts::Some(
+          list.iter().map(|s| s.to_string()).collect(),
+        ),
+        &nv,
+      )
+    };
+    assert!(allowed(&["npm:esbuild"]));
+    assert!(allowed(&["npm:esbuild@0.23.1"]));
+    assert!(allowed(&["npm:other", "npm:esbuild@^0.23"]));
+    assert!(!allowed(&["npm:esbuild@0.22"]));
+    assert!(!allowed(&["npm:esbuild-wasm"]));
+    assert!(can_run_scripts(&PackagesAllowedScripts::All, &nv));
+    assert!(!can_run_scripts(&PackagesAllowedScripts::None, &nv));
+  }
+
   #[test]
   fn test_setup_cache() {
     let temp_dir = TempDir::new();
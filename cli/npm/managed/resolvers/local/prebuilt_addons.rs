@@ -0,0 +1,277 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Detection of the prebuilt native addons that npm packages ship for the
+//! current platform, in the layouts of prebuildify and node-pre-gyp.
+//!
+//! Packages with such an addon load it by themselves, through node-gyp-build
+//! or node-pre-gyp, and usually have an install script that only downloads or
+//! compiles the addon when no prebuilt one matches. Such a script isn't needed
+//! when one is found, so no warning is shown when it isn't allowed to run.
+//! Scripts which are allowed to run are always run.
+//!
+//! Addons that are downloaded for the ABI of a specific Node.js version, like
+//! the ones of better-sqlite3 through prebuild-install, can't be loaded.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use deno_core::serde_json;
+use deno_npm::NpmSystemInfo;
+use serde::Deserialize;
+
+/// The N-API version implemented by the N-API host, which is reported as
+/// `process.versions.napi`.
+const NAPI_VERSION: u32 = 8;
+
+/// The programs of install scripts that only download or compile a native
+/// addon.
+const ADDON_INSTALL_PROGRAMS: &[&str] = &[
+  "node-gyp",
+  "node-gyp-build",
+  "node-gyp-build-optional-packages",
+  "node-pre-gyp",
+  "prebuild-install",
+];
+
+/// Whether the script only downloads or compiles a native addon, like
+/// `prebuild-install || node-gyp rebuild`.
+pub fn is_addon_install_script(script: &str) -> bool {
+  let mut commands = script
+    .split(['|', '&', ';'])
+    .map(str::trim)
+    .filter(|command| !command.is_empty())
+    .peekable();
+  commands.peek().is_some()
+    && commands.all(|command| {
+      let program = command.split_whitespace().next().unwrap_or_default();
+      ADDON_INSTALL_PROGRAMS.contains(&program)
+    })
+}
+
+/// Finds the prebuilt N-API addon of the package for the platform.
+///
+/// Addons that are built against the ABI of a specific Node.js version
+/// rather than N-API aren't supported by the N-API host, so they're never
+/// returned.
+pub fn find_prebuilt_addon(
+  package_path: &Path,
+  system_info: &NpmSystemInfo,
+) -> Option<PathBuf> {
+  find_prebuildify_addon(package_path, system_info)
+    .or_else(|| find_node_pre_gyp_addon(package_path, system_info))
+}
+
+fn libc() -> &'static str {
+  if cfg!(target_env = "musl") {
+    "musl"
+  } else {
+    "glibc"
+  }
+}
+
+/// Finds an addon in `prebuilds/<platform>-<arch>[+<arch>...]/`, whose file
+/// names are dot separated tags like `node.napi.glibc.node`.
+fn find_prebuildify_addon(
+  package_path: &Path,
+  system_info: &NpmSystemInfo,
+) -> Option<PathBuf> {
+  let prebuilds_dir = package_path.join("prebuilds");
+  let platform_dir = std::fs::read_dir(prebuilds_dir)
+    .ok()?
+    .filter_map(|entry| entry.ok())
+    .find(|entry| {
+      let name = entry.file_name();
+      let name = name.to_string_lossy();
+      name.split_once('-').is_some_and(|(platform, archs)| {
+        platform == system_info.os
+          && archs.split('+').any(|arch| arch == system_info.cpu)
+      })
+    })?;
+  let mut candidates = std::fs::read_dir(platform_dir.path())
+    .ok()?
+    .filter_map(|entry| entry.ok())
+    .filter_map(|entry| {
+      let name = entry.file_name().to_string_lossy().into_owned();
+      let tags = name.strip_suffix(".node")?.split('.').collect::<Vec<_>>();
+      is_supported_prebuildify_addon(&tags, &system_info.os).then(|| {
+        // prefer the addons that were built for node
+        let is_node = tags.contains(&"node");
+        (is_node, entry.path())
+      })
+    })
+    .collect::<Vec<_>>();
+  // sort for determinism, as the order of directory entries is unspecified
+  candidates.sort();
+  candidates.pop().map(|(_, path)| path)
+}
+
+fn is_supported_prebuildify_addon(tags: &[&str], os: &str) -> bool {
+  let mut is_napi = false;
+  for tag in tags {
+    match *tag {
+      "napi" => is_napi = true,
+      "electron" | "node-webkit" => return false,
+      "glibc" | "musl" => {
+        if os != "linux" || *tag != libc() {
+          return false;
+        }
+      }
+      // built against the ABI of a specific Node.js version
+      tag if tag.strip_prefix("abi").is_some_and(|abi| !abi.is_empty()) => {
+        return false;
+      }
+      _ => {}
+    }
+  }
+  is_napi
+}
+
+#[derive(Deserialize)]
+struct NodePreGypBinary {
+  module_name: String,
+  module_path: String,
+  #[serde(default)]
+  napi_versions: Vec<u32>,
+}
+
+#[derive(Deserialize)]
+struct NodePreGypPackageJson {
+  version: String,
+  binary: NodePreGypBinary,
+}
+
+/// Finds an addon at the `"binary"."module_path"` of the package.json, which
+/// node-pre-gyp expands for the platform and the N-API version.
+fn find_node_pre_gyp_addon(
+  package_path: &Path,
+  system_info: &NpmSystemInfo,
+) -> Option<PathBuf> {
+  let text = std::fs::read_to_string(package_path.join("package.json")).ok()?;
+  let package_json: NodePreGypPackageJson = serde_json::from_str(&text).ok()?;
+  let binary = &package_json.binary;
+  let mut napi_versions = binary
+    .napi_versions
+    .iter()
+    .copied()
+    .filter(|version| *version <= NAPI_VERSION)
+    .collect::<Vec<_>>();
+  // prefer the newest N-API version
+  napi_versions.sort_unstable_by(|a, b| b.cmp(a));
+  napi_versions.into_iter().find_map(|napi_version| {
+    let module_path = binary
+      .module_path
+      .replace("{module_name}", &binary.module_name)
+      .replace("{version}", &package_json.version)
+      .replace("{configuration}", "Release")
+      .replace("{platform}", &system_info.os)
+      .replace("{arch}", &system_info.cpu)
+      .replace("{libc}", libc())
+      .replace("{napi_build_version}", &napi_version.to_string())
+      .replace("{node_napi_label}", &format!("napi-v{napi_version}"));
+    if module_path.contains('{') {
+      // depends on the ABI of a specific Node.js version
+      return None;
+    }
+    let path = package_path
+      .join(module_path)
+      .join(format!("{}.node", binary.module_name));
+    path.is_file().then_some(path)
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use test_util::TempDir;
+
+  use super::*;
+
+  fn write_file(temp_dir: &TempDir, path: &str, text: &str) {
+    temp_dir.create_dir_all(Path::new(path).parent().unwrap());
+    temp_dir.write(path, text);
+  }
+
+  fn linux_x64() -> NpmSystemInfo {
+    NpmSystemInfo {
+      os: "linux".to_string(),
+      cpu: "x64".to_string(),
+    }
+  }
+
+  #[test]
+  fn test_is_addon_install_script() {
+    assert!(is_addon_install_script("node-gyp-build"));
+    assert!(is_addon_install_script(
+      "prebuild-install || node-gyp rebuild"
+    ));
+    assert!(is_addon_install_script(
+      "node-pre-gyp install --fallback-to-build"
+    ));
+    assert!(!is_addon_install_script("node-gyp-build && node setup.js"));
+    assert!(!is_addon_install_script(""));
+  }
+
+  #[test]
+  fn test_find_prebuildify_addon() {
+    let temp_dir = TempDir::new();
+    let package_path = temp_dir.path().as_path();
+    for name in [
+      "prebuilds/linux-x64/node.abi108.node",
+      "prebuilds/linux-x64/electron.napi.node",
+      "prebuilds/linux-x64/node.napi.node",
+      "prebuilds/darwin-x64+arm64/node.napi.node",
+    ] {
+      write_file(&temp_dir, name, "");
+    }
+    assert_eq!(
+      find_prebuilt_addon(package_path, &linux_x64()),
+      Some(package_path.join("prebuilds/linux-x64/node.napi.node"))
+    );
+    assert_eq!(
+      find_prebuilt_addon(
+        package_path,
+        &NpmSystemInfo {
+          os: "darwin".to_string(),
+          cpu: "arm64".to_string(),
+        }
+      ),
+      Some(package_path.join("prebuilds/darwin-x64+arm64/node.napi.node"))
+    );
+    assert_eq!(
+      find_prebuilt_addon(
+        package_path,
+        &NpmSystemInfo {
+          os: "win32".to_string(),
+          cpu: "x64".to_string(),
+        }
+      ),
+      None
+    );
+  }
+
+  #[test]
+  fn test_find_node_pre_gyp_addon() {
+    let temp_dir = TempDir::new();
+    let package_path = temp_dir.path().as_path();
+    write_file(
+      &temp_dir,
+      "package.json",
+      r#"{
+        "name": "addon",
+        "version": "1.2.3",
+        "binary": {
+          "module_name": "addon",
+          "module_path": "./lib/binding/{node_napi_label}-{platform}-{arch}",
+          "napi_versions": [3, 6, 9]
+        }
+      }"#,
+    );
+    assert_eq!(find_prebuilt_addon(package_path, &linux_x64()), None);
+    write_file(&temp_dir, "lib/binding/napi-v3-linux-x64/addon.node", "");
+    write_file(&temp_dir, "lib/binding/napi-v6-linux-x64/addon.node", "");
+    write_file(&temp_dir, "lib/binding/napi-v9-linux-x64/addon.node", "");
+    assert_eq!(
+      find_prebuilt_addon(package_path, &linux_x64()),
+      Some(package_path.join("./lib/binding/napi-v6-linux-x64/addon.node"))
+    );
+  }
+}
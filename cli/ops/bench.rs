@@ -121,6 +121,7 @@ fn op_register_bench(
   ignore: bool,
   only: bool,
   warmup: bool,
+  #[serde] tags: Vec<String>,
   #[buffer] ret_buf: &mut [u8],
 ) -> Result<(), AnyError> {
   if ret_buf.len() != 4 {
@@ -140,6 +141,7 @@ fn op_register_bench(
     ignore,
     only,
     warmup,
+    tags,
   };
   state
     .borrow_mut::<BenchContainer>()
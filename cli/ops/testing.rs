@@ -114,6 +114,7 @@ fn op_register_test(
   only: bool,
   sanitize_ops: bool,
   sanitize_resources: bool,
+  #[serde] tags: Vec<String>,
   #[string] file_name: String,
   #[smi] line_number: u32,
   #[smi] column_number: u32,
@@ -134,6 +135,7 @@ fn op_register_test(
     only,
     sanitize_ops,
     sanitize_resources,
+    tags,
     origin: origin.clone(),
     location: TestLocation {
       file_name,
@@ -47,6 +47,7 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use crate::args::deno_json::TsConfigPathsConfig;
 use crate::args::JsxImportSourceConfig;
 use crate::args::DENO_DISABLE_PEDANTIC_NODE_WARNINGS;
 use crate::node::CliNodeCodeTranslator;
@@ -428,6 +429,7 @@ pub struct CliGraphResolver {
   node_resolver: Option<Arc<CliNodeResolver>>,
   npm_resolver: Option<Arc<dyn CliNpmResolver>>,
   sloppy_imports_resolver: Option<Arc<SloppyImportsResolver>>,
+  tsconfig_paths_resolver: Option<Arc<TsConfigPathsResolver>>,
   workspace_resolver: Arc<WorkspaceResolver>,
   maybe_default_jsx_import_source: Option<String>,
   maybe_default_jsx_import_source_types: Option<String>,
@@ -442,6 +444,7 @@ pub struct CliGraphResolverOptions<'a> {
   pub node_resolver: Option<Arc<CliNodeResolver>>,
   pub npm_resolver: Option<Arc<dyn CliNpmResolver>>,
   pub sloppy_imports_resolver: Option<Arc<SloppyImportsResolver>>,
+  pub tsconfig_paths_resolver: Option<Arc<TsConfigPathsResolver>>,
   pub workspace_resolver: Arc<WorkspaceResolver>,
   pub bare_node_builtins_enabled: bool,
   pub maybe_jsx_import_source_config: Option<JsxImportSourceConfig>,
@@ -454,6 +457,7 @@ impl CliGraphResolver {
       node_resolver: options.node_resolver,
       npm_resolver: options.npm_resolver,
       sloppy_imports_resolver: options.sloppy_imports_resolver,
+      tsconfig_paths_resolver: options.tsconfig_paths_resolver,
       workspace_resolver: options.workspace_resolver,
       maybe_default_jsx_import_source: options
         .maybe_jsx_import_source_config
@@ -639,7 +643,16 @@ impl Resolver for CliGraphResolver {
             })
         }
       },
-      Err(err) => Err(err),
+      // fall back to the "paths" and "baseUrl" compiler options for the
+      // bare specifiers that aren't mapped
+      Err(err) => match self
+        .tsconfig_paths_resolver
+        .as_ref()
+        .and_then(|r| r.resolve(raw_specifier, mode))
+      {
+        Some(specifier) => Ok(specifier),
+        None => Err(err),
+      },
     };
 
     // When the user is vendoring, don't allow them to import directly from the vendor/ directory
@@ -1219,8 +1232,110 @@ impl SloppyImportsResolver {
   }
 }
 
+/// Resolves the bare specifiers with the `"paths"` and `"baseUrl"` compiler
+/// options, like tsc and ts-node do. The substitutions are probed like sloppy
+/// imports, so they may leave out the extension or point to a directory.
+#[derive(Debug)]
+pub struct TsConfigPathsResolver {
+  config: TsConfigPathsConfig,
+  probe_resolver: SloppyImportsResolver,
+}
+
+impl TsConfigPathsResolver {
+  pub fn new(config: TsConfigPathsConfig, fs: Arc<dyn FileSystem>) -> Self {
+    Self {
+      config,
+      probe_resolver: SloppyImportsResolver::new(fs),
+    }
+  }
+
+  pub fn new_without_stat_cache(
+    config: TsConfigPathsConfig,
+    fs: Arc<dyn FileSystem>,
+  ) -> Self {
+    Self {
+      config,
+      probe_resolver: SloppyImportsResolver::new_without_stat_cache(fs),
+    }
+  }
+
+  pub fn resolve(
+    &self,
+    raw_specifier: &str,
+    mode: ResolutionMode,
+  ) -> Option<ModuleSpecifier> {
+    if raw_specifier.starts_with("./")
+      || raw_specifier.starts_with("../")
+      || raw_specifier.starts_with('/')
+      || ModuleSpecifier::parse(raw_specifier).is_ok()
+    {
+      return None;
+    }
+
+    if let Some((substitutions, captured)) = self.match_paths(raw_specifier) {
+      let resolved = substitutions.iter().find_map(|substitution| {
+        let path = substitution.replacen('*', captured, 1);
+        self.probe(&self.config.paths_dir.join(path), mode)
+      });
+      if resolved.is_some() {
+        return resolved;
+      }
+    }
+
+    let base_url = self.config.base_url.as_ref()?;
+    self.probe(&base_url.join(raw_specifier), mode)
+  }
+
+  /// Finds the substitutions of the pattern that matches the specifier, along
+  /// with the text matched by the wildcard. Exact patterns take precedence
+  /// over the ones with a wildcard, of which the longest prefix wins.
+  fn match_paths<'a>(
+    &'a self,
+    raw_specifier: &'a str,
+  ) -> Option<(&'a [String], &'a str)> {
+    if let Some(substitutions) = self.config.paths.get(raw_specifier) {
+      return Some((substitutions, ""));
+    }
+    let mut best_match: Option<(usize, &[String], &str)> = None;
+    for (pattern, substitutions) in &self.config.paths {
+      let Some((prefix, suffix)) = pattern.split_once('*') else {
+        continue;
+      };
+      if raw_specifier.len() < prefix.len() + suffix.len()
+        || !raw_specifier.starts_with(prefix)
+        || !raw_specifier.ends_with(suffix)
+      {
+        continue;
+      }
+      if best_match.is_some_and(|(len, _, _)| len >= prefix.len()) {
+        continue;
+      }
+      let captured =
+        &raw_specifier[prefix.len()..raw_specifier.len() - suffix.len()];
+      best_match = Some((prefix.len(), substitutions, captured));
+    }
+    best_match.map(|(_, substitutions, captured)| (substitutions, captured))
+  }
+
+  fn probe(
+    &self,
+    path: &Path,
+    mode: ResolutionMode,
+  ) -> Option<ModuleSpecifier> {
+    let path = deno_core::normalize_path(path);
+    let specifier = ModuleSpecifier::from_file_path(&path).ok()?;
+    match self.probe_resolver.resolve(&specifier, mode) {
+      Some(resolution) => Some(resolution.into_specifier()),
+      None => (self.probe_resolver.stat_sync(&path)
+        == Some(SloppyImportsFsEntry::File))
+      .then_some(specifier),
+    }
+  }
+}
+
 #[cfg(test)]
 mod test {
+  use indexmap::IndexMap;
   use test_util::TestContext;
 
   use super::*;
@@ -1352,4 +1467,76 @@ mod test {
       "Maybe change the extension to '.mts'"
     );
   }
+
+  #[test]
+  fn test_tsconfig_paths_resolver() {
+    let context = TestContext::default();
+    let temp_dir = context.temp_dir().path();
+    temp_dir.join("src/utils").create_dir_all();
+    temp_dir.join("src/utils/math.ts").write("");
+    temp_dir.join("src/lib").create_dir_all();
+    temp_dir.join("src/lib/index.ts").write("");
+    temp_dir.join("src/config.ts").write("");
+    temp_dir.join("vendor/pkg").create_dir_all();
+    temp_dir.join("vendor/pkg/mod.js").write("");
+    temp_dir.join("vendor/pkg/mod.d.ts").write("");
+
+    let resolver = TsConfigPathsResolver::new(
+      TsConfigPathsConfig {
+        base_url: Some(temp_dir.join("src").to_path_buf()),
+        paths_dir: temp_dir.join("src").to_path_buf(),
+        paths: IndexMap::from([
+          ("@utils/*".to_string(), vec!["./utils/*".to_string()]),
+          ("@lib".to_string(), vec!["./lib".to_string()]),
+          (
+            "@lib/*".to_string(),
+            vec!["./missing/*".to_string(), "./lib/*".to_string()],
+          ),
+          ("pkg/*".to_string(), vec!["../vendor/pkg/*".to_string()]),
+          ("pkg/mod/*".to_string(), vec!["../vendor/pkg/*".to_string()]),
+        ]),
+      },
+      Arc::new(deno_fs::RealFs),
+    );
+    let resolve =
+      |specifier: &str| resolver.resolve(specifier, ResolutionMode::Execution);
+
+    // wildcard with a missing extension
+    assert_eq!(
+      resolve("@utils/math"),
+      Some(temp_dir.join("src/utils/math.ts").url_file())
+    );
+    assert_eq!(
+      resolve("@utils/math.ts"),
+      Some(temp_dir.join("src/utils/math.ts").url_file())
+    );
+    // exact pattern to a directory
+    assert_eq!(
+      resolve("@lib"),
+      Some(temp_dir.join("src/lib/index.ts").url_file())
+    );
+    // substitutions are tried in order
+    assert_eq!(
+      resolve("@lib/index"),
+      Some(temp_dir.join("src/lib/index.ts").url_file())
+    );
+    // the longest prefix wins
+    assert_eq!(
+      resolve("pkg/mod/mod"),
+      Some(temp_dir.join("vendor/pkg/mod.js").url_file())
+    );
+    // the declaration file is preferred for types
+    assert_eq!(
+      resolver.resolve("pkg/mod.js", ResolutionMode::Types),
+      Some(temp_dir.join("vendor/pkg/mod.d.ts").url_file())
+    );
+    // falls back to the base url
+    assert_eq!(
+      resolve("config"),
+      Some(temp_dir.join("src/config.ts").url_file())
+    );
+    assert_eq!(resolve("@utils/missing"), None);
+    assert_eq!(resolve("./config"), None);
+    assert_eq!(resolve("npm:@utils/math"), None);
+  }
 }
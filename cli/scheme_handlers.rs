@@ -0,0 +1,247 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Handlers of the modules of custom URL schemes, like `s3:`, `git+https:`
+//! or `corp:`, which are registered by embedders. The `"schemes"` property of
+//! the config file can only redirect a scheme to URLs, since fetching modules
+//! must not run anything that the permissions don't allow.
+//!
+//! The sources that the handlers return are cached in the DENO_DIR and their
+//! checksums are stored in the lockfile, like the ones of https modules.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use deno_config::deno_json::ConfigFile;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::ModuleSpecifier;
+use deno_graph::source::ChecksumIntegrityError;
+
+use crate::args::deno_json::read_schemes_config;
+use crate::args::deno_json::SchemeHandlerConfig;
+use crate::args::CliLockfile;
+use crate::cache::CACHE_PERM;
+use crate::util::checksum;
+use crate::util::fs::atomic_write_file_with_retries;
+
+/// What a scheme handler fetched for a specifier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemeHandlerResponse {
+  /// The source of the module, whose media type is inferred from the
+  /// extension of the specifier.
+  Module(Vec<u8>),
+  /// Another specifier that the module is loaded from, like an https one.
+  Redirect(ModuleSpecifier),
+}
+
+/// Fetches the modules of a custom URL scheme.
+#[async_trait::async_trait]
+pub trait SchemeHandler: std::fmt::Debug + Send + Sync {
+  async fn fetch(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Result<SchemeHandlerResponse, AnyError>;
+
+  /// Whether the handler only redirects the specifiers without fetching
+  /// anything, so it's also used with `--cached-only` and `--no-remote`.
+  fn is_redirect_only(&self) -> bool {
+    false
+  }
+}
+
+/// Expands the `{specifier}`, `{host}` and `{path}` placeholders of a
+/// template, where `{path}` doesn't have a leading slash.
+fn expand_template(template: &str, specifier: &ModuleSpecifier) -> String {
+  template
+    .replace("{specifier}", specifier.as_str())
+    .replace("{host}", specifier.host_str().unwrap_or_default())
+    .replace("{path}", specifier.path().trim_start_matches('/'))
+}
+
+/// Redirects the specifiers to the URL that a template expands to, like
+/// `https://modules.corp.example/{host}/{path}`.
+#[derive(Debug)]
+pub struct UrlTemplateSchemeHandler {
+  template: String,
+}
+
+impl UrlTemplateSchemeHandler {
+  pub fn new(template: String) -> Self {
+    Self { template }
+  }
+}
+
+#[async_trait::async_trait]
+impl SchemeHandler for UrlTemplateSchemeHandler {
+  async fn fetch(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Result<SchemeHandlerResponse, AnyError> {
+    let url = expand_template(&self.template, specifier);
+    let url = ModuleSpecifier::parse(&url).with_context(|| {
+      format!("Invalid URL \"{url}\" for module \"{specifier}\".")
+    })?;
+    Ok(SchemeHandlerResponse::Redirect(url))
+  }
+
+  fn is_redirect_only(&self) -> bool {
+    true
+  }
+}
+
+/// The registered scheme handlers, along with the cache of the sources that
+/// they fetched.
+#[derive(Debug)]
+pub struct SchemeHandlers {
+  handlers: HashMap<String, Arc<dyn SchemeHandler>>,
+  cache_dir: PathBuf,
+  maybe_lockfile: Option<Arc<CliLockfile>>,
+}
+
+impl SchemeHandlers {
+  pub fn new(
+    cache_dir: PathBuf,
+    maybe_lockfile: Option<Arc<CliLockfile>>,
+  ) -> Self {
+    Self {
+      handlers: Default::default(),
+      cache_dir,
+      maybe_lockfile,
+    }
+  }
+
+  /// Registers the handler of the scheme, which is lowercase and doesn't end
+  /// with a colon, replacing the previous handler of the scheme.
+  pub fn register(
+    &mut self,
+    scheme: impl Into<String>,
+    handler: Arc<dyn SchemeHandler>,
+  ) {
+    self.handlers.insert(scheme.into(), handler);
+  }
+
+  /// Registers the handlers of the `"schemes"` property of the config file.
+  pub fn register_from_config(
+    &mut self,
+    config_file: &ConfigFile,
+  ) -> Result<(), AnyError> {
+    for (scheme, SchemeHandlerConfig { url }) in
+      read_schemes_config(config_file)?
+    {
+      self.register(scheme, Arc::new(UrlTemplateSchemeHandler::new(url)));
+    }
+    Ok(())
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.handlers.is_empty()
+  }
+
+  pub fn get(&self, scheme: &str) -> Option<&Arc<dyn SchemeHandler>> {
+    self.handlers.get(scheme)
+  }
+
+  fn cache_path(&self, specifier: &ModuleSpecifier) -> PathBuf {
+    self
+      .cache_dir
+      .join(specifier.scheme())
+      .join(checksum::gen(&[specifier.as_str()]))
+  }
+
+  /// Gets the cached source of the module.
+  pub fn get_cached(&self, specifier: &ModuleSpecifier) -> Option<Vec<u8>> {
+    std::fs::read(self.cache_path(specifier)).ok()
+  }
+
+  pub fn set_cached(
+    &self,
+    specifier: &ModuleSpecifier,
+    source: &[u8],
+  ) -> Result<(), AnyError> {
+    let path = self.cache_path(specifier);
+    atomic_write_file_with_retries(&path, source, CACHE_PERM)
+      .with_context(|| format!("Failed caching {}", path.display()))
+  }
+
+  /// Verifies the source of the module against the checksum in the lockfile,
+  /// inserting the checksum when the lockfile doesn't have it yet unless the
+  /// lockfile is frozen.
+  pub fn check_lockfile_checksum(
+    &self,
+    specifier: &ModuleSpecifier,
+    source: &[u8],
+  ) -> Result<(), AnyError> {
+    let Some(cli_lockfile) = &self.maybe_lockfile else {
+      return Ok(());
+    };
+    let actual = checksum::gen(&[source]);
+    let mut lockfile = cli_lockfile.lock();
+    match lockfile.remote().get(specifier.as_str()) {
+      Some(expected) if *expected != actual => Err(
+        ChecksumIntegrityError {
+          actual,
+          expected: expected.clone(),
+        }
+        .into(),
+      ),
+      Some(_) => Ok(()),
+      None if cli_lockfile.frozen => Err(CliLockfile::not_locked_error(
+        &format!("The module \"{specifier}\""),
+      )),
+      None => {
+        lockfile.insert_remote(specifier.to_string(), actual);
+        Ok(())
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use test_util::TempDir;
+
+  use super::*;
+
+  #[test]
+  fn test_expand_template() {
+    let specifier =
+      ModuleSpecifier::parse("s3://my-bucket/lib/mod.ts").unwrap();
+    assert_eq!(
+      expand_template("https://modules.example/{host}/{path}", &specifier),
+      "https://modules.example/my-bucket/lib/mod.ts"
+    );
+    assert_eq!(
+      expand_template("{specifier}", &specifier),
+      "s3://my-bucket/lib/mod.ts"
+    );
+  }
+
+  #[tokio::test]
+  async fn test_url_template_scheme_handler() {
+    let handler = UrlTemplateSchemeHandler::new(
+      "https://modules.example/{host}/{path}".to_string(),
+    );
+    let specifier = ModuleSpecifier::parse("corp://std/fs/mod.ts").unwrap();
+    assert_eq!(
+      handler.fetch(&specifier).await.unwrap(),
+      SchemeHandlerResponse::Redirect(
+        ModuleSpecifier::parse("https://modules.example/std/fs/mod.ts")
+          .unwrap()
+      )
+    );
+  }
+
+  #[test]
+  fn test_scheme_handlers_cache() {
+    let temp_dir = TempDir::new();
+    let handlers = SchemeHandlers::new(temp_dir.path().to_path_buf(), None);
+    let specifier = ModuleSpecifier::parse("s3://bucket/mod.ts").unwrap();
+    assert_eq!(handlers.get_cached(&specifier), None);
+    handlers.set_cached(&specifier, b"export {};").unwrap();
+    assert_eq!(
+      handlers.get_cached(&specifier),
+      Some(b"export {};".to_vec())
+    );
+  }
+}
@@ -16,6 +16,7 @@ use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
+use std::sync::OnceLock;
 
 use deno_ast::ModuleSpecifier;
 use deno_config::workspace::PackageJsonDepResolution;
@@ -56,6 +57,7 @@ use crate::npm::InnerCliNpmResolverRef;
 use crate::shared::ReleaseChannel;
 use crate::standalone::virtual_fs::VfsEntry;
 use crate::util::archive;
+use crate::util::fs::canonicalize_path;
 use crate::util::fs::canonicalize_path_maybe_not_exists;
 use crate::util::progress_bar::ProgressBar;
 use crate::util::progress_bar::ProgressBarStyle;
@@ -117,10 +119,20 @@ pub struct Metadata {
   pub workspace_resolver: SerializedWorkspaceResolver,
   pub entrypoint_key: String,
   pub node_modules: Option<NodeModules>,
+  /// Files and directories embedded with `--include` that live in a separate
+  /// vfs rooted at the root directory, because the npm vfs isn't rooted there.
+  /// The offsets of its files are relative to the start of the npm files.
+  pub included_files: Option<VirtualDirectory>,
+  /// Whether the eszip and the files section are compressed with zstd.
+  pub compressed: bool,
+  /// Whether the V8 code cache of the modules is stored in `DENO_DIR` on
+  /// the first run, so later runs don't need to compile them again.
+  pub code_cache_enabled: bool,
+  pub node_conditions: Vec<String>,
   pub unstable_config: UnstableConfig,
 }
 
-pub fn load_npm_vfs(root_dir_path: PathBuf) -> Result<FileBackedVfs, AnyError> {
+fn read_section() -> Result<(Trailer, &'static [u8]), AnyError> {
   let data = libsui::find_section("d3n0l4nd").unwrap();
 
   // We do the first part sync so it can complete quickly
@@ -129,7 +141,49 @@ pub fn load_npm_vfs(root_dir_path: PathBuf) -> Result<FileBackedVfs, AnyError> {
     None => panic!("Could not find trailer"),
     Some(trailer) => trailer,
   };
-  let data = &data[TRAILER_SIZE..];
+  Ok((trailer, &data[TRAILER_SIZE..]))
+}
+
+/// The zstd level used for `--compress`.
+const COMPRESSION_LEVEL: i32 = 19;
+
+/// Decompresses the files section of an executable compiled with
+/// `--compress`, which only happens once a file of a vfs is read.
+fn decompressed_files(
+  compressed: &'static [u8],
+) -> std::io::Result<&'static [u8]> {
+  static FILES: OnceLock<Result<Vec<u8>, String>> = OnceLock::new();
+  FILES
+    .get_or_init(|| {
+      zstd::stream::decode_all(compressed).map_err(|err| err.to_string())
+    })
+    .as_deref()
+    .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err.clone()))
+}
+
+fn files_vfs(
+  trailer: &Trailer,
+  data: &'static [u8],
+  fs_root: VfsRoot,
+  compressed: bool,
+) -> FileBackedVfs {
+  let files_data = &data[trailer.npm_files_pos as usize..];
+  if compressed {
+    FileBackedVfs::new_lazy(
+      Box::new(move || decompressed_files(files_data)),
+      fs_root,
+    )
+  } else {
+    // only keep the data of the files rather than the whole section
+    FileBackedVfs::new(files_data.to_vec(), fs_root)
+  }
+}
+
+pub fn load_npm_vfs(
+  root_dir_path: PathBuf,
+  compressed: bool,
+) -> Result<FileBackedVfs, AnyError> {
+  let (trailer, data) = read_section()?;
 
   let vfs_data =
     &data[trailer.npm_vfs_pos as usize..trailer.npm_files_pos as usize];
@@ -145,9 +199,28 @@ pub fn load_npm_vfs(root_dir_path: PathBuf) -> Result<FileBackedVfs, AnyError> {
   let fs_root = VfsRoot {
     dir,
     root_path: root_dir_path,
-    start_file_offset: trailer.npm_files_pos,
+    start_file_offset: 0,
   };
-  Ok(FileBackedVfs::new(data.to_vec(), fs_root))
+  Ok(files_vfs(&trailer, data, fs_root, compressed))
+}
+
+pub fn load_included_files_vfs(
+  root_dir_path: PathBuf,
+  mut dir: VirtualDirectory,
+  compressed: bool,
+) -> Result<FileBackedVfs, AnyError> {
+  let (trailer, data) = read_section()?;
+  dir.name = root_dir_path
+    .file_name()
+    .unwrap()
+    .to_string_lossy()
+    .to_string();
+  let fs_root = VfsRoot {
+    dir,
+    root_path: root_dir_path,
+    start_file_offset: 0,
+  };
+  Ok(files_vfs(&trailer, data, fs_root, compressed))
 }
 
 fn write_binary_bytes(
@@ -161,7 +234,17 @@ fn write_binary_bytes(
 ) -> Result<(), AnyError> {
   let metadata = serde_json::to_string(metadata)?.as_bytes().to_vec();
   let npm_vfs = serde_json::to_string(&npm_vfs)?.as_bytes().to_vec();
-  let eszip_archive = eszip.into_bytes();
+  let mut eszip_archive = eszip.into_bytes();
+  let mut npm_files = Cow::Borrowed(npm_files.as_slice());
+  if compile_flags.compress {
+    eszip_archive =
+      zstd::stream::encode_all(eszip_archive.as_slice(), COMPRESSION_LEVEL)?;
+    let files = npm_files.concat();
+    npm_files = Cow::Owned(vec![zstd::stream::encode_all(
+      files.as_slice(),
+      COMPRESSION_LEVEL,
+    )?]);
+  }
 
   let mut writer = Vec::new();
 
@@ -183,7 +266,7 @@ fn write_binary_bytes(
   writer.write_all(&eszip_archive)?;
   writer.write_all(&metadata)?;
   writer.write_all(&npm_vfs)?;
-  for file in npm_files {
+  for file in npm_files.iter() {
     writer.write_all(file)?;
   }
 
@@ -201,8 +284,16 @@ fn write_binary_bytes(
       pe = pe.set_icon(&icon)?;
     }
 
-    pe.write_resource("d3n0l4nd", writer)?
-      .build(&mut file_writer)?;
+    let pe = pe.write_resource("d3n0l4nd", writer)?;
+    if compile_flags.windows_version_info.is_empty() {
+      pe.build(&mut file_writer)?;
+    } else {
+      let mut bin = Vec::new();
+      pe.build(&mut bin)?;
+      let bin =
+        set_windows_version_info(&bin, &compile_flags.windows_version_info)?;
+      file_writer.write_all(&bin)?;
+    }
   } else if target.contains("darwin") {
     libsui::Macho::from(original_bin)?
       .write_section("d3n0l4nd", writer)?
@@ -245,24 +336,32 @@ pub fn extract_standalone(
   let cli_args = cli_args.into_owned();
   // If we have an eszip, read it out
   Ok(Some(async move {
-    let bufreader =
-      deno_core::futures::io::BufReader::new(&data[TRAILER_SIZE..]);
+    let data = &data[TRAILER_SIZE..];
+    let mut metadata: Metadata = serde_json::from_slice(
+      &data[trailer.metadata_pos as usize..trailer.npm_vfs_pos as usize],
+    )
+    .context("Failed to read metadata from the current executable")?;
+
+    let eszip_data =
+      &data[trailer.eszip_pos as usize..trailer.metadata_pos as usize];
+    let eszip_data = if metadata.compressed {
+      Cow::Owned(
+        zstd::stream::decode_all(eszip_data)
+          .context("Failed to decompress eszip")?,
+      )
+    } else {
+      Cow::Borrowed(eszip_data)
+    };
+    let bufreader = deno_core::futures::io::BufReader::new(
+      deno_core::futures::io::Cursor::new(eszip_data),
+    );
 
     let (eszip, loader) = eszip::EszipV2::parse(bufreader)
       .await
       .context("Failed to parse eszip header")?;
 
-    let bufreader = loader.await.context("Failed to parse eszip archive")?;
-
-    let mut metadata = String::new();
-
-    bufreader
-      .take(trailer.metadata_len())
-      .read_to_string(&mut metadata)
-      .await
-      .context("Failed to read metadata from the current executable")?;
+    loader.await.context("Failed to parse eszip archive")?;
 
-    let mut metadata: Metadata = serde_json::from_str(&metadata).unwrap();
     metadata.argv.reserve(cli_args.len() - 1);
     for arg in cli_args.into_iter().skip(1) {
       metadata.argv.push(arg.into_string().unwrap());
@@ -359,12 +458,14 @@ impl<'a> DenoCompileBinaryWriter<'a> {
     }
   }
 
+  #[allow(clippy::too_many_arguments)]
   pub async fn write_bin(
     &self,
     writer: File,
     eszip: eszip::EszipV2,
     root_dir_url: EszipRelativeFileBaseUrl<'_>,
     entrypoint: &ModuleSpecifier,
+    include_files: &[PathBuf],
     compile_flags: &CompileFlags,
     cli_options: &CliOptions,
   ) -> Result<(), AnyError> {
@@ -390,12 +491,22 @@ impl<'a> DenoCompileBinaryWriter<'a> {
         )
       }
     }
+    if !compile_flags.windows_version_info.is_empty() {
+      let target = compile_flags.resolve_target();
+      if !target.contains("windows") {
+        bail!(
+          "The `--windows-version-info` flag is only available when targeting Windows (current: {})",
+          target,
+        )
+      }
+    }
     self.write_standalone_binary(
       writer,
       original_binary,
       eszip,
       root_dir_url,
       entrypoint,
+      include_files,
       cli_options,
       compile_flags,
     )
@@ -500,6 +611,7 @@ impl<'a> DenoCompileBinaryWriter<'a> {
     mut eszip: eszip::EszipV2,
     root_dir_url: EszipRelativeFileBaseUrl<'_>,
     entrypoint: &ModuleSpecifier,
+    include_files: &[PathBuf],
     cli_options: &CliOptions,
     compile_flags: &CompileFlags,
   ) -> Result<(), AnyError> {
@@ -512,19 +624,15 @@ impl<'a> DenoCompileBinaryWriter<'a> {
       None => None,
     };
     let root_path = root_dir_url.inner().to_file_path().unwrap();
-    let (npm_vfs, npm_files, node_modules) = match self.npm_resolver.as_inner()
-    {
+    let (npm_vfs_builder, node_modules) = match self.npm_resolver.as_inner() {
       InnerCliNpmResolverRef::Managed(managed) => {
         let snapshot =
           managed.serialized_valid_snapshot_for_system(&self.npm_system_info);
         if !snapshot.as_serialized().packages.is_empty() {
-          let (root_dir, files) = self
-            .build_vfs(&root_path, cli_options)?
-            .into_dir_and_files();
+          let builder = self.build_vfs(&root_path, cli_options)?;
           eszip.add_npm_snapshot(snapshot);
           (
-            Some(root_dir),
-            files,
+            Some(builder),
             Some(NodeModules::Managed {
               node_modules_dir: self.npm_resolver.root_node_modules_path().map(
                 |path| {
@@ -538,16 +646,13 @@ impl<'a> DenoCompileBinaryWriter<'a> {
             }),
           )
         } else {
-          (None, Vec::new(), None)
+          (None, None)
         }
       }
       InnerCliNpmResolverRef::Byonm(resolver) => {
-        let (root_dir, files) = self
-          .build_vfs(&root_path, cli_options)?
-          .into_dir_and_files();
+        let builder = self.build_vfs(&root_path, cli_options)?;
         (
-          Some(root_dir),
-          files,
+          Some(builder),
           Some(NodeModules::Byonm {
             root_node_modules_dir: resolver.root_node_modules_path().map(
               |node_modules_dir| {
@@ -564,13 +669,48 @@ impl<'a> DenoCompileBinaryWriter<'a> {
       }
     };
 
-    let env_vars_from_env_file = match cli_options.env_file_name() {
-      Some(env_filename) => {
-        log::info!("{} Environment variables from the file \"{}\" were embedded in the generated executable file", crate::colors::yellow("Warning"), env_filename);
-        get_file_env_vars(env_filename.to_string())?
+    // add the included files to the npm vfs when it's rooted at the root
+    // directory, otherwise to a separate one
+    let mut npm_vfs_builder = npm_vfs_builder;
+    let mut included_files_builder = None;
+    if !include_files.is_empty() {
+      let canonicalized_root_path = canonicalize_path(&root_path)?;
+      let builder = match &mut npm_vfs_builder {
+        Some(builder) if builder.root_path() == canonicalized_root_path => {
+          builder
+        }
+        _ => included_files_builder
+          .insert(VfsBuilder::new(canonicalized_root_path.clone())?),
+      };
+      for path in include_files {
+        if path.is_dir() {
+          builder.add_dir_recursive(path)?;
+        } else {
+          builder.add_file_at_path(path)?;
+        }
+      }
+    }
+    let (npm_vfs, mut npm_files) = match npm_vfs_builder {
+      Some(builder) => {
+        let (root_dir, files) = builder.into_dir_and_files();
+        (Some(root_dir), files)
       }
-      None => Default::default(),
+      None => (None, Vec::new()),
     };
+    let included_files = included_files_builder.map(|builder| {
+      let (mut root_dir, files) = builder.into_dir_and_files();
+      let npm_files_len = npm_files.iter().map(|f| f.len() as u64).sum();
+      offset_files(&mut root_dir, npm_files_len);
+      npm_files.extend(files);
+      root_dir
+    });
+
+    let mut env_vars_from_env_file = IndexMap::new();
+    for env_file in cli_options.env_files() {
+      log::info!("{} Environment variables from the file \"{}\" were embedded in the generated executable file", crate::colors::yellow("Warning"), env_file.display());
+      // variables of later files take precedence
+      env_vars_from_env_file.extend(get_file_env_vars(env_file)?);
+    }
 
     let metadata = Metadata {
       argv: compile_flags.args.clone(),
@@ -623,6 +763,10 @@ impl<'a> DenoCompileBinaryWriter<'a> {
         pkg_json_resolution: self.workspace_resolver.pkg_json_dep_resolution(),
       },
       node_modules,
+      included_files,
+      compressed: compile_flags.compress,
+      code_cache_enabled: cli_options.code_cache_enabled(),
+      node_conditions: cli_options.node_conditions().to_vec(),
       unstable_config: UnstableConfig {
         legacy_flag_enabled: false,
         bare_node_builtins: cli_options.unstable_bare_node_builtins(),
@@ -753,17 +897,76 @@ impl<'a> DenoCompileBinaryWriter<'a> {
   }
 }
 
+/// Sets the version resource of a Windows executable. The data is stored in
+/// the resource section like the rest of the embedded data, which keeps the
+/// executable signable with signtool.
+fn set_windows_version_info(
+  bin: &[u8],
+  entries: &[(String, String)],
+) -> Result<Vec<u8>, AnyError> {
+  let mut image = editpe::Image::parse(bin)?;
+  let mut resources = image.resource_directory().cloned().unwrap_or_default();
+  let mut version_info = editpe::VersionInfo::default();
+  let mut strings = IndexMap::new();
+  for (key, value) in entries {
+    if let Some(version) = parse_windows_version(value) {
+      match key.as_str() {
+        "FileVersion" => version_info.info.file_version = version,
+        "ProductVersion" => version_info.info.product_version = version,
+        _ => {}
+      }
+    }
+    strings.insert(key.clone(), value.clone());
+  }
+  // U.S. English with the Unicode code page
+  version_info.strings = vec![editpe::VersionStringTable {
+    key: "040904B0".to_string(),
+    strings,
+  }];
+  version_info.vars = vec![editpe::types::VersionU16 {
+    major: 0x0409,
+    minor: 0x04B0,
+  }];
+  resources.set_version_info(&version_info)?;
+  image.set_resource_directory(resources)?;
+  Ok(image.data().to_vec())
+}
+
+/// Parses a version like `1.2.3.4` into the format of a version resource,
+/// where missing components are zero.
+fn parse_windows_version(text: &str) -> Option<editpe::types::VersionU32> {
+  let mut parts = [0u16; 4];
+  for (index, part) in text.split('.').enumerate() {
+    *parts.get_mut(index)? = part.parse().ok()?;
+  }
+  Some(editpe::types::VersionU32 {
+    major: (parts[0] as u32) << 16 | parts[1] as u32,
+    minor: (parts[2] as u32) << 16 | parts[3] as u32,
+  })
+}
+
+/// Shifts the offsets of the files in the directory by the provided amount.
+fn offset_files(dir: &mut VirtualDirectory, offset: u64) {
+  for entry in &mut dir.entries {
+    match entry {
+      VfsEntry::Dir(dir) => offset_files(dir, offset),
+      VfsEntry::File(file) => file.offset += offset,
+      VfsEntry::Symlink(_) => {}
+    }
+  }
+}
+
 /// This function returns the environment variables specified
 /// in the passed environment file.
 fn get_file_env_vars(
-  filename: String,
+  path: &Path,
 ) -> Result<IndexMap<String, String>, dotenvy::Error> {
   let mut file_env_vars = IndexMap::new();
-  for item in dotenvy::from_filename_iter(filename)? {
+  for item in dotenvy::from_path_iter(path)? {
     let Ok((key, val)) = item else {
       continue; // this failure will be warned about on load
     };
-    file_env_vars.insert(key, val);
+    file_env_vars.entry(key).or_insert(val);
   }
   Ok(file_env_vars)
 }
@@ -25,6 +25,7 @@ use deno_core::RequestedModuleType;
 use deno_core::ResolutionKind;
 use deno_npm::npm_rc::ResolvedNpmRc;
 use deno_package_json::PackageJsonDepValue;
+use deno_runtime::code_cache;
 use deno_runtime::deno_fs;
 use deno_runtime::deno_node::create_host_defined_options;
 use deno_runtime::deno_node::NodeResolver;
@@ -42,6 +43,7 @@ use import_map::parse_from_json;
 use node_resolver::analyze::NodeCodeTranslator;
 use node_resolver::NodeResolutionMode;
 use std::borrow::Cow;
+use std::pin::Pin;
 use std::rc::Rc;
 use std::sync::Arc;
 
@@ -53,7 +55,9 @@ use crate::args::CacheSetting;
 use crate::args::NpmInstallDepsProvider;
 use crate::args::StorageKeyResolver;
 use crate::cache::Caches;
+use crate::cache::CodeCache;
 use crate::cache::DenoDirProvider;
+use crate::cache::FastInsecureHasher;
 use crate::cache::NodeAnalysisCache;
 use crate::http_util::HttpClientProvider;
 use crate::node::CliCjsCodeAnalyzer;
@@ -125,6 +129,7 @@ struct SharedModuleLoaderState {
   workspace_resolver: WorkspaceResolver,
   node_resolver: Arc<CliNodeResolver>,
   npm_module_loader: Arc<NpmModuleLoader>,
+  code_cache: Option<Arc<CodeCache>>,
 }
 
 #[derive(Clone)]
@@ -352,6 +357,7 @@ impl ModuleLoader for EmbeddedModuleLoader {
       ))));
     };
     let original_specifier = original_specifier.clone();
+    let shared = self.shared.clone();
 
     deno_core::ModuleLoadResponse::Async(
       async move {
@@ -360,26 +366,74 @@ impl ModuleLoader for EmbeddedModuleLoader {
         })?;
         let code = arc_u8_to_arc_str(code)
           .map_err(|_| type_error("Module source is not utf-8"))?;
-        Ok(deno_core::ModuleSource::new_with_redirect(
-          match module.inner.kind {
-            eszip::ModuleKind::JavaScript => ModuleType::JavaScript,
-            eszip::ModuleKind::Json => ModuleType::Json,
-            eszip::ModuleKind::Jsonc => {
-              return Err(type_error("jsonc modules not supported"))
-            }
-            eszip::ModuleKind::OpaqueData => {
-              unreachable!();
+        let module_type = match module.inner.kind {
+          eszip::ModuleKind::JavaScript => ModuleType::JavaScript,
+          eszip::ModuleKind::Json => ModuleType::Json,
+          eszip::ModuleKind::Jsonc => {
+            return Err(type_error("jsonc modules not supported"))
+          }
+          eszip::ModuleKind::OpaqueData => {
+            unreachable!();
+          }
+        };
+        let code_cache = if module_type == ModuleType::JavaScript {
+          shared.code_cache.as_ref().map(|cache| {
+            let code_hash = FastInsecureHasher::new_deno_versioned()
+              .write_hashable(&code)
+              .finish();
+            let data = cache
+              .get_sync(
+                &module.specifier,
+                code_cache::CodeCacheType::EsModule,
+                code_hash,
+              )
+              .map(Cow::from)
+              .inspect(|_| {
+                // This log line is also used by tests.
+                log::debug!(
+                  "V8 code cache hit for ES module: {}, [{code_hash:?}]",
+                  module.specifier
+                );
+              });
+            deno_core::SourceCodeCacheInfo {
+              hash: code_hash,
+              data,
             }
-          },
+          })
+        } else {
+          None
+        };
+        Ok(deno_core::ModuleSource::new_with_redirect(
+          module_type,
           ModuleSourceCode::String(code.into()),
           &original_specifier,
           &module.specifier,
-          None,
+          code_cache,
         ))
       }
       .boxed_local(),
     )
   }
+
+  fn code_cache_ready(
+    &self,
+    specifier: ModuleSpecifier,
+    source_hash: u64,
+    code_cache: &[u8],
+  ) -> Pin<Box<dyn std::future::Future<Output = ()>>> {
+    if let Some(cache) = self.shared.code_cache.as_ref() {
+      log::debug!(
+        "Updating V8 code cache for ES module: {specifier}, [{source_hash:?}]"
+      );
+      cache.set_sync(
+        &specifier,
+        code_cache::CodeCacheType::EsModule,
+        source_hash,
+        code_cache,
+      );
+    }
+    std::future::ready(()).boxed_local()
+  }
 }
 
 fn arc_u8_to_arc_str(
@@ -476,7 +530,22 @@ pub async fn run(
   );
   let npm_global_cache_dir = npm_cache_dir.get_cache_location();
   let cache_setting = CacheSetting::Only;
-  let (fs, npm_resolver, maybe_vfs_root) = match metadata.node_modules {
+  let maybe_included_files_vfs = metadata
+    .included_files
+    .map(|dir| {
+      binary::load_included_files_vfs(
+        root_path.clone(),
+        dir,
+        metadata.compressed,
+      )
+    })
+    .transpose()
+    .context("Failed to load included files vfs.")?;
+  let mut vfs_roots = Vec::new();
+  if maybe_included_files_vfs.is_some() {
+    vfs_roots.push(root_path.clone());
+  }
+  let (fs, npm_resolver) = match metadata.node_modules {
     Some(binary::NodeModules::Managed { node_modules_dir }) => {
       // this will always have a snapshot
       let snapshot = eszip.take_npm_snapshot().unwrap();
@@ -485,12 +554,16 @@ pub async fn run(
       } else {
         npm_cache_dir.root_dir().to_owned()
       };
-      let vfs = load_npm_vfs(vfs_root_dir_path.clone())
+      let vfs = load_npm_vfs(vfs_root_dir_path.clone(), metadata.compressed)
         .context("Failed to load npm vfs.")?;
       let maybe_node_modules_path = node_modules_dir
         .map(|node_modules_dir| vfs_root_dir_path.join(node_modules_dir));
-      let fs = Arc::new(DenoCompileFileSystem::new(vfs))
-        as Arc<dyn deno_fs::FileSystem>;
+      let fs = Arc::new(DenoCompileFileSystem::new(
+        [Some(vfs), maybe_included_files_vfs]
+          .into_iter()
+          .flatten()
+          .collect(),
+      )) as Arc<dyn deno_fs::FileSystem>;
       let npm_resolver =
         create_cli_npm_resolver(CliNpmResolverCreateOptions::Managed(
           CliNpmResolverManagedCreateOptions {
@@ -518,22 +591,28 @@ pub async fn run(
               scopes: Default::default(),
               registry_configs: Default::default(),
             }),
+            npmrc_fetch_options: Default::default(),
             lifecycle_scripts: Default::default(),
           },
         ))
         .await?;
-      (fs, npm_resolver, Some(vfs_root_dir_path))
+      vfs_roots.push(vfs_root_dir_path);
+      (fs, npm_resolver)
     }
     Some(binary::NodeModules::Byonm {
       root_node_modules_dir,
     }) => {
       let vfs_root_dir_path = root_path.clone();
-      let vfs = load_npm_vfs(vfs_root_dir_path.clone())
+      let vfs = load_npm_vfs(vfs_root_dir_path.clone(), metadata.compressed)
         .context("Failed to load vfs.")?;
       let root_node_modules_dir =
         root_node_modules_dir.map(|p| vfs.root().join(p));
-      let fs = Arc::new(DenoCompileFileSystem::new(vfs))
-        as Arc<dyn deno_fs::FileSystem>;
+      let fs = Arc::new(DenoCompileFileSystem::new(
+        [Some(vfs), maybe_included_files_vfs]
+          .into_iter()
+          .flatten()
+          .collect(),
+      )) as Arc<dyn deno_fs::FileSystem>;
       let npm_resolver = create_cli_npm_resolver(
         CliNpmResolverCreateOptions::Byonm(CliNpmResolverByonmCreateOptions {
           fs: fs.clone(),
@@ -541,10 +620,15 @@ pub async fn run(
         }),
       )
       .await?;
-      (fs, npm_resolver, Some(vfs_root_dir_path))
+      vfs_roots.push(vfs_root_dir_path);
+      (fs, npm_resolver)
     }
     None => {
-      let fs = Arc::new(deno_fs::RealFs) as Arc<dyn deno_fs::FileSystem>;
+      let fs = match maybe_included_files_vfs {
+        Some(vfs) => Arc::new(DenoCompileFileSystem::new(vec![vfs]))
+          as Arc<dyn deno_fs::FileSystem>,
+        None => Arc::new(deno_fs::RealFs) as Arc<dyn deno_fs::FileSystem>,
+      };
       let npm_resolver =
         create_cli_npm_resolver(CliNpmResolverCreateOptions::Managed(
           CliNpmResolverManagedCreateOptions {
@@ -564,11 +648,12 @@ pub async fn run(
             // Packages from different registries are already inlined in the ESZip,
             // so no need to create actual `.npmrc` configuration.
             npmrc: create_default_npmrc(),
+            npmrc_fetch_options: Default::default(),
             lifecycle_scripts: Default::default(),
           },
         ))
         .await?;
-      (fs, npm_resolver, None)
+      (fs, npm_resolver)
     }
   };
 
@@ -576,9 +661,13 @@ pub async fn run(
   let node_resolver = Arc::new(NodeResolver::new(
     deno_runtime::deno_node::DenoFsNodeResolverEnv::new(fs.clone()),
     npm_resolver.clone().into_npm_resolver(),
+    metadata.node_conditions,
   ));
   let cjs_resolutions = Arc::new(CjsResolutionStore::default());
   let cache_db = Caches::new(deno_dir_provider.clone());
+  let code_cache = metadata
+    .code_cache_enabled
+    .then(|| Arc::new(CodeCache::new(cache_db.code_cache_db())));
   let node_analysis_cache = NodeAnalysisCache::new(cache_db.node_analysis_db());
   let cjs_esm_code_analyzer =
     CliCjsCodeAnalyzer::new(node_analysis_cache, fs.clone());
@@ -657,13 +746,14 @@ pub async fn run(
         fs.clone(),
         cli_node_resolver,
       )),
+      code_cache: code_cache.clone(),
     }),
   };
 
   let permissions = {
     let mut permissions = metadata.permissions.to_options();
-    // if running with an npm vfs, grant read access to it
-    if let Some(vfs_root) = maybe_vfs_root {
+    // if running with a vfs, grant read access to it
+    for vfs_root in vfs_roots {
       match &mut permissions.allow_read {
         Some(vec) if vec.is_empty() => {
           // do nothing, already granted
@@ -698,8 +788,7 @@ pub async fn run(
     Arc::new(RuntimePermissionDescriptorParser::new(fs.clone()));
   let worker_factory = CliMainWorkerFactory::new(
     Arc::new(BlobStore::default()),
-    // Code cache is not supported for standalone binary yet.
-    None,
+    code_cache.map(|cache| cache as Arc<dyn code_cache::CodeCache>),
     feature_checker,
     fs,
     None,
@@ -737,6 +826,7 @@ pub async fn run(
         .unsafely_ignore_certificate_errors,
       create_hmr_runner: None,
       create_coverage_collector: None,
+      profiler: Default::default(),
       node_ipc: None,
       serve_port: None,
       serve_host: None,
@@ -67,6 +67,10 @@ impl VfsBuilder {
     })
   }
 
+  pub fn root_path(&self) -> &Path {
+    &self.root_path
+  }
+
   pub fn with_root_dir<R>(
     &mut self,
     with_root: impl FnOnce(&mut VirtualDirectory) -> R,
@@ -749,16 +753,43 @@ impl deno_io::fs::File for FileBackedVfsFile {
   }
 }
 
+/// Loads the data of the files in a vfs on first access.
+pub type LazyVfsFileData =
+  Box<dyn Fn() -> std::io::Result<&'static [u8]> + Send + Sync>;
+
+enum VfsFileData {
+  Loaded(Mutex<Vec<u8>>),
+  Lazy(LazyVfsFileData),
+}
+
+impl std::fmt::Debug for VfsFileData {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Loaded(_) => f.write_str("Loaded"),
+      Self::Lazy(_) => f.write_str("Lazy"),
+    }
+  }
+}
+
 #[derive(Debug)]
 pub struct FileBackedVfs {
-  file: Mutex<Vec<u8>>,
+  file: VfsFileData,
   fs_root: VfsRoot,
 }
 
 impl FileBackedVfs {
   pub fn new(file: Vec<u8>, fs_root: VfsRoot) -> Self {
     Self {
-      file: Mutex::new(file),
+      file: VfsFileData::Loaded(Mutex::new(file)),
+      fs_root,
+    }
+  }
+
+  /// Creates a vfs whose file data is only loaded once a file is read, which
+  /// is used when the data needs to be decompressed first.
+  pub fn new_lazy(load_file: LazyVfsFileData, fs_root: VfsRoot) -> Self {
+    Self {
+      file: VfsFileData::Lazy(load_file),
       fs_root,
     }
   }
@@ -839,7 +870,14 @@ impl FileBackedVfs {
     pos: u64,
     buf: &mut [u8],
   ) -> std::io::Result<usize> {
-    let data = self.file.lock();
+    let loaded;
+    let data = match &self.file {
+      VfsFileData::Loaded(data) => {
+        loaded = data.lock();
+        loaded.as_slice()
+      }
+      VfsFileData::Lazy(load_file) => load_file()?,
+    };
     let start = self.fs_root.start_file_offset + file.offset + pos;
     let end = start + buf.len() as u64;
     if end > data.len() as u64 {
@@ -16,9 +16,13 @@ use deno_task_shell::ExecutableCommand;
 use deno_task_shell::ExecuteResult;
 use deno_task_shell::ShellCommand;
 use deno_task_shell::ShellCommandContext;
+use deno_task_shell::ShellPipeReader;
+use deno_task_shell::ShellPipeWriter;
+use deno_task_shell::ShellState;
 use lazy_regex::Lazy;
 use regex::Regex;
 use tokio::task::LocalSet;
+use tokio_util::sync::CancellationToken;
 
 use crate::npm::CliNpmResolver;
 use crate::npm::InnerCliNpmResolverRef;
@@ -45,6 +49,15 @@ pub struct RunTaskOptions<'a> {
   pub argv: &'a [String],
   pub custom_commands: HashMap<String, Rc<dyn ShellCommand>>,
   pub root_node_modules_dir: Option<&'a Path>,
+  /// Where the output of the task is written to instead of stdout and stderr.
+  pub stdio: Option<TaskStdio>,
+  /// Kills the commands of the task once cancelled.
+  pub cancellation_token: Option<CancellationToken>,
+}
+
+pub struct TaskStdio {
+  pub stdout: ShellPipeWriter,
+  pub stderr: ShellPipeWriter,
 }
 
 pub type TaskCustomCommands = HashMap<String, Rc<dyn ShellCommand>>;
@@ -55,13 +68,34 @@ pub async fn run_task(opts: RunTaskOptions<'_>) -> Result<i32, AnyError> {
     .with_context(|| format!("Error parsing script '{}'.", opts.task_name))?;
   let env_vars =
     prepare_env_vars(opts.env_vars, opts.init_cwd, opts.root_node_modules_dir);
-  let local = LocalSet::new();
-  let future = deno_task_shell::execute(
+  let state = ShellState::new(env_vars, opts.cwd, opts.custom_commands);
+  let shell_token = state.token().clone();
+  let (stdout, stderr) = match opts.stdio {
+    Some(stdio) => (stdio.stdout, stdio.stderr),
+    None => (ShellPipeWriter::stdout(), ShellPipeWriter::stderr()),
+  };
+  let future = deno_task_shell::execute_with_pipes(
     seq_list,
-    env_vars,
-    opts.cwd,
-    opts.custom_commands,
+    state,
+    ShellPipeReader::stdin(),
+    stdout,
+    stderr,
   );
+  let cancellation_token = opts.cancellation_token;
+  let future = async move {
+    let Some(cancellation_token) = cancellation_token else {
+      return future.await;
+    };
+    tokio::pin!(future);
+    tokio::select! {
+      exit_code = &mut future => exit_code,
+      _ = cancellation_token.cancelled() => {
+        shell_token.cancel();
+        future.await
+      }
+    }
+  };
+  let local = LocalSet::new();
   Ok(local.run_until(future).await)
 }
 
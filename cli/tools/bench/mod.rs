@@ -104,6 +104,8 @@ pub struct BenchDescription {
   pub ignore: bool,
   pub only: bool,
   pub warmup: bool,
+  #[serde(default)]
+  pub tags: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -213,7 +215,7 @@ async fn bench_specifier_inner(
   let benchmarks = if used_only { only } else { no_only };
   let mut benchmarks = benchmarks
     .into_iter()
-    .filter(|(d, _)| d.warmup || filter.includes(&d.name) && !d.ignore)
+    .filter(|(d, _)| d.warmup || filter.includes(&d.name, &d.tags) && !d.ignore)
     .collect::<Vec<_>>();
   let mut groups = IndexSet::<Option<String>>::new();
   // make sure ungrouped benchmarks are placed above grouped
@@ -412,6 +414,11 @@ pub async fn run_benchmarks(
   let cli_options = factory.cli_options()?;
   let workspace_bench_options =
     cli_options.resolve_workspace_bench_options(&bench_flags);
+  let bench_filter = TestFilter::from_flags(
+    &workspace_bench_options.filter,
+    workspace_bench_options.tags.as_deref(),
+    workspace_bench_options.skip_tags.as_deref(),
+  )?;
   // Various bench files should not share the same permissions in terms of
   // `PermissionsContainer` - otherwise granting/revoking permissions in one
   // file would have impact on other files, which is undesirable.
@@ -459,7 +466,7 @@ pub async fn run_benchmarks(
     &permission_desc_parser,
     specifiers,
     BenchSpecifierOptions {
-      filter: TestFilter::from_flag(&workspace_bench_options.filter),
+      filter: bench_filter,
       json: workspace_bench_options.json,
       log_level,
     },
@@ -494,6 +501,11 @@ pub async fn run_benchmarks_with_watch(
         let cli_options = factory.cli_options()?;
         let workspace_bench_options =
           cli_options.resolve_workspace_bench_options(&bench_flags);
+        let bench_filter = TestFilter::from_flags(
+          &workspace_bench_options.filter,
+          workspace_bench_options.tags.as_deref(),
+          workspace_bench_options.skip_tags.as_deref(),
+        )?;
 
         let _ = watcher_communicator.watch_paths(cli_options.watch_paths());
 
@@ -585,7 +597,7 @@ pub async fn run_benchmarks_with_watch(
           &permission_desc_parser,
           specifiers,
           BenchSpecifierOptions {
-            filter: TestFilter::from_flag(&workspace_bench_options.filter),
+            filter: bench_filter,
             json: workspace_bench_options.json,
             log_level,
           },
@@ -0,0 +1,1097 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! `deno bundle`, which merges modules into JavaScript files with
+//! `swc_bundler`, removing the code that isn't used.
+//!
+//! Each entrypoint and each module that is dynamically imported gets its own
+//! bundle, and the dynamic imports are rewritten to point to those bundles.
+//! Modules that are statically imported by several bundles are hoisted into a
+//! common bundle, which exports them under generated names, so that they're
+//! only evaluated once. npm and node specifiers, and specifiers matching
+//! `--external`, are left as imports.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::Arc;
+
+use deno_ast::swc::ast;
+use deno_ast::swc::bundler::Bundler;
+use deno_ast::swc::bundler::Hook;
+use deno_ast::swc::bundler::Load;
+use deno_ast::swc::bundler::ModuleData;
+use deno_ast::swc::bundler::ModuleRecord;
+use deno_ast::swc::bundler::ModuleType;
+use deno_ast::swc::codegen::text_writer::JsWriter;
+use deno_ast::swc::codegen::Emitter;
+use deno_ast::swc::common::sync::Lrc;
+use deno_ast::swc::common::FileName;
+use deno_ast::swc::common::Globals;
+use deno_ast::swc::common::SourceFile;
+use deno_ast::swc::common::SourceMap;
+use deno_ast::swc::common::Span;
+use deno_ast::swc::common::SyntaxContext;
+use deno_ast::swc::common::DUMMY_SP;
+use deno_ast::swc::loader::resolve::Resolution;
+use deno_ast::swc::loader::resolve::Resolve;
+use deno_ast::swc::parser::EsSyntax;
+use deno_ast::swc::parser::Parser;
+use deno_ast::swc::parser::StringInput;
+use deno_ast::swc::parser::Syntax;
+use deno_ast::swc::transforms::helpers::Helpers;
+use deno_ast::swc::visit::VisitMut;
+use deno_ast::swc::visit::VisitMutWith;
+use deno_ast::MediaType;
+use deno_ast::ModuleSpecifier;
+use deno_core::anyhow::anyhow;
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::resolve_url_or_path;
+use deno_core::serde_json;
+use deno_graph::Module;
+use deno_graph::ModuleGraph;
+use deno_terminal::colors;
+use indexmap::IndexMap;
+use indexmap::IndexSet;
+
+use crate::args::BundleFlags;
+use crate::args::BundlePlatform;
+use crate::args::Flags;
+use crate::cache::FastInsecureHasher;
+use crate::emit::Emitter as CliEmitter;
+use crate::factory::CliFactory;
+use crate::util::display::human_size;
+
+/// The specifier of the generated entry of the common bundle.
+const COMMON_CHUNK_ENTRY: &str = "deno-bundle:common";
+
+pub async fn bundle(
+  flags: Arc<Flags>,
+  bundle_flags: BundleFlags,
+) -> Result<(), AnyError> {
+  let factory = CliFactory::from_flags(flags);
+  let cli_options = factory.cli_options()?;
+  let module_graph_creator = factory.module_graph_creator().await?;
+  let emitter = factory.emitter()?;
+
+  let entrypoints = bundle_flags
+    .entrypoints
+    .iter()
+    .map(|entrypoint| {
+      resolve_url_or_path(entrypoint, cli_options.initial_cwd())
+        .map_err(AnyError::from)
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+  let graph = module_graph_creator
+    .create_graph_and_maybe_check(entrypoints.clone())
+    .await?;
+
+  let mut plan = BundlePlan::new(&graph, &entrypoints, &bundle_flags)?;
+  let mut sources = load_sources(&graph, &plan, emitter).await?;
+  plan.resolve_shared_exports(&mut sources)?;
+  let chunks = plan
+    .chunks
+    .iter()
+    .map(|chunk| {
+      let code = bundle_chunk(chunk, &plan, &sources)
+        .with_context(|| format!("Failed to bundle {}", chunk.root))?;
+      Ok((chunk, code))
+    })
+    .collect::<Result<Vec<_>, AnyError>>()?;
+
+  if let Some(outdir) = &bundle_flags.outdir {
+    let outdir = cli_options.initial_cwd().join(outdir);
+    std::fs::create_dir_all(&outdir).with_context(|| {
+      format!("Failed to create directory {}", outdir.display())
+    })?;
+    for (chunk, code) in chunks {
+      write_bundle(&outdir.join(chunk.file_name()), &code)?;
+    }
+    return Ok(());
+  }
+
+  if chunks.len() > 1 {
+    bail!(
+      "Bundling created {} bundles as there are several entrypoints or dynamic imports. Use --outdir to write them to a directory.",
+      chunks.len()
+    );
+  }
+  let (_, code) = chunks.into_iter().next().unwrap();
+  match &bundle_flags.output {
+    Some(output) => {
+      let output = cli_options.initial_cwd().join(output);
+      if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+      }
+      write_bundle(&output, &code)?;
+    }
+    None => {
+      #[allow(clippy::print_stdout)]
+      {
+        print!("{}", code);
+      }
+    }
+  }
+  Ok(())
+}
+
+fn write_bundle(path: &Path, code: &str) -> Result<(), AnyError> {
+  std::fs::write(path, code)
+    .with_context(|| format!("Failed to write {}", path.display()))?;
+  log::info!(
+    "{} {} {}",
+    colors::green("Emit"),
+    path.display(),
+    colors::gray(format!("({})", human_size(code.len() as f64)))
+  );
+  Ok(())
+}
+
+struct Chunk {
+  root: ModuleSpecifier,
+  name: String,
+}
+
+impl Chunk {
+  fn file_name(&self) -> String {
+    format!("{}.js", self.name)
+  }
+}
+
+/// Which bundles to create, and how the specifiers of the bundled modules
+/// are rewritten.
+struct BundlePlan {
+  chunks: Vec<Chunk>,
+  /// The specifiers of the JavaScript and JSON modules to bundle.
+  modules: Vec<ModuleSpecifier>,
+  /// For each module, the specifiers to replace in its source.
+  rewrites: HashMap<ModuleSpecifier, HashMap<String, String>>,
+  /// The specifiers that are left as imports in the bundles.
+  externals: Vec<String>,
+  /// The modules hoisted into the common bundle, with the names they export
+  /// once they're resolved. A name is exported by the common bundle as
+  /// `__m{module index}_{name index}`, and the namespace of a module as
+  /// `__m{module index}`.
+  shared_modules: IndexMap<String, Vec<String>>,
+  /// The specifier the other bundles import the common bundle with.
+  common_chunk_specifier: Option<String>,
+}
+
+impl BundlePlan {
+  fn new(
+    graph: &ModuleGraph,
+    entrypoints: &[ModuleSpecifier],
+    bundle_flags: &BundleFlags,
+  ) -> Result<Self, AnyError> {
+    let mut plan = BundlePlan {
+      chunks: Vec::new(),
+      modules: Vec::new(),
+      rewrites: HashMap::new(),
+      externals: Vec::new(),
+      shared_modules: IndexMap::new(),
+      common_chunk_specifier: None,
+    };
+    let mut chunk_by_root = HashMap::new();
+    let mut chunk_names = HashSet::new();
+    let mut externals = HashSet::new();
+    for entrypoint in entrypoints {
+      let entrypoint = graph.resolve(entrypoint).clone();
+      if chunk_by_root.contains_key(&entrypoint) {
+        continue;
+      }
+      let mut name = file_stem(&entrypoint);
+      let mut i = 1;
+      while !chunk_names.insert(name.clone()) {
+        i += 1;
+        name = format!("{}-{}", file_stem(&entrypoint), i);
+      }
+      chunk_by_root.insert(entrypoint.clone(), name.clone());
+      plan.chunks.push(Chunk {
+        root: entrypoint,
+        name,
+      });
+    }
+
+    let mut static_dependencies = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut pending = plan
+      .chunks
+      .iter()
+      .map(|chunk| chunk.root.clone())
+      .collect::<VecDeque<_>>();
+    while let Some(specifier) = pending.pop_front() {
+      if !seen.insert(specifier.clone()) {
+        continue;
+      }
+      let module = match graph.get(&specifier) {
+        Some(Module::Js(module)) => module,
+        Some(Module::Json(_)) => {
+          plan.modules.push(specifier);
+          continue;
+        }
+        Some(_) => bail!("Can't bundle {}", specifier),
+        None => bail!("Module not found \"{}\".", specifier),
+      };
+      plan.modules.push(specifier.clone());
+      let mut rewrites = HashMap::new();
+      let mut dependencies = Vec::new();
+      for (raw_specifier, dependency) in &module.dependencies {
+        // type only dependencies
+        let Some(dependency_specifier) = dependency.get_code() else {
+          continue;
+        };
+        let is_external = is_external_specifier(
+          raw_specifier,
+          dependency_specifier,
+          &bundle_flags.external,
+        );
+        let dependency_module = graph.get(dependency_specifier);
+        let output_specifier = match dependency_module {
+          _ if is_external => {
+            let output_specifier = match bundle_flags.platform {
+              BundlePlatform::Deno
+                if matches!(dependency_specifier.scheme(), "npm" | "node") =>
+              {
+                dependency_specifier.to_string()
+              }
+              _ => raw_specifier.clone(),
+            };
+            externals.insert(output_specifier.clone());
+            output_specifier
+          }
+          Some(Module::Npm(_) | Module::Node(_) | Module::External(_)) => {
+            externals.insert(raw_specifier.clone());
+            raw_specifier.clone()
+          }
+          Some(Module::Js(_) | Module::Json(_)) => {
+            let target = graph.resolve(dependency_specifier).clone();
+            pending.push_back(target.clone());
+            if dependency.is_dynamic {
+              let name = chunk_by_root
+                .entry(target.clone())
+                .or_insert_with(|| {
+                  let name = dynamic_chunk_name(&target);
+                  plan.chunks.push(Chunk {
+                    root: target.clone(),
+                    name: name.clone(),
+                  });
+                  name
+                })
+                .clone();
+              let output_specifier = format!("./{name}.js");
+              externals.insert(output_specifier.clone());
+              output_specifier
+            } else {
+              dependencies.push(target.clone());
+              target.to_string()
+            }
+          }
+          None => bail!(
+            "Module not found \"{}\" imported from {}.",
+            dependency_specifier,
+            specifier
+          ),
+        };
+        rewrites.insert(raw_specifier.clone(), output_specifier);
+      }
+      static_dependencies.insert(specifier.clone(), dependencies);
+      plan.rewrites.insert(specifier, rewrites);
+    }
+
+    // The modules that are statically imported by several bundles.
+    let mut chunk_counts = HashMap::<&ModuleSpecifier, usize>::new();
+    for chunk in &plan.chunks {
+      let mut seen = HashSet::new();
+      let mut pending = vec![&chunk.root];
+      while let Some(specifier) = pending.pop() {
+        if !seen.insert(specifier) {
+          continue;
+        }
+        *chunk_counts.entry(specifier).or_default() += 1;
+        if let Some(dependencies) = static_dependencies.get(specifier) {
+          pending.extend(dependencies);
+        }
+      }
+    }
+    plan.shared_modules = plan
+      .modules
+      .iter()
+      .filter(|specifier| chunk_counts.get(specifier).is_some_and(|c| *c > 1))
+      .map(|specifier| (specifier.to_string(), Vec::new()))
+      .collect();
+    if !plan.shared_modules.is_empty() {
+      let mut hasher = FastInsecureHasher::new_without_deno_version();
+      for specifier in plan.shared_modules.keys() {
+        hasher.write_str(specifier);
+      }
+      let hash = hasher.finish();
+      let mut name = format!("chunk-{:08x}", hash as u32);
+      while !chunk_names.insert(name.clone()) {
+        name.push('_');
+      }
+      let output_specifier = format!("./{name}.js");
+      externals.insert(output_specifier.clone());
+      plan.common_chunk_specifier = Some(output_specifier);
+      plan.chunks.push(Chunk {
+        root: ModuleSpecifier::parse(COMMON_CHUNK_ENTRY).unwrap(),
+        name,
+      });
+    }
+    plan.externals = externals.into_iter().collect();
+    plan.externals.sort();
+    Ok(plan)
+  }
+
+  /// Resolves the names exported by the shared modules, and adds the source
+  /// of the entry of the common bundle, which exports them.
+  fn resolve_shared_exports(
+    &mut self,
+    sources: &mut HashMap<ModuleSpecifier, String>,
+  ) -> Result<(), AnyError> {
+    if self.shared_modules.is_empty() {
+      return Ok(());
+    }
+    let cm = Lrc::new(SourceMap::default());
+    let mut entry = String::new();
+    let mut shared_exports = Vec::with_capacity(self.shared_modules.len());
+    for (index, specifier) in self.shared_modules.keys().enumerate() {
+      let mut names = IndexSet::new();
+      self.collect_export_names(
+        &cm,
+        &ModuleSpecifier::parse(specifier)?,
+        sources,
+        &mut HashSet::new(),
+        &mut names,
+      )?;
+      let specifier = serde_json::to_string(specifier)?;
+      let namespace = namespace_name(index);
+      entry.push_str(&format!(
+        "import * as {namespace} from {specifier};\nexport {{ {namespace} }};\n"
+      ));
+      if !names.is_empty() {
+        let specifiers = names
+          .iter()
+          .enumerate()
+          .map(|(i, name)| {
+            format!(
+              "{} as {}",
+              export_name_source(name),
+              hoisted_name(index, i)
+            )
+          })
+          .collect::<Vec<_>>();
+        entry.push_str(&format!(
+          "export {{ {} }} from {specifier};\n",
+          specifiers.join(", ")
+        ));
+      }
+      shared_exports.push(names.into_iter().collect());
+    }
+    for (names, exports) in self.shared_modules.values_mut().zip(shared_exports)
+    {
+      *names = exports;
+    }
+    sources.insert(ModuleSpecifier::parse(COMMON_CHUNK_ENTRY)?, entry);
+    Ok(())
+  }
+
+  /// Collects the names exported by a module, following its `export *`
+  /// declarations.
+  fn collect_export_names(
+    &self,
+    cm: &Lrc<SourceMap>,
+    specifier: &ModuleSpecifier,
+    sources: &HashMap<ModuleSpecifier, String>,
+    seen: &mut HashSet<ModuleSpecifier>,
+    names: &mut IndexSet<String>,
+  ) -> Result<(), AnyError> {
+    if !seen.insert(specifier.clone()) {
+      return Ok(());
+    }
+    let source = sources
+      .get(specifier)
+      .ok_or_else(|| anyhow!("Module not found \"{}\".", specifier))?;
+    let (_, module) = parse_module(cm, specifier, source)?;
+    for item in &module.body {
+      let ast::ModuleItem::ModuleDecl(decl) = item else {
+        continue;
+      };
+      match decl {
+        ast::ModuleDecl::ExportDecl(export) => match &export.decl {
+          ast::Decl::Class(class) => {
+            names.insert(class.ident.sym.to_string());
+          }
+          ast::Decl::Fn(function) => {
+            names.insert(function.ident.sym.to_string());
+          }
+          ast::Decl::Var(var) => {
+            for declarator in &var.decls {
+              collect_binding_names(&declarator.name, names);
+            }
+          }
+          _ => {}
+        },
+        ast::ModuleDecl::ExportNamed(export) => {
+          for specifier in &export.specifiers {
+            let name = match specifier {
+              ast::ExportSpecifier::Named(named) => module_export_name(
+                named.exported.as_ref().unwrap_or(&named.orig),
+              ),
+              ast::ExportSpecifier::Namespace(namespace) => {
+                module_export_name(&namespace.name)
+              }
+              ast::ExportSpecifier::Default(default) => {
+                default.exported.sym.as_str()
+              }
+            };
+            names.insert(name.to_string());
+          }
+        }
+        ast::ModuleDecl::ExportDefaultDecl(_)
+        | ast::ModuleDecl::ExportDefaultExpr(_) => {
+          names.insert("default".to_string());
+        }
+        ast::ModuleDecl::ExportAll(export_all) => {
+          let target = self
+            .rewrites
+            .get(specifier)
+            .and_then(|rewrites| rewrites.get(export_all.src.value.as_str()))
+            .and_then(|target| ModuleSpecifier::parse(target).ok())
+            .filter(|target| sources.contains_key(target));
+          let Some(target) = target else {
+            bail!(
+              "Can't hoist {} into the common bundle as it re-exports all exports of the external module \"{}\".",
+              specifier,
+              export_all.src.value
+            );
+          };
+          // `export *` doesn't re-export the default export
+          let mut star_names = IndexSet::new();
+          self.collect_export_names(
+            cm,
+            &target,
+            sources,
+            seen,
+            &mut star_names,
+          )?;
+          names.extend(star_names.into_iter().filter(|name| name != "default"));
+        }
+        _ => {}
+      }
+    }
+    Ok(())
+  }
+}
+
+fn collect_binding_names(pat: &ast::Pat, names: &mut IndexSet<String>) {
+  match pat {
+    ast::Pat::Ident(ident) => {
+      names.insert(ident.id.sym.to_string());
+    }
+    ast::Pat::Array(array) => {
+      for elem in array.elems.iter().flatten() {
+        collect_binding_names(elem, names);
+      }
+    }
+    ast::Pat::Rest(rest) => collect_binding_names(&rest.arg, names),
+    ast::Pat::Object(object) => {
+      for prop in &object.props {
+        match prop {
+          ast::ObjectPatProp::KeyValue(key_value) => {
+            collect_binding_names(&key_value.value, names)
+          }
+          ast::ObjectPatProp::Assign(assign) => {
+            names.insert(assign.key.sym.to_string());
+          }
+          ast::ObjectPatProp::Rest(rest) => {
+            collect_binding_names(&rest.arg, names)
+          }
+        }
+      }
+    }
+    ast::Pat::Assign(assign) => collect_binding_names(&assign.left, names),
+    ast::Pat::Invalid(_) | ast::Pat::Expr(_) => {}
+  }
+}
+
+fn module_export_name(name: &ast::ModuleExportName) -> &str {
+  match name {
+    ast::ModuleExportName::Ident(ident) => ident.sym.as_str(),
+    ast::ModuleExportName::Str(str) => str.value.as_str(),
+  }
+}
+
+fn is_identifier(name: &str) -> bool {
+  name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '$')
+    && name
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Formats an export name for the source of a module, quoting it unless
+/// it's an identifier.
+fn export_name_source(name: &str) -> String {
+  if is_identifier(name) {
+    name.to_string()
+  } else {
+    serde_json::to_string(name).unwrap()
+  }
+}
+
+fn export_name(name: &str) -> ast::ModuleExportName {
+  if is_identifier(name) {
+    ast::ModuleExportName::Ident(ast::Ident::new(
+      name.into(),
+      DUMMY_SP,
+      SyntaxContext::empty(),
+    ))
+  } else {
+    ast::ModuleExportName::Str(ast::Str {
+      span: DUMMY_SP,
+      value: name.into(),
+      raw: None,
+    })
+  }
+}
+
+/// The name the common bundle exports a name of a shared module as.
+fn hoisted_name(module_index: usize, name_index: usize) -> String {
+  format!("__m{module_index}_{name_index}")
+}
+
+/// The name the common bundle exports the namespace of a shared module as.
+fn namespace_name(module_index: usize) -> String {
+  format!("__m{module_index}")
+}
+
+fn is_external_specifier(
+  raw_specifier: &str,
+  specifier: &ModuleSpecifier,
+  external: &[String],
+) -> bool {
+  if matches!(specifier.scheme(), "npm" | "node")
+    || specifier.path().contains("/node_modules/")
+  {
+    return true;
+  }
+  external.iter().any(|pattern| {
+    let matches = |value: &str| match pattern.strip_suffix('*') {
+      Some(prefix) => value.starts_with(prefix),
+      None => value == pattern,
+    };
+    matches(raw_specifier) || matches(specifier.as_str())
+  })
+}
+
+fn file_stem(specifier: &ModuleSpecifier) -> String {
+  let file_name = specifier
+    .path_segments()
+    .and_then(|mut segments| segments.next_back())
+    .filter(|segment| !segment.is_empty())
+    .unwrap_or("bundle");
+  let stem = match file_name.rsplit_once('.') {
+    Some((stem, _)) if !stem.is_empty() => stem,
+    _ => file_name,
+  };
+  stem
+    .chars()
+    .map(|c| {
+      if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+        c
+      } else {
+        '_'
+      }
+    })
+    .collect()
+}
+
+fn dynamic_chunk_name(specifier: &ModuleSpecifier) -> String {
+  let hash = FastInsecureHasher::new_without_deno_version()
+    .write_str(specifier.as_str())
+    .finish();
+  format!("{}-{:08x}", file_stem(specifier), hash as u32)
+}
+
+/// Loads the JavaScript source of the modules to bundle, transpiling the
+/// ones that need it.
+async fn load_sources(
+  graph: &ModuleGraph,
+  plan: &BundlePlan,
+  emitter: &CliEmitter,
+) -> Result<HashMap<ModuleSpecifier, String>, AnyError> {
+  let mut sources = HashMap::with_capacity(plan.modules.len());
+  for specifier in &plan.modules {
+    let source = match graph.get(specifier) {
+      Some(Module::Js(module)) => match module.media_type {
+        MediaType::JavaScript
+        | MediaType::Mjs
+        | MediaType::Cjs
+        | MediaType::Unknown => module.source.to_string(),
+        MediaType::TypeScript
+        | MediaType::Mts
+        | MediaType::Cts
+        | MediaType::Jsx
+        | MediaType::Tsx => {
+          let code = emitter
+            .emit_parsed_source(specifier, module.media_type, &module.source)
+            .await?;
+          String::from_utf8(code.to_vec())?
+        }
+        MediaType::Dts | MediaType::Dmts | MediaType::Dcts => String::new(),
+        media_type => {
+          bail!("Can't bundle {} of media type {}", specifier, media_type)
+        }
+      },
+      Some(Module::Json(module)) => {
+        format!("export default {};\n", module.source.trim())
+      }
+      _ => unreachable!(),
+    };
+    sources.insert(specifier.clone(), source);
+  }
+  Ok(sources)
+}
+
+fn bundle_chunk(
+  chunk: &Chunk,
+  plan: &BundlePlan,
+  sources: &HashMap<ModuleSpecifier, String>,
+) -> Result<String, AnyError> {
+  if let (Some((index, _, names)), Some(common_chunk_specifier)) = (
+    plan.shared_modules.get_full(chunk.root.as_str()),
+    &plan.common_chunk_specifier,
+  ) {
+    // the module is in the common bundle, which this bundle re-exports
+    let specifiers = names
+      .iter()
+      .enumerate()
+      .map(|(i, name)| {
+        format!("{} as {}", hoisted_name(index, i), export_name_source(name))
+      })
+      .collect::<Vec<_>>();
+    return Ok(format!(
+      "export {{ {} }} from {};\n",
+      specifiers.join(", "),
+      serde_json::to_string(common_chunk_specifier)?
+    ));
+  }
+
+  let globals = Globals::new();
+  let cm = Lrc::new(SourceMap::default());
+  let loader = BundleLoader {
+    cm: cm.clone(),
+    plan,
+    sources,
+  };
+  let config = deno_ast::swc::bundler::Config {
+    external_modules: plan
+      .externals
+      .iter()
+      .map(|specifier| specifier.as_str().into())
+      .collect(),
+    module: ModuleType::Es,
+    ..Default::default()
+  };
+  let mut bundler = Bundler::new(
+    &globals,
+    cm.clone(),
+    loader,
+    BundleResolver,
+    config,
+    Box::new(BundleHook),
+  );
+  let entries =
+    HashMap::from([(chunk.name.clone(), FileName::Url(chunk.root.clone()))]);
+  let bundle = bundler
+    .bundle(entries)?
+    .into_iter()
+    .next()
+    .ok_or_else(|| anyhow!("The bundler didn't create a bundle"))?;
+
+  let mut buf = Vec::new();
+  let mut emitter = Emitter {
+    cfg: Default::default(),
+    cm: cm.clone(),
+    comments: None,
+    wr: JsWriter::new(cm, "\n", &mut buf, None),
+  };
+  emitter.emit_module(&bundle.module)?;
+  let mut code = String::from_utf8(buf)?;
+  if !code.ends_with('\n') {
+    code.push('\n');
+  }
+  Ok(code)
+}
+
+struct BundleLoader<'a> {
+  cm: Lrc<SourceMap>,
+  plan: &'a BundlePlan,
+  sources: &'a HashMap<ModuleSpecifier, String>,
+}
+
+impl<'a> Load for BundleLoader<'a> {
+  fn load(
+    &self,
+    file_name: &FileName,
+  ) -> Result<ModuleData, deno_core::anyhow::Error> {
+    let FileName::Url(specifier) = file_name else {
+      bail!("Unexpected file name {}", file_name);
+    };
+    let source = self
+      .sources
+      .get(specifier)
+      .ok_or_else(|| anyhow!("Module not found \"{}\".", specifier))?;
+    let (fm, mut module) = parse_module(&self.cm, specifier, source)?;
+    if let Some(rewrites) = self.plan.rewrites.get(specifier) {
+      let is_shared = self.plan.shared_modules.contains_key(specifier.as_str());
+      let common_chunk = match &self.plan.common_chunk_specifier {
+        Some(common_chunk_specifier) if !is_shared => {
+          Some((common_chunk_specifier.as_str(), &self.plan.shared_modules))
+        }
+        _ => None,
+      };
+      module.visit_mut_with(&mut SpecifierRewriter {
+        rewrites,
+        common_chunk,
+      });
+    }
+    Ok(ModuleData {
+      fm,
+      module,
+      helpers: Helpers::new(false),
+    })
+  }
+}
+
+fn parse_module(
+  cm: &Lrc<SourceMap>,
+  specifier: &ModuleSpecifier,
+  source: &str,
+) -> Result<(Lrc<SourceFile>, ast::Module), AnyError> {
+  let fm = cm.new_source_file(
+    Lrc::new(FileName::Url(specifier.clone())),
+    source.to_string(),
+  );
+  let mut parser = Parser::new(
+    Syntax::Es(EsSyntax {
+      import_attributes: true,
+      explicit_resource_management: true,
+      ..Default::default()
+    }),
+    StringInput::from(&*fm),
+    None,
+  );
+  let module = parser.parse_module().map_err(|err| {
+    anyhow!("Failed to parse {}: {}", specifier, err.kind().msg())
+  })?;
+  Ok((fm, module))
+}
+
+/// Replaces the specifiers of the imports and exports of a module by the
+/// resolved specifiers of the modules to bundle, or the specifiers of the
+/// external modules.
+struct SpecifierRewriter<'a> {
+  rewrites: &'a HashMap<String, String>,
+  /// For the modules outside of the common bundle, its specifier and the
+  /// shared modules, whose imports are rewritten to imports of the names
+  /// the common bundle exports them as.
+  common_chunk: Option<(&'a str, &'a IndexMap<String, Vec<String>>)>,
+}
+
+impl<'a> SpecifierRewriter<'a> {
+  /// Rewrites an import or export of a shared module to one of the common
+  /// bundle.
+  fn hoist_shared_import(
+    &self,
+    node: &ast::ModuleDecl,
+  ) -> Option<ast::ModuleDecl> {
+    let (common_chunk_specifier, shared_modules) = self.common_chunk?;
+    let shared_module = |src: &ast::Str| {
+      let target = self.rewrites.get(src.value.as_str())?;
+      let (index, _, names) = shared_modules.get_full(target.as_str())?;
+      Some((index, names))
+    };
+    let common_chunk_src = |span| {
+      Box::new(ast::Str {
+        span,
+        value: common_chunk_specifier.into(),
+        raw: None,
+      })
+    };
+    // a missing export is left as is, so it still fails to link
+    let lookup = |index: usize, names: &[String], name: &str| match names
+      .iter()
+      .position(|n| n == name)
+    {
+      Some(i) => hoisted_name(index, i),
+      None => name.to_string(),
+    };
+    match node {
+      ast::ModuleDecl::Import(import) => {
+        let (index, names) = shared_module(&import.src)?;
+        let mut import = import.clone();
+        import.specifiers = import
+          .specifiers
+          .iter()
+          .map(|specifier| {
+            let (span, local, imported) = match specifier {
+              ast::ImportSpecifier::Named(named) => {
+                let name = match &named.imported {
+                  Some(imported) => module_export_name(imported),
+                  None => named.local.sym.as_str(),
+                };
+                (named.span, named.local.clone(), lookup(index, names, name))
+              }
+              ast::ImportSpecifier::Default(default) => (
+                default.span,
+                default.local.clone(),
+                lookup(index, names, "default"),
+              ),
+              ast::ImportSpecifier::Namespace(namespace) => (
+                namespace.span,
+                namespace.local.clone(),
+                namespace_name(index),
+              ),
+            };
+            ast::ImportSpecifier::Named(ast::ImportNamedSpecifier {
+              span,
+              local,
+              imported: Some(export_name(&imported)),
+              is_type_only: false,
+            })
+          })
+          .collect();
+        import.src = common_chunk_src(import.src.span);
+        import.with = None;
+        Some(ast::ModuleDecl::Import(import))
+      }
+      ast::ModuleDecl::ExportNamed(export) => {
+        let (index, names) = shared_module(export.src.as_deref()?)?;
+        let mut export = export.clone();
+        export.specifiers = export
+          .specifiers
+          .iter()
+          .map(|specifier| {
+            let (span, orig, exported) = match specifier {
+              ast::ExportSpecifier::Named(named) => {
+                let orig = module_export_name(&named.orig);
+                let exported = named
+                  .exported
+                  .as_ref()
+                  .map(module_export_name)
+                  .unwrap_or(orig);
+                (named.span, lookup(index, names, orig), exported.to_string())
+              }
+              ast::ExportSpecifier::Namespace(namespace) => (
+                namespace.span,
+                namespace_name(index),
+                module_export_name(&namespace.name).to_string(),
+              ),
+              ast::ExportSpecifier::Default(default) => (
+                default.exported.span,
+                lookup(index, names, "default"),
+                default.exported.sym.to_string(),
+              ),
+            };
+            ast::ExportSpecifier::Named(ast::ExportNamedSpecifier {
+              span,
+              orig: export_name(&orig),
+              exported: Some(export_name(&exported)),
+              is_type_only: false,
+            })
+          })
+          .collect();
+        export.src = Some(common_chunk_src(export.span));
+        export.with = None;
+        Some(ast::ModuleDecl::ExportNamed(export))
+      }
+      ast::ModuleDecl::ExportAll(export_all) => {
+        let (index, names) = shared_module(&export_all.src)?;
+        let specifiers = names
+          .iter()
+          .enumerate()
+          // `export *` doesn't re-export the default export
+          .filter(|(_, name)| *name != "default")
+          .map(|(i, name)| {
+            ast::ExportSpecifier::Named(ast::ExportNamedSpecifier {
+              span: export_all.span,
+              orig: export_name(&hoisted_name(index, i)),
+              exported: Some(export_name(name)),
+              is_type_only: false,
+            })
+          })
+          .collect();
+        Some(ast::ModuleDecl::ExportNamed(ast::NamedExport {
+          span: export_all.span,
+          specifiers,
+          src: Some(common_chunk_src(export_all.src.span)),
+          type_only: false,
+          with: None,
+        }))
+      }
+      _ => None,
+    }
+  }
+
+  fn rewrite(&self, src: &mut ast::Str) {
+    if let Some(output_specifier) = self.rewrites.get(src.value.as_str()) {
+      *src = ast::Str {
+        span: src.span,
+        value: output_specifier.as_str().into(),
+        raw: None,
+      };
+    }
+  }
+}
+
+impl<'a> VisitMut for SpecifierRewriter<'a> {
+  fn visit_mut_module_decl(&mut self, node: &mut ast::ModuleDecl) {
+    if let Some(hoisted) = self.hoist_shared_import(node) {
+      *node = hoisted;
+      return;
+    }
+    node.visit_mut_children_with(self);
+  }
+
+  fn visit_mut_import_decl(&mut self, node: &mut ast::ImportDecl) {
+    self.rewrite(&mut node.src);
+  }
+
+  fn visit_mut_named_export(&mut self, node: &mut ast::NamedExport) {
+    if let Some(src) = &mut node.src {
+      self.rewrite(src);
+    }
+  }
+
+  fn visit_mut_export_all(&mut self, node: &mut ast::ExportAll) {
+    self.rewrite(&mut node.src);
+  }
+
+  fn visit_mut_call_expr(&mut self, node: &mut ast::CallExpr) {
+    node.visit_mut_children_with(self);
+    if !matches!(node.callee, ast::Callee::Import(_)) {
+      return;
+    }
+    let Some(arg) = node.args.first_mut() else {
+      return;
+    };
+    match &mut *arg.expr {
+      ast::Expr::Lit(ast::Lit::Str(src)) => self.rewrite(src),
+      ast::Expr::Tpl(tpl) if tpl.exprs.is_empty() && tpl.quasis.len() == 1 => {
+        let quasi = &tpl.quasis[0];
+        let value = quasi.cooked.as_ref().unwrap_or(&quasi.raw);
+        if let Some(output_specifier) = self.rewrites.get(value.as_str()) {
+          *arg.expr = ast::Expr::Lit(ast::Lit::Str(ast::Str {
+            span: tpl.span,
+            value: output_specifier.as_str().into(),
+            raw: None,
+          }));
+        }
+      }
+      _ => {}
+    }
+  }
+}
+
+struct BundleResolver;
+
+impl Resolve for BundleResolver {
+  fn resolve(
+    &self,
+    _base: &FileName,
+    module_specifier: &str,
+  ) -> Result<Resolution, deno_core::anyhow::Error> {
+    // the loader rewrites the specifiers to the resolved ones
+    let specifier = ModuleSpecifier::parse(module_specifier)
+      .with_context(|| format!("Unresolved specifier {module_specifier}"))?;
+    Ok(Resolution {
+      filename: FileName::Url(specifier),
+      slug: None,
+    })
+  }
+}
+
+/// Replaces `import.meta` in the bundled modules, keeping `import.meta.main`
+/// of the entrypoint.
+struct BundleHook;
+
+impl Hook for BundleHook {
+  fn get_import_meta_props(
+    &self,
+    span: Span,
+    module_record: &ModuleRecord,
+  ) -> Result<Vec<ast::KeyValueProp>, deno_core::anyhow::Error> {
+    let main = if module_record.is_entry {
+      ast::Expr::Member(ast::MemberExpr {
+        span,
+        obj: Box::new(ast::Expr::MetaProp(ast::MetaPropExpr {
+          span,
+          kind: ast::MetaPropKind::ImportMeta,
+        })),
+        prop: ast::MemberProp::Ident(ast::IdentName::new("main".into(), span)),
+      })
+    } else {
+      ast::Expr::Lit(ast::Lit::Bool(ast::Bool { span, value: false }))
+    };
+    Ok(vec![
+      ast::KeyValueProp {
+        key: ast::PropName::Ident(ast::IdentName::new("url".into(), span)),
+        value: Box::new(ast::Expr::Lit(ast::Lit::Str(ast::Str {
+          span,
+          value: module_record.file_name.to_string().into(),
+          raw: None,
+        }))),
+      },
+      ast::KeyValueProp {
+        key: ast::PropName::Ident(ast::IdentName::new("main".into(), span)),
+        value: Box::new(main),
+      },
+    ])
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_file_stem() {
+    let specifier = |s: &str| ModuleSpecifier::parse(s).unwrap();
+    assert_eq!(file_stem(&specifier("file:///a/main.ts")), "main");
+    assert_eq!(
+      file_stem(&specifier("file:///a/main.worker.ts")),
+      "main_worker"
+    );
+    assert_eq!(file_stem(&specifier("https://deno.land/")), "bundle");
+    assert_eq!(file_stem(&specifier("https://deno.land/x/.env")), "_env");
+  }
+
+  #[test]
+  fn test_is_external_specifier() {
+    let specifier = |s: &str| ModuleSpecifier::parse(s).unwrap();
+    let external = vec!["https://esm.sh/*".to_string(), "./a.js".to_string()];
+    assert!(is_external_specifier(
+      "chalk",
+      &specifier("npm:chalk@5"),
+      &[]
+    ));
+    assert!(is_external_specifier("node:fs", &specifier("node:fs"), &[]));
+    assert!(is_external_specifier(
+      "preact",
+      &specifier("https://esm.sh/preact"),
+      &external
+    ));
+    assert!(is_external_specifier(
+      "./a.js",
+      &specifier("file:///a.js"),
+      &external
+    ));
+    assert!(!is_external_specifier(
+      "./b.js",
+      &specifier("file:///b.js"),
+      &external
+    ));
+  }
+}
@@ -0,0 +1,396 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Offline bundles of the cached dependencies of a module graph.
+//!
+//! A bundle is a tar archive with the files of the remote modules, the JSR
+//! package metadata and the npm packages in the DENO_DIR, along with a
+//! manifest of their checksums. Importing it on another machine populates
+//! the DENO_DIR there, so the modules run with `--cached-only` without any
+//! network access.
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::ModuleSpecifier;
+use deno_graph::ModuleGraph;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::args::jsr_url;
+use crate::args::CacheFlags;
+use crate::args::Flags;
+use crate::cache::GlobalHttpCache;
+use crate::cache::CACHE_PERM;
+use crate::colors;
+use crate::display;
+use crate::factory::CliFactory;
+use crate::util::checksum;
+use crate::util::fs::atomic_write_file_with_retries;
+
+const MANIFEST_PATH: &str = "manifest.json";
+const FILES_DIR: &str = "files/";
+const BUNDLE_VERSION: u32 = 1;
+
+/// The first entry of a bundle, which has the checksums of the files.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BundleManifest {
+  version: u32,
+  /// The sha256 checksums of the files, keyed by their paths relative to
+  /// the DENO_DIR with forward slashes.
+  files: BTreeMap<String, String>,
+}
+
+pub async fn cache(
+  flags: Arc<Flags>,
+  cache_flags: CacheFlags,
+) -> Result<(), AnyError> {
+  let factory = CliFactory::from_flags(flags);
+  if let Some(import_path) = &cache_flags.import {
+    let data = std::fs::read(import_path)
+      .with_context(|| format!("Failed reading {}", import_path))?;
+    let deno_dir = factory.deno_dir()?;
+    let files_count = import_bundle(&data, &deno_dir.root)
+      .with_context(|| format!("Failed importing {}", import_path))?;
+    log::info!(
+      "{} {} files from {}",
+      colors::green("Imported"),
+      files_count,
+      import_path
+    );
+  }
+  if cache_flags.files.is_empty() {
+    return Ok(());
+  }
+
+  let emitter = factory.emitter()?;
+  let main_graph_container = factory.main_module_graph_container().await?;
+  main_graph_container
+    .load_and_type_check_files(&cache_flags.files)
+    .await?;
+  let graph = main_graph_container.graph();
+  emitter.cache_module_emits(&graph).await?;
+
+  if let Some(export_path) = &cache_flags.export {
+    let paths = bundle_file_paths(&factory, &graph).await?;
+    let root = &factory.deno_dir()?.root;
+    let data = export_bundle(root, &paths)?;
+    atomic_write_file_with_retries(Path::new(export_path), &data, CACHE_PERM)
+      .with_context(|| format!("Failed writing {}", export_path))?;
+    log::info!(
+      "{} {} files to {} {}",
+      colors::green("Exported"),
+      paths.len(),
+      export_path,
+      colors::gray(&format!("({})", display::human_size(data.len() as f64)))
+    );
+  }
+  Ok(())
+}
+
+/// Gets the paths of the cached files that the graph needs to load without
+/// the network, which are the remote modules along with their redirects, the
+/// metadata of the JSR packages and the npm packages with their registry
+/// information.
+async fn bundle_file_paths(
+  factory: &CliFactory,
+  graph: &ModuleGraph,
+) -> Result<Vec<PathBuf>, AnyError> {
+  let http_cache = factory.global_http_cache()?;
+  let mut specifiers = graph
+    .specifiers()
+    .map(|(specifier, _)| specifier)
+    .chain(graph.redirects.keys())
+    .filter(|specifier| matches!(specifier.scheme(), "http" | "https"))
+    .cloned()
+    .collect::<Vec<_>>();
+  let mut package_names = HashSet::new();
+  for nv in graph.packages.mappings().values() {
+    if package_names.insert(nv.name.as_str()) {
+      specifiers.push(jsr_url().join(&format!("{}/meta.json", nv.name))?);
+    }
+    specifiers
+      .push(jsr_url().join(&format!("{}/{}_meta.json", nv.name, nv.version))?);
+  }
+
+  let mut paths = Vec::new();
+  for specifier in &specifiers {
+    if let Some(path) = http_cache_file_path(http_cache, specifier) {
+      paths.push(path);
+    }
+  }
+
+  let npm_resolver = factory.npm_resolver().await?;
+  if let Some(managed) = npm_resolver.as_managed() {
+    for path in managed.global_cache_paths() {
+      if path.is_file() {
+        paths.push(path);
+        continue;
+      }
+      for entry in walkdir::WalkDir::new(&path) {
+        let entry = entry?;
+        if entry.file_type().is_file() && entry.file_name() != ".deno_sync_lock"
+        {
+          paths.push(entry.into_path());
+        }
+      }
+    }
+  }
+
+  paths.sort();
+  paths.dedup();
+  Ok(paths)
+}
+
+fn http_cache_file_path(
+  http_cache: &GlobalHttpCache,
+  specifier: &ModuleSpecifier,
+) -> Option<PathBuf> {
+  #[allow(deprecated)]
+  let path = http_cache.get_global_cache_filepath(specifier).ok()?;
+  path.is_file().then_some(path)
+}
+
+/// Packs the files, which are in the DENO_DIR at the root, into a bundle.
+fn export_bundle(root: &Path, paths: &[PathBuf]) -> Result<Vec<u8>, AnyError> {
+  let mut manifest = BundleManifest {
+    version: BUNDLE_VERSION,
+    files: Default::default(),
+  };
+  let mut entries = Vec::with_capacity(paths.len());
+  for path in paths {
+    let Some(relative_path) = bundle_relative_path(root, path) else {
+      log::debug!("Skipping {}, which is outside the DENO_DIR", path.display());
+      continue;
+    };
+    let data = std::fs::read(path)
+      .with_context(|| format!("Failed reading {}", path.display()))?;
+    manifest
+      .files
+      .insert(relative_path.clone(), checksum::gen(&[&data]));
+    entries.push((relative_path, file_mode(path)?, data));
+  }
+
+  let mut builder = tar::Builder::new(Vec::new());
+  let manifest = serde_json::to_vec_pretty(&manifest)?;
+  let mut header = tar::Header::new_gnu();
+  header.set_size(manifest.len() as u64);
+  header.set_mode(CACHE_PERM);
+  builder.append_data(&mut header, MANIFEST_PATH, manifest.as_slice())?;
+  for (relative_path, mode, data) in entries {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(mode);
+    builder.append_data(
+      &mut header,
+      format!("{FILES_DIR}{relative_path}"),
+      data.as_slice(),
+    )?;
+  }
+  builder.finish()?;
+  Ok(builder.into_inner()?)
+}
+
+/// Verifies the checksums of the files of the bundle and then writes them to
+/// the DENO_DIR at the root, returning the number of files.
+fn import_bundle(data: &[u8], root: &Path) -> Result<usize, AnyError> {
+  let mut archive = tar::Archive::new(data);
+  let mut entries = archive.entries()?;
+  let manifest: BundleManifest = match entries.next() {
+    Some(entry) => {
+      let mut entry = entry?;
+      if entry.path()?.to_str() != Some(MANIFEST_PATH) {
+        bail!("The bundle doesn't start with a {MANIFEST_PATH}.");
+      }
+      let mut text = String::new();
+      entry.read_to_string(&mut text)?;
+      serde_json::from_str(&text)
+        .with_context(|| format!("Invalid {MANIFEST_PATH}"))?
+    }
+    None => bail!("The bundle is empty."),
+  };
+  if manifest.version != BUNDLE_VERSION {
+    bail!(
+      "Unsupported bundle version {}. Only version {} is supported.",
+      manifest.version,
+      BUNDLE_VERSION
+    );
+  }
+
+  let mut files = Vec::with_capacity(manifest.files.len());
+  for entry in entries {
+    let mut entry = entry?;
+    let entry_path = entry.path()?.to_string_lossy().into_owned();
+    let Some(relative_path) = entry_path.strip_prefix(FILES_DIR) else {
+      bail!("Unexpected entry \"{entry_path}\" in the bundle.");
+    };
+    let Some(expected) = manifest.files.get(relative_path) else {
+      bail!("The file \"{relative_path}\" isn't in the {MANIFEST_PATH}.");
+    };
+    let Some(path) = resolve_bundle_path(root, relative_path) else {
+      bail!("Invalid path \"{relative_path}\" in the bundle.");
+    };
+    let mode = entry.header().mode().unwrap_or(CACHE_PERM);
+    let mut data = Vec::with_capacity(entry.size() as usize);
+    entry.read_to_end(&mut data)?;
+    let actual = checksum::gen(&[&data]);
+    if actual != *expected {
+      bail!(
+        concat!(
+          "Integrity check failed for \"{}\" in the bundle.\n\n",
+          "Actual: {}\n",
+          "Expected: {}",
+        ),
+        relative_path,
+        actual,
+        expected
+      );
+    }
+    files.push((relative_path.to_string(), path, mode, data));
+  }
+  let found = files
+    .iter()
+    .map(|(relative_path, ..)| relative_path.as_str())
+    .collect::<HashSet<_>>();
+  if let Some(missing) = manifest
+    .files
+    .keys()
+    .find(|relative_path| !found.contains(relative_path.as_str()))
+  {
+    bail!(
+      "The file \"{missing}\" of the {MANIFEST_PATH} is missing in the bundle."
+    );
+  }
+
+  for (_, path, mode, data) in &files {
+    atomic_write_file_with_retries(path, data, *mode)
+      .with_context(|| format!("Failed writing {}", path.display()))?;
+  }
+  Ok(files.len())
+}
+
+/// Gets the path of a file relative to the root with forward slashes.
+fn bundle_relative_path(root: &Path, path: &Path) -> Option<String> {
+  let relative_path = path.strip_prefix(root).ok()?;
+  let components = relative_path
+    .components()
+    .map(|component| match component {
+      std::path::Component::Normal(name) => name.to_str(),
+      _ => None,
+    })
+    .collect::<Option<Vec<_>>>()?;
+  Some(components.join("/"))
+}
+
+/// Resolves a path of a bundle against the root, which fails for the paths
+/// that would escape the root.
+fn resolve_bundle_path(root: &Path, relative_path: &str) -> Option<PathBuf> {
+  let mut path = root.to_path_buf();
+  for name in relative_path.split('/') {
+    if name.is_empty()
+      || name == "."
+      || name == ".."
+      || name.contains(['\\', ':'])
+    {
+      return None;
+    }
+    path.push(name);
+  }
+  Some(path)
+}
+
+#[cfg(unix)]
+fn file_mode(path: &Path) -> Result<u32, AnyError> {
+  use std::os::unix::fs::PermissionsExt;
+  Ok(std::fs::metadata(path)?.permissions().mode() & 0o777)
+}
+
+#[cfg(not(unix))]
+fn file_mode(_path: &Path) -> Result<u32, AnyError> {
+  Ok(CACHE_PERM)
+}
+
+#[cfg(test)]
+mod test {
+  use test_util::TempDir;
+
+  use super::*;
+
+  #[test]
+  fn test_export_import_bundle() {
+    let temp_dir = TempDir::new();
+    let export_root = temp_dir.path().join("export").to_path_buf();
+    let files = [
+      ("remote/https/deno.land/abc", "export const a = 1;"),
+      ("npm/registry.npmjs.org/chalk/5.0.1/index.js", "export {};"),
+      ("npm/registry.npmjs.org/chalk/registry.json", "{}"),
+    ];
+    let mut paths = Vec::new();
+    for (relative_path, text) in files {
+      let path = export_root.join(relative_path);
+      std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+      std::fs::write(&path, text).unwrap();
+      paths.push(path);
+    }
+    let outside_path = temp_dir.path().join("outside.txt").to_path_buf();
+    std::fs::write(&outside_path, "").unwrap();
+    paths.push(outside_path);
+
+    let data = export_bundle(&export_root, &paths).unwrap();
+    let import_root = temp_dir.path().join("import").to_path_buf();
+    assert_eq!(import_bundle(&data, &import_root).unwrap(), 3);
+    for (relative_path, text) in files {
+      assert_eq!(
+        std::fs::read_to_string(import_root.join(relative_path)).unwrap(),
+        text
+      );
+    }
+    assert!(!import_root.join("outside.txt").exists());
+  }
+
+  #[test]
+  fn test_import_bundle_integrity() {
+    let temp_dir = TempDir::new();
+    let root = temp_dir.path().to_path_buf();
+    let path = root.join("remote/https/deno.land/abc");
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, "export const a = 1;").unwrap();
+    let mut data = export_bundle(&root, &[path]).unwrap();
+    // tamper with the contents of the file without changing its size
+    let source = b"export const a = 1;";
+    let index = data
+      .windows(source.len())
+      .position(|window| window == source)
+      .unwrap();
+    data[index + source.len() - 2] = b'2';
+    let import_root = root.join("import");
+    let err = import_bundle(&data, &import_root).unwrap_err();
+    assert!(err.to_string().starts_with("Integrity check failed"));
+    assert!(!import_root.exists());
+  }
+
+  #[test]
+  fn test_resolve_bundle_path() {
+    let root = Path::new("/deno_dir");
+    assert_eq!(
+      resolve_bundle_path(root, "remote/https/deno.land/abc"),
+      Some(
+        root
+          .join("remote")
+          .join("https")
+          .join("deno.land")
+          .join("abc")
+      )
+    );
+    assert_eq!(resolve_bundle_path(root, "../etc/passwd"), None);
+    assert_eq!(resolve_bundle_path(root, "/etc/passwd"), None);
+    assert_eq!(resolve_bundle_path(root, "remote/./abc"), None);
+  }
+}
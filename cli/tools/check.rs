@@ -1,5 +1,6 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::collections::BTreeSet;
 use std::collections::HashSet;
 use std::collections::VecDeque;
 use std::sync::Arc;
@@ -15,8 +16,10 @@ use once_cell::sync::Lazy;
 use regex::Regex;
 
 use crate::args::check_warn_tsconfig;
+use crate::args::deno_json::read_check_config;
 use crate::args::CheckFlags;
 use crate::args::CliOptions;
+use crate::args::DenoSubcommand;
 use crate::args::Flags;
 use crate::args::TsConfig;
 use crate::args::TsConfigType;
@@ -171,6 +174,7 @@ impl TypeChecker {
     }
 
     let type_check_mode = options.type_check_mode;
+    let npm_check_options = CheckNpmOptions::resolve(&self.cli_options)?;
     let ts_config = ts_config_result.ts_config;
     let maybe_check_hash = match self.npm_resolver.check_state_hash() {
       Some(npm_check_hash) => {
@@ -178,6 +182,7 @@ impl TypeChecker {
           &graph,
           npm_check_hash,
           type_check_mode,
+          &npm_check_options,
           &ts_config,
         ) {
           CheckHashResult::NoFiles => {
@@ -251,13 +256,20 @@ impl TypeChecker {
       check_mode: type_check_mode,
     })?;
 
-    let mut diagnostics = response.diagnostics.filter(|d| {
-      if self.is_remote_diagnostic(d) {
-        type_check_mode == TypeCheckMode::All && d.include_when_remote()
-      } else {
-        true
-      }
-    });
+    let mut diagnostics =
+      response
+        .diagnostics
+        .filter(|d| match self.diagnostic_origin(d) {
+          DiagnosticOrigin::Local => true,
+          DiagnosticOrigin::Remote => {
+            type_check_mode == TypeCheckMode::All && d.include_when_remote()
+          }
+          DiagnosticOrigin::Npm(specifier) => {
+            (type_check_mode == TypeCheckMode::All || npm_check_options.include)
+              && d.include_when_remote()
+              && !self.is_skipped_npm_package(&specifier, &npm_check_options)
+          }
+        });
 
     diagnostics.apply_fast_check_source_maps(&graph);
 
@@ -276,18 +288,84 @@ impl TypeChecker {
     Ok((graph, diagnostics))
   }
 
-  fn is_remote_diagnostic(&self, d: &tsc::Diagnostic) -> bool {
+  fn diagnostic_origin(&self, d: &tsc::Diagnostic) -> DiagnosticOrigin {
     let Some(file_name) = &d.file_name else {
-      return false;
+      return DiagnosticOrigin::Local;
     };
     if file_name.starts_with("https://") || file_name.starts_with("http://") {
-      return true;
+      return DiagnosticOrigin::Remote;
     }
     // check if in an npm package
     let Ok(specifier) = ModuleSpecifier::parse(file_name) else {
+      return DiagnosticOrigin::Local;
+    };
+    if self.node_resolver.in_npm_package(&specifier) {
+      DiagnosticOrigin::Npm(specifier)
+    } else {
+      DiagnosticOrigin::Local
+    }
+  }
+
+  fn is_skipped_npm_package(
+    &self,
+    specifier: &ModuleSpecifier,
+    npm_check_options: &CheckNpmOptions,
+  ) -> bool {
+    if npm_check_options.skip.is_empty() {
       return false;
+    }
+    let Ok(mut path) = specifier.to_file_path() else {
+      return false;
+    };
+    // nested package.json files, like the ones only declaring the module
+    // type of a directory, don't have a name
+    while let Ok(Some(pkg_json)) =
+      self.node_resolver.get_closest_package_json_from_path(&path)
+    {
+      if let Some(name) = &pkg_json.name {
+        return npm_check_options.skip.contains(name);
+      }
+      match pkg_json.path.parent() {
+        Some(dir_path) => path = dir_path.to_path_buf(),
+        None => break,
+      }
+    }
+    false
+  }
+}
+
+enum DiagnosticOrigin {
+  Local,
+  Remote,
+  Npm(ModuleSpecifier),
+}
+
+/// Whether type errors in npm packages are reported, which is set with the
+/// `--npm` and `--npm-skip` flags of `deno check` or the `"check"` section
+/// of the config file.
+#[derive(Debug, Default)]
+struct CheckNpmOptions {
+  include: bool,
+  skip: BTreeSet<String>,
+}
+
+impl CheckNpmOptions {
+  fn resolve(cli_options: &CliOptions) -> Result<Self, AnyError> {
+    let mut options = match cli_options.workspace().root_deno_json() {
+      Some(config_file) => {
+        let check_config = read_check_config(config_file)?;
+        Self {
+          include: check_config.npm,
+          skip: check_config.npm_skip.into_iter().collect(),
+        }
+      }
+      None => Self::default(),
     };
-    self.node_resolver.in_npm_package(&specifier)
+    if let DenoSubcommand::Check(check_flags) = cli_options.sub_command() {
+      options.include |= check_flags.npm;
+      options.skip.extend(check_flags.npm_skip.iter().cloned());
+    }
+    Ok(options)
   }
 }
 
@@ -302,6 +380,7 @@ fn get_check_hash(
   graph: &ModuleGraph,
   package_reqs_hash: u64,
   type_check_mode: TypeCheckMode,
+  npm_check_options: &CheckNpmOptions,
   ts_config: &TsConfig,
 ) -> CheckHashResult {
   let mut hasher = FastInsecureHasher::new_deno_versioned();
@@ -310,6 +389,10 @@ fn get_check_hash(
     TypeCheckMode::Local => 1,
     TypeCheckMode::None => 2,
   });
+  hasher.write_u8(npm_check_options.include as u8);
+  for package in &npm_check_options.skip {
+    hasher.write_str(package);
+  }
   hasher.write(&ts_config.as_bytes());
 
   let check_js = ts_config.get_check_js();
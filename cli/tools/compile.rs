@@ -1,11 +1,15 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 use crate::args::check_warn_tsconfig;
+use crate::args::deno_json::read_compile_config;
+use crate::args::CliOptions;
 use crate::args::CompileFlags;
 use crate::args::Flags;
 use crate::factory::CliFactory;
 use crate::http_util::HttpClientProvider;
 use crate::standalone::is_standalone_binary;
+use crate::util::fs::canonicalize_path;
+use deno_ast::MediaType;
 use deno_ast::ModuleSpecifier;
 use deno_core::anyhow::bail;
 use deno_core::anyhow::Context;
@@ -33,12 +37,11 @@ pub async fn compile(
   let binary_writer = factory.create_compile_binary_writer().await?;
   let http_client = factory.http_client_provider();
   let module_specifier = cli_options.resolve_main_module()?;
+  let includes = resolve_includes(&compile_flags, cli_options)?;
   let module_roots = {
-    let mut vec = Vec::with_capacity(compile_flags.include.len() + 1);
+    let mut vec = Vec::with_capacity(includes.modules.len() + 1);
     vec.push(module_specifier.clone());
-    for side_module in &compile_flags.include {
-      vec.push(resolve_url_or_path(side_module, cli_options.initial_cwd())?);
-    }
+    vec.extend(includes.modules);
     vec
   };
 
@@ -81,19 +84,38 @@ pub async fn compile(
   let ts_config_for_emit = cli_options
     .resolve_ts_config_for_emit(deno_config::deno_json::TsConfigType::Emit)?;
   check_warn_tsconfig(&ts_config_for_emit);
-  let (transpile_options, emit_options) =
+  let (transpile_options, mut emit_options) =
     crate::args::ts_config_to_transpile_and_emit_options(
       ts_config_for_emit.ts_config,
     )?;
+  if compile_flags.strip {
+    emit_options.source_map = deno_ast::SourceMapOption::None;
+  }
   let parser = parsed_source_cache.as_capturing_parser();
+  let include_file_urls = includes
+    .files
+    .iter()
+    .map(|path| {
+      if path.is_dir() {
+        ModuleSpecifier::from_directory_path(path)
+      } else {
+        ModuleSpecifier::from_file_path(path)
+      }
+      .unwrap()
+    })
+    .collect::<Vec<_>>();
   let root_dir_url = resolve_root_dir_from_specifiers(
     cli_options.workspace().root_dir(),
-    graph.specifiers().map(|(s, _)| s).chain(
-      cli_options
-        .node_modules_dir_path()
-        .and_then(|p| ModuleSpecifier::from_directory_path(p).ok())
-        .iter(),
-    ),
+    graph
+      .specifiers()
+      .map(|(s, _)| s)
+      .chain(
+        cli_options
+          .node_modules_dir_path()
+          .and_then(|p| ModuleSpecifier::from_directory_path(p).ok())
+          .iter(),
+      )
+      .chain(include_file_urls.iter()),
   );
   log::debug!("Binary root dir: {}", root_dir_url);
   let root_dir_url = EszipRelativeFileBaseUrl::new(&root_dir_url);
@@ -136,6 +158,7 @@ pub async fn compile(
       eszip,
       root_dir_url,
       &module_specifier,
+      &includes.files,
       &compile_flags,
       cli_options,
     )
@@ -157,6 +180,12 @@ pub async fn compile(
     })
   });
 
+  let write_result =
+    write_result.and_then(|_| match &compile_flags.sign_command {
+      Some(sign_command) => sign_executable(sign_command, &temp_path),
+      None => Ok(()),
+    });
+
   let write_result = write_result.and_then(|_| {
     std::fs::rename(&temp_path, &output_path).with_context(|| {
       format!(
@@ -176,6 +205,87 @@ pub async fn compile(
   Ok(())
 }
 
+/// Runs the `--sign-command` through the shell with the path of the
+/// executable appended as its last argument.
+fn sign_executable(sign_command: &str, path: &Path) -> Result<(), AnyError> {
+  log::info!("{} {}", colors::green("Sign"), path.display());
+  #[cfg(unix)]
+  let status = std::process::Command::new("sh")
+    .arg("-c")
+    .arg(format!("{sign_command} \"$0\""))
+    .arg(path)
+    .status();
+  #[cfg(windows)]
+  let status = {
+    use std::os::windows::process::CommandExt;
+    std::process::Command::new("cmd")
+      .arg("/C")
+      .raw_arg(format!("{sign_command} \"{}\"", path.display()))
+      .status()
+  };
+  let status = status
+    .with_context(|| format!("Failed running sign command '{sign_command}'"))?;
+  if !status.success() {
+    bail!("Sign command '{}' failed with {}", sign_command, status);
+  }
+  Ok(())
+}
+
+/// The values of `--include` and the `"compile.include"` config.
+#[derive(Default)]
+struct CompileIncludes {
+  /// Additional roots of the module graph.
+  modules: Vec<ModuleSpecifier>,
+  /// Files and directories to embed in the executable's virtual file system.
+  files: Vec<PathBuf>,
+}
+
+fn resolve_includes(
+  compile_flags: &CompileFlags,
+  cli_options: &CliOptions,
+) -> Result<CompileIncludes, AnyError> {
+  let mut specifiers = Vec::new();
+  for include in &compile_flags.include {
+    specifiers.push(resolve_url_or_path(include, cli_options.initial_cwd())?);
+  }
+  if let Some(config_file) = cli_options.start_dir.maybe_deno_json() {
+    for include in read_compile_config(config_file)?.include {
+      specifiers.push(config_file.specifier.join(&include).with_context(
+        || format!("Invalid \"compile.include\" entry '{include}'"),
+      )?);
+    }
+  }
+
+  let mut includes = CompileIncludes::default();
+  for specifier in specifiers {
+    let Some(path) = specifier
+      .scheme()
+      .eq("file")
+      .then(|| specifier.to_file_path().ok())
+      .flatten()
+    else {
+      includes.modules.push(specifier);
+      continue;
+    };
+    if path.is_dir() {
+      let path = canonicalize_path(&path)?;
+      includes.files.push(path);
+      continue;
+    }
+    // embed JSON files so they can be both imported and read
+    let media_type = MediaType::from_specifier(&specifier);
+    if matches!(media_type, MediaType::Unknown | MediaType::Json) {
+      let path = canonicalize_path(&path)
+        .with_context(|| format!("Failed to include '{}'", path.display()))?;
+      includes.files.push(path);
+    }
+    if media_type != MediaType::Unknown {
+      includes.modules.push(specifier);
+    }
+  }
+  Ok(includes)
+}
+
 /// This function writes out a final binary to specified path. If output path
 /// is not already standalone binary it will return error instead.
 fn validate_output_path(output_path: &Path) -> Result<(), AnyError> {
@@ -365,6 +475,10 @@ mod test {
         no_terminal: false,
         icon: None,
         include: vec![],
+        windows_version_info: vec![],
+        sign_command: None,
+        compress: false,
+        strip: false,
       },
       &std::env::current_dir().unwrap(),
     )
@@ -389,6 +503,10 @@ mod test {
         target: Some("x86_64-pc-windows-msvc".to_string()),
         include: vec![],
         icon: None,
+        windows_version_info: vec![],
+        sign_command: None,
+        compress: false,
+        strip: false,
         no_terminal: false,
       },
       &std::env::current_dir().unwrap(),
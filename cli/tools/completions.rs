@@ -0,0 +1,39 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::io::Write;
+use std::sync::Arc;
+
+use deno_core::error::AnyError;
+
+use crate::args::DynamicCompletionsKind;
+use crate::args::Flags;
+use crate::factory::CliFactory;
+use crate::tools;
+
+/// Prints the values the shell completion scripts ask for with
+/// `deno completions --dynamic=<KIND>`, one per line.
+pub async fn print_dynamic_completions(
+  flags: Arc<Flags>,
+  kind: DynamicCompletionsKind,
+) -> Result<(), AnyError> {
+  let values = match kind {
+    DynamicCompletionsKind::Installed => {
+      tools::installer::installed_names(None)?
+    }
+    DynamicCompletionsKind::Tasks | DynamicCompletionsKind::Tests => {
+      let factory = CliFactory::from_flags(flags);
+      let cli_options = factory.cli_options()?;
+      if kind == DynamicCompletionsKind::Tasks {
+        tools::task::task_names(&cli_options.start_dir)?
+      } else {
+        tools::test::collect_test_names(cli_options)?
+      }
+    }
+  };
+  let mut output = Vec::new();
+  for value in values {
+    writeln!(output, "{value}")?;
+  }
+  crate::display::write_to_stdout_ignore_sigpipe(&output)?;
+  Ok(())
+}
@@ -1,11 +1,13 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use crate::args::deno_json::read_coverage_config;
 use crate::args::CliOptions;
 use crate::args::CoverageFlags;
 use crate::args::FileFlags;
 use crate::args::Flags;
 use crate::cdp;
 use crate::factory::CliFactory;
+use crate::file_fetcher::FileFetcher;
 use crate::npm::CliNpmResolver;
 use crate::tools::fmt::format_json;
 use crate::tools::test::is_supported_test_path;
@@ -201,7 +203,7 @@ fn generate_coverage_report(
 ) -> CoverageReport {
   let maybe_source_map = maybe_source_map
     .as_ref()
-    .map(|source_map| SourceMap::from_slice(source_map).unwrap());
+    .and_then(|source_map| SourceMap::from_slice(source_map).ok());
   let text_lines = TextLines::new(&script_source);
 
   let comment_ranges = deno_ast::lex(&script_source, MediaType::JavaScript)
@@ -381,6 +383,71 @@ fn range_to_src_line_index(
   }
 }
 
+/// The source a JavaScript module was generated from, as described by its
+/// source map.
+struct OriginalSource {
+  specifier: ModuleSpecifier,
+  text: String,
+  source_map: Vec<u8>,
+}
+
+/// Resolves the original source of a module from its inline source map or
+/// the file referenced by its `sourceMappingURL` comment. Source maps that
+/// combine several sources (ex. bundles) can't be reported as a single file,
+/// so they're ignored rather than reporting lines of the wrong file.
+fn resolve_original_source(
+  specifier: &ModuleSpecifier,
+  code: &str,
+  file_fetcher: &FileFetcher,
+) -> Option<OriginalSource> {
+  let (map_specifier, source_map) = match source_map_from_code(code.as_bytes())
+  {
+    Some(source_map) => (specifier.clone(), source_map),
+    None => {
+      let url = code.lines().rev().find_map(|line| {
+        let line = line.trim();
+        line
+          .strip_prefix("//# sourceMappingURL=")
+          .or_else(|| line.strip_prefix("//@ sourceMappingURL="))
+      })?;
+      if url.starts_with("data:") {
+        return None;
+      }
+      let map_specifier = specifier.join(url.trim()).ok()?;
+      let map_file = if map_specifier.scheme() == "file" {
+        file_fetcher.get_source(&map_specifier)
+      } else {
+        file_fetcher.fetch_cached(&map_specifier, 10).ok()?
+      }?;
+      (map_specifier, map_file.source.to_vec())
+    }
+  };
+  let parsed = SourceMap::from_slice(&source_map).ok()?;
+  if parsed.get_source_count() != 1 {
+    return None;
+  }
+  let source = parsed.get_source(0)?;
+  let source = match parsed.get_source_root() {
+    Some(root) if !root.is_empty() => {
+      format!("{}/{}", root.trim_end_matches('/'), source)
+    }
+    _ => source.to_string(),
+  };
+  let source_specifier = map_specifier.join(&source).ok()?;
+  let text = match parsed.get_source_contents(0) {
+    Some(text) => text.to_string(),
+    None if source_specifier.scheme() == "file" => {
+      fs::read_to_string(source_specifier.to_file_path().ok()?).ok()?
+    }
+    None => return None,
+  };
+  Some(OriginalSource {
+    specifier: source_specifier,
+    text,
+    source_map,
+  })
+}
+
 fn collect_coverages(
   cli_options: &CliOptions,
   files: FileFlags,
@@ -440,10 +507,53 @@ fn collect_coverages(
   Ok(coverages)
 }
 
+/// Resolves the modules that coverage is reported for from the `"coverage"`
+/// section of the config file. The vendor and `node_modules` directories are
+/// always excluded.
+fn resolve_coverage_patterns(
+  cli_options: &CliOptions,
+) -> Result<FilePatterns, AnyError> {
+  let mut patterns =
+    FilePatterns::new_with_base(cli_options.initial_cwd().to_path_buf());
+  let maybe_config_file = cli_options
+    .start_dir
+    .maybe_deno_json()
+    .or_else(|| cli_options.workspace().root_deno_json());
+  if let Some(config_file) = maybe_config_file {
+    let config = read_coverage_config(config_file)?;
+    if let Ok(config_path) = config_file.specifier.to_file_path() {
+      let config_dir = config_path.parent().unwrap();
+      if !config.include.is_empty() {
+        patterns.include =
+          Some(PathOrPatternSet::from_include_relative_path_or_patterns(
+            config_dir,
+            &config.include,
+          )?);
+      }
+      patterns.exclude =
+        PathOrPatternSet::from_exclude_relative_path_or_patterns(
+          config_dir,
+          &config.exclude,
+        )?;
+    }
+  }
+  for dir in [
+    cli_options.vendor_dir_path(),
+    cli_options.node_modules_dir_path(),
+  ]
+  .into_iter()
+  .flatten()
+  {
+    patterns.exclude.push(PathOrPattern::Path(dir.clone()));
+  }
+  Ok(patterns)
+}
+
 fn filter_coverages(
   coverages: Vec<cdp::ScriptCoverage>,
   include: Vec<String>,
   exclude: Vec<String>,
+  coverage_patterns: &FilePatterns,
   npm_resolver: &dyn CliNpmResolver,
 ) -> Vec<cdp::ScriptCoverage> {
   let include: Vec<Regex> =
@@ -455,18 +565,23 @@ fn filter_coverages(
   coverages
     .into_iter()
     .filter(|e| {
+      let maybe_url = Url::parse(&e.url).ok();
       let is_internal = e.url.starts_with("ext:")
         || e.url.ends_with("__anonymous__")
         || e.url.ends_with("$deno$test.js")
         || e.url.ends_with(".snap")
         || is_supported_test_path(Path::new(e.url.as_str()))
-        || Url::parse(&e.url)
-          .ok()
-          .map(|url| npm_resolver.in_npm_package(&url))
+        || maybe_url
+          .as_ref()
+          .map(|url| npm_resolver.in_npm_package(url))
           .unwrap_or(false);
 
       let is_included = include.iter().any(|p| p.is_match(&e.url));
-      let is_excluded = exclude.iter().any(|p| p.is_match(&e.url));
+      let is_excluded = exclude.iter().any(|p| p.is_match(&e.url))
+        || maybe_url
+          .as_ref()
+          .map(|url| !coverage_patterns.matches_specifier(url))
+          .unwrap_or(false);
 
       (include.is_empty() || is_included) && !is_excluded && !is_internal
     })
@@ -501,10 +616,12 @@ pub async fn cover_files(
   if script_coverages.is_empty() {
     return Err(generic_error("No coverage files found"));
   }
+  let coverage_patterns = resolve_coverage_patterns(cli_options)?;
   let script_coverages = filter_coverages(
     script_coverages,
     coverage_flags.include,
     coverage_flags.exclude,
+    &coverage_patterns,
     npm_resolver.as_ref(),
   );
   if script_coverages.is_empty() {
@@ -586,22 +703,46 @@ pub async fn cover_files(
         unreachable!()
       }
     };
-    let runtime_code: String = match transpiled_code {
-      Some(code) => String::from_utf8(code)
-        .with_context(|| format!("Failed decoding {}", file.specifier))?,
-      None => original_source.to_string(),
+    let (runtime_code, source_map, maybe_original) = match transpiled_code {
+      Some(code) => {
+        let code = String::from_utf8(code)
+          .with_context(|| format!("Failed decoding {}", file.specifier))?;
+        let source_map = source_map_from_code(code.as_bytes());
+        (code, source_map, None)
+      }
+      // JavaScript that wasn't transpiled by Deno, like the build output of
+      // an npm package, is reported against the source it was generated from.
+      None => {
+        let maybe_original = resolve_original_source(
+          &file.specifier,
+          &original_source,
+          file_fetcher,
+        );
+        let source_map = maybe_original.as_ref().map(|o| o.source_map.clone());
+        (original_source.to_string(), source_map, maybe_original)
+      }
     };
-
-    let source_map = source_map_from_code(runtime_code.as_bytes());
-    let coverage_report = generate_coverage_report(
+    let mut coverage_report = generate_coverage_report(
       &script_coverage,
-      runtime_code.as_str().to_owned(),
+      runtime_code,
       &source_map,
       &out_mode,
     );
+    let report_text = match maybe_original {
+      Some(original) => {
+        if !coverage_patterns.matches_specifier(&original.specifier)
+          || npm_resolver.in_npm_package(&original.specifier)
+        {
+          continue;
+        }
+        coverage_report.url = original.specifier;
+        original.text
+      }
+      None => original_source.to_string(),
+    };
 
     if !coverage_report.found_lines.is_empty() {
-      reporter.report(&coverage_report, &original_source)?;
+      reporter.report(&coverage_report, &report_text)?;
     }
   }
 
@@ -4,6 +4,8 @@ use crate::args::DocFlags;
 use crate::args::DocHtmlFlag;
 use crate::args::DocSourceFileFlag;
 use crate::args::Flags;
+use crate::cache::LazyGraphSourceParser;
+use crate::cache::ParsedSourceCache;
 use crate::colors;
 use crate::display;
 use crate::factory::CliFactory;
@@ -11,6 +13,18 @@ use crate::graph_util::graph_exit_lock_errors;
 use crate::tsc::get_types_declaration_file_text;
 use crate::util::fs::collect_specifiers;
 use deno_ast::diagnostics::Diagnostic;
+use deno_ast::diagnostics::DiagnosticLevel;
+use deno_ast::diagnostics::DiagnosticLocation;
+use deno_ast::diagnostics::DiagnosticSnippet;
+use deno_ast::diagnostics::DiagnosticSnippetHighlight;
+use deno_ast::diagnostics::DiagnosticSnippetHighlightStyle;
+use deno_ast::diagnostics::DiagnosticSourcePos;
+use deno_ast::diagnostics::DiagnosticSourceRange;
+use deno_ast::swc::ast;
+use deno_ast::swc::common::comments::CommentKind;
+use deno_ast::swc::common::BytePos;
+use deno_ast::SourcePos;
+use deno_ast::SourceTextInfo;
 use deno_config::glob::FilePatterns;
 use deno_config::glob::PathOrPatternSet;
 use deno_core::anyhow::bail;
@@ -27,7 +41,9 @@ use deno_graph::ModuleSpecifier;
 use doc::html::ShortPath;
 use doc::DocDiagnostic;
 use indexmap::IndexMap;
+use std::borrow::Cow;
 use std::collections::BTreeMap;
+use std::collections::HashSet;
 use std::rc::Rc;
 use std::sync::Arc;
 
@@ -150,7 +166,16 @@ pub async fn doc(
 
       if doc_flags.lint {
         let diagnostics = doc_parser.take_diagnostics();
-        check_diagnostics(&diagnostics)?;
+        let link_diagnostics = check_js_doc_links(
+          &doc_flags,
+          &graph,
+          parsed_source_cache,
+          &doc_nodes_by_url,
+          &capturing_parser,
+          &analyzer,
+        )
+        .await?;
+        check_diagnostics(&diagnostics, &link_diagnostics)?;
       }
 
       doc_nodes_by_url
@@ -528,6 +553,12 @@ fn generate_docs_directory(
       .with_context(|| format!("Failed to write file {:?}", this_path))?;
   }
 
+  // Opt out of Jekyll processing so the site can be published as is to
+  // GitHub Pages, which would otherwise skip some of the generated files.
+  let nojekyll_path = path.join(".nojekyll");
+  std::fs::write(&nojekyll_path, "")
+    .with_context(|| format!("Failed to write file {:?}", nojekyll_path))?;
+
   log::info!(
     "{}",
     colors::green(format!(
@@ -563,20 +594,32 @@ fn print_docs_to_stdout(
     .map_err(AnyError::from)
 }
 
-fn check_diagnostics(diagnostics: &[DocDiagnostic]) -> Result<(), AnyError> {
+fn check_diagnostics(
+  diagnostics: &[DocDiagnostic],
+  link_diagnostics: &[BrokenLinkDiagnostic],
+) -> Result<(), AnyError> {
+  let diagnostics = diagnostics
+    .iter()
+    .map(|d| (&d.location, d.display().to_string()))
+    .chain(
+      link_diagnostics
+        .iter()
+        .map(|d| (&d.location, d.display().to_string())),
+    )
+    .collect::<Vec<_>>();
   if diagnostics.is_empty() {
     return Ok(());
   }
 
   // group by location then by line (sorted) then column (sorted)
   let mut diagnostic_groups = IndexMap::new();
-  for diagnostic in diagnostics {
+  for (location, diagnostic) in &diagnostics {
     diagnostic_groups
-      .entry(diagnostic.location.filename.clone())
+      .entry(location.filename.clone())
       .or_insert_with(BTreeMap::new)
-      .entry(diagnostic.location.line)
+      .entry(location.line)
       .or_insert_with(BTreeMap::new)
-      .entry(diagnostic.location.col)
+      .entry(location.col)
       .or_insert_with(Vec::new)
       .push(diagnostic);
   }
@@ -585,7 +628,7 @@ fn check_diagnostics(diagnostics: &[DocDiagnostic]) -> Result<(), AnyError> {
     for (_, diagnostics_by_col) in diagnostics_by_lc {
       for (_, diagnostics) in diagnostics_by_col {
         for diagnostic in diagnostics {
-          log::error!("{}\n", diagnostic.display());
+          log::error!("{}\n", diagnostic);
         }
       }
     }
@@ -596,3 +639,464 @@ fn check_diagnostics(diagnostics: &[DocDiagnostic]) -> Result<(), AnyError> {
     if diagnostics.len() == 1 { "" } else { "s" }
   );
 }
+
+/// Names of the ECMAScript globals, which aren't part of the built-in Deno
+/// types, that JSDoc links may refer to.
+const ES_GLOBALS: &[&str] = &[
+  "AggregateError",
+  "Array",
+  "ArrayBuffer",
+  "AsyncGenerator",
+  "AsyncGeneratorFunction",
+  "AsyncIterable",
+  "AsyncIterableIterator",
+  "AsyncIterator",
+  "Atomics",
+  "BigInt",
+  "BigInt64Array",
+  "BigUint64Array",
+  "Boolean",
+  "DataView",
+  "Date",
+  "Error",
+  "EvalError",
+  "FinalizationRegistry",
+  "Float32Array",
+  "Float64Array",
+  "Function",
+  "Generator",
+  "GeneratorFunction",
+  "Infinity",
+  "Int16Array",
+  "Int32Array",
+  "Int8Array",
+  "Intl",
+  "Iterable",
+  "IterableIterator",
+  "Iterator",
+  "JSON",
+  "Map",
+  "Math",
+  "NaN",
+  "Number",
+  "Object",
+  "Promise",
+  "PromiseLike",
+  "Proxy",
+  "RangeError",
+  "ReferenceError",
+  "Reflect",
+  "RegExp",
+  "Set",
+  "SharedArrayBuffer",
+  "String",
+  "Symbol",
+  "SyntaxError",
+  "TypeError",
+  "URIError",
+  "Uint16Array",
+  "Uint32Array",
+  "Uint8Array",
+  "Uint8ClampedArray",
+  "WeakMap",
+  "WeakRef",
+  "WeakSet",
+  "decodeURI",
+  "decodeURIComponent",
+  "encodeURI",
+  "encodeURIComponent",
+  "globalThis",
+  "isFinite",
+  "isNaN",
+  "parseFloat",
+  "parseInt",
+  "undefined",
+];
+
+/// A `{@link}` tag in a JSDoc comment whose target can't be resolved.
+struct BrokenLinkDiagnostic {
+  location: doc::Location,
+  len: usize,
+  target: String,
+  text_info: SourceTextInfo,
+}
+
+impl Diagnostic for BrokenLinkDiagnostic {
+  fn level(&self) -> DiagnosticLevel {
+    DiagnosticLevel::Error
+  }
+
+  fn code(&self) -> Cow<'_, str> {
+    Cow::Borrowed("broken-link")
+  }
+
+  fn message(&self) -> Cow<'_, str> {
+    Cow::Owned(format!(
+      "JSDoc link references unknown symbol '{}'",
+      self.target
+    ))
+  }
+
+  fn location(&self) -> DiagnosticLocation {
+    let specifier = ModuleSpecifier::parse(&self.location.filename).unwrap();
+    DiagnosticLocation::ModulePosition {
+      specifier: Cow::Owned(specifier),
+      source_pos: DiagnosticSourcePos::ByteIndex(self.location.byte_index),
+      text_info: Cow::Borrowed(&self.text_info),
+    }
+  }
+
+  fn snippet(&self) -> Option<DiagnosticSnippet<'_>> {
+    Some(DiagnosticSnippet {
+      source: Cow::Borrowed(&self.text_info),
+      highlights: vec![DiagnosticSnippetHighlight {
+        style: DiagnosticSnippetHighlightStyle::Error,
+        range: DiagnosticSourceRange {
+          start: DiagnosticSourcePos::ByteIndex(self.location.byte_index),
+          end: DiagnosticSourcePos::ByteIndex(
+            self.location.byte_index + self.len,
+          ),
+        },
+        description: None,
+      }],
+    })
+  }
+
+  fn hint(&self) -> Option<Cow<'_, str>> {
+    Some(Cow::Borrowed(
+      "check the spelling of the symbol or import it into the module",
+    ))
+  }
+
+  fn snippet_fixed(&self) -> Option<DiagnosticSnippet<'_>> {
+    None
+  }
+
+  fn info(&self) -> Cow<'_, [Cow<'_, str>]> {
+    Cow::Borrowed(&[])
+  }
+
+  fn docs_url(&self) -> Option<Cow<'_, str>> {
+    None
+  }
+}
+
+/// Checks that the `{@link}`, `{@linkcode}` and `{@linkplain}` tags in the
+/// JSDoc comments of the documented modules refer to symbols that are in
+/// scope, documented, or built-in.
+async fn check_js_doc_links(
+  doc_flags: &DocFlags,
+  graph: &deno_graph::ModuleGraph,
+  parsed_source_cache: &ParsedSourceCache,
+  doc_nodes_by_url: &IndexMap<ModuleSpecifier, Vec<doc::DocNode>>,
+  parser: &dyn ModuleParser,
+  analyzer: &dyn ModuleAnalyzer,
+) -> Result<Vec<BrokenLinkDiagnostic>, AnyError> {
+  let documented_names = doc_nodes_by_url
+    .values()
+    .flatten()
+    .map(|node| node.get_name())
+    .collect::<HashSet<_>>();
+  let source_parser = LazyGraphSourceParser::new(parsed_source_cache, graph);
+  let mut diagnostics = Vec::new();
+  for specifier in doc_nodes_by_url.keys() {
+    let Some(parsed_source) = source_parser.get_or_parse_source(specifier)?
+    else {
+      continue;
+    };
+    let module_names = top_level_names(parsed_source.program_ref());
+    let text_info = parsed_source.text_info_lazy();
+    for comment in parsed_source.comments().get_vec() {
+      if comment.kind != CommentKind::Block || !comment.text.starts_with('*') {
+        continue;
+      }
+      // skip past the leading `/*` of the comment
+      let comment_start = comment.span.lo + BytePos(2);
+      for link in parse_js_doc_links(&comment.text) {
+        let name = link.target.split(['.', '#']).next().unwrap();
+        if documented_names.contains(name) || module_names.contains(name) {
+          continue;
+        }
+        let pos = SourcePos::unsafely_from_byte_pos(
+          comment_start + BytePos(link.offset as u32),
+        );
+        let line_and_column = text_info.line_and_column_display(pos);
+        diagnostics.push(BrokenLinkDiagnostic {
+          location: doc::Location {
+            filename: specifier.as_str().into(),
+            line: line_and_column.line_number,
+            col: line_and_column.column_number - 1,
+            byte_index: pos.as_byte_index(text_info.range().start),
+          },
+          len: link.len,
+          target: link.target.to_string(),
+          text_info: text_info.clone(),
+        });
+      }
+    }
+  }
+
+  if !diagnostics.is_empty() {
+    // only load the built-in types when they're needed to resolve a link
+    let builtin_nodes =
+      generate_doc_nodes_for_builtin_types(doc_flags.clone(), parser, analyzer)
+        .await?;
+    let builtin_names = builtin_nodes
+      .values()
+      .flatten()
+      .map(|node| node.get_name())
+      .chain(ES_GLOBALS.iter().copied())
+      .collect::<HashSet<_>>();
+    diagnostics.retain(|diagnostic| {
+      let name = diagnostic.target.split(['.', '#']).next().unwrap();
+      !builtin_names.contains(name)
+    });
+  }
+
+  Ok(diagnostics)
+}
+
+#[derive(Debug, PartialEq)]
+struct JsDocLink<'a> {
+  /// The byte offset of the tag in the comment text.
+  offset: usize,
+  /// The byte length of the tag.
+  len: usize,
+  target: &'a str,
+}
+
+/// Parses the links to symbols out of the text of a JSDoc comment, ignoring
+/// links to URLs.
+fn parse_js_doc_links(text: &str) -> Vec<JsDocLink> {
+  let mut links = Vec::new();
+  let mut search_start = 0;
+  while let Some(index) = text[search_start..].find("{@link") {
+    let offset = search_start + index;
+    let tag = &text[offset + 1..];
+    search_start = offset + 1;
+    let Some(end) = tag.find('}') else {
+      break;
+    };
+    let tag = &tag[..end];
+    let Some(content) = tag
+      .strip_prefix("@linkcode")
+      .or_else(|| tag.strip_prefix("@linkplain"))
+      .or_else(|| tag.strip_prefix("@link"))
+    else {
+      continue;
+    };
+    if !content.is_empty() && !content.starts_with(char::is_whitespace) {
+      // some other tag, like `{@linkfoo}`
+      continue;
+    }
+    let target = content
+      .trim_start()
+      .split(|c: char| c.is_whitespace() || c == '|')
+      .next()
+      .unwrap_or_default();
+    let is_symbol = target
+      .chars()
+      .next()
+      .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$');
+    if !is_symbol || target.contains(':') {
+      continue;
+    }
+    links.push(JsDocLink {
+      offset,
+      len: end + 2,
+      target,
+    });
+  }
+  links
+}
+
+/// Collects the names declared or imported at the top level of a module.
+fn top_level_names(program: &ast::Program) -> HashSet<&str> {
+  fn add_decl_names<'a>(decl: &'a ast::Decl, names: &mut HashSet<&'a str>) {
+    match decl {
+      ast::Decl::Class(decl) => {
+        names.insert(&decl.ident.sym);
+      }
+      ast::Decl::Fn(decl) => {
+        names.insert(&decl.ident.sym);
+      }
+      ast::Decl::Var(decl) => {
+        for declarator in &decl.decls {
+          add_pat_names(&declarator.name, names);
+        }
+      }
+      ast::Decl::TsInterface(decl) => {
+        names.insert(&decl.id.sym);
+      }
+      ast::Decl::TsTypeAlias(decl) => {
+        names.insert(&decl.id.sym);
+      }
+      ast::Decl::TsEnum(decl) => {
+        names.insert(&decl.id.sym);
+      }
+      ast::Decl::TsModule(decl) => {
+        if let ast::TsModuleName::Ident(ident) = &decl.id {
+          names.insert(&ident.sym);
+        }
+      }
+      ast::Decl::Using(_) => {}
+    }
+  }
+
+  fn add_pat_names<'a>(pat: &'a ast::Pat, names: &mut HashSet<&'a str>) {
+    match pat {
+      ast::Pat::Ident(ident) => {
+        names.insert(&ident.id.sym);
+      }
+      ast::Pat::Array(array) => {
+        for elem in array.elems.iter().flatten() {
+          add_pat_names(elem, names);
+        }
+      }
+      ast::Pat::Rest(rest) => add_pat_names(&rest.arg, names),
+      ast::Pat::Object(object) => {
+        for prop in &object.props {
+          match prop {
+            ast::ObjectPatProp::KeyValue(key_value) => {
+              add_pat_names(&key_value.value, names)
+            }
+            ast::ObjectPatProp::Assign(assign) => {
+              names.insert(&assign.key.sym);
+            }
+            ast::ObjectPatProp::Rest(rest) => add_pat_names(&rest.arg, names),
+          }
+        }
+      }
+      ast::Pat::Assign(assign) => add_pat_names(&assign.left, names),
+      ast::Pat::Invalid(_) | ast::Pat::Expr(_) => {}
+    }
+  }
+
+  let mut names = HashSet::new();
+  match program {
+    ast::Program::Module(module) => {
+      for item in &module.body {
+        match item {
+          ast::ModuleItem::ModuleDecl(ast::ModuleDecl::Import(import)) => {
+            for specifier in &import.specifiers {
+              names.insert(match specifier {
+                ast::ImportSpecifier::Named(s) => &*s.local.sym,
+                ast::ImportSpecifier::Default(s) => &*s.local.sym,
+                ast::ImportSpecifier::Namespace(s) => &*s.local.sym,
+              });
+            }
+          }
+          ast::ModuleItem::ModuleDecl(ast::ModuleDecl::ExportDecl(export)) => {
+            add_decl_names(&export.decl, &mut names);
+          }
+          ast::ModuleItem::ModuleDecl(ast::ModuleDecl::ExportNamed(export)) => {
+            // the names under which the module exports them, as the local
+            // names are declared elsewhere in it or in the module they're
+            // re-exported from
+            for specifier in &export.specifiers {
+              let name = match specifier {
+                ast::ExportSpecifier::Named(s) => {
+                  s.exported.as_ref().unwrap_or(&s.orig)
+                }
+                ast::ExportSpecifier::Namespace(s) => &s.name,
+                ast::ExportSpecifier::Default(s) => {
+                  names.insert(&s.exported.sym);
+                  continue;
+                }
+              };
+              if let ast::ModuleExportName::Ident(ident) = name {
+                names.insert(&ident.sym);
+              }
+            }
+          }
+          ast::ModuleItem::ModuleDecl(ast::ModuleDecl::ExportDefaultDecl(
+            export,
+          )) => {
+            let ident = match &export.decl {
+              ast::DefaultDecl::Class(expr) => expr.ident.as_ref(),
+              ast::DefaultDecl::Fn(expr) => expr.ident.as_ref(),
+              ast::DefaultDecl::TsInterfaceDecl(decl) => Some(&decl.id),
+            };
+            if let Some(ident) = ident {
+              names.insert(&ident.sym);
+            }
+          }
+          ast::ModuleItem::ModuleDecl(ast::ModuleDecl::TsImportEquals(
+            import,
+          )) => {
+            names.insert(&import.id.sym);
+          }
+          ast::ModuleItem::Stmt(ast::Stmt::Decl(decl)) => {
+            add_decl_names(decl, &mut names);
+          }
+          _ => {}
+        }
+      }
+    }
+    ast::Program::Script(script) => {
+      for stmt in &script.body {
+        if let ast::Stmt::Decl(decl) = stmt {
+          add_decl_names(decl, &mut names);
+        }
+      }
+    }
+  }
+  names
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_js_doc_links() {
+    assert_eq!(
+      parse_js_doc_links(
+        "* See {@link Foo.bar} and {@linkcode Baz | the baz},\n\
+         * {@link https://deno.land} or {@linkplain #anchor}. {@linkfoo Qux}"
+      ),
+      vec![
+        JsDocLink {
+          offset: 6,
+          len: 15,
+          target: "Foo.bar",
+        },
+        JsDocLink {
+          offset: 26,
+          len: 25,
+          target: "Baz",
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn test_top_level_names() {
+    let parsed_source = deno_ast::parse_module(deno_ast::ParseParams {
+      specifier: ModuleSpecifier::parse("file:///mod.ts").unwrap(),
+      text: r#"
+        import { a as b } from "./a.ts";
+        const { c, d: [e, ...f], ...g } = {};
+        let [h = 1] = [];
+        function i() {}
+        export { i as j, b as "k" };
+        export { l as m, n } from "./l.ts";
+        export * as o from "./o.ts";
+      "#
+      .into(),
+      media_type: deno_ast::MediaType::TypeScript,
+      capture_tokens: false,
+      scope_analysis: false,
+      maybe_syntax: None,
+    })
+    .unwrap();
+    let mut names = top_level_names(parsed_source.program_ref())
+      .into_iter()
+      .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(
+      names,
+      ["b", "c", "e", "f", "g", "h", "i", "j", "m", "n", "o"]
+    );
+  }
+}
@@ -7,6 +7,7 @@
 //! the future it can be easily extended to provide
 //! the same functions as ops available in JS runtime.
 
+use crate::args::deno_json::read_fmt_sort_imports_config;
 use crate::args::CliOptions;
 use crate::args::Flags;
 use crate::args::FmtFlags;
@@ -22,6 +23,8 @@ use crate::util::file_watcher;
 use crate::util::fs::canonicalize_path;
 use crate::util::path::get_extension;
 use async_trait::async_trait;
+use deno_ast::MediaType;
+use deno_ast::ModuleSpecifier;
 use deno_ast::ParsedSource;
 use deno_config::glob::FileCollector;
 use deno_config::glob::FilePatterns;
@@ -37,6 +40,7 @@ use log::debug;
 use log::info;
 use log::warn;
 use std::borrow::Cow;
+use std::collections::HashSet;
 use std::fs;
 use std::io::stdin;
 use std::io::stdout;
@@ -50,6 +54,10 @@ use std::sync::Arc;
 
 use crate::cache::IncrementalCache;
 
+mod plugins;
+
+use plugins::FmtPlugins;
+
 /// Format JavaScript/TypeScript files.
 pub async fn format(
   flags: Arc<Flags>,
@@ -62,6 +70,7 @@ pub async fn format(
       .to_fmt_config(FilePatterns::new_with_base(start_dir.dir_path()))?;
     let fmt_options = FmtOptions::resolve(
       fmt_config,
+      read_fmt_sort_imports_config(start_dir)?,
       cli_options.resolve_config_unstable_fmt_options(),
       &fmt_flags,
     );
@@ -86,8 +95,15 @@ pub async fn format(
           let factory = CliFactory::from_flags(flags);
           let cli_options = factory.cli_options()?;
           let caches = factory.caches()?;
+          let plugins = Arc::new(
+            FmtPlugins::resolve(cli_options, factory.file_fetcher()?).await?,
+          );
           let mut paths_with_options_batches =
-            resolve_paths_with_options_batches(cli_options, &fmt_flags)?;
+            resolve_paths_with_options_batches(
+              cli_options,
+              &fmt_flags,
+              &plugins,
+            )?;
 
           for paths_with_options in &mut paths_with_options_batches {
             let _ = watcher_communicator
@@ -125,6 +141,7 @@ pub async fn format(
             cli_options,
             &fmt_flags,
             paths_with_options_batches,
+            plugins,
           )
           .await?;
 
@@ -137,10 +154,19 @@ pub async fn format(
     let factory = CliFactory::from_flags(flags);
     let cli_options = factory.cli_options()?;
     let caches = factory.caches()?;
+    let plugins = Arc::new(
+      FmtPlugins::resolve(cli_options, factory.file_fetcher()?).await?,
+    );
     let paths_with_options_batches =
-      resolve_paths_with_options_batches(cli_options, &fmt_flags)?;
-    format_files(caches, cli_options, &fmt_flags, paths_with_options_batches)
-      .await?;
+      resolve_paths_with_options_batches(cli_options, &fmt_flags, &plugins)?;
+    format_files(
+      caches,
+      cli_options,
+      &fmt_flags,
+      paths_with_options_batches,
+      plugins,
+    )
+    .await?;
   }
 
   Ok(())
@@ -155,14 +181,18 @@ struct PathsWithOptions {
 fn resolve_paths_with_options_batches(
   cli_options: &CliOptions,
   fmt_flags: &FmtFlags,
+  plugins: &FmtPlugins,
 ) -> Result<Vec<PathsWithOptions>, AnyError> {
   let members_fmt_options =
     cli_options.resolve_fmt_options_for_members(fmt_flags)?;
   let mut paths_with_options_batches =
     Vec::with_capacity(members_fmt_options.len());
   for (_ctx, member_fmt_options) in members_fmt_options {
-    let files =
-      collect_fmt_files(cli_options, member_fmt_options.files.clone())?;
+    let files = collect_fmt_files(
+      cli_options,
+      member_fmt_options.files.clone(),
+      plugins,
+    )?;
     if !files.is_empty() {
       paths_with_options_batches.push(PathsWithOptions {
         base: member_fmt_options.files.base.clone(),
@@ -182,6 +212,7 @@ async fn format_files(
   cli_options: &Arc<CliOptions>,
   fmt_flags: &FmtFlags,
   paths_with_options_batches: Vec<PathsWithOptions>,
+  plugins: Arc<FmtPlugins>,
 ) -> Result<(), AnyError> {
   let formatter: Box<dyn Formatter> = if fmt_flags.check {
     Box::new(CheckFormatter::default())
@@ -198,16 +229,24 @@ async fn format_files(
     let paths = paths_with_options.paths;
     let incremental_cache = Arc::new(IncrementalCache::new(
       caches.fmt_incremental_cache_db(),
-      &(&fmt_options.options, &fmt_options.unstable), // cache key
+      // cache key
+      &(
+        &fmt_options.options,
+        fmt_options.sort_imports,
+        &fmt_options.unstable,
+        plugins.cache_key(),
+      ),
       &paths,
     ));
     formatter
       .handle_files(
         paths,
         fmt_options.options,
+        fmt_options.sort_imports,
         fmt_options.unstable,
         incremental_cache.clone(),
         cli_options.ext_flag().clone(),
+        plugins.clone(),
       )
       .await?;
     incremental_cache.wait_completion().await;
@@ -219,11 +258,13 @@ async fn format_files(
 fn collect_fmt_files(
   cli_options: &CliOptions,
   files: FilePatterns,
+  plugins: &FmtPlugins,
 ) -> Result<Vec<PathBuf>, AnyError> {
   FileCollector::new(|e| {
     cli_options.ext_flag().as_ref().is_some_and(|ext| {
       is_supported_ext_fmt(Path::new(&format!("placeholder.{ext}")))
     }) || is_supported_ext_fmt(e.path)
+      || plugins.matches(e.path)
       || e.path.extension().is_none()
   })
   .ignore_git_folder()
@@ -462,6 +503,7 @@ pub fn format_file(
   file_path: &Path,
   file_text: &str,
   fmt_options: &FmtOptionsConfig,
+  sort_imports: bool,
   unstable_options: &UnstableFmtOptions,
   ext: Option<String>,
 ) -> Result<Option<String>, AnyError> {
@@ -510,18 +552,295 @@ pub fn format_file(
     "ipynb" => dprint_plugin_jupyter::format_text(
       file_text,
       |file_path: &Path, file_text: String| {
-        format_file(file_path, &file_text, fmt_options, unstable_options, None)
+        format_file(
+          file_path,
+          &file_text,
+          fmt_options,
+          sort_imports,
+          unstable_options,
+          None,
+        )
       },
     ),
     _ => {
-      let config = get_resolved_typescript_config(fmt_options);
-      dprint_plugin_typescript::format_text(
-        file_path,
-        Some(&ext),
-        file_text.to_string(),
-        &config,
-      )
+      let mut config = get_resolved_typescript_config(fmt_options);
+      if !sort_imports {
+        return dprint_plugin_typescript::format_text(
+          file_path,
+          Some(&ext),
+          file_text.to_string(),
+          &config,
+        );
+      }
+      config.import_declaration_sort_named_imports =
+        dprint_plugin_typescript::configuration::SortOrder::CaseInsensitive;
+      match sort_imports(file_text, &ext) {
+        Some(sorted_text) => {
+          let maybe_formatted = dprint_plugin_typescript::format_text(
+            file_path,
+            Some(&ext),
+            sorted_text.clone(),
+            &config,
+          )?;
+          Ok(Some(maybe_formatted.unwrap_or(sorted_text)))
+        }
+        None => dprint_plugin_typescript::format_text(
+          file_path,
+          Some(&ext),
+          file_text.to_string(),
+          &config,
+        ),
+      }
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum ImportGroup {
+  /// Node built-ins and the Deno standard library.
+  Std,
+  /// npm, jsr, remote and bare specifiers.
+  External,
+  /// Relative and absolute file specifiers.
+  Relative,
+}
+
+impl ImportGroup {
+  fn from_specifier(specifier: &str) -> Self {
+    if specifier.starts_with("node:")
+      || specifier.starts_with("jsr:@std/")
+      || specifier.starts_with("@std/")
+      || specifier.starts_with("https://deno.land/std")
+    {
+      ImportGroup::Std
+    } else if specifier.starts_with("./")
+      || specifier.starts_with("../")
+      || specifier.starts_with('/')
+      || specifier.starts_with("file:")
+    {
+      ImportGroup::Relative
+    } else {
+      ImportGroup::External
+    }
+  }
+}
+
+struct SortableImport<'a> {
+  group: ImportGroup,
+  specifier: &'a str,
+  text: Cow<'a, str>,
+}
+
+/// Sorts and groups the import declarations at the top of a module.
+///
+/// Imports are ordered by specifier within three groups (std, external
+/// packages and relative), separated by a blank line. Duplicate declarations
+/// and duplicate named specifiers are removed. Side effect imports are never
+/// reordered since their evaluation order may matter, so they split the
+/// imports into independently sorted runs.
+///
+/// The comments above an import and the ones following it on the same line
+/// move with it, except for the comments above the first import, which stay
+/// at the top of the file as its header.
+///
+/// Returns `None` when there's nothing to change or when the file doesn't
+/// parse.
+pub fn sort_imports(file_text: &str, ext: &str) -> Option<String> {
+  use deno_ast::swc::ast::ImportSpecifier;
+  use deno_ast::swc::ast::ModuleDecl;
+  use deno_ast::swc::ast::ModuleItem;
+  use deno_ast::SourceRangedForSpanned;
+
+  let specifier =
+    ModuleSpecifier::parse(&format!("file:///deno_fmt_stdin.{ext}")).ok()?;
+  let media_type = MediaType::from_specifier(&specifier);
+  let parsed_source = deno_ast::parse_module(deno_ast::ParseParams {
+    specifier,
+    text: file_text.into(),
+    media_type,
+    capture_tokens: false,
+    maybe_syntax: None,
+    scope_analysis: false,
+  })
+  .ok()?;
+  let ignore_file = parsed_source.get_leading_comments().is_some_and(|c| {
+    c.iter()
+      .any(|comment| comment.text.trim() == "deno-fmt-ignore-file")
+  });
+  if ignore_file {
+    return None;
+  }
+  let module = parsed_source.program_ref().as_module()?;
+  let text_info = parsed_source.text_info_lazy();
+  let file_start = text_info.range().start;
+  let text = text_info.text_str();
+
+  let decls = module
+    .body
+    .iter()
+    .map_while(|item| match item {
+      ModuleItem::ModuleDecl(ModuleDecl::Import(decl)) => Some(decl),
+      _ => None,
+    })
+    .collect::<Vec<_>>();
+  if decls.is_empty() {
+    return None;
+  }
+  let mut comments = parsed_source
+    .comments()
+    .get_vec()
+    .iter()
+    .map(|comment| {
+      comment.start().as_byte_index(file_start)
+        ..comment.end().as_byte_index(file_start)
+    })
+    .collect::<Vec<_>>();
+  comments.sort_by_key(|comment| comment.start);
+
+  // the text of each import with its comments
+  let mut units = Vec::with_capacity(decls.len());
+  let mut previous_end = 0;
+  for (i, decl) in decls.into_iter().enumerate() {
+    let decl_start = decl.start().as_byte_index(file_start);
+    let decl_end = decl.end().as_byte_index(file_start);
+    let start = if i == 0 {
+      decl_start
+    } else {
+      comments
+        .iter()
+        .find(|c| c.start >= previous_end && c.end <= decl_start)
+        .map(|c| c.start)
+        .unwrap_or(decl_start)
+    };
+    let mut end = decl_end;
+    for comment in comments.iter().filter(|c| c.start >= decl_end) {
+      if text[end..comment.start].contains('\n') {
+        break;
+      }
+      end = comment.end;
+    }
+    let has_inner_comments = comments
+      .iter()
+      .any(|c| c.start > decl_start && c.start < decl_end);
+    units.push((decl, start..end, has_inner_comments));
+    previous_end = end;
+  }
+  let region_start = units.first()?.1.start;
+  let region_end = units.last()?.1.end;
+
+  let mut seen = HashSet::new();
+  let mut blocks: Vec<String> = Vec::new();
+  let mut run: Vec<SortableImport> = Vec::new();
+  let flush_run = |run: &mut Vec<SortableImport>, blocks: &mut Vec<String>| {
+    run.sort_by(|a, b| {
+      a.group
+        .cmp(&b.group)
+        .then_with(|| {
+          a.specifier.to_lowercase().cmp(&b.specifier.to_lowercase())
+        })
+        .then_with(|| a.specifier.cmp(b.specifier))
+    });
+    let mut current: Option<(ImportGroup, String)> = None;
+    for import in run.drain(..) {
+      match &mut current {
+        Some((group, block)) if *group == import.group => {
+          block.push('\n');
+          block.push_str(&import.text);
+        }
+        _ => {
+          if let Some((_, block)) = current.take() {
+            blocks.push(block);
+          }
+          current = Some((import.group, import.text.into_owned()));
+        }
+      }
+    }
+    if let Some((_, block)) = current {
+      blocks.push(block);
+    }
+  };
+
+  for (decl, range, has_inner_comments) in units {
+    let decl_text = decl.text_fast(text_info);
+    let unit_text = &text[range.clone()];
+    let key = decl_text.split_whitespace().collect::<Vec<_>>().join(" ");
+    // a duplicate with comments is kept so they aren't lost
+    if !seen.insert(key) && !has_inner_comments && unit_text == decl_text {
+      continue;
+    }
+    if decl.specifiers.is_empty() {
+      // side effect import, keep it in place
+      flush_run(&mut run, &mut blocks);
+      blocks.push(unit_text.to_string());
+      continue;
     }
+
+    let named = decl
+      .specifiers
+      .iter()
+      .filter(|s| matches!(s, ImportSpecifier::Named(_)))
+      .collect::<Vec<_>>();
+    let mut named_seen = HashSet::new();
+    let unique_named = named
+      .iter()
+      .map(|s| s.text_fast(text_info))
+      .filter(|s| named_seen.insert(*s))
+      .collect::<Vec<_>>();
+    // the specifiers are only deduplicated when that can't drop comments
+    let text = if unique_named.len() == named.len() || has_inner_comments {
+      Cow::Borrowed(unit_text)
+    } else {
+      let named_start = named.first()?.start().as_byte_index(file_start);
+      let named_end = named.last()?.end().as_byte_index(file_start);
+      Cow::Owned(format!(
+        "{}{}{}",
+        &text[range.start..named_start],
+        unique_named.join(", "),
+        &text[named_end..range.end],
+      ))
+    };
+    run.push(SortableImport {
+      group: ImportGroup::from_specifier(&decl.src.value),
+      specifier: &decl.src.value,
+      text,
+    });
+  }
+  flush_run(&mut run, &mut blocks);
+
+  let sorted_text = format!(
+    "{}{}{}",
+    &text[..region_start],
+    blocks.join("\n\n"),
+    &text[region_end..],
+  );
+  if sorted_text == file_text {
+    None
+  } else {
+    Some(sorted_text)
+  }
+}
+
+/// Formats a file with the first formatter plugin that handles it, falling
+/// back to the built-in formatters.
+fn format_file_with_plugins(
+  plugins: &FmtPlugins,
+  file_path: &Path,
+  file_text: &str,
+  fmt_options: &FmtOptionsConfig,
+  sort_imports: bool,
+  unstable_options: &UnstableFmtOptions,
+  ext: Option<String>,
+) -> Result<Option<String>, AnyError> {
+  match plugins.format_file(file_path, file_text, fmt_options) {
+    Some(result) => result,
+    None => format_file(
+      file_path,
+      file_text,
+      fmt_options,
+      sort_imports,
+      unstable_options,
+      ext,
+    ),
   }
 }
 
@@ -541,9 +860,11 @@ trait Formatter {
     &self,
     paths: Vec<PathBuf>,
     fmt_options: FmtOptionsConfig,
+    sort_imports: bool,
     unstable_options: UnstableFmtOptions,
     incremental_cache: Arc<IncrementalCache>,
     ext: Option<String>,
+    plugins: Arc<FmtPlugins>,
   ) -> Result<(), AnyError>;
 
   fn finish(&self) -> Result<(), AnyError>;
@@ -561,9 +882,11 @@ impl Formatter for CheckFormatter {
     &self,
     paths: Vec<PathBuf>,
     fmt_options: FmtOptionsConfig,
+    sort_imports: bool,
     unstable_options: UnstableFmtOptions,
     incremental_cache: Arc<IncrementalCache>,
     ext: Option<String>,
+    plugins: Arc<FmtPlugins>,
   ) -> Result<(), AnyError> {
     // prevent threads outputting at the same time
     let output_lock = Arc::new(Mutex::new(0));
@@ -580,10 +903,12 @@ impl Formatter for CheckFormatter {
           return Ok(());
         }
 
-        match format_file(
+        match format_file_with_plugins(
+          &plugins,
           &file_path,
           &file_text,
           &fmt_options,
+          sort_imports,
           &unstable_options,
           ext.clone(),
         ) {
@@ -661,9 +986,11 @@ impl Formatter for RealFormatter {
     &self,
     paths: Vec<PathBuf>,
     fmt_options: FmtOptionsConfig,
+    sort_imports: bool,
     unstable_options: UnstableFmtOptions,
     incremental_cache: Arc<IncrementalCache>,
     ext: Option<String>,
+    plugins: Arc<FmtPlugins>,
   ) -> Result<(), AnyError> {
     let output_lock = Arc::new(Mutex::new(0)); // prevent threads outputting at the same time
 
@@ -683,10 +1010,12 @@ impl Formatter for RealFormatter {
           &file_path,
           &file_contents.text,
           |file_path, file_text| {
-            format_file(
+            format_file_with_plugins(
+              &plugins,
               file_path,
               file_text,
               &fmt_options,
+              sort_imports,
               &unstable_options,
               ext.clone(),
             )
@@ -814,6 +1143,7 @@ fn format_stdin(
     &file_path,
     &source,
     &fmt_options.options,
+    fmt_options.sort_imports,
     &fmt_options.unstable,
     None,
   )?;
@@ -1296,6 +1626,7 @@ mod test {
         single_quote: Some(true),
         ..Default::default()
       },
+      false,
       &UnstableFmtOptions::default(),
       None,
     )
@@ -1307,4 +1638,78 @@ mod test {
       "console.log(\"there's\");\nconsole.log('hi');\nconsole.log('bye');\n",
     );
   }
+
+  #[test]
+  fn test_sort_imports() {
+    let file_text = format_file(
+      &PathBuf::from("test.ts"),
+      concat!(
+        "import { b, a, b } from \"./b.ts\";\n",
+        "import { z } from \"npm:zod\";\n",
+        "import { join } from \"jsr:@std/path\";\n",
+        "import { z } from \"npm:zod\";\n",
+        "import \"./polyfill.ts\";\n",
+        "import { c } from \"../c.ts\";\n",
+        "\n",
+        "console.log(a, b, c, join, z);\n",
+      ),
+      &FmtOptionsConfig::default(),
+      true,
+      &UnstableFmtOptions::default(),
+      None,
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(
+      file_text,
+      concat!(
+        "import { join } from \"jsr:@std/path\";\n",
+        "\n",
+        "import { z } from \"npm:zod\";\n",
+        "\n",
+        "import { a, b } from \"./b.ts\";\n",
+        "\n",
+        "import \"./polyfill.ts\";\n",
+        "\n",
+        "import { c } from \"../c.ts\";\n",
+        "\n",
+        "console.log(a, b, c, join, z);\n",
+      ),
+    );
+  }
+
+  #[test]
+  fn test_sort_imports_keeps_comments() {
+    let file_text = concat!(
+      "// Copyright header\n",
+      "import { d } from \"./d.ts\";\n",
+      "// about c\n",
+      "import { c } from \"./c.ts\"; // trailing c\n",
+      "import {\n",
+      "  b, // first b\n",
+      "  b,\n",
+      "} from \"./b.ts\";\n",
+      "/* about a */\n",
+      "import { a } from \"./a.ts\";\n",
+      "\n",
+      "console.log(a, b, c, d);\n",
+    );
+    assert_eq!(
+      sort_imports(file_text, "ts").unwrap(),
+      concat!(
+        "// Copyright header\n",
+        "/* about a */\n",
+        "import { a } from \"./a.ts\";\n",
+        "import {\n",
+        "  b, // first b\n",
+        "  b,\n",
+        "} from \"./b.ts\";\n",
+        "// about c\n",
+        "import { c } from \"./c.ts\"; // trailing c\n",
+        "import { d } from \"./d.ts\";\n",
+        "\n",
+        "console.log(a, b, c, d);\n",
+      ),
+    );
+  }
 }
@@ -0,0 +1,124 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+// Host side of the dprint Wasm plugin protocol (schema version 4).
+//
+// This script is executed in a bare isolate that only has access to the
+// plugin's Wasm bytes, so plugins can't reach the file system or network.
+
+((globalThis) => {
+  const core = globalThis.Deno.core;
+  const { op_fmt_plugin_wasm_bytes } = core.ops;
+
+  let exports;
+  let hostBuffer = new Uint8Array(0);
+  const configIds = new Map();
+
+  function memoryView(ptr, length) {
+    return new Uint8Array(exports.memory.buffer, ptr, length);
+  }
+
+  function writeSharedString(text) {
+    const bytes = core.encode(text);
+    const ptr = exports.clear_shared_bytes(bytes.length);
+    memoryView(ptr, bytes.length).set(bytes);
+  }
+
+  function readSharedString(length) {
+    const ptr = exports.get_shared_bytes_ptr();
+    return core.decode(memoryView(ptr, length).slice());
+  }
+
+  function readSharedJson(length) {
+    return JSON.parse(readSharedString(length));
+  }
+
+  function createImports(module) {
+    const host = {
+      host_has_cancelled: () => 0,
+      host_write_buffer: (ptr) => {
+        memoryView(ptr, hostBuffer.length).set(hostBuffer);
+      },
+      // formatting embedded code with other plugins isn't supported, so
+      // always report that the embedded text didn't change
+      host_format: () => 0,
+      host_get_formatted_text: () => {
+        hostBuffer = new Uint8Array(0);
+        return 0;
+      },
+      host_get_error_text: () => {
+        hostBuffer = new Uint8Array(0);
+        return 0;
+      },
+    };
+    const imports = {};
+    for (const { module: name, name: field, kind } of WebAssembly.Module
+      .imports(module)) {
+      if (kind !== "function") {
+        throw new Error(`Unsupported plugin import: ${name}.${field}`);
+      }
+      imports[name] ??= {};
+      imports[name][field] = host[field] ?? (() => 0);
+    }
+    return imports;
+  }
+
+  function getConfigId(config) {
+    const key = JSON.stringify(config);
+    let id = configIds.get(key);
+    if (id === undefined) {
+      id = configIds.size + 1;
+      writeSharedString(key);
+      exports.register_config(id);
+      const diagnostics = readSharedJson(exports.get_config_diagnostics(id));
+      if (diagnostics.length > 0) {
+        throw new Error(
+          `Invalid configuration: ${
+            diagnostics.map((d) => d.message).join(", ")
+          }`,
+        );
+      }
+      configIds.set(key, id);
+    }
+    return id;
+  }
+
+  globalThis.initPlugin = (config) => {
+    const module = new WebAssembly.Module(op_fmt_plugin_wasm_bytes());
+    const isV4 = WebAssembly.Module.exports(module)
+      .some((e) => e.name === "dprint_plugin_version_4");
+    if (!isV4) {
+      throw new Error(
+        "Unsupported plugin. Only dprint Wasm plugins using schema version 4 are supported.",
+      );
+    }
+    exports = new WebAssembly.Instance(module, createImports(module)).exports;
+    const configId = getConfigId(config);
+    return {
+      info: readSharedJson(exports.get_plugin_info()),
+      fileMatching: readSharedJson(exports.get_config_file_matching(configId)),
+    };
+  };
+
+  globalThis.formatText = ({ filePath, fileText, config }) => {
+    const configId = getConfigId(config);
+    writeSharedString(filePath);
+    exports.set_file_path();
+    writeSharedString("{}");
+    exports.set_override_config();
+    writeSharedString(fileText);
+    switch (exports.format(configId)) {
+      case 0:
+        return { kind: "unchanged" };
+      case 1:
+        return {
+          kind: "changed",
+          text: readSharedString(exports.get_formatted_text()),
+        };
+      default:
+        return {
+          kind: "error",
+          message: readSharedString(exports.get_error_text()),
+        };
+    }
+  };
+})(globalThis);
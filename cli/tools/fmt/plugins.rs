@@ -0,0 +1,337 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Formatting with third party [dprint](https://dprint.dev) Wasm plugins
+//! declared in the config file, for example:
+//!
+//! ```jsonc
+//! {
+//!   "plugins": {
+//!     "fmt": ["https://plugins.dprint.dev/toml-0.6.2.wasm"]
+//!   }
+//! }
+//! ```
+//!
+//! Each plugin runs on its own thread in a bare `JsRuntime` that doesn't
+//! expose any Deno APIs, so a plugin only ever sees the text it formats.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+use deno_core::anyhow::anyhow;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::located_script_name;
+use deno_core::op2;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_core::serde_v8;
+use deno_core::v8;
+use deno_core::JsRuntime;
+use deno_core::ModuleSpecifier;
+use deno_core::OpState;
+use deno_core::RuntimeOptions;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::args::deno_json::read_plugins_config;
+use crate::args::CliOptions;
+use crate::args::FmtOptionsConfig;
+use crate::file_fetcher::FileFetcher;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PluginInfo {
+  name: String,
+  version: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct FileMatchingInfo {
+  file_extensions: Vec<String>,
+  file_names: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InitResponse {
+  info: PluginInfo,
+  #[serde(rename = "fileMatching")]
+  file_matching: FileMatchingInfo,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+enum FormatResponse {
+  Unchanged,
+  Changed { text: String },
+  Error { message: String },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FormatRequest<'a> {
+  file_path: &'a str,
+  file_text: &'a str,
+  config: &'a serde_json::Value,
+}
+
+struct PluginWasmBytes(Vec<u8>);
+
+#[op2]
+#[buffer]
+fn op_fmt_plugin_wasm_bytes(state: &mut OpState) -> Vec<u8> {
+  state.take::<PluginWasmBytes>().0
+}
+
+deno_core::extension!(deno_fmt_plugin,
+  ops = [op_fmt_plugin_wasm_bytes],
+  options = {
+    wasm_bytes: Vec<u8>,
+  },
+  state = |state, options| {
+    state.put(PluginWasmBytes(options.wasm_bytes));
+  },
+);
+
+struct PluginJob {
+  file_path: PathBuf,
+  file_text: String,
+  config: serde_json::Value,
+  sender: mpsc::Sender<Result<Option<String>, AnyError>>,
+}
+
+fn call_into_runtime<T: serde::de::DeserializeOwned>(
+  runtime: &mut JsRuntime,
+  source: String,
+) -> Result<T, AnyError> {
+  let global = runtime.execute_script(located_script_name!(), source)?;
+  let scope = &mut runtime.handle_scope();
+  let local = v8::Local::new(scope, global);
+  Ok(serde_v8::from_v8::<T>(scope, local)?)
+}
+
+/// A dprint Wasm plugin running on a dedicated thread.
+struct FmtPlugin {
+  specifier: ModuleSpecifier,
+  info: PluginInfo,
+  file_matching: FileMatchingInfo,
+  sender: mpsc::Sender<PluginJob>,
+}
+
+impl FmtPlugin {
+  fn start(
+    specifier: ModuleSpecifier,
+    wasm_bytes: Vec<u8>,
+  ) -> Result<Self, AnyError> {
+    let (init_sender, init_receiver) = mpsc::channel();
+    let (sender, receiver) = mpsc::channel::<PluginJob>();
+    std::thread::spawn(move || {
+      let mut runtime = JsRuntime::new(RuntimeOptions {
+        extensions: vec![deno_fmt_plugin::init_ops(wasm_bytes)],
+        ..Default::default()
+      });
+      let init_config = global_config(&FmtOptionsConfig::default());
+      let init_result = runtime
+        .execute_script(
+          "ext:cli/fmt_plugin_host.js",
+          deno_core::ascii_str_include!("plugin_host.js"),
+        )
+        .and_then(|_| {
+          call_into_runtime::<InitResponse>(
+            &mut runtime,
+            format!("globalThis.initPlugin({init_config})"),
+          )
+        });
+      let is_err = init_result.is_err();
+      if init_sender.send(init_result).is_err() || is_err {
+        return;
+      }
+      while let Ok(job) = receiver.recv() {
+        let file_path = job.file_path.to_string_lossy();
+        let result = serde_json::to_string(&FormatRequest {
+          file_path: &file_path,
+          file_text: &job.file_text,
+          config: &job.config,
+        })
+        .map_err(AnyError::from)
+        .and_then(|request| {
+          call_into_runtime::<FormatResponse>(
+            &mut runtime,
+            format!("globalThis.formatText({request})"),
+          )
+        })
+        .and_then(|response| match response {
+          FormatResponse::Unchanged => Ok(None),
+          FormatResponse::Changed { text } => Ok(Some(text)),
+          FormatResponse::Error { message } => Err(anyhow!("{}", message)),
+        });
+        let _ = job.sender.send(result);
+      }
+    });
+    let response = init_receiver
+      .recv()
+      .context("Formatter plugin thread exited unexpectedly.")?
+      .with_context(|| {
+        format!("Failed to initialize formatter plugin {specifier}")
+      })?;
+    Ok(Self {
+      specifier,
+      info: response.info,
+      file_matching: response.file_matching,
+      sender,
+    })
+  }
+
+  fn matches(&self, file_path: &Path) -> bool {
+    let Some(file_name) = file_path.file_name().and_then(|n| n.to_str()) else {
+      return false;
+    };
+    if self.file_matching.file_names.iter().any(|n| n == file_name) {
+      return true;
+    }
+    let file_name = file_name.to_lowercase();
+    self.file_matching.file_extensions.iter().any(|ext| {
+      let ext = ext.trim_start_matches('.').to_lowercase();
+      file_name
+        .strip_suffix(&ext)
+        .is_some_and(|rest| rest.ends_with('.'))
+    })
+  }
+
+  fn format_text(
+    &self,
+    file_path: &Path,
+    file_text: &str,
+    fmt_options: &FmtOptionsConfig,
+  ) -> Result<Option<String>, AnyError> {
+    let (sender, receiver) = mpsc::channel();
+    self
+      .sender
+      .send(PluginJob {
+        file_path: file_path.to_path_buf(),
+        file_text: file_text.to_string(),
+        config: global_config(fmt_options),
+        sender,
+      })
+      .map_err(|_| anyhow!("Formatter plugin {} exited.", self.info.name))?;
+    receiver
+      .recv()
+      .map_err(|_| anyhow!("Formatter plugin {} exited.", self.info.name))?
+  }
+}
+
+/// Builds the configuration passed to `register_config`, mapping the
+/// deno fmt options to dprint's global configuration.
+fn global_config(fmt_options: &FmtOptionsConfig) -> serde_json::Value {
+  let mut global = serde_json::Map::new();
+  global.insert("newLineKind".to_string(), json!("lf"));
+  if let Some(line_width) = fmt_options.line_width {
+    global.insert("lineWidth".to_string(), json!(line_width));
+  }
+  if let Some(indent_width) = fmt_options.indent_width {
+    global.insert("indentWidth".to_string(), json!(indent_width));
+  }
+  if let Some(use_tabs) = fmt_options.use_tabs {
+    global.insert("useTabs".to_string(), json!(use_tabs));
+  }
+  json!({
+    "global": global,
+    "plugin": {},
+  })
+}
+
+/// The formatter plugins declared in the workspace's root config file.
+#[derive(Default)]
+pub struct FmtPlugins(Vec<FmtPlugin>);
+
+impl FmtPlugins {
+  pub async fn resolve(
+    cli_options: &CliOptions,
+    file_fetcher: &FileFetcher,
+  ) -> Result<Self, AnyError> {
+    let Some(config_file) = cli_options.workspace().root_deno_json() else {
+      return Ok(Self::default());
+    };
+    let plugins_config = read_plugins_config(config_file)?;
+    let mut plugins = Vec::with_capacity(plugins_config.fmt.len());
+    for specifier in plugins_config.fmt {
+      let specifier =
+        config_file.specifier.join(&specifier).with_context(|| {
+          format!("Invalid formatter plugin specifier '{specifier}'")
+        })?;
+      let file = file_fetcher
+        .fetch_bypass_permissions(&specifier)
+        .await
+        .with_context(|| {
+          format!("Failed to load formatter plugin {specifier}")
+        })?;
+      let plugin = FmtPlugin::start(specifier, file.source.to_vec())?;
+      log::debug!(
+        "Loaded formatter plugin {} {} ({})",
+        plugin.info.name,
+        plugin.info.version,
+        plugin.specifier
+      );
+      plugins.push(plugin);
+    }
+    Ok(Self(plugins))
+  }
+
+  /// Whether a plugin handles the provided file. Plugins take precedence
+  /// over the built-in formatters.
+  pub fn matches(&self, file_path: &Path) -> bool {
+    self.0.iter().any(|plugin| plugin.matches(file_path))
+  }
+
+  /// Formats the file with the first plugin that handles it, returning
+  /// `None` when no plugin matches it.
+  pub fn format_file(
+    &self,
+    file_path: &Path,
+    file_text: &str,
+    fmt_options: &FmtOptionsConfig,
+  ) -> Option<Result<Option<String>, AnyError>> {
+    let plugin = self.0.iter().find(|plugin| plugin.matches(file_path))?;
+    Some(plugin.format_text(file_path, file_text, fmt_options))
+  }
+
+  /// Identifies the loaded plugins for the incremental cache.
+  pub fn cache_key(&self) -> Vec<(&str, &str, &str)> {
+    self
+      .0
+      .iter()
+      .map(|plugin| {
+        (
+          plugin.specifier.as_str(),
+          plugin.info.name.as_str(),
+          plugin.info.version.as_str(),
+        )
+      })
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_global_config() {
+    assert_eq!(
+      global_config(&FmtOptionsConfig {
+        line_width: Some(100),
+        use_tabs: Some(true),
+        ..Default::default()
+      }),
+      json!({
+        "global": {
+          "newLineKind": "lf",
+          "lineWidth": 100,
+          "useTabs": true,
+        },
+        "plugin": {},
+      })
+    );
+  }
+}
@@ -1,5 +1,7 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt;
@@ -28,6 +30,7 @@ use deno_terminal::colors;
 use crate::args::Flags;
 use crate::args::InfoFlags;
 use crate::display;
+use crate::emit::Emitter;
 use crate::factory::CliFactory;
 use crate::graph_util::graph_exit_lock_errors;
 use crate::npm::CliNpmResolver;
@@ -49,23 +52,26 @@ pub async fn info(
     let maybe_lockfile = cli_options.maybe_lockfile();
     let resolver = factory.workspace_resolver().await?;
 
-    let maybe_import_specifier =
-      if let Some(import_map) = resolver.maybe_import_map() {
-        if let Ok(imports_specifier) =
-          import_map.resolve(&specifier, import_map.base_url())
-        {
-          Some(imports_specifier)
+    let resolve_specifier = |specifier: &str| {
+      let maybe_import_specifier =
+        if let Some(import_map) = resolver.maybe_import_map() {
+          if let Ok(imports_specifier) =
+            import_map.resolve(specifier, import_map.base_url())
+          {
+            Some(imports_specifier)
+          } else {
+            None
+          }
         } else {
           None
-        }
-      } else {
-        None
-      };
+        };
 
-    let specifier = match maybe_import_specifier {
-      Some(specifier) => specifier,
-      None => resolve_url_or_path(&specifier, cli_options.initial_cwd())?,
+      match maybe_import_specifier {
+        Some(specifier) => Ok(specifier),
+        None => resolve_url_or_path(specifier, cli_options.initial_cwd()),
+      }
     };
+    let specifier = resolve_specifier(&specifier)?;
 
     let mut loader = module_graph_builder.create_graph_loader();
     loader.enable_loading_cache_info(); // for displaying the cache information
@@ -79,12 +85,32 @@ pub async fn info(
       lockfile.write_if_changed()?;
     }
 
-    if info_flags.json {
+    if let Some(why) = info_flags.why {
+      let query = if why.starts_with("npm:") {
+        WhyQuery::Package(why.clone())
+      } else {
+        let specifier = resolve_specifier(&why)?;
+        if specifier.scheme() == "npm" {
+          WhyQuery::Package(specifier.to_string())
+        } else {
+          WhyQuery::Module(specifier)
+        }
+      };
+      let mut output = String::new();
+      write_why(&graph, npm_resolver.as_ref(), &query, &why, &mut output)?;
+      display::write_to_stdout_ignore_sigpipe(output.as_bytes())?;
+    } else if info_flags.json {
       let mut json_graph = serde_json::json!(graph);
       if let Some(output) = json_graph.as_object_mut() {
         output.insert("version".to_string(), JSON_SCHEMA_VERSION.into());
       }
       add_npm_packages_to_json(&mut json_graph, npm_resolver.as_ref());
+      add_module_sizes_to_json(
+        &mut json_graph,
+        &graph,
+        factory.emitter()?,
+        npm_resolver.as_ref(),
+      );
       display::write_json_to_stdout(&json_graph)?;
     } else {
       let mut output = String::new();
@@ -270,11 +296,266 @@ fn add_npm_packages_to_json(
       .map(|id| serde_json::Value::String(id.as_serialized()))
       .collect::<Vec<_>>();
     kv.insert("dependencies".to_string(), deps.into());
+    if let Ok(size) = npm_resolver.package_size(&pkg.id) {
+      kv.insert("size".to_string(), size.into());
+    }
 
     json_packages.insert(pkg.id.as_serialized(), kv.into());
   }
 
   json.insert("npmPackages".to_string(), json_packages.into());
+
+  // packages that are resolved at several versions, which is usually
+  // caused by dependencies having incompatible version requirements
+  let mut versions_by_name = BTreeMap::<_, BTreeSet<_>>::new();
+  for pkg in snapshot.all_packages_for_every_system() {
+    versions_by_name
+      .entry(pkg.id.nv.name.as_str())
+      .or_default()
+      .insert(&pkg.id.nv.version);
+  }
+  let duplicates = versions_by_name
+    .into_iter()
+    .filter(|(_, versions)| versions.len() > 1)
+    .map(|(name, versions)| {
+      let versions = versions
+        .into_iter()
+        .map(|version| serde_json::Value::String(version.to_string()))
+        .collect::<Vec<_>>();
+      (name.to_string(), serde_json::Value::from(versions))
+    })
+    .collect::<serde_json::Map<_, _>>();
+  if !duplicates.is_empty() {
+    json.insert("duplicateNpmPackages".to_string(), duplicates.into());
+  }
+}
+
+/// Adds the size of the cached emit and the total size of the module and
+/// everything it depends on to each module of the json output.
+fn add_module_sizes_to_json(
+  json: &mut serde_json::Value,
+  graph: &ModuleGraph,
+  emitter: &Emitter,
+  npm_resolver: &dyn CliNpmResolver,
+) {
+  let npm_info = match npm_resolver.as_managed() {
+    Some(npm_resolver) => {
+      let npm_snapshot = npm_resolver.snapshot();
+      NpmInfo::build(graph, npm_resolver, &npm_snapshot)
+    }
+    None => NpmInfo::default(),
+  };
+  let modules = json.get_mut("modules").and_then(|m| m.as_array_mut());
+  let Some(modules) = modules else {
+    return;
+  };
+  let subtree_sizes = subtree_sizes(graph, &npm_info);
+  for module_json in modules.iter_mut() {
+    let Some(module_json) = module_json.as_object_mut() else {
+      continue;
+    };
+    let module = module_json
+      .get("specifier")
+      .and_then(|s| s.as_str())
+      .and_then(|s| ModuleSpecifier::parse(s).ok())
+      .and_then(|specifier| graph.get(&specifier));
+    let Some(module) = module else {
+      continue;
+    };
+    if let Some(module) = module.js() {
+      let emit = emitter.maybe_cached_emit(&module.specifier, &module.source);
+      if let Some(emit) = emit {
+        module_json.insert("emitSize".to_string(), emit.len().into());
+      }
+    }
+    if let Some(size) = subtree_sizes.get(module.specifier()) {
+      module_json.insert("totalSize".to_string(), (*size).into());
+    }
+  }
+}
+
+fn module_size(module: &Module) -> u64 {
+  match module {
+    Module::Js(module) => module.size() as u64,
+    Module::Json(module) => module.size() as u64,
+    Module::Node(_) | Module::Npm(_) | Module::External(_) => 0,
+  }
+}
+
+/// Sums up the sizes of each module and all the modules and npm packages it
+/// depends on, counting each of them once.
+fn subtree_sizes<'a>(
+  graph: &'a ModuleGraph,
+  npm_info: &NpmInfo,
+) -> HashMap<&'a ModuleSpecifier, u64> {
+  let mut module_indexes = HashMap::new();
+  let mut sizes = Vec::new();
+  for module in graph.modules() {
+    module_indexes.insert(module.specifier(), sizes.len());
+    sizes.push(module_size(module));
+  }
+  let mut package_indexes = HashMap::new();
+  let package_ids = npm_info
+    .packages
+    .values()
+    .flat_map(|package| {
+      std::iter::once(&package.id).chain(package.dependencies.values())
+    })
+    .chain(npm_info.resolved_ids.values());
+  for id in package_ids {
+    package_indexes.entry(id).or_insert_with(|| {
+      sizes.push(npm_info.package_sizes.get(id).copied().unwrap_or(0));
+      sizes.len() - 1
+    });
+  }
+
+  let mut edges = vec![Vec::new(); sizes.len()];
+  for module in graph.modules() {
+    let index = module_indexes[module.specifier()];
+    match module {
+      Module::Js(module) => {
+        let resolutions = module
+          .dependencies
+          .values()
+          .flat_map(|dep| [&dep.maybe_code, &dep.maybe_type])
+          .chain(module.maybe_types_dependency.iter().map(|d| &d.dependency));
+        for resolution in resolutions {
+          if let Some(dep) =
+            resolution.maybe_specifier().and_then(|s| graph.get(s))
+          {
+            edges[index].push(module_indexes[dep.specifier()]);
+          }
+        }
+      }
+      Module::Npm(module) => {
+        if let Some(id) = npm_info.resolved_ids.get(module.nv_reference.nv()) {
+          edges[index].push(package_indexes[id]);
+        }
+      }
+      Module::Json(_) | Module::Node(_) | Module::External(_) => {}
+    }
+  }
+  for package in npm_info.packages.values() {
+    let index = package_indexes[&package.id];
+    for id in package.dependencies.values() {
+      edges[index].push(package_indexes[id]);
+    }
+  }
+
+  let totals = dependency_totals(&sizes, &edges);
+  module_indexes
+    .into_iter()
+    .map(|(specifier, index)| (specifier, totals[index]))
+    .collect()
+}
+
+/// Sums up the size of each node of a graph and of all the nodes it depends
+/// on, counting each of them once.
+///
+/// The strongly connected components of the graph are found with Tarjan's
+/// algorithm, which completes a component after all the components it
+/// depends on, so its set of dependencies is the union of theirs and shared
+/// dependencies are only walked once.
+fn dependency_totals(sizes: &[u64], edges: &[Vec<usize>]) -> Vec<u64> {
+  let words = sizes.len().div_ceil(64);
+  let mut dependents = vec![0usize; sizes.len()];
+  for dep in edges.iter().flatten() {
+    dependents[*dep] += 1;
+  }
+  let mut visit_index = vec![usize::MAX; sizes.len()];
+  let mut low_link = vec![0; sizes.len()];
+  let mut on_stack = vec![false; sizes.len()];
+  let mut stack = Vec::new();
+  let mut component_of = vec![usize::MAX; sizes.len()];
+  // the dependency sets as bit sets, which are dropped once every component
+  // depending on them has taken them over
+  let mut component_sets: Vec<Vec<u64>> = Vec::new();
+  let mut pending_dependents = Vec::new();
+  let mut component_totals = Vec::new();
+  let mut next_visit_index = 0;
+  for start in 0..sizes.len() {
+    if visit_index[start] != usize::MAX {
+      continue;
+    }
+    let mut visiting = vec![(start, 0)];
+    visit_index[start] = next_visit_index;
+    low_link[start] = next_visit_index;
+    next_visit_index += 1;
+    stack.push(start);
+    on_stack[start] = true;
+    while let Some(&(node, edge)) = visiting.last() {
+      if let Some(&next) = edges[node].get(edge) {
+        visiting.last_mut().unwrap().1 += 1;
+        if visit_index[next] == usize::MAX {
+          visit_index[next] = next_visit_index;
+          low_link[next] = next_visit_index;
+          next_visit_index += 1;
+          stack.push(next);
+          on_stack[next] = true;
+          visiting.push((next, 0));
+        } else if on_stack[next] {
+          low_link[node] = low_link[node].min(visit_index[next]);
+        }
+        continue;
+      }
+      visiting.pop();
+      if let Some(&(parent, _)) = visiting.last() {
+        low_link[parent] = low_link[parent].min(low_link[node]);
+      }
+      if low_link[node] != visit_index[node] {
+        continue;
+      }
+
+      let component = component_sets.len();
+      let mut members = Vec::new();
+      let mut set = vec![0u64; words];
+      loop {
+        let member = stack.pop().unwrap();
+        on_stack[member] = false;
+        component_of[member] = component;
+        set[member / 64] |= 1 << (member % 64);
+        members.push(member);
+        if member == node {
+          break;
+        }
+      }
+      let mut pending = members.iter().map(|m| dependents[*m]).sum::<usize>();
+      for member in &members {
+        for dep in &edges[*member] {
+          let dep_component = component_of[*dep];
+          if dep_component == component {
+            pending -= 1;
+            continue;
+          }
+          for (word, dep_word) in
+            set.iter_mut().zip(&component_sets[dep_component])
+          {
+            *word |= dep_word;
+          }
+          pending_dependents[dep_component] -= 1;
+          if pending_dependents[dep_component] == 0 {
+            component_sets[dep_component] = Vec::new();
+          }
+        }
+      }
+      let mut total = 0;
+      for (word_index, word) in set.iter().enumerate() {
+        let mut word = *word;
+        while word != 0 {
+          total += sizes[word_index * 64 + word.trailing_zeros() as usize];
+          word &= word - 1;
+        }
+      }
+      component_totals.push(total);
+      pending_dependents.push(pending);
+      component_sets.push(if pending == 0 { Vec::new() } else { set });
+    }
+  }
+
+  component_of
+    .into_iter()
+    .map(|component| component_totals[component])
+    .collect()
 }
 
 struct TreeNode {
@@ -468,14 +749,7 @@ impl<'a> GraphDisplayContext<'a> {
         let total_modules_size = self
           .graph
           .modules()
-          .map(|m| {
-            let size = match m {
-              Module::Js(module) => module.size(),
-              Module::Json(module) => module.size(),
-              Module::Node(_) | Module::Npm(_) | Module::External(_) => 0,
-            };
-            size as f64
-          })
+          .map(|m| module_size(m) as f64)
           .sum::<f64>();
         let total_npm_package_size = self
           .npm_info
@@ -569,8 +843,7 @@ impl<'a> GraphDisplayContext<'a> {
           self.npm_info.package_sizes.get(&package.id).copied()
         }
         Specifier(_) => match module {
-          Module::Js(module) => Some(module.size() as u64),
-          Module::Json(module) => Some(module.size() as u64),
+          Module::Js(_) | Module::Json(_) => Some(module_size(module)),
           Module::Node(_) | Module::Npm(_) | Module::External(_) => None,
         },
       };
@@ -703,6 +976,216 @@ impl<'a> GraphDisplayContext<'a> {
   }
 }
 
+/// The maximum number of import chains shown by `deno info --why`, as the
+/// number of chains can grow exponentially with the size of the graph.
+const MAX_WHY_CHAINS: usize = 100;
+
+/// What `deno info --why` looks for in the graph.
+enum WhyQuery {
+  Module(ModuleSpecifier),
+  /// An npm package name with an optional version, ex. `npm:chalk` or
+  /// `npm:chalk@5.3.0`.
+  Package(String),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum WhyNode<'a> {
+  Module(&'a ModuleSpecifier),
+  Package(&'a NpmPackageId),
+}
+
+struct WhyContext<'a> {
+  graph: &'a ModuleGraph,
+  npm_info: &'a NpmInfo,
+  query: &'a WhyQuery,
+  /// Nodes that lead to a module matching the query.
+  leads_to_match: HashSet<WhyNode<'a>>,
+  chain_count: usize,
+}
+
+impl<'a> WhyContext<'a> {
+  fn is_match(&self, node: WhyNode) -> bool {
+    match (self.query, node) {
+      (WhyQuery::Module(specifier), WhyNode::Module(node)) => specifier == node,
+      (WhyQuery::Package(query), WhyNode::Package(id)) => {
+        let query = query.trim_start_matches("npm:").trim_start_matches('/');
+        id.nv.name == query
+          || id.nv.to_string() == query
+          || id.as_serialized() == query
+      }
+      _ => false,
+    }
+  }
+
+  fn dependencies(&self, node: WhyNode<'a>) -> Vec<WhyNode<'a>> {
+    match node {
+      WhyNode::Module(specifier) => {
+        let Some(module) = self.graph.get(specifier).and_then(|m| m.js())
+        else {
+          return Vec::new();
+        };
+        let resolutions = module
+          .maybe_types_dependency
+          .iter()
+          .map(|d| &d.dependency)
+          .chain(
+            module
+              .dependencies
+              .values()
+              .flat_map(|dep| [&dep.maybe_code, &dep.maybe_type]),
+          );
+        let mut deps = Vec::new();
+        for resolution in resolutions {
+          let dep = resolution
+            .maybe_specifier()
+            .and_then(|s| self.graph.get(s))
+            .map(|module| match module.npm() {
+              Some(npm) => {
+                match self.npm_info.resolved_ids.get(npm.nv_reference.nv()) {
+                  Some(id) => WhyNode::Package(id),
+                  None => WhyNode::Module(module.specifier()),
+                }
+              }
+              None => WhyNode::Module(module.specifier()),
+            });
+          if let Some(dep) = dep {
+            if !deps.contains(&dep) {
+              deps.push(dep);
+            }
+          }
+        }
+        deps
+      }
+      WhyNode::Package(id) => {
+        let Some(package) = self.npm_info.packages.get(id) else {
+          return Vec::new();
+        };
+        let mut deps = package.dependencies.values().collect::<Vec<_>>();
+        deps.sort();
+        deps.dedup();
+        deps.into_iter().map(WhyNode::Package).collect()
+      }
+    }
+  }
+
+  /// Finds the nodes that lead to a match by walking the reversed edges of
+  /// everything reachable from the root back from the matches.
+  fn fill_leads_to_match(&mut self, root: WhyNode<'a>) {
+    let mut dependents = HashMap::<WhyNode, Vec<WhyNode>>::new();
+    let mut matches = Vec::new();
+    let mut seen = HashSet::from([root]);
+    let mut pending = vec![root];
+    while let Some(node) = pending.pop() {
+      if self.is_match(node) {
+        matches.push(node);
+      }
+      for dep in self.dependencies(node) {
+        dependents.entry(dep).or_default().push(node);
+        if seen.insert(dep) {
+          pending.push(dep);
+        }
+      }
+    }
+    while let Some(node) = matches.pop() {
+      if self.leads_to_match.insert(node) {
+        if let Some(dependents) = dependents.get(&node) {
+          matches.extend(dependents);
+        }
+      }
+    }
+  }
+
+  /// Builds the tree of import chains from this node to the matches, where
+  /// chains that start the same way share their nodes.
+  fn build_chains(
+    &mut self,
+    node: WhyNode<'a>,
+    path: &mut Vec<WhyNode<'a>>,
+  ) -> Option<TreeNode> {
+    if !self.leads_to_match.contains(&node)
+      || path.contains(&node)
+      || self.chain_count >= MAX_WHY_CHAINS
+    {
+      return None;
+    }
+    let text = match node {
+      WhyNode::Module(specifier) => specifier.to_string(),
+      WhyNode::Package(id) => format!("npm:/{}", id.as_serialized()),
+    };
+    if self.is_match(node) {
+      self.chain_count += 1;
+      return Some(TreeNode::from_text(colors::bold(text).to_string()));
+    }
+    let mut tree_node = TreeNode::from_text(text);
+    path.push(node);
+    for dep in self.dependencies(node) {
+      if let Some(child) = self.build_chains(dep, path) {
+        tree_node.children.push(child);
+      }
+    }
+    path.pop();
+    if tree_node.children.is_empty() {
+      None
+    } else {
+      Some(tree_node)
+    }
+  }
+}
+
+fn write_why<TWrite: Write>(
+  graph: &ModuleGraph,
+  npm_resolver: &dyn CliNpmResolver,
+  query: &WhyQuery,
+  query_text: &str,
+  writer: &mut TWrite,
+) -> Result<(), AnyError> {
+  if graph.roots.len() != 1 {
+    bail!("displaying graphs that have multiple roots is not supported.");
+  }
+  let npm_info = match npm_resolver.as_managed() {
+    Some(npm_resolver) => {
+      let npm_snapshot = npm_resolver.snapshot();
+      NpmInfo::build(graph, npm_resolver, &npm_snapshot)
+    }
+    None => NpmInfo::default(),
+  };
+  let Some(root) = graph.get(&graph.roots[0]) else {
+    bail!("module could not be found");
+  };
+  let root = WhyNode::Module(root.specifier());
+  let mut context = WhyContext {
+    graph,
+    npm_info: &npm_info,
+    query,
+    leads_to_match: HashSet::new(),
+    chain_count: 0,
+  };
+  context.fill_leads_to_match(root);
+  let Some(tree) = context.build_chains(root, &mut Vec::new()) else {
+    bail!("'{}' is not a dependency of {}", query_text, graph.roots[0]);
+  };
+  writeln!(
+    writer,
+    "{} {}",
+    colors::bold("import chains:"),
+    context.chain_count
+  )?;
+  writeln!(writer)?;
+  print_tree_node(&tree, writer)?;
+  if context.chain_count >= MAX_WHY_CHAINS {
+    writeln!(writer)?;
+    writeln!(
+      writer,
+      "{}",
+      colors::gray(format!(
+        "Only the first {} import chains are shown.",
+        MAX_WHY_CHAINS
+      ))
+    )?;
+  }
+  Ok(())
+}
+
 fn maybe_size_to_text(maybe_size: Option<u64>) -> String {
   colors::gray(format!(
     "({})",
@@ -713,3 +1196,19 @@ fn maybe_size_to_text(maybe_size: Option<u64>) -> String {
   ))
   .to_string()
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_dependency_totals() {
+    // 0 -> 1 -> 2 <-> 3 -> 4, 0 -> 4, and 5 on its own
+    let sizes = [1, 10, 100, 1000, 10000, 100000];
+    let edges = [vec![1, 4], vec![2], vec![3], vec![2, 4], vec![], vec![]];
+    assert_eq!(
+      dependency_totals(&sizes, &edges),
+      [11111, 11110, 11100, 11100, 10000, 100000]
+    );
+  }
+}
@@ -1,15 +1,70 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use crate::args::Flags;
 use crate::args::InitFlags;
 use crate::colors;
+use crate::factory::CliFactory;
+use deno_core::anyhow::bail;
 use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
+use deno_core::serde_json;
 use deno_core::serde_json::json;
 use log::info;
+use std::collections::BTreeMap;
 use std::io::Write;
+use std::path::Component;
 use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Template {
+  Default,
+  Serve,
+  Lib,
+  Cli,
+  Workspace,
+}
+
+impl Template {
+  fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "default" | "app" => Some(Self::Default),
+      "serve" | "server" => Some(Self::Serve),
+      "lib" | "library" => Some(Self::Lib),
+      "cli" => Some(Self::Cli),
+      "workspace" | "monorepo" => Some(Self::Workspace),
+      _ => None,
+    }
+  }
+}
+
+/// A template fetched from a URL or a path, mapping relative file paths to
+/// their contents.
+#[derive(serde::Deserialize)]
+struct RemoteTemplate {
+  files: BTreeMap<String, String>,
+}
+
+pub async fn init_project(
+  flags: Arc<Flags>,
+  init_flags: InitFlags,
+) -> Result<(), AnyError> {
+  let template = if init_flags.serve {
+    Some(Template::Serve)
+  } else if init_flags.lib {
+    Some(Template::Lib)
+  } else {
+    match &init_flags.template {
+      None => Some(Template::Default),
+      Some(name) => Template::from_name(name),
+    }
+  };
+  // a template that isn't built-in is fetched before creating anything
+  let remote_template = match (template, &init_flags.template) {
+    (None, Some(template)) => Some(fetch_template(flags, template).await?),
+    _ => None,
+  };
 
-pub fn init_project(init_flags: InitFlags) -> Result<(), AnyError> {
   let cwd =
     std::env::current_dir().context("Can't read current working directory.")?;
   let dir = if let Some(dir) = &init_flags.dir {
@@ -20,7 +75,18 @@ pub fn init_project(init_flags: InitFlags) -> Result<(), AnyError> {
     cwd
   };
 
-  if init_flags.serve {
+  // Extract the directory name to use as the project name
+  let project_name = dir
+    .file_name()
+    .unwrap_or_else(|| dir.as_os_str())
+    .to_string_lossy()
+    .to_string();
+
+  if let Some(remote_template) = &remote_template {
+    for (path, content) in &remote_template.files {
+      create_file(&dir, path, content)?;
+    }
+  } else if template == Some(Template::Serve) {
     create_file(
       &dir,
       "main.ts",
@@ -109,14 +175,7 @@ Deno.test(async function serverFetchStatic() {
         }
       }),
     )?;
-  } else if init_flags.lib {
-    // Extract the directory name to use as the project name
-    let project_name = dir
-      .file_name()
-      .unwrap_or_else(|| dir.as_os_str())
-      .to_str()
-      .unwrap();
-
+  } else if template == Some(Template::Lib) {
     create_file(
       &dir,
       "mod.ts",
@@ -153,6 +212,127 @@ Deno.test(function addTest() {
         },
       }),
     )?;
+  } else if template == Some(Template::Cli) {
+    create_file(
+      &dir,
+      "main.ts",
+      r#"import { parseArgs } from "@std/cli/parse-args";
+
+export function greet(name: string): string {
+  return `Hello, ${name}!`;
+}
+
+if (import.meta.main) {
+  const args = parseArgs(Deno.args, {
+    string: ["name"],
+    boolean: ["help"],
+    alias: { h: "help", n: "name" },
+    default: { name: "world" },
+  });
+  if (args.help) {
+    console.log("Usage: main.ts [--name <name>]");
+    Deno.exit(0);
+  }
+  console.log(greet(args.name));
+}
+"#,
+    )?;
+    create_file(
+      &dir,
+      "main_test.ts",
+      r#"import { assertEquals } from "@std/assert";
+import { greet } from "./main.ts";
+
+Deno.test(function greetTest() {
+  assertEquals(greet("Deno"), "Hello, Deno!");
+});
+"#,
+    )?;
+
+    create_json_file(
+      &dir,
+      "deno.json",
+      &json!({
+        "tasks": {
+          "dev": "deno run --watch main.ts",
+          "compile": "deno compile -o bin/cli main.ts"
+        },
+        "imports": {
+          "@std/assert": "jsr:@std/assert@1",
+          "@std/cli": "jsr:@std/cli@1"
+        }
+      }),
+    )?;
+  } else if template == Some(Template::Workspace) {
+    let scope = workspace_scope(&project_name);
+    create_json_file(
+      &dir,
+      "deno.json",
+      &json!({
+        "workspace": ["./packages/greet", "./packages/app"],
+        "tasks": {
+          "dev": "deno run --watch packages/app/main.ts"
+        },
+        "imports": {
+          "@std/assert": "jsr:@std/assert@1"
+        }
+      }),
+    )?;
+
+    let greet_dir = dir.join("packages").join("greet");
+    std::fs::create_dir_all(&greet_dir)?;
+    create_json_file(
+      &greet_dir,
+      "deno.json",
+      &json!({
+        "name": format!("@{scope}/greet"),
+        "version": "0.1.0",
+        "exports": "./mod.ts"
+      }),
+    )?;
+    create_file(
+      &greet_dir,
+      "mod.ts",
+      r#"export function greet(name: string): string {
+  return `Hello, ${name}!`;
+}
+"#,
+    )?;
+    create_file(
+      &greet_dir,
+      "mod_test.ts",
+      r#"import { assertEquals } from "@std/assert";
+import { greet } from "./mod.ts";
+
+Deno.test(function greetTest() {
+  assertEquals(greet("Deno"), "Hello, Deno!");
+});
+"#,
+    )?;
+
+    let app_dir = dir.join("packages").join("app");
+    std::fs::create_dir_all(&app_dir)?;
+    create_json_file(
+      &app_dir,
+      "deno.json",
+      &json!({
+        "tasks": {
+          "start": "deno run main.ts"
+        }
+      }),
+    )?;
+    create_file(
+      &app_dir,
+      "main.ts",
+      &format!(
+        r#"import {{ greet }} from "@{scope}/greet";
+
+if (import.meta.main) {{
+  console.log(greet("world"));
+}}
+"#
+      ),
+    )?;
   } else {
     create_file(
       &dir,
@@ -193,6 +373,41 @@ Deno.test(function addTest() {
     )?;
   }
 
+  if init_flags.ci {
+    let test_command = if template == Some(Template::Serve) {
+      "deno test -R"
+    } else {
+      "deno test"
+    };
+    let workflows_dir = dir.join(".github").join("workflows");
+    std::fs::create_dir_all(&workflows_dir)?;
+    create_file(
+      &workflows_dir,
+      "ci.yml",
+      &format!(
+        r#"name: ci
+
+on:
+  push:
+    branches: [main]
+  pull_request:
+
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+      - uses: denoland/setup-deno@v2
+        with:
+          deno-version: v2.x
+      - run: deno fmt --check
+      - run: deno lint
+      - run: {test_command}
+"#
+      ),
+    )?;
+  }
+
   info!("✅ {}", colors::green("Project initialized"));
   info!("");
   info!("{}", colors::gray("Run these commands to get started"));
@@ -201,7 +416,12 @@ Deno.test(function addTest() {
     info!("  cd {}", dir);
     info!("");
   }
-  if init_flags.serve {
+  if remote_template.is_some() {
+    info!(
+      "  {}",
+      colors::gray("# See the template's files for what to do next")
+    );
+  } else if template == Some(Template::Serve) {
     info!("  {}", colors::gray("# Run the server"));
     info!("  deno serve -R main.ts");
     info!("");
@@ -213,7 +433,7 @@ Deno.test(function addTest() {
     info!("");
     info!("  {}", colors::gray("# Run the tests"));
     info!("  deno test -R");
-  } else if init_flags.lib {
+  } else if template == Some(Template::Lib) {
     info!("  {}", colors::gray("# Run the tests"));
     info!("  deno test");
     info!("");
@@ -225,6 +445,24 @@ Deno.test(function addTest() {
     info!("");
     info!("  {}", colors::gray("# Publish to JSR (dry run)"));
     info!("  deno publish --dry-run");
+  } else if template == Some(Template::Cli) {
+    info!("  {}", colors::gray("# Run the program"));
+    info!("  deno run main.ts --name=Deno");
+    info!("");
+    info!("  {}", colors::gray("# Run the tests"));
+    info!("  deno test");
+    info!("");
+    info!(
+      "  {}",
+      colors::gray("# Compile the program to an executable")
+    );
+    info!("  deno task compile");
+  } else if template == Some(Template::Workspace) {
+    info!("  {}", colors::gray("# Run the app"));
+    info!("  deno run packages/app/main.ts");
+    info!("");
+    info!("  {}", colors::gray("# Run the tests of all the packages"));
+    info!("  deno test");
   } else {
     info!("  {}", colors::gray("# Run the program"));
     info!("  deno run main.ts");
@@ -241,6 +479,53 @@ Deno.test(function addTest() {
   Ok(())
 }
 
+async fn fetch_template(
+  flags: Arc<Flags>,
+  template: &str,
+) -> Result<RemoteTemplate, AnyError> {
+  let factory = CliFactory::from_flags(flags);
+  let cli_options = factory.cli_options()?;
+  let specifier =
+    deno_core::resolve_url_or_path(template, cli_options.initial_cwd())?;
+  let file = factory
+    .file_fetcher()?
+    .fetch_bypass_permissions(&specifier)
+    .await
+    .with_context(|| format!("Failed to fetch template {specifier}"))?;
+  let text = file.into_text_decoded()?.source;
+  let template: RemoteTemplate = serde_json::from_str(&text)
+    .with_context(|| format!("Invalid template {specifier}"))?;
+  for path in template.files.keys() {
+    let is_relative = Path::new(path)
+      .components()
+      .all(|component| matches!(component, Component::Normal(_)));
+    if !is_relative || path.is_empty() {
+      bail!(
+        "Invalid template {}: file paths must be relative and inside the project directory, found '{}'",
+        specifier,
+        path
+      );
+    }
+  }
+  Ok(template)
+}
+
+/// Converts the project's directory name into a valid JSR scope for the
+/// packages of a workspace.
+fn workspace_scope(project_name: &str) -> String {
+  let scope = project_name
+    .to_lowercase()
+    .chars()
+    .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+    .collect::<String>();
+  let scope = scope.trim_matches('-');
+  if scope.is_empty() {
+    "workspace".to_string()
+  } else {
+    scope.to_string()
+  }
+}
+
 fn create_json_file(
   dir: &Path,
   filename: &str,
@@ -257,6 +542,9 @@ fn create_file(
   content: &str,
 ) -> Result<(), AnyError> {
   let path = dir.join(filename);
+  if let Some(parent) = path.parent() {
+    std::fs::create_dir_all(parent)?;
+  }
   if path.exists() {
     info!(
       "ℹ️ {}",
@@ -15,6 +15,7 @@ use crate::args::UninstallKind;
 use crate::factory::CliFactory;
 use crate::graph_container::ModuleGraphContainer;
 use crate::http_util::HttpClientProvider;
+use crate::npm::NpmCacheDir;
 use crate::util::fs::canonicalize_path_maybe_not_exists;
 
 use deno_core::anyhow::bail;
@@ -23,6 +24,9 @@ use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::resolve_url_or_path;
 use deno_core::url::Url;
+use deno_lockfile::Lockfile;
+use deno_lockfile::NewLockfileOptions;
+use deno_npm::NpmPackageId;
 use deno_semver::npm::NpmPackageReqReference;
 use log::Level;
 use once_cell::sync::Lazy;
@@ -207,15 +211,13 @@ pub async fn uninstall(
     UninstallKind::Local(remove_flags) => {
       return super::registry::remove(flags, remove_flags).await;
     }
+    UninstallKind::ListGlobal { root } => {
+      return list_installed(root.as_deref());
+    }
   };
 
-  let cwd = std::env::current_dir().context("Unable to get CWD")?;
-  let root = if let Some(root) = uninstall_flags.root {
-    canonicalize_path_maybe_not_exists(&cwd.join(root))?
-  } else {
-    get_installer_root()?
-  };
-  let installation_dir = root.join("bin");
+  let installation_dir =
+    resolve_installation_dir(uninstall_flags.root.as_deref())?;
 
   // ensure directory exists
   if let Ok(metadata) = fs::metadata(&installation_dir) {
@@ -226,6 +228,13 @@ pub async fn uninstall(
 
   let file_path = installation_dir.join(&uninstall_flags.name);
 
+  // read the shim's lockfile before it's deleted below
+  let maybe_lockfile = if uninstall_flags.clean_cache {
+    read_shim_lockfile(&file_path)?
+  } else {
+    None
+  };
+
   let mut removed = false;
 
   if file_path.exists() {
@@ -253,18 +262,154 @@ pub async fn uninstall(
   // There might be some extra files to delete
   // Note: tsconfig.json is legacy. We renamed it to deno.json.
   // Remove cleaning it up after January 2024
-  for ext in ["tsconfig.json", "deno.json", "lock.json"] {
-    let file_path = file_path.with_extension(ext);
-    if file_path.exists() {
-      fs::remove_file(&file_path)?;
-      log::info!("deleted {}", file_path.to_string_lossy());
+  for ext in ["tsconfig.json", "deno.json", "lock.json", "import_map.json"] {
+    for file_path in [
+      file_path.with_extension(ext),
+      get_hidden_file_with_ext(&file_path, ext),
+    ] {
+      if file_path.exists() {
+        fs::remove_file(&file_path)?;
+        log::info!("deleted {}", file_path.to_string_lossy());
+      }
     }
   }
 
+  if let Some(lockfile) = maybe_lockfile {
+    let factory = CliFactory::from_flags(flags);
+    let removed_count = remove_cached_dependencies(&factory, &lockfile)?;
+    log::info!("deleted {} cached dependencies", removed_count);
+  }
+
   log::info!("✅ Successfully uninstalled {}", uninstall_flags.name);
   Ok(())
 }
 
+fn resolve_installation_dir(root: Option<&str>) -> Result<PathBuf, AnyError> {
+  let root = if let Some(root) = root {
+    let cwd = std::env::current_dir().context("Unable to get CWD")?;
+    canonicalize_path_maybe_not_exists(&cwd.join(root))?
+  } else {
+    get_installer_root()?
+  };
+  Ok(root.join("bin"))
+}
+
+/// Whether the file is an executable generated by `deno install`, returning
+/// its name if so.
+fn installed_executable_name(path: &Path) -> Option<String> {
+  let file_name = path.file_name()?.to_str()?;
+  // skip the hidden config and lock files of the executables
+  if file_name.starts_with('.') {
+    return None;
+  }
+  let (name, marker) = match file_name.strip_suffix(".cmd") {
+    Some(name) => (name, "% generated by deno install %"),
+    // on Windows, the extensionless file is the shell script for git bash
+    None if cfg!(windows) => return None,
+    None => (file_name, "# generated by deno install"),
+  };
+  let content = fs::read_to_string(path).ok()?;
+  content
+    .lines()
+    .take(2)
+    .any(|line| line == marker)
+    .then(|| name.to_string())
+}
+
+#[allow(clippy::print_stdout)]
+fn list_installed(root: Option<&str>) -> Result<(), AnyError> {
+  let installation_dir = resolve_installation_dir(root)?;
+  let names = read_installed_names(&installation_dir)?;
+  if names.is_empty() {
+    log::info!("No executables installed in {}", installation_dir.display());
+    return Ok(());
+  }
+  for name in names {
+    println!("{name}");
+  }
+  Ok(())
+}
+
+/// The sorted names of the executables in the installation directory, or
+/// the default one when `root` isn't provided.
+pub fn installed_names(root: Option<&str>) -> Result<Vec<String>, AnyError> {
+  read_installed_names(&resolve_installation_dir(root)?)
+}
+
+fn read_installed_names(
+  installation_dir: &Path,
+) -> Result<Vec<String>, AnyError> {
+  let mut names = match fs::read_dir(installation_dir) {
+    Ok(entries) => entries
+      .filter_map(|entry| installed_executable_name(&entry.ok()?.path()))
+      .collect::<Vec<_>>(),
+    Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+    Err(err) => {
+      return Err(err).with_context(|| {
+        format!("Failed reading {}", installation_dir.display())
+      })
+    }
+  };
+  names.sort();
+  names.dedup();
+  Ok(names)
+}
+
+fn read_shim_lockfile(file_path: &Path) -> Result<Option<Lockfile>, AnyError> {
+  let lock_path = get_hidden_file_with_ext(file_path, "lock.json");
+  let content = match fs::read_to_string(&lock_path) {
+    Ok(content) => content,
+    Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+    Err(err) => {
+      return Err(err).with_context(|| {
+        format!("Failed reading lockfile '{}'", lock_path.display())
+      })
+    }
+  };
+  Ok(Some(Lockfile::new(NewLockfileOptions {
+    file_path: lock_path,
+    content: &content,
+    overwrite: false,
+  })?))
+}
+
+/// Removes the remote modules and npm packages recorded in the lockfile of an
+/// executable from the global cache. Other executables or projects that use
+/// them will download them again.
+fn remove_cached_dependencies(
+  factory: &CliFactory,
+  lockfile: &Lockfile,
+) -> Result<usize, AnyError> {
+  let mut removed_count = 0;
+  let http_cache = factory.global_http_cache()?;
+  for specifier in lockfile.remote().keys() {
+    let Ok(url) = Url::parse(specifier) else {
+      continue;
+    };
+    let path = http_cache.get_global_cache_filepath(&url)?;
+    if fs::remove_file(&path).is_ok() {
+      removed_count += 1;
+    }
+  }
+
+  let npmrc = factory.cli_options()?.npmrc();
+  let npm_cache_dir = NpmCacheDir::new(
+    factory.deno_dir()?.npm_folder_path(),
+    npmrc.get_all_known_registries_urls(),
+  );
+  for id in lockfile.content.packages.npm.keys() {
+    let Ok(id) = NpmPackageId::from_serialized(id) else {
+      continue;
+    };
+    let registry_url = npmrc.get_registry_url(&id.nv.name);
+    let folder = npm_cache_dir.package_folder_for_nv(&id.nv, registry_url);
+    if fs::remove_dir_all(&folder).is_ok() {
+      removed_count += 1;
+    }
+  }
+  Ok(removed_count)
+}
+
 pub(crate) async fn install_from_entrypoints(
   flags: Arc<Flags>,
   entrypoints: &[String],
@@ -348,27 +493,67 @@ async fn install_global(
   flags: Arc<Flags>,
   install_flags_global: InstallFlagsGlobal,
 ) -> Result<(), AnyError> {
+  // Cache the module with a lockfile that is then copied next to the shim,
+  // so it keeps resolving the same module versions when run. Without a
+  // --lock flag, a fresh lockfile is populated while caching the module.
+  let temp_dir = tempfile::TempDir::new()?;
+  let cache_flags = if flags.no_lock || flags.lock.is_some() {
+    flags.clone()
+  } else {
+    let mut cache_flags = (*flags).clone();
+    cache_flags.lock = Some(
+      temp_dir
+        .path()
+        .join("deno.lock")
+        .to_string_lossy()
+        .to_string(),
+    );
+    cache_flags.frozen_lockfile = None;
+    Arc::new(cache_flags)
+  };
+
   // ensure the module is cached
-  let factory = CliFactory::from_flags(flags.clone());
+  let factory = CliFactory::from_flags(cache_flags);
   factory
     .main_module_graph_container()
     .await?
     .load_and_type_check_files(&[install_flags_global.module_url.clone()])
     .await?;
+  let resolution = ShimResolution {
+    lockfile: factory
+      .cli_options()?
+      .maybe_lockfile()
+      .map(|lockfile| lockfile.lock().as_json_string()),
+    import_map: if flags.import_map_path.is_some() {
+      factory
+        .workspace_resolver()
+        .await?
+        .maybe_import_map()
+        .map(|import_map| import_map.to_json())
+    } else {
+      None
+    },
+  };
   let http_client = factory.http_client_provider();
 
   // create the install shim
-  create_install_shim(http_client, &flags, install_flags_global).await
+  create_install_shim(http_client, &flags, install_flags_global, resolution)
+    .await
 }
 
 async fn create_install_shim(
   http_client_provider: &HttpClientProvider,
   flags: &Flags,
   install_flags_global: InstallFlagsGlobal,
+  resolution: ShimResolution,
 ) -> Result<(), AnyError> {
-  let shim_data =
-    resolve_shim_data(http_client_provider, flags, &install_flags_global)
-      .await?;
+  let shim_data = resolve_shim_data(
+    http_client_provider,
+    flags,
+    &install_flags_global,
+    resolution,
+  )
+  .await?;
 
   // ensure directory exists
   if let Ok(metadata) = fs::metadata(&shim_data.installation_dir) {
@@ -410,6 +595,15 @@ async fn create_install_shim(
   Ok(())
 }
 
+/// How the module resolved when it was cached during installation.
+#[derive(Default)]
+struct ShimResolution {
+  /// Contents of the lockfile populated while caching the module.
+  lockfile: Option<String>,
+  /// The `--import-map` with its specifiers resolved to absolute URLs.
+  import_map: Option<String>,
+}
+
 struct ShimData {
   name: String,
   installation_dir: PathBuf,
@@ -422,6 +616,7 @@ async fn resolve_shim_data(
   http_client_provider: &HttpClientProvider,
   flags: &Flags,
   install_flags_global: &InstallFlagsGlobal,
+  resolution: ShimResolution,
 ) -> Result<ShimData, AnyError> {
   let cwd = std::env::current_dir().context("Unable to get CWD")?;
   let root = if let Some(root) = &install_flags_global.root {
@@ -510,6 +705,11 @@ async fn resolve_shim_data(
     executable_args.push("--frozen".to_string());
   }
 
+  if !flags.node_conditions.is_empty() {
+    executable_args
+      .push(format!("--conditions={}", flags.node_conditions.join(",")));
+  }
+
   if resolve_no_prompt(&flags.permissions) {
     executable_args.push("--no-prompt".to_string());
   }
@@ -531,7 +731,12 @@ async fn resolve_shim_data(
     executable_args.push(format!("--inspect-brk={inspect_brk}"));
   }
 
-  if let Some(import_map_path) = &flags.import_map_path {
+  if let Some(import_map) = resolution.import_map {
+    let copy_path = get_hidden_file_with_ext(&file_path, "import_map.json");
+    executable_args.push("--import-map".to_string());
+    executable_args.push(copy_path.to_str().unwrap().to_string());
+    extra_files.push((copy_path, import_map));
+  } else if let Some(import_map_path) = &flags.import_map_path {
     let import_map_url = resolve_url_or_path(import_map_path, &cwd)?;
     executable_args.push("--import-map".to_string());
     executable_args.push(import_map_url.to_string());
@@ -552,6 +757,11 @@ async fn resolve_shim_data(
 
   if flags.no_lock {
     executable_args.push("--no-lock".to_string());
+  } else if let Some(lockfile) = resolution.lockfile {
+    let copy_path = get_hidden_file_with_ext(&file_path, "lock.json");
+    executable_args.push("--lock".to_string());
+    executable_args.push(copy_path.to_str().unwrap().to_string());
+    extra_files.push((copy_path, lockfile));
   } else if flags.lock.is_some()
     // always use a lockfile for an npm entrypoint unless --no-lock
     || NpmPackageReqReference::from_specifier(&module_url).is_ok()
@@ -826,6 +1036,7 @@ mod tests {
         root: Some(temp_dir.path().to_string()),
         force: false,
       },
+      Default::default(),
     )
     .await
     .unwrap();
@@ -860,6 +1071,7 @@ mod tests {
         root: Some(env::temp_dir().to_string_lossy().to_string()),
         force: false,
       },
+      Default::default(),
     )
     .await
     .unwrap();
@@ -883,6 +1095,7 @@ mod tests {
         root: Some(env::temp_dir().to_string_lossy().to_string()),
         force: false,
       },
+      Default::default(),
     )
     .await
     .unwrap();
@@ -912,6 +1125,7 @@ mod tests {
         root: Some(env::temp_dir().to_string_lossy().to_string()),
         force: false,
       },
+      Default::default(),
     )
     .await
     .unwrap();
@@ -941,6 +1155,7 @@ mod tests {
         root: Some(env::temp_dir().to_string_lossy().to_string()),
         force: false,
       },
+      Default::default(),
     )
     .await
     .unwrap();
@@ -966,6 +1181,7 @@ mod tests {
         root: Some(env::temp_dir().to_string_lossy().to_string()),
         force: false,
       },
+      Default::default(),
     )
     .await
     .unwrap();
@@ -993,6 +1209,7 @@ mod tests {
         root: Some(env::temp_dir().to_string_lossy().to_string()),
         force: false,
       },
+      Default::default(),
     )
     .await
     .unwrap();
@@ -1025,6 +1242,7 @@ mod tests {
         root: Some(env::temp_dir().to_string_lossy().to_string()),
         force: false,
       },
+      Default::default(),
     )
     .await
     .unwrap();
@@ -1062,6 +1280,7 @@ mod tests {
         root: Some(env::temp_dir().to_string_lossy().to_string()),
         force: false,
       },
+      Default::default(),
     )
     .await
     .unwrap();
@@ -1095,6 +1314,7 @@ mod tests {
         root: Some(env::temp_dir().to_string_lossy().to_string()),
         force: false,
       },
+      Default::default(),
     )
     .await
     .unwrap();
@@ -1129,6 +1349,7 @@ mod tests {
         root: Some(temp_dir.to_string_lossy().to_string()),
         force: false,
       },
+      Default::default(),
     )
     .await
     .unwrap();
@@ -1167,6 +1388,7 @@ mod tests {
         root: Some(env::temp_dir().to_string_lossy().to_string()),
         force: false,
       },
+      Default::default(),
     )
     .await
     .unwrap();
@@ -1184,6 +1406,54 @@ mod tests {
     assert_eq!(shim_data.extra_files, vec![]);
   }
 
+  #[tokio::test]
+  async fn install_pinned_resolution() {
+    let temp_dir = canonicalize_path(&env::temp_dir()).unwrap();
+    let lockfile = r#"{ "version": "4" }"#.to_string();
+    let import_map = r#"{ "imports": {} }"#.to_string();
+    let shim_data = resolve_shim_data(
+      &HttpClientProvider::new(None, None),
+      &Flags {
+        import_map_path: Some("import_map.json".to_string()),
+        frozen_lockfile: Some(true),
+        ..Flags::default()
+      },
+      &InstallFlagsGlobal {
+        module_url: "http://localhost:4545/cat.ts".to_string(),
+        args: vec![],
+        name: None,
+        root: Some(temp_dir.to_string_lossy().to_string()),
+        force: false,
+      },
+      ShimResolution {
+        lockfile: Some(lockfile.clone()),
+        import_map: Some(import_map.clone()),
+      },
+    )
+    .await
+    .unwrap();
+
+    let import_map_path = temp_dir.join("bin").join(".cat.import_map.json");
+    let lock_path = temp_dir.join("bin").join(".cat.lock.json");
+    assert_eq!(
+      shim_data.args,
+      vec![
+        "run",
+        "--frozen",
+        "--import-map",
+        &import_map_path.to_string_lossy(),
+        "--no-config",
+        "--lock",
+        &lock_path.to_string_lossy(),
+        "http://localhost:4545/cat.ts"
+      ]
+    );
+    assert_eq!(
+      shim_data.extra_files,
+      vec![(import_map_path, import_map), (lock_path, lockfile)]
+    );
+  }
+
   #[tokio::test]
   async fn install_local_module() {
     let temp_dir = TempDir::new();
@@ -1203,6 +1473,7 @@ mod tests {
         root: Some(temp_dir.path().to_string()),
         force: false,
       },
+      Default::default(),
     )
     .await
     .unwrap();
@@ -1233,6 +1504,7 @@ mod tests {
         root: Some(temp_dir.path().to_string()),
         force: false,
       },
+      Default::default(),
     )
     .await
     .unwrap();
@@ -1254,6 +1526,7 @@ mod tests {
         root: Some(temp_dir.path().to_string()),
         force: false,
       },
+      Default::default(),
     )
     .await;
     assert!(no_force_result.is_err());
@@ -1276,6 +1549,7 @@ mod tests {
         root: Some(temp_dir.path().to_string()),
         force: true,
       },
+      Default::default(),
     )
     .await;
     assert!(force_result.is_ok());
@@ -1307,6 +1581,7 @@ mod tests {
         root: Some(temp_dir.path().to_string()),
         force: true,
       },
+      Default::default(),
     )
     .await;
     assert!(result.is_ok());
@@ -1337,6 +1612,7 @@ mod tests {
         root: Some(temp_dir.path().to_string()),
         force: false,
       },
+      Default::default(),
     )
     .await
     .unwrap();
@@ -1378,6 +1654,7 @@ mod tests {
         root: Some(temp_dir.path().to_string()),
         force: false,
       },
+      Default::default(),
     )
     .await
     .unwrap();
@@ -1423,6 +1700,7 @@ mod tests {
         root: Some(temp_dir.path().to_string()),
         force: true,
       },
+      Default::default(),
     )
     .await;
     assert!(result.is_ok());
@@ -1466,6 +1744,7 @@ mod tests {
         root: Some(temp_dir.path().to_string()),
         force: true,
       },
+      Default::default(),
     )
     .await;
     assert!(result.is_ok());
@@ -1487,6 +1766,35 @@ mod tests {
     assert!(content.contains(&expected_string));
   }
 
+  #[tokio::test]
+  async fn installed_executable_names() {
+    let temp_dir = TempDir::new();
+    let bin_dir = temp_dir.path().join("bin");
+    std::fs::create_dir(&bin_dir).unwrap();
+    create_install_shim(
+      &HttpClientProvider::new(None, None),
+      &Flags::default(),
+      InstallFlagsGlobal {
+        module_url: "http://localhost:4545/echo_server.ts".to_string(),
+        args: vec![],
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_string()),
+        force: false,
+      },
+      ShimResolution {
+        lockfile: Some("{}".to_string()),
+        import_map: None,
+      },
+    )
+    .await
+    .unwrap();
+    // not generated by deno install
+    std::fs::write(bin_dir.join("other"), "#!/bin/sh\necho hi\n").unwrap();
+
+    let names = read_installed_names(bin_dir.as_path()).unwrap();
+    assert_eq!(names, vec!["echo_test"]);
+  }
+
   #[tokio::test]
   async fn uninstall_basic() {
     let temp_dir = TempDir::new();
@@ -1521,6 +1829,7 @@ mod tests {
         kind: UninstallKind::Global(UninstallFlagsGlobal {
           name: "echo_test".to_string(),
           root: Some(temp_dir.path().to_string()),
+          clean_cache: false,
         }),
       },
     )
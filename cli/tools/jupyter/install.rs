@@ -1,11 +1,13 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use deno_core::anyhow::bail;
 use deno_core::error::AnyError;
 use deno_core::serde_json;
 use deno_core::serde_json::json;
 use std::env::current_exe;
 use std::io::Write;
 use std::path::Path;
+use std::path::PathBuf;
 
 use jupyter_runtime::dirs::user_data_dir;
 
@@ -13,17 +15,41 @@ const DENO_ICON_32: &[u8] = include_bytes!("./resources/deno-logo-32x32.png");
 const DENO_ICON_64: &[u8] = include_bytes!("./resources/deno-logo-64x64.png");
 const DENO_ICON_SVG: &[u8] = include_bytes!("./resources/deno-logo-svg.svg");
 
-pub fn status() -> Result<(), AnyError> {
-  let user_data_dir = user_data_dir()?;
+const DEFAULT_KERNEL_NAME: &str = "deno";
+const DEFAULT_DISPLAY_NAME: &str = "Deno";
 
-  let kernel_spec_dir_path = user_data_dir.join("kernels").join("deno");
-  let kernel_spec_path = kernel_spec_dir_path.join("kernel.json");
+fn kernel_dir(name: Option<&str>) -> Result<PathBuf, AnyError> {
+  let name = name.unwrap_or(DEFAULT_KERNEL_NAME);
+  // Jupyter only accepts these characters in kernel names
+  if name.is_empty()
+    || !name
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+  {
+    bail!(
+      "Invalid kernel name '{}'. Kernel names may only contain ASCII letters, numbers, '-', '_' and '.'.",
+      name
+    );
+  }
+  let data_dir = match std::env::var_os("JUPYTER_DATA_DIR") {
+    Some(data_dir) => PathBuf::from(data_dir),
+    None => user_data_dir()?,
+  };
+  Ok(data_dir.join("kernels").join(name))
+}
+
+pub fn status(name: Option<&str>) -> Result<(), AnyError> {
+  let kernel_spec_path = kernel_dir(name)?.join("kernel.json");
 
   if kernel_spec_path.exists() {
     log::info!("✅ Deno kernel already installed");
     Ok(())
   } else {
-    log::warn!("ℹ️ Deno kernel is not yet installed, run `deno jupyter --install` to set it up");
+    let install_command = match name {
+      Some(name) => format!("deno jupyter --install --name {name}"),
+      None => "deno jupyter --install".to_string(),
+    };
+    log::warn!("ℹ️ Deno kernel is not yet installed, run `{install_command}` to set it up");
     Ok(())
   }
 }
@@ -39,9 +65,11 @@ fn install_icon(
   Ok(())
 }
 
-pub fn install() -> Result<(), AnyError> {
-  let user_data_dir = user_data_dir()?;
-  let kernel_dir = user_data_dir.join("kernels").join("deno");
+pub fn install(
+  name: Option<&str>,
+  display_name: Option<&str>,
+) -> Result<(), AnyError> {
+  let kernel_dir = kernel_dir(name)?;
 
   std::fs::create_dir_all(&kernel_dir)?;
 
@@ -52,15 +80,15 @@ pub fn install() -> Result<(), AnyError> {
   // FIXME(bartlomieju): replace `current_exe` before landing?
   let json_data = json!({
       "argv": [current_exe().unwrap().to_string_lossy(), "jupyter", "--kernel", "--conn", "{connection_file}"],
-      "display_name": "Deno",
+      "display_name": display_name.unwrap_or(DEFAULT_DISPLAY_NAME),
       "language": "typescript",
   });
 
   let f = std::fs::File::create(kernel_json_path)?;
   serde_json::to_writer_pretty(f, &json_data)?;
-  install_icon(&user_data_dir, "logo-32x32.png", DENO_ICON_32)?;
-  install_icon(&user_data_dir, "logo-64x64.png", DENO_ICON_64)?;
-  install_icon(&user_data_dir, "logo-svg.svg", DENO_ICON_SVG)?;
+  install_icon(&kernel_dir, "logo-32x32.png", DENO_ICON_32)?;
+  install_icon(&kernel_dir, "logo-64x64.png", DENO_ICON_64)?;
+  install_icon(&kernel_dir, "logo-svg.svg", DENO_ICON_SVG)?;
 
   log::info!("✅ Deno kernelspec installed successfully.");
   Ok(())
@@ -2,6 +2,7 @@
 
 use std::collections::HashSet;
 use std::path::Path;
+use std::sync::Arc;
 
 use deno_ast::MediaType;
 use deno_ast::ModuleSpecifier;
@@ -19,6 +20,9 @@ use deno_lint::linter::LinterOptions;
 use crate::util::fs::atomic_write_file_with_retries;
 use crate::util::fs::specifier_from_file_path;
 
+use super::plugins::intern_rule_code;
+use super::plugins::LintPlugins;
+use super::rules::ConfigLintRule;
 use super::rules::FileOrPackageLintRule;
 use super::rules::PackageLintRule;
 use super::ConfiguredRules;
@@ -26,15 +30,21 @@ use super::ConfiguredRules;
 pub struct CliLinterOptions {
   pub configured_rules: ConfiguredRules,
   pub fix: bool,
+  /// Apply fixes in memory only, leaving the files on disk untouched.
+  pub fix_dry_run: bool,
   pub deno_lint_config: DenoLintConfig,
+  pub plugins: Option<Arc<LintPlugins>>,
 }
 
 #[derive(Debug)]
 pub struct CliLinter {
   fix: bool,
+  fix_dry_run: bool,
   package_rules: Vec<Box<dyn PackageLintRule>>,
+  config_rules: Vec<Box<dyn ConfigLintRule>>,
   linter: DenoLintLinter,
   deno_lint_config: DenoLintConfig,
+  plugins: Option<Arc<LintPlugins>>,
 }
 
 impl CliLinter {
@@ -42,6 +52,7 @@ impl CliLinter {
     let rules = options.configured_rules.rules;
     let mut deno_lint_rules = Vec::with_capacity(rules.len());
     let mut package_rules = Vec::with_capacity(rules.len());
+    let mut config_rules = Vec::new();
     for rule in rules {
       match rule.into_file_or_pkg_rule() {
         FileOrPackageLintRule::File(rule) => {
@@ -50,21 +61,43 @@ impl CliLinter {
         FileOrPackageLintRule::Package(rule) => {
           package_rules.push(rule);
         }
+        FileOrPackageLintRule::Config(rule) => {
+          config_rules.push(rule);
+        }
       }
     }
+    let mut all_rule_codes = options.configured_rules.all_rule_codes;
+    if let Some(plugins) = &options.plugins {
+      // so that ignore comments for plugin rules aren't reported as unknown
+      all_rule_codes.extend(
+        plugins
+          .rule_codes()
+          .iter()
+          .map(|code| intern_rule_code(code)),
+      );
+    }
     Self {
       fix: options.fix,
+      fix_dry_run: options.fix_dry_run,
       package_rules,
+      config_rules,
       linter: DenoLintLinter::new(LinterOptions {
         rules: deno_lint_rules,
-        all_rule_codes: options.configured_rules.all_rule_codes,
+        all_rule_codes,
         custom_ignore_file_directive: None,
         custom_ignore_diagnostic_directive: None,
       }),
       deno_lint_config: options.deno_lint_config,
+      plugins: options.plugins,
     }
   }
 
+  /// Whether the returned source of [`CliLinter::lint_file`] might have
+  /// fixes applied that weren't written to disk.
+  pub fn is_fix_dry_run(&self) -> bool {
+    self.fix_dry_run
+  }
+
   pub fn has_package_rules(&self) -> bool {
     !self.package_rules.is_empty()
   }
@@ -81,13 +114,50 @@ impl CliLinter {
     diagnostics
   }
 
+  pub fn has_config_rules(&self) -> bool {
+    !self.config_rules.is_empty()
+  }
+
+  pub fn lint_config_file(
+    &self,
+    specifier: &ModuleSpecifier,
+    text_info: &SourceTextInfo,
+    imports: Option<&[(ModuleSpecifier, String)]>,
+  ) -> Vec<LintDiagnostic> {
+    let mut diagnostics = Vec::new();
+    for rule in &self.config_rules {
+      diagnostics.extend(rule.lint_config_file(specifier, text_info, imports));
+    }
+    diagnostics
+  }
+
   pub fn lint_with_ast(
     &self,
     parsed_source: &ParsedSource,
   ) -> Vec<LintDiagnostic> {
-    self
+    let mut diagnostics = self
       .linter
-      .lint_with_ast(parsed_source, self.deno_lint_config.clone())
+      .lint_with_ast(parsed_source, self.deno_lint_config.clone());
+    self.extend_with_plugin_diagnostics(parsed_source, &mut diagnostics);
+    diagnostics
+  }
+
+  fn extend_with_plugin_diagnostics(
+    &self,
+    parsed_source: &ParsedSource,
+    diagnostics: &mut Vec<LintDiagnostic>,
+  ) {
+    let Some(plugins) = &self.plugins else {
+      return;
+    };
+    match plugins.lint(parsed_source) {
+      Ok(plugin_diagnostics) => diagnostics.extend(plugin_diagnostics),
+      Err(err) => log::warn!(
+        "Failed running lint plugins on '{}': {:#}",
+        parsed_source.specifier(),
+        err
+      ),
+    }
   }
 
   pub fn lint_file(
@@ -105,19 +175,18 @@ impl CliLinter {
       MediaType::from_specifier(&specifier)
     };
 
-    if self.fix {
-      self.lint_file_and_fix(&specifier, media_type, source_code, file_path)
+    let (parsed_source, mut diagnostics) = if self.fix || self.fix_dry_run {
+      self.lint_file_and_fix(&specifier, media_type, source_code, file_path)?
     } else {
-      self
-        .linter
-        .lint_file(LintFileOptions {
-          specifier,
-          media_type,
-          source_code,
-          config: self.deno_lint_config.clone(),
-        })
-        .map_err(AnyError::from)
-    }
+      self.linter.lint_file(LintFileOptions {
+        specifier,
+        media_type,
+        source_code,
+        config: self.deno_lint_config.clone(),
+      })?
+    };
+    self.extend_with_plugin_diagnostics(&parsed_source, &mut diagnostics);
+    Ok((parsed_source, diagnostics))
   }
 
   fn lint_file_and_fix(
@@ -173,7 +242,7 @@ impl CliLinter {
       }
     }
 
-    if fix_iterations > 0 {
+    if fix_iterations > 0 && !self.fix_dry_run {
       // everything looks good and the file still parses, so write it out
       atomic_write_file_with_retries(
         file_path,
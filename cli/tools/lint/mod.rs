@@ -5,6 +5,7 @@
 
 use deno_ast::ModuleSpecifier;
 use deno_ast::ParsedSource;
+use deno_ast::SourceTextInfo;
 use deno_config::deno_json::LintRulesConfig;
 use deno_config::glob::FileCollector;
 use deno_config::glob::FilePatterns;
@@ -18,13 +19,20 @@ use deno_core::parking_lot::Mutex;
 use deno_core::serde_json;
 use deno_core::unsync::future::LocalFutureExt;
 use deno_core::unsync::future::SharedLocal;
+use deno_graph::DependencyDescriptor;
+use deno_graph::DynamicArgument;
 use deno_graph::ModuleGraph;
+use deno_graph::ParserModuleAnalyzer;
+use deno_graph::TypeScriptReference;
 use deno_lint::diagnostic::LintDiagnostic;
 use deno_lint::linter::LintConfig;
+use deno_runtime::fs_util::specifier_to_file_path;
+use indexmap::IndexMap;
 use log::debug;
 use reporters::create_reporter;
 use reporters::LintReporter;
 use serde::Serialize;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fs;
 use std::io::stdin;
@@ -32,8 +40,13 @@ use std::io::Read;
 use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
+use crate::args::deno_json::read_lint_severity_config;
+use crate::args::deno_json::LintRuleSeverity;
+use crate::args::import_map::config_file_imports;
 use crate::args::CliOptions;
 use crate::args::Flags;
 use crate::args::LintFlags;
@@ -45,6 +58,7 @@ use crate::colors;
 use crate::factory::CliFactory;
 use crate::graph_util::ModuleGraphCreator;
 use crate::tools::fmt::run_parallelized;
+use crate::util::diff::diff;
 use crate::util::display;
 use crate::util::file_watcher;
 use crate::util::fs::canonicalize_path;
@@ -52,11 +66,14 @@ use crate::util::path::is_script_ext;
 use crate::util::sync::AtomicFlag;
 
 mod linter;
+mod plugins;
 mod reporters;
 mod rules;
 
 pub use linter::CliLinter;
 pub use linter::CliLinterOptions;
+pub use plugins::transpile_plugin;
+pub use plugins::LintPlugins;
 pub use rules::collect_no_slow_type_diagnostics;
 pub use rules::ConfiguredRules;
 pub use rules::LintRuleProvider;
@@ -107,12 +124,20 @@ pub async fn lint(
             };
           }
 
+          let plugins = LintPlugins::resolve(
+            cli_options.workspace().root_deno_json().map(|c| c.as_ref()),
+            factory.file_fetcher()?,
+          )
+          .await?;
           let mut linter = WorkspaceLinter::new(
             factory.caches()?.clone(),
             factory.lint_rule_provider().await?,
+            plugins,
             factory.module_graph_creator().await?.clone(),
             cli_options.start_dir.clone(),
             &cli_options.resolve_workspace_lint_options(&lint_flags)?,
+            changed_paths.is_none()
+              && lints_all_modules(cli_options, &lint_flags),
           );
           for paths_with_options in paths_with_options_batches {
             linter
@@ -140,6 +165,11 @@ pub async fn lint(
     let deno_lint_config = cli_options.resolve_deno_lint_config()?;
     let workspace_lint_options =
       cli_options.resolve_workspace_lint_options(&lint_flags)?;
+    let plugins = LintPlugins::resolve(
+      cli_options.workspace().root_deno_json().map(|c| c.as_ref()),
+      factory.file_fetcher()?,
+    )
+    .await?;
     let success = if is_stdin {
       let start_dir = &cli_options.start_dir;
       let reporter_lock = Arc::new(Mutex::new(create_reporter(
@@ -159,21 +189,31 @@ pub async fn lint(
       if let Some(ext) = cli_options.ext_flag() {
         file_path.set_extension(ext);
       }
-      let r = lint_stdin(&file_path, lint_rules, deno_lint_config);
+      let severities = LintRuleSeverities::resolve(start_dir)?;
+      let warning_count = AtomicUsize::new(0);
+      let r = lint_stdin(&file_path, lint_rules, deno_lint_config, plugins);
       let success = handle_lint_result(
         &file_path.to_string_lossy(),
         r,
         reporter_lock.clone(),
+        &severities,
+        &warning_count,
       );
       reporter_lock.lock().close(1);
       success
+        && !exceeds_max_warnings(
+          warning_count.into_inner(),
+          workspace_lint_options.max_warnings,
+        )
     } else {
       let mut linter = WorkspaceLinter::new(
         factory.caches()?.clone(),
         factory.lint_rule_provider().await?,
+        plugins,
         factory.module_graph_creator().await?.clone(),
         cli_options.start_dir.clone(),
         &workspace_lint_options,
+        lints_all_modules(cli_options, &lint_flags),
       );
       let paths_with_options_batches =
         resolve_paths_with_options_batches(cli_options, &lint_flags)?;
@@ -198,6 +238,13 @@ pub async fn lint(
   Ok(())
 }
 
+/// If all the modules of the workspace are linted, rather than the files
+/// given as arguments or those of a member.
+fn lints_all_modules(cli_options: &CliOptions, lint_flags: &LintFlags) -> bool {
+  lint_flags.files.include.is_empty()
+    && cli_options.start_dir.dir_url() == cli_options.workspace().root_dir()
+}
+
 struct PathsWithOptions {
   dir: WorkspaceDirectory,
   paths: Vec<PathBuf>,
@@ -234,33 +281,52 @@ type WorkspaceModuleGraphFuture =
 struct WorkspaceLinter {
   caches: Arc<Caches>,
   lint_rule_provider: LintRuleProvider,
+  plugins: Option<Arc<LintPlugins>>,
   module_graph_creator: Arc<ModuleGraphCreator>,
   workspace_dir: Arc<WorkspaceDirectory>,
   reporter_lock: Arc<Mutex<Box<dyn LintReporter + Send>>>,
   workspace_module_graph: Option<WorkspaceModuleGraphFuture>,
   has_error: Arc<AtomicFlag>,
+  warning_count: Arc<AtomicUsize>,
+  max_warnings: Option<usize>,
   file_count: usize,
+  /// The config files to lint with the config rules of the first member
+  /// which uses them.
+  config_file_linters:
+    IndexMap<ModuleSpecifier, (Arc<CliLinter>, Arc<LintRuleSeverities>)>,
+  workspace_imports: Arc<Mutex<Vec<(ModuleSpecifier, String)>>>,
+  /// If all the modules of the workspace are linted, so the collected
+  /// imports are complete.
+  lints_all_modules: bool,
 }
 
 impl WorkspaceLinter {
   pub fn new(
     caches: Arc<Caches>,
     lint_rule_provider: LintRuleProvider,
+    plugins: Option<Arc<LintPlugins>>,
     module_graph_creator: Arc<ModuleGraphCreator>,
     workspace_dir: Arc<WorkspaceDirectory>,
     workspace_options: &WorkspaceLintOptions,
+    lints_all_modules: bool,
   ) -> Self {
     let reporter_lock =
       Arc::new(Mutex::new(create_reporter(workspace_options.reporter_kind)));
     Self {
       caches,
       lint_rule_provider,
+      plugins,
       module_graph_creator,
       workspace_dir,
       reporter_lock,
       workspace_module_graph: None,
       has_error: Default::default(),
+      warning_count: Default::default(),
+      max_warnings: workspace_options.max_warnings,
       file_count: 0,
+      config_file_linters: Default::default(),
+      workspace_imports: Default::default(),
+      lints_all_modules,
     }
   }
 
@@ -278,11 +344,12 @@ impl WorkspaceLinter {
       lint_options.rules,
       member_dir.maybe_deno_json().map(|c| c.as_ref()),
     )?;
+    let severities = Arc::new(LintRuleSeverities::resolve(&member_dir)?);
     let maybe_incremental_cache =
       lint_rules.incremental_cache_state().map(|state| {
         Arc::new(IncrementalCache::new(
           self.caches.lint_incremental_cache_db(),
-          &state,
+          &(state, self.plugins.as_ref().map(|p| p.cache_key())),
           &paths,
         ))
       });
@@ -290,9 +357,16 @@ impl WorkspaceLinter {
     let linter = Arc::new(CliLinter::new(CliLinterOptions {
       configured_rules: lint_rules,
       fix: lint_options.fix,
+      fix_dry_run: lint_options.fix_dry_run,
       deno_lint_config: lint_config,
+      plugins: self.plugins.clone(),
     }));
 
+    let collects_imports = linter.has_config_rules();
+    if collects_imports {
+      self.add_config_files(&member_dir, &linter, &severities);
+    }
+
     let mut futures = Vec::with_capacity(2);
     if linter.has_package_rules() {
       if self.workspace_module_graph.is_none() {
@@ -315,6 +389,8 @@ impl WorkspaceLinter {
       let publish_config = member_dir.maybe_package_config();
       if let Some(publish_config) = publish_config {
         let has_error = self.has_error.clone();
+        let warning_count = self.warning_count.clone();
+        let severities = severities.clone();
         let reporter_lock = self.reporter_lock.clone();
         let linter = linter.clone();
         let path_urls = paths
@@ -332,12 +408,14 @@ impl WorkspaceLinter {
               return Ok(()); // entrypoint is not specified, so skip
             }
             let diagnostics = linter.lint_package(&graph, &export_urls);
-            if !diagnostics.is_empty() {
+            let success = report_diagnostics(
+              &mut **reporter_lock.lock(),
+              &diagnostics,
+              &severities,
+              &warning_count,
+            );
+            if !success {
               has_error.raise();
-              let mut reporter = reporter_lock.lock();
-              for diagnostic in &diagnostics {
-                reporter.visit_diagnostic(diagnostic);
-              }
             }
             Ok(())
           }
@@ -348,10 +426,12 @@ impl WorkspaceLinter {
 
     futures.push({
       let has_error = self.has_error.clone();
+      let warning_count = self.warning_count.clone();
       let reporter_lock = self.reporter_lock.clone();
       let maybe_incremental_cache = maybe_incremental_cache.clone();
       let linter = linter.clone();
       let cli_options = cli_options.clone();
+      let workspace_imports = self.workspace_imports.clone();
       async move {
         run_parallelized(paths, {
           move |file_path| {
@@ -365,13 +445,34 @@ impl WorkspaceLinter {
               }
             }
 
+            let maybe_original_text =
+              linter.is_fix_dry_run().then(|| file_text.clone());
             let r = linter.lint_file(
               &file_path,
               file_text,
               cli_options.ext_flag().as_deref(),
             );
+            if collects_imports {
+              if let Ok((parsed_source, _)) = &r {
+                workspace_imports
+                  .lock()
+                  .extend(module_imports(parsed_source));
+              }
+            }
             if let Ok((file_source, file_diagnostics)) = &r {
-              if let Some(incremental_cache) = &maybe_incremental_cache {
+              let fixed_text = file_source.text();
+              let maybe_dry_run_changes = maybe_original_text
+                .as_ref()
+                .filter(|text| text.as_str() != fixed_text.as_ref());
+              if let Some(original_text) = maybe_dry_run_changes {
+                // report the fixes that would be applied as a failure,
+                // similar to `deno fmt --check`
+                has_error.raise();
+                let _g = reporter_lock.lock();
+                log::info!("");
+                log::info!("{} {}:", colors::bold("from"), file_path.display());
+                log::info!("{}", diff(original_text, fixed_text));
+              } else if let Some(incremental_cache) = &maybe_incremental_cache {
                 if file_diagnostics.is_empty() {
                   // update the incremental cache if there were no diagnostics
                   incremental_cache.update_file(
@@ -387,6 +488,8 @@ impl WorkspaceLinter {
               &file_path.to_string_lossy(),
               r,
               reporter_lock.clone(),
+              &severities,
+              &warning_count,
             );
             if !success {
               has_error.raise();
@@ -418,13 +521,130 @@ impl WorkspaceLinter {
     Ok(())
   }
 
+  fn add_config_files(
+    &mut self,
+    member_dir: &WorkspaceDirectory,
+    linter: &Arc<CliLinter>,
+    severities: &Arc<LintRuleSeverities>,
+  ) {
+    let deno_jsons = member_dir
+      .maybe_deno_json()
+      .into_iter()
+      .chain(member_dir.workspace.root_deno_json());
+    for deno_json in deno_jsons {
+      if self.config_file_linters.contains_key(&deno_json.specifier) {
+        continue;
+      }
+      self
+        .workspace_imports
+        .lock()
+        .extend(config_file_imports(deno_json));
+      self.config_file_linters.insert(
+        deno_json.specifier.clone(),
+        (linter.clone(), severities.clone()),
+      );
+    }
+    if let Ok(Some(import_map_specifier)) =
+      member_dir.workspace.to_import_map_specifier()
+    {
+      self
+        .config_file_linters
+        .entry(import_map_specifier)
+        .or_insert_with(|| (linter.clone(), severities.clone()));
+    }
+  }
+
+  fn lint_config_files(&self) {
+    let imports = self.workspace_imports.lock();
+    for (specifier, (linter, severities)) in &self.config_file_linters {
+      // remote import maps are not linted
+      let Ok(path) = specifier_to_file_path(specifier) else {
+        continue;
+      };
+      let Ok(text) = fs::read_to_string(path) else {
+        continue;
+      };
+      let text_info = SourceTextInfo::new(text.into());
+      let diagnostics = linter.lint_config_file(
+        specifier,
+        &text_info,
+        self.lints_all_modules.then_some(imports.as_slice()),
+      );
+      let success = report_diagnostics(
+        &mut **self.reporter_lock.lock(),
+        &diagnostics,
+        severities,
+        &self.warning_count,
+      );
+      if !success {
+        self.has_error.raise();
+      }
+    }
+  }
+
   pub fn finish(self) -> bool {
+    self.lint_config_files();
     debug!("Found {} files", self.file_count);
     self.reporter_lock.lock().close(self.file_count);
-    !self.has_error.is_raised() // success
+    let warning_count = self.warning_count.load(Ordering::Relaxed);
+    // success
+    !self.has_error.is_raised()
+      && !exceeds_max_warnings(warning_count, self.max_warnings)
   }
 }
 
+/// The severities of the lint rules of a workspace member, from the
+/// `"lintSeverity"` property of the root config file, overridden by the one
+/// of the member.
+#[derive(Debug, Default)]
+pub struct LintRuleSeverities(pub HashMap<String, LintRuleSeverity>);
+
+impl LintRuleSeverities {
+  pub fn resolve(dir: &WorkspaceDirectory) -> Result<Self, AnyError> {
+    let mut severities = HashMap::new();
+    let maybe_root_deno_json = dir.workspace.root_deno_json();
+    if let Some(root_deno_json) = maybe_root_deno_json {
+      severities.extend(read_lint_severity_config(root_deno_json)?);
+    }
+    if let Some(deno_json) = dir.maybe_deno_json() {
+      let is_root = maybe_root_deno_json
+        .is_some_and(|root| root.specifier == deno_json.specifier);
+      if !is_root {
+        severities.extend(read_lint_severity_config(deno_json)?);
+      }
+    }
+    Ok(Self(severities))
+  }
+
+  fn get(&self, code: &str) -> LintRuleSeverity {
+    self.get_configured(code).unwrap_or_default()
+  }
+
+  /// Gets the severity of the rule when it's listed in the config file.
+  pub fn get_configured(&self, code: &str) -> Option<LintRuleSeverity> {
+    self.0.get(code).copied()
+  }
+}
+
+fn exceeds_max_warnings(
+  warning_count: usize,
+  max_warnings: Option<usize>,
+) -> bool {
+  let Some(max_warnings) = max_warnings else {
+    return false;
+  };
+  if warning_count <= max_warnings {
+    return false;
+  }
+  log::error!(
+    "{} Too many warnings: found {}, but --max-warnings allows {}.",
+    colors::red("error:"),
+    warning_count,
+    max_warnings,
+  );
+  true
+}
+
 fn collect_lint_files(
   cli_options: &CliOptions,
   files: FilePatterns,
@@ -497,6 +717,7 @@ fn lint_stdin(
   file_path: &Path,
   configured_rules: ConfiguredRules,
   deno_lint_config: LintConfig,
+  plugins: Option<Arc<LintPlugins>>,
 ) -> Result<(ParsedSource, Vec<LintDiagnostic>), AnyError> {
   let mut source_code = String::new();
   if stdin().read_to_string(&mut source_code).is_err() {
@@ -505,8 +726,10 @@ fn lint_stdin(
 
   let linter = CliLinter::new(CliLinterOptions {
     fix: false,
+    fix_dry_run: false,
     configured_rules,
     deno_lint_config,
+    plugins,
   });
 
   linter
@@ -514,10 +737,70 @@ fn lint_stdin(
     .map_err(AnyError::from)
 }
 
+/// Reports the diagnostics with the severities of their rules, returning
+/// whether none of them were errors.
+fn report_diagnostics(
+  reporter: &mut dyn LintReporter,
+  diagnostics: &[LintDiagnostic],
+  severities: &LintRuleSeverities,
+  warning_count: &AtomicUsize,
+) -> bool {
+  let mut success = true;
+  for d in diagnostics {
+    let severity = severities.get(&d.details.code);
+    match severity {
+      LintRuleSeverity::Warn => {
+        warning_count.fetch_add(1, Ordering::Relaxed);
+      }
+      LintRuleSeverity::Error => success = false,
+    }
+    reporter.visit_diagnostic(d, severity);
+  }
+  success
+}
+
+/// The `(referrer, specifier)` imports of a module.
+fn module_imports(
+  parsed_source: &ParsedSource,
+) -> Vec<(ModuleSpecifier, String)> {
+  let module_info = ParserModuleAnalyzer::module_info(parsed_source);
+  let referrer = parsed_source.specifier();
+  let mut specifiers = Vec::new();
+  for dependency in module_info.dependencies {
+    match dependency {
+      DependencyDescriptor::Static(dependency) => {
+        specifiers.push(dependency.specifier);
+        specifiers.extend(dependency.types_specifier.map(|s| s.text));
+      }
+      DependencyDescriptor::Dynamic(dependency) => {
+        if let DynamicArgument::String(specifier) = dependency.argument {
+          specifiers.push(specifier);
+        }
+        specifiers.extend(dependency.types_specifier.map(|s| s.text));
+      }
+    }
+  }
+  for reference in module_info.ts_references {
+    match reference {
+      TypeScriptReference::Path(s) | TypeScriptReference::Types(s) => {
+        specifiers.push(s.text);
+      }
+    }
+  }
+  specifiers.extend(module_info.jsdoc_imports.into_iter().map(|s| s.text));
+  specifiers.extend(module_info.self_types_specifier.map(|s| s.text));
+  specifiers
+    .into_iter()
+    .map(|specifier| (referrer.clone(), specifier))
+    .collect()
+}
+
 fn handle_lint_result(
   file_path: &str,
   result: Result<(ParsedSource, Vec<LintDiagnostic>), AnyError>,
   reporter_lock: Arc<Mutex<Box<dyn LintReporter + Send>>>,
+  severities: &LintRuleSeverities,
+  warning_count: &AtomicUsize,
 ) -> bool {
   let mut reporter = reporter_lock.lock();
 
@@ -539,10 +822,12 @@ fn handle_lint_result(
         }
         file_order => file_order,
       });
-      for d in &file_diagnostics {
-        reporter.visit_diagnostic(d);
-      }
-      file_diagnostics.is_empty()
+      report_diagnostics(
+        &mut **reporter,
+        &file_diagnostics,
+        severities,
+        warning_count,
+      )
     }
     Err(err) => {
       reporter.visit_error(file_path, &err);
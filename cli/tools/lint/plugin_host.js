@@ -0,0 +1,80 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+// Host side of lint plugins. This script is executed in a bare isolate
+// without any Deno APIs before the plugin modules are evaluated.
+
+((globalThis) => {
+  const core = globalThis.Deno.core;
+  const rules = [];
+
+  function print(isErr, args) {
+    core.print(args.map((arg) => String(arg)).join(" ") + "\n", isErr);
+  }
+
+  globalThis.console = {
+    log: (...args) => print(false, args),
+    info: (...args) => print(false, args),
+    warn: (...args) => print(true, args),
+    error: (...args) => print(true, args),
+  };
+
+  globalThis.registerLintPlugins = (plugins) => {
+    for (const [specifier, plugin] of plugins) {
+      if (
+        typeof plugin?.name !== "string" || typeof plugin.rules !== "object"
+      ) {
+        throw new Error(
+          `Lint plugin ${specifier} must default export an object with "name" and "rules" properties.`,
+        );
+      }
+      for (const [name, rule] of Object.entries(plugin.rules)) {
+        if (typeof rule?.lint !== "function") {
+          throw new Error(
+            `Rule "${name}" of lint plugin ${specifier} must have a "lint" function.`,
+          );
+        }
+        rules.push({
+          code: `${plugin.name}/${name}`,
+          docsUrl: typeof rule.docsUrl === "string" ? rule.docsUrl : specifier,
+          rule,
+        });
+      }
+    }
+  };
+
+  globalThis.getLintRuleCodes = () => rules.map(({ code }) => code);
+
+  globalThis.runLintRules = ({ specifier, source, ast }) => {
+    const diagnostics = [];
+    for (const { code, docsUrl, rule } of rules) {
+      const context = {
+        specifier,
+        source,
+        ast,
+        report({ span, message, hint }) {
+          diagnostics.push({
+            code,
+            docsUrl,
+            message: String(message),
+            hint: hint == null ? null : String(hint),
+            start: span?.start ?? null,
+            end: span?.end ?? null,
+          });
+        },
+      };
+      try {
+        rule.lint(context);
+      } catch (err) {
+        diagnostics.push({
+          code,
+          docsUrl,
+          message: `Lint rule threw an error: ${err?.stack ?? err}`,
+          hint: null,
+          start: null,
+          end: null,
+        });
+      }
+    }
+    return diagnostics;
+  };
+})(globalThis);
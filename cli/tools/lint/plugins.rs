@@ -0,0 +1,444 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Custom lint rules provided by plugin modules declared in the config file:
+//!
+//! ```jsonc
+//! {
+//!   "plugins": {
+//!     "lint": ["./lint_plugin.ts"]
+//!   }
+//! }
+//! ```
+//!
+//! A plugin module default exports its name and rules. Each rule receives the
+//! file's SWC AST and reports diagnostics using the spans of its nodes:
+//!
+//! ```js
+//! export default {
+//!   name: "my-plugin",
+//!   rules: {
+//!     "no-debugger": {
+//!       docsUrl: "https://example.com/rules/no-debugger",
+//!       lint(context) {
+//!         for (const item of context.ast.body) {
+//!           if (item.type === "DebuggerStatement") {
+//!             context.report({ span: item.span, message: "No debugger" });
+//!           }
+//!         }
+//!       },
+//!     },
+//!   },
+//! };
+//! ```
+//!
+//! The plugins are evaluated in a bare `JsRuntime` on a dedicated thread that
+//! has no access to any Deno APIs. Plugin modules must be self-contained as
+//! their imports aren't resolved.
+
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::sync::mpsc;
+use std::sync::Arc;
+
+use deno_ast::swc::common::BytePos;
+use deno_ast::ModuleSpecifier;
+use deno_ast::ParsedSource;
+use deno_ast::SourcePos;
+use deno_ast::SourceRange;
+use deno_config::deno_json::ConfigFile;
+use deno_core::anyhow::anyhow;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::located_script_name;
+use deno_core::parking_lot::Mutex;
+use deno_core::serde_json;
+use deno_core::serde_v8;
+use deno_core::v8;
+use deno_core::JsRuntime;
+use deno_core::RuntimeOptions;
+use deno_core::StaticModuleLoader;
+use deno_lint::diagnostic::LintDiagnostic;
+use deno_lint::diagnostic::LintDiagnosticDetails;
+use deno_lint::diagnostic::LintDiagnosticRange;
+use deno_runtime::tokio_util::create_basic_runtime;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+use crate::args::deno_json::read_plugins_config;
+use crate::file_fetcher::FileFetcher;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PluginDiagnostic {
+  code: String,
+  docs_url: String,
+  message: String,
+  hint: Option<String>,
+  start: Option<u32>,
+  end: Option<u32>,
+}
+
+struct LintJob {
+  specifier: String,
+  source: String,
+  ast: String,
+  sender: mpsc::Sender<Result<Vec<PluginDiagnostic>, AnyError>>,
+}
+
+fn call_into_runtime<T: serde::de::DeserializeOwned>(
+  runtime: &mut JsRuntime,
+  source: String,
+) -> Result<T, AnyError> {
+  let global = runtime.execute_script(located_script_name!(), source)?;
+  let scope = &mut runtime.handle_scope();
+  let local = v8::Local::new(scope, global);
+  Ok(serde_v8::from_v8::<T>(scope, local)?)
+}
+
+/// Loads the plugin modules into a new runtime and returns the codes of the
+/// rules they provide.
+async fn init_runtime(
+  runtime: &mut JsRuntime,
+  plugin_specifiers: &[ModuleSpecifier],
+) -> Result<Vec<String>, AnyError> {
+  runtime.execute_script(
+    "ext:cli/lint_plugin_host.js",
+    deno_core::ascii_str_include!("plugin_host.js"),
+  )?;
+  let mut main_code = String::new();
+  let mut registrations = Vec::with_capacity(plugin_specifiers.len());
+  for (i, specifier) in plugin_specifiers.iter().enumerate() {
+    let specifier = serde_json::to_string(specifier.as_str())?;
+    main_code.push_str(&format!("import plugin{i} from {specifier};\n"));
+    registrations.push(format!("[{specifier}, plugin{i}]"));
+  }
+  main_code.push_str(&format!(
+    "globalThis.registerLintPlugins([{}]);\n",
+    registrations.join(", ")
+  ));
+  let main_specifier = ModuleSpecifier::parse("ext:cli/lint_plugins.js")?;
+  let mod_id = runtime
+    .load_main_es_module_from_code(&main_specifier, main_code)
+    .await?;
+  let evaluation = runtime.mod_evaluate(mod_id);
+  runtime.run_event_loop(Default::default()).await?;
+  evaluation.await?;
+  call_into_runtime(runtime, "globalThis.getLintRuleCodes()".to_string())
+}
+
+/// The plugin rule codes handed out as `&'static str` to deno_lint.
+static INTERNED_RULE_CODES: Lazy<Mutex<HashSet<&'static str>>> =
+  Lazy::new(Default::default);
+
+/// Returns a static string for the provided rule code. deno_lint only knows
+/// about static rule codes, so each distinct code is allocated once for the
+/// rest of the process instead of on every reload of the plugins.
+pub fn intern_rule_code(code: &str) -> &'static str {
+  let mut codes = INTERNED_RULE_CODES.lock();
+  if let Some(code) = codes.get(code) {
+    return code;
+  }
+  let code: &'static str = Box::leak(code.to_string().into_boxed_str());
+  codes.insert(code);
+  code
+}
+
+/// Lint rules provided by the plugins declared in a config file.
+pub struct LintPlugins {
+  rule_codes: Vec<Arc<str>>,
+  cache_key: Vec<(String, Arc<str>)>,
+  sender: mpsc::Sender<LintJob>,
+}
+
+impl std::fmt::Debug for LintPlugins {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("LintPlugins")
+      .field("rule_codes", &self.rule_codes)
+      .finish()
+  }
+}
+
+impl LintPlugins {
+  /// Loads the lint plugins declared in the provided config file, returning
+  /// `None` when there aren't any.
+  pub async fn resolve(
+    maybe_config_file: Option<&ConfigFile>,
+    file_fetcher: &FileFetcher,
+  ) -> Result<Option<Arc<Self>>, AnyError> {
+    let Some(config_file) = maybe_config_file else {
+      return Ok(None);
+    };
+    let plugins_config = read_plugins_config(config_file)?;
+    if plugins_config.lint.is_empty() {
+      return Ok(None);
+    }
+    let mut modules = Vec::with_capacity(plugins_config.lint.len());
+    for specifier in plugins_config.lint {
+      let specifier =
+        config_file.specifier.join(&specifier).with_context(|| {
+          format!("Invalid lint plugin specifier '{specifier}'")
+        })?;
+      let file = file_fetcher
+        .fetch_bypass_permissions(&specifier)
+        .await
+        .and_then(|file| file.into_text_decoded())
+        .with_context(|| format!("Failed to load lint plugin {specifier}"))?;
+      let code = transpile_plugin(&specifier, file.media_type, file.source)
+        .with_context(|| format!("Failed to load lint plugin {specifier}"))?;
+      modules.push((specifier, code));
+    }
+    Self::start(modules).map(|plugins| Some(Arc::new(plugins)))
+  }
+
+  fn start(
+    modules: Vec<(ModuleSpecifier, Arc<str>)>,
+  ) -> Result<Self, AnyError> {
+    let cache_key = modules
+      .iter()
+      .map(|(specifier, code)| (specifier.to_string(), code.clone()))
+      .collect::<Vec<_>>();
+    let (init_sender, init_receiver) = mpsc::channel();
+    let (sender, receiver) = mpsc::channel::<LintJob>();
+    std::thread::spawn(move || {
+      let plugin_specifiers =
+        modules.iter().map(|(s, _)| s.clone()).collect::<Vec<_>>();
+      let module_loader = StaticModuleLoader::new(
+        modules
+          .into_iter()
+          .map(|(specifier, code)| (specifier, code.to_string())),
+      );
+      let tokio_runtime = create_basic_runtime();
+      let mut runtime = JsRuntime::new(RuntimeOptions {
+        module_loader: Some(Rc::new(module_loader)),
+        ..Default::default()
+      });
+      let init_result =
+        tokio_runtime.block_on(init_runtime(&mut runtime, &plugin_specifiers));
+      let is_err = init_result.is_err();
+      if init_sender.send(init_result).is_err() || is_err {
+        return;
+      }
+      while let Ok(job) = receiver.recv() {
+        let result = serde_json::to_string(&job.specifier)
+          .and_then(|specifier| {
+            serde_json::to_string(&job.source).map(|source| (specifier, source))
+          })
+          .map_err(AnyError::from)
+          .and_then(|(specifier, source)| {
+            call_into_runtime(
+              &mut runtime,
+              format!(
+                "globalThis.runLintRules({{ specifier: {specifier}, source: {source}, ast: {} }})",
+                job.ast
+              ),
+            )
+          });
+        let _ = job.sender.send(result);
+      }
+    });
+    let rule_codes = init_receiver
+      .recv()
+      .context("Lint plugin thread exited unexpectedly.")?
+      .context("Failed to initialize lint plugins")?
+      .into_iter()
+      .map(Arc::from)
+      .collect();
+    Ok(Self {
+      rule_codes,
+      cache_key,
+      sender,
+    })
+  }
+
+  pub fn rule_codes(&self) -> &[Arc<str>] {
+    &self.rule_codes
+  }
+
+  /// Identifies the loaded plugins for the incremental cache.
+  pub fn cache_key(&self) -> &[(String, Arc<str>)] {
+    &self.cache_key
+  }
+
+  /// Runs the plugin rules on the provided source, dropping diagnostics
+  /// suppressed by `deno-lint-ignore` and `deno-lint-ignore-file` comments.
+  pub fn lint(
+    &self,
+    parsed_source: &ParsedSource,
+  ) -> Result<Vec<LintDiagnostic>, AnyError> {
+    let file_ignore_codes = parsed_source
+      .get_leading_comments()
+      .into_iter()
+      .flatten()
+      .find_map(|comment| {
+        parse_ignore_codes(&comment.text, "deno-lint-ignore-file")
+      });
+    if file_ignore_codes
+      .as_ref()
+      .is_some_and(|codes| codes.is_empty())
+    {
+      return Ok(Vec::new());
+    }
+    let ast = serde_json::to_string(parsed_source.program_ref())?;
+    let (sender, receiver) = mpsc::channel();
+    self
+      .sender
+      .send(LintJob {
+        specifier: parsed_source.specifier().to_string(),
+        source: parsed_source.text().to_string(),
+        ast,
+        sender,
+      })
+      .map_err(|_| anyhow!("Lint plugin thread exited."))?;
+    let diagnostics = receiver
+      .recv()
+      .map_err(|_| anyhow!("Lint plugin thread exited."))??;
+    let text_info = parsed_source.text_info_lazy();
+    let file_range = text_info.range();
+    let is_ignored = |code: &str, range: Option<&SourceRange>| {
+      if file_ignore_codes
+        .as_ref()
+        .is_some_and(|codes| codes.contains(&code))
+      {
+        return true;
+      }
+      let Some(previous_line) = range
+        .and_then(|range| text_info.line_index(range.start).checked_sub(1))
+      else {
+        return false;
+      };
+      text_info
+        .line_text(previous_line)
+        .trim()
+        .strip_prefix("//")
+        .and_then(|comment| parse_ignore_codes(comment, "deno-lint-ignore"))
+        .is_some_and(|codes| codes.is_empty() || codes.contains(&code))
+    };
+    Ok(
+      diagnostics
+        .into_iter()
+        .filter_map(|diagnostic| {
+          let range =
+            diagnostic
+              .start
+              .zip(diagnostic.end)
+              .and_then(|(start, end)| {
+                let start = SourcePos::unsafely_from_byte_pos(BytePos(start));
+                let end = SourcePos::unsafely_from_byte_pos(BytePos(end));
+                let is_valid = start <= end
+                  && start >= file_range.start.as_source_pos()
+                  && end <= file_range.end;
+                is_valid.then(|| SourceRange::new(start, end))
+              });
+          if is_ignored(&diagnostic.code, range.as_ref()) {
+            return None;
+          }
+          Some(LintDiagnostic {
+            specifier: parsed_source.specifier().clone(),
+            range: range.map(|range| LintDiagnosticRange {
+              text_info: text_info.clone(),
+              range,
+              description: None,
+            }),
+            details: LintDiagnosticDetails {
+              message: diagnostic.message,
+              code: diagnostic.code,
+              hint: diagnostic.hint,
+              fixes: Vec::new(),
+              custom_docs_url: Some(diagnostic.docs_url),
+              info: Vec::new(),
+            },
+          })
+        })
+        .collect(),
+    )
+  }
+}
+
+/// Parses the codes of an ignore comment, returning an empty list when the
+/// directive applies to all rules.
+fn parse_ignore_codes<'a>(
+  comment_text: &'a str,
+  directive: &str,
+) -> Option<Vec<&'a str>> {
+  let rest = comment_text.trim_start().strip_prefix(directive)?;
+  if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+    return None;
+  }
+  Some(
+    rest
+      .split(|c: char| c.is_whitespace() || c == ',')
+      .take_while(|code| *code != "--")
+      .filter(|code| !code.is_empty())
+      .collect(),
+  )
+}
+
+/// Transpiles a plugin module to JavaScript, since plugins are loaded into a
+/// bare `JsRuntime`.
+pub fn transpile_plugin(
+  specifier: &ModuleSpecifier,
+  media_type: deno_ast::MediaType,
+  source: Arc<str>,
+) -> Result<Arc<str>, AnyError> {
+  use deno_ast::MediaType;
+  match media_type {
+    MediaType::JavaScript | MediaType::Mjs | MediaType::Unknown => Ok(source),
+    MediaType::TypeScript
+    | MediaType::Mts
+    | MediaType::Jsx
+    | MediaType::Tsx => {
+      let parsed_source = deno_ast::parse_module(deno_ast::ParseParams {
+        specifier: specifier.clone(),
+        text: source,
+        media_type,
+        capture_tokens: false,
+        maybe_syntax: None,
+        scope_analysis: false,
+      })?;
+      let transpiled = parsed_source
+        .transpile(
+          &Default::default(),
+          &deno_ast::EmitOptions {
+            source_map: deno_ast::SourceMapOption::None,
+            ..Default::default()
+          },
+        )?
+        .into_source()
+        .into_string()?
+        .text;
+      Ok(transpiled.into())
+    }
+    _ => Err(anyhow!("Unsupported media type {media_type}")),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_parse_ignore_codes() {
+    assert_eq!(
+      parse_ignore_codes(" deno-lint-ignore", "deno-lint-ignore"),
+      Some(vec![])
+    );
+    assert_eq!(
+      parse_ignore_codes(
+        " deno-lint-ignore my-plugin/a other/b -- reason",
+        "deno-lint-ignore"
+      ),
+      Some(vec!["my-plugin/a", "other/b"])
+    );
+    assert_eq!(
+      parse_ignore_codes(
+        " deno-lint-ignore-file my-plugin/a",
+        "deno-lint-ignore"
+      ),
+      None
+    );
+    assert_eq!(
+      parse_ignore_codes(" some comment", "deno-lint-ignore"),
+      None
+    );
+  }
+}
@@ -1,14 +1,27 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
 use deno_ast::diagnostics::Diagnostic;
+use deno_ast::diagnostics::DiagnosticLevel;
+use deno_ast::diagnostics::DiagnosticLocation;
+use deno_ast::diagnostics::DiagnosticSnippet;
+use deno_ast::diagnostics::DiagnosticSnippetHighlightStyle;
+use deno_ast::ModuleSpecifier;
 use deno_core::error::AnyError;
 use deno_core::serde_json;
+use deno_core::url::Url;
 use deno_lint::diagnostic::LintDiagnostic;
 use deno_runtime::colors;
 use log::info;
 use serde::Serialize;
 
+use crate::args::deno_json::LintRuleSeverity;
 use crate::args::LintReporterKind;
+use crate::cache::FastInsecureHasher;
+use crate::version::DENO_VERSION_INFO;
 
 use super::LintError;
 
@@ -19,17 +32,91 @@ pub fn create_reporter(kind: LintReporterKind) -> Box<dyn LintReporter + Send> {
     LintReporterKind::Pretty => Box::new(PrettyLintReporter::new()),
     LintReporterKind::Json => Box::new(JsonLintReporter::new()),
     LintReporterKind::Compact => Box::new(CompactLintReporter::new()),
+    LintReporterKind::Sarif => Box::new(SarifLintReporter::new()),
   }
 }
 
 pub trait LintReporter {
-  fn visit_diagnostic(&mut self, d: &LintDiagnostic);
+  fn visit_diagnostic(
+    &mut self,
+    d: &LintDiagnostic,
+    severity: LintRuleSeverity,
+  );
   fn visit_error(&mut self, file_path: &str, err: &AnyError);
   fn close(&mut self, check_count: usize);
 }
 
+/// Displays a lint diagnostic of a rule configured as a warning.
+struct WarningLintDiagnostic<'a>(&'a LintDiagnostic);
+
+impl Diagnostic for WarningLintDiagnostic<'_> {
+  fn level(&self) -> DiagnosticLevel {
+    DiagnosticLevel::Warning
+  }
+
+  fn code(&self) -> Cow<'_, str> {
+    self.0.code()
+  }
+
+  fn message(&self) -> Cow<'_, str> {
+    self.0.message()
+  }
+
+  fn location(&self) -> DiagnosticLocation {
+    self.0.location()
+  }
+
+  fn snippet(&self) -> Option<DiagnosticSnippet<'_>> {
+    let mut snippet = self.0.snippet()?;
+    for highlight in &mut snippet.highlights {
+      highlight.style = DiagnosticSnippetHighlightStyle::Warning;
+    }
+    Some(snippet)
+  }
+
+  fn hint(&self) -> Option<Cow<'_, str>> {
+    self.0.hint()
+  }
+
+  fn snippet_fixed(&self) -> Option<DiagnosticSnippet<'_>> {
+    self.0.snippet_fixed()
+  }
+
+  fn info(&self) -> Cow<'_, [Cow<'_, str>]> {
+    self.0.info()
+  }
+
+  fn docs_url(&self) -> Option<Cow<'_, str>> {
+    self.0.docs_url()
+  }
+}
+
+/// Formats the number of problems, like `Found 3 problems (1 error, 2
+/// warnings)`, where the breakdown is only shown if there are warnings.
+fn found_problems_message(
+  error_count: u32,
+  warning_count: u32,
+) -> Option<String> {
+  let mut message = match error_count + warning_count {
+    0 => return None,
+    1 => "Found 1 problem".to_string(),
+    n => format!("Found {} problems", n),
+  };
+  if warning_count > 0 {
+    message.push_str(&format!(
+      " ({} error{}, {} warning{})",
+      error_count,
+      if error_count == 1 { "" } else { "s" },
+      warning_count,
+      if warning_count == 1 { "" } else { "s" },
+    ));
+  }
+  Some(message)
+}
+
 struct PrettyLintReporter {
   lint_count: u32,
+  warning_count: u32,
   fixable_diagnostics: u32,
 }
 
@@ -37,19 +124,32 @@ impl PrettyLintReporter {
   fn new() -> PrettyLintReporter {
     PrettyLintReporter {
       lint_count: 0,
+      warning_count: 0,
       fixable_diagnostics: 0,
     }
   }
 }
 
 impl LintReporter for PrettyLintReporter {
-  fn visit_diagnostic(&mut self, d: &LintDiagnostic) {
-    self.lint_count += 1;
+  fn visit_diagnostic(
+    &mut self,
+    d: &LintDiagnostic,
+    severity: LintRuleSeverity,
+  ) {
     if !d.details.fixes.is_empty() {
       self.fixable_diagnostics += 1;
     }
 
-    log::error!("{}\n", d.display());
+    match severity {
+      LintRuleSeverity::Warn => {
+        self.warning_count += 1;
+        log::warn!("{}\n", WarningLintDiagnostic(d).display());
+      }
+      LintRuleSeverity::Error => {
+        self.lint_count += 1;
+        log::error!("{}\n", d.display());
+      }
+    }
   }
 
   fn visit_error(&mut self, file_path: &str, err: &AnyError) {
@@ -64,12 +164,10 @@ impl LintReporter for PrettyLintReporter {
     } else {
       "".to_string()
     };
-    match self.lint_count {
-      1 => info!("Found 1 problem{}", fixable_suffix),
-      n if n > 1 => {
-        info!("Found {} problems{}", self.lint_count, fixable_suffix)
-      }
-      _ => (),
+    if let Some(message) =
+      found_problems_message(self.lint_count, self.warning_count)
+    {
+      info!("{}{}", message, fixable_suffix);
     }
 
     match check_count {
@@ -81,34 +179,60 @@ impl LintReporter for PrettyLintReporter {
 
 struct CompactLintReporter {
   lint_count: u32,
+  warning_count: u32,
 }
 
 impl CompactLintReporter {
   fn new() -> CompactLintReporter {
-    CompactLintReporter { lint_count: 0 }
+    CompactLintReporter {
+      lint_count: 0,
+      warning_count: 0,
+    }
   }
 }
 
 impl LintReporter for CompactLintReporter {
-  fn visit_diagnostic(&mut self, d: &LintDiagnostic) {
-    self.lint_count += 1;
+  fn visit_diagnostic(
+    &mut self,
+    d: &LintDiagnostic,
+    severity: LintRuleSeverity,
+  ) {
+    let (level, suffix) = match severity {
+      LintRuleSeverity::Warn => {
+        self.warning_count += 1;
+        (log::Level::Warn, " [warning]")
+      }
+      LintRuleSeverity::Error => {
+        self.lint_count += 1;
+        (log::Level::Error, "")
+      }
+    };
 
     match &d.range {
       Some(range) => {
         let text_info = &range.text_info;
         let range = &range.range;
         let line_and_column = text_info.line_and_column_display(range.start);
-        log::error!(
-          "{}: line {}, col {} - {} ({})",
+        log::log!(
+          level,
+          "{}: line {}, col {} - {} ({}){}",
           d.specifier,
           line_and_column.line_number,
           line_and_column.column_number,
           d.message(),
           d.code(),
+          suffix,
         )
       }
       None => {
-        log::error!("{}: {} ({})", d.specifier, d.message(), d.code())
+        log::log!(
+          level,
+          "{}: {} ({}){}",
+          d.specifier,
+          d.message(),
+          d.code(),
+          suffix
+        )
       }
     }
   }
@@ -119,10 +243,10 @@ impl LintReporter for CompactLintReporter {
   }
 
   fn close(&mut self, check_count: usize) {
-    match self.lint_count {
-      1 => info!("Found 1 problem"),
-      n if n > 1 => info!("Found {} problems", self.lint_count),
-      _ => (),
+    if let Some(message) =
+      found_problems_message(self.lint_count, self.warning_count)
+    {
+      info!("{}", message);
     }
 
     match check_count {
@@ -168,6 +292,10 @@ struct JsonLintDiagnostic {
   pub message: String,
   pub code: String,
   pub hint: Option<String>,
+  /// Only set for rules configured as warnings, so the output of existing
+  /// configurations doesn't change.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub severity: Option<&'static str>,
 }
 
 #[derive(Serialize)]
@@ -188,7 +316,11 @@ impl JsonLintReporter {
 }
 
 impl LintReporter for JsonLintReporter {
-  fn visit_diagnostic(&mut self, d: &LintDiagnostic) {
+  fn visit_diagnostic(
+    &mut self,
+    d: &LintDiagnostic,
+    severity: LintRuleSeverity,
+  ) {
     self.diagnostics.push(JsonLintDiagnostic {
       filename: d.specifier.to_string(),
       range: d.range.as_ref().map(|range| {
@@ -208,6 +340,7 @@ impl LintReporter for JsonLintReporter {
       message: d.message().to_string(),
       code: d.code().to_string(),
       hint: d.hint().map(|h| h.to_string()),
+      severity: (severity == LintRuleSeverity::Warn).then_some("warning"),
     });
   }
 
@@ -254,3 +387,326 @@ fn sort_diagnostics(diagnostics: &mut [JsonLintDiagnostic]) {
     }
   });
 }
+
+const SARIF_SCHEMA_URL: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_SRCROOT: &str = "%SRCROOT%";
+
+// WARNING: Ensure doesn't change because it's used in the SARIF output
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifMessage {
+  text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifMultiformatMessage {
+  text: String,
+  markdown: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRuleProperties {
+  tags: Vec<&'static str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRule {
+  id: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  short_description: Option<SarifMessage>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  help: Option<SarifMultiformatMessage>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  help_uri: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  properties: Option<SarifRuleProperties>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifArtifactLocation {
+  uri: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  uri_base_id: Option<&'static str>,
+}
+
+/// The range of a result, where lines and columns start at 1 and columns
+/// count code points, as the `columnKind` of the run declares.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRegion {
+  start_line: usize,
+  start_column: usize,
+  end_line: usize,
+  end_column: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation {
+  artifact_location: SarifArtifactLocation,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  region: Option<SarifRegion>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLocation {
+  physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+  rule_id: String,
+  rule_index: usize,
+  level: &'static str,
+  message: SarifMessage,
+  locations: Vec<SarifLocation>,
+  partial_fingerprints: HashMap<&'static str, String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifNotification {
+  level: &'static str,
+  message: SarifMessage,
+  locations: Vec<SarifLocation>,
+}
+
+struct PendingSarifResult {
+  position: Option<(usize, usize)>,
+  line_hash: u64,
+  result: SarifResult,
+}
+
+/// Reports diagnostics in the [SARIF 2.1.0](https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html)
+/// format understood by GitHub code scanning and other dashboards.
+struct SarifLintReporter {
+  maybe_cwd: Option<PathBuf>,
+  rules: Vec<SarifRule>,
+  rule_indexes: HashMap<String, usize>,
+  results: Vec<PendingSarifResult>,
+  notifications: Vec<SarifNotification>,
+}
+
+impl SarifLintReporter {
+  fn new() -> SarifLintReporter {
+    SarifLintReporter {
+      maybe_cwd: std::env::current_dir().ok(),
+      rules: Vec::new(),
+      rule_indexes: HashMap::new(),
+      results: Vec::new(),
+      notifications: Vec::new(),
+    }
+  }
+
+  /// Paths below the current directory are made relative to `%SRCROOT%` so
+  /// results can be matched to files in a repository checkout.
+  fn artifact_location(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> SarifArtifactLocation {
+    let maybe_relative = self.maybe_cwd.as_ref().and_then(|cwd| {
+      let path = specifier.to_file_path().ok()?;
+      let relative = path.strip_prefix(cwd).ok()?;
+      Some(
+        relative
+          .components()
+          .map(|c| c.as_os_str().to_string_lossy())
+          .collect::<Vec<_>>()
+          .join("/"),
+      )
+    });
+    match maybe_relative {
+      Some(uri) => SarifArtifactLocation {
+        uri,
+        uri_base_id: Some(SARIF_SRCROOT),
+      },
+      None => SarifArtifactLocation {
+        uri: specifier.to_string(),
+        uri_base_id: None,
+      },
+    }
+  }
+
+  fn rule_index(&mut self, d: &LintDiagnostic) -> usize {
+    let code = d.code();
+    if let Some(index) = self.rule_indexes.get(code.as_ref()) {
+      return *index;
+    }
+    let maybe_rule = deno_lint::rules::get_all_rules()
+      .into_iter()
+      .find(|rule| rule.code() == code);
+    let index = self.rules.len();
+    self.rules.push(SarifRule {
+      id: code.to_string(),
+      short_description: maybe_rule.as_ref().and_then(|rule| {
+        let summary = rule.docs().split("\n\n").next()?.trim();
+        (!summary.is_empty()).then(|| SarifMessage {
+          text: summary.to_string(),
+        })
+      }),
+      help: maybe_rule.as_ref().map(|rule| SarifMultiformatMessage {
+        text: rule.docs().to_string(),
+        markdown: rule.docs().to_string(),
+      }),
+      help_uri: d.docs_url().map(|url| url.into_owned()),
+      properties: maybe_rule.as_ref().map(|rule| SarifRuleProperties {
+        tags: rule.tags().to_vec(),
+      }),
+    });
+    self.rule_indexes.insert(code.to_string(), index);
+    index
+  }
+}
+
+impl LintReporter for SarifLintReporter {
+  fn visit_diagnostic(
+    &mut self,
+    d: &LintDiagnostic,
+    severity: LintRuleSeverity,
+  ) {
+    let rule_index = self.rule_index(d);
+    let artifact_location = self.artifact_location(&d.specifier);
+    let mut line_text = "";
+    let mut position = None;
+    let region = d.range.as_ref().map(|range| {
+      let text_info = &range.text_info;
+      let start = text_info.line_and_column_display(range.range.start);
+      let end = text_info.line_and_column_display(range.range.end);
+      line_text = text_info.line_text(start.line_number - 1);
+      position = Some((start.line_number, start.column_number));
+      SarifRegion {
+        start_line: start.line_number,
+        start_column: start.column_number,
+        end_line: end.line_number,
+        end_column: end.column_number,
+      }
+    });
+    // Fingerprint on the rule, file and trimmed line text rather than the
+    // position so results are still deduplicated when unrelated lines move.
+    let line_hash = FastInsecureHasher::new_without_deno_version()
+      .write_str(&d.code())
+      .write_str(&artifact_location.uri)
+      .write_str(line_text.trim())
+      .finish();
+    let mut message = d.message().to_string();
+    if let Some(hint) = d.hint() {
+      message.push_str("\n\nhint: ");
+      message.push_str(&hint);
+    }
+    self.results.push(PendingSarifResult {
+      position,
+      line_hash,
+      result: SarifResult {
+        rule_id: d.code().to_string(),
+        rule_index,
+        level: match severity {
+          LintRuleSeverity::Warn => "warning",
+          LintRuleSeverity::Error => "error",
+        },
+        message: SarifMessage { text: message },
+        locations: vec![SarifLocation {
+          physical_location: SarifPhysicalLocation {
+            artifact_location,
+            region,
+          },
+        }],
+        partial_fingerprints: HashMap::new(),
+      },
+    });
+  }
+
+  fn visit_error(&mut self, file_path: &str, err: &AnyError) {
+    let locations = Url::from_file_path(file_path)
+      .ok()
+      .map(|specifier| SarifLocation {
+        physical_location: SarifPhysicalLocation {
+          artifact_location: self.artifact_location(&specifier),
+          region: None,
+        },
+      })
+      .into_iter()
+      .collect();
+    self.notifications.push(SarifNotification {
+      level: "error",
+      message: SarifMessage {
+        text: format!("Error linting {file_path}: {err}"),
+      },
+      locations,
+    });
+  }
+
+  fn close(&mut self, _check_count: usize) {
+    // sort so that we guarantee a deterministic output, which also keeps
+    // the occurrence counts of the fingerprints stable
+    self.results.sort_by(|a, b| {
+      let a_location = &a.result.locations[0].physical_location;
+      let b_location = &b.result.locations[0].physical_location;
+      a_location
+        .artifact_location
+        .uri
+        .cmp(&b_location.artifact_location.uri)
+        .then_with(|| match (a.position, b.position) {
+          (Some(a), Some(b)) => a.cmp(&b),
+          (Some(_), None) => std::cmp::Ordering::Less,
+          (None, Some(_)) => std::cmp::Ordering::Greater,
+          (None, None) => std::cmp::Ordering::Equal,
+        })
+    });
+    let mut original_uri_base_ids = serde_json::Map::new();
+    if let Some(cwd_url) = self
+      .maybe_cwd
+      .as_ref()
+      .and_then(|cwd| Url::from_directory_path(cwd).ok())
+    {
+      original_uri_base_ids.insert(
+        SARIF_SRCROOT.to_string(),
+        serde_json::json!({ "uri": cwd_url.to_string() }),
+      );
+    }
+    let mut occurrences = HashMap::<u64, usize>::new();
+    let results = std::mem::take(&mut self.results)
+      .into_iter()
+      .map(|pending| {
+        let occurrence = occurrences.entry(pending.line_hash).or_default();
+        *occurrence += 1;
+        let mut result = pending.result;
+        result.partial_fingerprints.insert(
+          "primaryLocationLineHash",
+          format!("{:016x}:{}", pending.line_hash, occurrence),
+        );
+        result
+      })
+      .collect::<Vec<_>>();
+    let json = serde_json::json!({
+      "$schema": SARIF_SCHEMA_URL,
+      "version": "2.1.0",
+      "runs": [{
+        "tool": {
+          "driver": {
+            "name": "deno lint",
+            "version": DENO_VERSION_INFO.deno,
+            "informationUri": "https://docs.deno.com/go/lint",
+            "rules": &self.rules,
+          }
+        },
+        "columnKind": "unicodeCodePoints",
+        "originalUriBaseIds": original_uri_base_ids,
+        "invocations": [{
+          "executionSuccessful": self.notifications.is_empty(),
+          "toolExecutionNotifications": &self.notifications,
+        }],
+        "results": results,
+      }],
+    });
+    #[allow(clippy::print_stdout)]
+    {
+      println!("{}", serde_json::to_string_pretty(&json).unwrap());
+    }
+  }
+}
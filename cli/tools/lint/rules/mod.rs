@@ -5,6 +5,7 @@ use std::collections::HashSet;
 use std::sync::Arc;
 
 use deno_ast::ModuleSpecifier;
+use deno_ast::SourceTextInfo;
 use deno_config::deno_json::ConfigFile;
 use deno_config::deno_json::LintRulesConfig;
 use deno_config::workspace::WorkspaceResolver;
@@ -12,12 +13,17 @@ use deno_core::anyhow::bail;
 use deno_core::error::AnyError;
 use deno_graph::ModuleGraph;
 use deno_lint::diagnostic::LintDiagnostic;
+use deno_lint::diagnostic::LintDiagnosticDetails;
+use deno_lint::diagnostic::LintDiagnosticRange;
 use deno_lint::rules::LintRule;
 
+use crate::args::import_map::ImportMapEntryText;
 use crate::resolver::SloppyImportsResolver;
 
+mod no_duplicate_import_map_entries;
 mod no_sloppy_imports;
 mod no_slow_types;
+mod no_unused_import_map_entries;
 
 // used for publishing
 pub use no_slow_types::collect_no_slow_type_diagnostics;
@@ -40,6 +46,58 @@ pub trait PackageLintRule: std::fmt::Debug + Send + Sync {
   ) -> Vec<LintDiagnostic>;
 }
 
+/// A rule which lints the deno.json and import map files of the workspace.
+pub trait ConfigLintRule: std::fmt::Debug + Send + Sync {
+  fn code(&self) -> &'static str;
+
+  fn tags(&self) -> &'static [&'static str] {
+    &[]
+  }
+
+  fn docs(&self) -> &'static str;
+
+  fn help_docs_url(&self) -> Cow<'static, str>;
+
+  /// The `imports` are those of all the modules of the workspace, which are
+  /// only known when the whole workspace is linted.
+  fn lint_config_file(
+    &self,
+    specifier: &ModuleSpecifier,
+    text_info: &SourceTextInfo,
+    imports: Option<&[(ModuleSpecifier, String)]>,
+  ) -> Vec<LintDiagnostic>;
+}
+
+fn import_map_entry_diagnostic(
+  specifier: &ModuleSpecifier,
+  text_info: &SourceTextInfo,
+  entry: &ImportMapEntryText,
+  code: &str,
+  message: String,
+  hint: &str,
+) -> LintDiagnostic {
+  let start = text_info.range().start;
+  LintDiagnostic {
+    specifier: specifier.clone(),
+    range: Some(LintDiagnosticRange {
+      text_info: text_info.clone(),
+      range: deno_ast::SourceRange::new(
+        start + entry.range.start,
+        start + entry.range.end,
+      ),
+      description: None,
+    }),
+    details: LintDiagnosticDetails {
+      message,
+      code: code.to_string(),
+      hint: Some(hint.to_string()),
+      fixes: Vec::new(),
+      custom_docs_url: None,
+      info: Vec::new(),
+    },
+  }
+}
+
 pub(super) trait ExtendedLintRule: LintRule {
   /// If the rule supports the incremental cache.
   fn supports_incremental_cache(&self) -> bool;
@@ -52,6 +110,7 @@ pub(super) trait ExtendedLintRule: LintRule {
 pub enum FileOrPackageLintRule {
   File(Box<dyn LintRule>),
   Package(Box<dyn PackageLintRule>),
+  Config(Box<dyn ConfigLintRule>),
 }
 
 #[derive(Debug)]
@@ -59,6 +118,7 @@ enum CliLintRuleKind {
   DenoLint(Box<dyn LintRule>),
   Extended(Box<dyn ExtendedLintRule>),
   Package(Box<dyn PackageLintRule>),
+  Config(Box<dyn ConfigLintRule>),
 }
 
 #[derive(Debug)]
@@ -71,6 +131,7 @@ impl CliLintRule {
       DenoLint(rule) => rule.code(),
       Extended(rule) => rule.code(),
       Package(rule) => rule.code(),
+      Config(rule) => rule.code(),
     }
   }
 
@@ -80,6 +141,7 @@ impl CliLintRule {
       DenoLint(rule) => rule.tags(),
       Extended(rule) => rule.tags(),
       Package(rule) => rule.tags(),
+      Config(rule) => rule.tags(),
     }
   }
 
@@ -89,6 +151,7 @@ impl CliLintRule {
       DenoLint(rule) => rule.docs(),
       Extended(rule) => rule.docs(),
       Package(rule) => rule.docs(),
+      Config(rule) => rule.docs(),
     }
   }
 
@@ -100,6 +163,7 @@ impl CliLintRule {
       }
       Extended(rule) => rule.help_docs_url(),
       Package(rule) => rule.help_docs_url(),
+      Config(rule) => rule.help_docs_url(),
     }
   }
 
@@ -110,6 +174,8 @@ impl CliLintRule {
       Extended(rule) => rule.supports_incremental_cache(),
       // graph rules don't go through the incremental cache, so allow it
       Package(_) => true,
+      // the imports of every module are needed, even the unchanged ones
+      Config(_) => false,
     }
   }
 
@@ -119,6 +185,7 @@ impl CliLintRule {
       DenoLint(rule) => FileOrPackageLintRule::File(rule),
       Extended(rule) => FileOrPackageLintRule::File(rule.into_base()),
       Package(rule) => FileOrPackageLintRule::Package(rule),
+      Config(rule) => FileOrPackageLintRule::Config(rule),
     }
   }
 }
@@ -186,14 +253,26 @@ impl LintRuleProvider {
     let cli_graph_rules = vec![CliLintRule(CliLintRuleKind::Package(
       Box::new(no_slow_types::NoSlowTypesRule),
     ))];
+    let cli_config_rules = vec![
+      CliLintRule(CliLintRuleKind::Config(Box::new(
+        no_duplicate_import_map_entries::NoDuplicateImportMapEntriesRule,
+      ))),
+      CliLintRule(CliLintRuleKind::Config(Box::new(
+        no_unused_import_map_entries::NoUnusedImportMapEntriesRule,
+      ))),
+    ];
     let mut all_rule_names = HashSet::with_capacity(
-      deno_lint_rules.len() + cli_lint_rules.len() + cli_graph_rules.len(),
+      deno_lint_rules.len()
+        + cli_lint_rules.len()
+        + cli_graph_rules.len()
+        + cli_config_rules.len(),
     );
     let all_rules = deno_lint_rules
       .into_iter()
       .map(|rule| CliLintRule(CliLintRuleKind::DenoLint(rule)))
       .chain(cli_lint_rules)
       .chain(cli_graph_rules)
+      .chain(cli_config_rules)
       .inspect(|rule| {
         all_rule_names.insert(rule.code());
       });
@@ -0,0 +1,57 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::borrow::Cow;
+
+use deno_ast::ModuleSpecifier;
+use deno_ast::SourceTextInfo;
+use deno_lint::diagnostic::LintDiagnostic;
+
+use crate::args::import_map::find_duplicate_import_map_entries;
+use crate::args::import_map::parse_import_map_entries;
+
+use super::import_map_entry_diagnostic;
+use super::ConfigLintRule;
+
+const CODE: &str = "no-duplicate-import-map-entries";
+
+#[derive(Debug)]
+pub struct NoDuplicateImportMapEntriesRule;
+
+impl ConfigLintRule for NoDuplicateImportMapEntriesRule {
+  fn code(&self) -> &'static str {
+    CODE
+  }
+
+  fn docs(&self) -> &'static str {
+    include_str!("no_duplicate_import_map_entries.md")
+  }
+
+  fn help_docs_url(&self) -> Cow<'static, str> {
+    Cow::Owned(format!("https://lint.deno.land/rules/{CODE}"))
+  }
+
+  fn lint_config_file(
+    &self,
+    specifier: &ModuleSpecifier,
+    text_info: &SourceTextInfo,
+    _imports: Option<&[(ModuleSpecifier, String)]>,
+  ) -> Vec<LintDiagnostic> {
+    let entries = parse_import_map_entries(text_info.text_str());
+    find_duplicate_import_map_entries(&entries)
+      .into_iter()
+      .map(|entry| {
+        import_map_entry_diagnostic(
+          specifier,
+          text_info,
+          entry,
+          CODE,
+          format!(
+            "The import map entry \"{}\" is ignored because it is defined again later.",
+            entry.key
+          ),
+          "Remove the entry or the one defined later",
+        )
+      })
+      .collect()
+  }
+}
@@ -0,0 +1,61 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::borrow::Cow;
+
+use deno_ast::ModuleSpecifier;
+use deno_ast::SourceTextInfo;
+use deno_lint::diagnostic::LintDiagnostic;
+
+use crate::args::import_map::find_unused_import_map_entries;
+use crate::args::import_map::parse_import_map_entries;
+
+use super::import_map_entry_diagnostic;
+use super::ConfigLintRule;
+
+const CODE: &str = "no-unused-import-map-entries";
+
+#[derive(Debug)]
+pub struct NoUnusedImportMapEntriesRule;
+
+impl ConfigLintRule for NoUnusedImportMapEntriesRule {
+  fn code(&self) -> &'static str {
+    CODE
+  }
+
+  fn docs(&self) -> &'static str {
+    include_str!("no_unused_import_map_entries.md")
+  }
+
+  fn help_docs_url(&self) -> Cow<'static, str> {
+    Cow::Owned(format!("https://lint.deno.land/rules/{CODE}"))
+  }
+
+  fn lint_config_file(
+    &self,
+    specifier: &ModuleSpecifier,
+    text_info: &SourceTextInfo,
+    imports: Option<&[(ModuleSpecifier, String)]>,
+  ) -> Vec<LintDiagnostic> {
+    // only the imports of the whole workspace tell if an entry is unused
+    let Some(imports) = imports else {
+      return Vec::new();
+    };
+    let entries = parse_import_map_entries(text_info.text_str());
+    find_unused_import_map_entries(specifier, &entries, imports)
+      .into_iter()
+      .map(|entry| {
+        import_map_entry_diagnostic(
+          specifier,
+          text_info,
+          entry,
+          CODE,
+          format!(
+            "The import map entry \"{}\" is not used by any module of the workspace.",
+            entry.key
+          ),
+          "Remove the entry",
+        )
+      })
+      .collect()
+  }
+}
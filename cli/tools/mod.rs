@@ -1,9 +1,12 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 pub mod bench;
+pub mod bundle;
+pub mod cache;
 pub mod check;
 pub mod clean;
 pub mod compile;
+pub mod completions;
 pub mod coverage;
 pub mod doc;
 pub mod fmt;
@@ -33,6 +33,7 @@ use serde::Serialize;
 use sha2::Digest;
 use tokio::process::Command;
 
+use crate::args::deno_json::read_registry_config;
 use crate::args::jsr_api_url;
 use crate::args::jsr_url;
 use crate::args::CliOptions;
@@ -88,6 +89,7 @@ pub async fn publish(
 
   let cli_options = cli_factory.cli_options()?;
   let directory_path = cli_options.initial_cwd();
+  let registry = PublishRegistry::resolve(cli_options)?;
   let publish_configs = cli_options.start_dir.jsr_packages_for_publish();
   if publish_configs.is_empty() {
     match cli_options.start_dir.maybe_deno_json() {
@@ -158,6 +160,10 @@ pub async fn publish(
     }
   }
 
+  if registry.is_custom {
+    log::info!("{} {}", colors::gray("Using registry"), registry.url);
+  }
+
   if publish_flags.dry_run {
     for (_, package) in prepared_data.package_by_name {
       log::info!(
@@ -175,6 +181,7 @@ pub async fn publish(
 
   perform_publish(
     &cli_factory.http_client_provider().get_or_create()?,
+    &registry,
     prepared_data.publish_order_graph,
     prepared_data.package_by_name,
     auth_method,
@@ -185,6 +192,35 @@ pub async fn publish(
   Ok(())
 }
 
+/// The registry that packages are published to, which is JSR unless the
+/// root config file has a `"registry"` property.
+struct PublishRegistry {
+  url: Url,
+  api_url: Url,
+  is_custom: bool,
+}
+
+impl PublishRegistry {
+  fn resolve(cli_options: &CliOptions) -> Result<Self, AnyError> {
+    let maybe_url = match cli_options.workspace().root_deno_json() {
+      Some(config_file) => read_registry_config(config_file)?,
+      None => None,
+    };
+    Ok(match maybe_url {
+      Some(url) => Self {
+        api_url: url.join("api/")?,
+        url,
+        is_custom: true,
+      },
+      None => Self {
+        url: jsr_url().clone(),
+        api_url: jsr_api_url().clone(),
+        is_custom: false,
+      },
+    })
+  }
+}
+
 struct PreparedPublishPackage {
   scope: String,
   package: String,
@@ -825,13 +861,14 @@ async fn ensure_scopes_and_packages_exist(
 
 async fn perform_publish(
   http_client: &HttpClient,
+  registry: &PublishRegistry,
   mut publish_order_graph: PublishOrderGraph,
   mut prepared_package_by_name: HashMap<String, Rc<PreparedPublishPackage>>,
   auth_method: AuthMethod,
   provenance: bool,
 ) -> Result<(), AnyError> {
-  let registry_api_url = jsr_api_url();
-  let registry_url = jsr_url();
+  let registry_api_url = &registry.api_url;
+  let registry_url = &registry.url;
 
   let packages = prepared_package_by_name
     .values()
@@ -1028,7 +1065,7 @@ async fn publish_package(
   // Enable provenance by default on Github actions with OIDC token
   if enable_provenance {
     // Get the version manifest from the registry
-    let meta_url = jsr_url().join(&format!(
+    let meta_url = registry_url.join(&format!(
       "@{}/{}/{}_meta.json",
       package.scope, package.package, package.version
     ))?;
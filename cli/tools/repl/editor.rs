@@ -239,7 +239,21 @@ impl Validator for EditorHelper {
     &self,
     ctx: &mut ValidationContext,
   ) -> Result<ValidationResult, ReadlineError> {
-    Ok(validate(ctx.input()))
+    Ok(validate(&strip_continuation_prompts(ctx.input())))
+  }
+}
+
+/// The prompt at the start of the lines that continue incomplete input.
+const CONTINUATION_PROMPT: &str = "... ";
+
+/// Removes the continuation prompts that [`ContinuationEventHandler`]
+/// inserted into the input.
+fn strip_continuation_prompts(input: &str) -> Cow<str> {
+  let prompt_line_start = format!("\n{CONTINUATION_PROMPT}");
+  if input.contains(&prompt_line_start) {
+    input.replace(&prompt_line_start, "\n").into()
+  } else {
+    input.into()
   }
 }
 
@@ -325,6 +339,19 @@ fn validate(input: &str) -> ValidationResult {
 }
 
 impl Highlighter for EditorHelper {
+  fn highlight_prompt<'b, 's: 'b, 'p: 'b>(
+    &'s self,
+    prompt: &'p str,
+    default: bool,
+  ) -> Cow<'b, str> {
+    if default {
+      prompt.into()
+    } else {
+      // the prompt of a history search, e.g. "(reverse-i-search)`let': "
+      colors::gray(prompt).to_string().into()
+    }
+  }
+
   fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
     hint.into()
   }
@@ -416,10 +443,20 @@ impl Highlighter for EditorHelper {
       );
     }
 
+    let prompt_line_start = format!("\n{CONTINUATION_PROMPT}");
+    if out_line.contains(&prompt_line_start) {
+      out_line = out_line.replace(
+        &prompt_line_start,
+        &format!("\n{}", colors::gray(CONTINUATION_PROMPT)),
+      );
+    }
     out_line.into()
   }
 }
 
+/// Maximum number of entries kept in the history file.
+const HISTORY_SIZE: usize = 1000;
+
 #[derive(Clone)]
 pub struct ReplEditor {
   inner: Arc<Mutex<Editor<EditorHelper, rustyline::history::FileHistory>>>,
@@ -435,6 +472,11 @@ impl ReplEditor {
   ) -> Result<Self, AnyError> {
     let editor_config = Config::builder()
       .completion_type(CompletionType::List)
+      .max_history_size(HISTORY_SIZE)
+      .expect("Failed to set history size.")
+      .history_ignore_dups(true)
+      .expect("Failed to set history duplicates.")
+      .bracketed_paste(true)
       .build();
 
     let mut editor =
@@ -451,6 +493,25 @@ impl ReplEditor {
       KeyEvent(KeyCode::Tab, Modifiers::NONE),
       EventHandler::Conditional(Box::new(TabEventHandler)),
     );
+    for enter in [
+      KeyEvent(KeyCode::Enter, Modifiers::NONE),
+      KeyEvent(KeyCode::Char('J'), Modifiers::CTRL),
+    ] {
+      editor.bind_sequence(
+        enter,
+        EventHandler::Conditional(Box::new(ContinuationEventHandler)),
+      );
+    }
+    // Searches the history for entries starting with the text before the
+    // cursor, which also recalls whole multi-line blocks for re-editing.
+    editor.bind_sequence(
+      KeyEvent(KeyCode::Up, Modifiers::ALT),
+      EventHandler::Simple(Cmd::HistorySearchBackward),
+    );
+    editor.bind_sequence(
+      KeyEvent(KeyCode::Down, Modifiers::ALT),
+      EventHandler::Simple(Cmd::HistorySearchForward),
+    );
     let should_exit_on_interrupt = Arc::new(AtomicBool::new(false));
     editor.bind_sequence(
       KeyEvent(KeyCode::Char('r'), Modifiers::CTRL),
@@ -477,11 +538,18 @@ impl ReplEditor {
     })
   }
 
-  pub fn readline(&self) -> Result<String, ReadlineError> {
-    self.inner.lock().readline("> ")
+  pub fn readline(&self, prompt: &str) -> Result<String, ReadlineError> {
+    let line = self.inner.lock().readline(prompt)?;
+    Ok(strip_continuation_prompts(&line).into_owned())
   }
 
   pub fn update_history(&self, entry: String) {
+    // Trailing newlines are left over from incomplete input and would make
+    // an identical block look like a new history entry.
+    let entry = entry.trim_end();
+    if entry.is_empty() {
+      return;
+    }
     let _ = self.inner.lock().add_history_entry(entry);
     if let Some(history_file_path) = &self.history_file_path {
       if let Err(e) = self.inner.lock().append_history(history_file_path) {
@@ -521,6 +589,33 @@ impl ConditionalEventHandler for ReverseSearchHistoryEventHandler {
   }
 }
 
+/// Starts a new line of incomplete input with a continuation prompt. Since
+/// rustyline doesn't render prompts on the lines after the first one, the
+/// prompt is inserted into the input and stripped when it's read.
+struct ContinuationEventHandler;
+impl ConditionalEventHandler for ContinuationEventHandler {
+  fn handle(
+    &self,
+    _: &Event,
+    n: RepeatCount,
+    _: bool,
+    ctx: &EventContext,
+  ) -> Option<Cmd> {
+    if continues_input(ctx.line()) {
+      Some(Cmd::Insert(n, format!("\n{CONTINUATION_PROMPT}")))
+    } else {
+      None // default accept or insert line
+    }
+  }
+}
+
+fn continues_input(line: &str) -> bool {
+  matches!(
+    validate(&strip_continuation_prompts(line)),
+    ValidationResult::Incomplete
+  )
+}
+
 /// A custom tab key event handler
 /// It uses a heuristic to determine if the user is requesting completion or if they want to insert an actual tab
 /// The heuristic goes like this:
@@ -563,6 +658,8 @@ impl ConditionalEventHandler for TabEventHandler {
 mod test {
   use rustyline::validate::ValidationResult;
 
+  use super::continues_input;
+  use super::strip_continuation_prompts;
   use super::validate;
 
   #[test]
@@ -578,4 +675,17 @@ let left = test( arr.slice( 0 , arr.length/2 ) )"#;
     let code = r#"/testing/;"#;
     assert!(matches!(validate(code), ValidationResult::Valid(_)));
   }
+
+  #[test]
+  fn continuation_prompts() {
+    assert!(continues_input("function f() {"));
+    assert!(continues_input("function f() {\n... return 1;"));
+    assert!(!continues_input("function f() {\n... return 1;\n... }"));
+    // a spread on a continuation line keeps its dots
+    assert_eq!(
+      strip_continuation_prompts("f(\n... ...args\n... )"),
+      "f(\n...args\n)"
+    );
+    assert_eq!(strip_continuation_prompts("`\n... foo\n... `"), "`\nfoo\n`");
+  }
 }
@@ -56,7 +56,10 @@ impl Repl {
         Ok(line) => {
           self.editor.set_should_exit_on_interrupt(false);
           self.editor.update_history(line.clone());
-          let output = self.session.evaluate_line_and_get_output(&line).await;
+          let output = match self.session.evaluate_repl_command(&line).await {
+            Some(output) => output,
+            None => self.session.evaluate_line_and_get_output(&line).await,
+          };
 
           // We check for close and break here instead of making it a loop condition to get
           // consistent behavior in when the user evaluates a call to close().
@@ -64,7 +67,10 @@ impl Repl {
             break;
           }
 
-          println!("{}", output);
+          if !matches!(&output, EvaluationOutput::Value(value) if value.is_empty())
+          {
+            println!("{}", output);
+          }
         }
         Err(ReadlineError::Interrupted) => {
           if self.editor.should_exit_on_interrupt() {
@@ -94,7 +100,8 @@ async fn read_line_and_poll(
   message_handler: &mut RustylineSyncMessageHandler,
   editor: ReplEditor,
 ) -> Result<String, ReadlineError> {
-  let mut line_fut = spawn_blocking(move || editor.readline());
+  let prompt = repl_session.prompt().await;
+  let mut line_fut = spawn_blocking(move || editor.readline(&prompt));
   let mut poll_worker = true;
   let notifications_rc = repl_session.notifications.clone();
   let mut notifications = notifications_rc.lock().await;
@@ -184,7 +191,7 @@ pub async fn run(
     .await?;
   worker.setup_repl().await?;
   let worker = worker.into_main_worker();
-  let session = ReplSession::initialize(
+  let mut session = ReplSession::initialize(
     cli_options,
     npm_resolver,
     resolver,
@@ -193,6 +200,7 @@ pub async fn run(
     test_event_receiver,
   )
   .await?;
+  session.install_repl_api().await?;
   let rustyline_channel = rustyline_channel();
 
   let helper = EditorHelper {
@@ -140,6 +140,69 @@ globalThis.clear = console.clear.bind(console);
   )
 }
 
+/// The `repl` global that setup scripts use to customize the REPL, e.g.
+/// from a file passed to `--eval-file`.
+fn get_repl_api() -> String {
+  format!(
+    r#"
+Object.assign({0}, {{
+  prompt: "> ",
+  commands: new Map(),
+  async runCommand(name, args) {{
+    await {0}.commands.get(name).action(args);
+  }},
+}});
+{0}.commands.set("help", {{
+  help: "Print the available REPL commands",
+  action() {{
+    const names = [...{0}.commands.keys()].sort();
+    const width = Math.max(...names.map((name) => name.length));
+    for (const name of names) {{
+      console.log(`.${{name.padEnd(width)}}  ${{{0}.commands.get(name).help ?? ""}}`);
+    }}
+  }},
+}});
+Object.defineProperty(globalThis, "repl", {{
+  configurable: true,
+  writable: true,
+  enumerable: false,
+  value: Object.freeze({{
+    setPrompt(prompt) {{
+      {0}.prompt = String(prompt);
+    }},
+    defineCommand(name, command) {{
+      if (typeof name !== "string" || !/^[A-Za-z_][\w-]*$/.test(name)) {{
+        throw new TypeError(`Invalid REPL command name: ${{name}}`);
+      }}
+      if (typeof command === "function") {{
+        command = {{ action: command }};
+      }}
+      if (typeof command?.action !== "function") {{
+        throw new TypeError(`REPL command "${{name}}" must have an "action" function.`);
+      }}
+      {0}.commands.set(name, {{ help: command.help, action: command.action }});
+    }},
+    defineContext(values) {{
+      for (const [name, value] of Object.entries(values)) {{
+        Object.defineProperty(globalThis, name, {{
+          value,
+          writable: true,
+          enumerable: true,
+          configurable: true,
+        }});
+      }}
+    }},
+  }}),
+}});
+"#,
+    *REPL_INTERNALS_NAME
+  )
+}
+
+/// Matches a REPL command invocation like `.load ./file.ts`.
+static REPL_COMMAND_RE: Lazy<Regex> =
+  Lazy::new(|| Regex::new(r"(?s)^\.([A-Za-z_][\w-]*)(?:\s+(.*))?$").unwrap());
+
 pub enum EvaluationOutput {
   Value(String),
   Error(String),
@@ -192,6 +255,8 @@ pub struct ReplSession {
   test_event_receiver: Option<TestEventReceiver>,
   jsx: ReplJsxState,
   experimental_decorators: bool,
+  /// The prompt read by [`Self::closing`].
+  prompt: Option<String>,
 }
 
 impl ReplSession {
@@ -288,6 +353,7 @@ impl ReplSession {
         import_source: None,
       },
       experimental_decorators,
+      prompt: None,
     };
 
     // inject prelude
@@ -303,18 +369,104 @@ impl ReplSession {
     self.test_reporter_factory = f;
   }
 
+  /// Whether `close()` was called, which is checked after every line. The
+  /// prompt set with `repl.setPrompt()` is read in the same evaluation, so
+  /// [`Self::prompt`] doesn't need another one.
   pub async fn closing(&mut self) -> Result<bool, AnyError> {
-    let expression = format!(r#"{}.closed"#, *REPL_INTERNALS_NAME);
-    let closed = self
-      .evaluate_expression(&expression)
-      .await?
-      .result
-      .value
-      .unwrap()
-      .as_bool()
-      .unwrap();
+    let response = self
+      .post_message_with_event_loop(
+        "Runtime.evaluate",
+        Some(cdp::EvaluateArgs {
+          expression: format!(
+            r#"[{0}.closed, {0}.prompt]"#,
+            *REPL_INTERNALS_NAME
+          ),
+          object_group: None,
+          include_command_line_api: None,
+          silent: None,
+          context_id: Some(self.context_id),
+          return_by_value: Some(true),
+          generate_preview: None,
+          user_gesture: None,
+          await_promise: None,
+          throw_on_side_effect: None,
+          timeout: None,
+          disable_breaks: None,
+          repl_mode: None,
+          allow_unsafe_eval_blocked_by_csp: None,
+          unique_context_id: None,
+        }),
+      )
+      .await?;
+    let response: cdp::EvaluateResponse = serde_json::from_value(response)?;
+    let value = response.result.value.unwrap_or_default();
+    self.prompt = value[1].as_str().map(|prompt| prompt.to_string());
+    Ok(value[0].as_bool().unwrap())
+  }
 
-    Ok(closed)
+  /// Installs the `repl` global used to customize the prompt, add commands
+  /// and define context values. Not used by the Jupyter kernel.
+  pub async fn install_repl_api(&mut self) -> Result<(), AnyError> {
+    self.evaluate_expression(&get_repl_api()).await?;
+    Ok(())
+  }
+
+  /// The prompt set with `repl.setPrompt()`, as of the last call to
+  /// [`Self::closing`].
+  pub async fn prompt(&mut self) -> String {
+    if self.prompt.is_none() {
+      // before the first line, after the `--eval-file` scripts ran
+      let _ = self.closing().await;
+    }
+    self.prompt.clone().unwrap_or_else(|| "> ".to_string())
+  }
+
+  /// Runs the command registered with `repl.defineCommand()` when the line
+  /// invokes one, returning `None` when it should be evaluated as code.
+  pub async fn evaluate_repl_command(
+    &mut self,
+    line: &str,
+  ) -> Option<EvaluationOutput> {
+    let captures = REPL_COMMAND_RE.captures(line.trim())?;
+    let name = serde_json::to_string(&captures[1]).unwrap();
+    let args = serde_json::to_string(
+      captures.get(2).map(|m| m.as_str().trim()).unwrap_or(""),
+    )
+    .unwrap();
+    let is_command = self
+      .evaluate_expression(&format!(
+        "{}.commands?.has({name})",
+        *REPL_INTERNALS_NAME
+      ))
+      .await
+      .ok()
+      .and_then(|response| response.result.value)
+      .and_then(|value| value.as_bool())
+      .unwrap_or(false);
+    if !is_command {
+      return None;
+    }
+
+    let result = self
+      .evaluate_expression(&format!(
+        "await {}.runCommand({name}, {args})",
+        *REPL_INTERNALS_NAME
+      ))
+      .await
+      .map(|response| match response.exception_details {
+        Some(exception_details) => {
+          let description = exception_details
+            .exception
+            .and_then(|exception| exception.description)
+            .unwrap_or_else(|| "Unknown exception".to_string());
+          EvaluationOutput::Error(format!(
+            "{} {}",
+            exception_details.text, description
+          ))
+        }
+        None => EvaluationOutput::Value(String::new()),
+      });
+    Some(result_to_evaluation_output(result))
   }
 
   pub async fn post_message_with_event_loop<T: serde::Serialize>(
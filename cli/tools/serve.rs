@@ -1,10 +1,12 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use deno_core::error::AnyError;
 use deno_core::futures::TryFutureExt;
 use deno_core::ModuleSpecifier;
+use deno_runtime::deno_http::DeclarativeServerShutdown;
 use deno_runtime::deno_permissions::PermissionsContainer;
 
 use super::run::check_permission_before_script;
@@ -14,8 +16,13 @@ use crate::args::ServeFlags;
 use crate::args::WatchFlagsWithPaths;
 use crate::factory::CliFactory;
 use crate::util::file_watcher::WatcherRestartMode;
+use crate::worker::CliMainWorker;
 use crate::worker::CliMainWorkerFactory;
 
+/// How long `deno serve --watch` waits for the requests in flight to finish
+/// before restarting the server.
+const GRACEFUL_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
 pub async fn serve(
   flags: Arc<Flags>,
   serve_flags: ServeFlags,
@@ -53,6 +60,7 @@ pub async fn serve(
     permissions,
     serve_flags.worker_count,
     false,
+    None,
   )
   .await
 }
@@ -63,6 +71,7 @@ async fn do_serve(
   permissions: PermissionsContainer,
   worker_count: Option<usize>,
   hmr: bool,
+  shutdown: Option<DeclarativeServerShutdown>,
 ) -> Result<i32, AnyError> {
   let mut worker = worker_factory
     .create_main_worker(
@@ -74,6 +83,7 @@ async fn do_serve(
       permissions.clone(),
     )
     .await?;
+  put_shutdown(&mut worker, shutdown.clone());
   let worker_count = match worker_count {
     None | Some(1) => return worker.run().await,
     Some(c) => c,
@@ -88,14 +98,22 @@ async fn do_serve(
     let worker_factory = worker_factory.clone();
     let main_module = main_module.clone();
     let permissions = permissions.clone();
+    let shutdown = shutdown.clone();
     let (tx, rx) = tokio::sync::oneshot::channel();
     channels.push(rx);
     std::thread::Builder::new()
       .name(format!("serve-worker-{i}"))
       .spawn(move || {
         deno_runtime::tokio_util::create_and_run_current_thread(async move {
-          let result =
-            run_worker(i, worker_factory, main_module, permissions, hmr).await;
+          let result = run_worker(
+            i,
+            worker_factory,
+            main_module,
+            permissions,
+            hmr,
+            shutdown,
+          )
+          .await;
           let _ = tx.send(result);
         });
       })?;
@@ -126,6 +144,7 @@ async fn run_worker(
   main_module: ModuleSpecifier,
   permissions: PermissionsContainer,
   hmr: bool,
+  shutdown: Option<DeclarativeServerShutdown>,
 ) -> Result<i32, AnyError> {
   let mut worker = worker_factory
     .create_main_worker(
@@ -137,6 +156,7 @@ async fn run_worker(
       permissions,
     )
     .await?;
+  put_shutdown(&mut worker, shutdown);
   if hmr {
     worker.run_for_watcher().await?;
     Ok(0)
@@ -145,6 +165,15 @@ async fn run_worker(
   }
 }
 
+fn put_shutdown(
+  worker: &mut CliMainWorker,
+  shutdown: Option<DeclarativeServerShutdown>,
+) {
+  if let Some(shutdown) = shutdown {
+    worker.put_op_state(shutdown);
+  }
+}
+
 async fn serve_with_watch(
   flags: Arc<Flags>,
   watch_flags: WatchFlagsWithPaths,
@@ -175,9 +204,51 @@ async fn serve_with_watch(
         let permissions = factory.create_permissions_container()?;
         let worker_factory = factory.create_cli_main_worker_factory().await?;
 
-        do_serve(worker_factory, main_module, permissions, worker_count, hmr)
+        if hmr {
+          do_serve(
+            worker_factory,
+            main_module,
+            permissions,
+            worker_count,
+            hmr,
+            None,
+          )
           .await?;
+          return Ok(());
+        }
 
+        // Rather than dropping the server when a file changes, it's shut
+        // down gracefully so the requests in flight can finish first.
+        let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+        let serve_future = do_serve(
+          worker_factory,
+          main_module,
+          permissions,
+          worker_count,
+          hmr,
+          Some(DeclarativeServerShutdown(shutdown_rx)),
+        );
+        tokio::pin!(serve_future);
+        watcher_communicator.change_restart_mode(WatcherRestartMode::Manual);
+        tokio::select! {
+          result = &mut serve_future => {
+            watcher_communicator
+              .change_restart_mode(WatcherRestartMode::Automatic);
+            result?;
+            return Ok(());
+          }
+          _ = watcher_communicator.watch_for_changed_paths() => {}
+        }
+        let _ = shutdown_tx.send(true);
+        if tokio::time::timeout(GRACEFUL_SHUTDOWN_TIMEOUT, serve_future)
+          .await
+          .is_err()
+        {
+          log::debug!("Timed out waiting for the requests in flight to finish");
+        }
+        watcher_communicator.force_restart()?;
+        // the watcher restarts the process
+        std::future::pending::<()>().await;
         Ok(())
       })
     },
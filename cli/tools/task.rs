@@ -3,22 +3,34 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
 
+use deno_config::deno_json::ConfigFile;
 use deno_config::deno_json::Task;
 use deno_config::workspace::TaskOrScript;
 use deno_config::workspace::WorkspaceDirectory;
+use deno_config::workspace::WorkspaceMemberTasksConfig;
+use deno_config::workspace::WorkspaceMemberTasksConfigFile;
 use deno_config::workspace::WorkspaceTasksConfig;
 use deno_core::anyhow::anyhow;
 use deno_core::anyhow::bail;
 use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
+use deno_core::futures::stream::FuturesUnordered;
+use deno_core::futures::FutureExt;
+use deno_core::futures::StreamExt;
 use deno_core::normalize_path;
+use deno_core::url::Url;
+use deno_package_json::PackageJson;
 use deno_task_shell::ShellCommand;
+use deno_task_shell::ShellPipeReader;
+use tokio_util::sync::CancellationToken;
 
+use crate::args::deno_json::read_tasks_config;
 use crate::args::CliOptions;
 use crate::args::Flags;
 use crate::args::TaskFlags;
@@ -48,7 +60,7 @@ pub async fn execute_script(
         v == "1"
       })
       .unwrap_or(false);
-  let tasks_config = start_dir.to_tasks_config()?;
+  let (tasks_config, task_dependencies) = resolve_tasks_config(start_dir)?;
   let tasks_config = if force_use_pkg_json {
     tasks_config.with_only_pkg_json()
   } else {
@@ -74,7 +86,7 @@ pub async fn execute_script(
   match tasks_config.task(task_name) {
     Some((dir_url, task_or_script)) => match task_or_script {
       TaskOrScript::Task(_tasks, script) => {
-        let cwd = match task_flags.cwd {
+        let cwd = match &task_flags.cwd {
           Some(path) => canonicalize_path(&PathBuf::from(path))
             .context("failed canonicalizing --cwd")?,
           None => normalize_path(dir_url.to_file_path().unwrap()),
@@ -84,6 +96,28 @@ pub async fn execute_script(
           npm_resolver.as_ref(),
           node_resolver,
         )?;
+        let dependencies = DependencyResolver {
+          tasks_config: &tasks_config,
+          task_dependencies: &task_dependencies,
+          indexes: Default::default(),
+          path: Default::default(),
+          tasks: Default::default(),
+        }
+        .resolve(dir_url, task_name)?;
+        if !dependencies.is_empty() {
+          let exit_code = run_dependencies(RunDependenciesOptions {
+            tasks: &dependencies,
+            cwd: task_flags.cwd.is_some().then_some(cwd.as_path()),
+            env_vars: &env_vars,
+            custom_commands: &custom_commands,
+            npm_resolver: npm_resolver.as_ref(),
+            cli_options,
+          })
+          .await?;
+          if exit_code != 0 {
+            return Ok(exit_code);
+          }
+        }
         run_task(RunTaskOptions {
           task_name,
           script,
@@ -193,10 +227,342 @@ async fn run_task(opts: RunTaskOptions<'_>) -> Result<i32, AnyError> {
     root_node_modules_dir: npm_resolver
       .root_node_modules_path()
       .map(|p| p.as_path()),
+    stdio: None,
+    cancellation_token: None,
   })
   .await
 }
 
+/// The tasks that the tasks of the config files depend on, keyed by the
+/// folder of the config file and the name of the task.
+type TaskDependencies = HashMap<(Url, String), Vec<String>>;
+
+/// The names of the tasks that `deno task` can run in the directory, for
+/// shell completions.
+pub fn task_names(
+  start_dir: &WorkspaceDirectory,
+) -> Result<Vec<String>, AnyError> {
+  let (tasks_config, _) = resolve_tasks_config(start_dir)?;
+  let mut seen_task_names = HashSet::with_capacity(tasks_config.tasks_count());
+  let mut names = Vec::with_capacity(tasks_config.tasks_count());
+  for config in [&tasks_config.member, &tasks_config.root]
+    .into_iter()
+    .flatten()
+  {
+    let deno_json_names = config
+      .deno_json
+      .iter()
+      .flat_map(|config| config.tasks.keys());
+    let package_json_names = config
+      .package_json
+      .iter()
+      .flat_map(|config| config.tasks.keys());
+    for name in deno_json_names.chain(package_json_names) {
+      if seen_task_names.insert(name) {
+        names.push(name.clone());
+      }
+    }
+  }
+  Ok(names)
+}
+
+/// Resolves the tasks the same way `deno_config` does, except that the
+/// config files with tasks in the object form are read by the CLI, which
+/// also collects the tasks they depend on.
+fn resolve_tasks_config(
+  start_dir: &WorkspaceDirectory,
+) -> Result<(WorkspaceTasksConfig, TaskDependencies), AnyError> {
+  fn to_member_tasks_config(
+    maybe_deno_json: Option<&ConfigFile>,
+    maybe_pkg_json: Option<&PackageJson>,
+    task_dependencies: &mut TaskDependencies,
+  ) -> Result<Option<WorkspaceMemberTasksConfig>, AnyError> {
+    let deno_json = match maybe_deno_json {
+      Some(deno_json) => {
+        let folder_url =
+          Url::from_directory_path(deno_json.dir_path()).unwrap();
+        let tasks = match read_tasks_config(deno_json)? {
+          Some(tasks) => Some(
+            tasks
+              .into_iter()
+              .map(|(name, task)| {
+                if !task.depends_on().is_empty() {
+                  task_dependencies.insert(
+                    (folder_url.clone(), name.clone()),
+                    task.depends_on().to_vec(),
+                  );
+                }
+                (name, Task::Definition(task.command().to_string()))
+              })
+              .collect(),
+          ),
+          None => deno_json.to_tasks_config().with_context(|| {
+            format!("Failed parsing '{}'.", deno_json.specifier)
+          })?,
+        };
+        tasks.map(|tasks| WorkspaceMemberTasksConfigFile { folder_url, tasks })
+      }
+      None => None,
+    };
+    let package_json = maybe_pkg_json.and_then(|pkg_json| {
+      pkg_json
+        .scripts
+        .clone()
+        .map(|scripts| WorkspaceMemberTasksConfigFile {
+          folder_url: Url::from_directory_path(pkg_json.dir_path()).unwrap(),
+          tasks: scripts,
+        })
+    });
+    if deno_json.is_none() && package_json.is_none() {
+      return Ok(None);
+    }
+    Ok(Some(WorkspaceMemberTasksConfig {
+      deno_json,
+      package_json,
+    }))
+  }
+
+  let workspace = &start_dir.workspace;
+  let member_deno_json = start_dir.maybe_deno_json();
+  let member_pkg_json = start_dir.maybe_pkg_json();
+  // the root config files are only used when the directory is a member
+  let root_deno_json = member_deno_json
+    .filter(|c| c.dir_path() != workspace.root_dir_path())
+    .and(workspace.root_deno_json());
+  let root_pkg_json = member_pkg_json
+    .filter(|c| c.dir_path() != workspace.root_dir_path())
+    .and(workspace.root_pkg_json());
+  let mut task_dependencies = TaskDependencies::new();
+  let tasks_config = WorkspaceTasksConfig {
+    root: to_member_tasks_config(
+      root_deno_json.map(|c| c.as_ref()),
+      root_pkg_json.map(|c| c.as_ref()),
+      &mut task_dependencies,
+    )?,
+    member: to_member_tasks_config(
+      member_deno_json.map(|c| c.as_ref()),
+      member_pkg_json.map(|c| c.as_ref()),
+      &mut task_dependencies,
+    )?,
+  };
+  Ok((tasks_config, task_dependencies))
+}
+
+/// A task that needs to run before the requested task.
+struct DependencyTask {
+  name: String,
+  script: String,
+  folder_url: Url,
+  /// Indexes of the tasks that need to complete before this one.
+  depends_on: Vec<usize>,
+}
+
+struct DependencyResolver<'a> {
+  tasks_config: &'a WorkspaceTasksConfig,
+  task_dependencies: &'a TaskDependencies,
+  indexes: HashMap<(Url, String), usize>,
+  path: Vec<(Url, String)>,
+  tasks: Vec<DependencyTask>,
+}
+
+impl<'a> DependencyResolver<'a> {
+  /// Resolves all the tasks the task transitively depends on, with each
+  /// task listed after the tasks it depends on.
+  fn resolve(
+    mut self,
+    folder_url: &Url,
+    task_name: &str,
+  ) -> Result<Vec<DependencyTask>, AnyError> {
+    self.resolve_dependencies(folder_url, task_name)?;
+    Ok(self.tasks)
+  }
+
+  fn resolve_dependencies(
+    &mut self,
+    folder_url: &Url,
+    task_name: &str,
+  ) -> Result<Vec<usize>, AnyError> {
+    let key = (folder_url.clone(), task_name.to_string());
+    let Some(depends_on) = self.task_dependencies.get(&key) else {
+      return Ok(Vec::new());
+    };
+    self.path.push(key);
+    let mut indexes = Vec::with_capacity(depends_on.len());
+    for name in depends_on {
+      let Some((dep_folder_url, task_or_script)) = self.tasks_config.task(name)
+      else {
+        bail!(
+          "Task '{}' depends on '{}', which was not found.",
+          task_name,
+          name
+        );
+      };
+      let dep_key = (dep_folder_url.clone(), name.clone());
+      if self.path.contains(&dep_key) {
+        let cycle = self
+          .path
+          .iter()
+          .map(|(_, name)| name.as_str())
+          .chain(std::iter::once(name.as_str()))
+          .collect::<Vec<_>>();
+        bail!("Task dependency cycle detected: {}", cycle.join(" -> "));
+      }
+      if let Some(index) = self.indexes.get(&dep_key) {
+        indexes.push(*index);
+        continue;
+      }
+      let script = match task_or_script {
+        TaskOrScript::Task(_, script) => script.to_string(),
+        TaskOrScript::Script(_, script) => script.to_string(),
+      };
+      let depends_on = self.resolve_dependencies(dep_folder_url, name)?;
+      let index = self.tasks.len();
+      self.tasks.push(DependencyTask {
+        name: name.clone(),
+        script,
+        folder_url: dep_folder_url.clone(),
+        depends_on,
+      });
+      self.indexes.insert(dep_key, index);
+      indexes.push(index);
+    }
+    self.path.pop();
+    Ok(indexes)
+  }
+}
+
+struct RunDependenciesOptions<'a> {
+  tasks: &'a [DependencyTask],
+  /// The directory provided with `--cwd`, otherwise the tasks run in the
+  /// directory of their config file.
+  cwd: Option<&'a Path>,
+  env_vars: &'a HashMap<String, String>,
+  custom_commands: &'a HashMap<String, Rc<dyn ShellCommand>>,
+  npm_resolver: &'a dyn CliNpmResolver,
+  cli_options: &'a CliOptions,
+}
+
+/// Runs the tasks that the requested task depends on, running the tasks that
+/// don't depend on each other in parallel. Once a task fails, the tasks that
+/// are still running are killed and its exit code is returned.
+async fn run_dependencies(
+  opts: RunDependenciesOptions<'_>,
+) -> Result<i32, AnyError> {
+  let mut started = vec![false; opts.tasks.len()];
+  let mut completed = vec![false; opts.tasks.len()];
+  let cancellation_token = CancellationToken::new();
+  let mut running = FuturesUnordered::new();
+  loop {
+    for (index, task) in opts.tasks.iter().enumerate() {
+      if !started[index] && task.depends_on.iter().all(|i| completed[*i]) {
+        started[index] = true;
+        running.push(
+          run_dependency(task, &opts, cancellation_token.clone())
+            .map(move |result| (index, result)),
+        );
+      }
+    }
+    let Some((index, result)) = running.next().await else {
+      return Ok(0);
+    };
+    let exit_code = match result {
+      Ok(exit_code) => exit_code,
+      Err(err) => {
+        cancellation_token.cancel();
+        while running.next().await.is_some() {}
+        return Err(err);
+      }
+    };
+    if exit_code != 0 {
+      log::error!(
+        "{} Task {} failed with exit code {}.",
+        colors::red("error:"),
+        colors::cyan(&opts.tasks[index].name),
+        exit_code
+      );
+      cancellation_token.cancel();
+      while running.next().await.is_some() {}
+      return Ok(exit_code);
+    }
+    completed[index] = true;
+  }
+}
+
+async fn run_dependency(
+  task: &DependencyTask,
+  opts: &RunDependenciesOptions<'_>,
+  cancellation_token: CancellationToken,
+) -> Result<i32, AnyError> {
+  let cwd = match opts.cwd {
+    Some(cwd) => cwd.to_path_buf(),
+    None => normalize_path(task.folder_url.to_file_path().unwrap()),
+  };
+  output_task(&task.name, &task.script);
+  let prefix = format!("{} ", colors::cyan(format!("[{}]", task.name)));
+  let (stdout_reader, stdout) = deno_task_shell::pipe();
+  let (stderr_reader, stderr) = deno_task_shell::pipe();
+  let stdout_handle =
+    forward_prefixed_output(stdout_reader, prefix.clone(), || {
+      std::io::stdout().lock()
+    });
+  let stderr_handle =
+    forward_prefixed_output(stderr_reader, prefix, || std::io::stderr().lock());
+  let result = task_runner::run_task(task_runner::RunTaskOptions {
+    task_name: &task.name,
+    script: &task.script,
+    cwd: &cwd,
+    env_vars: opts.env_vars.clone(),
+    custom_commands: opts.custom_commands.clone(),
+    init_cwd: opts.cli_options.initial_cwd(),
+    argv: &[],
+    root_node_modules_dir: opts
+      .npm_resolver
+      .root_node_modules_path()
+      .map(|p| p.as_path()),
+    stdio: Some(task_runner::TaskStdio { stdout, stderr }),
+    cancellation_token: Some(cancellation_token),
+  })
+  .await;
+  let _ = stdout_handle.await;
+  let _ = stderr_handle.await;
+  result
+}
+
+/// Forwards the output of a task that runs alongside other tasks, prefixing
+/// each of its lines with the name of the task.
+fn forward_prefixed_output<W: Write + 'static>(
+  mut reader: ShellPipeReader,
+  prefix: String,
+  writer: fn() -> W,
+) -> tokio::task::JoinHandle<()> {
+  fn write_line(mut writer: impl Write, prefix: &str, line: &[u8]) {
+    let _ = writer
+      .write_all(prefix.as_bytes())
+      .and_then(|_| writer.write_all(line))
+      .and_then(|_| writer.flush());
+  }
+
+  tokio::task::spawn_blocking(move || {
+    let mut pending = Vec::new();
+    let mut buffer = [0; 1024];
+    loop {
+      let size = match reader.read(&mut buffer) {
+        Ok(0) | Err(_) => break,
+        Ok(size) => size,
+      };
+      pending.extend_from_slice(&buffer[..size]);
+      while let Some(index) = pending.iter().position(|b| *b == b'\n') {
+        let line = pending.drain(..=index).collect::<Vec<_>>();
+        write_line(writer(), &prefix, &line);
+      }
+    }
+    if !pending.is_empty() {
+      pending.push(b'\n');
+      write_line(writer(), &prefix, &pending);
+    }
+  })
+}
+
 fn output_task(task_name: &str, script: &str) {
   log::info!(
     "{} {} {}",
@@ -347,6 +347,8 @@ pub const OP_DETAILS: phf::Map<&'static str, [&'static str; 2]> = phf_map! {
   "op_host_recv_message" => ["receive a message from a web worker", "terminating a `Worker`"],
   "op_http_accept" => ["accept a HTTP request", "closing a `Deno.HttpConn`"],
   "op_http_shutdown" => ["shutdown a HTTP connection", "awaiting `Deno.HttpEvent#respondWith`"],
+  "op_http_sse_finished" => ["wait for a server-sent event stream to finish", "awaiting `Deno.ServerSentEventWriter#finished`"],
+  "op_http_sse_send" => ["send a server-sent event", "awaiting the result of a `Deno.ServerSentEventWriter#send` call"],
   "op_http_upgrade_websocket" => ["upgrade a HTTP connection to a WebSocket", "awaiting `Deno.HttpEvent#respondWith`"],
   "op_http_write" => ["write HTTP response body", "awaiting `Deno.HttpEvent#respondWith`"],
   "op_http_write_headers" => ["write HTTP response headers", "awaiting `Deno.HttpEvent#respondWith`"],
@@ -354,9 +356,12 @@ pub const OP_DETAILS: phf::Map<&'static str, [&'static str; 2]> = phf_map! {
   "op_net_accept_tcp" => ["accept a TCP stream", "closing a `Deno.Listener`"],
   "op_net_accept_tls" => ["accept a TLS stream", "closing a `Deno.TlsListener`"],
   "op_net_accept_unix" => ["accept a Unix stream", "closing a `Deno.Listener`"],
+  "op_net_accept_vsock" => ["accept a vsock stream", "closing a `Deno.Listener`"],
   "op_net_connect_tcp" => ["connect to a TCP server", "awaiting a `Deno.connect` call"],
   "op_net_connect_tls" => ["connect to a TLS server", "awaiting a `Deno.connectTls` call"],
+  "op_net_connect_udp" => ["connect a UDP socket", "awaiting the result of a `Deno.DatagramConn#connect` call"],
   "op_net_connect_unix" => ["connect to a Unix server", "awaiting a `Deno.connect` call"],
+  "op_net_connect_vsock" => ["connect to a vsock server", "awaiting a `Deno.connect` call"],
   "op_net_recv_udp" => ["receive a datagram message via UDP", "awaiting the result of `Deno.DatagramConn#receive` call, or not breaking out of a for await loop looping over a `Deno.DatagramConn`"],
   "op_net_recv_unixpacket" => ["receive a datagram message via Unixpacket", "awaiting the result of `Deno.DatagramConn#receive` call, or not breaking out of a for await loop looping over a `Deno.DatagramConn`"],
   "op_net_send_udp" => ["send a datagram message via UDP", "awaiting the result of `Deno.DatagramConn#send` call"],
@@ -17,6 +17,7 @@ use crate::util::fs::collect_specifiers;
 use crate::util::path::get_extension;
 use crate::util::path::is_script_ext;
 use crate::util::path::matches_pattern_or_exact_path;
+use crate::util::v8::profiler::Profiler;
 use crate::worker::CliMainWorkerFactory;
 use crate::worker::CoverageCollector;
 
@@ -60,6 +61,7 @@ use deno_runtime::WorkerExecutionMode;
 use indexmap::IndexMap;
 use indexmap::IndexSet;
 use log::Level;
+use once_cell::sync::Lazy;
 use rand::rngs::SmallRng;
 use rand::seq::SliceRandom;
 use rand::SeedableRng;
@@ -89,6 +91,7 @@ use tokio::signal;
 mod channel;
 pub mod fmt;
 pub mod reporters;
+mod tags;
 
 pub use channel::create_single_test_event_channel;
 pub use channel::create_test_event_channel;
@@ -103,6 +106,7 @@ use reporters::JunitTestReporter;
 use reporters::PrettyTestReporter;
 use reporters::TapTestReporter;
 use reporters::TestReporter;
+pub use tags::TagExpression;
 
 /// How many times we're allowed to spin the event loop before considering something a leak.
 const MAX_SANITIZER_LOOP_SPINS: usize = 16;
@@ -168,10 +172,14 @@ pub struct TestFilter {
   pub regex: Option<Regex>,
   pub include: Option<Vec<String>>,
   pub exclude: Vec<String>,
+  /// Only include the tests whose tags match, from `--tags`.
+  pub tags: Option<TagExpression>,
+  /// Exclude the tests whose tags match, from `--skip-tags`.
+  pub skip_tags: Option<TagExpression>,
 }
 
 impl TestFilter {
-  pub fn includes(&self, name: &String) -> bool {
+  pub fn includes(&self, name: &String, tags: &[String]) -> bool {
     if let Some(substring) = &self.substring {
       if !name.contains(substring) {
         return false;
@@ -190,6 +198,16 @@ impl TestFilter {
     if self.exclude.contains(name) {
       return false;
     }
+    if let Some(expr) = &self.tags {
+      if !expr.matches(tags) {
+        return false;
+      }
+    }
+    if let Some(expr) = &self.skip_tags {
+      if expr.matches(tags) {
+        return false;
+      }
+    }
     true
   }
 
@@ -211,6 +229,27 @@ impl TestFilter {
       ..Default::default()
     }
   }
+
+  /// Creates the filter of the `--filter`, `--tags` and `--skip-tags` flags.
+  pub fn from_flags(
+    filter: &Option<String>,
+    tags: Option<&str>,
+    skip_tags: Option<&str>,
+  ) -> Result<Self, AnyError> {
+    Ok(Self {
+      tags: TagExpression::parse_maybe(tags)?,
+      skip_tags: TagExpression::parse_maybe(skip_tags)?,
+      ..Self::from_flag(filter)
+    })
+  }
+
+  /// Whether any tests can be filtered out.
+  pub fn is_filtering(&self) -> bool {
+    self.substring.is_some()
+      || self.regex.is_some()
+      || self.tags.is_some()
+      || self.skip_tags.is_some()
+  }
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Eq, Hash)]
@@ -277,6 +316,8 @@ pub struct TestDescription {
   pub location: TestLocation,
   pub sanitize_ops: bool,
   pub sanitize_resources: bool,
+  #[serde(default)]
+  pub tags: Vec<String>,
 }
 
 /// May represent a failure of a test or test step.
@@ -610,7 +651,14 @@ async fn configure_main_worker(
   permissions_container: PermissionsContainer,
   worker_sender: TestEventWorkerSender,
   options: &TestSpecifierOptions,
-) -> Result<(Option<Box<dyn CoverageCollector>>, MainWorker), anyhow::Error> {
+) -> Result<
+  (
+    Option<Box<dyn CoverageCollector>>,
+    Option<Profiler>,
+    MainWorker,
+  ),
+  anyhow::Error,
+> {
   let mut worker = worker_factory
     .create_custom_worker(
       WorkerExecutionMode::Test,
@@ -625,6 +673,7 @@ async fn configure_main_worker(
     )
     .await?;
   let coverage_collector = worker.maybe_setup_coverage_collector().await?;
+  let profiler = worker.maybe_setup_profiler().await?;
   if options.trace_leaks {
     worker.execute_script_static(
       located_script_name!(),
@@ -651,7 +700,7 @@ async fn configure_main_worker(
       }
     }
   }?;
-  Ok((coverage_collector, worker))
+  Ok((coverage_collector, profiler, worker))
 }
 
 /// Test a single specifier as documentation containing test programs, an executable test module or
@@ -667,7 +716,7 @@ pub async fn test_specifier(
   if fail_fast_tracker.should_stop() {
     return Ok(());
   }
-  let (coverage_collector, mut worker) = configure_main_worker(
+  let (coverage_collector, profiler, mut worker) = configure_main_worker(
     worker_factory,
     &specifier,
     permissions_container,
@@ -679,6 +728,7 @@ pub async fn test_specifier(
   match test_specifier_inner(
     &mut worker,
     coverage_collector,
+    profiler,
     specifier.clone(),
     fail_fast_tracker,
     options,
@@ -710,6 +760,7 @@ pub async fn test_specifier(
 async fn test_specifier_inner(
   worker: &mut MainWorker,
   mut coverage_collector: Option<Box<dyn CoverageCollector>>,
+  mut profiler: Option<Profiler>,
   specifier: ModuleSpecifier,
   fail_fast_tracker: FailFastTracker,
   options: TestSpecifierOptions,
@@ -748,6 +799,15 @@ async fn test_specifier_inner(
       )
       .await?;
   }
+  if let Some(profiler) = &mut profiler {
+    worker
+      .js_runtime
+      .with_event_loop_future(
+        profiler.stop().boxed_local(),
+        PollEventLoopOptions::default(),
+      )
+      .await?;
+  }
   Ok(())
 }
 
@@ -830,7 +890,7 @@ async fn run_tests_for_worker_inner(
   let mut tests_to_run = Vec::with_capacity(tests.len());
   let mut used_only = false;
   for ((_, d), f) in tests.tests.iter().zip(test_functions) {
-    if !options.filter.includes(&d.name) {
+    if !options.filter.includes(&d.name, &d.tags) {
       continue;
     }
 
@@ -1405,6 +1465,55 @@ fn is_supported_test_path_predicate(entry: WalkEntry) -> bool {
   }
 }
 
+static TEST_NAME_RE: Lazy<Regex> = Lazy::new(|| {
+  Regex::new(
+    r#"Deno\.test(?:\.only|\.ignore)?\(\s*(?:\{\s*name:\s*)?(?:"([^"\n]+)"|'([^'\n]+)'|`([^`$\n]+)`|(?:async\s+)?function\s+([\w$]+))"#,
+  )
+  .unwrap()
+});
+
+/// The names of the tests in the test modules of the workspace, for shell
+/// completions. They're found by scanning the sources for `Deno.test()` calls
+/// instead of running the modules, so names that are computed at runtime
+/// aren't included.
+pub fn collect_test_names(
+  cli_options: &CliOptions,
+) -> Result<Vec<String>, AnyError> {
+  let members_with_test_options =
+    cli_options.resolve_test_options_for_members(&TestFlags::default())?;
+  let mut names = Vec::new();
+  let mut seen_names = HashSet::new();
+  for (_, test_options) in members_with_test_options {
+    let specifiers = collect_specifiers(
+      test_options.files,
+      cli_options.vendor_dir_path().map(ToOwned::to_owned),
+      is_supported_test_path_predicate,
+    )?;
+    for specifier in specifiers {
+      let Ok(path) = specifier.to_file_path() else {
+        continue;
+      };
+      let Ok(source) = std::fs::read_to_string(&path) else {
+        continue;
+      };
+      for captures in TEST_NAME_RE.captures_iter(&source) {
+        let name = captures
+          .iter()
+          .skip(1)
+          .flatten()
+          .next()
+          .map(|m| m.as_str().to_string());
+        if let Some(name) = name {
+          if seen_names.insert(name.clone()) {
+            names.push(name);
+          }
+        }
+      }
+    }
+  }
+  Ok(names)
+}
+
 /// Checks if the path has a basename and extension Deno supports for tests.
 pub(crate) fn is_supported_test_path(path: &Path) -> bool {
   has_supported_test_path_name(path) && is_script_ext(path)
@@ -1546,6 +1655,11 @@ pub async fn run_tests(
   let cli_options = factory.cli_options()?;
   let workspace_test_options =
     cli_options.resolve_workspace_test_options(&test_flags);
+  let test_filter = TestFilter::from_flags(
+    &workspace_test_options.filter,
+    workspace_test_options.tags.as_deref(),
+    workspace_test_options.skip_tags.as_deref(),
+  )?;
   let file_fetcher = factory.file_fetcher()?;
   // Various test files should not share the same permissions in terms of
   // `PermissionsContainer` - otherwise granting/revoking permissions in one
@@ -1614,12 +1728,12 @@ pub async fn run_tests(
       concurrent_jobs: workspace_test_options.concurrent_jobs,
       fail_fast: workspace_test_options.fail_fast,
       log_level,
-      filter: workspace_test_options.filter.is_some(),
+      filter: test_filter.is_filtering(),
       reporter: workspace_test_options.reporter,
       junit_path: workspace_test_options.junit_path,
       hide_stacktraces: workspace_test_options.hide_stacktraces,
       specifier: TestSpecifierOptions {
-        filter: TestFilter::from_flag(&workspace_test_options.filter),
+        filter: test_filter,
         shuffle: workspace_test_options.shuffle,
         trace_leaks: workspace_test_options.trace_leaks,
       },
@@ -1667,6 +1781,11 @@ pub async fn run_tests_with_watch(
         let cli_options = factory.cli_options()?;
         let workspace_test_options =
           cli_options.resolve_workspace_test_options(&test_flags);
+        let test_filter = TestFilter::from_flags(
+          &workspace_test_options.filter,
+          workspace_test_options.tags.as_deref(),
+          workspace_test_options.skip_tags.as_deref(),
+        )?;
 
         let _ = watcher_communicator.watch_paths(cli_options.watch_paths());
         let graph_kind = cli_options.type_check_mode().as_graph_kind();
@@ -1790,12 +1909,12 @@ pub async fn run_tests_with_watch(
             concurrent_jobs: workspace_test_options.concurrent_jobs,
             fail_fast: workspace_test_options.fail_fast,
             log_level,
-            filter: workspace_test_options.filter.is_some(),
+            filter: test_filter.is_filtering(),
             reporter: workspace_test_options.reporter,
             junit_path: workspace_test_options.junit_path,
             hide_stacktraces: workspace_test_options.hide_stacktraces,
             specifier: TestSpecifierOptions {
-              filter: TestFilter::from_flag(&workspace_test_options.filter),
+              filter: test_filter,
               shuffle: workspace_test_options.shuffle,
               trace_leaks: workspace_test_options.trace_leaks,
             },
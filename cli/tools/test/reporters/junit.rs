@@ -97,6 +97,9 @@ impl TestReporter for JunitTestReporter {
       String::from("col"),
       description.location.column_number.to_string(),
     );
+    for tag in &description.tags {
+      case.add_property(quick_junit::Property::new("tag", tag.as_str()));
+    }
     self.cases.insert(description.id, case);
 
     self.test_name_tree.add_node(description.clone().into());
@@ -0,0 +1,232 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Boolean expressions over the `tags` of tests and benchmarks, used by the
+//! `--tags` and `--skip-tags` flags, like `slow & !windows` or
+//! `unit | (integration & !flaky)`.
+
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TagExpression {
+  Tag(String),
+  Not(Box<TagExpression>),
+  And(Box<TagExpression>, Box<TagExpression>),
+  Or(Box<TagExpression>, Box<TagExpression>),
+}
+
+impl TagExpression {
+  /// Parses an expression where `!` binds tighter than `&`, which binds
+  /// tighter than `|`.
+  pub fn parse(text: &str) -> Result<Self, AnyError> {
+    let mut parser = Parser {
+      text,
+      tokens: tokenize(text)?,
+      pos: 0,
+    };
+    let expr = parser.parse_or()?;
+    if let Some(token) = parser.peek() {
+      bail!(
+        "Invalid tag expression '{}': unexpected '{}'.",
+        text,
+        token.as_str()
+      );
+    }
+    Ok(expr)
+  }
+
+  pub fn parse_maybe(
+    maybe_text: Option<&str>,
+  ) -> Result<Option<Self>, AnyError> {
+    maybe_text.map(Self::parse).transpose()
+  }
+
+  pub fn matches(&self, tags: &[String]) -> bool {
+    match self {
+      Self::Tag(tag) => tags.contains(tag),
+      Self::Not(expr) => !expr.matches(tags),
+      Self::And(a, b) => a.matches(tags) && b.matches(tags),
+      Self::Or(a, b) => a.matches(tags) || b.matches(tags),
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+  Tag(String),
+  Not,
+  And,
+  Or,
+  OpenParen,
+  CloseParen,
+}
+
+impl Token {
+  fn as_str(&self) -> &str {
+    match self {
+      Token::Tag(tag) => tag,
+      Token::Not => "!",
+      Token::And => "&",
+      Token::Or => "|",
+      Token::OpenParen => "(",
+      Token::CloseParen => ")",
+    }
+  }
+}
+
+fn is_tag_char(c: char) -> bool {
+  c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ':' | '/' | '@')
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, AnyError> {
+  let mut tokens = Vec::new();
+  let mut chars = text.chars().peekable();
+  while let Some(c) = chars.next() {
+    let token = match c {
+      c if c.is_whitespace() => continue,
+      '!' => Token::Not,
+      '&' => Token::And,
+      '|' => Token::Or,
+      '(' => Token::OpenParen,
+      ')' => Token::CloseParen,
+      c if is_tag_char(c) => {
+        let mut tag = c.to_string();
+        while let Some(c) = chars.next_if(|c| is_tag_char(*c)) {
+          tag.push(c);
+        }
+        Token::Tag(tag)
+      }
+      c => bail!("Invalid tag expression '{}': unexpected '{}'.", text, c),
+    };
+    tokens.push(token);
+  }
+  Ok(tokens)
+}
+
+struct Parser<'a> {
+  text: &'a str,
+  tokens: Vec<Token>,
+  pos: usize,
+}
+
+impl<'a> Parser<'a> {
+  fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.pos)
+  }
+
+  fn eat(&mut self, token: &Token) -> bool {
+    if self.peek() == Some(token) {
+      self.pos += 1;
+      true
+    } else {
+      false
+    }
+  }
+
+  fn parse_or(&mut self) -> Result<TagExpression, AnyError> {
+    let mut expr = self.parse_and()?;
+    while self.eat(&Token::Or) {
+      expr = TagExpression::Or(Box::new(expr), Box::new(self.parse_and()?));
+    }
+    Ok(expr)
+  }
+
+  fn parse_and(&mut self) -> Result<TagExpression, AnyError> {
+    let mut expr = self.parse_unary()?;
+    while self.eat(&Token::And) {
+      expr = TagExpression::And(Box::new(expr), Box::new(self.parse_unary()?));
+    }
+    Ok(expr)
+  }
+
+  fn parse_unary(&mut self) -> Result<TagExpression, AnyError> {
+    let Some(token) = self.tokens.get(self.pos).cloned() else {
+      bail!("Invalid tag expression '{}': expected a tag.", self.text);
+    };
+    self.pos += 1;
+    match token {
+      Token::Tag(tag) => Ok(TagExpression::Tag(tag)),
+      Token::Not => Ok(TagExpression::Not(Box::new(self.parse_unary()?))),
+      Token::OpenParen => {
+        let expr = self.parse_or()?;
+        if !self.eat(&Token::CloseParen) {
+          bail!("Invalid tag expression '{}': expected ')'.", self.text);
+        }
+        Ok(expr)
+      }
+      Token::And | Token::Or | Token::CloseParen => bail!(
+        "Invalid tag expression '{}': unexpected '{}'.",
+        self.text,
+        token.as_str()
+      ),
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn tags(tags: &[&str]) -> Vec<String> {
+    tags.iter().map(|t| t.to_string()).collect()
+  }
+
+  #[test]
+  fn test_parse() {
+    assert_eq!(
+      TagExpression::parse("slow & !windows").unwrap(),
+      TagExpression::And(
+        Box::new(TagExpression::Tag("slow".to_string())),
+        Box::new(TagExpression::Not(Box::new(TagExpression::Tag(
+          "windows".to_string()
+        )))),
+      )
+    );
+    // `&` binds tighter than `|`
+    assert_eq!(
+      TagExpression::parse("a | b & c").unwrap(),
+      TagExpression::Or(
+        Box::new(TagExpression::Tag("a".to_string())),
+        Box::new(TagExpression::And(
+          Box::new(TagExpression::Tag("b".to_string())),
+          Box::new(TagExpression::Tag("c".to_string())),
+        )),
+      )
+    );
+    assert_eq!(
+      TagExpression::parse("(a | b) & c").unwrap(),
+      TagExpression::And(
+        Box::new(TagExpression::Or(
+          Box::new(TagExpression::Tag("a".to_string())),
+          Box::new(TagExpression::Tag("b".to_string())),
+        )),
+        Box::new(TagExpression::Tag("c".to_string())),
+      )
+    );
+  }
+
+  #[test]
+  fn test_parse_invalid() {
+    for text in ["", "a &", "& a", "(a | b", "a b", "a)", "a % b", "!"] {
+      assert!(TagExpression::parse(text).is_err(), "{}", text);
+    }
+    assert_eq!(
+      TagExpression::parse("a b").unwrap_err().to_string(),
+      "Invalid tag expression 'a b': unexpected 'b'."
+    );
+  }
+
+  #[test]
+  fn test_matches() {
+    let expr = TagExpression::parse("slow & !windows").unwrap();
+    assert!(expr.matches(&tags(&["slow"])));
+    assert!(expr.matches(&tags(&["slow", "linux"])));
+    assert!(!expr.matches(&tags(&["slow", "windows"])));
+    assert!(!expr.matches(&tags(&[])));
+
+    let expr = TagExpression::parse("unit | (integration & !flaky)").unwrap();
+    assert!(expr.matches(&tags(&["unit", "flaky"])));
+    assert!(expr.matches(&tags(&["integration"])));
+    assert!(!expr.matches(&tags(&["integration", "flaky"])));
+  }
+}
@@ -16,6 +16,8 @@ use crate::util::progress_bar::ProgressBarStyle;
 use crate::version;
 
 use async_trait::async_trait;
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
 use deno_core::anyhow::bail;
 use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
@@ -38,6 +40,24 @@ const RELEASE_URL: &str = "https://github.com/denoland/deno/releases";
 const CANARY_URL: &str = "https://dl.deno.land/canary";
 const DL_RELEASE_URL: &str = "https://dl.deno.land/release";
 
+/// Release server to upgrade from instead of dl.deno.land, for example to
+/// distribute the builds of a fork. It's expected to follow the layout of
+/// dl.deno.land and may provide additional channels that are upgraded to
+/// with `--channel=<name>`.
+const UPGRADE_URL_ENV_VAR: &str = "DENO_UPGRADE_URL";
+/// Base64 encoded Ed25519 public key. Every archive downloaded from a custom
+/// release server must have a matching signature published next to it with
+/// a `.sig` extension.
+const UPGRADE_PUBLIC_KEY_ENV_VAR: &str = "DENO_UPGRADE_PUBLIC_KEY";
+
+/// The release server and public key of the builds of a fork are embedded
+/// when building it, with the `DENO_RELEASE_URL` and `DENO_RELEASE_PUBLIC_KEY`
+/// environment variables. The ones of `DENO_UPGRADE_URL` and
+/// `DENO_UPGRADE_PUBLIC_KEY` take precedence over them.
+const RELEASE_URL_BUILT_IN: Option<&str> = option_env!("DENO_RELEASE_URL");
+const RELEASE_PUBLIC_KEY_BUILT_IN: Option<&str> =
+  option_env!("DENO_RELEASE_PUBLIC_KEY");
+
 pub static ARCHIVE_NAME: Lazy<String> =
   Lazy::new(|| format!("deno-{}.zip", env!("TARGET")));
 
@@ -504,12 +524,33 @@ pub async fn upgrade(
   let force_selection_of_new_version =
     upgrade_flags.force || full_path_output_flag.is_some();
 
+  if let Ok(url) = env::var(UPGRADE_URL_ENV_VAR) {
+    check_custom_release_server_url(&url)?;
+  }
+  let public_key = upgrade_public_key()?;
+  if public_key.is_none() && custom_release_server_url().is_some() {
+    bail!(
+      "Can't verify the builds of {} as this executable has no release public key. Set {} to the public key of the release server.",
+      base_upgrade_url(),
+      UPGRADE_PUBLIC_KEY_ENV_VAR
+    );
+  }
+
   let requested_version =
     RequestedVersion::from_upgrade_flags(upgrade_flags.clone())?;
 
   log::info!("Current Deno version: v{}", version::DENO_VERSION_INFO.deno);
 
   let maybe_selected_version_to_upgrade = match &requested_version {
+    RequestedVersion::Custom { channel, version } => {
+      select_custom_channel_version_for_upgrade(
+        &client,
+        channel,
+        version.clone(),
+        force_selection_of_new_version,
+      )
+      .await?
+    }
     RequestedVersion::Latest(channel) => {
       find_latest_version_to_upgrade(
         http_client_provider.clone(),
@@ -532,16 +573,29 @@ pub async fn upgrade(
     return Ok(());
   };
 
-  let download_url = get_download_url(
-    &selected_version_to_upgrade.version_or_hash,
-    requested_version.release_channel(),
-  )?;
+  let download_url = match &requested_version {
+    RequestedVersion::Custom { channel, .. } => {
+      get_custom_channel_download_url(
+        channel,
+        &selected_version_to_upgrade.version_or_hash,
+      )?
+    }
+    _ => get_download_url(
+      &selected_version_to_upgrade.version_or_hash,
+      requested_version.release_channel(),
+    )?,
+  };
   log::info!("{}", colors::gray(format!("Downloading {}", &download_url)));
-  let Some(archive_data) = download_package(&client, download_url).await?
+  let Some(archive_data) =
+    download_package(&client, download_url.clone()).await?
   else {
     log::error!("Download could not be found, aborting");
     std::process::exit(1)
   };
+  if let Some(public_key) = &public_key {
+    verify_archive_signature(&client, &download_url, &archive_data, public_key)
+      .await?;
+  }
 
   log::info!(
     "{}",
@@ -565,7 +619,7 @@ pub async fn upgrade(
   if upgrade_flags.dry_run {
     fs::remove_file(&new_exe_path)?;
     log::info!("Upgraded successfully (dry run)");
-    if requested_version.release_channel() == ReleaseChannel::Stable {
+    if requested_version.has_release_notes() {
       print_release_notes(
         version::DENO_VERSION_INFO.deno,
         &selected_version_to_upgrade.version_or_hash,
@@ -590,12 +644,9 @@ pub async fn upgrade(
   log::info!(
     "\nUpgraded successfully to Deno {} {}\n",
     colors::green(selected_version_to_upgrade.display()),
-    colors::gray(&format!(
-      "({})",
-      selected_version_to_upgrade.release_channel.name()
-    ))
+    colors::gray(&format!("({})", requested_version.channel_name()))
   );
-  if requested_version.release_channel() == ReleaseChannel::Stable {
+  if requested_version.has_release_notes() {
     print_release_notes(
       version::DENO_VERSION_INFO.deno,
       &selected_version_to_upgrade.version_or_hash,
@@ -612,10 +663,43 @@ pub async fn upgrade(
 enum RequestedVersion {
   Latest(ReleaseChannel),
   SpecificVersion(ReleaseChannel, String),
+  /// A channel that's only available on a custom release server.
+  Custom {
+    channel: String,
+    version: Option<String>,
+  },
 }
 
 impl RequestedVersion {
   fn from_upgrade_flags(upgrade_flags: UpgradeFlags) -> Result<Self, AnyError> {
+    if let Some(channel) = &upgrade_flags.channel {
+      let channel = channel.to_lowercase();
+      let version = upgrade_flags
+        .version_or_hash_or_channel
+        .as_ref()
+        .or(upgrade_flags.version.as_ref())
+        .map(|v| v.strip_prefix('v').unwrap_or(v).to_string());
+      if let Ok(release_channel) = ReleaseChannel::deserialize(&channel) {
+        return match version {
+          Some(version) => Ok(Self::SpecificVersion(release_channel, version)),
+          None => Ok(Self::Latest(release_channel)),
+        };
+      }
+      if !lazy_regex::regex_is_match!("^[a-z0-9][a-z0-9_-]*$", &channel) {
+        bail!("Invalid channel name passed ({})", colors::gray(channel));
+      }
+      if let Some(version) = &version {
+        if !is_valid_version_or_hash(version) {
+          bail!(
+            "Invalid version passed ({})\n\nPass a semver, or a full 40 character git commit hash, or a release channel name.\n\nUsage:\n{}",
+            colors::gray(version),
+            UPGRADE_USAGE
+          );
+        }
+      }
+      return Ok(Self::Custom { channel, version });
+    }
+
     let is_canary = upgrade_flags.canary;
     let re_hash = lazy_regex::regex!("^[0-9a-f]{40}$");
     let channel = if is_canary {
@@ -681,12 +765,75 @@ impl RequestedVersion {
   }
 
   /// Channels that use Git hashes as versions are considered canary.
+  /// Custom channels publish semver versions like the stable channel, but
+  /// have no release notes.
   pub fn release_channel(&self) -> ReleaseChannel {
     match self {
       Self::Latest(channel) => *channel,
       Self::SpecificVersion(channel, _) => *channel,
+      Self::Custom { .. } => ReleaseChannel::Stable,
+    }
+  }
+
+  fn channel_name(&self) -> &str {
+    match self {
+      Self::Latest(channel) | Self::SpecificVersion(channel, _) => {
+        channel.name()
+      }
+      Self::Custom { channel, .. } => channel,
+    }
+  }
+
+  fn has_release_notes(&self) -> bool {
+    matches!(
+      self,
+      Self::Latest(ReleaseChannel::Stable)
+        | Self::SpecificVersion(ReleaseChannel::Stable, _)
+    )
+  }
+}
+
+async fn select_custom_channel_version_for_upgrade(
+  client: &HttpClient,
+  channel: &str,
+  version: Option<String>,
+  force: bool,
+) -> Result<Option<AvailableVersion>, AnyError> {
+  if custom_release_server_url().is_none() {
+    bail!(
+      "The {} channel is not available from dl.deno.land. Set the {} environment variable to the URL of the release server that provides it.",
+      channel,
+      UPGRADE_URL_ENV_VAR
+    );
+  }
+  let version = match version {
+    Some(version) => version,
+    None => {
+      log::info!(
+        "{}",
+        colors::gray(&format!("Looking up {} version", channel))
+      );
+      let url = get_custom_channel_latest_version_url(channel)?;
+      let text = client.download_text(url).await.with_context(|| {
+        format!("No {} release available at the moment.", channel)
+      })?;
+      text.trim().trim_start_matches('v').to_string()
     }
+  };
+
+  if !force && version == version::DENO_VERSION_INFO.deno {
+    log::info!(
+      "Local deno version {} is the most recent {} release",
+      color_print::cformat!("<g>{}</>", version),
+      channel
+    );
+    return Ok(None);
   }
+
+  Ok(Some(AvailableVersion {
+    version_or_hash: version,
+    release_channel: ReleaseChannel::Stable,
+  }))
 }
 
 fn select_specific_version_for_upgrade(
@@ -872,16 +1019,116 @@ fn base_upgrade_url() -> Cow<'static, str> {
   // this is used by the test suite
   if let Ok(url) = env::var("DENO_DONT_USE_INTERNAL_BASE_UPGRADE_URL") {
     Cow::Owned(url)
+  } else if let Some(url) = custom_release_server_url() {
+    Cow::Owned(url)
   } else {
     Cow::Borrowed("https://dl.deno.land")
   }
 }
 
+fn custom_release_server_url() -> Option<String> {
+  let url = match env::var(UPGRADE_URL_ENV_VAR) {
+    Ok(url) if !url.trim().is_empty() => url,
+    _ => RELEASE_URL_BUILT_IN?.to_string(),
+  };
+  let url = url.trim().trim_end_matches('/');
+  if url.is_empty() || check_custom_release_server_url(url).is_err() {
+    return None;
+  }
+  Some(url.to_string())
+}
+
+/// Builds are only downloaded from custom release servers over https, except
+/// from a local server like the one of the test suite.
+fn check_custom_release_server_url(url: &str) -> Result<(), AnyError> {
+  let url = url.trim();
+  if url.is_empty() {
+    return Ok(());
+  }
+  let parsed = Url::parse(url)
+    .with_context(|| format!("Invalid {UPGRADE_URL_ENV_VAR} URL: {url}"))?;
+  let is_localhost =
+    matches!(parsed.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"));
+  match parsed.scheme() {
+    "https" => Ok(()),
+    "http" if is_localhost => Ok(()),
+    _ => bail!(
+      "Invalid {} URL: {}. Builds can only be downloaded over https.",
+      UPGRADE_URL_ENV_VAR,
+      url
+    ),
+  }
+}
+
+/// Versions from a custom release server end up in the download URL, so
+/// they must be a semver or a full git commit hash.
+fn is_valid_version_or_hash(version: &str) -> bool {
+  Version::parse_standard(version).is_ok()
+    || lazy_regex::regex_is_match!("^[0-9a-f]{40}$", version)
+}
+
+fn check_custom_version_or_hash(version: &str) -> Result<(), AnyError> {
+  if !is_valid_version_or_hash(version) {
+    bail!(
+      "Invalid version \"{}\" from the release server at {}. Expected a semver or a full 40 character git commit hash.",
+      version,
+      base_upgrade_url()
+    );
+  }
+  Ok(())
+}
+
+fn get_custom_channel_latest_version_url(
+  channel: &str,
+) -> Result<Url, AnyError> {
+  let url = format!("{}/{}-latest.txt", base_upgrade_url(), channel);
+  Url::parse(&url)
+    .with_context(|| format!("Invalid {UPGRADE_URL_ENV_VAR} URL: {url}"))
+}
+
+fn get_custom_channel_download_url(
+  channel: &str,
+  version: &str,
+) -> Result<Url, AnyError> {
+  check_custom_version_or_hash(version)?;
+  let url = format!(
+    "{}/{}/v{}/{}",
+    base_upgrade_url(),
+    channel,
+    version,
+    *ARCHIVE_NAME
+  );
+  Url::parse(&url)
+    .with_context(|| format!("Invalid {UPGRADE_URL_ENV_VAR} URL: {url}"))
+}
+
 fn get_download_url(
   version: &str,
   release_channel: ReleaseChannel,
 ) -> Result<Url, AnyError> {
+  if custom_release_server_url().is_some() {
+    check_custom_version_or_hash(version)?;
+  }
   let download_url = match release_channel {
+    // a custom release server follows the layout of dl.deno.land
+    ReleaseChannel::Stable | ReleaseChannel::Rc | ReleaseChannel::Lts
+      if custom_release_server_url().is_some() =>
+    {
+      format!(
+        "{}/release/v{}/{}",
+        base_upgrade_url(),
+        version,
+        *ARCHIVE_NAME
+      )
+    }
+    ReleaseChannel::Canary if custom_release_server_url().is_some() => {
+      format!(
+        "{}/canary/{}/{}",
+        base_upgrade_url(),
+        version,
+        *ARCHIVE_NAME
+      )
+    }
     ReleaseChannel::Stable => {
       format!("{}/download/v{}/{}", RELEASE_URL, version, *ARCHIVE_NAME)
     }
@@ -919,6 +1166,60 @@ async fn download_package(
   Ok(maybe_bytes)
 }
 
+fn upgrade_public_key() -> Result<Option<Vec<u8>>, AnyError> {
+  let public_key = match env::var(UPGRADE_PUBLIC_KEY_ENV_VAR) {
+    Ok(public_key) if !public_key.trim().is_empty() => public_key,
+    _ => match RELEASE_PUBLIC_KEY_BUILT_IN {
+      Some(public_key) if !public_key.trim().is_empty() => {
+        public_key.to_string()
+      }
+      _ => return Ok(None),
+    },
+  };
+  let public_key = BASE64_STANDARD
+    .decode(public_key.trim())
+    .with_context(|| format!("Invalid {UPGRADE_PUBLIC_KEY_ENV_VAR}"))?;
+  Ok(Some(public_key))
+}
+
+/// Verifies the archive against the base64 encoded Ed25519 signature that
+/// is published next to it.
+async fn verify_archive_signature(
+  client: &HttpClient,
+  download_url: &Url,
+  archive_data: &[u8],
+  public_key: &[u8],
+) -> Result<(), AnyError> {
+  let signature_url = Url::parse(&format!("{download_url}.sig"))?;
+  let signature = client
+    .download_text(signature_url.clone())
+    .await
+    .with_context(|| format!("Failed downloading {signature_url}"))?;
+  check_archive_signature(archive_data, signature.trim(), public_key)
+    .with_context(|| {
+      format!("Failed verifying the signature of {download_url}")
+    })?;
+  log::info!("{}", colors::gray("Verified the archive's signature"));
+  Ok(())
+}
+
+fn check_archive_signature(
+  archive_data: &[u8],
+  signature: &str,
+  public_key: &[u8],
+) -> Result<(), AnyError> {
+  let signature = BASE64_STANDARD
+    .decode(signature)
+    .context("The signature is not valid base64.")?;
+  ring::signature::UnparsedPublicKey::new(&ring::signature::ED25519, public_key)
+    .verify(archive_data, &signature)
+    .map_err(|_| {
+      deno_core::anyhow::anyhow!(
+        "The signature does not match the release public key."
+      )
+    })
+}
+
 fn replace_exe(from: &Path, to: &Path) -> Result<(), std::io::Error> {
   if cfg!(windows) {
     // On windows you cannot replace the currently running executable.
@@ -1098,6 +1399,7 @@ mod test {
       version: None,
       output: None,
       version_or_hash_or_channel: None,
+      channel: None,
     };
 
     let req_ver =
@@ -1205,6 +1507,103 @@ mod test {
       err,
       "Pass a semver, or a full 40 character git commit hash, or a release channel name."
     );
+
+    upgrade_flags.version_or_hash_or_channel = None;
+    upgrade_flags.channel = Some("rc".to_string());
+    let req_ver =
+      RequestedVersion::from_upgrade_flags(upgrade_flags.clone()).unwrap();
+    assert_eq!(req_ver, RequestedVersion::Latest(ReleaseChannel::Rc));
+
+    upgrade_flags.channel = Some("Nightly".to_string());
+    let req_ver =
+      RequestedVersion::from_upgrade_flags(upgrade_flags.clone()).unwrap();
+    assert_eq!(
+      req_ver,
+      RequestedVersion::Custom {
+        channel: "nightly".to_string(),
+        version: None,
+      }
+    );
+    assert_eq!(req_ver.channel_name(), "nightly");
+    assert!(!req_ver.has_release_notes());
+
+    upgrade_flags.version_or_hash_or_channel = Some("v2.1.0".to_string());
+    let req_ver =
+      RequestedVersion::from_upgrade_flags(upgrade_flags.clone()).unwrap();
+    assert_eq!(
+      req_ver,
+      RequestedVersion::Custom {
+        channel: "nightly".to_string(),
+        version: Some("2.1.0".to_string()),
+      }
+    );
+
+    upgrade_flags.version_or_hash_or_channel = Some("2.1.0/../x".to_string());
+    let err = RequestedVersion::from_upgrade_flags(upgrade_flags.clone())
+      .unwrap_err()
+      .to_string();
+    assert_contains!(err, "Invalid version passed");
+    upgrade_flags.version_or_hash_or_channel = Some("v2.1.0".to_string());
+
+    upgrade_flags.channel = Some("../escape".to_string());
+    let err = RequestedVersion::from_upgrade_flags(upgrade_flags.clone())
+      .unwrap_err()
+      .to_string();
+    assert_contains!(err, "Invalid channel name passed");
+  }
+
+  #[test]
+  fn test_check_custom_release_server_url() {
+    check_custom_release_server_url("https://builds.example.com/deno").unwrap();
+    check_custom_release_server_url("http://localhost:4545").unwrap();
+    check_custom_release_server_url("http://127.0.0.1:4545/deno").unwrap();
+    let err = check_custom_release_server_url("http://builds.example.com")
+      .unwrap_err()
+      .to_string();
+    assert_contains!(err, "Builds can only be downloaded over https.");
+    let err = check_custom_release_server_url("file:///tmp/builds")
+      .unwrap_err()
+      .to_string();
+    assert_contains!(err, "Builds can only be downloaded over https.");
+    let err = check_custom_release_server_url("not a url")
+      .unwrap_err()
+      .to_string();
+    assert_contains!(err, "Invalid DENO_UPGRADE_URL URL");
+  }
+
+  #[test]
+  fn test_is_valid_version_or_hash() {
+    assert!(is_valid_version_or_hash("2.1.0"));
+    assert!(is_valid_version_or_hash("2.1.0-rc.1"));
+    assert!(is_valid_version_or_hash(
+      "5c69b4861b52ab406e73b9cd85c254f0505cb20f"
+    ));
+    assert!(!is_valid_version_or_hash("2.1.0/../../evil"));
+    assert!(!is_valid_version_or_hash("5c69b4861b52a"));
+    assert!(!is_valid_version_or_hash(""));
+  }
+
+  #[test]
+  fn test_check_archive_signature() {
+    use ring::signature::KeyPair;
+
+    let rng = ring::rand::SystemRandom::new();
+    let pkcs8 = ring::signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+    let key_pair =
+      ring::signature::Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+    let public_key = key_pair.public_key().as_ref();
+    let archive_data = b"archive";
+    let signature = BASE64_STANDARD.encode(key_pair.sign(archive_data));
+
+    check_archive_signature(archive_data, &signature, public_key).unwrap();
+    let err = check_archive_signature(b"tampered", &signature, public_key)
+      .unwrap_err()
+      .to_string();
+    assert_contains!(err, "The signature does not match");
+    let err = check_archive_signature(archive_data, "not base64!", public_key)
+      .unwrap_err()
+      .to_string();
+    assert_contains!(err, "not valid base64");
   }
 
   #[test]
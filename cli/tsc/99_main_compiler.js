@@ -41,6 +41,7 @@ delete Object.prototype.__proto__;
     "listen",
     "listenDatagram",
     "openKv",
+    "upgradeHttp",
   ]);
   const unstableMsgSuggestion =
     "If not, try changing the 'lib' compiler option to include 'deno.unstable' " +
@@ -171,6 +172,8 @@ delete Object.prototype.__proto__;
 
   /** @type {Map<string, ts.SourceFile>} */
   const documentRegistrySourceFileCache = new Map();
+  // The hits and misses of the source file cache, reported in LSP profiles.
+  const sourceFileCacheStats = { hits: 0, misses: 0 };
   const { getKeyForCompilationSettings } = ts.createDocumentRegistry(); // reuse this code
   /** @type {ts.DocumentRegistry} */
   const documentRegistry = {
@@ -210,6 +213,7 @@ delete Object.prototype.__proto__;
       const mapKey = path + key;
       let sourceFile = documentRegistrySourceFileCache.get(mapKey);
       if (!sourceFile || sourceFile.version !== version) {
+        sourceFileCacheStats.misses++;
         sourceFile = ts.createLanguageServiceSourceFile(
           fileName,
           scriptSnapshot,
@@ -226,6 +230,8 @@ delete Object.prototype.__proto__;
           scriptKind,
         );
         documentRegistrySourceFileCache.set(mapKey, sourceFile);
+      } else {
+        sourceFileCacheStats.hits++;
       }
       const sourceRefCount = sourceRefCounts.get(fileName) ?? 0;
       sourceRefCounts.set(fileName, sourceRefCount + 1);
@@ -266,8 +272,9 @@ delete Object.prototype.__proto__;
       sourceFileOptions,
     ) {
       const mapKey = path + key;
-      let sourceFile = documentRegistrySourceFileCache.get(mapKey) ??
-        this.acquireDocumentWithKey(
+      let sourceFile = documentRegistrySourceFileCache.get(mapKey);
+      if (!sourceFile) {
+        return this.acquireDocumentWithKey(
           fileName,
           path,
           compilationSettingsOrHost,
@@ -277,8 +284,10 @@ delete Object.prototype.__proto__;
           scriptKind,
           sourceFileOptions,
         );
+      }
 
       if (sourceFile.version !== version) {
+        sourceFileCacheStats.misses++;
         sourceFile = ts.updateLanguageServiceSourceFile(
           sourceFile,
           scriptSnapshot,
@@ -288,6 +297,8 @@ delete Object.prototype.__proto__;
           ),
         );
         documentRegistrySourceFileCache.set(mapKey, sourceFile);
+      } else {
+        sourceFileCacheStats.hits++;
       }
       return sourceFile;
     },
@@ -1204,6 +1215,797 @@ delete Object.prototype.__proto__;
     return errorString;
   }
 
+  // Type hierarchies are not part of the TypeScript language service API, so
+  // they are provided here on top of the type checker. Only classes and
+  // interfaces are part of a type hierarchy.
+
+  /**
+   * @param {ts.Node} node
+   * @returns {node is ts.ClassLikeDeclaration | ts.InterfaceDeclaration}
+   */
+  function isTypeHierarchyDeclaration(node) {
+    return ts.isClassLike(node) || ts.isInterfaceDeclaration(node);
+  }
+
+  /**
+   * @param {ts.TypeChecker} checker
+   * @param {ts.Node} node
+   * @returns {ts.Symbol | undefined}
+   */
+  function getTypeHierarchySymbol(checker, node) {
+    let symbol = checker.getSymbolAtLocation(node);
+    if (symbol && symbol.flags & ts.SymbolFlags.Alias) {
+      symbol = checker.getAliasedSymbol(symbol);
+    }
+    return symbol?.declarations?.some(isTypeHierarchyDeclaration)
+      ? symbol
+      : undefined;
+  }
+
+  /**
+   * @param {ts.TypeChecker} checker
+   * @param {ts.SourceFile} sourceFile
+   * @param {number} position
+   * @returns {ts.Symbol | undefined}
+   */
+  function getTypeHierarchySymbolAtPosition(checker, sourceFile, position) {
+    /** @type {ts.Identifier | undefined} */
+    let identifier;
+    /** @param {ts.Node} node */
+    function visit(node) {
+      if (position < node.getStart(sourceFile) || position > node.end) {
+        return;
+      }
+      if (ts.isIdentifier(node)) {
+        identifier = node;
+      } else {
+        ts.forEachChild(node, visit);
+      }
+    }
+    ts.forEachChild(sourceFile, visit);
+    return identifier && getTypeHierarchySymbol(checker, identifier);
+  }
+
+  /**
+   * The symbols of the classes and interfaces that a declaration extends or
+   * implements.
+   * @param {ts.TypeChecker} checker
+   * @param {ts.ClassLikeDeclaration | ts.InterfaceDeclaration} declaration
+   * @returns {ts.Symbol[]}
+   */
+  function getHeritageSymbols(checker, declaration) {
+    const symbols = [];
+    for (const clause of declaration.heritageClauses ?? []) {
+      for (const type of clause.types) {
+        const expression = ts.isPropertyAccessExpression(type.expression)
+          ? type.expression.name
+          : type.expression;
+        const symbol = getTypeHierarchySymbol(checker, expression);
+        if (symbol) {
+          symbols.push(symbol);
+        }
+      }
+    }
+    return symbols;
+  }
+
+  /**
+   * @param {ts.ClassLikeDeclaration | ts.InterfaceDeclaration} declaration
+   */
+  function toTypeHierarchyItem(declaration) {
+    const sourceFile = declaration.getSourceFile();
+    const start = declaration.getStart(sourceFile);
+    const nameNode = declaration.name ?? declaration;
+    const nameStart = nameNode.getStart(sourceFile);
+    return {
+      name: declaration.name?.text ?? "default",
+      kind: ts.isClassLike(declaration)
+        ? ts.ScriptElementKind.classElement
+        : ts.ScriptElementKind.interfaceElement,
+      kindModifiers: ts.getJSDocDeprecatedTag(declaration)
+        ? ts.ScriptElementKindModifier.deprecatedModifier
+        : ts.ScriptElementKindModifier.none,
+      file: sourceFile.fileName,
+      span: { start, length: declaration.end - start },
+      selectionSpan: { start: nameStart, length: nameNode.end - nameStart },
+    };
+  }
+
+  /**
+   * @param {ts.Symbol[]} symbols
+   */
+  function toTypeHierarchyItems(symbols) {
+    return symbols.flatMap((symbol) =>
+      (symbol.declarations ?? []).filter(isTypeHierarchyDeclaration).map(
+        toTypeHierarchyItem,
+      )
+    );
+  }
+
+  /**
+   * @param {ts.LanguageService} ls
+   * @param {string} method
+   * @param {string} specifier
+   * @param {number} position
+   */
+  function typeHierarchyRequest(ls, method, specifier, position) {
+    const program = ls.getProgram();
+    const sourceFile = program?.getSourceFile(specifier);
+    if (!program || !sourceFile) {
+      return method == "$prepareTypeHierarchy" ? null : [];
+    }
+    const checker = program.getTypeChecker();
+    const symbol = getTypeHierarchySymbolAtPosition(
+      checker,
+      sourceFile,
+      position,
+    );
+    switch (method) {
+      case "$prepareTypeHierarchy":
+        return symbol ? toTypeHierarchyItems([symbol]) : null;
+      case "$provideTypeHierarchySupertypes": {
+        if (!symbol) {
+          return [];
+        }
+        const supertypes = new Set();
+        for (const declaration of symbol.declarations ?? []) {
+          if (isTypeHierarchyDeclaration(declaration)) {
+            for (const supertype of getHeritageSymbols(checker, declaration)) {
+              supertypes.add(supertype);
+            }
+          }
+        }
+        return toTypeHierarchyItems([...supertypes]);
+      }
+      case "$provideTypeHierarchySubtypes": {
+        if (!symbol) {
+          return [];
+        }
+        const subtypes = [];
+        /** @param {ts.Node} node */
+        const visit = (node) => {
+          if (
+            isTypeHierarchyDeclaration(node) &&
+            getHeritageSymbols(checker, node).includes(symbol)
+          ) {
+            subtypes.push(toTypeHierarchyItem(node));
+          }
+          ts.forEachChild(node, visit);
+        };
+        for (const file of program.getSourceFiles()) {
+          if (!program.isSourceFileDefaultLibrary(file)) {
+            ts.forEachChild(file, visit);
+          }
+        }
+        return subtypes;
+      }
+      default:
+        throw new TypeError(`Invalid type hierarchy request: "${method}"`);
+    }
+  }
+
+  // TypeScript can't inline functions, nor variables that are exported, so
+  // these refactors are registered here on top of its refactor and text
+  // changes APIs. Exported declarations are also inlined into the modules
+  // that import them, which is only done when all of their uses there can be
+  // inlined, and the imports are removed.
+
+  const INLINE_EXPORTED_VARIABLE = "Inline exported variable";
+  const INLINE_FUNCTION = "Inline function";
+
+  /**
+   * @param {ts.Node} declaration
+   */
+  function isDeclarationExported(declaration) {
+    const statement = ts.isVariableDeclaration(declaration)
+      ? declaration.parent.parent
+      : declaration;
+    return ts.some(
+      /** @type {ts.HasModifiers} */ (statement).modifiers,
+      ts.isExportModifier,
+    );
+  }
+
+  /**
+   * Gets the references to the name of a declaration or import specifier in
+   * a file, or `undefined` when one of them can't be inlined, like an
+   * assignment, a re-export or a recursive reference.
+   * @param {ts.Declaration & { name: ts.Identifier }} declaration
+   * @param {ts.TypeChecker} checker
+   * @param {ts.SourceFile} sourceFile
+   * @returns {ts.Identifier[] | undefined}
+   */
+  function getInlinableReferences(declaration, checker, sourceFile) {
+    /** @type {ts.Identifier[]} */
+    const references = [];
+    const cannotInline = ts.FindAllReferences.Core.eachSymbolReferenceInFile(
+      declaration.name,
+      checker,
+      sourceFile,
+      (/** @type {ts.Identifier} */ ref) => {
+        if (
+          (ts.FindAllReferences.isWriteAccessForReference(ref) &&
+            !ts.isShorthandPropertyAssignment(ref.parent)) ||
+          ts.isExportSpecifier(ref.parent) ||
+          ts.isExportAssignment(ref.parent) ||
+          ts.isTypeQueryNode(ref.parent) ||
+          ts.rangeContainsRange(declaration, ref)
+        ) {
+          return true;
+        }
+        references.push(ref);
+      },
+    );
+    return cannotInline ? undefined : references;
+  }
+
+  /**
+   * Gets the import specifiers of an exported declaration in the other
+   * modules of the program along with the references to them, or
+   * `undefined` when a module uses the declaration in another way, like
+   * through a namespace import.
+   * @param {ts.Program} program
+   * @param {ts.SourceFile} file
+   * @param {ts.Symbol} symbol
+   */
+  function getImportReferences(program, file, symbol) {
+    const checker = program.getTypeChecker();
+    /** @param {ts.Symbol | undefined} other */
+    const isSymbol = (other) =>
+      !!other && checker.getMergedSymbol(ts.skipAlias(other, checker)) ===
+          symbol;
+    const importReferences = [];
+    for (const sourceFile of program.getSourceFiles()) {
+      if (
+        sourceFile === file || sourceFile.isDeclarationFile ||
+        program.isSourceFileFromExternalLibrary(sourceFile) ||
+        !sourceFile.text.includes(symbol.name)
+      ) {
+        continue;
+      }
+      /** @type {ts.ImportSpecifier[]} */
+      const importSpecifiers = [];
+      /** @type {ts.Identifier[]} */
+      const references = [];
+      for (const statement of sourceFile.statements) {
+        const namedBindings = ts.isImportDeclaration(statement)
+          ? statement.importClause?.namedBindings
+          : undefined;
+        if (!namedBindings || !ts.isNamedImports(namedBindings)) {
+          continue;
+        }
+        for (const element of namedBindings.elements) {
+          if (!isSymbol(checker.getSymbolAtLocation(element.name))) {
+            continue;
+          }
+          const elementReferences = getInlinableReferences(
+            element,
+            checker,
+            sourceFile,
+          );
+          if (!elementReferences) {
+            return undefined;
+          }
+          importSpecifiers.push(element);
+          references.push(...elementReferences);
+        }
+      }
+      /** @param {ts.Node} node */
+      const hasOtherReference = (node) => {
+        if (ts.isIdentifier(node)) {
+          return node.text === symbol.name && !references.includes(node) &&
+            !importSpecifiers.some((specifier) =>
+              ts.rangeContainsRange(specifier, node)
+            ) && isSymbol(checker.getSymbolAtLocation(node));
+        }
+        return !!ts.forEachChild(node, hasOtherReference);
+      };
+      if (hasOtherReference(sourceFile)) {
+        return undefined;
+      }
+      if (importSpecifiers.length > 0) {
+        importReferences.push({ sourceFile, importSpecifiers, references });
+      }
+    }
+    return importReferences;
+  }
+
+  /**
+   * Whether the node refers to a binding of the module other than the
+   * declaration itself, which isn't in scope in the importing modules.
+   * @param {ts.Node} node
+   * @param {ts.Node} declaration
+   * @param {ts.SourceFile} file
+   * @param {ts.TypeChecker} checker
+   * @returns {boolean}
+   */
+  function usesModuleBindings(node, declaration, file, checker) {
+    if (
+      ts.isIdentifier(node) &&
+      !(ts.isPropertyAccessExpression(node.parent) &&
+        node.parent.name === node)
+    ) {
+      const symbol = checker.getSymbolAtLocation(node);
+      if (
+        symbol?.declarations?.some((d) =>
+          d.getSourceFile() === file && !ts.rangeContainsRange(declaration, d)
+        )
+      ) {
+        return true;
+      }
+    }
+    return !!ts.forEachChild(
+      node,
+      (child) => usesModuleBindings(child, declaration, file, checker),
+    );
+  }
+
+  /**
+   * @param {ts.Program} program
+   * @param {ts.VariableDeclaration | ts.FunctionDeclaration} declaration
+   * @param {ts.Expression} replacement
+   */
+  function getDeclarationInliningInfo(program, declaration, replacement) {
+    const file = declaration.getSourceFile();
+    if (
+      file.isDeclarationFile || program.isSourceFileFromExternalLibrary(file)
+    ) {
+      return undefined;
+    }
+    const checker = program.getTypeChecker();
+    const references = getInlinableReferences(
+      /** @type {any} */ (declaration),
+      checker,
+      file,
+    );
+    if (!references) {
+      return undefined;
+    }
+    const importReferences = isDeclarationExported(declaration)
+      ? getImportReferences(
+        program,
+        file,
+        checker.getMergedSymbol(/** @type {any} */ (declaration).symbol),
+      )
+      : [];
+    if (
+      !importReferences ||
+      (references.length === 0 && importReferences.length === 0)
+    ) {
+      return undefined;
+    }
+    if (
+      importReferences.length > 0 &&
+      usesModuleBindings(replacement, declaration, file, checker)
+    ) {
+      return {
+        error:
+          "Declarations using module bindings cannot be inlined into other modules.",
+      };
+    }
+    return { file, references, importReferences, declaration, replacement };
+  }
+
+  /** @param {ts.Node} node */
+  function clearTextRanges(node) {
+    ts.setTextRangePosEnd(node, -1, -1);
+    ts.forEachChild(node, clearTextRanges);
+  }
+
+  /**
+   * Mirrors how TypeScript's "Inline variable" refactor wraps the inlined
+   * expression, also wrapping the expressions that can't start a statement.
+   * @param {ts.Node} reference
+   * @param {ts.Expression} replacement
+   */
+  function getReplacementExpression(reference, replacement) {
+    replacement = ts.getSynthesizedDeepClone(replacement);
+    // the positions are of the declaration's file, which can be another one
+    clearTextRanges(replacement);
+    const { parent } = reference;
+    if (
+      ts.isExpression(parent) &&
+      (ts.getExpressionPrecedence(replacement) <
+          ts.getExpressionPrecedence(parent) ||
+        ts.needsParentheses(parent))
+    ) {
+      return ts.factory.createParenthesizedExpression(replacement);
+    }
+    if (
+      ts.isFunctionLike(replacement) &&
+      (ts.isCallLikeExpression(parent) ||
+        ts.isPropertyAccessExpression(parent))
+    ) {
+      return ts.factory.createParenthesizedExpression(replacement);
+    }
+    if (
+      ts.isPropertyAccessExpression(parent) &&
+      (ts.isNumericLiteral(replacement) ||
+        ts.isObjectLiteralExpression(replacement))
+    ) {
+      return ts.factory.createParenthesizedExpression(replacement);
+    }
+    if (
+      ts.isExpressionStatement(parent) &&
+      (ts.isObjectLiteralExpression(replacement) ||
+        ts.isFunctionExpression(replacement) ||
+        ts.isClassExpression(replacement))
+    ) {
+      return ts.factory.createParenthesizedExpression(replacement);
+    }
+    if (
+      ts.isIdentifier(reference) && ts.isShorthandPropertyAssignment(parent)
+    ) {
+      return ts.factory.createPropertyAssignment(
+        reference.text,
+        replacement,
+      );
+    }
+    return replacement;
+  }
+
+  /**
+   * Creates the refactor, which only offers its action when `getInfo` finds
+   * something to inline at the position.
+   * @param {string} name
+   * @param {string} description
+   * @param {string} kind
+   * @param {(file: ts.SourceFile, position: number, tryWithReferenceToken: boolean, program: ts.Program) => any} getInfo
+   * @param {(tracker: any, info: any, checker: ts.TypeChecker) => void} applyEdits
+   */
+  function registerInlineRefactor(
+    name,
+    description,
+    kind,
+    getInfo,
+    applyEdits,
+  ) {
+    const action = { name, description, kind };
+    ts.refactor.registerRefactor(name, {
+      kinds: [kind],
+      getAvailableActions(context) {
+        const { file, program, preferences, startPosition, triggerReason } =
+          context;
+        const info = getInfo(
+          file,
+          startPosition,
+          triggerReason === "invoked",
+          program,
+        );
+        if (!info) {
+          return ts.emptyArray;
+        }
+        if (!ts.refactor.isRefactorErrorInfo(info)) {
+          return [{ name, description, actions: [action] }];
+        }
+        if (preferences.provideRefactorNotApplicableReason) {
+          return [{
+            name,
+            description,
+            actions: [{ ...action, notApplicableReason: info.error }],
+          }];
+        }
+        return ts.emptyArray;
+      },
+      getEditsForAction(context, actionName) {
+        assert(actionName === name, "Unexpected refactor invoked");
+        const { file, program, startPosition } = context;
+        const info = getInfo(file, startPosition, true, program);
+        if (!info || ts.refactor.isRefactorErrorInfo(info)) {
+          return undefined;
+        }
+        const checker = program.getTypeChecker();
+        const edits = ts.textChanges.ChangeTracker.with(
+          context,
+          (tracker) => {
+            applyEdits(tracker, info, checker);
+            for (const { sourceFile, importSpecifiers } of info
+              .importReferences) {
+              for (const importSpecifier of importSpecifiers) {
+                tracker.delete(sourceFile, importSpecifier);
+              }
+            }
+            tracker.delete(info.file, info.declaration);
+          },
+        );
+        return { edits };
+      },
+    });
+  }
+
+  /**
+   * @param {ts.TypeChecker} checker
+   * @param {ts.Identifier} token
+   * @returns {ts.Symbol | undefined}
+   */
+  function resolveTokenDefinition(checker, token) {
+    const definition = /** @type {any} */ (checker).resolveName(
+      token.text,
+      token,
+      ts.SymbolFlags.Value,
+      /*excludeGlobals*/ false,
+    );
+    return definition &&
+      checker.getMergedSymbol(ts.skipAlias(definition, checker));
+  }
+
+  registerInlineRefactor(
+    INLINE_EXPORTED_VARIABLE,
+    "Inline variable",
+    "refactor.inline.variable",
+    (file, position, tryWithReferenceToken, program) => {
+      const checker = program.getTypeChecker();
+      const token = ts.getTouchingPropertyName(file, position);
+      if (!ts.isIdentifier(token)) {
+        return undefined;
+      }
+      /** @type {ts.Node | undefined} */
+      let declaration = token.parent;
+      if (
+        !(ts.isVariableDeclaration(declaration) &&
+          declaration.name === token) && tryWithReferenceToken
+      ) {
+        declaration = resolveTokenDefinition(checker, token)?.declarations
+          ?.[0];
+      }
+      if (
+        !declaration || !ts.isInitializedVariable(declaration) ||
+        !ts.isVariableDeclarationInVariableStatement(declaration) ||
+        !ts.isIdentifier(declaration.name) ||
+        !isDeclarationExported(declaration) ||
+        checker.getMergedSymbol(/** @type {any} */ (declaration).symbol)
+            .declarations?.length !== 1
+      ) {
+        // the variables that aren't exported are inlined by TypeScript
+        return undefined;
+      }
+      return getDeclarationInliningInfo(
+        program,
+        declaration,
+        declaration.initializer,
+      );
+    },
+    (tracker, { file, references, importReferences, replacement }) => {
+      for (const node of references) {
+        tracker.replaceNode(
+          file,
+          node,
+          getReplacementExpression(node, replacement),
+        );
+      }
+      for (const { sourceFile, references } of importReferences) {
+        for (const node of references) {
+          tracker.replaceNode(
+            sourceFile,
+            node,
+            getReplacementExpression(node, replacement),
+          );
+        }
+      }
+    },
+  );
+
+  /**
+   * Gets the expression that a function returns when it consists of a
+   * single return statement that doesn't depend on its own `this` or
+   * `arguments`, and has plain parameters.
+   * @param {ts.FunctionDeclaration} declaration
+   * @returns {ts.Expression | undefined}
+   */
+  function getInlinableReturnExpression(declaration) {
+    const { body, parameters, asteriskToken } = declaration;
+    if (
+      !body || asteriskToken || ts.isAsyncFunction(declaration) ||
+      body.statements.length !== 1
+    ) {
+      return undefined;
+    }
+    const statement = body.statements[0];
+    if (!ts.isReturnStatement(statement) || !statement.expression) {
+      return undefined;
+    }
+    if (
+      parameters.some((p) =>
+        !ts.isIdentifier(p.name) || p.initializer || p.dotDotDotToken
+      )
+    ) {
+      return undefined;
+    }
+    /** @param {ts.Node} node */
+    const usesFunctionScope = (node) =>
+      node.kind === ts.SyntaxKind.ThisKeyword ||
+      (ts.isIdentifier(node) && node.text === "arguments") ||
+      ((!ts.isFunctionLike(node) || ts.isArrowFunction(node)) &&
+        !!ts.forEachChild(node, usesFunctionScope));
+    return usesFunctionScope(statement.expression)
+      ? undefined
+      : statement.expression;
+  }
+
+  /**
+   * Gets the index of the parameter that the node refers to, or -1.
+   * @param {ts.TypeChecker} checker
+   * @param {ts.Symbol[]} parameters
+   * @param {ts.Node} node
+   */
+  function getParameterIndex(checker, parameters, node) {
+    if (ts.isShorthandPropertyAssignment(node)) {
+      const symbol = checker.getShorthandAssignmentValueSymbol(node);
+      return symbol ? parameters.indexOf(symbol) : -1;
+    }
+    if (ts.isIdentifier(node)) {
+      const symbol = checker.getSymbolAtLocation(node);
+      return symbol ? parameters.indexOf(symbol) : -1;
+    }
+    return -1;
+  }
+
+  /**
+   * @param {ts.FunctionDeclaration} declaration
+   * @param {ts.TypeChecker} checker
+   */
+  function getParameterSymbols(declaration, checker) {
+    return declaration.parameters.map((p) =>
+      /** @type {ts.Symbol} */ (checker.getSymbolAtLocation(p.name))
+    );
+  }
+
+  /**
+   * @param {ts.CallExpression} call
+   * @param {ts.FunctionDeclaration} declaration
+   * @param {ts.Expression} returned
+   * @param {ts.TypeChecker} checker
+   */
+  function getInlinedCallExpression(call, declaration, returned, checker) {
+    const parameters = getParameterSymbols(declaration, checker);
+    /** @param {number} index */
+    const getArgument = (index) =>
+      call.arguments[index] ?? ts.factory.createIdentifier("undefined");
+    const replacement = ts.getSynthesizedDeepCloneWithReplacements(
+      returned,
+      /*includeTrivia*/ false,
+      (node) => {
+        const index = getParameterIndex(checker, parameters, node);
+        if (index === -1) {
+          return undefined;
+        }
+        const name = ts.isShorthandPropertyAssignment(node) ? node.name : node;
+        return getReplacementExpression(name, getArgument(index));
+      },
+    );
+    return getReplacementExpression(call, replacement);
+  }
+
+  /**
+   * Whether an argument can be repeated when its parameter is used more than
+   * once, as it has no side effects.
+   * @param {ts.Expression} node
+   */
+  function isSimpleArgument(node) {
+    return ts.isIdentifier(node) || ts.isLiteralExpression(node) ||
+      node.kind === ts.SyntaxKind.TrueKeyword ||
+      node.kind === ts.SyntaxKind.FalseKeyword ||
+      node.kind === ts.SyntaxKind.NullKeyword ||
+      node.kind === ts.SyntaxKind.ThisKeyword;
+  }
+
+  registerInlineRefactor(
+    INLINE_FUNCTION,
+    "Inline function",
+    "refactor.inline.function",
+    (file, position, tryWithReferenceToken, program) => {
+      const checker = program.getTypeChecker();
+      const token = ts.getTouchingPropertyName(file, position);
+      if (!ts.isIdentifier(token)) {
+        return undefined;
+      }
+      /** @type {ts.FunctionDeclaration | undefined} */
+      let declaration;
+      if (
+        ts.isFunctionDeclaration(token.parent) && token.parent.name === token
+      ) {
+        declaration = token.parent;
+      } else if (tryWithReferenceToken) {
+        const definition = resolveTokenDefinition(checker, token);
+        if (!definition) {
+          return { error: "Could not find function to inline." };
+        }
+        const declarations = definition.declarations?.filter(
+          ts.isFunctionDeclaration,
+        );
+        if (declarations?.length !== 1) {
+          return undefined;
+        }
+        declaration = declarations[0];
+      } else {
+        return undefined;
+      }
+      if (
+        checker.getMergedSymbol(/** @type {any} */ (declaration).symbol)
+            .declarations?.length !== 1 ||
+        ts.hasSyntacticModifier(declaration, ts.ModifierFlags.Default)
+      ) {
+        return undefined;
+      }
+      const returned = getInlinableReturnExpression(declaration);
+      if (!returned) {
+        return {
+          error: "Only functions returning a single expression can be inlined.",
+        };
+      }
+      const info = getDeclarationInliningInfo(program, declaration, returned);
+      if (!info || ts.refactor.isRefactorErrorInfo(info)) {
+        return info;
+      }
+      const parameters = getParameterSymbols(declaration, checker);
+      const parameterUses = parameters.map(() => 0);
+      /** @param {ts.Node} node */
+      const countParameterUses = (node) => {
+        const index = getParameterIndex(checker, parameters, node);
+        if (index !== -1) {
+          parameterUses[index]++;
+        } else {
+          ts.forEachChild(node, countParameterUses);
+        }
+      };
+      countParameterUses(returned);
+      const references = [
+        ...info.references,
+        ...info.importReferences.flatMap((r) => r.references),
+      ];
+      for (const reference of references) {
+        const call = reference.parent;
+        if (
+          !ts.isCallExpression(call) || call.expression !== reference ||
+          call.arguments.length > declaration.parameters.length ||
+          call.arguments.some(ts.isSpreadElement)
+        ) {
+          return undefined;
+        }
+        if (
+          call.arguments.some((argument, index) =>
+            parameterUses[index] > 1 && !isSimpleArgument(argument)
+          )
+        ) {
+          return {
+            error: "Arguments used more than once must not have side effects.",
+          };
+        }
+      }
+      return info;
+    },
+    (tracker, info, checker) => {
+      const { file, references, importReferences, declaration, replacement } =
+        info;
+      for (const node of references) {
+        tracker.replaceNode(
+          file,
+          node.parent,
+          getInlinedCallExpression(
+            node.parent,
+            declaration,
+            replacement,
+            checker,
+          ),
+        );
+      }
+      for (const { sourceFile, references } of importReferences) {
+        for (const node of references) {
+          tracker.replaceNode(
+            sourceFile,
+            node.parent,
+            getInlinedCallExpression(
+              node.parent,
+              declaration,
+              replacement,
+              checker,
+            ),
+          );
+        }
+      }
+    },
+  );
+
   /**
    * @param {number} id
    * @param {string} method
@@ -1288,6 +2090,9 @@ delete Object.prototype.__proto__;
       case "$getAssets": {
         return respond(id, getAssets());
       }
+      case "$getSourceFileCacheStats": {
+        return respond(id, sourceFileCacheStats);
+      }
       case "$getDiagnostics": {
         const projectVersion = args[1];
         // there's a possibility that we receive a change notification
@@ -1321,6 +2126,25 @@ delete Object.prototype.__proto__;
           return respond(id, {});
         }
       }
+      case "$prepareTypeHierarchy":
+      case "$provideTypeHierarchySupertypes":
+      case "$provideTypeHierarchySubtypes": {
+        try {
+          return respond(
+            id,
+            typeHierarchyRequest(ls, method, args[0], args[1]),
+          );
+        } catch (e) {
+          if (!isCancellationError(e)) {
+            return respond(
+              id,
+              null,
+              formatErrorWithArgs(e, [id, method, args, scope, maybeChange]),
+            );
+          }
+          return respond(id);
+        }
+      }
       default:
         if (typeof ls[method] === "function") {
           // The `getCompletionEntryDetails()` method returns null if the
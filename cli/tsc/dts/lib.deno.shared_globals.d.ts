@@ -746,10 +746,28 @@ declare interface ErrorConstructor {
  * which also supports setting a {@linkcode Deno.HttpClient} which provides a
  * way to connect via proxies and use custom TLS certificates.
  *
+ * A `ReadableStream` body is sent with the length of a `Content-Length`
+ * header instead of chunked, and the request fails if the stream doesn't
+ * match it. `onUploadProgress` is called as the request body is sent.
+ *
+ * ```ts
+ * const file = await Deno.open("./large.bin");
+ * const { size } = await file.stat();
+ * await fetch("https://example.com/upload", {
+ *   method: "PUT",
+ *   headers: { "content-length": String(size) },
+ *   body: file.readable,
+ *   onUploadProgress: ({ loaded, total }) => console.log(loaded, total),
+ * });
+ * ```
+ *
  * @tags allow-net, allow-read
  * @category Fetch
  */
 declare function fetch(
   input: Request | URL | string,
-  init?: RequestInit & { client: Deno.HttpClient },
+  init?: RequestInit & {
+    client?: Deno.HttpClient;
+    onUploadProgress?: (progress: Deno.UploadProgress) => void;
+  },
 ): Promise<Response>;
@@ -64,6 +64,8 @@ declare namespace Deno {
     leave: () => Promise<void>;
     /** Sets the multicast loopback option. If enabled, multicast packets will be looped back to the local socket. */
     setLoopback: (loopback: boolean) => Promise<void>;
+    /** Sets the hop limit of outgoing multicast packets for this socket. */
+    setTTL: (ttl: number) => Promise<void>;
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -93,8 +95,32 @@ declare namespace Deno {
      */
     receive(p?: Uint8Array): Promise<[Uint8Array, Addr]>;
     /** Sends a message to the target via the connection. The method resolves
-     * with the number of bytes sent. */
-    send(p: Uint8Array, addr: Addr): Promise<number>;
+     * with the number of bytes sent.
+     *
+     * The address may be omitted for a UDP socket that was connected with
+     * {@linkcode DatagramConn.connect}, which sends the message to its peer. */
+    send(p: Uint8Array, addr?: Addr): Promise<number>;
+    /** Connects a UDP socket to a peer. Afterwards, messages can be sent
+     * without an address, and only messages from the peer are received.
+     * Connecting again replaces the peer.
+     *
+     * Requires `allow-net` permission for the peer.
+     *
+     * ```ts
+     * const conn = Deno.listenDatagram({ port: 0, transport: "udp" });
+     * await conn.connect({ transport: "udp", hostname: "127.0.0.1", port: 53 });
+     * await conn.send(query);
+     * ```
+     */
+    connect(addr: NetAddr): Promise<void>;
+    /** The address of the peer of a connected UDP socket, or `null`. */
+    readonly remoteAddr: NetAddr | null;
+    /** Sets whether the socket may send messages to broadcast addresses
+     * (`SO_BROADCAST`). Enabled by default for UDP sockets. */
+    setBroadcast(broadcast: boolean): Promise<void>;
+    /** Sets the time-to-live (or, for IPv6, the hop limit) of outgoing
+     * unicast packets of a UDP socket. */
+    setTTL(ttl: number): Promise<void>;
     /** Close closes the socket. Any pending message promises will be rejected
      * with errors. */
     close(): void;
@@ -122,6 +148,103 @@ declare namespace Deno {
     reusePort?: boolean;
   }
 
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Unstable options which can be passed to {@linkcode Deno.serve} to create
+   * a server listening on a TCP port.
+   *
+   * @category HTTP Server
+   * @experimental
+   */
+  export interface ServeTcpOptions extends ServeOptions<Deno.NetAddr> {
+    /** Also serve HTTP/3 over QUIC on the same UDP port, which is advertised
+     * to HTTP/1.1 and HTTP/2 clients with an `Alt-Svc` response header.
+     * Requires `cert` and `key`.
+     *
+     * @default {false} */
+    http3?: boolean;
+
+    /** Allow HTTP/1.1 clients to switch the connection to cleartext HTTP/2
+     * with an `Upgrade: h2c` request. Clients which start the connection with
+     * the HTTP/2 connection preface are always served over HTTP/2. Can't be
+     * used with `cert` and `key`.
+     *
+     * @default {false} */
+    h2c?: boolean;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * The object that is returned from a {@linkcode Deno.upgradeHttp}
+   * request.
+   *
+   * @category HTTP Server
+   * @experimental
+   */
+  export interface HttpUpgrade {
+    /** The response object that must be returned from the handler for the
+     * upgrade to be successful. */
+    response: Response;
+    /** Resolves to the connection of the request once the response has been
+     * sent. */
+    conn: Promise<Conn>;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Options which can be set when performing a {@linkcode Deno.upgradeHttp}
+   * upgrade of a {@linkcode Request}.
+   *
+   * @category HTTP Server
+   * @experimental
+   */
+  export interface UpgradeHttpOptions {
+    /** The protocol sent in the `Upgrade` header of the response. Defaults to
+     * the `Upgrade` header of the request. */
+    protocol?: string;
+    /** Additional headers to send with the response. */
+    headers?: HeadersInit;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Take over the connection of an incoming HTTP/1.1 request, to speak a
+   * custom protocol over it.
+   *
+   * For a request with `Connection: Upgrade` and `Upgrade` headers, the
+   * returned response is a `101 Switching Protocols` response. For a
+   * `CONNECT` request, it is a `200 OK` response that establishes a tunnel.
+   * Once it has been returned from the handler and sent, the connection is
+   * handed over:
+   *
+   * ```ts
+   * Deno.serve((req) => {
+   *   if (req.method !== "CONNECT") {
+   *     return new Response(null, { status: 405 });
+   *   }
+   *   const { conn, response } = Deno.upgradeHttp(req);
+   *   conn.then(async (conn) => {
+   *     using upstream = await Deno.connect({ hostname: "example.com", port: 80 });
+   *     await Promise.all([
+   *       conn.readable.pipeTo(upstream.writable),
+   *       upstream.readable.pipeTo(conn.writable),
+   *     ]);
+   *   });
+   *   return response;
+   * });
+   * ```
+   *
+   * Requests received over HTTP/2 or HTTP/3 can't be upgraded, and the
+   * returned `conn` rejects for them.
+   *
+   * @category HTTP Server
+   * @experimental
+   */
+  export function upgradeHttp(
+    request: Request,
+    options?: UpgradeHttpOptions,
+  ): HttpUpgrade;
+
   /** **UNSTABLE**: New API, yet to be vetted.
    *
    * Unstable options which can be set when opening a datagram listener via
@@ -142,6 +265,14 @@ declare namespace Deno {
      *
      * @default {false} */
     loopback?: boolean;
+
+    /** The size of the receive buffer of the socket in bytes (`SO_RCVBUF`),
+     * which bounds how many messages are queued before new ones are dropped.
+     * The system may adjust it, e.g. Linux doubles it. */
+    recvBufferSize?: number;
+
+    /** The size of the send buffer of the socket in bytes (`SO_SNDBUF`). */
+    sendBufferSize?: number;
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -1266,6 +1397,9 @@ declare interface WebSocketStreamOptions {
   protocols?: string[];
   signal?: AbortSignal;
   headers?: HeadersInit;
+  /** Offers to compress the messages with the `permessage-deflate` extension
+   * (RFC 7692). Pass an object to configure the compression. */
+  compression?: boolean | Deno.WebSocketCompressionOptions;
 }
 
 /** **UNSTABLE**: New API, yet to be vetted.
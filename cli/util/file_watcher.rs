@@ -4,6 +4,8 @@ use crate::args::Flags;
 use crate::colors;
 use crate::util::fs::canonicalize_path;
 
+use deno_config::glob::GlobPattern;
+use deno_config::glob::PathGlobMatch;
 use deno_config::glob::PathOrPatternSet;
 use deno_core::error::AnyError;
 use deno_core::error::JsError;
@@ -21,10 +23,12 @@ use notify::Watcher;
 use std::cell::RefCell;
 use std::collections::HashSet;
 use std::io::IsTerminal;
+use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::time::Duration;
+use std::time::Instant;
 use tokio::select;
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::UnboundedReceiver;
@@ -32,6 +36,9 @@ use tokio::time::sleep;
 
 const CLEAR_SCREEN: &str = "\x1B[2J\x1B[1;1H";
 const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(200);
+/// How long to wait for the tasks of a previous run to finish before
+/// restarting anyway.
+const TEARDOWN_TIMEOUT: Duration = Duration::from_secs(1);
 
 struct DebouncedReceiver {
   // The `recv()` call could be used in a tokio `select!` macro,
@@ -39,16 +46,20 @@ struct DebouncedReceiver {
   // lose items if a `recv()` never completes
   received_items: HashSet<PathBuf>,
   receiver: UnboundedReceiver<Vec<PathBuf>>,
+  interval: Duration,
 }
 
 impl DebouncedReceiver {
-  fn new_with_sender() -> (Arc<mpsc::UnboundedSender<Vec<PathBuf>>>, Self) {
+  fn new_with_sender(
+    interval: Duration,
+  ) -> (Arc<mpsc::UnboundedSender<Vec<PathBuf>>>, Self) {
     let (sender, receiver) = mpsc::unbounded_channel();
     (
       Arc::new(sender),
       Self {
         receiver,
         received_items: HashSet::new(),
+        interval,
       },
     )
   }
@@ -65,7 +76,7 @@ impl DebouncedReceiver {
         items = self.receiver.recv() => {
           self.received_items.extend(items?);
         }
-        _ = sleep(DEBOUNCE_INTERVAL) => {
+        _ = sleep(self.interval) => {
           return Some(self.received_items.drain().collect());
         }
       }
@@ -251,12 +262,15 @@ where
   F: Future<Output = Result<(), AnyError>>,
 {
   let exclude_set = flags.resolve_watch_exclude_set()?;
+  let watch_patterns = flags.resolve_watch_patterns()?;
   let (paths_to_watch_tx, mut paths_to_watch_rx) =
     tokio::sync::mpsc::unbounded_channel();
   let (restart_tx, mut restart_rx) = tokio::sync::mpsc::unbounded_channel();
   let (changed_paths_tx, changed_paths_rx) = tokio::sync::broadcast::channel(4);
   let (watcher_sender, mut watcher_receiver) =
-    DebouncedReceiver::new_with_sender();
+    DebouncedReceiver::new_with_sender(
+      flags.watch_debounce().unwrap_or(DEBOUNCE_INTERVAL),
+    );
 
   let PrintConfig {
     banner,
@@ -277,10 +291,26 @@ where
   let changed_paths = Rc::new(RefCell::new(None));
   let changed_paths_ = changed_paths.clone();
   let watcher_ = watcher_communicator.clone();
+  let watched_paths = Rc::new(RefCell::new(HashSet::new()));
+  let watched_paths_ = watched_paths.clone();
+  let watch_patterns_ = watch_patterns.clone();
 
   deno_core::unsync::spawn(async move {
     loop {
-      let received_changed_paths = watcher_receiver.recv().await;
+      let mut received_changed_paths = watcher_receiver.recv().await;
+      // the base directories of the watch patterns are watched as a whole,
+      // so only restart for the files in them that match a pattern
+      if let Some(paths) = &mut received_changed_paths {
+        if !watch_patterns_.is_empty() {
+          let watched_paths = watched_paths_.borrow();
+          paths.retain(|path| {
+            is_watched_path(path, &watched_paths, &watch_patterns_)
+          });
+          if paths.is_empty() {
+            continue;
+          }
+        }
+      }
       changed_paths_
         .borrow_mut()
         .clone_from(&received_changed_paths);
@@ -297,21 +327,36 @@ where
     }
   });
 
+  let runtime_metrics = tokio::runtime::Handle::current().metrics();
+  let mut idle_task_count = runtime_metrics.active_tasks_count();
   loop {
-    // We may need to give the runtime a tick to settle, as cancellations may need to propagate
-    // to tasks. We choose yielding 10 times to the runtime as a decent heuristic. If watch tests
-    // start to fail, this may need to be increased.
-    for _ in 0..10 {
-      tokio::task::yield_now().await;
-    }
+    // Tasks spawned by the previous run, like the ones serving its listening
+    // sockets, are cancelled when it's dropped but only let go of their
+    // resources once the runtime polls them again, so wait for them to finish
+    // before starting over.
+    idle_task_count = wait_for_teardown(idle_task_count).await;
 
     let mut watcher = new_watcher(watcher_sender.clone())?;
-    consume_paths_to_watch(&mut watcher, &mut paths_to_watch_rx, &exclude_set);
+    watched_paths.borrow_mut().clear();
+    for pattern in &watch_patterns {
+      let _ = watcher.watch(&pattern.base_path(), RecursiveMode::Recursive);
+    }
+    consume_paths_to_watch(
+      &mut watcher,
+      &mut paths_to_watch_rx,
+      &exclude_set,
+      &watched_paths,
+    );
 
     let receiver_future = async {
       loop {
         let maybe_paths = paths_to_watch_rx.recv().await;
-        add_paths_to_watcher(&mut watcher, &maybe_paths.unwrap(), &exclude_set);
+        add_paths_to_watcher(
+          &mut watcher,
+          &maybe_paths.unwrap(),
+          &exclude_set,
+          &watched_paths,
+        );
       }
     };
     let operation_future = error_handler(operation(
@@ -335,7 +380,12 @@ where
         continue;
       },
       success = operation_future => {
-        consume_paths_to_watch(&mut watcher, &mut paths_to_watch_rx, &exclude_set);
+        consume_paths_to_watch(
+          &mut watcher,
+          &mut paths_to_watch_rx,
+          &exclude_set,
+          &watched_paths,
+        );
         // TODO(bartlomieju): print exit code here?
         info!(
           "{} {} {}. Restarting on file change...",
@@ -352,7 +402,12 @@ where
     let receiver_future = async {
       loop {
         let maybe_paths = paths_to_watch_rx.recv().await;
-        add_paths_to_watcher(&mut watcher, &maybe_paths.unwrap(), &exclude_set);
+        add_paths_to_watcher(
+          &mut watcher,
+          &maybe_paths.unwrap(),
+          &exclude_set,
+          &watched_paths,
+        );
       }
     };
 
@@ -369,6 +424,45 @@ where
   }
 }
 
+/// Yields to the runtime until there are no more tasks than `idle_task_count`,
+/// or until `TEARDOWN_TIMEOUT` elapses. Returns the task count to wait for on
+/// the next restart.
+async fn wait_for_teardown(idle_task_count: usize) -> usize {
+  // We may need to give the runtime a tick to settle, as cancellations may need to propagate
+  // to tasks.
+  for _ in 0..10 {
+    tokio::task::yield_now().await;
+  }
+  let runtime_metrics = tokio::runtime::Handle::current().metrics();
+  let deadline = Instant::now() + TEARDOWN_TIMEOUT;
+  while runtime_metrics.active_tasks_count() > idle_task_count {
+    if Instant::now() >= deadline {
+      // don't wait for these tasks again on the next restart
+      let task_count = runtime_metrics.active_tasks_count();
+      log::debug!(
+        "{} tasks of the previous run are still running.",
+        task_count - idle_task_count
+      );
+      return task_count;
+    }
+    sleep(Duration::from_millis(10)).await;
+  }
+  idle_task_count
+}
+
+fn is_watched_path(
+  path: &Path,
+  watched_paths: &HashSet<PathBuf>,
+  watch_patterns: &[GlobPattern],
+) -> bool {
+  watch_patterns
+    .iter()
+    .any(|pattern| pattern.matches_path(path) == PathGlobMatch::Matched)
+    || watched_paths
+      .iter()
+      .any(|watched_path| path.starts_with(watched_path))
+}
+
 fn new_watcher(
   sender: Arc<mpsc::UnboundedSender<Vec<PathBuf>>>,
 ) -> Result<RecommendedWatcher, AnyError> {
@@ -401,6 +495,7 @@ fn add_paths_to_watcher(
   watcher: &mut RecommendedWatcher,
   paths: &[PathBuf],
   paths_to_exclude: &PathOrPatternSet,
+  all_watched_paths: &RefCell<HashSet<PathBuf>>,
 ) {
   // Ignore any error e.g. `PathNotFound`
   let mut watched_paths = Vec::new();
@@ -414,17 +509,24 @@ fn add_paths_to_watcher(
     let _ = watcher.watch(path, RecursiveMode::Recursive);
   }
   log::debug!("Watching paths: {:?}", watched_paths);
+  // changed paths are canonicalized, so these need to be as well
+  all_watched_paths.borrow_mut().extend(
+    watched_paths
+      .into_iter()
+      .map(|path| canonicalize_path(&path).unwrap_or(path)),
+  );
 }
 
 fn consume_paths_to_watch(
   watcher: &mut RecommendedWatcher,
   receiver: &mut UnboundedReceiver<Vec<PathBuf>>,
   exclude_set: &PathOrPatternSet,
+  watched_paths: &RefCell<HashSet<PathBuf>>,
 ) {
   loop {
     match receiver.try_recv() {
       Ok(paths) => {
-        add_paths_to_watcher(watcher, &paths, exclude_set);
+        add_paths_to_watcher(watcher, &paths, exclude_set, watched_paths);
       }
       Err(e) => match e {
         mpsc::error::TryRecvError::Empty => {
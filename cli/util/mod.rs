@@ -11,6 +11,7 @@ pub mod extract;
 pub mod file_watcher;
 pub mod fs;
 pub mod logger;
+pub mod patch;
 pub mod path;
 pub mod progress_bar;
 pub mod result;
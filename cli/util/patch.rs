@@ -0,0 +1,401 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Parsing and applying of unified diffs, like the ones output by
+//! `git diff` and `diff -u`.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+
+use crate::cache::CACHE_PERM;
+use crate::util::fs::atomic_write_file_with_retries;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum HunkLine {
+  Context(String),
+  Remove(String),
+  Add(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Hunk {
+  /// The one based line of the original file the hunk starts at.
+  old_start: usize,
+  lines: Vec<HunkLine>,
+  /// Whether the last line of the hunk's original text has no newline.
+  old_missing_newline: bool,
+  /// Whether the last line of the hunk's new text has no newline.
+  new_missing_newline: bool,
+}
+
+impl Hunk {
+  fn old_lines(&self) -> Vec<&str> {
+    self
+      .lines
+      .iter()
+      .filter_map(|line| match line {
+        HunkLine::Context(text) | HunkLine::Remove(text) => Some(text.as_str()),
+        HunkLine::Add(_) => None,
+      })
+      .collect()
+  }
+
+  fn new_lines(&self) -> Vec<&str> {
+    self
+      .lines
+      .iter()
+      .filter_map(|line| match line {
+        HunkLine::Context(text) | HunkLine::Add(text) => Some(text.as_str()),
+        HunkLine::Remove(_) => None,
+      })
+      .collect()
+  }
+}
+
+/// The changes to a single file of a patch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePatch {
+  /// The path of the original file, or `None` when the file is created.
+  pub old_path: Option<PathBuf>,
+  /// The path of the new file, or `None` when the file is deleted.
+  pub new_path: Option<PathBuf>,
+  hunks: Vec<Hunk>,
+}
+
+impl FilePatch {
+  /// Applies the hunks to the text of the original file. A hunk whose
+  /// context moved is applied at the closest position it matches.
+  pub fn apply(&self, original: &str) -> Result<String, AnyError> {
+    let mut ends_with_newline = original.is_empty() || original.ends_with('\n');
+    let mut lines: Vec<&str> = if original.is_empty() {
+      Vec::new()
+    } else {
+      original
+        .strip_suffix('\n')
+        .unwrap_or(original)
+        .split('\n')
+        .collect()
+    };
+    // the number of lines that were added minus the removed ones so far
+    let mut offset: isize = 0;
+    for hunk in &self.hunks {
+      let old_lines = hunk.old_lines();
+      let new_lines = hunk.new_lines();
+      let expected_index = (hunk.old_start.saturating_sub(1) as isize + offset)
+        .clamp(0, lines.len() as isize) as usize;
+      let Some(index) = find_lines(&lines, &old_lines, expected_index) else {
+        bail!(
+          "Hunk at line {} does not match the contents of the file.",
+          hunk.old_start
+        );
+      };
+      let touches_end = index + old_lines.len() == lines.len();
+      if touches_end && hunk.old_missing_newline == ends_with_newline {
+        bail!(
+          "Hunk at line {} does not match the end of the file.",
+          hunk.old_start
+        );
+      }
+      lines.splice(index..index + old_lines.len(), new_lines.iter().copied());
+      if touches_end {
+        ends_with_newline = !hunk.new_missing_newline;
+      }
+      offset += new_lines.len() as isize - old_lines.len() as isize;
+    }
+    if lines.is_empty() {
+      return Ok(String::new());
+    }
+    let mut text = lines.join("\n");
+    if ends_with_newline {
+      text.push('\n');
+    }
+    Ok(text)
+  }
+}
+
+/// Finds where the lines occur in the text, searching outwards from the
+/// expected index.
+fn find_lines(text: &[&str], lines: &[&str], expected: usize) -> Option<usize> {
+  if lines.len() > text.len() {
+    return None;
+  }
+  let last_start = text.len() - lines.len();
+  let matches_at = |index: usize| text[index..index + lines.len()] == *lines;
+  let expected = expected.min(last_start);
+  for distance in 0..=last_start {
+    if let Some(index) = expected.checked_add(distance) {
+      if index <= last_start && matches_at(index) {
+        return Some(index);
+      }
+    }
+    if let Some(index) = expected.checked_sub(distance) {
+      if distance > 0 && matches_at(index) {
+        return Some(index);
+      }
+    }
+  }
+  None
+}
+
+/// Parses a unified diff into the changes of each of its files.
+pub fn parse_patch(text: &str) -> Result<Vec<FilePatch>, AnyError> {
+  let lines = text.split('\n').collect::<Vec<_>>();
+  let mut patches = Vec::new();
+  let mut i = 0;
+  while i < lines.len() {
+    let Some(old_header) = lines[i].strip_prefix("--- ") else {
+      // skip the extended headers of git and any preamble
+      i += 1;
+      continue;
+    };
+    let Some(new_header) =
+      lines.get(i + 1).and_then(|line| line.strip_prefix("+++ "))
+    else {
+      i += 1;
+      continue;
+    };
+    let line_number = i + 1;
+    let old_path = parse_header_path(old_header)
+      .with_context(|| format!("Invalid file header at line {line_number}."))?;
+    let new_path = parse_header_path(new_header).with_context(|| {
+      format!("Invalid file header at line {}.", line_number + 1)
+    })?;
+    if old_path.is_none() && new_path.is_none() {
+      bail!("Invalid file header at line {line_number}.");
+    }
+    i += 2;
+
+    let mut hunks = Vec::new();
+    while let Some(header) = lines.get(i).and_then(|l| l.strip_prefix("@@ ")) {
+      let line_number = i + 1;
+      let (old_start, old_count, new_count) = parse_hunk_header(header)
+        .with_context(|| {
+          format!("Invalid hunk header at line {line_number}.")
+        })?;
+      i += 1;
+      let mut hunk = Hunk {
+        old_start,
+        lines: Vec::new(),
+        old_missing_newline: false,
+        new_missing_newline: false,
+      };
+      let mut old_remaining = old_count;
+      let mut new_remaining = new_count;
+      while old_remaining > 0 || new_remaining > 0 {
+        let Some(line) = lines.get(i) else {
+          bail!("Unexpected end of the hunk at line {line_number}.");
+        };
+        let (kind, text) = match line.chars().next() {
+          Some(kind @ (' ' | '-' | '+')) => (kind, &line[1..]),
+          // some editors strip the trailing space of empty context lines
+          None => (' ', ""),
+          _ => bail!("Invalid line in the hunk at line {}.", i + 1),
+        };
+        let text = text.to_string();
+        match kind {
+          ' ' if old_remaining > 0 && new_remaining > 0 => {
+            old_remaining -= 1;
+            new_remaining -= 1;
+            hunk.lines.push(HunkLine::Context(text));
+          }
+          '-' if old_remaining > 0 => {
+            old_remaining -= 1;
+            hunk.lines.push(HunkLine::Remove(text));
+          }
+          '+' if new_remaining > 0 => {
+            new_remaining -= 1;
+            hunk.lines.push(HunkLine::Add(text));
+          }
+          _ => {
+            bail!("Hunk at line {line_number} has more lines than declared.")
+          }
+        }
+        i += 1;
+        if lines.get(i).is_some_and(|l| l.starts_with('\\')) {
+          // "\ No newline at end of file"
+          match hunk.lines.last() {
+            Some(HunkLine::Context(_)) => {
+              hunk.old_missing_newline = true;
+              hunk.new_missing_newline = true;
+            }
+            Some(HunkLine::Remove(_)) => hunk.old_missing_newline = true,
+            Some(HunkLine::Add(_)) => hunk.new_missing_newline = true,
+            None => {}
+          }
+          i += 1;
+        }
+      }
+      hunks.push(hunk);
+    }
+    patches.push(FilePatch {
+      old_path,
+      new_path,
+      hunks,
+    });
+  }
+  Ok(patches)
+}
+
+/// Parses the path of a `---` or `+++` header, which is `None` for
+/// `/dev/null`. The `a/` and `b/` prefixes of git are removed.
+fn parse_header_path(header: &str) -> Result<Option<PathBuf>, AnyError> {
+  // a tab separates the timestamp that `diff -u` outputs
+  let path = header.split('\t').next().unwrap().trim_end();
+  if path == "/dev/null" {
+    return Ok(None);
+  }
+  let path = path
+    .strip_prefix("a/")
+    .or_else(|| path.strip_prefix("b/"))
+    .unwrap_or(path);
+  let path = PathBuf::from(path);
+  let is_relative = path
+    .components()
+    .all(|c| matches!(c, std::path::Component::Normal(_)));
+  if path.as_os_str().is_empty() || !is_relative {
+    bail!("Expected a relative path, but found '{}'.", path.display());
+  }
+  Ok(Some(path))
+}
+
+/// Parses the ranges of a hunk header, like `-1,3 +1,4 @@`.
+fn parse_hunk_header(header: &str) -> Result<(usize, usize, usize), AnyError> {
+  fn parse_range(range: &str) -> Option<(usize, usize)> {
+    match range.split_once(',') {
+      Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+      None => Some((range.parse().ok()?, 1)),
+    }
+  }
+
+  let mut parts = header.split(' ');
+  let (Some(old_range), Some(new_range)) = (
+    parts.next().and_then(|p| p.strip_prefix('-')),
+    parts.next().and_then(|p| p.strip_prefix('+')),
+  ) else {
+    bail!("Expected the ranges of the hunk.");
+  };
+  let (Some((old_start, old_count)), Some((_, new_count))) =
+    (parse_range(old_range), parse_range(new_range))
+  else {
+    bail!("Expected the ranges of the hunk.");
+  };
+  Ok((old_start, old_count, new_count))
+}
+
+/// Applies a unified diff to the files of a directory.
+///
+/// Changed files are written to new files rather than being modified in
+/// place, so that files which are hard linked from elsewhere are unaffected.
+pub fn apply_patch_to_dir(
+  dir: &Path,
+  patch_text: &str,
+) -> Result<(), AnyError> {
+  for file_patch in parse_patch(patch_text)? {
+    let original = match &file_patch.old_path {
+      Some(old_path) => {
+        let path = dir.join(old_path);
+        std::fs::read_to_string(&path)
+          .with_context(|| format!("Reading {}", path.display()))?
+      }
+      None => String::new(),
+    };
+    let display_path = file_patch
+      .new_path
+      .as_ref()
+      .or(file_patch.old_path.as_ref())
+      .unwrap()
+      .display()
+      .to_string();
+    let text = file_patch
+      .apply(&original)
+      .with_context(|| format!("Failed patching '{display_path}'."))?;
+    match &file_patch.new_path {
+      Some(new_path) => {
+        let path = dir.join(new_path);
+        if let Some(parent) = path.parent() {
+          std::fs::create_dir_all(parent)
+            .with_context(|| format!("Creating {}", parent.display()))?;
+        }
+        atomic_write_file_with_retries(&path, text, CACHE_PERM)
+          .with_context(|| format!("Writing {}", path.display()))?;
+      }
+      None => {
+        if !text.is_empty() {
+          bail!("Failed patching '{display_path}'. The deleted file has remaining contents.");
+        }
+      }
+    }
+    if let Some(old_path) = &file_patch.old_path {
+      if file_patch.new_path.as_ref() != Some(old_path) {
+        let path = dir.join(old_path);
+        std::fs::remove_file(&path)
+          .with_context(|| format!("Removing {}", path.display()))?;
+      }
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_apply_patch() {
+    let patches = parse_patch(
+      r#"diff --git a/index.js b/index.js
+index 27bb0e4..9a3b6d2 100644
+--- a/index.js
++++ b/index.js
+@@ -1,4 +1,4 @@
+ module.exports = function (num) {
+-  return typeof num === "number";
++  return typeof num === "number" || typeof num === "bigint";
+ };
+ // end
+diff --git a/new.js b/new.js
+new file mode 100644
+--- /dev/null
++++ b/new.js
+@@ -0,0 +1 @@
++export {};
+\ No newline at end of file
+"#,
+    )
+    .unwrap();
+    assert_eq!(patches.len(), 2);
+    assert_eq!(patches[0].old_path, Some(PathBuf::from("index.js")));
+    assert_eq!(patches[1].old_path, None);
+    assert_eq!(patches[1].new_path, Some(PathBuf::from("new.js")));
+
+    // the hunk still applies after lines were inserted before it
+    let original = concat!(
+      "// header\n",
+      "module.exports = function (num) {\n",
+      "  return typeof num === \"number\";\n",
+      "};\n",
+      "// end\n",
+    );
+    assert_eq!(
+      patches[0].apply(original).unwrap(),
+      concat!(
+        "// header\n",
+        "module.exports = function (num) {\n",
+        "  return typeof num === \"number\" || typeof num === \"bigint\";\n",
+        "};\n",
+        "// end\n",
+      )
+    );
+    assert_eq!(patches[1].apply("").unwrap(), "export {};");
+    assert!(patches[0].apply("something else\n").is_err());
+  }
+
+  #[test]
+  fn test_parse_patch_rejects_paths_outside_dir() {
+    let err = parse_patch("--- a/../x.js\n+++ b/../x.js\n").unwrap_err();
+    assert_eq!(err.to_string(), "Invalid file header at line 1.");
+  }
+}
@@ -135,6 +135,8 @@ struct InternalState {
   start_time: Instant,
   keep_alive_count: usize,
   total_entries: usize,
+  /// The bytes downloaded by the entries that finished.
+  finished_size: u64,
   entries: Vec<Arc<ProgressBarEntry>>,
 }
 
@@ -152,6 +154,7 @@ impl ProgressBarInner {
         start_time: Instant::now(),
         keep_alive_count: 0,
         total_entries: 0,
+        finished_size: 0,
         entries: Vec::new(),
       })),
       renderer,
@@ -189,7 +192,8 @@ impl ProgressBarInner {
       .entries
       .binary_search_by(|e| e.id.cmp(&entry_id))
     {
-      internal_state.entries.remove(index);
+      let entry = internal_state.entries.remove(index);
+      internal_state.finished_size += entry.position();
       self.decrement_keep_alive(&mut internal_state);
     }
   }
@@ -247,6 +251,8 @@ impl DrawThreadRenderer for ProgressBarInner {
         terminal_width: size.cols,
         pending_entries: state.entries.len(),
         total_entries: state.total_entries,
+        downloaded_size: state.finished_size
+          + state.entries.iter().map(|e| e.position()).sum::<u64>(),
         display_entries,
         percent_done: {
           let mut total_percent_sum = 0f64;
@@ -25,6 +25,8 @@ pub struct ProgressData {
   pub pending_entries: usize,
   pub percent_done: f64,
   pub total_entries: usize,
+  /// The bytes downloaded by all the entries, including the finished ones.
+  pub downloaded_size: u64,
   pub duration: Duration,
 }
 
@@ -171,7 +173,19 @@ impl ProgressBarRenderer for TextOnlyProgressBarRenderer {
       )
     };
 
-    display_str.push_str(&format!("{}{}\n", fmt_elapsed_time, total_text));
+    let downloaded_text = if data.downloaded_size == 0 {
+      String::new()
+    } else {
+      format!(
+        " {}",
+        human_download_size(data.downloaded_size, data.downloaded_size)
+      )
+    };
+
+    display_str.push_str(&format!(
+      "{}{}{}\n",
+      fmt_elapsed_time, total_text, downloaded_text
+    ));
 
     for i in 0..4 {
       let Some(display_entry) = data.display_entries.get(i) else {
@@ -246,6 +260,7 @@ mod test {
   }
 
   const BYTES_TO_KIB: u64 = 2u64.pow(10);
+  const BYTES_TO_MIB: u64 = 2u64.pow(20);
 
   #[test]
   fn should_render_bar_progress() {
@@ -262,6 +277,7 @@ mod test {
       duration: Duration::from_secs(1),
       pending_entries: 1,
       total_entries: 1,
+      downloaded_size: 0,
       percent_done: 0f64,
       terminal_width: 50,
     };
@@ -319,6 +335,7 @@ mod test {
       duration: Duration::from_secs(1),
       pending_entries: 1,
       total_entries: 3,
+      downloaded_size: 0,
       percent_done: 0f64,
       terminal_width: 50,
     };
@@ -327,6 +344,12 @@ mod test {
     assert_contains!(text, "Blocking ⣯");
     assert_contains!(text, "2/3\n - data 0.00KiB/10.00KiB\n\n\n\n");
 
+    data.display_entries[0].position = 5 * BYTES_TO_KIB;
+    data.downloaded_size = 2 * BYTES_TO_MIB + 5 * BYTES_TO_KIB;
+    let text = renderer.render(data.clone());
+    let text = test_util::strip_ansi_codes(&text);
+    assert_contains!(text, "2/3 2.00MiB\n - data 5.00KiB/10.00KiB\n\n\n\n");
+
     data.pending_entries = 0;
     data.total_entries = 1;
     data.display_entries[0].position = 0;
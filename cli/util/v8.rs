@@ -1,6 +1,7 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 pub mod convert;
+pub mod profiler;
 
 #[inline(always)]
 pub fn get_v8_flags_from_env() -> Vec<String> {
@@ -0,0 +1,178 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! CPU and sampling heap profiling through a local inspector session, for
+//! the `--cpu-prof` and `--heap-prof` flags. The profiles are written in the
+//! same formats as Node.js does, which Chrome DevTools can load.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_core::LocalInspectorSession;
+
+const DEFAULT_CPU_SAMPLING_INTERVAL_US: u64 = 1000;
+const DEFAULT_HEAP_SAMPLING_INTERVAL_BYTES: u64 = 512 * 1024;
+
+/// Number of the profile in the process, distinguishing the profiles of the
+/// workers of `deno test`.
+static PROFILE_SEQUENCE: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone, Debug)]
+pub struct ProfileOptions {
+  pub dir: PathBuf,
+  pub name: Option<String>,
+  pub interval: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ProfilerOptions {
+  pub cpu: Option<ProfileOptions>,
+  pub heap: Option<ProfileOptions>,
+}
+
+impl ProfilerOptions {
+  pub fn is_enabled(&self) -> bool {
+    self.cpu.is_some() || self.heap.is_some()
+  }
+}
+
+pub struct Profiler {
+  session: LocalInspectorSession,
+  options: ProfilerOptions,
+}
+
+impl Profiler {
+  pub fn new(session: LocalInspectorSession, options: ProfilerOptions) -> Self {
+    Self { session, options }
+  }
+
+  pub async fn start(&mut self) -> Result<(), AnyError> {
+    if let Some(cpu) = &self.options.cpu {
+      let interval = cpu.interval.unwrap_or(DEFAULT_CPU_SAMPLING_INTERVAL_US);
+      self
+        .session
+        .post_message::<()>("Profiler.enable", None)
+        .await?;
+      self
+        .session
+        .post_message(
+          "Profiler.setSamplingInterval",
+          Some(json!({ "interval": interval })),
+        )
+        .await?;
+      self
+        .session
+        .post_message::<()>("Profiler.start", None)
+        .await?;
+    }
+    if let Some(heap) = &self.options.heap {
+      let interval = heap
+        .interval
+        .unwrap_or(DEFAULT_HEAP_SAMPLING_INTERVAL_BYTES);
+      self
+        .session
+        .post_message::<()>("HeapProfiler.enable", None)
+        .await?;
+      self
+        .session
+        .post_message(
+          "HeapProfiler.startSampling",
+          Some(json!({ "samplingInterval": interval })),
+        )
+        .await?;
+    }
+    Ok(())
+  }
+
+  /// Stops the profilers and writes the profiles.
+  pub async fn stop(&mut self) -> Result<(), AnyError> {
+    if let Some(cpu) = &self.options.cpu {
+      let mut result = self
+        .session
+        .post_message::<()>("Profiler.stop", None)
+        .await?;
+      write_profile(cpu, "CPU", "cpuprofile", result["profile"].take())?;
+      self
+        .session
+        .post_message::<()>("Profiler.disable", None)
+        .await?;
+    }
+    if let Some(heap) = &self.options.heap {
+      let mut result = self
+        .session
+        .post_message::<()>("HeapProfiler.stopSampling", None)
+        .await?;
+      write_profile(heap, "Heap", "heapprofile", result["profile"].take())?;
+      self
+        .session
+        .post_message::<()>("HeapProfiler.disable", None)
+        .await?;
+    }
+    Ok(())
+  }
+}
+
+fn write_profile(
+  options: &ProfileOptions,
+  prefix: &str,
+  extension: &str,
+  profile: serde_json::Value,
+) -> Result<(), AnyError> {
+  let name = match &options.name {
+    Some(name) => name.clone(),
+    None => default_profile_name(prefix, extension),
+  };
+  let path = options.dir.join(name);
+  write_profile_file(&path, &profile)
+    .with_context(|| format!("Failed writing profile to {}", path.display()))
+}
+
+fn write_profile_file(
+  path: &Path,
+  profile: &serde_json::Value,
+) -> Result<(), AnyError> {
+  if let Some(parent) = path.parent() {
+    std::fs::create_dir_all(parent)?;
+  }
+  std::fs::write(path, serde_json::to_vec(profile)?)?;
+  log::debug!("Wrote profile to {}", path.display());
+  Ok(())
+}
+
+/// Generates a file name like `CPU.20240927.134512.4242.0.cpuprofile` from
+/// the UTC time, the process id and a sequence number, like Node.js does.
+fn default_profile_name(prefix: &str, extension: &str) -> String {
+  let sequence = PROFILE_SEQUENCE.fetch_add(1, Ordering::Relaxed);
+  format!(
+    "{}.{}.{}.{}.{}",
+    prefix,
+    chrono::Utc::now().format("%Y%m%d.%H%M%S"),
+    std::process::id(),
+    sequence,
+    extension
+  )
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_default_profile_name() {
+    let name = default_profile_name("CPU", "cpuprofile");
+    let parts = name.split('.').collect::<Vec<_>>();
+    assert_eq!(parts.len(), 6);
+    assert_eq!(parts[0], "CPU");
+    assert_eq!(parts[1].len(), 8);
+    assert_eq!(parts[2].len(), 6);
+    assert_eq!(parts[3], std::process::id().to_string());
+    assert_eq!(parts[5], "cpuprofile");
+    let next_name = default_profile_name("CPU", "cpuprofile");
+    assert_ne!(name, next_name);
+  }
+}
@@ -4,6 +4,7 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::task::Poll;
 
 use deno_ast::ModuleSpecifier;
 use deno_core::anyhow::bail;
@@ -33,6 +34,7 @@ use deno_runtime::ops::worker_host::CreateWebWorkerCb;
 use deno_runtime::permissions::RuntimePermissionDescriptorParser;
 use deno_runtime::web_worker::WebWorker;
 use deno_runtime::web_worker::WebWorkerOptions;
+use deno_runtime::worker::DeferredExit;
 use deno_runtime::worker::MainWorker;
 use deno_runtime::worker::WorkerOptions;
 use deno_runtime::BootstrapOptions;
@@ -52,6 +54,8 @@ use crate::npm::CliNpmResolver;
 use crate::util::checksum;
 use crate::util::file_watcher::WatcherCommunicator;
 use crate::util::file_watcher::WatcherRestartMode;
+use crate::util::v8::profiler::Profiler;
+use crate::util::v8::profiler::ProfilerOptions;
 use crate::version;
 
 pub struct ModuleLoaderAndSourceMapGetter {
@@ -116,6 +120,7 @@ pub struct CliMainWorkerOptions {
   pub skip_op_registration: bool,
   pub create_hmr_runner: Option<CreateHmrRunnerCb>,
   pub create_coverage_collector: Option<CreateCoverageCollectorCb>,
+  pub profiler: ProfilerOptions,
   pub node_ipc: Option<i64>,
   pub serve_port: Option<u16>,
   pub serve_host: Option<String>,
@@ -176,8 +181,81 @@ impl CliMainWorker {
   pub async fn run(&mut self) -> Result<i32, AnyError> {
     let mut maybe_coverage_collector =
       self.maybe_setup_coverage_collector().await?;
+    let mut maybe_profiler = self.maybe_setup_profiler().await?;
     let mut maybe_hmr_runner = self.maybe_setup_hmr_runner().await?;
 
+    // the profiles are also written when the program exits early, so
+    // `Deno.exit()` only terminates the execution until they are
+    let maybe_deferred_exit = maybe_profiler.as_ref().map(|_| {
+      let deferred_exit = DeferredExit::new(
+        self.worker.js_runtime.v8_isolate().thread_safe_handle(),
+      );
+      self
+        .worker
+        .js_runtime
+        .op_state()
+        .borrow_mut()
+        .put(deferred_exit.clone());
+      deferred_exit
+    });
+    let result = self
+      .run_main_module(
+        &mut maybe_hmr_runner,
+        maybe_coverage_collector.is_none() && maybe_profiler.is_none(),
+      )
+      .await;
+    let exited = maybe_deferred_exit
+      .as_ref()
+      .is_some_and(|deferred_exit| deferred_exit.is_requested());
+    if result.is_err() || exited {
+      if let Some(profiler) = maybe_profiler.as_mut() {
+        self.stop_profiler_after_exit(profiler).await?;
+      }
+      if exited {
+        return Ok(self.worker.exit_code());
+      }
+    }
+    result?;
+
+    if let Some(coverage_collector) = maybe_coverage_collector.as_mut() {
+      self
+        .worker
+        .js_runtime
+        .with_event_loop_future(
+          coverage_collector.stop_collecting().boxed_local(),
+          PollEventLoopOptions::default(),
+        )
+        .await?;
+    }
+    if let Some(profiler) = maybe_profiler.as_mut() {
+      self
+        .worker
+        .js_runtime
+        .with_event_loop_future(
+          profiler.stop().boxed_local(),
+          PollEventLoopOptions::default(),
+        )
+        .await?;
+    }
+    if let Some(hmr_runner) = maybe_hmr_runner.as_mut() {
+      self
+        .worker
+        .js_runtime
+        .with_event_loop_future(
+          hmr_runner.stop().boxed_local(),
+          PollEventLoopOptions::default(),
+        )
+        .await?;
+    }
+
+    Ok(self.worker.exit_code())
+  }
+
+  async fn run_main_module(
+    &mut self,
+    maybe_hmr_runner: &mut Option<Box<dyn HmrRunner>>,
+    wait_for_inspector: bool,
+  ) -> Result<(), AnyError> {
     log::debug!("main_module {}", self.main_module);
 
     if self.is_main_cjs {
@@ -216,10 +294,7 @@ impl CliMainWorker {
           return Err(e);
         }
       } else {
-        self
-          .worker
-          .run_event_loop(maybe_coverage_collector.is_none())
-          .await?;
+        self.worker.run_event_loop(wait_for_inspector).await?;
       }
 
       let web_continue = self.worker.dispatch_beforeunload_event()?;
@@ -233,29 +308,28 @@ impl CliMainWorker {
 
     self.worker.dispatch_unload_event()?;
     self.worker.dispatch_process_exit_event()?;
+    Ok(())
+  }
 
-    if let Some(coverage_collector) = maybe_coverage_collector.as_mut() {
-      self
-        .worker
-        .js_runtime
-        .with_event_loop_future(
-          coverage_collector.stop_collecting().boxed_local(),
-          PollEventLoopOptions::default(),
-        )
-        .await?;
-    }
-    if let Some(hmr_runner) = maybe_hmr_runner.as_mut() {
-      self
-        .worker
-        .js_runtime
-        .with_event_loop_future(
-          hmr_runner.stop().boxed_local(),
-          PollEventLoopOptions::default(),
-        )
-        .await?;
-    }
-
-    Ok(self.worker.exit_code())
+  /// Writes the profiles after the program called `Deno.exit()` or failed.
+  /// The execution of JavaScript stays terminated, so the event loop only
+  /// serves the inspector session of the profiler and no more callbacks run.
+  async fn stop_profiler_after_exit(
+    &mut self,
+    profiler: &mut Profiler,
+  ) -> Result<(), AnyError> {
+    let js_runtime = &mut self.worker.js_runtime;
+    js_runtime.v8_isolate().terminate_execution();
+    let mut stop = profiler.stop().boxed_local();
+    std::future::poll_fn(|cx| {
+      if let Poll::Ready(result) = stop.poll_unpin(cx) {
+        return Poll::Ready(result);
+      }
+      // the errors of the terminated callbacks are expected
+      let _ = js_runtime.poll_event_loop(cx, PollEventLoopOptions::default());
+      Poll::Pending
+    })
+    .await
   }
 
   pub async fn run_for_watcher(self) -> Result<(), AnyError> {
@@ -403,6 +477,27 @@ impl CliMainWorker {
     Ok(Some(coverage_collector))
   }
 
+  pub async fn maybe_setup_profiler(
+    &mut self,
+  ) -> Result<Option<Profiler>, AnyError> {
+    if !self.shared.options.profiler.is_enabled() {
+      return Ok(None);
+    }
+
+    let session = self.worker.create_inspector_session();
+    let mut profiler =
+      Profiler::new(session, self.shared.options.profiler.clone());
+    self
+      .worker
+      .js_runtime
+      .with_event_loop_future(
+        profiler.start().boxed_local(),
+        PollEventLoopOptions::default(),
+      )
+      .await?;
+    Ok(Some(profiler))
+  }
+
   pub fn execute_script_static(
     &mut self,
     name: &'static str,
@@ -410,6 +505,11 @@ impl CliMainWorker {
   ) -> Result<v8::Global<v8::Value>, AnyError> {
     self.worker.js_runtime.execute_script(name, source_code)
   }
+
+  /// Makes a value available to the worker's ops.
+  pub fn put_op_state<T: 'static>(&mut self, value: T) {
+    self.worker.js_runtime.op_state().borrow_mut().put(value);
+  }
 }
 
 #[derive(Clone)]
@@ -808,6 +908,7 @@ fn create_web_worker_callback(
       node_services: Some(shared.create_node_init_services()),
       worker_type: args.worker_type,
       maybe_inspector_server,
+      should_wait_for_inspector_session: shared.options.inspect_wait,
       get_error_class_fn: Some(&errors::get_error_class_name),
       blob_store: shared.blob_store.clone(),
       broadcast_channel: shared.broadcast_channel.clone(),
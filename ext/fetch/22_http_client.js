@@ -13,7 +13,10 @@
 import { core, primordials } from "ext:core/mod.js";
 
 import { SymbolDispose } from "ext:deno_web/00_infra.js";
-import { op_fetch_custom_client } from "ext:core/ops";
+import {
+  op_fetch_client_close_idle_connections,
+  op_fetch_custom_client,
+} from "ext:core/ops";
 import { loadTlsKeyPair } from "ext:deno_net/02_tls.js";
 
 const { internalRidSymbol } = core;
@@ -53,6 +56,10 @@ class HttpClient {
     core.close(this.#rid);
   }
 
+  closeIdleConnections() {
+    op_fetch_client_close_idle_connections(this.#rid);
+  }
+
   [SymbolDispose]() {
     core.tryClose(this.#rid);
   }
@@ -88,6 +88,7 @@ function processUrlList(urlList, urlListProcessed) {
  * @property {(() => string)[]} urlList
  * @property {string[]} urlListProcessed
  * @property {number | null} clientRid NOTE: non standard extension for `Deno.HttpClient`.
+ * @property {((progress: Deno.UploadProgress) => void) | null} onUploadProgress NOTE: non standard extension.
  * @property {Blob | null} blobUrlEntry
  */
 
@@ -136,6 +137,7 @@ function newInnerRequest(method, url, headerList, body, maybeBlob) {
     urlList: [typeof url === "string" ? () => url : url],
     urlListProcessed: [],
     clientRid: null,
+    onUploadProgress: null,
     blobUrlEntry,
     url() {
       if (this.urlListProcessed[0] === undefined) {
@@ -187,6 +189,7 @@ function cloneInnerRequest(request, skipBody = false) {
     urlList: [() => request.url()],
     urlListProcessed: [request.url()],
     clientRid: request.clientRid,
+    onUploadProgress: request.onUploadProgress,
     blobUrlEntry: request.blobUrlEntry,
     url() {
       if (this.urlListProcessed[0] === undefined) {
@@ -381,6 +384,22 @@ class Request {
       request.clientRid = init.client?.[internalRidSymbol] ?? null;
     }
 
+    // NOTE: non standard extension. This handles the onUploadProgress callback
+    if (init.onUploadProgress !== undefined) {
+      if (
+        init.onUploadProgress !== null &&
+        typeof init.onUploadProgress !== "function"
+      ) {
+        throw webidl.makeException(
+          TypeError,
+          "`onUploadProgress` must be a function",
+          prefix,
+          "Argument 2",
+        );
+      }
+      request.onUploadProgress = init.onUploadProgress;
+    }
+
     // 28.
     this[_request] = request;
 
@@ -573,6 +592,7 @@ webidl.converters["RequestInit"] = webidl.createDictionaryConverter(
       ),
     },
     { key: "client", converter: webidl.converters.any },
+    { key: "onUploadProgress", converter: webidl.converters.any },
   ],
 );
 
@@ -35,6 +35,8 @@ const {
   ArrayPrototypePush,
   ObjectDefineProperties,
   ObjectPrototypeIsPrototypeOf,
+  PromisePrototypeThen,
+  PromiseResolve,
   RangeError,
   RegExpPrototypeExec,
   SafeArrayIterator,
@@ -72,6 +74,8 @@ const _brand = webidl.brand;
  * @property {null | typeof __window.bootstrap.fetchBody.InnerBody} body
  * @property {boolean} aborted
  * @property {string} [error]
+ * @property {Promise<[string, string][] | null>} [trailers]
+ * @property {string | null} [protocol]
  */
 
 /**
@@ -116,6 +120,8 @@ function cloneInnerResponse(response) {
     status: response.status,
     statusMessage: response.statusMessage,
     aborted: response.aborted,
+    trailers: response.trailers,
+    protocol: response.protocol,
     url() {
       if (this.urlList.length == 0) return null;
       return this.urlList[this.urlList.length - 1];
@@ -391,6 +397,32 @@ class Response {
     return this[_headers];
   }
 
+  /**
+   * Non-standard: the trailers received after the body of a `fetch()`
+   * response whose request sent `TE: trailers`.
+   * @returns {Promise<Headers>}
+   */
+  get trailers() {
+    webidl.assertBranded(this, ResponsePrototype);
+    const trailers = this[_response].trailers;
+    if (trailers === undefined) {
+      return PromiseResolve(headersFromHeaderList([], "immutable"));
+    }
+    return PromisePrototypeThen(
+      trailers,
+      (list) => headersFromHeaderList(list ?? [], "immutable"),
+    );
+  }
+
+  /**
+   * Non-standard: the protocol a `fetch()` response was received with.
+   * @returns {string | null}
+   */
+  get protocol() {
+    webidl.assertBranded(this, ResponsePrototype);
+    return this[_response].protocol ?? null;
+  }
+
   /**
    * @returns {Response}
    */
@@ -13,7 +13,9 @@
 import { core, primordials } from "ext:core/mod.js";
 import {
   op_fetch,
+  op_fetch_response_trailers,
   op_fetch_send,
+  op_fetch_upload_progress,
   op_wasm_streaming_feed,
   op_wasm_streaming_set_url,
 } from "ext:core/ops";
@@ -29,6 +31,7 @@ const {
   PromisePrototypeCatch,
   SafeArrayIterator,
   String,
+  StringPrototypeIncludes,
   StringPrototypeStartsWith,
   StringPrototypeToLowerCase,
   TypeError,
@@ -37,6 +40,7 @@ const {
 
 import * as webidl from "ext:deno_webidl/00_webidl.js";
 import { byteLowerCase } from "ext:deno_web/00_infra.js";
+import { reportError } from "ext:deno_web/02_event.js";
 import {
   errorReadableStream,
   getReadableStreamResourceBacking,
@@ -90,6 +94,41 @@ function createResponseBodyStream(responseBodyRid, terminator) {
   return readable;
 }
 
+/**
+ * Whether the request accepts trailers after the response body, by sending
+ * `TE: trailers`.
+ * @param {[string, string][]} headerList
+ * @returns {boolean}
+ */
+function acceptsTrailers(headerList) {
+  for (let i = 0; i < headerList.length; i++) {
+    const { 0: name, 1: value } = headerList[i];
+    if (
+      byteLowerCase(name) === "te" &&
+      StringPrototypeIncludes(byteLowerCase(value), "trailers")
+    ) {
+      return true;
+    }
+  }
+  return false;
+}
+
+/**
+ * @param {number} rid
+ * @param {(progress: Deno.UploadProgress) => void} onUploadProgress
+ */
+async function reportUploadProgress(rid, onUploadProgress) {
+  try {
+    while (true) {
+      const progress = await op_fetch_upload_progress(rid);
+      if (progress === null) break;
+      onUploadProgress({ loaded: progress[0], total: progress[1] });
+    }
+  } finally {
+    core.tryClose(rid);
+  }
+}
+
 /**
  * @param {InnerRequest} req
  * @param {boolean} recursive
@@ -149,7 +188,7 @@ async function mainFetch(req, recursive, terminator) {
     }
   }
 
-  const { requestRid, cancelHandleRid } = op_fetch(
+  const { requestRid, cancelHandleRid, uploadProgressRid } = op_fetch(
     req.method,
     req.currentUrl(),
     req.headerList,
@@ -157,7 +196,25 @@ async function mainFetch(req, recursive, terminator) {
     reqBody !== null || reqRid !== null,
     reqBody,
     reqRid,
+    req.onUploadProgress !== null,
   );
+  let responded = false;
+  let uploadProgressError = null;
+  if (uploadProgressRid !== null) {
+    PromisePrototypeCatch(
+      reportUploadProgress(uploadProgressRid, req.onUploadProgress),
+      (err) => {
+        // A throwing callback aborts the request, which then rejects with its
+        // error. Once the response was received, the error is only reported.
+        if (responded) {
+          reportError(err);
+          return;
+        }
+        uploadProgressError = { err };
+        onAbort();
+      },
+    );
+  }
 
   function onAbort() {
     if (cancelHandleRid !== null) {
@@ -169,13 +226,19 @@ async function mainFetch(req, recursive, terminator) {
   try {
     resp = await opFetchSend(requestRid);
   } catch (err) {
+    if (uploadProgressError !== null) throw uploadProgressError.err;
     if (terminator.aborted) return abortedNetworkError();
     throw err;
   } finally {
+    responded = true;
     if (cancelHandleRid !== null) {
       core.tryClose(cancelHandleRid);
     }
   }
+  if (uploadProgressError !== null) {
+    core.tryClose(resp.responseRid);
+    throw uploadProgressError.err;
+  }
   // Re-throw any body errors
   if (resp.error !== null) {
     const { 0: message, 1: cause } = resp.error;
@@ -191,6 +254,7 @@ async function mainFetch(req, recursive, terminator) {
     status: resp.status,
     body: null,
     statusMessage: resp.statusText,
+    protocol: resp.protocol,
     type: "basic",
     url() {
       if (this.urlList.length == 0) return null;
@@ -220,6 +284,12 @@ async function mainFetch(req, recursive, terminator) {
       response.body = null;
       core.close(resp.responseRid);
     } else {
+      if (acceptsTrailers(req.headerList)) {
+        // Must be called before the body is read and its resource closed
+        const trailers = op_fetch_response_trailers(resp.responseRid);
+        core.unrefOpPromise(trailers);
+        response.trailers = trailers;
+      }
       response.body = new InnerBody(
         createResponseBodyStream(resp.responseRid, terminator),
       );
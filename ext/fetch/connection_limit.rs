@@ -0,0 +1,140 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! A connector capping the number of connections that a client keeps open,
+//! whether they're in use or idle in its pool.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+
+use http::Uri;
+use hyper_util::client::legacy::connect::Connected;
+use hyper_util::client::legacy::connect::Connection;
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::Semaphore;
+use tower_service::Service;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Clone)]
+pub(crate) struct ConnectionLimit<C> {
+  inner: C,
+  permits: Option<Arc<Semaphore>>,
+}
+
+impl<C> ConnectionLimit<C> {
+  pub(crate) fn new(inner: C, max_connections: Option<usize>) -> Self {
+    Self {
+      inner,
+      permits: max_connections.map(|max| Arc::new(Semaphore::new(max))),
+    }
+  }
+}
+
+/// A connection holding one of the permits of its [`ConnectionLimit`] until
+/// it's closed.
+pub struct Limited<T> {
+  io: T,
+  _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl<C> Service<Uri> for ConnectionLimit<C>
+where
+  C: Service<Uri> + Clone + Send + 'static,
+  C::Future: Send + 'static,
+  C::Error: Into<BoxError>,
+{
+  type Response = Limited<C::Response>;
+  type Error = BoxError;
+  type Future = BoxFuture<Result<Self::Response, Self::Error>>;
+
+  fn poll_ready(
+    &mut self,
+    cx: &mut Context<'_>,
+  ) -> Poll<Result<(), Self::Error>> {
+    self.inner.poll_ready(cx).map_err(Into::into)
+  }
+
+  fn call(&mut self, dst: Uri) -> Self::Future {
+    let Some(permits) = self.permits.clone() else {
+      let connecting = self.inner.call(dst);
+      return Box::pin(async move {
+        let io = connecting.await.map_err(Into::into)?;
+        Ok(Limited { io, _permit: None })
+      });
+    };
+    // Connecting only starts once a permit is available. The connectors of
+    // the proxies are always ready, so a clone of them can be called later.
+    let mut inner = self.inner.clone();
+    std::mem::swap(&mut self.inner, &mut inner);
+    Box::pin(async move {
+      let permit = permits.acquire_owned().await?;
+      let io = inner.call(dst).await.map_err(Into::into)?;
+      Ok(Limited {
+        io,
+        _permit: Some(permit),
+      })
+    })
+  }
+}
+
+impl<T> hyper::rt::Read for Limited<T>
+where
+  T: hyper::rt::Read + Unpin,
+{
+  fn poll_read(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: hyper::rt::ReadBufCursor<'_>,
+  ) -> Poll<std::io::Result<()>> {
+    Pin::new(&mut self.io).poll_read(cx, buf)
+  }
+}
+
+impl<T> hyper::rt::Write for Limited<T>
+where
+  T: hyper::rt::Write + Unpin,
+{
+  fn poll_write(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &[u8],
+  ) -> Poll<std::io::Result<usize>> {
+    Pin::new(&mut self.io).poll_write(cx, buf)
+  }
+
+  fn poll_flush(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<std::io::Result<()>> {
+    Pin::new(&mut self.io).poll_flush(cx)
+  }
+
+  fn poll_shutdown(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<std::io::Result<()>> {
+    Pin::new(&mut self.io).poll_shutdown(cx)
+  }
+
+  fn is_write_vectored(&self) -> bool {
+    self.io.is_write_vectored()
+  }
+
+  fn poll_write_vectored(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    bufs: &[std::io::IoSlice<'_>],
+  ) -> Poll<std::io::Result<usize>> {
+    Pin::new(&mut self.io).poll_write_vectored(cx, bufs)
+  }
+}
+
+impl<T: Connection> Connection for Limited<T> {
+  fn connected(&self) -> Connected {
+    self.io.connected()
+  }
+}
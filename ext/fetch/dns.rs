@@ -0,0 +1,120 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! The resolvers used by HTTP clients to look up the addresses of hosts.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::io;
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+
+use deno_core::error::AnyError;
+use hyper_util::client::legacy::connect::dns::GaiResolver;
+use hyper_util::client::legacy::connect::dns::Name;
+use tower_service::Service;
+use trust_dns_resolver::system_conf;
+use trust_dns_resolver::TokioAsyncResolver;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+#[derive(Clone, Debug, Default)]
+pub struct Resolver {
+  kind: ResolverKind,
+  /// Static addresses of hosts, looked up before querying the resolver.
+  hosts: Arc<HashMap<String, Vec<IpAddr>>>,
+}
+
+#[derive(Clone, Debug)]
+enum ResolverKind {
+  /// Resolves through `getaddrinfo` on a blocking thread.
+  System(GaiResolver),
+  /// Resolves with the name servers of the system configuration, caching
+  /// the answers for as long as their TTL.
+  BuiltIn(TokioAsyncResolver),
+}
+
+impl Default for ResolverKind {
+  fn default() -> Self {
+    Self::System(GaiResolver::new())
+  }
+}
+
+impl Resolver {
+  /// A resolver deferring to the one of the operating system.
+  pub fn system() -> Self {
+    Self::default()
+  }
+
+  /// A resolver querying the name servers of the system configuration
+  /// itself. Up to `cache_size` answers are cached; 0 disables the cache.
+  pub fn built_in(cache_size: usize) -> Result<Self, AnyError> {
+    let (config, mut opts) = system_conf::read_system_conf()?;
+    opts.cache_size = cache_size;
+    Ok(Self {
+      kind: ResolverKind::BuiltIn(TokioAsyncResolver::tokio(config, opts)),
+      hosts: Default::default(),
+    })
+  }
+
+  /// Resolves the given hosts to fixed addresses instead, like the
+  /// `--resolve` option of curl.
+  pub fn with_hosts(mut self, hosts: HashMap<String, Vec<IpAddr>>) -> Self {
+    let hosts = hosts
+      .into_iter()
+      .map(|(host, addrs)| (host.to_ascii_lowercase(), addrs))
+      .collect();
+    self.hosts = Arc::new(hosts);
+    self
+  }
+}
+
+impl Service<Name> for Resolver {
+  type Response = std::vec::IntoIter<SocketAddr>;
+  type Error = io::Error;
+  type Future = BoxFuture<Result<Self::Response, Self::Error>>;
+
+  fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    match &mut self.kind {
+      ResolverKind::System(gai) => gai.poll_ready(cx),
+      ResolverKind::BuiltIn(_) => Poll::Ready(Ok(())),
+    }
+  }
+
+  fn call(&mut self, name: Name) -> Self::Future {
+    // The port is filled in by the connector.
+    if let Some(addrs) = self.hosts.get(&name.as_str().to_ascii_lowercase()) {
+      let addrs = addrs
+        .iter()
+        .map(|ip| SocketAddr::new(*ip, 0))
+        .collect::<Vec<_>>();
+      return Box::pin(async move { Ok(addrs.into_iter()) });
+    }
+    match &mut self.kind {
+      ResolverKind::System(gai) => {
+        let resolving = gai.call(name);
+        Box::pin(async move {
+          let addrs = resolving.await?;
+          Ok(addrs.collect::<Vec<_>>().into_iter())
+        })
+      }
+      ResolverKind::BuiltIn(resolver) => {
+        let resolver = resolver.clone();
+        Box::pin(async move {
+          let lookup = resolver
+            .lookup_ip(name.as_str())
+            .await
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+          let addrs = lookup
+            .iter()
+            .map(|ip| SocketAddr::new(ip, 0))
+            .collect::<Vec<_>>();
+          Ok(addrs.into_iter())
+        })
+      }
+    }
+  }
+}
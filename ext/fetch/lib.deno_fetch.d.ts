@@ -377,6 +377,25 @@ declare interface Response extends Body {
   readonly statusText: string;
   readonly type: ResponseType;
   readonly url: string;
+  /** Non-standard: resolves to the trailers received after the body, once it
+   * has been read to the end. Trailers are only received for `fetch()`
+   * requests that accept them with a `TE: trailers` header, and resolve to
+   * empty headers otherwise.
+   *
+   * ```ts
+   * const res = await fetch("https://grpc.example/Service/Method", {
+   *   method: "POST",
+   *   headers: { "content-type": "application/grpc", "te": "trailers" },
+   *   body,
+   * });
+   * await res.arrayBuffer();
+   * console.log((await res.trailers).get("grpc-status"));
+   * ```
+   */
+  readonly trailers: Promise<Headers>;
+  /** Non-standard: the protocol a `fetch()` response was received with,
+   * `"h2"` or `"http/1.1"`, and `null` for other responses. */
+  readonly protocol: string | null;
   clone(): Response;
 }
 
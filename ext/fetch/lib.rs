@@ -1,14 +1,26 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+mod connection_limit;
+pub mod dns;
 mod fs_fetch_handler;
 mod proxy;
+mod stream_limit;
+mod tcp_connector;
 #[cfg(test)]
 mod tests;
+mod upload;
 
+use crate::connection_limit::ConnectionLimit;
+use crate::stream_limit::StreamLimit;
+use crate::stream_limit::StreamLimited;
+use crate::tcp_connector::TcpConnector;
+use crate::upload::UploadBody;
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::cmp::min;
+use std::collections::HashMap;
 use std::convert::From;
+use std::net::IpAddr;
 use std::path::Path;
 use std::path::PathBuf;
 use std::pin::Pin;
@@ -21,12 +33,14 @@ use deno_core::anyhow::anyhow;
 use deno_core::anyhow::Error;
 use deno_core::error::type_error;
 use deno_core::error::AnyError;
+use deno_core::futures::future;
 use deno_core::futures::stream::Peekable;
 use deno_core::futures::Future;
 use deno_core::futures::FutureExt;
 use deno_core::futures::Stream;
 use deno_core::futures::StreamExt;
 use deno_core::futures::TryFutureExt;
+use deno_core::futures::TryStreamExt;
 use deno_core::op2;
 use deno_core::url::Url;
 use deno_core::AsyncRefCell;
@@ -42,6 +56,8 @@ use deno_core::OpState;
 use deno_core::RcRef;
 use deno_core::Resource;
 use deno_core::ResourceId;
+use deno_net::happy_eyeballs::LocalBinding;
+use deno_tls::rustls::client::Resumption;
 use deno_tls::rustls::RootCertStore;
 use deno_tls::Proxy;
 use deno_tls::RootCertStoreProvider;
@@ -62,16 +78,19 @@ use http::header::PROXY_AUTHORIZATION;
 use http::header::RANGE;
 use http::header::USER_AGENT;
 use http::Extensions;
+use http::HeaderMap;
 use http::Method;
 use http::Uri;
 use http_body_util::BodyExt;
+use http_body_util::BodyStream;
 use hyper::body::Frame;
-use hyper_util::client::legacy::connect::HttpConnector;
 use hyper_util::client::legacy::connect::HttpInfo;
 use hyper_util::rt::TokioExecutor;
 use hyper_util::rt::TokioTimer;
 use serde::Deserialize;
 use serde::Serialize;
+use tokio::sync::oneshot;
+use tokio::sync::watch;
 use tower::ServiceExt;
 use tower_http::decompression::Decompression;
 
@@ -123,8 +142,11 @@ deno_core::extension!(deno_fetch,
   ops = [
     op_fetch<FP>,
     op_fetch_send,
+    op_fetch_response_trailers,
+    op_fetch_upload_progress,
     op_utf8_to_byte_string,
     op_fetch_custom_client<FP>,
+    op_fetch_client_close_idle_connections,
   ],
   esm = [
     "20_headers.js",
@@ -187,6 +209,7 @@ pub fn get_declaration() -> PathBuf {
 pub struct FetchReturn {
   pub request_rid: ResourceId,
   pub cancel_handle_rid: Option<ResourceId>,
+  pub upload_progress_rid: Option<ResourceId>,
 }
 
 pub fn get_or_create_client_from_state(
@@ -221,8 +244,19 @@ pub fn create_client_from_options(
         .unwrap_or_default(),
       pool_max_idle_per_host: None,
       pool_idle_timeout: None,
+      max_connections: None,
+      dns_resolver: dns::Resolver::default(),
+      happy_eyeballs_delay: None,
+      local_address: None,
+      interface: None,
+      tls_session_cache_size: None,
+      revocation: None,
       http1: true,
       http2: true,
+      http2_prior_knowledge: false,
+      http2_max_concurrent_streams: None,
+      http2_initial_stream_window_size: None,
+      http2_initial_connection_window_size: None,
     },
   )
 }
@@ -346,13 +380,14 @@ pub fn op_fetch<FP>(
   has_body: bool,
   #[buffer] data: Option<JsBuffer>,
   #[smi] resource: Option<ResourceId>,
+  track_upload_progress: bool,
 ) -> Result<FetchReturn, AnyError>
 where
   FP: FetchPermissions + 'static,
 {
   let (client, allow_host) = if let Some(rid) = client_rid {
     let r = state.resource_table.get::<HttpClientResource>(rid)?;
-    (r.client.clone(), r.allow_host)
+    (r.client(), r.allow_host)
   } else {
     (get_or_create_client_from_state(state)?, false)
   };
@@ -362,6 +397,7 @@ where
 
   // Check scheme before asking for net permission
   let scheme = url.scheme();
+  let mut upload_progress_rid = None;
   let (request_rid, cancel_handle_rid) = match scheme {
     "file" => {
       let path = url.to_file_path().map_err(|_| {
@@ -405,6 +441,9 @@ where
         .map_err(|_| type_error(format!("Invalid URL {url}")))?;
 
       let mut con_len = None;
+      // Streams of unknown length are sent with the length of their
+      // Content-Length header, if any, instead of chunked.
+      let mut declared_len = None;
       let body = if has_body {
         match (data, resource) {
           (Some(data), _) => {
@@ -421,7 +460,10 @@ where
               (body_size, Some(n)) if body_size == n && body_size > 0 => {
                 con_len = Some(body_size);
               }
-              _ => {}
+              _ => {
+                declared_len = declared_content_length(&headers)?;
+                con_len = declared_len;
+              }
             }
             ReqBody::new(ResourceToBodyAdapter::new(resource))
           }
@@ -438,6 +480,23 @@ where
           .boxed()
       };
 
+      let progress = if track_upload_progress {
+        let (tx, rx) = watch::channel(0);
+        upload_progress_rid =
+          Some(state.resource_table.add(UploadProgressResource {
+            progress: AsyncRefCell::new(rx),
+            total: con_len,
+          }));
+        Some(tx)
+      } else {
+        None
+      };
+      let body = if declared_len.is_some() || progress.is_some() {
+        ReqBody::new(UploadBody::new(body, declared_len, progress))
+      } else {
+        body
+      };
+
       let mut request = http::Request::new(body);
       *request.method_mut() = method.clone();
       *request.uri_mut() = uri.clone();
@@ -538,9 +597,31 @@ where
   Ok(FetchReturn {
     request_rid,
     cancel_handle_rid,
+    upload_progress_rid,
   })
 }
 
+fn declared_content_length(
+  headers: &[(ByteString, ByteString)],
+) -> Result<Option<u64>, AnyError> {
+  let Some((_, value)) = headers
+    .iter()
+    .find(|(name, _)| name.eq_ignore_ascii_case(CONTENT_LENGTH.as_ref()))
+  else {
+    return Ok(None);
+  };
+  std::str::from_utf8(value)
+    .ok()
+    .and_then(|value| value.trim().parse().ok())
+    .map(Some)
+    .ok_or_else(|| {
+      type_error(format!(
+        "Invalid Content-Length header: received {:?}",
+        String::from_utf8_lossy(value)
+      ))
+    })
+}
+
 #[derive(Default, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FetchResponse {
@@ -552,6 +633,8 @@ pub struct FetchResponse {
   pub content_length: Option<u64>,
   pub remote_addr_ip: Option<String>,
   pub remote_addr_port: Option<u16>,
+  /// The protocol the response was received with, `h2` or `http/1.1`.
+  pub protocol: Option<&'static str>,
   /// This field is populated if some error occurred which needs to be
   /// reconstructed in the JS side to set the error _cause_.
   /// In the tuple, the first element is an error message and the second one is
@@ -616,6 +699,12 @@ pub async fn op_fetch_send(
   } else {
     (None, None)
   };
+  let protocol = match res.version() {
+    http::Version::HTTP_2 => Some("h2"),
+    http::Version::HTTP_11 => Some("http/1.1"),
+    http::Version::HTTP_10 => Some("http/1.0"),
+    _ => None,
+  };
 
   let response_rid = state
     .borrow_mut()
@@ -631,10 +720,74 @@ pub async fn op_fetch_send(
     content_length,
     remote_addr_ip,
     remote_addr_port,
+    protocol,
     error: None,
   })
 }
 
+/// Resolves to the trailers of a response once its body has been read to the
+/// end, or `None` if it had no trailers. This must be called while the response
+/// resource is still open.
+#[op2(async)]
+#[serde]
+pub async fn op_fetch_response_trailers(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+) -> Result<Option<Vec<(ByteString, ByteString)>>, AnyError> {
+  let trailers_rx = state
+    .borrow()
+    .resource_table
+    .get::<FetchResponseResource>(rid)?
+    .trailers_rx
+    .borrow_mut()
+    .take();
+  let Some(trailers_rx) = trailers_rx else {
+    return Ok(None);
+  };
+  // The sender is dropped when the body ends without trailers
+  let Ok(trailers) = trailers_rx.await else {
+    return Ok(None);
+  };
+  Ok(Some(
+    trailers
+      .iter()
+      .map(|(name, value)| (name.as_str().into(), value.as_bytes().into()))
+      .collect(),
+  ))
+}
+
+pub struct UploadProgressResource {
+  progress: AsyncRefCell<watch::Receiver<u64>>,
+  total: Option<u64>,
+}
+
+impl Resource for UploadProgressResource {
+  fn name(&self) -> Cow<str> {
+    "fetchUploadProgress".into()
+  }
+}
+
+/// Resolves to the number of bytes of the request body sent so far, along
+/// with the length of the body if known, once it changed. Resolves to `None`
+/// when the body is done being sent.
+#[op2(async)]
+#[serde]
+pub async fn op_fetch_upload_progress(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+) -> Result<Option<(u64, Option<u64>)>, AnyError> {
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<UploadProgressResource>(rid)?;
+  let mut progress = RcRef::map(&resource, |r| &r.progress).borrow_mut().await;
+  if progress.changed().await.is_err() {
+    return Ok(None);
+  }
+  let sent = *progress.borrow_and_update();
+  Ok(Some((sent, resource.total)))
+}
+
 type CancelableResponseResult =
   Result<Result<http::Response<ResBody>, AnyError>, Canceled>;
 
@@ -680,14 +833,20 @@ pub struct FetchResponseResource {
   pub response_reader: AsyncRefCell<FetchResponseReader>,
   pub cancel: CancelHandle,
   pub size: Option<u64>,
+  /// Sends the trailers of the response once its body has been read to the end.
+  trailers_tx: RefCell<Option<oneshot::Sender<HeaderMap>>>,
+  trailers_rx: RefCell<Option<oneshot::Receiver<HeaderMap>>>,
 }
 
 impl FetchResponseResource {
   pub fn new(response: http::Response<ResBody>, size: Option<u64>) -> Self {
+    let (trailers_tx, trailers_rx) = oneshot::channel();
     Self {
       response_reader: AsyncRefCell::new(FetchResponseReader::Start(response)),
       cancel: CancelHandle::default(),
       size,
+      trailers_tx: RefCell::new(Some(trailers_tx)),
+      trailers_rx: RefCell::new(Some(trailers_rx)),
     }
   }
 
@@ -718,12 +877,27 @@ impl Resource for FetchResponseResource {
 
         match std::mem::take(&mut *reader) {
           FetchResponseReader::Start(resp) => {
-            let stream: BytesStream =
-              Box::pin(resp.into_body().into_data_stream().map(|r| {
-                r.map_err(|err| {
-                  std::io::Error::new(std::io::ErrorKind::Other, err)
+            let mut trailers_tx = self.trailers_tx.borrow_mut().take();
+            let stream: BytesStream = Box::pin(
+              BodyStream::new(resp.into_body())
+                .try_filter_map(move |frame| {
+                  let data = match frame.into_data() {
+                    Ok(data) => Some(data),
+                    Err(frame) => {
+                      if let (Ok(trailers), Some(tx)) =
+                        (frame.into_trailers(), trailers_tx.take())
+                      {
+                        let _ = tx.send(trailers);
+                      }
+                      None
+                    }
+                  };
+                  future::ready(Ok(data))
                 })
-              }));
+                .map_err(|err| {
+                  std::io::Error::new(std::io::ErrorKind::Other, err)
+                }),
+            );
             *reader = FetchResponseReader::BodyReader(stream.peekable());
           }
           FetchResponseReader::BodyReader(_) => unreachable!(),
@@ -768,7 +942,7 @@ impl Resource for FetchResponseResource {
 }
 
 pub struct HttpClientResource {
-  pub client: Client,
+  client: RefCell<Client>,
   pub allow_host: bool,
 }
 
@@ -780,7 +954,14 @@ impl Resource for HttpClientResource {
 
 impl HttpClientResource {
   fn new(client: Client, allow_host: bool) -> Self {
-    Self { client, allow_host }
+    Self {
+      client: RefCell::new(client),
+      allow_host,
+    }
+  }
+
+  pub fn client(&self) -> Client {
+    self.client.borrow().clone()
   }
 }
 
@@ -791,11 +972,23 @@ pub struct CreateHttpClientArgs {
   proxy: Option<Proxy>,
   pool_max_idle_per_host: Option<usize>,
   pool_idle_timeout: Option<serde_json::Value>,
+  max_connections: Option<usize>,
+  dns: Option<DnsArgs>,
+  happy_eyeballs_delay: Option<u64>,
+  local_address: Option<IpAddr>,
+  interface: Option<String>,
+  tls_session_cache_size: Option<usize>,
+  revocation: Option<deno_tls::RevocationCheck>,
   #[serde(default = "default_true")]
   http1: bool,
   #[serde(default = "default_true")]
   http2: bool,
   #[serde(default)]
+  http2_prior_knowledge: bool,
+  http2_max_concurrent_streams: Option<usize>,
+  http2_initial_stream_window_size: Option<u32>,
+  http2_initial_connection_window_size: Option<u32>,
+  #[serde(default)]
   allow_host: bool,
 }
 
@@ -803,6 +996,56 @@ fn default_true() -> bool {
   true
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsArgs {
+  resolver: Option<DnsResolverKind>,
+  cache_size: Option<usize>,
+  #[serde(default)]
+  hosts: HashMap<String, Vec<String>>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+enum DnsResolverKind {
+  System,
+  Builtin,
+}
+
+impl DnsArgs {
+  fn into_resolver(self) -> Result<dns::Resolver, AnyError> {
+    let resolver = match (self.resolver, self.cache_size) {
+      (None | Some(DnsResolverKind::System), None) => dns::Resolver::system(),
+      (None | Some(DnsResolverKind::System), Some(_)) => {
+        return Err(type_error(
+          "'cacheSize' is only supported by the \"builtin\" resolver",
+        ))
+      }
+      (Some(DnsResolverKind::Builtin), cache_size) => {
+        dns::Resolver::built_in(cache_size.unwrap_or(DEFAULT_DNS_CACHE_SIZE))?
+      }
+    };
+    let mut hosts = HashMap::with_capacity(self.hosts.len());
+    for (host, addrs) in self.hosts {
+      let addrs = addrs
+        .iter()
+        .map(|addr| {
+          addr.parse::<IpAddr>().map_err(|_| {
+            type_error(format!(
+              "Invalid IP address for host '{host}': received '{addr}'"
+            ))
+          })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+      hosts.insert(host, addrs);
+    }
+    Ok(resolver.with_hosts(hosts))
+  }
+}
+
+/// The number of answers cached by the built-in resolver by default.
+const DEFAULT_DNS_CACHE_SIZE: usize = 32;
+
 #[op2]
 #[smi]
 pub fn op_fetch_custom_client<FP>(
@@ -813,6 +1056,9 @@ pub fn op_fetch_custom_client<FP>(
 where
   FP: FetchPermissions + 'static,
 {
+  if args.max_connections == Some(0) {
+    return Err(type_error("'maxConnections' must be greater than 0"));
+  }
   if let Some(proxy) = args.proxy.clone() {
     let permissions = state.borrow_mut::<FP>();
     let url = Url::parse(&proxy.url)?;
@@ -847,8 +1093,24 @@ where
           _ => Some(None),
         },
       ),
+      max_connections: args.max_connections,
+      dns_resolver: args
+        .dns
+        .map(DnsArgs::into_resolver)
+        .transpose()?
+        .unwrap_or_default(),
+      happy_eyeballs_delay: args.happy_eyeballs_delay,
+      local_address: args.local_address,
+      interface: args.interface,
+      tls_session_cache_size: args.tls_session_cache_size,
+      revocation: args.revocation,
       http1: args.http1,
       http2: args.http2,
+      http2_prior_knowledge: args.http2_prior_knowledge,
+      http2_max_concurrent_streams: args.http2_max_concurrent_streams,
+      http2_initial_stream_window_size: args.http2_initial_stream_window_size,
+      http2_initial_connection_window_size: args
+        .http2_initial_connection_window_size,
     },
   )?;
 
@@ -858,6 +1120,19 @@ where
   Ok(rid)
 }
 
+/// Closes the idle connections of a client by replacing its pool. The
+/// connections in use are closed once their requests are done.
+#[op2(fast)]
+pub fn op_fetch_client_close_idle_connections(
+  state: &mut OpState,
+  #[smi] rid: ResourceId,
+) -> Result<(), AnyError> {
+  let resource = state.resource_table.get::<HttpClientResource>(rid)?;
+  let mut client = resource.client.borrow_mut();
+  *client = client.with_new_pool();
+  Ok(())
+}
+
 #[derive(Debug, Clone)]
 pub struct CreateHttpClientOptions {
   pub root_cert_store: Option<RootCertStore>,
@@ -867,8 +1142,34 @@ pub struct CreateHttpClientOptions {
   pub client_cert_chain_and_key: Option<TlsKey>,
   pub pool_max_idle_per_host: Option<usize>,
   pub pool_idle_timeout: Option<Option<u64>>,
+  /// The maximum number of connections kept open at once, idle or not.
+  pub max_connections: Option<usize>,
+  pub dns_resolver: dns::Resolver,
+  /// How long to wait, in milliseconds, for a connection to an address of a
+  /// host before also trying the next one.
+  pub happy_eyeballs_delay: Option<u64>,
+  /// The local address to connect from. Only the addresses of a host of its
+  /// family are connected to.
+  pub local_address: Option<IpAddr>,
+  /// The name of the network interface to connect through. Only supported on
+  /// Linux.
+  pub interface: Option<String>,
+  /// The number of servers to keep TLS sessions of, to resume them when
+  /// connecting again. 0 disables resumption.
+  pub tls_session_cache_size: Option<usize>,
+  /// Checks the certificates of servers against revocation lists.
+  pub revocation: Option<deno_tls::RevocationCheck>,
   pub http1: bool,
   pub http2: bool,
+  /// Use HTTP/2 for every connection without negotiating it: cleartext
+  /// connections start right away with HTTP/2 (h2c), and TLS connections
+  /// don't offer ALPN.
+  pub http2_prior_knowledge: bool,
+  /// The maximum number of requests in flight at once on the HTTP/2
+  /// connection to an origin. Requires HTTP/2 to be used for every connection.
+  pub http2_max_concurrent_streams: Option<usize>,
+  pub http2_initial_stream_window_size: Option<u32>,
+  pub http2_initial_connection_window_size: Option<u32>,
 }
 
 impl Default for CreateHttpClientOptions {
@@ -881,8 +1182,19 @@ impl Default for CreateHttpClientOptions {
       client_cert_chain_and_key: None,
       pool_max_idle_per_host: None,
       pool_idle_timeout: None,
+      max_connections: None,
+      dns_resolver: dns::Resolver::default(),
+      happy_eyeballs_delay: None,
+      local_address: None,
+      interface: None,
+      tls_session_cache_size: None,
+      revocation: None,
       http1: true,
       http2: true,
+      http2_prior_knowledge: false,
+      http2_max_concurrent_streams: None,
+      http2_initial_stream_window_size: None,
+      http2_initial_connection_window_size: None,
     }
   }
 }
@@ -899,24 +1211,40 @@ pub fn create_http_client(
     options.unsafely_ignore_certificate_errors,
     options.client_cert_chain_and_key.into(),
     deno_tls::SocketUse::Http,
+    options.revocation,
   )?;
 
+  // rustls caches the sessions of up to 256 servers by default. The config
+  // used for proxies is cloned from this one and shares the same cache.
+  match options.tls_session_cache_size {
+    Some(0) => tls_config.resumption = Resumption::disabled(),
+    Some(size) => tls_config.resumption = Resumption::in_memory_sessions(size),
+    None => {}
+  }
+
   // Proxy TLS should not send ALPN
   tls_config.alpn_protocols.clear();
   let proxy_tls_config = Arc::from(tls_config.clone());
 
+  // With prior knowledge, HTTP/2 is used without negotiating it
   let mut alpn_protocols = vec![];
-  if options.http2 {
+  if options.http2 && !options.http2_prior_knowledge {
     alpn_protocols.push("h2".into());
   }
-  if options.http1 {
+  if options.http1 && !options.http2_prior_knowledge {
     alpn_protocols.push("http/1.1".into());
   }
   tls_config.alpn_protocols = alpn_protocols;
   let tls_config = Arc::from(tls_config);
 
-  let mut http_connector = HttpConnector::new();
-  http_connector.enforce_http(false);
+  let tcp_connector = TcpConnector::new(
+    options.dns_resolver,
+    LocalBinding {
+      local_address: options.local_address,
+      interface: options.interface,
+    },
+    options.happy_eyeballs_delay,
+  );
 
   let user_agent = user_agent.parse::<HeaderValue>().map_err(|_| {
     type_error(format!(
@@ -936,16 +1264,20 @@ pub fn create_http_client(
     if let Some(basic_auth) = &proxy.basic_auth {
       intercept.set_auth(&basic_auth.username, &basic_auth.password);
     }
+    intercept.set_no_proxy(&proxy.no_proxy);
     proxies.prepend(intercept);
   }
   let proxies = Arc::new(proxies);
-  let connector = proxy::ProxyConnector {
-    http: http_connector,
-    proxies: proxies.clone(),
-    tls: tls_config,
-    tls_proxy: proxy_tls_config,
-    user_agent: Some(user_agent.clone()),
-  };
+  let connector = ConnectionLimit::new(
+    proxy::ProxyConnector {
+      http: tcp_connector,
+      proxies: proxies.clone(),
+      tls: tls_config,
+      tls_proxy: proxy_tls_config,
+      user_agent: Some(user_agent.clone()),
+    },
+    options.max_connections,
+  );
 
   if let Some(pool_max_idle_per_host) = options.pool_max_idle_per_host {
     builder.pool_max_idle_per_host(pool_max_idle_per_host);
@@ -967,14 +1299,44 @@ pub fn create_http_client(
       return Err(type_error("Cannot create Http Client: either `http1` or `http2` needs to be set to true"))
     }
   }
+  if options.http2_prior_knowledge {
+    if !options.http2 {
+      return Err(type_error("Cannot create Http Client: `http2PriorKnowledge` requires `http2` to be set to true"));
+    }
+    builder.http2_only(true);
+  }
 
-  let pooled_client = builder.build(connector);
-  let decompress = Decompression::new(pooled_client).gzip(true).br(true);
+  let http2_only = options.http2_prior_knowledge || !options.http1;
+  let stream_limit = match options.http2_max_concurrent_streams {
+    Some(0) => {
+      return Err(type_error(
+        "'http2MaxConcurrentStreams' must be greater than 0",
+      ))
+    }
+    // Unless every connection uses HTTP/2, the requests to an origin may be
+    // spread over several HTTP/1.1 connections.
+    Some(_) if !http2_only => {
+      return Err(type_error(
+        "'http2MaxConcurrentStreams' requires HTTP/2 to be used for every connection, with `http1: false` or `http2PriorKnowledge: true`",
+      ))
+    }
+    Some(max_streams) => Some(StreamLimit::new(max_streams)),
+    None => None,
+  };
+  if let Some(size) = options.http2_initial_stream_window_size {
+    builder.http2_initial_stream_window_size(size);
+  }
+  if let Some(size) = options.http2_initial_connection_window_size {
+    builder.http2_initial_connection_window_size(size);
+  }
 
+  let pool = PoolBuilder { builder, connector };
   Ok(Client {
-    inner: decompress,
+    inner: pool.build(),
+    pool,
     proxies,
     user_agent,
+    stream_limit,
   })
 }
 
@@ -989,12 +1351,39 @@ pub fn op_utf8_to_byte_string(
 #[derive(Clone, Debug)]
 pub struct Client {
   inner: Decompression<hyper_util::client::legacy::Client<Connector, ReqBody>>,
+  // Used to replace the pool of connections
+  pool: PoolBuilder,
   // Used to check whether to include a proxy-authorization header
   proxies: Arc<proxy::Proxies>,
   user_agent: HeaderValue,
+  // Caps the concurrent HTTP/2 streams to each origin
+  stream_limit: Option<StreamLimit>,
+}
+
+type Connector = ConnectionLimit<proxy::ProxyConnector<TcpConnector>>;
+
+#[derive(Clone)]
+struct PoolBuilder {
+  builder: hyper_util::client::legacy::Builder,
+  connector: Connector,
+}
+
+impl PoolBuilder {
+  fn build(
+    &self,
+  ) -> Decompression<hyper_util::client::legacy::Client<Connector, ReqBody>> {
+    let pooled_client = self.builder.build(self.connector.clone());
+    Decompression::new(pooled_client).gzip(true).br(true)
+  }
 }
 
-type Connector = proxy::ProxyConnector<HttpConnector>;
+impl std::fmt::Debug for PoolBuilder {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("PoolBuilder")
+      .field("connector", &self.connector)
+      .finish_non_exhaustive()
+  }
+}
 
 // clippy is wrong here
 #[allow(clippy::declare_interior_mutable_const)]
@@ -1053,6 +1442,16 @@ impl std::error::Error for ClientSendError {
 }
 
 impl Client {
+  /// Returns a client like this one, without its pooled connections. They're
+  /// closed once every client using them is dropped, as the connection limit
+  /// is shared.
+  pub fn with_new_pool(&self) -> Self {
+    Self {
+      inner: self.pool.build(),
+      ..self.clone()
+    }
+  }
+
   pub async fn send(
     self,
     mut req: http::Request<ReqBody>,
@@ -1070,12 +1469,26 @@ impl Client {
 
     let uri = req.uri().clone();
 
+    let permit = match &self.stream_limit {
+      Some(stream_limit) => Some(stream_limit.acquire(&uri).await),
+      None => None,
+    };
     let resp = self
       .inner
       .oneshot(req)
       .await
       .map_err(|e| ClientSendError { uri, source: e })?;
-    Ok(resp.map(|b| b.map_err(|e| anyhow!(e)).boxed()))
+    let resp = resp.map(|b| b.map_err(|e| anyhow!(e)).boxed());
+    Ok(match permit {
+      Some(permit) => resp.map(|body| {
+        StreamLimited {
+          body,
+          _permit: permit,
+        }
+        .boxed()
+      }),
+      None => resp,
+    })
   }
 }
 
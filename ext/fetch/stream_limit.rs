@@ -0,0 +1,139 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Caps the number of requests that a client has in flight to each origin.
+//! As a client whose connections all use HTTP/2 multiplexes the requests to an
+//! origin on a single connection, this caps its concurrent streams.
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+
+use bytes::Bytes;
+use deno_core::anyhow::Error;
+use http::Uri;
+use hyper::body::Body;
+use hyper::body::Frame;
+use hyper::body::SizeHint;
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::Semaphore;
+
+use crate::ResBody;
+
+#[derive(Debug, Clone)]
+pub(crate) struct StreamLimit {
+  max_streams: usize,
+  origins: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
+}
+
+impl StreamLimit {
+  pub(crate) fn new(max_streams: usize) -> Self {
+    Self {
+      max_streams,
+      origins: Default::default(),
+    }
+  }
+
+  /// Waits until a stream to the origin of the URI may be opened. The stream
+  /// counts towards the limit until the returned permit is dropped.
+  pub(crate) async fn acquire(&self, uri: &Uri) -> StreamPermit {
+    let origin = format!(
+      "{}://{}",
+      uri.scheme_str().unwrap_or_default(),
+      uri.authority().map(|a| a.as_str()).unwrap_or_default()
+    );
+    let permits = self
+      .origins
+      .lock()
+      .unwrap()
+      .entry(origin.clone())
+      .or_insert_with(|| Arc::new(Semaphore::new(self.max_streams)))
+      .clone();
+    // The semaphore is never closed
+    let permit = permits.acquire_owned().await.unwrap();
+    StreamPermit {
+      permit: Some(permit),
+      origin,
+      origins: self.origins.clone(),
+    }
+  }
+}
+
+/// A stream counting towards the limit of its origin. Once the last stream to
+/// an origin is dropped, and no other is waiting, its semaphore is removed, so
+/// that a long-lived client doesn't keep one for every origin it contacted.
+pub(crate) struct StreamPermit {
+  permit: Option<OwnedSemaphorePermit>,
+  origin: String,
+  origins: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
+}
+
+impl Drop for StreamPermit {
+  fn drop(&mut self) {
+    drop(self.permit.take());
+    let mut origins = self.origins.lock().unwrap();
+    // Permits and waiters each hold a reference to the semaphore, which they
+    // take while the map is locked, so only the map's is left once the origin
+    // is idle.
+    if origins
+      .get(&self.origin)
+      .is_some_and(|permits| Arc::strong_count(permits) == 1)
+    {
+      origins.remove(&self.origin);
+    }
+  }
+}
+
+/// A response body holding on to its stream's permit until it's dropped.
+pub(crate) struct StreamLimited {
+  pub body: ResBody,
+  pub _permit: StreamPermit,
+}
+
+impl Body for StreamLimited {
+  type Data = Bytes;
+  type Error = Error;
+
+  fn poll_frame(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+    Pin::new(&mut self.body).poll_frame(cx)
+  }
+
+  fn is_end_stream(&self) -> bool {
+    self.body.is_end_stream()
+  }
+
+  fn size_hint(&self) -> SizeHint {
+    self.body.size_hint()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn evicts_idle_origins() {
+    let limit = StreamLimit::new(1);
+    let a = Uri::from_static("https://a.example/foo");
+    let b = Uri::from_static("https://b.example/");
+    let first = limit.acquire(&a).await;
+    let second = limit.acquire(&b).await;
+    assert_eq!(limit.origins.lock().unwrap().len(), 2);
+    drop(second);
+    assert_eq!(limit.origins.lock().unwrap().len(), 1);
+
+    // A waiter keeps the origin until it got and dropped its permit
+    let waiting = limit.acquire(&a);
+    tokio::pin!(waiting);
+    assert!(deno_core::futures::poll!(&mut waiting).is_pending());
+    drop(first);
+    assert_eq!(limit.origins.lock().unwrap().len(), 1);
+    drop(waiting.await);
+    assert!(limit.origins.lock().unwrap().is_empty());
+  }
+}
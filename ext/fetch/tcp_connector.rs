@@ -0,0 +1,120 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! The connector opening the TCP connections of HTTP clients, which can
+//! connect from a local address or network interface.
+
+use std::future::Future;
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+
+use deno_net::happy_eyeballs;
+use deno_net::happy_eyeballs::LocalBinding;
+use http::uri::Scheme;
+use http::Uri;
+use hyper_util::client::legacy::connect::dns::Name;
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpStream;
+use tower_service::Service;
+
+use crate::dns;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Clone)]
+pub(crate) enum TcpConnector {
+  Http(HttpConnector<dns::Resolver>),
+  /// Connects from a local address, to the addresses of a host of its family
+  /// only, or through a network interface, which hyper's connector doesn't
+  /// support.
+  Bound {
+    resolver: dns::Resolver,
+    binding: LocalBinding,
+    delay: Duration,
+  },
+}
+
+impl TcpConnector {
+  pub(crate) fn new(
+    resolver: dns::Resolver,
+    binding: LocalBinding,
+    happy_eyeballs_delay: Option<u64>,
+  ) -> Self {
+    if binding.local_address.is_none() && binding.interface.is_none() {
+      let mut http_connector = HttpConnector::new_with_resolver(resolver);
+      http_connector.enforce_http(false);
+      if let Some(delay) = happy_eyeballs_delay {
+        http_connector
+          .set_happy_eyeballs_timeout(Some(Duration::from_millis(delay)));
+      }
+      return Self::Http(http_connector);
+    }
+    Self::Bound {
+      resolver,
+      binding,
+      // the same default as hyper's connector
+      delay: Duration::from_millis(happy_eyeballs_delay.unwrap_or(300)),
+    }
+  }
+}
+
+impl Service<Uri> for TcpConnector {
+  type Response = TokioIo<TcpStream>;
+  type Error = BoxError;
+  type Future = BoxFuture<Result<Self::Response, Self::Error>>;
+
+  fn poll_ready(
+    &mut self,
+    cx: &mut Context<'_>,
+  ) -> Poll<Result<(), Self::Error>> {
+    match self {
+      Self::Http(http_connector) => {
+        http_connector.poll_ready(cx).map_err(Into::into)
+      }
+      Self::Bound { .. } => Poll::Ready(Ok(())),
+    }
+  }
+
+  fn call(&mut self, dst: Uri) -> Self::Future {
+    let (mut resolver, binding, delay) = match self {
+      Self::Http(http_connector) => {
+        let connecting = http_connector.call(dst);
+        return Box::pin(async move { connecting.await.map_err(Into::into) });
+      }
+      Self::Bound {
+        resolver,
+        binding,
+        delay,
+      } => (resolver.clone(), binding.clone(), *delay),
+    };
+    Box::pin(async move {
+      let Some(host) = dst.host() else {
+        return Err("URI has no host".into());
+      };
+      // the brackets of IPv6 addresses are part of the host of URIs
+      let host = host.trim_start_matches('[').trim_end_matches(']');
+      let default_port = if dst.scheme() == Some(&Scheme::HTTPS) {
+        443
+      } else {
+        80
+      };
+      let port = dst.port_u16().unwrap_or(default_port);
+      let addrs = match host.parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => resolver
+          .call(Name::from_str(host)?)
+          .await?
+          .map(|addr| SocketAddr::new(addr.ip(), port))
+          .collect(),
+      };
+      let stream = happy_eyeballs::connect_from(addrs, delay, &binding).await?;
+      Ok(TokioIo::new(stream))
+    })
+  }
+}
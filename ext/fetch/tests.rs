@@ -1,6 +1,9 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use bytes::Bytes;
@@ -11,7 +14,10 @@ use tokio::io::AsyncReadExt;
 use tokio::io::AsyncWriteExt;
 
 use super::create_http_client;
+use super::dns;
+use super::Client;
 use super::CreateHttpClientOptions;
+use super::ResBody;
 
 static EXAMPLE_CRT: &[u8] = include_bytes!("../tls/testdata/example1_cert.der");
 static EXAMPLE_KEY: &[u8] =
@@ -52,31 +58,332 @@ async fn test_socks_proxy_h2() {
   run_test_client(prx_addr, src_addr, "socks5", http::Version::HTTP_2).await;
 }
 
+#[tokio::test]
+async fn test_no_proxy_connects_directly() {
+  let src_addr = create_https_server(false).await;
+  // Nothing listens at the proxy address, so requests only succeed if they
+  // bypass the proxy.
+  let prx_tcp = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let prx_addr = prx_tcp.local_addr().unwrap();
+  drop(prx_tcp);
+
+  let client = create_test_client(deno_tls::Proxy {
+    url: format!("http://{}", prx_addr),
+    basic_auth: None,
+    no_proxy: vec!["127.0.0.1".into()],
+  });
+  let resp = send_test_request(client, src_addr).await;
+  assert_eq!(resp.status(), http::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_max_connections() {
+  let (src_addr, connections) = create_counting_http_server().await;
+  let client = create_http_client(
+    "fetch/test",
+    CreateHttpClientOptions {
+      max_connections: Some(1),
+      ..Default::default()
+    },
+  )
+  .unwrap();
+
+  let requests = (0..3).map(|_| get_body(client.clone(), src_addr));
+  for body in deno_core::futures::future::join_all(requests).await {
+    assert_eq!(body, "hello from server");
+  }
+  assert_eq!(connections.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_http2_prior_knowledge_max_concurrent_streams() {
+  let (src_addr, max_streams) = create_h2c_server().await;
+  let client = create_http_client(
+    "fetch/test",
+    CreateHttpClientOptions {
+      http2_prior_knowledge: true,
+      http2_max_concurrent_streams: Some(2),
+      ..Default::default()
+    },
+  )
+  .unwrap();
+
+  let requests = (0..5).map(|_| get_body(client.clone(), src_addr));
+  for body in deno_core::futures::future::join_all(requests).await {
+    assert_eq!(body, "hello from server");
+  }
+  assert_eq!(max_streams.load(Ordering::SeqCst), 2);
+
+  // Without HTTP/2 being used for every connection, streams can't be capped
+  assert!(create_http_client(
+    "fetch/test",
+    CreateHttpClientOptions {
+      http2_max_concurrent_streams: Some(2),
+      ..Default::default()
+    },
+  )
+  .is_err());
+}
+
+#[tokio::test]
+async fn test_close_idle_connections() {
+  let (src_addr, connections) = create_counting_http_server().await;
+  let client =
+    create_http_client("fetch/test", CreateHttpClientOptions::default())
+      .unwrap();
+
+  get_body(client.clone(), src_addr).await;
+  get_body(client.clone(), src_addr).await;
+  assert_eq!(connections.load(Ordering::SeqCst), 1);
+
+  let new_client = client.with_new_pool();
+  drop(client);
+  get_body(new_client, src_addr).await;
+  assert_eq!(connections.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_dns_resolver_hosts() {
+  let (src_addr, connections) = create_counting_http_server().await;
+  let hosts = HashMap::from([(
+    "Deno.Test".to_string(),
+    vec!["127.0.0.1".parse().unwrap()],
+  )]);
+  let client = create_http_client(
+    "fetch/test",
+    CreateHttpClientOptions {
+      dns_resolver: dns::Resolver::system().with_hosts(hosts),
+      ..Default::default()
+    },
+  )
+  .unwrap();
+
+  let req = http::Request::builder()
+    .uri(format!("http://deno.test:{}/foo", src_addr.port()))
+    .body(
+      http_body_util::Empty::new()
+        .map_err(|err| match err {})
+        .boxed(),
+    )
+    .unwrap();
+  let resp = client.send(req).await.unwrap();
+  let body = resp.collect().await.unwrap().to_bytes();
+  assert_eq!(body, "hello from server");
+  assert_eq!(connections.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_tls_session_resumption() {
+  use deno_tls::rustls::HandshakeKind;
+
+  async fn handshakes(tls_session_cache_size: Option<usize>) -> Vec<bool> {
+    let (src_addr, resumed) = create_resumption_server().await;
+    let client = create_http_client(
+      "fetch/test",
+      CreateHttpClientOptions {
+        unsafely_ignore_certificate_errors: Some(vec![]),
+        // Every request opens a new connection
+        pool_max_idle_per_host: Some(0),
+        tls_session_cache_size,
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    for _ in 0..2 {
+      let req = http::Request::builder()
+        .uri(format!("https://localhost:{}/foo", src_addr.port()))
+        .body(
+          http_body_util::Empty::new()
+            .map_err(|err| match err {})
+            .boxed(),
+        )
+        .unwrap();
+      let resp = client.clone().send(req).await.unwrap();
+      resp.collect().await.unwrap();
+    }
+    let resumed = resumed.lock().unwrap();
+    resumed
+      .iter()
+      .map(|kind| *kind == Some(HandshakeKind::Resumed))
+      .collect()
+  }
+
+  assert_eq!(handshakes(None).await, [false, true]);
+  assert_eq!(handshakes(Some(0)).await, [false, false]);
+}
+
+/// Serves HTTP/1.1 over TLS, recording the kind of each handshake.
+async fn create_resumption_server() -> (
+  SocketAddr,
+  Arc<std::sync::Mutex<Vec<Option<deno_tls::rustls::HandshakeKind>>>>,
+) {
+  let mut tls_config = deno_tls::rustls::server::ServerConfig::builder()
+    .with_no_client_auth()
+    .with_single_cert(
+      vec![EXAMPLE_CRT.into()],
+      webpki::types::PrivateKeyDer::try_from(EXAMPLE_KEY).unwrap(),
+    )
+    .unwrap();
+  tls_config.alpn_protocols.push("http/1.1".into());
+  let tls_acceptor = tokio_rustls::TlsAcceptor::from(Arc::from(tls_config));
+  let src_tcp = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let src_addr = src_tcp.local_addr().unwrap();
+  let handshakes = Arc::new(std::sync::Mutex::new(vec![]));
+
+  let handshakes_ = handshakes.clone();
+  tokio::spawn(async move {
+    while let Ok((sock, _)) = src_tcp.accept().await {
+      let conn = tls_acceptor.accept(sock).await.unwrap();
+      handshakes_
+        .lock()
+        .unwrap()
+        .push(conn.get_ref().1.handshake_kind());
+      let fut = hyper::server::conn::http1::Builder::new().serve_connection(
+        hyper_util::rt::TokioIo::new(conn),
+        hyper::service::service_fn(|_req| async {
+          Ok::<_, std::convert::Infallible>(http::Response::new(
+            http_body_util::Full::<Bytes>::new("hello from server".into()),
+          ))
+        }),
+      );
+      tokio::spawn(fut);
+    }
+  });
+
+  (src_addr, handshakes)
+}
+
+async fn get_body(client: Client, src_addr: SocketAddr) -> Bytes {
+  let req = http::Request::builder()
+    .uri(format!("http://{}/foo", src_addr))
+    .body(
+      http_body_util::Empty::new()
+        .map_err(|err| match err {})
+        .boxed(),
+    )
+    .unwrap();
+  let resp = client.send(req).await.unwrap();
+  resp.collect().await.unwrap().to_bytes()
+}
+
+/// Serves HTTP/1.1 slowly enough for concurrent requests to overlap, and
+/// counts the connections it accepted.
+async fn create_counting_http_server() -> (SocketAddr, Arc<AtomicUsize>) {
+  let src_tcp = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let src_addr = src_tcp.local_addr().unwrap();
+  let connections = Arc::new(AtomicUsize::new(0));
+
+  let accepted = connections.clone();
+  tokio::spawn(async move {
+    while let Ok((sock, _)) = src_tcp.accept().await {
+      accepted.fetch_add(1, Ordering::SeqCst);
+      let fut = hyper::server::conn::http1::Builder::new().serve_connection(
+        hyper_util::rt::TokioIo::new(sock),
+        hyper::service::service_fn(|_req| async {
+          tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+          Ok::<_, std::convert::Infallible>(http::Response::new(
+            http_body_util::Full::<Bytes>::new("hello from server".into()),
+          ))
+        }),
+      );
+      tokio::spawn(fut);
+    }
+  });
+
+  (src_addr, connections)
+}
+
+/// Serves cleartext HTTP/2 slowly enough for concurrent requests to overlap,
+/// and records the most streams it served at once.
+async fn create_h2c_server() -> (SocketAddr, Arc<AtomicUsize>) {
+  let src_tcp = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let src_addr = src_tcp.local_addr().unwrap();
+  let active = Arc::new(AtomicUsize::new(0));
+  let max_streams = Arc::new(AtomicUsize::new(0));
+
+  let max = max_streams.clone();
+  tokio::spawn(async move {
+    while let Ok((sock, _)) = src_tcp.accept().await {
+      let active = active.clone();
+      let max = max.clone();
+      let fut = hyper::server::conn::http2::Builder::new(
+        hyper_util::rt::TokioExecutor::new(),
+      )
+      .serve_connection(
+        hyper_util::rt::TokioIo::new(sock),
+        hyper::service::service_fn(move |req| {
+          let active = active.clone();
+          let max = max.clone();
+          async move {
+            assert_eq!(req.version(), http::Version::HTTP_2);
+            let streams = active.fetch_add(1, Ordering::SeqCst) + 1;
+            max.fetch_max(streams, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            active.fetch_sub(1, Ordering::SeqCst);
+            Ok::<_, std::convert::Infallible>(http::Response::new(
+              http_body_util::Full::<Bytes>::new("hello from server".into()),
+            ))
+          }
+        }),
+      );
+      tokio::spawn(fut);
+    }
+  });
+
+  (src_addr, max_streams)
+}
+
 async fn run_test_client(
   prx_addr: SocketAddr,
   src_addr: SocketAddr,
   proto: &str,
   ver: http::Version,
 ) {
-  let client = create_http_client(
+  let client = create_test_client(deno_tls::Proxy {
+    url: format!("{}://{}", proto, prx_addr),
+    basic_auth: None,
+    no_proxy: vec![],
+  });
+  let resp = send_test_request(client, src_addr).await;
+  assert_eq!(resp.status(), http::StatusCode::OK);
+  assert_eq!(resp.version(), ver);
+  let hello = resp.collect().await.unwrap().to_bytes();
+  assert_eq!(hello, "hello from server");
+}
+
+fn create_test_client(proxy: deno_tls::Proxy) -> Client {
+  create_http_client(
     "fetch/test",
     CreateHttpClientOptions {
       root_cert_store: None,
       ca_certs: vec![],
-      proxy: Some(deno_tls::Proxy {
-        url: format!("{}://{}", proto, prx_addr),
-        basic_auth: None,
-      }),
+      proxy: Some(proxy),
       unsafely_ignore_certificate_errors: Some(vec![]),
       client_cert_chain_and_key: None,
       pool_max_idle_per_host: None,
       pool_idle_timeout: None,
+      max_connections: None,
+      dns_resolver: Default::default(),
+      happy_eyeballs_delay: None,
+      local_address: None,
+      interface: None,
+      tls_session_cache_size: None,
+      revocation: None,
       http1: true,
       http2: true,
+      http2_prior_knowledge: false,
+      http2_max_concurrent_streams: None,
+      http2_initial_stream_window_size: None,
+      http2_initial_connection_window_size: None,
     },
   )
-  .unwrap();
+  .unwrap()
+}
 
+async fn send_test_request(
+  client: Client,
+  src_addr: SocketAddr,
+) -> http::Response<ResBody> {
   let req = http::Request::builder()
     .uri(format!("https://{}/foo", src_addr))
     .body(
@@ -85,11 +392,7 @@ async fn run_test_client(
         .boxed(),
     )
     .unwrap();
-  let resp = client.send(req).await.unwrap();
-  assert_eq!(resp.status(), http::StatusCode::OK);
-  assert_eq!(resp.version(), ver);
-  let hello = resp.collect().await.unwrap().to_bytes();
-  assert_eq!(hello, "hello from server");
+  client.send(req).await.unwrap()
 }
 
 async fn create_https_server(allow_h2: bool) -> SocketAddr {
@@ -0,0 +1,135 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Request bodies that report how much of them was sent, and check that they
+//! are exactly as long as their declared `Content-Length`.
+
+use std::pin::Pin;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+
+use bytes::Bytes;
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use hyper::body::Body;
+use hyper::body::Frame;
+use hyper::body::SizeHint;
+use tokio::sync::watch;
+
+use crate::ReqBody;
+
+pub struct UploadBody {
+  inner: ReqBody,
+  sent: u64,
+  expected_len: Option<u64>,
+  progress: Option<watch::Sender<u64>>,
+}
+
+impl UploadBody {
+  pub fn new(
+    inner: ReqBody,
+    expected_len: Option<u64>,
+    progress: Option<watch::Sender<u64>>,
+  ) -> Self {
+    Self {
+      inner,
+      sent: 0,
+      expected_len,
+      progress,
+    }
+  }
+}
+
+impl Body for UploadBody {
+  type Data = Bytes;
+  type Error = AnyError;
+
+  fn poll_frame(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+    let frame = ready!(Pin::new(&mut self.inner).poll_frame(cx));
+    match frame {
+      Some(Ok(frame)) => {
+        if let Some(data) = frame.data_ref() {
+          self.sent += data.len() as u64;
+          if let Some(expected_len) = self.expected_len {
+            if self.sent > expected_len {
+              return Poll::Ready(Some(Err(type_error(format!(
+                "Request body is longer than its Content-Length of {expected_len} bytes"
+              )))));
+            }
+          }
+          if let Some(progress) = &self.progress {
+            progress.send_replace(self.sent);
+          }
+        }
+        Poll::Ready(Some(Ok(frame)))
+      }
+      None => match self.expected_len {
+        Some(expected_len) if self.sent < expected_len => {
+          Poll::Ready(Some(Err(type_error(format!(
+            "Request body ended after {} of the {expected_len} bytes of its Content-Length",
+            self.sent
+          )))))
+        }
+        _ => Poll::Ready(None),
+      },
+      err => Poll::Ready(err),
+    }
+  }
+
+  fn is_end_stream(&self) -> bool {
+    self.inner.is_end_stream()
+  }
+
+  fn size_hint(&self) -> SizeHint {
+    match self.expected_len {
+      Some(expected_len) => {
+        SizeHint::with_exact(expected_len.saturating_sub(self.sent))
+      }
+      None => self.inner.size_hint(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use http_body_util::BodyExt;
+
+  fn chunks(chunks: &'static [&'static str]) -> ReqBody {
+    let frames = chunks
+      .iter()
+      .map(|chunk| Ok::<_, AnyError>(Frame::data(Bytes::from(*chunk))));
+    http_body_util::StreamBody::new(deno_core::futures::stream::iter(frames))
+      .boxed()
+  }
+
+  #[tokio::test]
+  async fn reports_progress() {
+    let (tx, rx) = watch::channel(0);
+    let body = UploadBody::new(chunks(&["foo", "bar"]), Some(6), Some(tx));
+    assert_eq!(body.size_hint().exact(), Some(6));
+    let body = body.collect().await.unwrap().to_bytes();
+    assert_eq!(body, "foobar");
+    assert_eq!(*rx.borrow(), 6);
+  }
+
+  #[tokio::test]
+  async fn checks_declared_length() {
+    let body = UploadBody::new(chunks(&["foo", "bar"]), Some(4), None);
+    let err = body.collect().await.unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "Request body is longer than its Content-Length of 4 bytes"
+    );
+
+    let body = UploadBody::new(chunks(&["foo", "bar"]), Some(8), None);
+    let err = body.collect().await.unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "Request body ended after 6 of the 8 bytes of its Content-Length"
+    );
+  }
+}
@@ -13,9 +13,18 @@ import {
   op_http_close_after_finish,
   op_http_get_request_headers,
   op_http_get_request_method_and_url,
+  op_http_get_request_trailers,
   op_http_read_request_body,
+  op_http_send_informational,
   op_http_serve,
+  op_http_serve_connection_events,
+  op_http_serve_h3,
+  op_http_serve_metrics,
+  op_http_serve_metrics_handle,
+  op_http_serve_next_connection_event,
   op_http_serve_on,
+  op_http_serve_wait_for_shutdown,
+  op_http_set_h3_certificate,
   op_http_set_promise_complete,
   op_http_set_response_body_bytes,
   op_http_set_response_body_resource,
@@ -23,18 +32,35 @@ import {
   op_http_set_response_header,
   op_http_set_response_headers,
   op_http_set_response_trailers,
+  op_http_set_serve_compression,
+  op_http_set_serve_h2c_upgrade,
+  op_http_set_serve_timeouts,
   op_http_try_wait,
+  op_http_upgrade_connection_next,
   op_http_upgrade_raw,
   op_http_upgrade_websocket_next,
   op_http_wait,
 } from "ext:core/ops";
 const {
+  ArrayIsArray,
+  ArrayPrototypeEvery,
   ArrayPrototypePush,
+  MapPrototypeDelete,
+  MapPrototypeGet,
+  MapPrototypeSet,
+  MathCeil,
+  NumberIsFinite,
+  NumberIsInteger,
+  NumberIsSafeInteger,
   ObjectHasOwn,
   ObjectPrototypeIsPrototypeOf,
   PromisePrototypeCatch,
   PromisePrototypeThen,
+  RangeError,
+  SafeArrayIterator,
+  SafeMap,
   StringPrototypeIncludes,
+  StringPrototypeToLowerCase,
   Symbol,
   TypeError,
   TypedArrayPrototypeGetSymbolToStringTag,
@@ -43,6 +69,11 @@ const {
 } = primordials;
 
 import { InnerBody } from "ext:deno_fetch/22_body.js";
+import {
+  headerListFromHeaders,
+  Headers,
+  headersFromHeaderList,
+} from "ext:deno_fetch/20_headers.js";
 import { Event } from "ext:deno_web/02_event.js";
 import {
   fromInnerResponse,
@@ -58,14 +89,11 @@ import {
 import { AbortController } from "ext:deno_web/03_abort_signal.js";
 import {
   _eventLoop,
-  _idleTimeoutDuration,
-  _idleTimeoutTimeout,
   _protocol,
   _readyState,
   _rid,
   _role,
   _server,
-  _serverHandleIdleTimeout,
   SERVER,
   WebSocket,
 } from "ext:deno_websocket/01_websocket.js";
@@ -76,8 +104,18 @@ import {
   ReadableStreamPrototype,
   resourceForReadableStream,
 } from "ext:deno_web/06_streams.js";
-import { listen, listenOptionApiName, TcpConn } from "ext:deno_net/01_net.js";
-import { hasTlsKeyPairOptions, listenTls } from "ext:deno_net/02_tls.js";
+import {
+  Conn,
+  listen,
+  listenOptionApiName,
+  TcpConn,
+} from "ext:deno_net/01_net.js";
+import {
+  hasTlsKeyPairOptions,
+  listenTls,
+  loadTlsKeyPair,
+  sniCertificateList,
+} from "ext:deno_net/02_tls.js";
 import { SymbolAsyncDispose } from "ext:deno_web/00_infra.js";
 
 const _upgraded = Symbol("_upgraded");
@@ -126,6 +164,52 @@ function upgradeHttpRaw(req, conn) {
   throw new TypeError("'upgradeHttpRaw' may only be used with Deno.serve");
 }
 
+function upgradeHttp(req, options = { __proto__: null }) {
+  const inner = toInnerRequest(req);
+  let status;
+  const headerList = [];
+  if (req.method === "CONNECT") {
+    // A tunnel is established by any 2xx response to a CONNECT request
+    status = 200;
+  } else {
+    const upgrade = req.headers.get("upgrade");
+    if (upgrade === null) {
+      throw new TypeError("Invalid Header: 'upgrade' header must be set");
+    }
+    const connection = req.headers.get("connection");
+    if (
+      connection === null ||
+      !StringPrototypeIncludes(StringPrototypeToLowerCase(connection), "upgrade")
+    ) {
+      throw new TypeError(
+        "Invalid Header: 'connection' header must contain 'Upgrade'",
+      );
+    }
+    status = 101;
+    // Going through `Headers` validates and normalizes the protocol
+    const upgradeHeaders = new Headers([
+      ["connection", "Upgrade"],
+      ["upgrade", options.protocol ?? upgrade],
+    ]);
+    ArrayPrototypePush(
+      headerList,
+      ...new SafeArrayIterator(headerListFromHeaders(upgradeHeaders)),
+    );
+  }
+  if (options.headers !== undefined) {
+    ArrayPrototypePush(
+      headerList,
+      ...new SafeArrayIterator(
+        headerListFromHeaders(new Headers(options.headers)),
+      ),
+    );
+  }
+  if (inner._wantsUpgrade) {
+    return inner._wantsUpgrade("upgradeHttp", status, headerList);
+  }
+  throw new TypeError("'Deno.upgradeHttp' may only be used with Deno.serve");
+}
+
 function addTrailers(resp, headerList) {
   const inner = toInnerResponse(resp);
   op_http_set_response_trailers(inner.external, headerList);
@@ -198,6 +282,27 @@ class InnerRequest {
       return { response: UPGRADE_RESPONSE_SENTINEL, conn };
     }
 
+    // upgradeHttp is sync, but the connection is only handed over once the response is sent
+    if (upgradeType == "upgradeHttp") {
+      const { 0: status, 1: headerList } = originalArgs;
+      const external = this.#external;
+      const remoteAddr = this.remoteAddr;
+      const localAddr = this.#context.listener?.addr;
+
+      this.url();
+      this.headerList;
+      this.close();
+
+      this.#upgraded = () => {};
+
+      const conn = PromisePrototypeThen(
+        op_http_upgrade_connection_next(external, status, headerList),
+        (rid) => new Conn(rid, remoteAddr, localAddr),
+      );
+
+      return { response: UPGRADE_RESPONSE_SENTINEL, conn };
+    }
+
     // upgradeWebSocket is sync
     if (upgradeType == "upgradeWebSocket") {
       const response = originalArgs[0];
@@ -234,13 +339,6 @@ class InnerRequest {
           ws.dispatchEvent(event);
 
           ws[_eventLoop]();
-          if (ws[_idleTimeoutDuration]) {
-            ws.addEventListener(
-              "close",
-              () => clearTimeout(ws[_idleTimeoutTimeout]),
-            );
-          }
-          ws[_serverHandleIdleTimeout]();
         } catch (error) {
           const event = new ErrorEvent("error", { error });
           ws.dispatchEvent(event);
@@ -305,6 +403,46 @@ class InnerRequest {
     return this.#completed.promise;
   }
 
+  sendInformational(status, headers) {
+    if (this.#external === null) {
+      throw new TypeError(
+        "Cannot send an informational response: request closed",
+      );
+    }
+    // 100 Continue is sent by the server and 101 Switching Protocols by upgrades
+    if (!NumberIsInteger(status) || status < 102 || status > 199) {
+      throw new RangeError(
+        `The informational status provided (${status}) must be an integer in the range [102, 199]`,
+      );
+    }
+    return op_http_send_informational(
+      this.#external,
+      status,
+      headerListFromHeaders(new Headers(headers)),
+    );
+  }
+
+  get requestTrailers() {
+    if (this.#external === null) {
+      throw new TypeError("Request closed");
+    }
+    const trailers = op_http_get_request_trailers(this.#external);
+    if (trailers === null) {
+      return null;
+    }
+    return headersFromHeaderList(trailers, "immutable");
+  }
+
+  setResponseTrailers(trailers) {
+    if (this.#external === null) {
+      throw new TypeError("Cannot set the response trailers: request closed");
+    }
+    op_http_set_response_trailers(
+      this.#external,
+      headerListFromHeaders(new Headers(trailers)),
+    );
+  }
+
   get remoteAddr() {
     const transport = this.#context.listener?.addr.transport;
     if (transport === "unix" || transport === "unixpacket") {
@@ -380,6 +518,8 @@ class CallbackContext {
   /** @type {Promise<void> | undefined} */
   closing;
   listener;
+  /** Whether the server also serves HTTP/3 */
+  http3;
 
   constructor(signal, args, listener) {
     // The abort signal triggers a non-graceful shutdown
@@ -396,6 +536,7 @@ class CallbackContext {
     this.fallbackHost = args[2];
     this.closed = false;
     this.listener = listener;
+    this.http3 = false;
   }
 
   close() {
@@ -419,6 +560,15 @@ class ServeHandlerInfo {
   get completed() {
     return this.#inner.completed;
   }
+  sendInformational(status, headers = undefined) {
+    return this.#inner.sendInformational(status, headers);
+  }
+  get requestTrailers() {
+    return this.#inner.requestTrailers;
+  }
+  setResponseTrailers(trailers) {
+    this.#inner.setResponseTrailers(trailers);
+  }
 }
 
 function fastSyncResponseOrStream(
@@ -580,13 +730,30 @@ type RawServeOptions = {
   hostname?: string;
   signal?: AbortSignal;
   reusePort?: boolean;
+  http3?: boolean;
+  h2c?: boolean;
   key?: string;
   cert?: string;
+  headerReadTimeoutMs?: number;
+  requestBodyIdleTimeoutMs?: number;
+  responseWriteTimeoutMs?: number;
+  compression?: boolean | {
+    gzipLevel?: number;
+    brotliQuality?: number;
+    minSize?: number;
+    contentTypes?: string[];
+  };
   onError?: (error: unknown) => Response | Promise<Response>;
   onListen?: (params: { hostname: string; port: number }) => void;
+  onConnect?: (info: ServeConnectionInfo) => void;
+  onClose?: (info: ServeConnectionInfo) => void;
   handler?: RawHandler;
 };
 
+type ServeConnectionInfo = {
+  remoteAddr: Deno.NetAddr | Deno.UnixAddr;
+};
+
 const kLoadBalanced = Symbol("kLoadBalanced");
 
 function formatHostName(hostname: string): string {
@@ -604,6 +771,90 @@ function formatHostName(hostname: string): string {
   return StringPrototypeIncludes(hostname, ":") ? `[${hostname}]` : hostname;
 }
 
+const kServeTimeoutOptions = [
+  "headerReadTimeoutMs",
+  "requestBodyIdleTimeoutMs",
+  "responseWriteTimeoutMs",
+];
+
+/**
+ * Validates the timeout options of `Deno.serve`, returning `undefined` when
+ * none are set.
+ */
+function serveTimeouts(options) {
+  let timeouts;
+  for (let i = 0; i < kServeTimeoutOptions.length; ++i) {
+    const name = kServeTimeoutOptions[i];
+    const value = options[name];
+    if (value === undefined) {
+      continue;
+    }
+    if (typeof value !== "number" || !NumberIsFinite(value) || value <= 0) {
+      throw new TypeError(
+        `'${name}' must be a positive finite number, received ${value}`,
+      );
+    }
+    timeouts ??= { __proto__: null };
+    timeouts[name] = MathCeil(value);
+  }
+  return timeouts;
+}
+
+function validateCompressionInteger(name, value, max) {
+  if (
+    value !== undefined &&
+    (!NumberIsInteger(value) || value < 0 || value > max)
+  ) {
+    throw new TypeError(
+      `'compression.${name}' must be an integer between 0 and ${max}, received ${value}`,
+    );
+  }
+}
+
+/**
+ * Validates the `compression` option of `Deno.serve`, returning `undefined`
+ * when the default behavior is requested.
+ */
+function serveCompression(options) {
+  const compression = options.compression;
+  if (compression === undefined || compression === true) {
+    return undefined;
+  }
+  if (compression === false) {
+    return { __proto__: null, enabled: false };
+  }
+  if (typeof compression !== "object" || compression === null) {
+    throw new TypeError(
+      `'compression' must be a boolean or an object, received ${typeof compression}`,
+    );
+  }
+  const { gzipLevel, brotliQuality, minSize, contentTypes } = compression;
+  validateCompressionInteger("gzipLevel", gzipLevel, 9);
+  validateCompressionInteger("brotliQuality", brotliQuality, 11);
+  if (minSize !== undefined && (!NumberIsSafeInteger(minSize) || minSize < 0)) {
+    throw new TypeError(
+      `'compression.minSize' must be a non-negative integer, received ${minSize}`,
+    );
+  }
+  if (
+    contentTypes !== undefined &&
+    (!ArrayIsArray(contentTypes) ||
+      !ArrayPrototypeEvery(contentTypes, (ct) => typeof ct === "string"))
+  ) {
+    throw new TypeError(
+      "'compression.contentTypes' must be an array of strings",
+    );
+  }
+  return {
+    __proto__: null,
+    enabled: true,
+    gzipLevel,
+    brotliQuality,
+    minSize,
+    contentTypes,
+  };
+}
+
 function serve(arg1, arg2) {
   let options: RawServeOptions | undefined;
   let handler: RawHandler | undefined;
@@ -641,6 +892,25 @@ function serve(arg1, arg2) {
     console.error(error);
     return internalServerError();
   };
+  const timeouts = serveTimeouts(options);
+  const compression = serveCompression(options);
+  const h2c = options.h2c ?? false;
+  if (h2c && wantsHttps) {
+    throw new TypeError(
+      "Unsupported 'h2c' option provided: HTTPS negotiates HTTP/2 with ALPN",
+    );
+  }
+  const { onConnect, onClose } = options;
+  if (onConnect !== undefined && typeof onConnect !== "function") {
+    throw new TypeError(
+      `'onConnect' must be a function, received ${typeof onConnect}`,
+    );
+  }
+  if (onClose !== undefined && typeof onClose !== "function") {
+    throw new TypeError(
+      `'onClose' must be a function, received ${typeof onClose}`,
+    );
+  }
 
   if (wantsUnix) {
     const listener = listen({
@@ -656,7 +926,7 @@ function serve(arg1, arg2) {
         // deno-lint-ignore no-console
         console.error(`Listening on ${path}`);
       }
-    });
+    }, { __proto__: null, timeouts, compression, h2c, onConnect, onClose });
   }
 
   const listenOpts = {
@@ -676,6 +946,11 @@ function serve(arg1, arg2) {
       "Unsupported 'alpnProtocols' option provided. 'h2' and 'http/1.1' are automatically supported.",
     );
   }
+  if (options.http3 && !wantsHttps) {
+    throw new TypeError(
+      "Both 'cert' and 'key' must be provided to enable HTTP/3",
+    );
+  }
 
   let listener;
   if (wantsHttps) {
@@ -686,6 +961,7 @@ function serve(arg1, arg2) {
     }
     listenOpts.cert = options.cert;
     listenOpts.key = options.key;
+    listenOpts.sniCertificates = options.sniCertificates;
     listenOpts.alpnProtocols = ["h2", "http/1.1"];
     listener = listenTls(listenOpts);
     listenOpts.port = listener.addr.port;
@@ -707,18 +983,139 @@ function serve(arg1, arg2) {
     }
   };
 
-  return serveHttpOnListener(listener, signal, handler, onError, onListen);
+  const http3KeyPair = options.http3
+    ? loadTlsKeyPair("Deno.serve", options)
+    : undefined;
+  return serveHttpOnListener(
+    listener,
+    signal,
+    handler,
+    onError,
+    onListen,
+    {
+      __proto__: null,
+      http3KeyPair,
+      http3SniCertificates: sniCertificateList(options.sniCertificates),
+      timeouts,
+      compression,
+      h2c,
+      onConnect,
+      onClose,
+    },
+  );
 }
 
 /**
- * Serve HTTP/1.1 and/or HTTP/2 on an arbitrary listener.
+ * Calls the `onConnect` and `onClose` callbacks with the connections of the
+ * server, until it is closed. Both receive the same info object.
  */
-function serveHttpOnListener(listener, signal, handler, onError, onListen) {
+function serveConnectionEvents(context, onConnect, onClose) {
+  const rid = op_http_serve_connection_events(context.serverRid);
+  const connections = new SafeMap();
+  const callHook = (name, hook, info) => {
+    if (hook === undefined) {
+      return;
+    }
+    try {
+      hook(info);
+    } catch (error) {
+      // deno-lint-ignore no-console
+      console.error(`Exception in ${name} callback`, error);
+    }
+  };
+  (async () => {
+    try {
+      while (true) {
+        const promise = op_http_serve_next_connection_event(rid);
+        // The requests of the server keep it alive, not its connection events
+        core.unrefOpPromise(promise);
+        const event = await promise;
+        if (event === null) {
+          break;
+        }
+        if (event.open) {
+          const addr = context.listener?.addr;
+          const transport = addr?.transport;
+          const remoteAddr = transport === "unix" || transport === "unixpacket"
+            ? { transport, path: addr.path }
+            : { transport: "tcp", hostname: event.hostname, port: event.port };
+          const info = { remoteAddr };
+          MapPrototypeSet(connections, event.id, info);
+          callHook("onConnect", onConnect, info);
+        } else {
+          const info = MapPrototypeGet(connections, event.id);
+          MapPrototypeDelete(connections, event.id);
+          if (info !== undefined) {
+            callHook("onClose", onClose, info);
+          }
+        }
+      }
+    } catch (error) {
+      if (
+        !ObjectPrototypeIsPrototypeOf(BadResourcePrototype, error) &&
+        !ObjectPrototypeIsPrototypeOf(InterruptedPrototype, error)
+      ) {
+        throw error;
+      }
+    } finally {
+      core.tryClose(rid);
+    }
+  })();
+}
+
+/**
+ * Serve HTTP/1.1 and/or HTTP/2 on an arbitrary listener, and HTTP/3 on the
+ * same UDP port when a key pair for it is provided in `serveOptions`, along
+ * with the timeouts of its connections, how its responses are compressed,
+ * whether they may upgrade to h2c and the callbacks for its connections.
+ */
+function serveHttpOnListener(
+  listener,
+  signal,
+  handler,
+  onError,
+  onListen,
+  serveOptions = { __proto__: null },
+) {
   const context = new CallbackContext(
     signal,
     op_http_serve(listener[internalRidSymbol]),
     listener,
   );
+  // Connections are only accepted once the event loop runs, so the timeouts
+  // apply to all of them.
+  if (serveOptions.timeouts !== undefined) {
+    op_http_set_serve_timeouts(context.serverRid, serveOptions.timeouts);
+  }
+  if (serveOptions.compression !== undefined) {
+    op_http_set_serve_compression(context.serverRid, serveOptions.compression);
+  }
+  if (serveOptions.h2c) {
+    op_http_set_serve_h2c_upgrade(context.serverRid);
+  }
+  if (
+    serveOptions.onConnect !== undefined || serveOptions.onClose !== undefined
+  ) {
+    serveConnectionEvents(
+      context,
+      serveOptions.onConnect,
+      serveOptions.onClose,
+    );
+  }
+  if (serveOptions.http3KeyPair !== undefined) {
+    try {
+      op_http_serve_h3(
+        context.serverRid,
+        listener.addr,
+        serveOptions.http3KeyPair,
+        serveOptions.http3SniCertificates,
+      );
+      context.http3 = true;
+    } catch (error) {
+      context.close();
+      throw error;
+    }
+  }
   const callback = mapToCallback(context, handler, onError);
 
   onListen(context.scheme);
@@ -745,6 +1142,7 @@ function serveHttpOnConnection(connection, signal, handler, onError, onListen) {
 function serveHttpOn(context, addr, callback) {
   let ref = true;
   let currentPromise = null;
+  const metricsHandle = op_http_serve_metrics_handle(context.serverRid);
 
   const promiseErrorHandler = (error) => {
     // Abnormal exit
@@ -813,11 +1211,44 @@ function serveHttpOn(context, addr, callback) {
   return {
     addr,
     finished,
-    async shutdown() {
+    metrics() {
+      return op_http_serve_metrics(metricsHandle);
+    },
+    setCertificate(options) {
+      const listener = context.listener;
+      if (typeof listener?.setCertificate !== "function") {
+        throw new TypeError(
+          "Cannot set the certificate of a server that doesn't serve HTTPS",
+        );
+      }
+      listener.setCertificate(options);
+      if (context.http3) {
+        op_http_set_h3_certificate(
+          context.serverRid,
+          loadTlsKeyPair("Deno.HttpServer.setCertificate", options),
+          sniCertificateList(options.sniCertificates),
+        );
+      }
+    },
+    async shutdown(options = { __proto__: null }) {
+      const gracePeriodMs = options?.gracePeriodMs;
+      if (
+        gracePeriodMs !== undefined &&
+        (typeof gracePeriodMs !== "number" || !NumberIsFinite(gracePeriodMs) ||
+          gracePeriodMs < 0)
+      ) {
+        throw new TypeError(
+          `'gracePeriodMs' must be a non-negative finite number, received ${gracePeriodMs}`,
+        );
+      }
       try {
         if (!context.closing && !context.closed) {
           // Shut this HTTP server down gracefully
-          context.closing = op_http_close(context.serverRid, true);
+          context.closing = op_http_close(
+            context.serverRid,
+            true,
+            gracePeriodMs === undefined ? undefined : MathCeil(gracePeriodMs),
+          );
         }
 
         await context.closing;
@@ -866,7 +1297,7 @@ function registerDeclarativeServer(exports) {
       );
     }
     return ({ servePort, serveHost, serveIsMain, serveWorkerCount }) => {
-      Deno.serve({
+      const server = Deno.serve({
         port: servePort,
         hostname: serveHost,
         [kLoadBalanced]: (serveIsMain && serveWorkerCount > 1) ||
@@ -892,6 +1323,10 @@ function registerDeclarativeServer(exports) {
           return exports.fetch(req, connInfo);
         },
       });
+      // `deno serve --watch` asks for a graceful shutdown before restarting
+      const shutdownPromise = op_http_serve_wait_for_shutdown();
+      core.unrefOpPromise(shutdownPromise);
+      PromisePrototypeThen(shutdownPromise, () => server.shutdown());
     };
   }
 }
@@ -902,5 +1337,6 @@ export {
   serve,
   serveHttpOnConnection,
   serveHttpOnListener,
+  upgradeHttp,
   upgradeHttpRaw,
 };
@@ -44,14 +44,11 @@ import {
 } from "ext:deno_fetch/23_request.js";
 import {
   _eventLoop,
-  _idleTimeoutDuration,
-  _idleTimeoutTimeout,
   _protocol,
   _readyState,
   _rid,
   _role,
   _server,
-  _serverHandleIdleTimeout,
   SERVER,
   WebSocket,
 } from "ext:deno_websocket/01_websocket.js";
@@ -361,6 +358,7 @@ function createRespondWith(
       if (ws) {
         const wsRid = await op_http_upgrade_websocket(
           readStreamRid,
+          resp.headers.get("sec-websocket-extensions"),
         );
         ws[_rid] = wsRid;
         ws[_protocol] = resp.headers.get("sec-websocket-protocol");
@@ -373,13 +371,6 @@ function createRespondWith(
         ws.dispatchEvent(event);
 
         ws[_eventLoop]();
-        if (ws[_idleTimeoutDuration]) {
-          ws.addEventListener(
-            "close",
-            () => clearTimeout(ws[_idleTimeoutTimeout]),
-          );
-        }
-        ws[_serverHandleIdleTimeout]();
       }
     } catch (error) {
       abortRequest(request);
@@ -0,0 +1,101 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+import { core, primordials } from "ext:core/mod.js";
+import {
+  op_http_sse_create,
+  op_http_sse_end,
+  op_http_sse_finished,
+  op_http_sse_send,
+} from "ext:core/ops";
+const {
+  MathMin,
+  NumberIsSafeInteger,
+  PromisePrototypeThen,
+  RangeError,
+  TypeError,
+} = primordials;
+
+import { Headers } from "ext:deno_fetch/20_headers.js";
+import { Response } from "ext:deno_fetch/23_response.js";
+import { readableStreamForRid } from "ext:deno_web/06_streams.js";
+
+const DEFAULT_HEARTBEAT_INTERVAL = 15_000;
+const DEFAULT_HIGH_WATER_MARK = 64;
+// The options are passed to the op as small integers
+const MAX_OPTION_VALUE = 0x7FFFFFFF;
+
+class ServerSentEventWriter {
+  #rid;
+  #finished;
+
+  constructor(rid) {
+    this.#rid = rid;
+    const finished = op_http_sse_finished(rid);
+    // The writer of a response that is never sent must not keep the event loop
+    // alive.
+    core.unrefOpPromise(finished);
+    this.#finished = PromisePrototypeThen(finished, () => {
+      core.tryClose(rid);
+    });
+  }
+
+  /** Resolves once all events were sent after `close`, or the client
+   * disconnected. */
+  get finished() {
+    return this.#finished;
+  }
+
+  send(event) {
+    if (typeof event !== "object" || event === null) {
+      throw new TypeError("The event must be an object");
+    }
+    return op_http_sse_send(this.#rid, {
+      data: event.data,
+      event: event.event,
+      id: event.id,
+      retry: event.retry,
+      comment: event.comment,
+    });
+  }
+
+  close() {
+    op_http_sse_end(this.#rid);
+  }
+}
+
+function validateOption(name, value, defaultValue) {
+  if (value === undefined) {
+    return defaultValue;
+  }
+  if (!NumberIsSafeInteger(value) || value < 0) {
+    throw new RangeError(`Invalid ${name}: ${value}`);
+  }
+  return MathMin(value, MAX_OPTION_VALUE);
+}
+
+function createServerSentEventStream(options = { __proto__: null }) {
+  const heartbeatInterval = validateOption(
+    "heartbeatInterval",
+    options.heartbeatInterval,
+    DEFAULT_HEARTBEAT_INTERVAL,
+  );
+  const highWaterMark = validateOption(
+    "highWaterMark",
+    options.highWaterMark,
+    DEFAULT_HIGH_WATER_MARK,
+  );
+
+  const headers = new Headers(options.headers);
+  headers.set("content-type", "text/event-stream");
+  // `no-transform` keeps the stream from being buffered by response
+  // compression.
+  headers.set("cache-control", "no-cache, no-transform");
+
+  const rid = op_http_sse_create(heartbeatInterval, highWaterMark);
+  const response = new Response(readableStreamForRid(rid, false), {
+    status: options.status ?? 200,
+    headers,
+  });
+  return { response, writer: new ServerSentEventWriter(rid) };
+}
+
+export { createServerSentEventStream, ServerSentEventWriter };
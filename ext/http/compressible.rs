@@ -637,6 +637,22 @@ pub fn is_content_compressible(ct: impl AsRef<[u8]>) -> bool {
   known_compressible(prefix) || known_mime(prefix).unwrap_or_default()
 }
 
+/// Determine if the supplied content type matches one of the lowercase patterns, which are
+/// either a full media type (`text/plain`) or a type wildcard (`text/*`).
+pub fn content_type_matches(ct: impl AsRef<[u8]>, patterns: &[String]) -> bool {
+  let ct = ct.as_ref();
+  let prefix = ct.split(|c| *c == b';').next().unwrap().trim_ascii();
+  patterns
+    .iter()
+    .any(|pattern| match pattern.strip_suffix("/*") {
+      Some(ty) => prefix
+        .split(|c| *c == b'/')
+        .next()
+        .is_some_and(|t| t.eq_ignore_ascii_case(ty.as_bytes())),
+      None => prefix.eq_ignore_ascii_case(pattern.as_bytes()),
+    })
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -653,4 +669,13 @@ mod tests {
     assert!(is_content_compressible("text/plain;charset=UTF-8"));
     assert!(is_content_compressible("text/PlAIn; charset=utf-8"));
   }
+
+  #[test]
+  fn content_type_patterns() {
+    let patterns = vec!["application/json".to_string(), "text/*".to_string()];
+    assert!(content_type_matches("application/json", &patterns));
+    assert!(content_type_matches("Text/HTML; charset=utf-8", &patterns));
+    assert!(!content_type_matches("application/javascript", &patterns));
+    assert!(!content_type_matches("textual/plain", &patterns));
+  }
 }
@@ -0,0 +1,249 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! The parts of HTTP/2 framing (RFC 9113 Section 4) and HPACK (RFC 7541) that
+//! are needed to follow and insert frames next to the ones hyper reads and
+//! writes.
+
+pub(crate) const FRAME_HEADER_LEN: usize = 9;
+pub(crate) const FRAME_TYPE_HEADERS: u8 = 0x1;
+pub(crate) const FRAME_TYPE_SETTINGS: u8 = 0x4;
+pub(crate) const FRAME_TYPE_PUSH_PROMISE: u8 = 0x5;
+pub(crate) const FRAME_TYPE_CONTINUATION: u8 = 0x9;
+pub(crate) const FLAG_END_STREAM: u8 = 0x1;
+pub(crate) const FLAG_ACK: u8 = 0x1;
+pub(crate) const FLAG_END_HEADERS: u8 = 0x4;
+
+/// The default `SETTINGS_MAX_FRAME_SIZE`, which every peer has to accept.
+pub(crate) const MAX_FRAME_SIZE: usize = 16_384;
+
+pub(crate) struct FrameHeader {
+  pub len: usize,
+  pub kind: u8,
+  pub flags: u8,
+  pub stream_id: u32,
+}
+
+impl FrameHeader {
+  pub fn parse(header: &[u8; FRAME_HEADER_LEN]) -> Self {
+    Self {
+      len: u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize,
+      kind: header[3],
+      flags: header[4],
+      stream_id: u32::from_be_bytes([
+        header[5] & 0x7f,
+        header[6],
+        header[7],
+        header[8],
+      ]),
+    }
+  }
+
+  pub fn encode(&self, buf: &mut Vec<u8>) {
+    buf.extend_from_slice(&(self.len as u32).to_be_bytes()[1..]);
+    buf.push(self.kind);
+    buf.push(self.flags);
+    buf.extend_from_slice(&self.stream_id.to_be_bytes());
+  }
+}
+
+/// Appends an integer with an N-bit prefix (RFC 7541 Section 5.1).
+pub(crate) fn encode_int(
+  buf: &mut Vec<u8>,
+  first_byte: u8,
+  prefix_bits: u8,
+  value: u64,
+) {
+  let max_prefix = (1u64 << prefix_bits) - 1;
+  if value < max_prefix {
+    buf.push(first_byte | value as u8);
+    return;
+  }
+  buf.push(first_byte | max_prefix as u8);
+  let mut value = value - max_prefix;
+  while value >= 0x80 {
+    buf.push((value & 0x7f) as u8 | 0x80);
+    value >>= 7;
+  }
+  buf.push(value as u8);
+}
+
+/// Encodes a field line as a literal without indexing, so the dynamic table of
+/// the decoder is left as the peer expects it (RFC 7541 Section 6.2.2).
+pub(crate) fn encode_field(buf: &mut Vec<u8>, name: &[u8], value: &[u8]) {
+  buf.push(0x00);
+  encode_int(buf, 0x00, 7, name.len() as u64);
+  buf.extend_from_slice(name);
+  encode_int(buf, 0x00, 7, value.len() as u64);
+  buf.extend_from_slice(value);
+}
+
+/// Appends a header block for a stream as a `HEADERS` frame, followed by
+/// `CONTINUATION` frames for the part that doesn't fit in it.
+pub(crate) fn encode_header_frames(
+  buf: &mut Vec<u8>,
+  stream_id: u32,
+  block: &[u8],
+  end_stream: bool,
+) {
+  let mut chunks = block.chunks(MAX_FRAME_SIZE).peekable();
+  let mut kind = FRAME_TYPE_HEADERS;
+  let mut flags = if end_stream { FLAG_END_STREAM } else { 0 };
+  loop {
+    let chunk = chunks.next().unwrap_or_default();
+    let last = chunks.peek().is_none();
+    if last {
+      flags |= FLAG_END_HEADERS;
+    }
+    FrameHeader {
+      len: chunk.len(),
+      kind,
+      flags,
+      stream_id,
+    }
+    .encode(buf);
+    buf.extend_from_slice(chunk);
+    if last {
+      return;
+    }
+    kind = FRAME_TYPE_CONTINUATION;
+    flags = 0;
+  }
+}
+
+/// Follows the frames of one direction of an HTTP/2 connection, to find the
+/// points between them where another frame can be inserted.
+#[derive(Default)]
+pub(crate) struct FrameBoundaries {
+  header: [u8; FRAME_HEADER_LEN],
+  header_len: usize,
+  payload_left: usize,
+  /// Whether a header block continues in a `CONTINUATION` frame, which has to
+  /// immediately follow the previous frame.
+  in_header_block: bool,
+}
+
+impl FrameBoundaries {
+  /// Consumes the next bytes of the connection, calling `on_frame` with the
+  /// header of every frame that they start.
+  pub fn advance(
+    &mut self,
+    mut bytes: &[u8],
+    mut on_frame: impl FnMut(&FrameHeader),
+  ) {
+    while !bytes.is_empty() {
+      if self.payload_left > 0 {
+        let n = self.payload_left.min(bytes.len());
+        self.payload_left -= n;
+        bytes = &bytes[n..];
+        continue;
+      }
+      let n = (FRAME_HEADER_LEN - self.header_len).min(bytes.len());
+      self.header[self.header_len..self.header_len + n]
+        .copy_from_slice(&bytes[..n]);
+      self.header_len += n;
+      bytes = &bytes[n..];
+      if self.header_len < FRAME_HEADER_LEN {
+        continue;
+      }
+      self.header_len = 0;
+      let header = FrameHeader::parse(&self.header);
+      self.payload_left = header.len;
+      if matches!(
+        header.kind,
+        FRAME_TYPE_HEADERS | FRAME_TYPE_PUSH_PROMISE | FRAME_TYPE_CONTINUATION
+      ) {
+        self.in_header_block = header.flags & FLAG_END_HEADERS == 0;
+      }
+      on_frame(&header);
+    }
+  }
+
+  /// Whether the connection is between two frames and outside of a header
+  /// block, so another frame can be inserted.
+  pub fn at_boundary(&self) -> bool {
+    self.header_len == 0 && self.payload_left == 0 && !self.in_header_block
+  }
+
+  /// The number of bytes after which the connection might be at a boundary.
+  pub fn next_boundary(&self) -> usize {
+    if self.payload_left > 0 {
+      self.payload_left
+    } else {
+      FRAME_HEADER_LEN - self.header_len
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn encode_int_with_prefix() {
+    // RFC 7541 Appendix C.1
+    let mut buf = Vec::new();
+    encode_int(&mut buf, 0x00, 5, 10);
+    encode_int(&mut buf, 0x00, 5, 1337);
+    encode_int(&mut buf, 0x00, 8, 42);
+    assert_eq!(buf, [0x0a, 0x1f, 0x9a, 0x0a, 0x2a]);
+  }
+
+  #[test]
+  fn encode_header_block_in_continuations() {
+    let block = vec![0x42; MAX_FRAME_SIZE + 1];
+    let mut buf = Vec::new();
+    encode_header_frames(&mut buf, 3, &block, false);
+    let headers =
+      FrameHeader::parse(buf[..FRAME_HEADER_LEN].try_into().unwrap());
+    assert_eq!(headers.len, MAX_FRAME_SIZE);
+    assert_eq!(headers.kind, FRAME_TYPE_HEADERS);
+    assert_eq!(headers.flags, 0);
+    assert_eq!(headers.stream_id, 3);
+    let continuation_start = FRAME_HEADER_LEN + MAX_FRAME_SIZE;
+    let continuation = FrameHeader::parse(
+      buf[continuation_start..continuation_start + FRAME_HEADER_LEN]
+        .try_into()
+        .unwrap(),
+    );
+    assert_eq!(continuation.len, 1);
+    assert_eq!(continuation.kind, FRAME_TYPE_CONTINUATION);
+    assert_eq!(continuation.flags, FLAG_END_HEADERS);
+    assert_eq!(buf.len(), 2 * FRAME_HEADER_LEN + block.len());
+  }
+
+  #[test]
+  fn follow_frame_boundaries() {
+    let mut frames = Vec::new();
+    // SETTINGS, then a header block split in two
+    frames.extend_from_slice(&[0, 0, 0, FRAME_TYPE_SETTINGS, 0, 0, 0, 0, 0]);
+    encode_header_frames(&mut frames, 1, &[0x42; MAX_FRAME_SIZE + 1], true);
+    let mut boundaries = FrameBoundaries::default();
+    let mut kinds = Vec::new();
+    boundaries.advance(&frames[..4], |frame| kinds.push(frame.kind));
+    assert!(!boundaries.at_boundary());
+    assert_eq!(boundaries.next_boundary(), 5);
+    boundaries
+      .advance(&frames[4..FRAME_HEADER_LEN], |frame| kinds.push(frame.kind));
+    assert!(boundaries.at_boundary());
+    // Between the frames of the header block, which can't be interrupted
+    let continuation_start = 2 * FRAME_HEADER_LEN + MAX_FRAME_SIZE;
+    boundaries
+      .advance(&frames[FRAME_HEADER_LEN..continuation_start], |frame| {
+        kinds.push(frame.kind)
+      });
+    assert!(!boundaries.at_boundary());
+    assert_eq!(boundaries.next_boundary(), FRAME_HEADER_LEN);
+    boundaries.advance(&frames[continuation_start..], |frame| {
+      kinds.push(frame.kind)
+    });
+    assert!(boundaries.at_boundary());
+    assert_eq!(
+      kinds,
+      [
+        FRAME_TYPE_SETTINGS,
+        FRAME_TYPE_HEADERS,
+        FRAME_TYPE_CONTINUATION
+      ]
+    );
+  }
+}
@@ -0,0 +1,516 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Cleartext HTTP/2 (h2c) through the HTTP/1.1 `Upgrade` mechanism
+//! (RFC 7540 Section 3.2). Connections which start with the HTTP/2 connection
+//! preface instead ("prior knowledge") are detected before this point.
+//!
+//! The request which asks for the upgrade is answered on HTTP/2 stream 1, but
+//! hyper can only serve HTTP/2 requests that arrive as frames. Once the client
+//! has sent its connection preface, the request is therefore re-encoded as a
+//! `HEADERS` frame for stream 1 and spliced into the bytes read by the server.
+//!
+//! The settings of the `HTTP2-Settings` header are spliced in the same way, as
+//! a `SETTINGS` frame ahead of the one of the client. The upgrade acknowledges
+//! them, so the acknowledgement written by the server is dropped.
+
+use crate::h2_frame::encode_field;
+use crate::h2_frame::encode_header_frames;
+use crate::h2_frame::FrameBoundaries;
+use crate::h2_frame::FrameHeader;
+use crate::h2_frame::FLAG_ACK;
+use crate::h2_frame::FRAME_HEADER_LEN;
+use crate::h2_frame::FRAME_TYPE_SETTINGS;
+use crate::h2_frame::MAX_FRAME_SIZE;
+use crate::http_next::HTTP2_PREFIX;
+use base64::prelude::BASE64_URL_SAFE_NO_PAD;
+use base64::Engine;
+use bytes::Buf;
+use bytes::Bytes;
+use deno_core::futures::future;
+use deno_core::futures::TryFutureExt;
+use http::header::CONNECTION;
+use http::header::HOST;
+use http::header::TE;
+use http::header::UPGRADE;
+use http::HeaderMap;
+use http::HeaderValue;
+use http::Method;
+use http::Request;
+use http::Response;
+use http::StatusCode;
+use http::Version;
+use http_body_util::Either;
+use http_body_util::Empty;
+use hyper::body::Body;
+use hyper::body::Incoming;
+use hyper::service::Service;
+use hyper::upgrade::OnUpgrade;
+use std::cell::RefCell;
+use std::io;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
+use tokio::io::ReadBuf;
+
+const HTTP2_SETTINGS: &str = "http2-settings";
+
+/// Headers which are only meaningful to an HTTP/1.1 connection, and which
+/// aren't allowed in HTTP/2.
+const CONNECTION_SPECIFIC_HEADERS: &[&str] = &[
+  "connection",
+  "host",
+  HTTP2_SETTINGS,
+  "keep-alive",
+  "proxy-connection",
+  "transfer-encoding",
+  "upgrade",
+];
+
+/// An h2c upgrade accepted by the [`H2cUpgradeService`].
+pub(crate) struct H2cUpgrade {
+  pub on_upgrade: OnUpgrade,
+  /// The settings of the `HTTP2-Settings` header, encoded as a `SETTINGS`
+  /// frame.
+  pub settings_frame: Bytes,
+  /// The upgrade request, encoded as a `HEADERS` frame for stream 1.
+  pub headers_frame: Bytes,
+}
+
+/// Wraps the service of an HTTP/1.1 connection, answering a request that asks
+/// to upgrade to h2c with `101 Switching Protocols` instead of passing it on.
+/// The accepted upgrade is stored so the connection can continue as HTTP/2.
+pub(crate) struct H2cUpgradeService<S> {
+  inner: S,
+  upgrade: Rc<RefCell<Option<H2cUpgrade>>>,
+}
+
+impl<S> H2cUpgradeService<S> {
+  pub fn new(inner: S) -> (Self, Rc<RefCell<Option<H2cUpgrade>>>) {
+    let upgrade = Rc::new(RefCell::new(None));
+    (
+      Self {
+        inner,
+        upgrade: upgrade.clone(),
+      },
+      upgrade,
+    )
+  }
+}
+
+impl<S, B> Service<Request<Incoming>> for H2cUpgradeService<S>
+where
+  S: Service<Request<Incoming>, Response = Response<B>>,
+  B: Body,
+{
+  type Response = Response<Either<B, Empty<B::Data>>>;
+  type Error = S::Error;
+  #[allow(clippy::type_complexity)]
+  type Future = future::Either<
+    future::Ready<Result<Self::Response, S::Error>>,
+    future::MapOk<S::Future, fn(Response<B>) -> Self::Response>,
+  >;
+
+  fn call(&self, mut req: Request<Incoming>) -> Self::Future {
+    // Only the first upgrade is taken, as the connection switches protocols
+    // right after it.
+    if self.upgrade.borrow().is_none() {
+      if let Some((settings_frame, headers_frame)) = upgrade_frames(&req) {
+        *self.upgrade.borrow_mut() = Some(H2cUpgrade {
+          on_upgrade: hyper::upgrade::on(&mut req),
+          settings_frame,
+          headers_frame,
+        });
+        let mut response = Response::new(Either::Right(Empty::new()));
+        *response.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+        let headers = response.headers_mut();
+        headers.insert(CONNECTION, HeaderValue::from_static("Upgrade"));
+        headers.insert(UPGRADE, HeaderValue::from_static("h2c"));
+        return future::Either::Left(future::ready(Ok(response)));
+      }
+    }
+    future::Either::Right(
+      self
+        .inner
+        .call(req)
+        .map_ok((|res: Response<B>| res.map(Either::Left)) as fn(_) -> _),
+    )
+  }
+}
+
+/// Returns whether a comma-separated header contains the given token.
+fn has_token(headers: &HeaderMap, name: &str, token: &str) -> bool {
+  headers.get_all(name).iter().any(|value| {
+    value.to_str().is_ok_and(|value| {
+      value
+        .split(',')
+        .any(|item| item.trim().eq_ignore_ascii_case(token))
+    })
+  })
+}
+
+/// If the request asks to upgrade to h2c, returns its `HTTP2-Settings` header
+/// encoded as a `SETTINGS` frame, and the request encoded as a `HEADERS` frame
+/// for stream 1.
+///
+/// An upgrade that can't be performed is ignored, so the request is answered
+/// over HTTP/1.1 instead, as RFC 7540 allows. This is the case for a request
+/// with a body, which would have to be fully read before switching protocols,
+/// and for invalid settings.
+fn upgrade_frames(req: &Request<Incoming>) -> Option<(Bytes, Bytes)> {
+  let headers = req.headers();
+  if req.version() != Version::HTTP_11
+    || req.method() == Method::CONNECT
+    || !req.body().is_end_stream()
+    || !has_token(headers, "upgrade", "h2c")
+    || !has_token(headers, "connection", "upgrade")
+    || !has_token(headers, "connection", HTTP2_SETTINGS)
+    || headers.get_all(HTTP2_SETTINGS).iter().count() != 1
+  {
+    return None;
+  }
+  let settings_frame = decode_settings_frame(headers.get(HTTP2_SETTINGS)?)?;
+  Some((settings_frame, encode_headers_frame(req)))
+}
+
+/// Decodes the `HTTP2-Settings` header, which holds the payload of a
+/// `SETTINGS` frame in base64url (RFC 7540 Section 3.2.1), into the frame.
+fn decode_settings_frame(value: &HeaderValue) -> Option<Bytes> {
+  let value = value.as_bytes().trim_ascii();
+  let value = value.strip_suffix(b"==").unwrap_or(value);
+  let value = value.strip_suffix(b"=").unwrap_or(value);
+  let payload = BASE64_URL_SAFE_NO_PAD.decode(value).ok()?;
+  // Each setting is a 16-bit identifier and a 32-bit value
+  if payload.len() % 6 != 0 || payload.len() > MAX_FRAME_SIZE {
+    return None;
+  }
+  let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + payload.len());
+  FrameHeader {
+    len: payload.len(),
+    kind: FRAME_TYPE_SETTINGS,
+    flags: 0,
+    stream_id: 0,
+  }
+  .encode(&mut frame);
+  frame.extend_from_slice(&payload);
+  Some(frame.into())
+}
+
+fn encode_headers_frame<B>(req: &Request<B>) -> Bytes {
+  let headers = req.headers();
+  let mut block = Vec::new();
+  encode_field(&mut block, b":method", req.method().as_str().as_bytes());
+  encode_field(&mut block, b":scheme", b"http");
+  let authority = match req.uri().authority() {
+    Some(authority) => Some(authority.as_str().as_bytes()),
+    None => headers.get(HOST).map(HeaderValue::as_bytes),
+  };
+  if let Some(authority) = authority {
+    encode_field(&mut block, b":authority", authority);
+  }
+  let path = req.uri().path_and_query().map_or("/", |path| path.as_str());
+  encode_field(&mut block, b":path", path.as_bytes());
+
+  // Any header named by the connection header is also specific to it
+  let is_connection_specific = |name: &str| {
+    CONNECTION_SPECIFIC_HEADERS.contains(&name)
+      || has_token(headers, "connection", name)
+  };
+  for (name, value) in headers {
+    if is_connection_specific(name.as_str()) {
+      continue;
+    }
+    if name == TE && value != "trailers" {
+      continue;
+    }
+    encode_field(&mut block, name.as_str().as_bytes(), value.as_bytes());
+  }
+
+  let mut frames = Vec::with_capacity(FRAME_HEADER_LEN + block.len());
+  encode_header_frames(&mut frames, 1, &block, true);
+  frames.into()
+}
+
+/// Reads the connection preface of the client after an upgrade, returning a
+/// stream which replays it with the settings of the upgrade inserted ahead of
+/// the initial `SETTINGS` frame, and the upgrade request after it, where
+/// HTTP/2 allows a `HEADERS` frame to appear.
+pub(crate) async fn read_client_preface<S: AsyncRead + Unpin>(
+  mut io: S,
+  settings_frame: &[u8],
+  headers_frame: &[u8],
+) -> io::Result<H2cStream<S>> {
+  let mut preface = vec![0; HTTP2_PREFIX.len() + FRAME_HEADER_LEN];
+  io.read_exact(&mut preface).await?;
+  let frame_header =
+    FrameHeader::parse(preface[HTTP2_PREFIX.len()..].try_into().unwrap());
+  if !preface.starts_with(HTTP2_PREFIX)
+    || frame_header.kind != FRAME_TYPE_SETTINGS
+    || frame_header.len > MAX_FRAME_SIZE
+  {
+    return Err(io::Error::new(
+      io::ErrorKind::InvalidData,
+      "Invalid HTTP/2 connection preface",
+    ));
+  }
+  let mut settings = vec![0; FRAME_HEADER_LEN + frame_header.len];
+  settings[..FRAME_HEADER_LEN].copy_from_slice(&preface[HTTP2_PREFIX.len()..]);
+  io.read_exact(&mut settings[FRAME_HEADER_LEN..]).await?;
+  preface.truncate(HTTP2_PREFIX.len());
+  preface.extend_from_slice(settings_frame);
+  preface.extend_from_slice(&settings);
+  preface.extend_from_slice(headers_frame);
+  Ok(H2cStream {
+    prefix: preface.into(),
+    io,
+    skip_settings_ack: true,
+    write_frames: FrameBoundaries::default(),
+  })
+}
+
+/// A stream which reads the given prefix before the bytes of the inner stream,
+/// and drops the acknowledgement of the settings of the upgrade.
+pub(crate) struct H2cStream<S> {
+  prefix: Bytes,
+  io: S,
+  /// Whether the acknowledgement of the first `SETTINGS` frame, which holds
+  /// the settings of the upgrade, is yet to be written.
+  skip_settings_ack: bool,
+  /// The frames written by the server, until the acknowledgement is dropped.
+  write_frames: FrameBoundaries,
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for H2cStream<S> {
+  fn poll_read(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &mut ReadBuf<'_>,
+  ) -> Poll<io::Result<()>> {
+    if self.prefix.is_empty() {
+      return Pin::new(&mut self.io).poll_read(cx, buf);
+    }
+    let n = buf.remaining().min(self.prefix.len());
+    buf.put_slice(&self.prefix[..n]);
+    self.prefix.advance(n);
+    Poll::Ready(Ok(()))
+  }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for H2cStream<S> {
+  fn poll_write(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &[u8],
+  ) -> Poll<io::Result<usize>> {
+    if !self.skip_settings_ack {
+      return Pin::new(&mut self.io).poll_write(cx, buf);
+    }
+    // hyper writes whole frames to its buffer, so a frame header is never
+    // split at a frame boundary
+    if self.write_frames.at_boundary() && buf.len() >= FRAME_HEADER_LEN {
+      let frame =
+        FrameHeader::parse(buf[..FRAME_HEADER_LEN].try_into().unwrap());
+      if frame.kind == FRAME_TYPE_SETTINGS && frame.flags & FLAG_ACK != 0 {
+        self.skip_settings_ack = false;
+        return Poll::Ready(Ok(FRAME_HEADER_LEN));
+      }
+    }
+    let len = buf.len().min(self.write_frames.next_boundary());
+    let n = ready!(Pin::new(&mut self.io).poll_write(cx, &buf[..len]))?;
+    self.write_frames.advance(&buf[..n], |_| {});
+    Poll::Ready(Ok(n))
+  }
+
+  fn poll_flush(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<io::Result<()>> {
+    Pin::new(&mut self.io).poll_flush(cx)
+  }
+
+  fn poll_shutdown(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<io::Result<()>> {
+    Pin::new(&mut self.io).poll_shutdown(cx)
+  }
+
+  fn is_write_vectored(&self) -> bool {
+    self.io.is_write_vectored()
+  }
+
+  fn poll_write_vectored(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    bufs: &[io::IoSlice<'_>],
+  ) -> Poll<io::Result<usize>> {
+    if !self.skip_settings_ack {
+      return Pin::new(&mut self.io).poll_write_vectored(cx, bufs);
+    }
+    let buf = bufs
+      .iter()
+      .find(|buf| !buf.is_empty())
+      .map_or(&[][..], |buf| &**buf);
+    self.poll_write(cx, buf)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use http::header::CONTENT_TYPE;
+  use http_body_util::Full;
+  use hyper::server::conn::http1;
+  use hyper::server::conn::http2;
+  use hyper::service::service_fn;
+  use hyper_util::rt::TokioExecutor;
+  use hyper_util::rt::TokioIo;
+  use std::convert::Infallible;
+  use tokio::io::AsyncWriteExt;
+
+  #[test]
+  fn test_encode_headers_frame() {
+    let req = Request::builder()
+      .uri("/path?query")
+      .header(HOST, "localhost")
+      .header(CONNECTION, "Upgrade, HTTP2-Settings, x-hop")
+      .header(UPGRADE, "h2c")
+      .header(HTTP2_SETTINGS, "")
+      .header("x-hop", "1")
+      .header(TE, "gzip")
+      .header(CONTENT_TYPE, "text/plain")
+      .body(())
+      .unwrap();
+    let frame = encode_headers_frame(&req);
+    let mut block = Vec::new();
+    encode_field(&mut block, b":method", b"GET");
+    encode_field(&mut block, b":scheme", b"http");
+    encode_field(&mut block, b":authority", b"localhost");
+    encode_field(&mut block, b":path", b"/path?query");
+    encode_field(&mut block, b"content-type", b"text/plain");
+    assert_eq!(
+      &frame[..FRAME_HEADER_LEN],
+      &[0, 0, block.len() as u8, 0x1, 0x5, 0, 0, 0, 1]
+    );
+    assert_eq!(&frame[FRAME_HEADER_LEN..], block);
+  }
+
+  #[test]
+  fn test_decode_settings_frame() {
+    let frame =
+      decode_settings_frame(&HeaderValue::from_static("AAMAAABkAAQAoAAA"))
+        .unwrap();
+    assert_eq!(
+      &frame[..],
+      &[
+        0, 0, 12, 0x4, 0, 0, 0, 0, 0, //
+        0, 3, 0, 0, 0, 100, //
+        0, 4, 0, 0xa0, 0, 0,
+      ]
+    );
+    // Surrounding whitespace is ignored, and an empty header holds no settings
+    assert_eq!(
+      decode_settings_frame(&HeaderValue::from_static(" AAMAAABk "))
+        .unwrap()
+        .len(),
+      FRAME_HEADER_LEN + 6
+    );
+    assert_eq!(
+      decode_settings_frame(&HeaderValue::from_static(""))
+        .unwrap()
+        .len(),
+      FRAME_HEADER_LEN
+    );
+    // A setting is 6 bytes long
+    assert!(decode_settings_frame(&HeaderValue::from_static("AAMA")).is_none());
+    assert!(
+      decode_settings_frame(&HeaderValue::from_static("not base64!")).is_none()
+    );
+  }
+
+  #[tokio::test]
+  async fn test_upgrade() {
+    let (mut client, server) = tokio::io::duplex(16 * 1024);
+    let svc = service_fn(|req: Request<Incoming>| async move {
+      Ok::<_, Infallible>(Response::new(Full::new(Bytes::from(format!(
+        "{:?} {}",
+        req.version(),
+        req.uri()
+      )))))
+    });
+    let server = async move {
+      let (h1_svc, upgrade) = H2cUpgradeService::new(svc);
+      http1::Builder::new()
+        .serve_connection(TokioIo::new(server), h1_svc)
+        .with_upgrades()
+        .await
+        .unwrap();
+      let upgrade = upgrade.take().unwrap();
+      let io = TokioIo::new(upgrade.on_upgrade.await.unwrap());
+      let io = read_client_preface(
+        io,
+        &upgrade.settings_frame,
+        &upgrade.headers_frame,
+      )
+      .await
+      .unwrap();
+      http2::Builder::new(TokioExecutor::new())
+        .serve_connection(TokioIo::new(io), svc)
+        .await
+        .unwrap();
+    };
+    let client = async move {
+      client
+        .write_all(
+          b"GET /path HTTP/1.1\r\nHost: localhost\r\n\
+          Connection: Upgrade, HTTP2-Settings\r\nUpgrade: h2c\r\n\
+          HTTP2-Settings: AAMAAABkAAQAoAAAAAIAAAAA\r\n\r\n",
+        )
+        .await
+        .unwrap();
+      let mut head = Vec::new();
+      while !head.ends_with(b"\r\n\r\n") {
+        head.push(client.read_u8().await.unwrap());
+      }
+      assert!(head.starts_with(b"HTTP/1.1 101 Switching Protocols\r\n"));
+
+      // The connection preface, with an empty SETTINGS frame
+      client.write_all(HTTP2_PREFIX).await.unwrap();
+      client
+        .write_all(&[0, 0, 0, 0x4, 0, 0, 0, 0, 0])
+        .await
+        .unwrap();
+
+      // Read frames until the response body arrives on stream 1. Only the
+      // client's SETTINGS frame is acknowledged, as the upgrade acknowledges
+      // the settings of HTTP2-Settings.
+      let mut settings_acks = 0;
+      loop {
+        let mut header = [0; FRAME_HEADER_LEN];
+        client.read_exact(&mut header).await.unwrap();
+        let len = u32::from_be_bytes([0, header[0], header[1], header[2]]);
+        let mut payload = vec![0; len as usize];
+        client.read_exact(&mut payload).await.unwrap();
+        let stream_id = u32::from_be_bytes(header[5..].try_into().unwrap());
+        if header[3] == FRAME_TYPE_SETTINGS && header[4] & FLAG_ACK != 0 {
+          settings_acks += 1;
+        }
+        // DATA
+        if header[3] == 0x0 {
+          assert_eq!(stream_id, 1);
+          assert_eq!(payload, b"HTTP/2.0 http://localhost/path");
+          assert_eq!(settings_acks, 1);
+          break;
+        }
+      }
+    };
+    tokio::select! {
+      _ = client => {}
+      _ = server => panic!("connection closed"),
+    }
+  }
+}
@@ -0,0 +1,622 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! HTTP/3 (RFC 9114) support for `Deno.serve`. A QUIC endpoint is bound to the
+//! UDP port of an existing HTTPS server and its requests are handed to the same
+//! JavaScript handler as the ones of the TCP listener, while responses of the
+//! latter advertise the endpoint with an `Alt-Svc` header.
+
+use crate::http_next::HttpJoinHandle;
+use crate::http_next::HttpLifetime;
+use crate::informational::InformationalQueue;
+use crate::informational::InformationalResponse;
+use crate::request_body::IncomingBody;
+use crate::request_properties::HttpConnectionProperties;
+use crate::service::handle_request;
+use crate::service::HttpRecord;
+use crate::service::HttpServeTimeouts;
+use crate::service::HttpServerState;
+use crate::service::Request;
+use crate::service::Response;
+use crate::service::SignallingRc;
+use bytes::Buf;
+use bytes::Bytes;
+use deno_core::anyhow::anyhow;
+use deno_core::error::AnyError;
+use deno_core::futures::future::poll_fn;
+use deno_core::futures::future::select;
+use deno_core::futures::future::Either;
+use deno_core::futures::stream::FuturesUnordered;
+use deno_core::futures::StreamExt;
+use deno_core::op2;
+use deno_core::unsync::spawn;
+use deno_core::CancelFuture;
+use deno_core::CancelTryFuture;
+use deno_core::OpState;
+use deno_core::RcRef;
+use deno_core::ResourceId;
+use deno_net::ops::IpAddr;
+use deno_net::raw::NetworkStreamType;
+use deno_tls::rustls;
+use deno_tls::SniCertResolver;
+use deno_tls::SniCertificate;
+use deno_tls::TlsKey;
+use deno_tls::TlsKeys;
+use deno_tls::TlsKeysHolder;
+use h3::error::Code;
+use h3::error::ErrorLevel;
+use h3::server::RequestStream;
+use http::header::CONTENT_LENGTH;
+use http::header::COOKIE;
+use http::HeaderValue;
+use http::Version;
+use hyper::body::Body;
+use hyper::body::Frame;
+use hyper::body::SizeHint;
+use quinn::crypto::rustls::QuicServerConfig;
+use scopeguard::guard;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+const ALPN_H3: &[u8] = b"h3";
+
+type RequestSendStream = RequestStream<h3_quinn::SendStream<Bytes>, Bytes>;
+type RequestRecvStream = RequestStream<h3_quinn::RecvStream, Bytes>;
+
+/// Headers which are only meaningful for HTTP/1.1 connections and must not be
+/// sent over HTTP/3.
+static CONNECTION_HEADERS: [&str; 5] = [
+  "connection",
+  "keep-alive",
+  "proxy-connection",
+  "transfer-encoding",
+  "upgrade",
+];
+
+/// Awaits `fut`, returning `None` if the timeout elapses first.
+async fn maybe_timeout<F: Future>(
+  timeout: Option<Duration>,
+  fut: F,
+) -> Option<F::Output> {
+  match timeout {
+    Some(timeout) => tokio::time::timeout(timeout, fut).await.ok(),
+    None => Some(fut.await),
+  }
+}
+
+async fn write_with_timeout(
+  write_timeout: Option<Duration>,
+  write: impl Future<Output = Result<(), h3::Error>>,
+) -> Result<(), AnyError> {
+  maybe_timeout(write_timeout, write)
+    .await
+    .ok_or_else(|| {
+      io::Error::new(io::ErrorKind::TimedOut, "Response write timed out")
+    })?
+    .map_err(AnyError::from)
+}
+
+/// The body of an HTTP/3 request, fed by the task reading its stream.
+pub struct Http3RequestBody {
+  rx: mpsc::Receiver<Result<Frame<Bytes>, AnyError>>,
+  size_hint: SizeHint,
+}
+
+impl Body for Http3RequestBody {
+  type Data = Bytes;
+  type Error = AnyError;
+
+  fn poll_frame(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+    self.rx.poll_recv(cx)
+  }
+
+  fn size_hint(&self) -> SizeHint {
+    self.size_hint.clone()
+  }
+}
+
+/// Forwards the data of a request stream to its body, followed by its
+/// trailers.
+async fn forward_request_body(
+  mut recv: RequestRecvStream,
+  tx: mpsc::Sender<Result<Frame<Bytes>, AnyError>>,
+) {
+  loop {
+    let item = match recv.recv_data().await {
+      Ok(Some(mut data)) => {
+        Ok(Frame::data(data.copy_to_bytes(data.remaining())))
+      }
+      Ok(None) => break,
+      Err(err) => Err(err.into()),
+    };
+    let is_err = item.is_err();
+    if tx.send(item).await.is_err() || is_err {
+      return;
+    }
+  }
+  let item = match recv.recv_trailers().await {
+    Ok(Some(trailers)) => Ok(Frame::trailers(trailers)),
+    Ok(None) => return,
+    Err(err) => Err(err.into()),
+  };
+  let _ = tx.send(item).await;
+}
+
+/// Builds the request passed to the handler from the head decoded by h3,
+/// which has already validated its pseudo-headers.
+fn build_request(
+  request: http::Request<()>,
+  body: IncomingBody,
+) -> Result<Request, AnyError> {
+  let (mut parts, ()) = request.into_parts();
+  parts.version = Version::HTTP_3;
+  // Cookies may be split into several field lines (RFC 9114 Section 4.2.1).
+  if parts.headers.get_all(COOKIE).iter().nth(1).is_some() {
+    let cookies = parts
+      .headers
+      .get_all(COOKIE)
+      .iter()
+      .map(HeaderValue::as_bytes)
+      .collect::<Vec<_>>()
+      .join(&b"; "[..]);
+    parts.headers.insert(
+      COOKIE,
+      HeaderValue::from_maybe_shared(Bytes::from(cookies))?,
+    );
+  }
+  Ok(Request::from_parts(parts, body))
+}
+
+async fn write_informational(
+  send: &mut RequestSendStream,
+  response: InformationalResponse,
+  write_timeout: Option<Duration>,
+) -> Result<(), AnyError> {
+  let mut head = http::Response::new(());
+  *head.status_mut() = response.status;
+  *head.headers_mut() = response.headers;
+  write_with_timeout(write_timeout, send.send_response(head)).await
+}
+
+async fn write_response(
+  send: &mut RequestSendStream,
+  response: Response,
+  write_timeout: Option<Duration>,
+) -> Result<(), AnyError> {
+  let (mut parts, mut body) = response.into_parts();
+  for name in CONNECTION_HEADERS {
+    parts.headers.remove(name);
+  }
+  let head = http::Response::from_parts(parts, ());
+  write_with_timeout(write_timeout, send.send_response(head)).await?;
+  while !body.is_end_stream() {
+    let Some(frame) = poll_fn(|cx| Pin::new(&mut body).poll_frame(cx)).await
+    else {
+      break;
+    };
+    match frame?.into_data() {
+      Ok(data) if data.is_empty() => {}
+      Ok(data) => {
+        write_with_timeout(write_timeout, send.send_data(data)).await?
+      }
+      Err(frame) => {
+        if let Ok(trailers) = frame.into_trailers() {
+          write_with_timeout(write_timeout, send.send_trailers(trailers))
+            .await?;
+        }
+      }
+    }
+  }
+  write_with_timeout(write_timeout, send.finish()).await
+}
+
+async fn serve_request(
+  request: http::Request<()>,
+  stream: RequestStream<h3_quinn::BidiStream<Bytes>, Bytes>,
+  request_info: HttpConnectionProperties,
+  server_state: SignallingRc<HttpServerState>,
+  tx: mpsc::Sender<Rc<HttpRecord>>,
+  timeouts: HttpServeTimeouts,
+) -> Result<(), AnyError> {
+  let (mut send, recv) = stream.split();
+  let content_length = request
+    .headers()
+    .get(CONTENT_LENGTH)
+    .and_then(|value| value.to_str().ok()?.parse().ok());
+  let mut size_hint = SizeHint::new();
+  if let Some(content_length) = content_length {
+    size_hint.set_exact(content_length);
+  }
+  let (body_tx, body_rx) = mpsc::channel(1);
+  let body = IncomingBody::Http3(Http3RequestBody {
+    rx: body_rx,
+    size_hint,
+  });
+  let mut request = match build_request(request, body) {
+    Ok(request) => request,
+    Err(err) => {
+      send.stop_stream(Code::H3_MESSAGE_ERROR);
+      return Err(err);
+    }
+  };
+  let informational = InformationalQueue::default();
+  request.extensions_mut().insert(informational.clone());
+
+  let respond = std::pin::pin!(async {
+    let mut response =
+      std::pin::pin!(handle_request(request, request_info, server_state, tx));
+    // Informational responses are written while the handler is running
+    let response = loop {
+      let next_informational = poll_fn(|cx| informational.poll_pop(cx));
+      match select(response.as_mut(), std::pin::pin!(next_informational)).await
+      {
+        Either::Left((response, _)) => break response,
+        Either::Right(((_, informational), _)) => {
+          write_informational(&mut send, informational, timeouts.response_write)
+            .await?
+        }
+      }
+    };
+    let response = response.map_err(|err| anyhow!(err))?;
+    while let Some((_, informational)) = informational.pop() {
+      write_informational(&mut send, informational, timeouts.response_write)
+        .await?;
+    }
+    write_response(&mut send, response, timeouts.response_write).await
+  });
+  let forward_body = std::pin::pin!(forward_request_body(recv, body_tx));
+  // Once the response is written, the rest of the request body is discarded.
+  match select(respond, forward_body).await {
+    Either::Left((res, _)) => res,
+    Either::Right((_, respond)) => respond.await,
+  }
+}
+
+async fn serve_connection(
+  incoming: quinn::Incoming,
+  local_port: u16,
+  lifetime: HttpLifetime,
+  tx: mpsc::Sender<Rc<HttpRecord>>,
+) -> Result<(), AnyError> {
+  let metrics = lifetime.server_state.borrow().metrics.clone();
+  let conn = incoming.await.inspect_err(|_| metrics.handshake_failed())?;
+  let remote_addr = conn.remote_address();
+  let request_info = HttpConnectionProperties {
+    peer_address: remote_addr.ip().to_string().into(),
+    peer_port: Some(remote_addr.port()),
+    local_port: Some(local_port),
+    stream_type: NetworkStreamType::Tls,
+  };
+  // The bytes of the connection are counted once it closes.
+  let _connection_metrics = guard(
+    (metrics.open_connection(&request_info), conn.clone()),
+    |(connection_metrics, conn)| {
+      let stats = conn.stats();
+      connection_metrics.read(stats.udp_rx.bytes as usize);
+      connection_metrics.written(stats.udp_tx.bytes as usize);
+    },
+  );
+
+  let timeouts = lifetime.server_state.borrow().timeouts;
+  let mut h3_conn: h3::server::Connection<h3_quinn::Connection, Bytes> =
+    h3::server::Connection::new(h3_quinn::Connection::new(conn)).await?;
+
+  // h3 only yields a request once its headers are read, so a client that is
+  // too slow to send them is bounded by the idle timeout of the connection
+  // rather than by `header_read`.
+  let mut requests = FuturesUnordered::new();
+  let mut cancelled = std::pin::pin!(std::future::pending::<()>()
+    .or_cancel(lifetime.listen_cancel_handle.clone()));
+  loop {
+    tokio::select! {
+      res = h3_conn.accept() => match res {
+        Ok(Some((request, stream))) => {
+          requests.push(serve_request(
+            request,
+            stream,
+            request_info.clone(),
+            lifetime.server_state.clone(),
+            tx.clone(),
+            timeouts,
+          ));
+        }
+        Ok(None) => break,
+        Err(err) => match err.get_error_level() {
+          // A malformed request only resets its own stream.
+          ErrorLevel::StreamError => {}
+          ErrorLevel::ConnectionError => return Err(err.into()),
+        },
+      },
+      Some(_) = requests.next(), if !requests.is_empty() => {}
+      _ = &mut cancelled => {
+        // In a graceful shutdown, tell the client to stop sending requests
+        // and let the pending ones complete.
+        h3_conn.shutdown(0).await?;
+        break;
+      }
+    }
+  }
+  while requests.next().await.is_some() {}
+  Ok(())
+}
+
+fn server_config(
+  tls_key: TlsKey,
+  sni_certificates: Vec<SniCertificate>,
+) -> Result<quinn::ServerConfig, AnyError> {
+  let tls_config = rustls::ServerConfig::builder_with_protocol_versions(&[
+    &rustls::version::TLS13,
+  ])
+  .with_no_client_auth();
+  let mut tls_config = if sni_certificates.is_empty() {
+    let TlsKey(cert_chain, private_key) = tls_key;
+    tls_config.with_single_cert(cert_chain, private_key)?
+  } else {
+    let resolver = SniCertResolver::new(tls_key, None, sni_certificates)?;
+    tls_config.with_cert_resolver(Arc::new(resolver))
+  };
+  tls_config.alpn_protocols = vec![ALPN_H3.to_vec()];
+  let crypto = QuicServerConfig::try_from(tls_config)?;
+  Ok(quinn::ServerConfig::with_crypto(Arc::new(crypto)))
+}
+
+fn create_endpoint(
+  addr: SocketAddr,
+  tls_key: TlsKey,
+  sni_certificates: Vec<SniCertificate>,
+) -> Result<quinn::Endpoint, AnyError> {
+  let config = server_config(tls_key, sni_certificates)?;
+  Ok(quinn::Endpoint::server(config, addr)?)
+}
+
+/// Binds an HTTP/3 endpoint to the UDP port of the HTTPS server `rid`,
+/// returning the port it listens on.
+#[op2]
+pub fn op_http_serve_h3(
+  state: &mut OpState,
+  #[smi] rid: ResourceId,
+  #[serde] addr: IpAddr,
+  #[cppgc] keys: &TlsKeysHolder,
+  #[serde] sni_certificates: Option<Vec<SniCertificate>>,
+) -> Result<u16, AnyError> {
+  state.feature_checker.check_or_exit(
+    deno_net::UNSTABLE_FEATURE_NAME,
+    "Deno.serve({ http3: true })",
+  );
+
+  let join_handle = state.resource_table.get::<HttpJoinHandle>(rid)?;
+  let TlsKeys::Static(tls_key) = keys.take() else {
+    return Err(anyhow!("HTTP/3 requires a static certificate and key"));
+  };
+  let tx = join_handle
+    .sender()
+    .ok_or_else(|| anyhow!("HTTP server is already closed"))?;
+  let addr = SocketAddr::new(addr.hostname.parse()?, addr.port);
+  let endpoint =
+    create_endpoint(addr, tls_key, sni_certificates.unwrap_or_default())
+      .map_err(|err| {
+        deno_core::error::custom_error(
+          "InvalidData",
+          "Error creating HTTP/3 endpoint",
+        )
+        .context(err)
+      })?;
+  let local_port = endpoint.local_addr()?.port();
+  *join_handle.h3_endpoint.borrow_mut() = Some(endpoint.clone());
+
+  let lifetime = join_handle.lifetime();
+  lifetime.server_state.borrow_mut().alt_svc = Some(HeaderValue::from_str(
+    &format!("h3=\":{local_port}\"; ma=86400"),
+  )?);
+
+  let listen_cancel_handle = join_handle.listen_cancel_handle();
+  let h3_handle = spawn(async move {
+    while let Ok(Some(incoming)) = endpoint
+      .accept()
+      .or_cancel(listen_cancel_handle.clone())
+      .await
+    {
+      spawn(
+        serve_connection(incoming, local_port, lifetime.clone(), tx.clone())
+          .try_or_cancel(lifetime.connection_cancel_handle.clone()),
+      );
+    }
+    // Don't keep the server alive while the connections drain.
+    drop(lifetime);
+    drop(tx);
+    endpoint.wait_idle().await;
+    Ok::<_, AnyError>(())
+  });
+
+  // Shutting down the server waits for both listeners.
+  let mut handle = RcRef::map(&join_handle, |this| &this.join_handle)
+    .try_borrow_mut()
+    .ok_or_else(|| anyhow!("HTTP server is busy"))?;
+  let tcp_handle = handle.take();
+  *handle = Some(spawn(async move {
+    let tcp_result = match tcp_handle {
+      Some(tcp_handle) => tcp_handle.await?,
+      None => Ok(()),
+    };
+    let h3_result = h3_handle.await?;
+    tcp_result.and(h3_result)
+  }));
+
+  Ok(local_port)
+}
+
+/// Replaces the certificate of the HTTP/3 endpoint of the server `rid` for
+/// the connections it accepts from now on.
+#[op2]
+pub fn op_http_set_h3_certificate(
+  state: &mut OpState,
+  #[smi] rid: ResourceId,
+  #[cppgc] keys: &TlsKeysHolder,
+  #[serde] sni_certificates: Option<Vec<SniCertificate>>,
+) -> Result<(), AnyError> {
+  let join_handle = state.resource_table.get::<HttpJoinHandle>(rid)?;
+  let TlsKeys::Static(tls_key) = keys.take() else {
+    return Err(anyhow!("HTTP/3 requires a static certificate and key"));
+  };
+  let config = server_config(tls_key, sni_certificates.unwrap_or_default())
+    .map_err(|err| {
+      deno_core::error::custom_error(
+        "InvalidData",
+        "Error creating TLS certificate",
+      )
+      .context(err)
+    })?;
+  if let Some(endpoint) = &*join_handle.h3_endpoint.borrow() {
+    endpoint.set_server_config(Some(config));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::response_body::Compression;
+  use crate::response_body::ResponseBytesInner;
+  use deno_core::CancelHandle;
+  use http_body_util::BodyExt;
+  use quinn::crypto::rustls::QuicClientConfig;
+  use std::io::BufReader;
+
+  #[test]
+  fn test_build_request() {
+    let request = http::Request::post("https://localhost:4443/upload?x=1")
+      .header("cookie", "a=1")
+      .header("content-type", "text/plain")
+      .header("cookie", "b=2")
+      .body(())
+      .unwrap();
+    let (_, rx) = mpsc::channel(1);
+    let request = build_request(
+      request,
+      IncomingBody::Http3(Http3RequestBody {
+        rx,
+        size_hint: SizeHint::new(),
+      }),
+    )
+    .unwrap();
+    assert_eq!(request.uri(), "https://localhost:4443/upload?x=1");
+    assert_eq!(request.version(), Version::HTTP_3);
+    assert_eq!(request.headers()[COOKIE], "a=1; b=2");
+    assert_eq!(request.headers()["content-type"], "text/plain");
+  }
+
+  #[tokio::test]
+  async fn test_serve_request_over_quic() -> Result<(), AnyError> {
+    let cert_chain = deno_tls::load_certs(&mut BufReader::new(
+      &include_bytes!("../../tests/testdata/tls/localhost.crt")[..],
+    ))?;
+    let private_key = deno_tls::load_private_keys(include_bytes!(
+      "../../tests/testdata/tls/localhost.key"
+    ))?
+    .remove(0);
+    let endpoint =
+      create_endpoint("127.0.0.1:0".parse()?, TlsKey(cert_chain, private_key))?;
+    let server_addr = endpoint.local_addr()?;
+
+    let mut root_cert_store = rustls::RootCertStore::empty();
+    root_cert_store.add_parsable_certificates(deno_tls::load_certs(
+      &mut BufReader::new(
+        &include_bytes!("../../tests/testdata/tls/RootCA.pem")[..],
+      ),
+    )?);
+    let mut client_crypto = rustls::ClientConfig::builder()
+      .with_root_certificates(root_cert_store)
+      .with_no_client_auth();
+    client_crypto.alpn_protocols = vec![ALPN_H3.to_vec()];
+    let mut client_endpoint = quinn::Endpoint::client("127.0.0.1:0".parse()?)?;
+    client_endpoint.set_default_client_config(quinn::ClientConfig::new(
+      Arc::new(QuicClientConfig::try_from(client_crypto)?),
+    ));
+
+    let (tx, mut rx) = mpsc::channel(10);
+    let lifetime = HttpLifetime {
+      connection_cancel_handle: CancelHandle::new_rc(),
+      listen_cancel_handle: CancelHandle::new_rc(),
+      server_state: HttpServerState::new(),
+    };
+    let listen_cancel_handle = lifetime.listen_cancel_handle.clone();
+
+    let server = async {
+      let incoming = endpoint
+        .accept()
+        .await
+        .ok_or_else(|| anyhow!("Endpoint closed"))?;
+      serve_connection(incoming, server_addr.port(), lifetime, tx).await
+    };
+    // The JavaScript handler echoes the request body.
+    let handler = async {
+      let record = rx.recv().await.ok_or_else(|| anyhow!("No request"))?;
+      assert_eq!(record.request_parts().version, Version::HTTP_3);
+      assert_eq!(record.request_parts().uri.path(), "/upload");
+      assert_eq!(record.request_parts().headers[COOKIE], "a=1; b=2");
+      let body = record
+        .take_request_body()
+        .ok_or_else(|| anyhow!("No request body"))?
+        .collect()
+        .await?
+        .to_bytes();
+      let mut response_body = b"hello ".to_vec();
+      response_body.extend_from_slice(&body);
+      record.response_parts().headers.insert(
+        http::header::CONNECTION,
+        HeaderValue::from_static("keep-alive"),
+      );
+      record.set_response_body(ResponseBytesInner::from_vec(
+        Compression::None,
+        response_body,
+      ));
+      record.complete();
+      Ok::<_, AnyError>(())
+    };
+    let client = async {
+      let conn = client_endpoint.connect(server_addr, "localhost")?.await?;
+      let (mut driver, mut send_request) =
+        h3::client::new(h3_quinn::Connection::new(conn)).await?;
+      let request = async {
+        let request = http::Request::post("https://localhost/upload")
+          .header("cookie", "a=1")
+          .header("cookie", "b=2")
+          .body(())?;
+        let mut stream = send_request.send_request(request).await?;
+        stream.send_data(Bytes::from_static(b"world")).await?;
+        stream.finish().await?;
+        let response = stream.recv_response().await?;
+        assert_eq!(response.status(), 200);
+        assert!(response.headers().get(http::header::CONNECTION).is_none());
+        let mut body = Vec::new();
+        while let Some(mut chunk) = stream.recv_data().await? {
+          body.extend_from_slice(&chunk.copy_to_bytes(chunk.remaining()));
+        }
+        assert_eq!(body, b"hello world");
+        Ok::<_, AnyError>(())
+      };
+      tokio::select! {
+        res = request => res?,
+        res = poll_fn(|cx| driver.poll_close(cx)) => {
+          res?;
+          return Err(anyhow!("HTTP/3 connection closed early"));
+        }
+      }
+      // Shuts the server connection down gracefully.
+      listen_cancel_handle.cancel();
+      Ok(())
+    };
+    tokio::try_join!(server, handler, client)?;
+    Ok(())
+  }
+}
@@ -1,24 +1,37 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+use crate::compressible::content_type_matches;
 use crate::compressible::is_content_compressible;
 use crate::extract_network_stream;
+use crate::h2c;
+use crate::h2c::H2cUpgradeService;
+use crate::informational::InformationalQueue;
+use crate::informational::InformationalResponse;
+use crate::metrics::HttpConnectionEvent;
+use crate::metrics::HttpServerMetrics;
+use crate::metrics::HttpServerMetricsSnapshot;
 use crate::network_buffered_stream::NetworkStreamPrefixCheck;
 use crate::request_body::HttpRequestBody;
+use crate::request_body::IncomingBody;
 use crate::request_properties::HttpConnectionProperties;
 use crate::request_properties::HttpListenProperties;
 use crate::request_properties::HttpPropertyExtractor;
 use crate::response_body::Compression;
 use crate::response_body::ResponseBytesInner;
+use crate::serve_stream::ServeStream;
 use crate::service::handle_request;
 use crate::service::http_general_trace;
 use crate::service::http_trace;
 use crate::service::HttpRecord;
 use crate::service::HttpRecordResponse;
 use crate::service::HttpRequestBodyAutocloser;
+use crate::service::HttpServeCompression;
+use crate::service::HttpServeTimeouts;
 use crate::service::HttpServerState;
 use crate::service::SignallingRc;
 use crate::websocket_upgrade::WebSocketUpgrade;
 use crate::LocalExecutor;
 use cache_control::CacheControl;
+use deno_core::error::type_error;
 use deno_core::error::AnyError;
 use deno_core::external;
 use deno_core::futures::future::poll_fn;
@@ -44,6 +57,7 @@ use deno_core::ResourceId;
 use deno_net::ops_tls::TlsStream;
 use deno_net::raw::NetworkStream;
 use deno_websocket::ws_create_server_stream;
+use hyper::body::Body;
 use hyper::body::Incoming;
 use hyper::header::HeaderMap;
 use hyper::header::ACCEPT_ENCODING;
@@ -53,15 +67,20 @@ use hyper::header::CONTENT_LENGTH;
 use hyper::header::CONTENT_RANGE;
 use hyper::header::CONTENT_TYPE;
 use hyper::header::COOKIE;
+use hyper::header::SEC_WEBSOCKET_EXTENSIONS;
 use hyper::http::HeaderName;
 use hyper::http::HeaderValue;
 use hyper::server::conn::http1;
 use hyper::server::conn::http2;
 use hyper::service::service_fn;
 use hyper::service::HttpService;
+use hyper::service::Service;
 use hyper::StatusCode;
+use hyper::Version;
 use hyper_util::rt::TokioIo;
+use hyper_util::rt::TokioTimer;
 use once_cell::sync::Lazy;
+use serde::Deserialize;
 use smallvec::SmallVec;
 use std::borrow::Cow;
 use std::cell::RefCell;
@@ -71,15 +90,37 @@ use std::io;
 use std::pin::Pin;
 use std::ptr::null;
 use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
 
 use super::fly_accept_encoding;
 use fly_accept_encoding::Encoding;
 
+use tokio::io::AsyncRead;
 use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
 use tokio::io::AsyncWriteExt;
 
 type Request = hyper::Request<Incoming>;
 
+/// Converts a request from hyper, allowing HTTP/1.1 and HTTP/2 requests to send informational
+/// responses through the stream of their connection.
+fn incoming_request(
+  req: Request,
+  informational: &InformationalQueue,
+) -> crate::service::Request {
+  let mut req = req.map(IncomingBody::Hyper);
+  let informational = match req.version() {
+    Version::HTTP_11 => Some(informational.clone()),
+    Version::HTTP_2 => informational.for_next_stream(),
+    _ => None,
+  };
+  if let Some(informational) = informational {
+    req.extensions_mut().insert(informational);
+  }
+  req
+}
+
 static USE_WRITEV: Lazy<bool> = Lazy::new(|| {
   let enable = std::env::var("DENO_USE_WRITEV").ok();
 
@@ -102,7 +143,7 @@ static USE_WRITEV: Lazy<bool> = Lazy::new(|| {
 ///
 /// That is, the connection preface starts with the string PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n). This sequence
 /// MUST be followed by a SETTINGS frame (Section 6.5), which MAY be empty.
-const HTTP2_PREFIX: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+pub(crate) const HTTP2_PREFIX: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
 
 /// ALPN negotiation for "h2"
 const TLS_ALPN_HTTP_2: &[u8] = b"h2";
@@ -229,7 +270,7 @@ pub async fn op_http_upgrade_websocket_next(
     unsafe { take_external!(external, "op_http_upgrade_websocket_next") };
   // Stage 1: set the response to 101 Switching Protocols and send it
   let upgrade = http.upgrade()?;
-  {
+  let extensions = {
     let mut response_parts = http.response_parts();
     response_parts.status = StatusCode::SWITCHING_PROTOCOLS;
     for (name, value) in headers {
@@ -238,7 +279,12 @@ pub async fn op_http_upgrade_websocket_next(
         HeaderValue::from_bytes(&value).unwrap(),
       );
     }
-  }
+    response_parts
+      .headers
+      .get(SEC_WEBSOCKET_EXTENSIONS)
+      .and_then(|value| value.to_str().ok())
+      .map(str::to_owned)
+  };
   http.complete();
 
   // Stage 2: wait for the request to finish upgrading
@@ -246,7 +292,78 @@ pub async fn op_http_upgrade_websocket_next(
 
   // Stage 3: take the extracted raw network stream and upgrade it to a websocket, then return it
   let (stream, bytes) = extract_network_stream(upgraded);
-  ws_create_server_stream(&mut state.borrow_mut(), stream, bytes)
+  ws_create_server_stream(
+    &mut state.borrow_mut(),
+    stream,
+    bytes,
+    extensions.as_deref(),
+  )
+}
+
+/// Sends the response that switches the connection of an HTTP/1.1 upgrade or
+/// `CONNECT` request over, and hands the connection itself to JavaScript.
+#[op2(async)]
+#[smi]
+pub async fn op_http_upgrade_connection_next(
+  state: Rc<RefCell<OpState>>,
+  external: *const c_void,
+  #[smi] status: u16,
+  #[serde] headers: Vec<(ByteString, ByteString)>,
+) -> Result<ResourceId, AnyError> {
+  let http =
+    // SAFETY: external is deleted before calling this op.
+    unsafe { take_external!(external, "op_http_upgrade_connection_next") };
+  // Stage 1: set the response and send it, failing the request if it can't be
+  // upgraded (ie: it was received over HTTP/2)
+  let upgrade = match http.upgrade() {
+    Ok(upgrade) => upgrade,
+    Err(err) => {
+      set_promise_complete(http, 500);
+      return Err(err);
+    }
+  };
+  let headers = match parse_upgrade_headers(headers) {
+    Ok(headers) => headers,
+    Err(err) => {
+      set_promise_complete(http, 500);
+      return Err(err);
+    }
+  };
+  {
+    let mut response_parts = http.response_parts();
+    response_parts.status = StatusCode::from_u16(status)?;
+    for (name, value) in headers {
+      response_parts.headers.append(name, value);
+    }
+  }
+  http.complete();
+
+  // Stage 2: wait for the request to finish upgrading
+  let upgraded = upgrade.await?;
+
+  // Stage 3: take the extracted raw network stream, returning the bytes the
+  // client already sent after its request first
+  let (stream, bytes) = extract_network_stream(upgraded);
+  let (read, write) = tokio::io::split(stream);
+  Ok(state.borrow_mut().resource_table.add(UpgradeStream::new(
+    io::Cursor::new(bytes).chain(read),
+    write,
+  )))
+}
+
+fn parse_upgrade_headers(
+  headers: Vec<(ByteString, ByteString)>,
+) -> Result<Vec<(HeaderName, HeaderValue)>, AnyError> {
+  headers
+    .into_iter()
+    .map(|(name, value)| {
+      let name = HeaderName::from_bytes(&name)
+        .map_err(|err| type_error(format!("Invalid header name: {err}")))?;
+      let value = HeaderValue::from_bytes(&value)
+        .map_err(|err| type_error(format!("Invalid header value: {err}")))?;
+      Ok((name, value))
+    })
+    .collect()
 }
 
 #[op2(fast)]
@@ -426,7 +543,12 @@ pub fn op_http_read_request_body(
     // SAFETY: op is called with external.
     unsafe { clone_external!(external, "op_http_read_request_body") };
   let rid = if let Some(incoming) = http.take_request_body() {
-    let body_resource = Rc::new(HttpRequestBody::new(incoming));
+    let idle_timeout = http.server_timeouts().request_body_idle;
+    let body_resource = Rc::new(HttpRequestBody::new(
+      incoming,
+      idle_timeout,
+      http.request_trailers(),
+    ));
     state.borrow_mut().resource_table.add_rc(body_resource)
   } else {
     // This should not be possible, but rather than panicking we'll return an invalid
@@ -493,6 +615,53 @@ pub fn op_http_set_response_headers(
   }
 }
 
+/// Queues an informational response, such as `103 Early Hints`, returning whether the connection
+/// is able to send it.
+#[op2]
+pub fn op_http_send_informational(
+  external: *const c_void,
+  #[smi] status: u16,
+  #[serde] headers: Vec<(ByteString, ByteString)>,
+) -> Result<bool, AnyError> {
+  let http =
+    // SAFETY: op is called with external.
+    unsafe { clone_external!(external, "op_http_send_informational") };
+  let status = StatusCode::from_u16(status)?;
+  if !status.is_informational() {
+    return Err(type_error(format!("Invalid informational status {status}")));
+  }
+  let mut header_map = HeaderMap::with_capacity(headers.len());
+  for (name, value) in headers {
+    header_map.append(
+      HeaderName::from_bytes(&name)?,
+      HeaderValue::from_bytes(&value)?,
+    );
+  }
+  Ok(http.send_informational(InformationalResponse {
+    status,
+    headers: header_map,
+  }))
+}
+
+#[op2]
+#[serde]
+pub fn op_http_get_request_trailers(
+  external: *const c_void,
+) -> Option<Vec<(ByteString, ByteString)>> {
+  let http =
+    // SAFETY: op is called with external.
+    unsafe { clone_external!(external, "op_http_get_request_trailers") };
+  let trailers = http.request_trailers();
+  let trailers = trailers.borrow();
+  let trailers = trailers.as_ref()?;
+  Some(
+    trailers
+      .iter()
+      .map(|(name, value)| (name.as_str().into(), value.as_bytes().into()))
+      .collect(),
+  )
+}
+
 #[op2]
 pub fn op_http_set_response_trailers(
   external: *const c_void,
@@ -515,14 +684,18 @@ pub fn op_http_set_response_trailers(
 fn is_request_compressible(
   length: Option<usize>,
   headers: &HeaderMap,
+  options: &HttpServeCompression,
 ) -> Compression {
+  if !options.enabled {
+    return Compression::None;
+  }
   if let Some(length) = length {
-    // By the time we add compression headers and Accept-Encoding, it probably doesn't make sense
-    // to compress stuff that's smaller than this.
-    if length < 64 {
+    if length < options.min_size {
       return Compression::None;
     }
   }
+  let gzip = Compression::GZip(options.gzip_level);
+  let brotli = Compression::Brotli(options.brotli_quality);
 
   let Some(accept_encoding) = headers.get(ACCEPT_ENCODING) else {
     return Compression::None;
@@ -530,9 +703,9 @@ fn is_request_compressible(
 
   match accept_encoding.to_str() {
     // Firefox and Chrome send this -- no need to parse
-    Ok("gzip, deflate, br") => return Compression::Brotli,
-    Ok("gzip") => return Compression::GZip,
-    Ok("br") => return Compression::Brotli,
+    Ok("gzip, deflate, br") => return brotli,
+    Ok("gzip") => return gzip,
+    Ok("br") => return brotli,
     _ => (),
   }
 
@@ -548,15 +721,22 @@ fn is_request_compressible(
       )
     });
   match fly_accept_encoding::preferred(accepted) {
-    Ok(Some(fly_accept_encoding::Encoding::Gzip)) => Compression::GZip,
-    Ok(Some(fly_accept_encoding::Encoding::Brotli)) => Compression::Brotli,
+    Ok(Some(fly_accept_encoding::Encoding::Gzip)) => gzip,
+    Ok(Some(fly_accept_encoding::Encoding::Brotli)) => brotli,
     _ => Compression::None,
   }
 }
 
-fn is_response_compressible(headers: &HeaderMap) -> bool {
+fn is_response_compressible(
+  headers: &HeaderMap,
+  options: &HttpServeCompression,
+) -> bool {
   if let Some(content_type) = headers.get(CONTENT_TYPE) {
-    if !is_content_compressible(content_type) {
+    let compressible = match &options.content_types {
+      Some(content_types) => content_type_matches(content_type, content_types),
+      None => is_content_compressible(content_type),
+    };
+    if !compressible {
       return false;
     }
   } else {
@@ -583,17 +763,21 @@ fn is_response_compressible(headers: &HeaderMap) -> bool {
 fn modify_compressibility_from_response(
   compression: Compression,
   headers: &mut HeaderMap,
+  options: &HttpServeCompression,
 ) -> Compression {
+  if !options.enabled {
+    return Compression::None;
+  }
   ensure_vary_accept_encoding(headers);
   if compression == Compression::None {
     return Compression::None;
   }
-  if !is_response_compressible(headers) {
+  if !is_response_compressible(headers, options) {
     return Compression::None;
   }
   let encoding = match compression {
-    Compression::Brotli => "br",
-    Compression::GZip => "gzip",
+    Compression::Brotli(_) => "br",
+    Compression::GZip(_) => "gzip",
     _ => unreachable!(),
   };
   weaken_etag(headers);
@@ -646,12 +830,16 @@ fn set_response(
   // The request may have been cancelled by this point and if so, there's no need for us to
   // do all of this work to send the response.
   if !http.cancelled() {
+    let options = http.server_compression();
     let compression =
-      is_request_compressible(length, &http.request_parts().headers);
+      is_request_compressible(length, &http.request_parts().headers, &options);
     let mut response_headers =
       std::cell::RefMut::map(http.response_parts(), |this| &mut this.headers);
-    let compression =
-      modify_compressibility_from_response(compression, &mut response_headers);
+    let compression = modify_compressibility_from_response(
+      compression,
+      &mut response_headers,
+      &options,
+    );
     drop(response_headers);
     http.set_response_body(response_fn(compression));
 
@@ -756,14 +944,24 @@ pub fn op_http_set_response_body_bytes(
   }
 }
 
-fn serve_http11_unconditional(
+fn serve_http11_unconditional<B>(
   io: impl HttpServeStream,
-  svc: impl HttpService<Incoming, ResBody = HttpRecordResponse> + 'static,
+  svc: impl HttpService<Incoming, ResBody = B> + 'static,
   cancel: Rc<CancelHandle>,
-) -> impl Future<Output = Result<(), hyper::Error>> + 'static {
-  let conn = http1::Builder::new()
-    .keep_alive(true)
-    .writev(*USE_WRITEV)
+  header_read_timeout: Option<Duration>,
+) -> impl Future<Output = Result<(), hyper::Error>> + 'static
+where
+  B: Body + 'static,
+  B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+  let mut builder = http1::Builder::new();
+  builder.keep_alive(true).writev(*USE_WRITEV);
+  if let Some(header_read_timeout) = header_read_timeout {
+    builder
+      .timer(TokioTimer::new())
+      .header_read_timeout(header_read_timeout);
+  }
+  let conn = builder
     .serve_connection(TokioIo::new(io), svc)
     .with_upgrades();
 
@@ -796,24 +994,88 @@ fn serve_http2_unconditional(
   }
 }
 
-async fn serve_http2_autodetect(
+/// Serves HTTP/1.1, continuing the connection as HTTP/2 if a request asks to upgrade to h2c.
+async fn serve_http11_h2c_upgrade<S>(
   io: impl HttpServeStream,
-  svc: impl HttpService<Incoming, ResBody = HttpRecordResponse> + 'static,
+  svc: S,
   cancel: Rc<CancelHandle>,
-) -> Result<(), AnyError> {
+  header_read_timeout: Option<Duration>,
+) -> Result<(), AnyError>
+where
+  S: Service<Request, Response = hyper::Response<HttpRecordResponse>>
+    + Clone
+    + 'static,
+  S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+  let (h1_svc, upgrade) = H2cUpgradeService::new(svc.clone());
+  serve_http11_unconditional(io, h1_svc, cancel.clone(), header_read_timeout)
+    .await?;
+  let Some(upgrade) = upgrade.take() else {
+    return Ok(());
+  };
+  let io = TokioIo::new(upgrade.on_upgrade.await?);
+  let io = with_header_read_timeout(
+    header_read_timeout,
+    h2c::read_client_preface(
+      io,
+      &upgrade.settings_frame,
+      &upgrade.headers_frame,
+    ),
+  )
+  .await?;
+  serve_http2_unconditional(io, svc, cancel)
+    .await
+    .map_err(|e| e.into())
+}
+
+/// Serves HTTP/2 if the connection starts with the HTTP/2 connection preface, and HTTP/1.1
+/// otherwise. Cleartext connections may also switch to HTTP/2 with an `Upgrade: h2c` request.
+async fn serve_http2_autodetect<S>(
+  io: impl HttpServeStream,
+  svc: S,
+  cancel: Rc<CancelHandle>,
+  header_read_timeout: Option<Duration>,
+  allow_h2c_upgrade: bool,
+) -> Result<(), AnyError>
+where
+  S: Service<Request, Response = hyper::Response<HttpRecordResponse>>
+    + Clone
+    + 'static,
+  S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
   let prefix = NetworkStreamPrefixCheck::new(io, HTTP2_PREFIX);
-  let (matches, io) = prefix.match_prefix().await?;
+  let (matches, io) =
+    with_header_read_timeout(header_read_timeout, prefix.match_prefix())
+      .await?;
   if matches {
     serve_http2_unconditional(io, svc, cancel)
       .await
       .map_err(|e| e.into())
+  } else if allow_h2c_upgrade {
+    serve_http11_h2c_upgrade(io, svc, cancel, header_read_timeout).await
   } else {
-    serve_http11_unconditional(io, svc, cancel)
+    serve_http11_unconditional(io, svc, cancel, header_read_timeout)
       .await
       .map_err(|e| e.into())
   }
 }
 
+/// Fails with [`io::ErrorKind::TimedOut`] if a client doesn't complete the start of a connection
+/// in time.
+async fn with_header_read_timeout<T>(
+  header_read_timeout: Option<Duration>,
+  fut: impl Future<Output = io::Result<T>>,
+) -> io::Result<T> {
+  let Some(header_read_timeout) = header_read_timeout else {
+    return fut.await;
+  };
+  tokio::time::timeout(header_read_timeout, fut)
+    .await
+    .map_err(|_| {
+      io::Error::new(io::ErrorKind::TimedOut, "Request header read timed out")
+    })?
+}
+
 fn serve_https(
   mut io: TlsStream,
   request_info: HttpConnectionProperties,
@@ -826,12 +1088,31 @@ fn serve_https(
     listen_cancel_handle,
   } = lifetime;
 
+  let timeouts = server_state.borrow().timeouts;
+  let metrics = server_state.borrow().metrics.clone();
+  let informational = InformationalQueue::default();
+  let svc_informational = informational.clone();
+  let connection_info = request_info.clone();
   let svc = service_fn(move |req: Request| {
-    handle_request(req, request_info.clone(), server_state.clone(), tx.clone())
+    handle_request(
+      incoming_request(req, &svc_informational),
+      request_info.clone(),
+      server_state.clone(),
+      tx.clone(),
+    )
   });
   spawn(
-    async {
-      let handshake = io.handshake().await?;
+    async move {
+      let handshake =
+        with_header_read_timeout(timeouts.header_read, io.handshake())
+          .await
+          .inspect_err(|_| metrics.handshake_failed())?;
+      let io = ServeStream::new(
+        io,
+        timeouts.response_write,
+        informational,
+        metrics.open_connection(&connection_info),
+      );
       // If the client specifically negotiates a protocol, we will use it. If not, we'll auto-detect
       // based on the prefix bytes
       let handshake = handshake.alpn;
@@ -840,11 +1121,24 @@ fn serve_https(
           .await
           .map_err(|e| e.into())
       } else if Some(TLS_ALPN_HTTP_11) == handshake.as_deref() {
-        serve_http11_unconditional(io, svc, listen_cancel_handle)
-          .await
-          .map_err(|e| e.into())
+        serve_http11_unconditional(
+          io,
+          svc,
+          listen_cancel_handle,
+          timeouts.header_read,
+        )
+        .await
+        .map_err(|e| e.into())
       } else {
-        serve_http2_autodetect(io, svc, listen_cancel_handle).await
+        // h2c isn't allowed over TLS
+        serve_http2_autodetect(
+          io,
+          svc,
+          listen_cancel_handle,
+          timeouts.header_read,
+          false,
+        )
+        .await
       }
     }
     .try_or_cancel(connection_cancel_handle),
@@ -863,12 +1157,34 @@ fn serve_http(
     listen_cancel_handle,
   } = lifetime;
 
+  let timeouts = server_state.borrow().timeouts;
+  let h2c_upgrade = server_state.borrow().h2c_upgrade;
+  let connection_metrics =
+    server_state.borrow().metrics.open_connection(&request_info);
+  let informational = InformationalQueue::default();
+  let svc_informational = informational.clone();
   let svc = service_fn(move |req: Request| {
-    handle_request(req, request_info.clone(), server_state.clone(), tx.clone())
+    handle_request(
+      incoming_request(req, &svc_informational),
+      request_info.clone(),
+      server_state.clone(),
+      tx.clone(),
+    )
   });
   spawn(
-    serve_http2_autodetect(io, svc, listen_cancel_handle)
-      .try_or_cancel(connection_cancel_handle),
+    serve_http2_autodetect(
+      ServeStream::new(
+        io,
+        timeouts.response_write,
+        informational,
+        connection_metrics,
+      ),
+      svc,
+      listen_cancel_handle,
+      timeouts.header_read,
+      h2c_upgrade,
+    )
+    .try_or_cancel(connection_cancel_handle),
   )
 }
 
@@ -901,32 +1217,49 @@ where
 }
 
 #[derive(Clone)]
-struct HttpLifetime {
-  connection_cancel_handle: Rc<CancelHandle>,
-  listen_cancel_handle: Rc<CancelHandle>,
-  server_state: SignallingRc<HttpServerState>,
+pub(crate) struct HttpLifetime {
+  pub connection_cancel_handle: Rc<CancelHandle>,
+  pub listen_cancel_handle: Rc<CancelHandle>,
+  pub server_state: SignallingRc<HttpServerState>,
 }
 
-struct HttpJoinHandle {
-  join_handle: AsyncRefCell<Option<JoinHandle<Result<(), AnyError>>>>,
+pub(crate) struct HttpJoinHandle {
+  pub join_handle: AsyncRefCell<Option<JoinHandle<Result<(), AnyError>>>>,
   connection_cancel_handle: Rc<CancelHandle>,
   listen_cancel_handle: Rc<CancelHandle>,
   rx: AsyncRefCell<tokio::sync::mpsc::Receiver<Rc<HttpRecord>>>,
+  // A weak sender, so that the requests channel still closes once the
+  // listener is done.
+  tx: tokio::sync::mpsc::WeakSender<Rc<HttpRecord>>,
   server_state: SignallingRc<HttpServerState>,
+  /// The HTTP/3 endpoint serving next to the listener, whose certificate is
+  /// replaced along with the listener's.
+  pub h3_endpoint: RefCell<Option<quinn::Endpoint>>,
 }
 
 impl HttpJoinHandle {
-  fn new(rx: tokio::sync::mpsc::Receiver<Rc<HttpRecord>>) -> Self {
+  fn new(
+    tx: &tokio::sync::mpsc::Sender<Rc<HttpRecord>>,
+    rx: tokio::sync::mpsc::Receiver<Rc<HttpRecord>>,
+  ) -> Self {
     Self {
       join_handle: AsyncRefCell::new(None),
       connection_cancel_handle: CancelHandle::new_rc(),
       listen_cancel_handle: CancelHandle::new_rc(),
       rx: AsyncRefCell::new(rx),
+      tx: tx.downgrade(),
       server_state: HttpServerState::new(),
+      h3_endpoint: RefCell::new(None),
     }
   }
 
-  fn lifetime(self: &Rc<Self>) -> HttpLifetime {
+  /// Returns a sender for requests received by another listener, unless the
+  /// server has already shut down.
+  pub fn sender(&self) -> Option<tokio::sync::mpsc::Sender<Rc<HttpRecord>>> {
+    self.tx.upgrade()
+  }
+
+  pub fn lifetime(self: &Rc<Self>) -> HttpLifetime {
     HttpLifetime {
       connection_cancel_handle: self.connection_cancel_handle.clone(),
       listen_cancel_handle: self.listen_cancel_handle.clone(),
@@ -938,7 +1271,7 @@ impl HttpJoinHandle {
     self.connection_cancel_handle.clone()
   }
 
-  fn listen_cancel_handle(self: &Rc<Self>) -> Rc<CancelHandle> {
+  pub fn listen_cancel_handle(self: &Rc<Self>) -> Rc<CancelHandle> {
     self.listen_cancel_handle.clone()
   }
 }
@@ -960,6 +1293,8 @@ impl Drop for HttpJoinHandle {
     // In some cases we may be dropped without closing, so let's cancel everything on the way out
     self.connection_cancel_handle.cancel();
     self.listen_cancel_handle.cancel();
+    // Once the server is closed, the connection events end
+    self.server_state.borrow().metrics.unsubscribe();
   }
 }
 
@@ -978,7 +1313,7 @@ where
   let listen_properties = HTTP::listen_properties_from_listener(&listener)?;
 
   let (tx, rx) = tokio::sync::mpsc::channel(10);
-  let resource: Rc<HttpJoinHandle> = Rc::new(HttpJoinHandle::new(rx));
+  let resource: Rc<HttpJoinHandle> = Rc::new(HttpJoinHandle::new(&tx, rx));
   let listen_cancel_clone = resource.listen_cancel_handle();
 
   let lifetime = resource.lifetime();
@@ -1027,7 +1362,7 @@ where
   let listen_properties = HTTP::listen_properties_from_connection(&connection)?;
 
   let (tx, rx) = tokio::sync::mpsc::channel(10);
-  let resource: Rc<HttpJoinHandle> = Rc::new(HttpJoinHandle::new(rx));
+  let resource: Rc<HttpJoinHandle> = Rc::new(HttpJoinHandle::new(&tx, rx));
 
   let handle: JoinHandle<Result<(), deno_core::anyhow::Error>> =
     serve_http_on::<HTTP>(
@@ -1049,6 +1384,149 @@ where
   ))
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServeTimeoutsArgs {
+  header_read_timeout_ms: Option<u64>,
+  request_body_idle_timeout_ms: Option<u64>,
+  response_write_timeout_ms: Option<u64>,
+}
+
+/// Sets the timeouts of the HTTP handle, which apply to the connections it accepts from now on.
+#[op2]
+pub fn op_http_set_serve_timeouts(
+  state: &mut OpState,
+  #[smi] rid: ResourceId,
+  #[serde] args: ServeTimeoutsArgs,
+) -> Result<(), AnyError> {
+  let join_handle = state.resource_table.get::<HttpJoinHandle>(rid)?;
+  join_handle.server_state.borrow_mut().timeouts = HttpServeTimeouts {
+    header_read: args.header_read_timeout_ms.map(Duration::from_millis),
+    request_body_idle: args
+      .request_body_idle_timeout_ms
+      .map(Duration::from_millis),
+    response_write: args.response_write_timeout_ms.map(Duration::from_millis),
+  };
+  Ok(())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServeCompressionArgs {
+  enabled: bool,
+  gzip_level: Option<u32>,
+  brotli_quality: Option<u32>,
+  min_size: Option<usize>,
+  content_types: Option<Vec<String>>,
+}
+
+/// Sets how the HTTP handle compresses responses.
+#[op2]
+pub fn op_http_set_serve_compression(
+  state: &mut OpState,
+  #[smi] rid: ResourceId,
+  #[serde] args: ServeCompressionArgs,
+) -> Result<(), AnyError> {
+  let join_handle = state.resource_table.get::<HttpJoinHandle>(rid)?;
+  let defaults = HttpServeCompression::default();
+  join_handle.server_state.borrow_mut().compression =
+    Rc::new(HttpServeCompression {
+      enabled: args.enabled,
+      gzip_level: args.gzip_level.unwrap_or(defaults.gzip_level),
+      brotli_quality: args.brotli_quality.unwrap_or(defaults.brotli_quality),
+      min_size: args.min_size.unwrap_or(defaults.min_size),
+      content_types: args.content_types.map(|content_types| {
+        content_types
+          .into_iter()
+          .map(|content_type| content_type.to_ascii_lowercase())
+          .collect()
+      }),
+    });
+  Ok(())
+}
+
+/// Allows the cleartext connections the HTTP handle accepts from now on to
+/// upgrade to HTTP/2 with an `Upgrade: h2c` request.
+#[op2(fast)]
+pub fn op_http_set_serve_h2c_upgrade(
+  state: &mut OpState,
+  #[smi] rid: ResourceId,
+) -> Result<(), AnyError> {
+  let join_handle = state.resource_table.get::<HttpJoinHandle>(rid)?;
+  join_handle.server_state.borrow_mut().h2c_upgrade = true;
+  Ok(())
+}
+
+/// The metrics of a server, which remain readable after it is closed.
+pub struct HttpServerMetricsHandle(Arc<HttpServerMetrics>);
+
+impl deno_core::GarbageCollected for HttpServerMetricsHandle {}
+
+#[op2]
+#[cppgc]
+pub fn op_http_serve_metrics_handle(
+  state: &mut OpState,
+  #[smi] rid: ResourceId,
+) -> Result<HttpServerMetricsHandle, AnyError> {
+  let join_handle = state.resource_table.get::<HttpJoinHandle>(rid)?;
+  let metrics = join_handle.server_state.borrow().metrics.clone();
+  Ok(HttpServerMetricsHandle(metrics))
+}
+
+#[op2]
+#[serde]
+pub fn op_http_serve_metrics(
+  #[cppgc] handle: &HttpServerMetricsHandle,
+) -> HttpServerMetricsSnapshot {
+  handle.0.snapshot()
+}
+
+/// The connection events of a server, which end once the server is closed.
+struct HttpConnectionEvents {
+  rx: AsyncRefCell<tokio::sync::mpsc::UnboundedReceiver<HttpConnectionEvent>>,
+  cancel_handle: CancelHandle,
+}
+
+impl Resource for HttpConnectionEvents {
+  fn name(&self) -> Cow<str> {
+    "httpConnectionEvents".into()
+  }
+
+  fn close(self: Rc<Self>) {
+    self.cancel_handle.cancel();
+  }
+}
+
+/// Starts sending the events of the connections the HTTP handle accepts from now on.
+#[op2(fast)]
+#[smi]
+pub fn op_http_serve_connection_events(
+  state: &mut OpState,
+  #[smi] rid: ResourceId,
+) -> Result<ResourceId, AnyError> {
+  let join_handle = state.resource_table.get::<HttpJoinHandle>(rid)?;
+  let rx = join_handle.server_state.borrow().metrics.subscribe();
+  Ok(state.resource_table.add(HttpConnectionEvents {
+    rx: AsyncRefCell::new(rx),
+    cancel_handle: CancelHandle::new(),
+  }))
+}
+
+#[op2(async)]
+#[serde]
+pub async fn op_http_serve_next_connection_event(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+) -> Result<Option<HttpConnectionEvent>, AnyError> {
+  let events = state
+    .borrow()
+    .resource_table
+    .get::<HttpConnectionEvents>(rid)?;
+  let cancel_handle = RcRef::map(&events, |this| &this.cancel_handle);
+  let mut rx = RcRef::map(&events, |this| &this.rx).borrow_mut().await;
+  Ok(rx.recv().or_cancel(cancel_handle).await.unwrap_or(None))
+}
+
 /// Synchronous, non-blocking call to see if there are any further HTTP requests. If anything
 /// goes wrong in this method we return null and let the async handler pick up the real error.
 #[op2(fast)]
@@ -1147,11 +1625,16 @@ pub fn op_http_cancel(
   Ok(())
 }
 
+/// Closes the HTTP handle. A graceful shutdown stops accepting connections,
+/// sends GOAWAY to HTTP/2 and HTTP/3 clients and closes HTTP/1.1 connections
+/// after their current response. The connections that are still open when the
+/// optional grace period elapses are aborted.
 #[op2(async)]
 pub async fn op_http_close(
   state: Rc<RefCell<OpState>>,
   #[smi] rid: ResourceId,
   graceful: bool,
+  #[serde] grace_period_ms: Option<u64>,
 ) -> Result<(), AnyError> {
   let join_handle = state
     .borrow_mut()
@@ -1162,7 +1645,18 @@ pub async fn op_http_close(
     http_general_trace!("graceful shutdown");
     // In a graceful shutdown, we close the listener and allow all the remaining connections to drain
     join_handle.listen_cancel_handle().cancel();
-    poll_fn(|cx| join_handle.server_state.poll_complete(cx)).await;
+    let drained = poll_fn(|cx| join_handle.server_state.poll_complete(cx));
+    if let Some(grace_period_ms) = grace_period_ms {
+      let grace_period = Duration::from_millis(grace_period_ms);
+      if tokio::time::timeout(grace_period, drained).await.is_err() {
+        http_general_trace!("grace period elapsed, aborting connections");
+        join_handle.connection_cancel_handle().cancel();
+        // Give streaming responses a tick to close
+        tokio::task::yield_now().await;
+      }
+    } else {
+      drained.await;
+    }
   } else {
     http_general_trace!("forceful shutdown");
     // In a forceful shutdown, we close everything
@@ -1185,19 +1679,19 @@ pub async fn op_http_close(
 }
 
 struct UpgradeStream {
-  read: AsyncRefCell<tokio::io::ReadHalf<tokio::io::DuplexStream>>,
-  write: AsyncRefCell<tokio::io::WriteHalf<tokio::io::DuplexStream>>,
+  read: AsyncRefCell<Box<dyn AsyncRead + Unpin>>,
+  write: AsyncRefCell<Box<dyn AsyncWrite + Unpin>>,
   cancel_handle: CancelHandle,
 }
 
 impl UpgradeStream {
   pub fn new(
-    read: tokio::io::ReadHalf<tokio::io::DuplexStream>,
-    write: tokio::io::WriteHalf<tokio::io::DuplexStream>,
+    read: impl AsyncRead + Unpin + 'static,
+    write: impl AsyncWrite + Unpin + 'static,
   ) -> Self {
     Self {
-      read: AsyncRefCell::new(read),
-      write: AsyncRefCell::new(write),
+      read: AsyncRefCell::new(Box::new(read)),
+      write: AsyncRefCell::new(Box::new(write)),
       cancel_handle: CancelHandle::new(),
     }
   }
@@ -1251,6 +1745,12 @@ impl UpgradeStream {
 
     Ok(total)
   }
+
+  async fn shutdown(self: Rc<Self>) -> Result<(), AnyError> {
+    let mut write = RcRef::map(self, |this| &this.write).borrow_mut().await;
+    write.shutdown().await?;
+    Ok(())
+  }
 }
 
 impl Resource for UpgradeStream {
@@ -1261,6 +1761,10 @@ impl Resource for UpgradeStream {
   deno_core::impl_readable_byob!();
   deno_core::impl_writable!();
 
+  fn shutdown(self: Rc<Self>) -> AsyncResult<()> {
+    Box::pin(self.shutdown())
+  }
+
   fn close(self: Rc<Self>) {
     self.cancel_handle.cancel();
   }
@@ -0,0 +1,194 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Informational (1xx) responses, such as `103 Early Hints`, which a handler
+//! can send before the final response of a request.
+
+use crate::h2_frame::encode_field;
+use crate::h2_frame::encode_header_frames;
+use bytes::Bytes;
+use http::HeaderMap;
+use http::StatusCode;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+
+pub(crate) struct InformationalResponse {
+  pub status: StatusCode,
+  pub headers: HeaderMap,
+}
+
+impl InformationalResponse {
+  /// Serializes the response as an HTTP/1.1 status line and headers.
+  pub fn encode_http1(&self) -> Bytes {
+    let reason = match self.status.as_u16() {
+      103 => "Early Hints",
+      _ => self.status.canonical_reason().unwrap_or_default(),
+    };
+    let mut buf =
+      format!("HTTP/1.1 {} {reason}\r\n", self.status.as_str()).into_bytes();
+    for (name, value) in &self.headers {
+      buf.extend_from_slice(name.as_str().as_bytes());
+      buf.extend_from_slice(b": ");
+      buf.extend_from_slice(value.as_bytes());
+      buf.extend_from_slice(b"\r\n");
+    }
+    buf.extend_from_slice(b"\r\n");
+    buf.into()
+  }
+
+  /// Serializes the response as the HTTP/2 frames of a header block for the
+  /// given stream.
+  pub fn encode_http2(&self, stream_id: u32) -> Bytes {
+    let mut block = Vec::new();
+    encode_field(&mut block, b":status", self.status.as_str().as_bytes());
+    for (name, value) in &self.headers {
+      encode_field(&mut block, name.as_str().as_bytes(), value.as_bytes());
+    }
+    let mut buf = Vec::new();
+    encode_header_frames(&mut buf, stream_id, &block, false);
+    buf.into()
+  }
+}
+
+#[derive(Default)]
+struct InformationalQueueInner {
+  /// The queued responses, with the HTTP/2 stream they are sent on.
+  pending: VecDeque<(Option<u32>, InformationalResponse)>,
+  waker: Option<Waker>,
+  /// The HTTP/2 streams opened by the client which haven't been matched with
+  /// their request yet, in the order they were opened.
+  opened_streams: VecDeque<u32>,
+}
+
+/// The informational responses waiting to be written to a connection, which
+/// is inserted into the extensions of the requests that are able to send them.
+///
+/// On an HTTP/2 connection, each request has its own handle on the queue,
+/// which sends its responses on the stream of the request.
+#[derive(Clone, Default)]
+pub(crate) struct InformationalQueue {
+  inner: Arc<Mutex<InformationalQueueInner>>,
+  stream_id: Option<u32>,
+}
+
+impl InformationalQueue {
+  pub fn push(&self, response: InformationalResponse) {
+    let mut inner = self.inner.lock().unwrap();
+    inner.pending.push_back((self.stream_id, response));
+    if let Some(waker) = inner.waker.take() {
+      drop(inner);
+      waker.wake();
+    }
+  }
+
+  pub fn pop(&self) -> Option<(Option<u32>, InformationalResponse)> {
+    self.inner.lock().unwrap().pending.pop_front()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.inner.lock().unwrap().pending.is_empty()
+  }
+
+  /// Takes the next informational response, or registers the waker for when
+  /// there is one.
+  pub fn poll_pop(
+    &self,
+    cx: &mut Context<'_>,
+  ) -> Poll<(Option<u32>, InformationalResponse)> {
+    let mut inner = self.inner.lock().unwrap();
+    match inner.pending.pop_front() {
+      Some(response) => Poll::Ready(response),
+      None => {
+        inner.waker = Some(cx.waker().clone());
+        Poll::Pending
+      }
+    }
+  }
+
+  /// Records an HTTP/2 stream opened by the client.
+  pub fn stream_opened(&self, stream_id: u32) {
+    self
+      .inner
+      .lock()
+      .unwrap()
+      .opened_streams
+      .push_back(stream_id);
+  }
+
+  /// Returns the handle for the next HTTP/2 request, which hyper passes on in
+  /// the order that the client opened their streams, or `None` when the
+  /// streams of the connection aren't known.
+  pub fn for_next_stream(&self) -> Option<Self> {
+    let stream_id = self.inner.lock().unwrap().opened_streams.pop_front()?;
+    Some(Self {
+      inner: self.inner.clone(),
+      stream_id: Some(stream_id),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::h2_frame::FRAME_HEADER_LEN;
+  use http::header::LINK;
+  use http::HeaderValue;
+
+  #[test]
+  fn encode_early_hints() {
+    let mut headers = HeaderMap::new();
+    headers.append(
+      LINK,
+      HeaderValue::from_static("</style.css>; rel=preload; as=style"),
+    );
+    let response = InformationalResponse {
+      status: StatusCode::from_u16(103).unwrap(),
+      headers,
+    };
+    assert_eq!(
+      response.encode_http1(),
+      &b"HTTP/1.1 103 Early Hints\r\nlink: </style.css>; rel=preload; as=style\r\n\r\n"[..]
+    );
+  }
+
+  #[test]
+  fn encode_early_hints_http2() {
+    let mut headers = HeaderMap::new();
+    headers.append(LINK, HeaderValue::from_static("</style.css>"));
+    let response = InformationalResponse {
+      status: StatusCode::from_u16(103).unwrap(),
+      headers,
+    };
+    let mut block = Vec::new();
+    encode_field(&mut block, b":status", b"103");
+    encode_field(&mut block, b"link", b"</style.css>");
+    let frame = response.encode_http2(3);
+    assert_eq!(
+      &frame[..FRAME_HEADER_LEN],
+      &[0, 0, block.len() as u8, 0x1, 0x4, 0, 0, 0, 3]
+    );
+    assert_eq!(&frame[FRAME_HEADER_LEN..], block);
+  }
+
+  #[test]
+  fn queue_per_stream() {
+    let queue = InformationalQueue::default();
+    assert!(queue.for_next_stream().is_none());
+    queue.stream_opened(1);
+    queue.stream_opened(3);
+    let first = queue.for_next_stream().unwrap();
+    let second = queue.for_next_stream().unwrap();
+    let response = || InformationalResponse {
+      status: StatusCode::from_u16(103).unwrap(),
+      headers: HeaderMap::new(),
+    };
+    second.push(response());
+    first.push(response());
+    assert_eq!(queue.pop().unwrap().0, Some(3));
+    assert_eq!(queue.pop().unwrap().0, Some(1));
+    assert!(queue.pop().is_none());
+  }
+}
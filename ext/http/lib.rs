@@ -76,17 +76,26 @@ use tokio::io::AsyncWriteExt;
 use crate::network_buffered_stream::NetworkBufferedStream;
 use crate::reader_stream::ExternallyAbortableReaderStream;
 use crate::reader_stream::ShutdownHandle;
+use crate::serve_stream::ServeStream;
 
 pub mod compressible;
 mod fly_accept_encoding;
+mod h2_frame;
+mod h2c;
+mod http3;
 mod http_next;
+mod informational;
+mod metrics;
 mod network_buffered_stream;
 mod reader_stream;
 mod request_body;
 mod request_properties;
 mod response_body;
+mod serve_stream;
 mod service;
+mod sse;
 mod websocket_upgrade;
+mod write_timeout_stream;
 
 use fly_accept_encoding::Encoding;
 pub use request_properties::DefaultHttpPropertyExtractor;
@@ -112,6 +121,7 @@ deno_core::extension!(
     http_next::op_http_get_request_header,
     http_next::op_http_get_request_headers,
     http_next::op_http_get_request_method_and_url<HTTP>,
+    http_next::op_http_get_request_trailers,
     http_next::op_http_read_request_body,
     http_next::op_http_serve_on<HTTP>,
     http_next::op_http_serve<HTTP>,
@@ -123,6 +133,7 @@ deno_core::extension!(
     http_next::op_http_set_response_headers,
     http_next::op_http_set_response_trailers,
     http_next::op_http_upgrade_websocket_next,
+    http_next::op_http_upgrade_connection_next,
     http_next::op_http_upgrade_raw,
     http_next::op_raw_write_vectored,
     http_next::op_can_write_vectored,
@@ -130,10 +141,45 @@ deno_core::extension!(
     http_next::op_http_wait,
     http_next::op_http_close,
     http_next::op_http_cancel,
+    http3::op_http_serve_h3,
+    http3::op_http_set_h3_certificate,
+    http_next::op_http_send_informational,
+    http_next::op_http_set_serve_compression,
+    http_next::op_http_set_serve_h2c_upgrade,
+    http_next::op_http_serve_metrics_handle,
+    http_next::op_http_serve_metrics,
+    http_next::op_http_serve_connection_events,
+    http_next::op_http_serve_next_connection_event,
+    http_next::op_http_set_serve_timeouts,
+    op_http_serve_wait_for_shutdown,
+    sse::op_http_sse_create,
+    sse::op_http_sse_end,
+    sse::op_http_sse_finished,
+    sse::op_http_sse_send,
   ],
-  esm = ["00_serve.ts", "01_http.js", "02_websocket.ts"],
+  esm = ["00_serve.ts", "01_http.js", "02_websocket.ts", "03_sse.ts"],
 );
 
+/// Put into the `OpState` of a worker running a declarative server with
+/// `deno serve` to shut the server down gracefully, letting the requests in
+/// flight finish, once the value of the channel becomes `true`.
+#[derive(Clone)]
+pub struct DeclarativeServerShutdown(pub tokio::sync::watch::Receiver<bool>);
+
+#[op2(async)]
+async fn op_http_serve_wait_for_shutdown(state: Rc<RefCell<OpState>>) {
+  let maybe_shutdown = state
+    .borrow()
+    .try_borrow::<DeclarativeServerShutdown>()
+    .cloned();
+  if let Some(DeclarativeServerShutdown(mut receiver)) = maybe_shutdown {
+    if receiver.wait_for(|shutdown| *shutdown).await.is_ok() {
+      return;
+    }
+  }
+  pending::<()>().await
+}
+
 pub enum HttpSocketAddr {
   IpSocket(std::net::SocketAddr),
   #[cfg(unix)]
@@ -1037,6 +1083,7 @@ fn op_http_websocket_accept_header(
 async fn op_http_upgrade_websocket(
   state: Rc<RefCell<OpState>>,
   #[smi] rid: ResourceId,
+  #[string] extensions: Option<String>,
 ) -> Result<ResourceId, AnyError> {
   let stream = state
     .borrow_mut()
@@ -1053,8 +1100,12 @@ async fn op_http_upgrade_websocket(
 
   let (transport, bytes) =
     extract_network_stream(hyper_v014::upgrade::on(request).await?);
-  let ws_rid =
-    ws_create_server_stream(&mut state.borrow_mut(), transport, bytes)?;
+  let ws_rid = ws_create_server_stream(
+    &mut state.borrow_mut(),
+    transport,
+    bytes,
+    extensions.as_deref(),
+  )?;
   Ok(ws_rid)
 }
 
@@ -1145,27 +1196,47 @@ fn maybe_extract_network_stream<
     Err(x) => x,
   };
 
-  match upgraded.downcast::<NetworkBufferedStream<T>>() {
+  let upgraded = match upgraded.downcast::<NetworkBufferedStream<T>>() {
     Ok((stream, upgraded_bytes)) => {
-      // Both the upgrade and the stream might have unread bytes
       let (io, stream_bytes) = stream.into_inner();
-      let bytes = match (stream_bytes.is_empty(), upgraded_bytes.is_empty()) {
-        (false, false) => Bytes::default(),
-        (true, false) => upgraded_bytes,
-        (false, true) => stream_bytes,
-        (true, true) => {
-          // The upgraded bytes come first as they have already been read
-          let mut v = upgraded_bytes.to_vec();
-          v.append(&mut stream_bytes.to_vec());
-          Bytes::from(v)
-        }
-      };
-      Ok((io.into(), bytes))
+      return Ok((io.into(), merge_unread_bytes(upgraded_bytes, stream_bytes)));
+    }
+    Err(x) => x,
+  };
+
+  // Connections served by `op_http_serve`
+  let upgraded = match upgraded.downcast::<ServeStream<T>>() {
+    Ok((stream, bytes)) => return Ok((stream.into_inner().into(), bytes)),
+    Err(x) => x,
+  };
+
+  match upgraded.downcast::<NetworkBufferedStream<ServeStream<T>>>() {
+    Ok((stream, upgraded_bytes)) => {
+      let (io, stream_bytes) = stream.into_inner();
+      Ok((
+        io.into_inner().into(),
+        merge_unread_bytes(upgraded_bytes, stream_bytes),
+      ))
     }
     Err(x) => Err(x),
   }
 }
 
+/// Both the upgrade and the stream might have unread bytes.
+fn merge_unread_bytes(upgraded_bytes: Bytes, stream_bytes: Bytes) -> Bytes {
+  match (stream_bytes.is_empty(), upgraded_bytes.is_empty()) {
+    (false, false) => Bytes::default(),
+    (true, false) => upgraded_bytes,
+    (false, true) => stream_bytes,
+    (true, true) => {
+      // The upgraded bytes come first as they have already been read
+      let mut v = upgraded_bytes.to_vec();
+      v.append(&mut stream_bytes.to_vec());
+      Bytes::from(v)
+    }
+  }
+}
+
 fn extract_network_stream<U: CanDowncastUpgrade>(
   upgraded: U,
 ) -> (NetworkStream, Bytes) {
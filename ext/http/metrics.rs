@@ -0,0 +1,226 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Counters describing the activity of a server, and the connection events
+//! behind the `onConnect` and `onClose` callbacks of `Deno.serve`.
+
+use crate::request_properties::HttpConnectionProperties;
+use serde::Serialize;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use tokio::sync::mpsc;
+
+/// The metrics of a server. They're shared with the streams of its
+/// connections, which may be sent to other threads by hyper.
+#[derive(Default)]
+pub(crate) struct HttpServerMetrics {
+  active_connections: AtomicU64,
+  total_connections: AtomicU64,
+  in_flight_requests: AtomicU64,
+  total_requests: AtomicU64,
+  bytes_read: AtomicU64,
+  bytes_written: AtomicU64,
+  handshake_errors: AtomicU64,
+  next_connection_id: AtomicU64,
+  events: Mutex<Option<mpsc::UnboundedSender<HttpConnectionEvent>>>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpServerMetricsSnapshot {
+  active_connections: u64,
+  total_connections: u64,
+  in_flight_requests: u64,
+  total_requests: u64,
+  bytes_read: u64,
+  bytes_written: u64,
+  handshake_errors: u64,
+}
+
+/// A connection being opened or closed. The events of a connection share its
+/// `id`, and the address is only sent when it is opened.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpConnectionEvent {
+  id: u64,
+  open: bool,
+  hostname: Option<String>,
+  port: Option<u16>,
+}
+
+impl HttpServerMetrics {
+  pub fn snapshot(&self) -> HttpServerMetricsSnapshot {
+    HttpServerMetricsSnapshot {
+      active_connections: self.active_connections.load(Ordering::Relaxed),
+      total_connections: self.total_connections.load(Ordering::Relaxed),
+      in_flight_requests: self.in_flight_requests.load(Ordering::Relaxed),
+      total_requests: self.total_requests.load(Ordering::Relaxed),
+      bytes_read: self.bytes_read.load(Ordering::Relaxed),
+      bytes_written: self.bytes_written.load(Ordering::Relaxed),
+      handshake_errors: self.handshake_errors.load(Ordering::Relaxed),
+    }
+  }
+
+  /// Counts a connection which failed before it could serve requests, such as
+  /// during the TLS handshake.
+  pub fn handshake_failed(&self) {
+    self.handshake_errors.fetch_add(1, Ordering::Relaxed);
+  }
+
+  /// Counts a connection as active until the returned [`ConnectionMetrics`]
+  /// is dropped.
+  pub fn open_connection(
+    self: &Arc<Self>,
+    properties: &HttpConnectionProperties,
+  ) -> ConnectionMetrics {
+    self.active_connections.fetch_add(1, Ordering::Relaxed);
+    self.total_connections.fetch_add(1, Ordering::Relaxed);
+    let id = self.next_connection_id.fetch_add(1, Ordering::Relaxed);
+    self.send_event(HttpConnectionEvent {
+      id,
+      open: true,
+      hostname: Some(properties.peer_address.to_string()),
+      port: properties.peer_port,
+    });
+    ConnectionMetrics {
+      metrics: self.clone(),
+      id,
+    }
+  }
+
+  /// Counts a request as in flight until the returned [`RequestMetrics`] is
+  /// dropped.
+  pub fn start_request(self: &Arc<Self>) -> RequestMetrics {
+    self.in_flight_requests.fetch_add(1, Ordering::Relaxed);
+    self.total_requests.fetch_add(1, Ordering::Relaxed);
+    RequestMetrics(self.clone())
+  }
+
+  /// Starts sending the events of the connections opened from now on.
+  pub fn subscribe(&self) -> mpsc::UnboundedReceiver<HttpConnectionEvent> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    *self.events.lock().unwrap() = Some(tx);
+    rx
+  }
+
+  /// Stops sending connection events, which ends the subscription once the
+  /// pending events are received.
+  pub fn unsubscribe(&self) {
+    self.events.lock().unwrap().take();
+  }
+
+  fn send_event(&self, event: HttpConnectionEvent) {
+    if let Some(tx) = &*self.events.lock().unwrap() {
+      _ = tx.send(event);
+    }
+  }
+}
+
+/// Tracks an open connection in the [`HttpServerMetrics`].
+pub(crate) struct ConnectionMetrics {
+  metrics: Arc<HttpServerMetrics>,
+  id: u64,
+}
+
+impl ConnectionMetrics {
+  pub fn read(&self, n: usize) {
+    self
+      .metrics
+      .bytes_read
+      .fetch_add(n as u64, Ordering::Relaxed);
+  }
+
+  pub fn written(&self, n: usize) {
+    self
+      .metrics
+      .bytes_written
+      .fetch_add(n as u64, Ordering::Relaxed);
+  }
+}
+
+impl Drop for ConnectionMetrics {
+  fn drop(&mut self) {
+    self
+      .metrics
+      .active_connections
+      .fetch_sub(1, Ordering::Relaxed);
+    self.metrics.send_event(HttpConnectionEvent {
+      id: self.id,
+      open: false,
+      hostname: None,
+      port: None,
+    });
+  }
+}
+
+/// Tracks an in-flight request in the [`HttpServerMetrics`].
+pub(crate) struct RequestMetrics(Arc<HttpServerMetrics>);
+
+impl Drop for RequestMetrics {
+  fn drop(&mut self) {
+    self.0.in_flight_requests.fetch_sub(1, Ordering::Relaxed);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use deno_net::raw::NetworkStreamType;
+
+  #[test]
+  fn test_metrics() {
+    let metrics = Arc::new(HttpServerMetrics::default());
+    let mut events = metrics.subscribe();
+    let properties = HttpConnectionProperties {
+      peer_address: "127.0.0.1".into(),
+      peer_port: Some(1234),
+      local_port: Some(8000),
+      stream_type: NetworkStreamType::Tcp,
+    };
+    let connection = metrics.open_connection(&properties);
+    connection.read(10);
+    connection.written(20);
+    let request = metrics.start_request();
+    metrics.handshake_failed();
+    assert_eq!(
+      metrics.snapshot(),
+      HttpServerMetricsSnapshot {
+        active_connections: 1,
+        total_connections: 1,
+        in_flight_requests: 1,
+        total_requests: 1,
+        bytes_read: 10,
+        bytes_written: 20,
+        handshake_errors: 1,
+      }
+    );
+    drop(request);
+    drop(connection);
+    let snapshot = metrics.snapshot();
+    assert_eq!(snapshot.active_connections, 0);
+    assert_eq!(snapshot.in_flight_requests, 0);
+    assert_eq!(snapshot.total_connections, 1);
+
+    metrics.unsubscribe();
+    assert_eq!(
+      events.try_recv().unwrap(),
+      HttpConnectionEvent {
+        id: 0,
+        open: true,
+        hostname: Some("127.0.0.1".to_string()),
+        port: Some(1234),
+      }
+    );
+    assert_eq!(
+      events.try_recv().unwrap(),
+      HttpConnectionEvent {
+        id: 0,
+        open: false,
+        hostname: None,
+        port: None,
+      }
+    );
+    assert!(events.try_recv().is_err());
+  }
+}
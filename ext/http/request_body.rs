@@ -1,4 +1,5 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+use crate::http3::Http3RequestBody;
 use bytes::Bytes;
 use deno_core::error::AnyError;
 use deno_core::futures::stream::Peekable;
@@ -9,17 +10,63 @@ use deno_core::AsyncResult;
 use deno_core::BufView;
 use deno_core::RcRef;
 use deno_core::Resource;
+use http::HeaderMap;
 use hyper::body::Body;
+use hyper::body::Frame;
 use hyper::body::Incoming;
 use hyper::body::SizeHint;
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::pin::Pin;
 use std::rc::Rc;
 use std::task::ready;
 use std::task::Poll;
+use std::time::Duration;
 
-/// Converts a hyper incoming body stream into a stream of [`Bytes`] that we can use to read in V8.
-struct ReadFuture(Incoming);
+/// The body of a request received over HTTP/1.1, HTTP/2 or HTTP/3.
+pub enum IncomingBody {
+  Hyper(Incoming),
+  Http3(Http3RequestBody),
+}
+
+impl Body for IncomingBody {
+  type Data = Bytes;
+  type Error = AnyError;
+
+  fn poll_frame(
+    self: Pin<&mut Self>,
+    cx: &mut std::task::Context<'_>,
+  ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+    match self.get_mut() {
+      IncomingBody::Hyper(body) => {
+        Pin::new(body).poll_frame(cx).map_err(AnyError::from)
+      }
+      IncomingBody::Http3(body) => Pin::new(body).poll_frame(cx),
+    }
+  }
+
+  fn is_end_stream(&self) -> bool {
+    match self {
+      IncomingBody::Hyper(body) => body.is_end_stream(),
+      IncomingBody::Http3(body) => body.is_end_stream(),
+    }
+  }
+
+  fn size_hint(&self) -> SizeHint {
+    match self {
+      IncomingBody::Hyper(body) => body.size_hint(),
+      IncomingBody::Http3(body) => body.size_hint(),
+    }
+  }
+}
+
+/// The trailers of a request, which are received once its body has been read
+/// to the end.
+pub type RequestTrailers = Rc<RefCell<Option<HeaderMap>>>;
+
+/// Converts an incoming body stream into a stream of [`Bytes`] that we can use
+/// to read in V8, keeping the trailers of the body aside.
+struct ReadFuture(IncomingBody, RequestTrailers);
 
 impl Stream for ReadFuture {
   type Item = Result<Bytes, AnyError>;
@@ -34,34 +81,68 @@ impl Stream for ReadFuture {
       let res = ready!(Pin::new(&mut this.0).poll_frame(cx));
       break match res {
         Some(Ok(frame)) => {
-          if let Ok(data) = frame.into_data() {
+          match frame.into_data() {
             // Ensure that we never yield an empty frame
-            if !data.is_empty() {
+            Ok(data) if !data.is_empty() => {
               break Poll::Ready(Some(Ok::<_, AnyError>(data)));
             }
+            Ok(_) => {}
+            Err(frame) => {
+              if let Ok(trailers) = frame.into_trailers() {
+                *this.1.borrow_mut() = Some(trailers);
+              }
+            }
           }
           // Loop again so we don't lose the waker
           continue;
         }
-        Some(Err(e)) => Poll::Ready(Some(Err(e.into()))),
+        Some(Err(e)) => Poll::Ready(Some(Err(e))),
         None => Poll::Ready(None),
       };
     }
   }
 }
 
-pub struct HttpRequestBody(AsyncRefCell<Peekable<ReadFuture>>, SizeHint);
+pub struct HttpRequestBody(
+  AsyncRefCell<Peekable<ReadFuture>>,
+  SizeHint,
+  Option<Duration>,
+);
 
 impl HttpRequestBody {
-  pub fn new(body: Incoming) -> Self {
+  /// Creates the body resource. When `idle_timeout` is set, a read fails once
+  /// the client hasn't sent any data for that long. The trailers of the body
+  /// are stored into `trailers`.
+  pub fn new(
+    body: IncomingBody,
+    idle_timeout: Option<Duration>,
+    trailers: RequestTrailers,
+  ) -> Self {
     let size_hint = body.size_hint();
-    Self(AsyncRefCell::new(ReadFuture(body).peekable()), size_hint)
+    Self(
+      AsyncRefCell::new(ReadFuture(body, trailers).peekable()),
+      size_hint,
+      idle_timeout,
+    )
   }
 
   async fn read(self: Rc<Self>, limit: usize) -> Result<BufView, AnyError> {
+    let idle_timeout = self.2;
     let peekable = RcRef::map(self, |this| &this.0);
     let mut peekable = peekable.borrow_mut().await;
-    match Pin::new(&mut *peekable).peek_mut().await {
+    let peeked = Pin::new(&mut *peekable).peek_mut();
+    let peeked = match idle_timeout {
+      Some(idle_timeout) => tokio::time::timeout(idle_timeout, peeked)
+        .await
+        .map_err(|_| {
+          std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            "Request body read timed out",
+          )
+        })?,
+      None => peeked.await,
+    };
+    match peeked {
       None => Ok(BufView::empty()),
       Some(Err(_)) => Err(peekable.next().await.unwrap().err().unwrap()),
       Some(Ok(bytes)) => {
@@ -56,11 +56,13 @@ pub trait PollFrame: Unpin {
   fn size_hint(&self) -> SizeHint;
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Compression {
   None,
-  GZip,
-  Brotli,
+  /// GZip with the given compression level.
+  GZip(u32),
+  /// Brotli with the given quality.
+  Brotli(u32),
 }
 
 pub enum ResponseStream {
@@ -133,11 +135,11 @@ impl ResponseBytesInner {
 
   fn from_stream(compression: Compression, stream: ResponseStream) -> Self {
     match compression {
-      Compression::GZip => {
-        Self::GZipStream(Box::new(GZipResponseStream::new(stream)))
+      Compression::GZip(level) => {
+        Self::GZipStream(Box::new(GZipResponseStream::new(stream, level)))
       }
-      Compression::Brotli => {
-        Self::BrotliStream(Box::new(BrotliResponseStream::new(stream)))
+      Compression::Brotli(quality) => {
+        Self::BrotliStream(Box::new(BrotliResponseStream::new(stream, quality)))
       }
       _ => Self::UncompressedStream(stream),
     }
@@ -156,20 +158,17 @@ impl ResponseBytesInner {
 
   pub fn from_bufview(compression: Compression, buf: BufView) -> Self {
     match compression {
-      Compression::GZip => {
+      Compression::GZip(level) => {
         let mut writer =
-          GzEncoder::new(Vec::new(), flate2::Compression::fast());
+          GzEncoder::new(Vec::new(), flate2::Compression::new(level));
         writer.write_all(&buf).unwrap();
         Self::Bytes(BufView::from(writer.finish().unwrap()))
       }
-      Compression::Brotli => {
-        // quality level 6 is based on google's nginx default value for
-        // on-the-fly compression
-        // https://github.com/google/ngx_brotli#brotli_comp_level
+      Compression::Brotli(quality) => {
         // lgwin 22 is equivalent to brotli window size of (2**22)-16 bytes
         // (~4MB)
         let mut writer =
-          brotli::CompressorWriter::new(Vec::new(), 65 * 1024, 6, 22);
+          brotli::CompressorWriter::new(Vec::new(), 65 * 1024, quality, 22);
         writer.write_all(&buf).unwrap();
         writer.flush().unwrap();
         Self::Bytes(BufView::from(writer.into_inner()))
@@ -180,15 +179,15 @@ impl ResponseBytesInner {
 
   pub fn from_vec(compression: Compression, vec: Vec<u8>) -> Self {
     match compression {
-      Compression::GZip => {
+      Compression::GZip(level) => {
         let mut writer =
-          GzEncoder::new(Vec::new(), flate2::Compression::fast());
+          GzEncoder::new(Vec::new(), flate2::Compression::new(level));
         writer.write_all(&vec).unwrap();
         Self::Bytes(BufView::from(writer.finish().unwrap()))
       }
-      Compression::Brotli => {
+      Compression::Brotli(quality) => {
         let mut writer =
-          brotli::CompressorWriter::new(Vec::new(), 65 * 1024, 6, 22);
+          brotli::CompressorWriter::new(Vec::new(), 65 * 1024, quality, 22);
         writer.write_all(&vec).unwrap();
         writer.flush().unwrap();
         Self::Bytes(BufView::from(writer.into_inner()))
@@ -207,19 +206,36 @@ pub struct ResourceBodyAdapter {
   auto_close: bool,
   stm: Rc<dyn Resource>,
   future: AsyncResult<BufView>,
+  /// The number of bytes left to send, for resources of a known length such
+  /// as files. Their responses are sent with a `Content-Length` rather than
+  /// chunked, and end there even if the resource grew in the meantime.
+  remaining: Option<u64>,
 }
 
+const RESOURCE_READ_SIZE: u64 = 64 * 1024;
+
 impl ResourceBodyAdapter {
   pub fn new(stm: Rc<dyn Resource>, auto_close: bool) -> Self {
-    let future = stm.clone().read(64 * 1024);
+    let remaining = match stm.size_hint() {
+      (lower, Some(upper)) if lower == upper => Some(upper),
+      _ => None,
+    };
+    let future = stm.clone().read(read_size(remaining));
     ResourceBodyAdapter {
       auto_close,
       stm,
       future,
+      remaining,
     }
   }
 }
 
+fn read_size(remaining: Option<u64>) -> usize {
+  remaining.map_or(RESOURCE_READ_SIZE, |remaining| {
+    remaining.min(RESOURCE_READ_SIZE)
+  }) as usize
+}
+
 impl PollFrame for ResponseStream {
   fn poll_frame(
     mut self: Pin<&mut Self>,
@@ -248,15 +264,24 @@ impl PollFrame for ResourceBodyAdapter {
   ) -> std::task::Poll<ResponseStreamResult> {
     let res = match ready!(self.future.poll_unpin(cx)) {
       Err(err) => ResponseStreamResult::Error(err),
-      Ok(buf) => {
+      Ok(mut buf) => {
         if buf.is_empty() {
           if self.auto_close {
             self.stm.clone().close();
           }
           ResponseStreamResult::EndOfStream
         } else {
-          // Re-arm the future
-          self.future = self.stm.clone().read(64 * 1024);
+          if let Some(remaining) = &mut self.remaining {
+            buf.truncate((*remaining).min(buf.len() as u64) as usize);
+            *remaining -= buf.len() as u64;
+          }
+          // Re-arm the future, or end the stream once the known length was
+          // sent.
+          self.future = if self.remaining == Some(0) {
+            Box::pin(std::future::ready(Ok(BufView::empty())))
+          } else {
+            self.stm.clone().read(read_size(self.remaining))
+          };
           ResponseStreamResult::NonEmptyBuf(buf)
         }
       }
@@ -265,6 +290,9 @@ impl PollFrame for ResourceBodyAdapter {
   }
 
   fn size_hint(&self) -> SizeHint {
+    if let Some(remaining) = self.remaining {
+      return SizeHint::with_exact(remaining);
+    }
     let hint = self.stm.size_hint();
     let mut size_hint = SizeHint::new();
     size_hint.set_lower(hint.0);
@@ -314,9 +342,9 @@ pub struct GZipResponseStream {
 }
 
 impl GZipResponseStream {
-  pub fn new(underlying: ResponseStream) -> Self {
+  pub fn new(underlying: ResponseStream, level: u32) -> Self {
     Self {
-      stm: flate2::Compress::new(flate2::Compression::fast(), false),
+      stm: flate2::Compress::new(flate2::Compression::new(level), false),
       crc: flate2::Crc::new(),
       next_buf: None,
       partial: None,
@@ -461,12 +489,10 @@ pub struct BrotliResponseStream {
 }
 
 impl BrotliResponseStream {
-  pub fn new(underlying: ResponseStream) -> Self {
+  pub fn new(underlying: ResponseStream, quality: u32) -> Self {
     let mut stm = BrotliEncoderStateStruct::new(StandardAlloc::default());
-    // Quality level 6 is based on google's nginx default value for on-the-fly compression
-    // https://github.com/google/ngx_brotli#brotli_comp_level
     // lgwin 22 is equivalent to brotli window size of (2**22)-16 bytes (~4MB)
-    stm.set_parameter(BrotliEncoderParameter::BROTLI_PARAM_QUALITY, 6);
+    stm.set_parameter(BrotliEncoderParameter::BROTLI_PARAM_QUALITY, quality);
     stm.set_parameter(BrotliEncoderParameter::BROTLI_PARAM_LGWIN, 22);
     Self {
       stm,
@@ -657,7 +683,7 @@ mod tests {
     }
     let (tx, rx) = tokio::sync::mpsc::channel(1);
     let underlying = ResponseStream::TestChannel(rx);
-    let mut resp = GZipResponseStream::new(underlying);
+    let mut resp = GZipResponseStream::new(underlying, 1);
     let handle = tokio::task::spawn(async move {
       for chunk in v {
         tx.send(chunk.into()).await.ok().unwrap();
@@ -699,7 +725,7 @@ mod tests {
     }
     let (tx, rx) = tokio::sync::mpsc::channel(1);
     let underlying = ResponseStream::TestChannel(rx);
-    let mut resp = BrotliResponseStream::new(underlying);
+    let mut resp = BrotliResponseStream::new(underlying, 6);
     let handle = tokio::task::spawn(async move {
       for chunk in v {
         tx.send(chunk.into()).await.ok().unwrap();
@@ -747,6 +773,40 @@ mod tests {
     test_gzip(vec![].into_iter()).await;
   }
 
+  struct SizedResource(std::cell::RefCell<Vec<Vec<u8>>>, u64);
+
+  impl Resource for SizedResource {
+    fn read(self: Rc<Self>, limit: usize) -> AsyncResult<BufView> {
+      let mut chunk = self.0.borrow_mut().pop().unwrap_or_default();
+      chunk.truncate(limit);
+      Box::pin(std::future::ready(Ok(BufView::from(chunk))))
+    }
+
+    fn size_hint(&self) -> (u64, Option<u64>) {
+      (self.1, Some(self.1))
+    }
+  }
+
+  #[tokio::test]
+  async fn test_resource_of_known_length() {
+    // The resource grew past the length it reported
+    let chunks = vec![b"baz".to_vec(), b"bar".to_vec(), b"foo".to_vec()];
+    let stm = Rc::new(SizedResource(chunks.into(), 5));
+    let mut adapter = ResourceBodyAdapter::new(stm, false);
+    assert_eq!(adapter.size_hint().exact(), Some(5));
+
+    let mut body = vec![];
+    loop {
+      match poll_fn(|cx| Pin::new(&mut adapter).poll_frame(cx)).await {
+        ResponseStreamResult::NonEmptyBuf(buf) => body.extend_from_slice(&buf),
+        ResponseStreamResult::EndOfStream => break,
+        _ => unreachable!(),
+      }
+      assert_eq!(adapter.size_hint().exact(), Some(5 - body.len() as u64));
+    }
+    assert_eq!(body, b"fooba");
+  }
+
   #[tokio::test]
   async fn test_simple_zeros() {
     test_brotli(vec![vec![0; 0x10000]].into_iter()).await;
@@ -0,0 +1,486 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use crate::h2_frame::FrameBoundaries;
+use crate::h2_frame::FRAME_TYPE_HEADERS;
+use crate::http_next::HTTP2_PREFIX;
+use crate::informational::InformationalQueue;
+use crate::informational::InformationalResponse;
+use crate::metrics::ConnectionMetrics;
+use crate::write_timeout_stream::WriteTimeoutStream;
+use bytes::Buf;
+use bytes::Bytes;
+use std::io;
+use std::pin::Pin;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::io::ReadBuf;
+
+/// The protocol of a connection, as told by the first bytes of the client.
+enum Protocol {
+  Unknown {
+    preface_read: usize,
+  },
+  Http1,
+  Http2 {
+    read_frames: FrameBoundaries,
+    last_stream_id: u32,
+  },
+}
+
+/// [`ServeStream`] wraps the stream of a served connection in a
+/// [`WriteTimeoutStream`], and:
+///
+///  - Writes the informational responses queued to the [`InformationalQueue`],
+///    as hyper has no API to send them. On HTTP/1.1, they go in between the
+///    messages of the server: once everything hyper has written is flushed,
+///    its write buffer is empty, so the connection is at a message boundary.
+///    On HTTP/2, they go in between the frames written by hyper, as `HEADERS`
+///    frames on the stream of their request. Streams are matched with their
+///    requests by following the frames of the client, as hyper passes the
+///    requests on in the order that their streams were opened.
+///  - Counts the bytes read and written in the server metrics, where the
+///    connection is active until the stream is dropped.
+pub struct ServeStream<S> {
+  io: WriteTimeoutStream<S>,
+  informational: InformationalQueue,
+  protocol: Protocol,
+  /// The frames written by hyper, unless the connection is HTTP/1.1.
+  write_frames: FrameBoundaries,
+  /// Whether all of the bytes written by hyper have been flushed.
+  flushed: bool,
+  /// The remainder of an informational response being written.
+  writing_informational: Option<Bytes>,
+  metrics: ConnectionMetrics,
+}
+
+impl<S> ServeStream<S> {
+  pub fn new(
+    io: S,
+    timeout: Option<Duration>,
+    informational: InformationalQueue,
+    metrics: ConnectionMetrics,
+  ) -> Self {
+    Self {
+      io: WriteTimeoutStream::new(io, timeout),
+      informational,
+      protocol: Protocol::Unknown { preface_read: 0 },
+      write_frames: FrameBoundaries::default(),
+      flushed: true,
+      writing_informational: None,
+      metrics,
+    }
+  }
+
+  pub fn into_inner(self) -> S {
+    self.io.into_inner()
+  }
+
+  /// Follows the bytes read from the client, to tell the protocol of the
+  /// connection and the HTTP/2 streams that it opens.
+  fn track_read(&mut self, mut bytes: &[u8]) {
+    if let Protocol::Unknown { preface_read } = &mut self.protocol {
+      let n = (HTTP2_PREFIX.len() - *preface_read).min(bytes.len());
+      if bytes[..n] != HTTP2_PREFIX[*preface_read..*preface_read + n] {
+        self.protocol = Protocol::Http1;
+        return;
+      }
+      *preface_read += n;
+      bytes = &bytes[n..];
+      if *preface_read < HTTP2_PREFIX.len() {
+        return;
+      }
+      self.protocol = Protocol::Http2 {
+        read_frames: FrameBoundaries::default(),
+        last_stream_id: 0,
+      };
+    }
+    if let Protocol::Http2 {
+      read_frames,
+      last_stream_id,
+    } = &mut self.protocol
+    {
+      let informational = &self.informational;
+      read_frames.advance(bytes, |frame| {
+        // A HEADERS frame on an existing stream carries trailers
+        if frame.kind == FRAME_TYPE_HEADERS && frame.stream_id > *last_stream_id
+        {
+          *last_stream_id = frame.stream_id;
+          informational.stream_opened(frame.stream_id);
+        }
+      });
+    }
+  }
+
+  fn written(&mut self, bytes: &[u8]) {
+    if bytes.is_empty() {
+      return;
+    }
+    self.flushed = false;
+    self.metrics.written(bytes.len());
+    if !matches!(self.protocol, Protocol::Http1) {
+      self.write_frames.advance(bytes, |_| {});
+    }
+  }
+
+  /// Whether an informational response can be written in between the bytes
+  /// written by hyper.
+  fn at_message_boundary(&self) -> bool {
+    match self.protocol {
+      Protocol::Unknown { .. } => false,
+      Protocol::Http1 => self.flushed,
+      Protocol::Http2 { .. } => self.write_frames.at_boundary(),
+    }
+  }
+
+  /// The number of bytes of hyper that can be written before the queued
+  /// informational responses.
+  fn writable_len(&self, len: usize) -> usize {
+    match self.protocol {
+      Protocol::Http2 { .. }
+        if !self.write_frames.at_boundary()
+          && !self.informational.is_empty() =>
+      {
+        len.min(self.write_frames.next_boundary())
+      }
+      _ => len,
+    }
+  }
+
+  fn encode_informational(
+    &self,
+    stream_id: Option<u32>,
+    response: &InformationalResponse,
+  ) -> Option<Bytes> {
+    match (&self.protocol, stream_id) {
+      (Protocol::Http1, None) => Some(response.encode_http1()),
+      (Protocol::Http2 { .. }, Some(stream_id)) => {
+        Some(response.encode_http2(stream_id))
+      }
+      _ => None,
+    }
+  }
+
+  /// Writes the queued informational responses while the connection is at a
+  /// message boundary.
+  fn poll_informational(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>>
+  where
+    S: AsyncWrite + Unpin,
+  {
+    loop {
+      if let Some(mut buf) = self.writing_informational.take() {
+        let n = match Pin::new(&mut self.io).poll_write(cx, &buf) {
+          Poll::Pending => {
+            self.writing_informational = Some(buf);
+            return Poll::Pending;
+          }
+          Poll::Ready(res) => res?,
+        };
+        if n == 0 {
+          return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+        }
+        self.metrics.written(n);
+        buf.advance(n);
+        if !buf.is_empty() {
+          self.writing_informational = Some(buf);
+        }
+        continue;
+      }
+      if !self.at_message_boundary() {
+        return Poll::Ready(Ok(()));
+      }
+      match self.informational.poll_pop(cx) {
+        Poll::Ready((stream_id, response)) => {
+          self.writing_informational =
+            self.encode_informational(stream_id, &response)
+        }
+        Poll::Pending => return Poll::Ready(Ok(())),
+      }
+    }
+  }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for ServeStream<S> {
+  fn poll_read(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &mut ReadBuf<'_>,
+  ) -> Poll<io::Result<()>> {
+    let filled = buf.filled().len();
+    let res = Pin::new(&mut self.io).poll_read(cx, buf);
+    let read = &buf.filled()[filled..];
+    self.metrics.read(read.len());
+    self.track_read(read);
+    res
+  }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for ServeStream<S> {
+  fn poll_write(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &[u8],
+  ) -> Poll<io::Result<usize>> {
+    ready!(self.poll_informational(cx))?;
+    let buf = &buf[..self.writable_len(buf.len())];
+    let n = ready!(Pin::new(&mut self.io).poll_write(cx, buf))?;
+    self.written(&buf[..n]);
+    Poll::Ready(Ok(n))
+  }
+
+  fn poll_flush(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<io::Result<()>> {
+    loop {
+      ready!(self.poll_informational(cx))?;
+      ready!(Pin::new(&mut self.io).poll_flush(cx))?;
+      if self.flushed {
+        return Poll::Ready(Ok(()));
+      }
+      // Now that everything hyper wrote is flushed, the informational
+      // responses can be written
+      self.flushed = true;
+    }
+  }
+
+  fn poll_shutdown(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<io::Result<()>> {
+    Pin::new(&mut self.io).poll_shutdown(cx)
+  }
+
+  fn is_write_vectored(&self) -> bool {
+    self.io.is_write_vectored()
+  }
+
+  fn poll_write_vectored(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    bufs: &[io::IoSlice<'_>],
+  ) -> Poll<io::Result<usize>> {
+    ready!(self.poll_informational(cx))?;
+    let len = bufs.iter().map(|buf| buf.len()).sum();
+    if self.writable_len(len) < len {
+      // Only write up to the next frame boundary
+      let buf = bufs.iter().find(|buf| !buf.is_empty()).unwrap();
+      return self.poll_write(cx, buf);
+    }
+    let n = ready!(Pin::new(&mut self.io).poll_write_vectored(cx, bufs))?;
+    let mut left = n;
+    for buf in bufs {
+      let written = left.min(buf.len());
+      self.written(&buf[..written]);
+      left -= written;
+    }
+    Poll::Ready(Ok(n))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::h2_frame::encode_field;
+  use crate::h2_frame::encode_header_frames;
+  use crate::h2_frame::FrameHeader;
+  use crate::h2_frame::FRAME_HEADER_LEN;
+  use crate::h2_frame::FRAME_TYPE_SETTINGS;
+  use crate::metrics::HttpServerMetrics;
+  use crate::request_properties::HttpConnectionProperties;
+  use deno_net::raw::NetworkStreamType;
+  use http::HeaderMap;
+  use http::StatusCode;
+  use std::sync::Arc;
+  use tokio::io::AsyncReadExt;
+  use tokio::io::AsyncWriteExt;
+
+  fn connection_metrics() -> ConnectionMetrics {
+    Arc::new(HttpServerMetrics::default()).open_connection(
+      &HttpConnectionProperties {
+        peer_address: "127.0.0.1".into(),
+        peer_port: None,
+        local_port: None,
+        stream_type: NetworkStreamType::Tcp,
+      },
+    )
+  }
+
+  #[tokio::test]
+  async fn writes_informational_between_messages() {
+    let (mut client, server) = tokio::io::duplex(1024);
+    let informational = InformationalQueue::default();
+    let mut server = ServeStream::new(
+      server,
+      None,
+      informational.clone(),
+      connection_metrics(),
+    );
+    let response = || InformationalResponse {
+      status: StatusCode::from_u16(103).unwrap(),
+      headers: HeaderMap::new(),
+    };
+    server.write_all(b"first ").await.unwrap();
+    // Not at a message boundary until the first message is flushed
+    informational.push(response());
+    server.write_all(b"message ").await.unwrap();
+    server.flush().await.unwrap();
+    informational.push(response());
+    server.write_all(b"second").await.unwrap();
+    server.shutdown().await.unwrap();
+    drop(server);
+    let mut read = String::new();
+    client.read_to_string(&mut read).await.unwrap();
+    assert_eq!(
+      read,
+      "first message HTTP/1.1 103 Early Hints\r\n\r\nHTTP/1.1 103 Early Hints\r\n\r\nsecond"
+    );
+  }
+
+  #[tokio::test]
+  async fn writes_informational_before_hyper_response() {
+    use hyper::service::service_fn;
+    use hyper_util::rt::TokioIo;
+
+    let (mut client, server) = tokio::io::duplex(1024);
+    let informational = InformationalQueue::default();
+    let server = ServeStream::new(
+      server,
+      None,
+      informational.clone(),
+      connection_metrics(),
+    );
+    let (ready_tx, ready_rx) = tokio::sync::oneshot::channel::<()>();
+    let ready_rx = std::sync::Mutex::new(Some(ready_rx));
+    let svc = service_fn(move |_req| {
+      informational.push(InformationalResponse {
+        status: StatusCode::from_u16(103).unwrap(),
+        headers: HeaderMap::new(),
+      });
+      let ready_rx = ready_rx.lock().unwrap().take().unwrap();
+      async move {
+        ready_rx.await.unwrap();
+        Ok::<_, std::convert::Infallible>(hyper::Response::new(
+          http_body_util::Full::new(Bytes::from_static(b"ok")),
+        ))
+      }
+    });
+    let conn = hyper::server::conn::http1::Builder::new()
+      .serve_connection(TokioIo::new(server), svc);
+    let client = async move {
+      client
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+      let mut buf = vec![0; 1024];
+      let n = client.read(&mut buf).await.unwrap();
+      assert_eq!(&buf[..n], b"HTTP/1.1 103 Early Hints\r\n\r\n");
+      ready_tx.send(()).unwrap();
+      let mut read = Vec::new();
+      while !read.ends_with(b"ok") {
+        let n = client.read(&mut buf).await.unwrap();
+        read.extend_from_slice(&buf[..n]);
+      }
+      assert!(read.starts_with(b"HTTP/1.1 200 OK\r\n"));
+    };
+    tokio::select! {
+      _ = client => {}
+      res = conn => panic!("connection closed: {res:?}"),
+    }
+  }
+
+  #[tokio::test]
+  async fn writes_informational_on_http2_stream() {
+    use hyper::service::service_fn;
+    use hyper_util::rt::TokioExecutor;
+    use hyper_util::rt::TokioIo;
+
+    let (mut client, server) = tokio::io::duplex(64 * 1024);
+    let informational = InformationalQueue::default();
+    let server = ServeStream::new(
+      server,
+      None,
+      informational.clone(),
+      connection_metrics(),
+    );
+    let (ready_tx, ready_rx) = tokio::sync::oneshot::channel::<()>();
+    let ready_rx = std::sync::Mutex::new(Some(ready_rx));
+    let svc = service_fn(move |req: hyper::Request<hyper::body::Incoming>| {
+      let hints = req.uri().path() == "/hints";
+      let ready_rx = if hints {
+        // The handle of the stream of the request is taken in order
+        let informational = informational.for_next_stream().unwrap();
+        informational.push(InformationalResponse {
+          status: StatusCode::from_u16(103).unwrap(),
+          headers: HeaderMap::new(),
+        });
+        ready_rx.lock().unwrap().take()
+      } else {
+        informational.for_next_stream().unwrap();
+        None
+      };
+      async move {
+        if let Some(ready_rx) = ready_rx {
+          ready_rx.await.unwrap();
+        }
+        Ok::<_, std::convert::Infallible>(hyper::Response::new(
+          http_body_util::Full::new(Bytes::from_static(b"ok")),
+        ))
+      }
+    });
+    let conn = hyper::server::conn::http2::Builder::new(TokioExecutor::new())
+      .serve_connection(TokioIo::new(server), svc);
+
+    let request = |stream_id, path: &[u8]| {
+      let mut block = Vec::new();
+      encode_field(&mut block, b":method", b"GET");
+      encode_field(&mut block, b":scheme", b"http");
+      encode_field(&mut block, b":authority", b"localhost");
+      encode_field(&mut block, b":path", path);
+      let mut frames = Vec::new();
+      encode_header_frames(&mut frames, stream_id, &block, true);
+      frames
+    };
+    let client = async move {
+      client.write_all(HTTP2_PREFIX).await.unwrap();
+      client
+        .write_all(&[0, 0, 0, FRAME_TYPE_SETTINGS, 0, 0, 0, 0, 0])
+        .await
+        .unwrap();
+      client.write_all(&request(1, b"/")).await.unwrap();
+      client.write_all(&request(3, b"/hints")).await.unwrap();
+
+      let mut hints = Vec::new();
+      encode_field(&mut hints, b":status", b"103");
+      let mut responses = Vec::new();
+      let mut ready_tx = Some(ready_tx);
+      while responses.len() < 3 {
+        let mut header = [0; FRAME_HEADER_LEN];
+        client.read_exact(&mut header).await.unwrap();
+        let frame = FrameHeader::parse(&header);
+        let mut payload = vec![0; frame.len];
+        client.read_exact(&mut payload).await.unwrap();
+        if frame.kind != FRAME_TYPE_HEADERS {
+          continue;
+        }
+        let informational = payload == hints;
+        if informational {
+          // The early hints are sent before the final response is ready
+          ready_tx.take().unwrap().send(()).unwrap();
+        }
+        responses.push((frame.stream_id, informational));
+      }
+      assert!(responses.contains(&(1, false)));
+      let hints_at = responses.iter().position(|r| *r == (3, true)).unwrap();
+      let final_at = responses.iter().position(|r| *r == (3, false)).unwrap();
+      assert!(hints_at < final_at);
+    };
+    tokio::select! {
+      _ = client => {}
+      res = conn => panic!("connection closed: {res:?}"),
+    }
+  }
+}
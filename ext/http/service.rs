@@ -1,4 +1,10 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+use crate::informational::InformationalQueue;
+use crate::informational::InformationalResponse;
+use crate::metrics::HttpServerMetrics;
+use crate::metrics::RequestMetrics;
+use crate::request_body::IncomingBody;
+use crate::request_body::RequestTrailers;
 use crate::request_properties::HttpConnectionProperties;
 use crate::response_body::ResponseBytesInner;
 use crate::response_body::ResponseStreamResult;
@@ -7,10 +13,13 @@ use deno_core::futures::ready;
 use deno_core::BufView;
 use deno_core::OpState;
 use deno_core::ResourceId;
+use http::header::ALT_SVC;
+use http::header::TRAILER;
 use http::request::Parts;
+use http::HeaderValue;
+use http::Version;
 use hyper::body::Body;
 use hyper::body::Frame;
-use hyper::body::Incoming;
 use hyper::body::SizeHint;
 use hyper::header::HeaderMap;
 use hyper::upgrade::OnUpgrade;
@@ -25,11 +34,13 @@ use std::future::Future;
 use std::mem::ManuallyDrop;
 use std::pin::Pin;
 use std::rc::Rc;
+use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
 use std::task::Waker;
+use std::time::Duration;
 
-pub type Request = hyper::Request<Incoming>;
+pub type Request = hyper::Request<IncomingBody>;
 pub type Response = hyper::Response<HttpRecordResponse>;
 
 #[cfg(feature = "__http_tracing")]
@@ -71,8 +82,58 @@ macro_rules! http_trace {
 pub(crate) use http_general_trace;
 pub(crate) use http_trace;
 
+/// Timeouts which keep clients that make no progress from holding connections open.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct HttpServeTimeouts {
+  /// For the TLS handshake and the request headers of HTTP/1.1 and HTTP/3.
+  pub header_read: Option<Duration>,
+  /// For each read of a request body.
+  pub request_body_idle: Option<Duration>,
+  /// For a response write which doesn't make any progress.
+  pub response_write: Option<Duration>,
+}
+
+/// How responses are automatically compressed.
+#[derive(Debug)]
+pub(crate) struct HttpServeCompression {
+  pub enabled: bool,
+  /// The gzip compression level, from 0 to 9.
+  pub gzip_level: u32,
+  /// The brotli quality, from 0 to 11.
+  pub brotli_quality: u32,
+  /// Responses of a known length below this size aren't compressed.
+  pub min_size: usize,
+  /// The content types to compress instead of the known compressible ones.
+  pub content_types: Option<Vec<String>>,
+}
+
+impl Default for HttpServeCompression {
+  fn default() -> Self {
+    Self {
+      enabled: true,
+      // Fast compression
+      gzip_level: 1,
+      // Quality level 6 is based on google's nginx default value for on-the-fly compression
+      // https://github.com/google/ngx_brotli#brotli_comp_level
+      brotli_quality: 6,
+      // By the time we add compression headers and Accept-Encoding, it probably doesn't make
+      // sense to compress stuff that's smaller than this.
+      min_size: 64,
+      content_types: None,
+    }
+  }
+}
+
 pub(crate) struct HttpServerStateInner {
   pool: Vec<(Rc<HttpRecord>, HeaderMap)>,
+  /// The `Alt-Svc` header advertising the HTTP/3 endpoint, if any.
+  pub alt_svc: Option<HeaderValue>,
+  pub timeouts: HttpServeTimeouts,
+  pub compression: Rc<HttpServeCompression>,
+  /// Whether cleartext connections may upgrade to HTTP/2 with an
+  /// `Upgrade: h2c` request.
+  pub h2c_upgrade: bool,
+  pub metrics: Arc<HttpServerMetrics>,
 }
 
 /// A signalling version of `Rc` that allows one to poll for when all other references
@@ -136,6 +197,11 @@ impl HttpServerState {
   pub fn new() -> SignallingRc<Self> {
     SignallingRc::new(Self(RefCell::new(HttpServerStateInner {
       pool: Vec::new(),
+      alt_svc: None,
+      timeouts: HttpServeTimeouts::default(),
+      compression: Default::default(),
+      h2c_upgrade: false,
+      metrics: Default::default(),
     })))
   }
 }
@@ -149,12 +215,12 @@ impl std::ops::Deref for HttpServerState {
 }
 
 enum RequestBodyState {
-  Incoming(Incoming),
+  Incoming(IncomingBody),
   Resource(#[allow(dead_code)] HttpRequestBodyAutocloser),
 }
 
-impl From<Incoming> for RequestBodyState {
-  fn from(value: Incoming) -> Self {
+impl From<IncomingBody> for RequestBodyState {
+  fn from(value: IncomingBody) -> Self {
     RequestBodyState::Incoming(value)
   }
 }
@@ -186,6 +252,11 @@ pub(crate) async fn handle_request(
   // and execution could stop at any await point.
   // The HttpRecord must live until JavaScript is done processing so is wrapped
   // in an Rc. The guard ensures unneeded resources are freed at cancellation.
+  let alt_svc = if request.version() == Version::HTTP_3 {
+    None
+  } else {
+    server_state.borrow().alt_svc.clone()
+  };
   let guarded_record = guard(
     HttpRecord::new(request, request_info, server_state),
     HttpRecord::cancel,
@@ -202,7 +273,10 @@ pub(crate) async fn handle_request(
   // Defuse the guard. Must not await after this point.
   let record = ScopeGuard::into_inner(guarded_record);
   http_trace!(record, "handle_request complete");
-  let response = record.into_response();
+  let mut response = record.into_response();
+  if let Some(alt_svc) = alt_svc {
+    response.headers_mut().entry(ALT_SVC).or_insert(alt_svc);
+  }
   Ok(response)
 }
 
@@ -211,6 +285,7 @@ struct HttpRecordInner {
   request_info: HttpConnectionProperties,
   request_parts: http::request::Parts,
   request_body: Option<RequestBodyState>,
+  request_trailers: RequestTrailers,
   response_parts: Option<http::response::Parts>,
   response_ready: bool,
   response_waker: Option<Waker>,
@@ -218,9 +293,11 @@ struct HttpRecordInner {
   response_body_finished: bool,
   response_body_waker: Option<Waker>,
   trailers: Option<HeaderMap>,
+  declares_trailers: bool,
   been_dropped: bool,
   finished: bool,
   needs_close_after_finish: bool,
+  _request_metrics: RequestMetrics,
 }
 
 pub struct HttpRecord(RefCell<Option<HttpRecordInner>>);
@@ -245,6 +322,7 @@ impl HttpRecord {
     let (request_parts, request_body) = request.into_parts();
     let request_body = Some(request_body.into());
     let (mut response_parts, _) = http::Response::new(()).into_parts();
+    let request_metrics = server_state.borrow().metrics.start_request();
     let record =
       if let Some((record, headers)) = server_state.borrow_mut().pool.pop() {
         response_parts.headers = headers;
@@ -266,6 +344,7 @@ impl HttpRecord {
       request_info,
       request_parts,
       request_body,
+      request_trailers: Default::default(),
       response_parts: Some(response_parts),
       response_ready: false,
       response_waker: None,
@@ -273,9 +352,11 @@ impl HttpRecord {
       response_body_finished: false,
       response_body_waker: None,
       trailers: None,
+      declares_trailers: false,
       been_dropped: false,
       finished: false,
       needs_close_after_finish: false,
+      _request_metrics: request_metrics,
     });
     record
   }
@@ -354,8 +435,8 @@ impl HttpRecord {
       .ok_or_else(|| AnyError::msg("upgrade unavailable"))
   }
 
-  /// Take the Hyper body from this record.
-  pub fn take_request_body(&self) -> Option<Incoming> {
+  /// Take the request body from this record.
+  pub fn take_request_body(&self) -> Option<IncomingBody> {
     let body_holder = &mut self.self_mut().request_body;
     let body = body_holder.take();
     match body {
@@ -436,6 +517,12 @@ impl HttpRecord {
     RefMut::map(self.self_mut(), |inner| &mut inner.trailers)
   }
 
+  /// Get the trailers of the request, which are set once its body is read to
+  /// the end.
+  pub fn request_trailers(&self) -> RequestTrailers {
+    self.self_ref().request_trailers.clone()
+  }
+
   pub fn set_response_body(&self, response_body: ResponseBytesInner) {
     let mut inner = self.self_mut();
     debug_assert!(matches!(inner.response_body, ResponseBytesInner::Empty));
@@ -444,11 +531,38 @@ impl HttpRecord {
 
   /// Take the response.
   fn into_response(self: Rc<Self>) -> Response {
-    let parts = self.self_mut().response_parts.take().unwrap();
+    let mut inner = self.self_mut();
+    let mut parts = inner.response_parts.take().unwrap();
+    // HTTP/1.1 only sends the trailers named by the `Trailer` header, so
+    // declare the trailers that are already set if the handler didn't.
+    if let Some(trailers) = inner.trailers.as_ref().filter(|t| !t.is_empty()) {
+      if !parts.headers.contains_key(TRAILER) {
+        let names = trailers
+          .keys()
+          .map(|name| name.as_str())
+          .collect::<Vec<_>>()
+          .join(", ");
+        parts
+          .headers
+          .insert(TRAILER, HeaderValue::from_str(&names).unwrap());
+      }
+    }
+    inner.declares_trailers = parts.headers.contains_key(TRAILER);
+    drop(inner);
     let body = HttpRecordResponse(ManuallyDrop::new(self));
     Response::from_parts(parts, body)
   }
 
+  /// Get the timeouts of the server this record belongs to.
+  pub fn server_timeouts(&self) -> HttpServeTimeouts {
+    self.self_ref().server_state.borrow().timeouts
+  }
+
+  /// Get the response compression options of the server this record belongs to.
+  pub fn server_compression(&self) -> Rc<HttpServeCompression> {
+    self.self_ref().server_state.borrow().compression.clone()
+  }
+
   /// Get a reference to the connection properties.
   pub fn request_info(&self) -> Ref<'_, HttpConnectionProperties> {
     Ref::map(self.self_ref(), |inner| &inner.request_info)
@@ -459,6 +573,22 @@ impl HttpRecord {
     Ref::map(self.self_ref(), |inner| &inner.request_parts)
   }
 
+  /// Queue an informational response to be sent ahead of the final response, returning `false`
+  /// if the connection can't send it or the final response has already been sent.
+  pub fn send_informational(&self, response: InformationalResponse) -> bool {
+    let inner = self.self_ref();
+    if inner.response_ready || inner.been_dropped {
+      return false;
+    }
+    let Some(queue) =
+      inner.request_parts.extensions.get::<InformationalQueue>()
+    else {
+      return false;
+    };
+    queue.push(response);
+    true
+  }
+
   /// Resolves when response head is ready.
   fn response_ready(&self) -> impl Future<Output = ()> + '_ {
     struct HttpRecordReady<'a>(&'a HttpRecord);
@@ -578,7 +708,16 @@ impl Body for HttpRecordResponse {
   fn size_hint(&self) -> SizeHint {
     // The size hint currently only used in the case where it is exact bounds in hyper, but we'll pass it through
     // anyways just in case hyper needs it.
-    self.0.self_ref().response_body.size_hint()
+    let inner = self.0.self_ref();
+    let size_hint = inner.response_body.size_hint();
+    // Trailers can only follow a chunked HTTP/1.1 body, rather than one with a
+    // content length
+    if inner.declares_trailers && size_hint.exact().is_some() {
+      let mut chunked = SizeHint::new();
+      chunked.set_lower(size_hint.lower());
+      return chunked;
+    }
+    size_hint
   }
 }
 
@@ -599,6 +738,7 @@ mod tests {
   use bytes::Buf;
   use deno_net::raw::NetworkStreamType;
   use hyper::body::Body;
+  use hyper::body::Incoming;
   use hyper::service::service_fn;
   use hyper::service::HttpService;
   use hyper_util::rt::TokioIo;
@@ -652,7 +792,7 @@ mod tests {
     };
     let svc = service_fn(move |req: hyper::Request<Incoming>| {
       handle_request(
-        req,
+        req.map(IncomingBody::Hyper),
         request_info.clone(),
         server_state.clone(),
         tx.clone(),
@@ -689,4 +829,86 @@ mod tests {
     assert_eq!(server_state_check.strong_count(), 1);
     Ok(())
   }
+
+  #[tokio::test]
+  async fn test_handle_request_trailers() -> Result<(), AnyError> {
+    use crate::request_body::HttpRequestBody;
+    use bytes::Bytes;
+    use deno_core::futures::stream;
+    use deno_core::Resource;
+    use http_body_util::BodyExt;
+    use http_body_util::StreamBody;
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel(10);
+    let server_state = HttpServerState::new();
+    let request_info = HttpConnectionProperties {
+      peer_address: "".into(),
+      peer_port: None,
+      local_port: None,
+      stream_type: NetworkStreamType::Tcp,
+    };
+    let svc = service_fn(move |req: hyper::Request<Incoming>| {
+      handle_request(
+        req.map(IncomingBody::Hyper),
+        request_info.clone(),
+        server_state.clone(),
+        tx.clone(),
+      )
+    });
+
+    let mut request_trailers = HeaderMap::new();
+    request_trailers.insert("x-checksum", HeaderValue::from_static("abc"));
+    let client_body = StreamBody::new(stream::iter([
+      Ok::<_, std::convert::Infallible>(Frame::data(Bytes::from_static(
+        b"hello",
+      ))),
+      Ok(Frame::trailers(request_trailers)),
+    ]));
+    let client_req = http::Request::builder()
+      .method("POST")
+      .uri("/")
+      .header(TRAILER, "x-checksum")
+      // HTTP/1.1 servers only send trailers to clients that accept them
+      .header("te", "trailers")
+      .body(client_body)?;
+
+    tokio::try_join!(
+      async move {
+        let record = rx.recv().await.unwrap();
+        let body = Rc::new(HttpRequestBody::new(
+          record.take_request_body().unwrap(),
+          None,
+          record.request_trailers(),
+        ));
+        while !Resource::read(body.clone(), 1024).await.unwrap().is_empty() {}
+        assert_eq!(
+          record.request_trailers().borrow().as_ref().unwrap()["x-checksum"],
+          "abc"
+        );
+
+        let mut trailers = HeaderMap::new();
+        trailers.insert("grpc-status", HeaderValue::from_static("0"));
+        *record.trailers() = Some(trailers);
+        record.set_response_body(ResponseBytesInner::from_vec(
+          Compression::None,
+          b"hello world".to_vec(),
+        ));
+        record.complete();
+        Ok(())
+      },
+      async move {
+        serve_request(client_req, svc, |res| async {
+          // The trailers are declared, and sent after a chunked body
+          assert_eq!(res.headers()[TRAILER], "grpc-status");
+          assert_eq!(res.headers()["transfer-encoding"], "chunked");
+          let body = res.collect().await?;
+          assert_eq!(body.trailers().unwrap()["grpc-status"], "0");
+          assert_eq!(body.to_bytes().chunk(), b"hello world");
+          Ok(())
+        })
+        .await
+      },
+    )?;
+    Ok(())
+  }
 }
@@ -0,0 +1,270 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! A native server-sent events (`text/event-stream`) response body. Events
+//! are framed in Rust and queued on a bounded channel that the response body
+//! drains, so that a slow client applies backpressure to `send`, and a
+//! disconnected one fails it.
+
+use bytes::Bytes;
+use bytes::BytesMut;
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::op2;
+use deno_core::AsyncRefCell;
+use deno_core::AsyncResult;
+use deno_core::BufView;
+use deno_core::CancelFuture;
+use deno_core::CancelHandle;
+use deno_core::OpState;
+use deno_core::RcRef;
+use deno_core::Resource;
+use deno_core::ResourceId;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// The comment sent when no event was sent for the heartbeat interval, which
+/// keeps proxies from timing out the idle connection.
+const HEARTBEAT: &[u8] = b":\n\n";
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerSentEvent {
+  data: Option<String>,
+  event: Option<String>,
+  id: Option<String>,
+  retry: Option<u64>,
+  comment: Option<String>,
+}
+
+impl ServerSentEvent {
+  /// Frames the event in the `text/event-stream` format.
+  pub fn encode(&self) -> Result<Bytes, AnyError> {
+    let mut buf = BytesMut::new();
+    if let Some(comment) = &self.comment {
+      for line in split_lines(comment) {
+        buf.extend_from_slice(b": ");
+        buf.extend_from_slice(line.as_bytes());
+        buf.extend_from_slice(b"\n");
+      }
+    }
+    if let Some(event) = &self.event {
+      encode_single_line_field(&mut buf, "event", event)?;
+    }
+    if let Some(id) = &self.id {
+      if id.contains('\0') {
+        return Err(type_error("The event id must not contain NUL"));
+      }
+      encode_single_line_field(&mut buf, "id", id)?;
+    }
+    if let Some(retry) = self.retry {
+      buf.extend_from_slice(format!("retry: {retry}\n").as_bytes());
+    }
+    if let Some(data) = &self.data {
+      for line in split_lines(data) {
+        buf.extend_from_slice(b"data: ");
+        buf.extend_from_slice(line.as_bytes());
+        buf.extend_from_slice(b"\n");
+      }
+    }
+    if buf.is_empty() {
+      return Err(type_error("The event must have at least one field"));
+    }
+    buf.extend_from_slice(b"\n");
+    Ok(buf.freeze())
+  }
+}
+
+/// Splits on the line terminators of the event stream format: CRLF, LF and CR.
+fn split_lines(s: &str) -> impl Iterator<Item = &str> {
+  s.split("\r\n").flat_map(|s| s.split(['\n', '\r']))
+}
+
+fn encode_single_line_field(
+  buf: &mut BytesMut,
+  name: &str,
+  value: &str,
+) -> Result<(), AnyError> {
+  if value.contains(['\n', '\r']) {
+    return Err(type_error(format!(
+      "The event {name} must not contain line breaks"
+    )));
+  }
+  buf.extend_from_slice(name.as_bytes());
+  buf.extend_from_slice(b": ");
+  buf.extend_from_slice(value.as_bytes());
+  buf.extend_from_slice(b"\n");
+  Ok(())
+}
+
+pub struct ServerSentEventsResource {
+  tx: RefCell<Option<mpsc::Sender<Bytes>>>,
+  rx: AsyncRefCell<mpsc::Receiver<Bytes>>,
+  /// The rest of an event that did not fit into the last read.
+  leftover: RefCell<Bytes>,
+  heartbeat: Option<Duration>,
+  /// Canceled once the stream ends, either because the client disconnected
+  /// or because all events were sent after `end`.
+  finished: CancelHandle,
+}
+
+impl ServerSentEventsResource {
+  async fn next_chunk(self: &Rc<Self>) -> Option<Bytes> {
+    let mut rx = RcRef::map(self, |r| &r.rx).borrow_mut().await;
+    match self.heartbeat {
+      Some(interval) => match tokio::time::timeout(interval, rx.recv()).await {
+        Ok(chunk) => chunk,
+        Err(_) => Some(Bytes::from_static(HEARTBEAT)),
+      },
+      None => rx.recv().await,
+    }
+  }
+}
+
+impl Resource for ServerSentEventsResource {
+  fn name(&self) -> Cow<str> {
+    "serverSentEvents".into()
+  }
+
+  fn read(self: Rc<Self>, limit: usize) -> AsyncResult<BufView> {
+    Box::pin(async move {
+      let mut chunk = self.leftover.take();
+      if chunk.is_empty() {
+        let cancel = RcRef::map(&self, |r| &r.finished);
+        match self.next_chunk().or_cancel(cancel).await {
+          Ok(Some(next)) => chunk = next,
+          Ok(None) => {
+            self.finished.cancel();
+            return Ok(BufView::empty());
+          }
+          Err(_) => return Ok(BufView::empty()),
+        }
+      }
+      if chunk.len() > limit {
+        *self.leftover.borrow_mut() = chunk.split_off(limit);
+      }
+      Ok(BufView::from(chunk))
+    })
+  }
+
+  fn close(self: Rc<Self>) {
+    self.tx.borrow_mut().take();
+    self.finished.cancel();
+  }
+}
+
+/// Creates the body of a server-sent events response. A `heartbeat_interval`
+/// of zero disables heartbeats, and `high_water_mark` is the number of events
+/// that may be queued before `send` waits for the client to catch up.
+#[op2(fast)]
+#[smi]
+pub fn op_http_sse_create(
+  state: &mut OpState,
+  #[smi] heartbeat_interval: u32,
+  #[smi] high_water_mark: u32,
+) -> Result<ResourceId, AnyError> {
+  if high_water_mark == 0 {
+    return Err(type_error("highWaterMark must be greater than 0"));
+  }
+  let (tx, rx) = mpsc::channel(high_water_mark as usize);
+  let heartbeat = (heartbeat_interval > 0)
+    .then(|| Duration::from_millis(heartbeat_interval as u64));
+  Ok(state.resource_table.add(ServerSentEventsResource {
+    tx: RefCell::new(Some(tx)),
+    rx: AsyncRefCell::new(rx),
+    leftover: Default::default(),
+    heartbeat,
+    finished: Default::default(),
+  }))
+}
+
+#[op2(async)]
+pub async fn op_http_sse_send(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+  #[serde] event: ServerSentEvent,
+) -> Result<(), AnyError> {
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<ServerSentEventsResource>(rid)?;
+  let chunk = event.encode()?;
+  let disconnected = || {
+    std::io::Error::new(
+      std::io::ErrorKind::BrokenPipe,
+      "The server-sent event stream is closed",
+    )
+  };
+  let Some(tx) = resource.tx.borrow().clone() else {
+    return Err(disconnected().into());
+  };
+  let cancel = RcRef::map(&resource, |r| &r.finished);
+  match tx.send(chunk).or_cancel(cancel).await {
+    Ok(Ok(())) => Ok(()),
+    _ => Err(disconnected().into()),
+  }
+}
+
+/// Ends the stream once the queued events have been sent.
+#[op2(fast)]
+pub fn op_http_sse_end(
+  state: &mut OpState,
+  #[smi] rid: ResourceId,
+) -> Result<(), AnyError> {
+  let resource = state.resource_table.get::<ServerSentEventsResource>(rid)?;
+  resource.tx.borrow_mut().take();
+  Ok(())
+}
+
+/// Resolves once the stream has ended, or the client disconnected.
+#[op2(async)]
+pub async fn op_http_sse_finished(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+) -> Result<(), AnyError> {
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<ServerSentEventsResource>(rid)?;
+  let cancel = RcRef::map(&resource, |r| &r.finished);
+  let _ = std::future::pending::<()>().or_cancel(cancel).await;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_encode_server_sent_event() {
+    let event = ServerSentEvent {
+      data: Some("first\nsecond\r\nthird".to_string()),
+      event: Some("update".to_string()),
+      id: Some("42".to_string()),
+      retry: Some(1000),
+      comment: Some("hi".to_string()),
+    };
+    assert_eq!(
+      event.encode().unwrap(),
+      Bytes::from_static(
+        b": hi\nevent: update\nid: 42\nretry: 1000\ndata: first\ndata: second\ndata: third\n\n"
+      )
+    );
+
+    let event = ServerSentEvent {
+      data: Some(String::new()),
+      ..Default::default()
+    };
+    assert_eq!(event.encode().unwrap(), Bytes::from_static(b"data: \n\n"));
+
+    let event = ServerSentEvent {
+      event: Some("a\nb".to_string()),
+      ..Default::default()
+    };
+    assert!(event.encode().is_err());
+    assert!(ServerSentEvent::default().encode().is_err());
+  }
+}
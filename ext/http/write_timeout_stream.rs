@@ -0,0 +1,176 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::io::ReadBuf;
+use tokio::time::Sleep;
+
+/// [`WriteTimeoutStream`] wraps a stream so that a write which doesn't make any
+/// progress for the given duration fails with [`io::ErrorKind::TimedOut`].
+/// This keeps a client that stops reading a response from holding its
+/// connection open indefinitely. Without a timeout, the stream is passed
+/// through unchanged.
+pub struct WriteTimeoutStream<S> {
+  io: S,
+  timeout: Option<Duration>,
+  sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl<S> WriteTimeoutStream<S> {
+  pub fn new(io: S, timeout: Option<Duration>) -> Self {
+    Self {
+      io,
+      timeout,
+      sleep: None,
+    }
+  }
+
+  pub fn into_inner(self) -> S {
+    self.io
+  }
+
+  /// Tracks a pending write, failing once it has been pending for too long.
+  fn poll_stalled(&mut self, cx: &mut Context<'_>) -> io::Result<()> {
+    let Some(timeout) = self.timeout else {
+      return Ok(());
+    };
+    let sleep = self
+      .sleep
+      .get_or_insert_with(|| Box::pin(tokio::time::sleep(timeout)));
+    if sleep.as_mut().poll(cx).is_ready() {
+      self.sleep = None;
+      return Err(io::Error::new(
+        io::ErrorKind::TimedOut,
+        "Response write timed out",
+      ));
+    }
+    Ok(())
+  }
+
+  fn poll_write_op<T>(
+    &mut self,
+    cx: &mut Context<'_>,
+    op: impl FnOnce(Pin<&mut S>, &mut Context<'_>) -> Poll<io::Result<T>>,
+  ) -> Poll<io::Result<T>>
+  where
+    S: Unpin,
+  {
+    match op(Pin::new(&mut self.io), cx) {
+      Poll::Pending => {
+        self.poll_stalled(cx)?;
+        Poll::Pending
+      }
+      ready => {
+        self.sleep = None;
+        ready
+      }
+    }
+  }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for WriteTimeoutStream<S> {
+  fn poll_read(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &mut ReadBuf<'_>,
+  ) -> Poll<io::Result<()>> {
+    Pin::new(&mut self.io).poll_read(cx, buf)
+  }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for WriteTimeoutStream<S> {
+  fn poll_write(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &[u8],
+  ) -> Poll<io::Result<usize>> {
+    self.poll_write_op(cx, |io, cx| io.poll_write(cx, buf))
+  }
+
+  fn poll_flush(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<io::Result<()>> {
+    self.poll_write_op(cx, |io, cx| io.poll_flush(cx))
+  }
+
+  fn poll_shutdown(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<io::Result<()>> {
+    self.poll_write_op(cx, |io, cx| io.poll_shutdown(cx))
+  }
+
+  fn is_write_vectored(&self) -> bool {
+    self.io.is_write_vectored()
+  }
+
+  fn poll_write_vectored(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    bufs: &[io::IoSlice<'_>],
+  ) -> Poll<io::Result<usize>> {
+    self.poll_write_op(cx, |io, cx| io.poll_write_vectored(cx, bufs))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tokio::io::AsyncReadExt;
+  use tokio::io::AsyncWriteExt;
+
+  #[tokio::test]
+  async fn times_out_stalled_write() {
+    let (_client, server) = tokio::io::duplex(4);
+    let mut server =
+      WriteTimeoutStream::new(server, Some(Duration::from_millis(50)));
+    // The client never reads, so the write stalls once the buffer is full
+    let err = server.write_all(b"hello world").await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+  }
+
+  #[tokio::test]
+  async fn progress_resets_timeout() {
+    let (mut client, server) = tokio::io::duplex(4);
+    let mut server =
+      WriteTimeoutStream::new(server, Some(Duration::from_millis(100)));
+    let reader = async {
+      let mut buf = [0; 4];
+      let mut read = Vec::new();
+      while read.len() < 12 {
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        let n = client.read(&mut buf).await.unwrap();
+        read.extend_from_slice(&buf[..n]);
+      }
+      read
+    };
+    let (res, read) = tokio::join!(server.write_all(b"hello world!"), reader);
+    res.unwrap();
+    assert_eq!(read, b"hello world!");
+  }
+
+  #[tokio::test]
+  async fn passes_through_without_timeout() {
+    let (mut client, server) = tokio::io::duplex(4);
+    let mut server = WriteTimeoutStream::new(server, None);
+    let reader = async {
+      tokio::time::sleep(Duration::from_millis(100)).await;
+      let mut read = Vec::new();
+      client.read_to_end(&mut read).await.unwrap();
+      read
+    };
+    let writer = async move {
+      server.write_all(b"hello world").await.unwrap();
+      server.shutdown().await.unwrap();
+    };
+    let (_, read) = tokio::join!(writer, reader);
+    assert_eq!(read, b"hello world");
+  }
+}
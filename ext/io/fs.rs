@@ -1,6 +1,7 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 use std::borrow::Cow;
+use std::cell::OnceCell;
 use std::io;
 use std::rc::Rc;
 use std::time::SystemTime;
@@ -255,11 +256,33 @@ pub trait File {
 pub struct FileResource {
   name: String,
   file: Rc<dyn File>,
+  size_hint: OnceCell<(u64, Option<u64>)>,
 }
 
 impl FileResource {
   pub fn new(file: Rc<dyn File>, name: String) -> Self {
-    Self { name, file }
+    Self {
+      name,
+      file,
+      size_hint: OnceCell::new(),
+    }
+  }
+
+  fn compute_size_hint(&self) -> (u64, Option<u64>) {
+    let Ok(stat) = self.file.clone().stat_sync() else {
+      return (0, None);
+    };
+    if !stat.is_file {
+      return (0, None);
+    }
+    let Ok(position) = self.file.clone().seek_sync(io::SeekFrom::Current(0))
+    else {
+      return (0, None);
+    };
+    match stat.size.saturating_sub(position) {
+      0 => (0, None),
+      remaining => (remaining, Some(remaining)),
+    }
   }
 
   fn with_resource<F, R>(
@@ -371,4 +394,15 @@ impl deno_core::Resource for FileResource {
   fn backing_fd(self: Rc<Self>) -> Option<ResourceHandleFd> {
     self.file.clone().backing_fd()
   }
+
+  /// Regular files report the number of bytes left to read from their
+  /// current position, so that they can be sent with a known length. Empty
+  /// files don't, as some (like those in `/proc`) only look empty.
+  ///
+  /// The hint is computed on the first call and cached, so the blocking
+  /// `stat` and `seek` only happen once, when the file starts being used as
+  /// a body.
+  fn size_hint(&self) -> (u64, Option<u64>) {
+    *self.size_hint.get_or_init(|| self.compute_size_hint())
+  }
 }
@@ -204,8 +204,19 @@ impl<P: RemoteDbHandlerPermissions + 'static> DatabaseHandler
           .unwrap(),
         pool_max_idle_per_host: None,
         pool_idle_timeout: None,
+        max_connections: None,
+        dns_resolver: Default::default(),
+        happy_eyeballs_delay: None,
+        local_address: None,
+        interface: None,
+        tls_session_cache_size: None,
+        revocation: None,
         http1: false,
         http2: true,
+        http2_prior_knowledge: false,
+        http2_max_concurrent_streams: None,
+        http2_initial_stream_window_size: None,
+        http2_initial_connection_window_size: None,
       },
     )?;
     let fetch_client = FetchClient(client);
@@ -11,20 +11,30 @@ import {
   op_dns_resolve,
   op_net_accept_tcp,
   op_net_accept_unix,
+  op_net_accept_vsock,
   op_net_connect_tcp,
+  op_net_connect_udp,
   op_net_connect_unix,
+  op_net_connect_vsock,
+  op_net_get_tcp_listener_socket_options,
+  op_net_get_tcp_socket_options,
   op_net_join_multi_v4_udp,
   op_net_join_multi_v6_udp,
   op_net_leave_multi_v4_udp,
   op_net_leave_multi_v6_udp,
   op_net_listen_tcp,
   op_net_listen_unix,
+  op_net_listen_vsock,
   op_net_recv_udp,
   op_net_recv_unixpacket,
   op_net_send_udp,
   op_net_send_unixpacket,
+  op_net_set_broadcast_udp,
   op_net_set_multi_loopback_udp,
   op_net_set_multi_ttl_udp,
+  op_net_set_tcp_listener_socket_options,
+  op_net_set_tcp_socket_options,
+  op_net_set_ttl_udp,
   op_set_keepalive,
   op_set_nodelay,
 } from "ext:core/ops";
@@ -214,6 +224,14 @@ class TcpConn extends Conn {
   setKeepAlive(keepAlive = true) {
     return op_set_keepalive(this.#rid, keepAlive);
   }
+
+  setSocketOptions(options) {
+    op_net_set_tcp_socket_options(this.#rid, options);
+  }
+
+  getSocketOptions() {
+    return op_net_get_tcp_socket_options(this.#rid);
+  }
 }
 
 class UnixConn extends Conn {
@@ -259,6 +277,9 @@ class Listener {
       case "unix":
         promise = op_net_accept_unix(this.#rid);
         break;
+      case "vsock":
+        promise = op_net_accept_vsock(this.#rid);
+        break;
       default:
         throw new Error(`Unsupported transport: ${this.addr.transport}`);
     }
@@ -276,6 +297,10 @@ class Listener {
         { transport: "unix", path: remoteAddr },
         { transport: "unix", path: localAddr },
       );
+    } else if (this.addr.transport == "vsock") {
+      localAddr.transport = "vsock";
+      remoteAddr.transport = "vsock";
+      return new Conn(rid, remoteAddr, localAddr);
     } else {
       throw new Error("unreachable");
     }
@@ -302,6 +327,20 @@ class Listener {
     return PromiseResolve({ value, done: true });
   }
 
+  setSocketOptions(options) {
+    if (this.addr.transport !== "tcp") {
+      throw new Error(`Unsupported transport: ${this.addr.transport}`);
+    }
+    op_net_set_tcp_listener_socket_options(this.#rid, options);
+  }
+
+  getSocketOptions() {
+    if (this.addr.transport !== "tcp") {
+      throw new Error(`Unsupported transport: ${this.addr.transport}`);
+    }
+    return op_net_get_tcp_listener_socket_options(this.#rid);
+  }
+
   close() {
     core.close(this.#rid);
   }
@@ -332,6 +371,7 @@ class Listener {
 class DatagramConn {
   #rid = 0;
   #addr = null;
+  #remoteAddr = null;
   #unref = false;
   #promise = null;
 
@@ -345,6 +385,30 @@ class DatagramConn {
     return this.#addr;
   }
 
+  get remoteAddr() {
+    return this.#remoteAddr;
+  }
+
+  async connect(addr) {
+    if (this.addr.transport !== "udp") {
+      throw new Error(`Unsupported transport: ${this.addr.transport}`);
+    }
+    const remoteAddr = await op_net_connect_udp(this.#rid, {
+      hostname: addr.hostname ?? "127.0.0.1",
+      port: validatePort(addr.port),
+    });
+    remoteAddr.transport = "udp";
+    this.#remoteAddr = remoteAddr;
+  }
+
+  async setBroadcast(broadcast) {
+    await op_net_set_broadcast_udp(this.#rid, broadcast);
+  }
+
+  async setTTL(ttl) {
+    await op_net_set_ttl_udp(this.#rid, ttl);
+  }
+
   async joinMulticastV4(addr, multiInterface) {
     await op_net_join_multi_v4_udp(
       this.#rid,
@@ -368,6 +432,7 @@ class DatagramConn {
       setTTL: (ttl) =>
         op_net_set_multi_ttl_udp(
           this.#rid,
+          true,
           ttl,
         ),
     };
@@ -393,6 +458,12 @@ class DatagramConn {
           false,
           loopback,
         ),
+      setTTL: (ttl) =>
+        op_net_set_multi_ttl_udp(
+          this.#rid,
+          false,
+          ttl,
+        ),
     };
   }
 
@@ -430,6 +501,10 @@ class DatagramConn {
   async send(p, opts) {
     switch (this.addr.transport) {
       case "udp":
+        // Connected sockets send to their peer when no address is given
+        if (opts === undefined && this.#remoteAddr !== null) {
+          return await op_net_send_udp(this.#rid, null, p);
+        }
         return await op_net_send_udp(
           this.#rid,
           { hostname: opts.hostname ?? "127.0.0.1", port: opts.port },
@@ -509,6 +584,14 @@ function listen(args) {
       };
       return new Listener(rid, addr);
     }
+    case "vsock": {
+      const { 0: rid, 1: addr } = op_net_listen_vsock({
+        cid: args.cid,
+        port: args.port,
+      });
+      addr.transport = "vsock";
+      return new Listener(rid, addr);
+    }
     default:
       throw new TypeError(`Unsupported transport: '${transport}'`);
   }
@@ -544,6 +627,10 @@ function createListenDatagram(udpOpFn, unixOpFn) {
           },
           args.reuseAddress ?? false,
           args.loopback ?? false,
+          {
+            recvBufferSize: args.recvBufferSize,
+            sendBufferSize: args.sendBufferSize,
+          },
         );
         addr.transport = "udp";
         return new DatagramConn(rid, addr);
@@ -571,6 +658,8 @@ async function connect(args) {
           hostname: args.hostname ?? "127.0.0.1",
           port,
         },
+        args.happyEyeballsDelay,
+        { localAddress: args.localAddress, interface: args.interface },
       );
       localAddr.transport = "tcp";
       remoteAddr.transport = "tcp";
@@ -586,6 +675,16 @@ async function connect(args) {
         { transport: "unix", path: localAddr },
       );
     }
+    case "vsock": {
+      const { 0: rid, 1: localAddr, 2: remoteAddr } =
+        await op_net_connect_vsock({
+          cid: args.cid,
+          port: args.port,
+        });
+      localAddr.transport = "vsock";
+      remoteAddr.transport = "vsock";
+      return new Conn(rid, remoteAddr, localAddr);
+    }
     default:
       throw new TypeError(`Unsupported transport: '${transport}'`);
   }
@@ -5,18 +5,25 @@ const { internalRidSymbol } = core;
 import {
   op_net_accept_tls,
   op_net_connect_tls,
+  op_net_get_tcp_socket_options,
   op_net_listen_tls,
+  op_net_set_tcp_socket_options,
   op_tls_cert_resolver_create,
   op_tls_cert_resolver_poll,
   op_tls_cert_resolver_resolve,
   op_tls_cert_resolver_resolve_error,
   op_tls_handshake,
   op_tls_key_null,
+  op_tls_key_pkcs12,
   op_tls_key_static,
+  op_tls_listener_config_create,
+  op_tls_listener_config_set_keys,
   op_tls_start,
 } from "ext:core/ops";
 const {
+  ArrayPrototypeMap,
   ObjectDefineProperty,
+  ObjectEntries,
   TypeError,
   SymbolFor,
 } = primordials;
@@ -39,6 +46,14 @@ class TlsConn extends Conn {
   handshake() {
     return op_tls_handshake(this.#rid);
   }
+
+  setSocketOptions(options) {
+    op_net_set_tcp_socket_options(this.#rid, options);
+  }
+
+  getSocketOptions() {
+    return op_net_get_tcp_socket_options(this.#rid);
+  }
 }
 
 async function connectTls({
@@ -50,6 +65,10 @@ async function connectTls({
   keyFormat = undefined,
   cert = undefined,
   key = undefined,
+  happyEyeballsDelay = undefined,
+  revocation = undefined,
+  localAddress = undefined,
+  interface: networkInterface = undefined,
 }) {
   if (transport !== "tcp") {
     throw new TypeError(`Unsupported transport: '${transport}'`);
@@ -65,7 +84,15 @@ async function connectTls({
   const serverName = arguments[0][serverNameSymbol] ?? null;
   const { 0: rid, 1: localAddr, 2: remoteAddr } = await op_net_connect_tls(
     { hostname, port },
-    { caCerts, alpnProtocols, serverName },
+    {
+      caCerts,
+      alpnProtocols,
+      serverName,
+      happyEyeballsDelay,
+      revocation,
+      localAddress,
+      interface: networkInterface,
+    },
     keyPair,
   );
   localAddr.transport = "tcp";
@@ -75,8 +102,9 @@ async function connectTls({
 
 class TlsListener extends Listener {
   #rid = 0;
+  #config;
 
-  constructor(rid, addr) {
+  constructor(rid, addr, config) {
     super(rid, addr);
     ObjectDefineProperty(this, internalRidSymbol, {
       __proto__: null,
@@ -84,6 +112,20 @@ class TlsListener extends Listener {
       value: rid,
     });
     this.#rid = rid;
+    this.#config = config;
+  }
+
+  setCertificate(options) {
+    if (!hasTlsKeyPairOptions(options)) {
+      throw new TypeError(
+        "A key and certificate are required for `Deno.TlsListener.setCertificate`",
+      );
+    }
+    const keyPair = loadTlsKeyPair("Deno.TlsListener.setCertificate", options);
+    op_tls_listener_config_set_keys(this.#config, keyPair, {
+      ocspResponse: options.ocspResponse,
+      sniCertificates: sniCertificateList(options.sniCertificates),
+    });
   }
 
   async accept() {
@@ -105,7 +147,8 @@ function hasTlsKeyPairOptions(options) {
   if (options[resolverSymbol] !== undefined) {
     return true;
   }
-  return (options.cert !== undefined || options.key !== undefined);
+  return (options.cert !== undefined || options.key !== undefined ||
+    options.pkcs12 !== undefined);
 }
 
 /**
@@ -116,15 +159,33 @@ function loadTlsKeyPair(api, {
   keyFormat,
   cert,
   key,
+  pkcs12,
+  password,
 }) {
   // TODO(mmastrac): remove this temporary symbol when the API lands
   if (arguments[1][resolverSymbol] !== undefined) {
     return createTlsKeyResolver(arguments[1][resolverSymbol]);
   }
 
+  if (keyFormat === "pkcs12") {
+    if (cert !== undefined || key !== undefined) {
+      throw new TypeError(
+        `If \`keyFormat\` is "pkcs12", \`cert\` and \`key\` must not be specified for \`${api}\`.`,
+      );
+    }
+    if (pkcs12 === undefined) {
+      throw new TypeError(
+        `If \`keyFormat\` is "pkcs12", \`pkcs12\` must be specified for \`${api}\`.`,
+      );
+    }
+    return op_tls_key_pkcs12(pkcs12, password ?? "");
+  }
+
   // Check for "pem" format
   if (keyFormat !== undefined && keyFormat !== "pem") {
-    throw new TypeError('If `keyFormat` is specified, it must be "pem"');
+    throw new TypeError(
+      'If `keyFormat` is specified, it must be "pem" or "pkcs12"',
+    );
   }
 
   if (cert !== undefined && key === undefined) {
@@ -145,12 +206,28 @@ function loadTlsKeyPair(api, {
   }
 }
 
+/**
+ * Flattens the certificates of the server names of a listener, for the op.
+ * @param {Record<string, Deno.TlsCertifiedKeyPem> | undefined} sniCertificates
+ */
+function sniCertificateList(sniCertificates) {
+  if (sniCertificates === undefined) {
+    return undefined;
+  }
+  return ArrayPrototypeMap(
+    ObjectEntries(sniCertificates),
+    ({ 0: serverName, 1: { cert, key } }) => ({ serverName, cert, key }),
+  );
+}
+
 function listenTls({
   port,
   hostname = "0.0.0.0",
   transport = "tcp",
   alpnProtocols = undefined,
   reusePort = false,
+  ocspResponse = undefined,
+  sniCertificates = undefined,
 }) {
   if (transport !== "tcp") {
     throw new TypeError(`Unsupported transport: '${transport}'`);
@@ -163,12 +240,17 @@ function listenTls({
     );
   }
   const keyPair = loadTlsKeyPair("Deno.listenTls", arguments[0]);
+  const config = op_tls_listener_config_create(keyPair, {
+    alpnProtocols,
+    ocspResponse,
+    sniCertificates: sniCertificateList(sniCertificates),
+  });
   const { 0: rid, 1: localAddr } = op_net_listen_tls(
     { hostname, port },
-    { alpnProtocols, reusePort },
-    keyPair,
+    { reusePort },
+    config,
   );
-  return new TlsListener(rid, localAddr);
+  return new TlsListener(rid, localAddr, config);
 }
 
 // deno-lint-ignore require-await
@@ -225,6 +307,7 @@ export {
   hasTlsKeyPairOptions,
   listenTls,
   loadTlsKeyPair,
+  sniCertificateList,
   startTls,
   TlsConn,
   TlsListener,
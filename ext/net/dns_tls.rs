@@ -0,0 +1,96 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! The TLS config of the connections to DNS-over-TLS and DNS-over-HTTPS name
+//! servers.
+//!
+//! The resolver depends on an older version of rustls than deno_tls, so the
+//! config can't be created with `deno_tls::create_client_config`. Instead, it
+//! is built from the same root certificates, which include the ones of
+//! `--cert` and `DENO_TLS_CA_STORE`, and honors
+//! `--unsafely-ignore-certificate-errors` in the same way.
+
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use deno_tls::create_default_root_cert_store;
+use deno_tls::rustls::RootCertStore;
+use rustls_v021::client::ServerCertVerified;
+use rustls_v021::client::ServerCertVerifier;
+use rustls_v021::client::WebPkiVerifier;
+use rustls_v021::Certificate;
+use rustls_v021::ClientConfig;
+use rustls_v021::Error;
+use rustls_v021::OwnedTrustAnchor;
+use rustls_v021::ServerName;
+
+pub(crate) fn create_client_config(
+  root_cert_store: Option<RootCertStore>,
+  unsafely_ignore_certificate_errors: Option<Vec<String>>,
+) -> Arc<ClientConfig> {
+  let root_cert_store =
+    root_cert_store.unwrap_or_else(create_default_root_cert_store);
+  let mut roots = rustls_v021::RootCertStore::empty();
+  roots.add_trust_anchors(root_cert_store.roots.iter().map(|anchor| {
+    OwnedTrustAnchor::from_subject_spki_name_constraints(
+      anchor.subject.to_vec(),
+      anchor.subject_public_key_info.to_vec(),
+      anchor.name_constraints.as_ref().map(|der| der.to_vec()),
+    )
+  }));
+  let mut config = ClientConfig::builder()
+    .with_safe_defaults()
+    .with_root_certificates(roots.clone())
+    .with_no_client_auth();
+  if let Some(ic_allowlist) = unsafely_ignore_certificate_errors {
+    config.dangerous().set_certificate_verifier(Arc::new(
+      NoCertificateVerification {
+        ic_allowlist,
+        default_verifier: WebPkiVerifier::new(roots, None),
+      },
+    ));
+  }
+  Arc::new(config)
+}
+
+/// Like `deno_tls::NoCertificateVerification`, skips the verification of the
+/// certificates of all servers when the allowlist is empty, or else of the
+/// servers in it.
+struct NoCertificateVerification {
+  ic_allowlist: Vec<String>,
+  default_verifier: WebPkiVerifier,
+}
+
+impl ServerCertVerifier for NoCertificateVerification {
+  fn verify_server_cert(
+    &self,
+    end_entity: &Certificate,
+    intermediates: &[Certificate],
+    server_name: &ServerName,
+    scts: &mut dyn Iterator<Item = &[u8]>,
+    ocsp_response: &[u8],
+    now: SystemTime,
+  ) -> Result<ServerCertVerified, Error> {
+    if self.ic_allowlist.is_empty() {
+      return Ok(ServerCertVerified::assertion());
+    }
+    let dns_name_or_ip_address = match server_name {
+      ServerName::DnsName(dns_name) => dns_name.as_ref().to_owned(),
+      ServerName::IpAddress(ip_address) => ip_address.to_string(),
+      _ => {
+        return Err(Error::General("Unknown `ServerName` variant".to_string()))
+      }
+    };
+    if self.ic_allowlist.contains(&dns_name_or_ip_address) {
+      Ok(ServerCertVerified::assertion())
+    } else {
+      self.default_verifier.verify_server_cert(
+        end_entity,
+        intermediates,
+        server_name,
+        scts,
+        ocsp_response,
+        now,
+      )
+    }
+  }
+}
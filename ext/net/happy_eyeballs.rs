@@ -0,0 +1,256 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Connecting to dual-stack hosts by racing their addresses, as described in
+//! RFC 8305 ("Happy Eyeballs Version 2").
+
+use std::collections::VecDeque;
+use std::io;
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use deno_core::error::custom_error;
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_core::futures::stream::FuturesUnordered;
+use deno_core::futures::StreamExt;
+use serde::Deserialize;
+use tokio::net::TcpSocket;
+use tokio::net::TcpStream;
+use tokio::time::Instant;
+
+/// The delay after which the next address is tried while the previous
+/// attempt is still pending, as recommended by RFC 8305.
+pub const DEFAULT_CONNECTION_ATTEMPT_DELAY: Duration =
+  Duration::from_millis(250);
+
+/// The local end of outbound connections, for hosts with several interfaces
+/// or addresses.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalBinding {
+  /// The local address to connect from. Only the addresses of its family
+  /// are connected to.
+  pub local_address: Option<IpAddr>,
+  /// The name of the network interface to connect through
+  /// (`SO_BINDTODEVICE`). Only supported on Linux.
+  pub interface: Option<String>,
+}
+
+impl LocalBinding {
+  fn is_default(&self) -> bool {
+    self.local_address.is_none() && self.interface.is_none()
+  }
+
+  async fn connect(&self, addr: SocketAddr) -> io::Result<TcpStream> {
+    if self.is_default() {
+      return TcpStream::connect(addr).await;
+    }
+    let socket = if addr.is_ipv4() {
+      TcpSocket::new_v4()?
+    } else {
+      TcpSocket::new_v6()?
+    };
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    if let Some(interface) = &self.interface {
+      socket2::SockRef::from(&socket)
+        .bind_device(Some(interface.as_bytes()))?;
+    }
+    if let Some(local_address) = self.local_address {
+      socket.bind(SocketAddr::new(local_address, 0))?;
+    }
+    socket.connect(addr).await
+  }
+}
+
+/// Connects to the first of `addrs` that accepts a connection. A new attempt
+/// starts whenever the last one fails or hasn't succeeded within `delay`,
+/// so an unreachable address only stalls the connection by that much.
+pub async fn connect(
+  addrs: impl IntoIterator<Item = SocketAddr>,
+  delay: Duration,
+) -> Result<TcpStream, AnyError> {
+  connect_from(addrs, delay, &LocalBinding::default()).await
+}
+
+/// Like [`connect`], but connects from the given local address or interface.
+pub async fn connect_from(
+  addrs: impl IntoIterator<Item = SocketAddr>,
+  delay: Duration,
+  binding: &LocalBinding,
+) -> Result<TcpStream, AnyError> {
+  if cfg!(not(any(target_os = "linux", target_os = "android")))
+    && binding.interface.is_some()
+  {
+    return Err(custom_error(
+      "NotSupported",
+      "Binding to a network interface is only supported on Linux",
+    ));
+  }
+  let mut addrs = match binding.local_address {
+    Some(local_address) => interleave_families(
+      addrs
+        .into_iter()
+        .filter(|addr| addr.is_ipv4() == local_address.is_ipv4()),
+    ),
+    None => interleave_families(addrs),
+  };
+  let mut attempts = FuturesUnordered::new();
+  let mut last_err = None;
+
+  let next_attempt = tokio::time::sleep(Duration::ZERO);
+  tokio::pin!(next_attempt);
+  loop {
+    tokio::select! {
+      biased;
+      Some(result) = attempts.next() => match result {
+        Ok(stream) => return Ok(stream),
+        Err(err) => {
+          last_err = Some(err);
+          // Don't wait for the delay when there's nothing else in flight.
+          if attempts.is_empty() {
+            next_attempt.as_mut().reset(Instant::now());
+          }
+        }
+      },
+      _ = &mut next_attempt, if !addrs.is_empty() => {
+        let addr = addrs.pop_front().unwrap();
+        attempts.push(binding.connect(addr));
+        next_attempt.as_mut().reset(Instant::now() + delay);
+      },
+      else => {
+        return Err(match last_err {
+          Some(err) => err.into(),
+          None if binding.local_address.is_some() => generic_error(
+            "No resolved address found of the family of the local address",
+          ),
+          None => generic_error("No resolved address found"),
+        });
+      }
+    }
+  }
+}
+
+/// Alternates between IPv6 and IPv4 addresses, starting with the family of
+/// the first one and otherwise keeping the order of the resolver.
+fn interleave_families(
+  addrs: impl IntoIterator<Item = SocketAddr>,
+) -> VecDeque<SocketAddr> {
+  let mut addrs = addrs.into_iter().peekable();
+  let Some(first) = addrs.peek().copied() else {
+    return VecDeque::new();
+  };
+  let (mut preferred, mut fallback): (VecDeque<_>, VecDeque<_>) =
+    addrs.partition(|addr| addr.is_ipv6() == first.is_ipv6());
+
+  let mut interleaved =
+    VecDeque::with_capacity(preferred.len() + fallback.len());
+  loop {
+    match (preferred.pop_front(), fallback.pop_front()) {
+      (None, None) => return interleaved,
+      (a, b) => interleaved.extend(a.into_iter().chain(b)),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io;
+
+  fn addrs(addrs: &[&str]) -> Vec<SocketAddr> {
+    addrs.iter().map(|addr| addr.parse().unwrap()).collect()
+  }
+
+  #[test]
+  fn interleaves_families() {
+    let sorted = interleave_families(addrs(&[
+      "[::1]:80",
+      "[::2]:80",
+      "[::3]:80",
+      "127.0.0.1:80",
+      "127.0.0.2:80",
+    ]));
+    assert_eq!(
+      Vec::from(sorted),
+      addrs(&[
+        "[::1]:80",
+        "127.0.0.1:80",
+        "[::2]:80",
+        "127.0.0.2:80",
+        "[::3]:80",
+      ])
+    );
+
+    let sorted = interleave_families(addrs(&["127.0.0.1:80", "[::1]:80"]));
+    assert_eq!(Vec::from(sorted), addrs(&["127.0.0.1:80", "[::1]:80"]));
+  }
+
+  #[tokio::test]
+  async fn connects_after_failed_attempts() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    // Nothing listens on the port of a dropped listener.
+    let closed = {
+      let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+      listener.local_addr().unwrap()
+    };
+
+    // Failures start the next attempt right away, way before the delay.
+    let stream = tokio::time::timeout(
+      Duration::from_secs(5),
+      connect([closed, closed, addr], Duration::from_secs(60)),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(stream.peer_addr().unwrap(), addr);
+
+    let err = connect([closed], DEFAULT_CONNECTION_ATTEMPT_DELAY)
+      .await
+      .unwrap_err();
+    assert_eq!(
+      err.downcast_ref::<io::Error>().unwrap().kind(),
+      io::ErrorKind::ConnectionRefused
+    );
+    let err = connect([], DEFAULT_CONNECTION_ATTEMPT_DELAY)
+      .await
+      .unwrap_err();
+    assert_eq!(err.to_string(), "No resolved address found");
+  }
+
+  #[tokio::test]
+  async fn connects_from_local_address() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let local_address = "127.0.0.1".parse().unwrap();
+    let binding = LocalBinding {
+      local_address: Some(local_address),
+      interface: None,
+    };
+
+    // The IPv6 address is skipped, as the local address is IPv4
+    let stream = connect_from(
+      addrs(&["[::1]:1"]).into_iter().chain([addr]),
+      DEFAULT_CONNECTION_ATTEMPT_DELAY,
+      &binding,
+    )
+    .await
+    .unwrap();
+    assert_eq!(stream.local_addr().unwrap().ip(), local_address);
+    let (_, peer_addr) = listener.accept().await.unwrap();
+    assert_eq!(peer_addr, stream.local_addr().unwrap());
+
+    let err = connect_from(
+      addrs(&["[::1]:1"]),
+      DEFAULT_CONNECTION_ATTEMPT_DELAY,
+      &binding,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "No resolved address found of the family of the local address"
+    );
+  }
+}
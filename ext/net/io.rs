@@ -110,22 +110,20 @@ impl Resource for TcpStreamResource {
 
 impl TcpStreamResource {
   pub fn set_nodelay(self: Rc<Self>, nodelay: bool) -> Result<(), AnyError> {
-    self.map_socket(Box::new(move |socket| Ok(socket.set_nodelay(nodelay)?)))
+    self.map_socket(move |socket| Ok(socket.set_nodelay(nodelay)?))
   }
 
   pub fn set_keepalive(
     self: Rc<Self>,
     keepalive: bool,
   ) -> Result<(), AnyError> {
-    self
-      .map_socket(Box::new(move |socket| Ok(socket.set_keepalive(keepalive)?)))
+    self.map_socket(move |socket| Ok(socket.set_keepalive(keepalive)?))
   }
 
-  #[allow(clippy::type_complexity)]
-  fn map_socket(
+  pub fn map_socket<R>(
     self: Rc<Self>,
-    map: Box<dyn FnOnce(SockRef) -> Result<(), AnyError>>,
-  ) -> Result<(), AnyError> {
+    map: impl FnOnce(SockRef) -> Result<R, AnyError>,
+  ) -> Result<R, AnyError> {
     if let Some(wr) = RcRef::map(self, |r| &r.wr).try_borrow() {
       let stream = wr.as_ref().as_ref();
       let socket = socket2::SockRef::from(stream);
@@ -18,8 +18,19 @@ declare namespace Deno {
     path: string;
   }
 
+  /** The address of a vsock (`AF_VSOCK`) socket, used to communicate between
+   * virtual machines and their host.
+   *
+   * @category Network */
+  export interface VsockAddr {
+    transport: "vsock";
+    /** The context ID of the virtual machine or host. */
+    cid: number;
+    port: number;
+  }
+
   /** @category Network */
-  export type Addr = NetAddr | UnixAddr;
+  export type Addr = NetAddr | UnixAddr | VsockAddr;
 
   /** A generic network listener for stream-oriented protocols.
    *
@@ -53,13 +64,72 @@ declare namespace Deno {
    *
    * @category Network
    */
-  export type TlsListener = Listener<TlsConn, NetAddr>;
+  export interface TlsListener extends Listener<TlsConn, NetAddr> {
+    /** Replaces the certificate and key of the listener, for example after
+     * they were renewed. Connections accepted from now on use the new ones,
+     * while connections that are already established are not interrupted.
+     * The ALPN protocols of the listener are kept, while its
+     * {@linkcode ListenTlsOptions.sniCertificates} are replaced as well.
+     *
+     * ```ts
+     * const listener = Deno.listenTls({ port: 443, cert, key });
+     * // ...
+     * listener.setCertificate({ cert: renewedCert, key: renewedKey });
+     * ```
+     */
+    setCertificate(
+      options:
+        & TlsCertifiedKeyPem
+        & Pick<ListenTlsOptions, "ocspResponse" | "sniCertificates">,
+    ): void;
+    /** Sets the given options of the listening socket, which the accepted
+     * connections inherit, leaving the others as they are. */
+    setSocketOptions(
+      options: Omit<TcpListenerSocketOptions, "reusePort">,
+    ): void;
+    /** Returns the current options of the listening socket. */
+    getSocketOptions(): TcpListenerSocketOptions;
+  }
 
   /** Specialized listener that accepts TCP connections.
    *
    * @category Network
    */
-  export type TcpListener = Listener<TcpConn, NetAddr>;
+  export interface TcpListener extends Listener<TcpConn, NetAddr> {
+    /** Sets the given options of the listening socket, which the accepted
+     * connections inherit, leaving the others as they are.
+     *
+     * ```ts
+     * const listener = Deno.listen({ port: 8000, reusePort: true });
+     * listener.setSocketOptions({ recvBufferSize: 1 << 20 });
+     * ```
+     */
+    setSocketOptions(
+      options: Omit<TcpListenerSocketOptions, "reusePort">,
+    ): void;
+    /** Returns the current options of the listening socket. Options that are
+     * not supported on this platform are `undefined`. */
+    getSocketOptions(): TcpListenerSocketOptions;
+  }
+
+  /** Options of a TCP listener, which can be set with
+   * {@linkcode TcpListener.setSocketOptions}.
+   *
+   * @category Network */
+  export interface TcpListenerSocketOptions {
+    /** Whether other sockets can listen on the same port (`SO_REUSEPORT`),
+     * which is set with the `reusePort` option of {@linkcode Deno.listen}.
+     * Not supported on Windows. */
+    readonly reusePort?: boolean;
+    /** The size of the receive buffer in bytes (`SO_RCVBUF`), which also
+     * determines the window scaling of the accepted connections. */
+    recvBufferSize?: number;
+    /** The size of the send buffer in bytes (`SO_SNDBUF`). */
+    sendBufferSize?: number;
+    /** The type-of-service field of the IP packets (`IP_TOS`), or the traffic
+     * class of IPv6 packets (`IPV6_TCLASS`). */
+    tos?: number;
+  }
 
   /** Specialized listener that accepts Unix connections.
    *
@@ -67,6 +137,12 @@ declare namespace Deno {
    */
   export type UnixListener = Listener<UnixConn, UnixAddr>;
 
+  /** Specialized listener that accepts vsock connections.
+   *
+   * @category Network
+   */
+  export type VsockListener = Listener<VsockConn, VsockAddr>;
+
   /** @category Network */
   export interface Conn<A extends Addr = Addr> extends Disposable {
     /** Read the incoming data from the connection into an array buffer (`p`).
@@ -155,6 +231,11 @@ declare namespace Deno {
      * not happened yet. Calling this method is optional; the TLS handshake
      * will be completed automatically as soon as data is sent or received. */
     handshake(): Promise<TlsHandshakeInfo>;
+    /** Sets the given options of the TCP socket, leaving the others as they
+     * are. See {@linkcode TcpConn.setSocketOptions}. */
+    setSocketOptions(options: TcpSocketOptions): void;
+    /** Returns the current options of the TCP socket. */
+    getSocketOptions(): TcpSocketOptions;
   }
 
   /** @category Network */
@@ -223,6 +304,39 @@ declare namespace Deno {
     options: UnixListenOptions & { transport: "unix" },
   ): UnixListener;
 
+  /** Options which can be set when opening a vsock listener via
+   * {@linkcode Deno.listen}.
+   *
+   * @category Network
+   */
+  export interface VsockListenOptions {
+    /** The context ID to listen on. Use `0xFFFFFFFF` (`VMADDR_CID_ANY`) to
+     * accept connections addressed to any context ID of this machine. */
+    cid: number;
+    /** The port to listen on. */
+    port: number;
+  }
+
+  /** Listen announces on a vsock address, accepting connections from virtual
+   * machines or their host.
+   *
+   * ```ts
+   * const listener = Deno.listen({ cid: 0xFFFFFFFF, port: 5000, transport: "vsock" })
+   * ```
+   *
+   * Only supported on Linux and macOS.
+   *
+   * Requires `allow-net` permission for the vsock address, like
+   * `--allow-net=vsock:<cid>:<port>`.
+   *
+   * @tags allow-net
+   * @category Network
+   */
+  // deno-lint-ignore adjacent-overload-signatures
+  export function listen(
+    options: VsockListenOptions & { transport: "vsock" },
+  ): VsockListener;
+
   /**
    * Provides certified key material from strings. The key material is provided in
    * `PEM`-format (Privacy Enhanced Mail, https://www.rfc-editor.org/rfc/rfc1422) which can be identified by having
@@ -249,6 +363,24 @@ declare namespace Deno {
     cert: string;
   }
 
+  /**
+   * Provides a certified key from a PKCS#12 archive, also known as a `.p12`
+   * or `.pfx` file. Archives encrypted with AES (the default of OpenSSL 3)
+   * and with the legacy 3DES and RC2 encryption are supported.
+   *
+   * @category Network
+   */
+  export interface TlsCertifiedKeyPkcs12 {
+    /** The format of this key material, which must be PKCS#12. */
+    keyFormat: "pkcs12";
+    /** The archive holding the private key and its certificate chain. */
+    pkcs12: Uint8Array;
+    /** The password protecting the archive.
+     *
+     * @default {""} */
+    password?: string;
+  }
+
   /** @category Network */
   export interface ListenTlsOptions extends TcpListenOptions {
     transport?: "tcp";
@@ -258,6 +390,29 @@ declare namespace Deno {
      * TLS handshake.
      */
     alpnProtocols?: string[];
+    /** A DER-encoded OCSP response for the certificate, which is stapled to
+     * handshakes so that clients don't need to contact the OCSP responder of
+     * the certificate authority themselves. Refresh it with
+     * {@linkcode TlsListener.setCertificate} before it expires. */
+    ocspResponse?: Uint8Array;
+    /** The certificates to serve to the clients that ask for a server name
+     * (SNI) in their handshake, by server name. A name may start with a `*.`
+     * wildcard label, which matches one label. Other clients are served the
+     * certificate of the listener.
+     *
+     * ```ts
+     * const listener = Deno.listenTls({
+     *   port: 443,
+     *   cert,
+     *   key,
+     *   sniCertificates: {
+     *     "example.com": { cert: exampleCert, key: exampleKey },
+     *     "*.example.com": { cert: wildcardCert, key: wildcardKey },
+     *   },
+     * });
+     * ```
+     */
+    sniCertificates?: Record<string, TlsCertifiedKeyPem>;
   }
 
   /** Listen announces on the local transport address over TLS (transport layer
@@ -290,6 +445,38 @@ declare namespace Deno {
      * @default {"127.0.0.1"} */
     hostname?: string;
     transport?: "tcp";
+    /** When the host resolves to several addresses, how long to wait, in
+     * milliseconds, for a connection to one of them before also trying the
+     * next one. The addresses alternate between IPv6 and IPv4, so a broken
+     * network of either family only delays connecting by this much.
+     *
+     * @default {250} */
+    happyEyeballsDelay?: number;
+    /** The local IP address to connect from, on hosts with several
+     * addresses. Only the addresses of the host of the same family are
+     * connected to. */
+    localAddress?: string;
+    /** The name of the network interface to connect through, e.g. `"eth1"`,
+     * on hosts with several interfaces. Only supported on Linux. */
+    interface?: string;
+    /** Check the certificate of the server against certificate revocation
+     * lists. */
+    revocation?: RevocationCheckOptions;
+  }
+
+  /** Options to check certificates against certificate revocation lists
+   * (CRLs).
+   *
+   * @category Network */
+  export interface RevocationCheckOptions {
+    /** The certificate revocation lists, in PEM format. */
+    crls: string[];
+    /** What to do with a certificate whose issuer has no CRL in `crls`:
+     * reject it with `"hard-fail"`, or accept it with `"soft-fail"`.
+     * Certificates listed as revoked are always rejected.
+     *
+     * @default {"hard-fail"} */
+    mode?: "hard-fail" | "soft-fail";
   }
 
   /**
@@ -320,6 +507,47 @@ declare namespace Deno {
     setNoDelay(noDelay?: boolean): void;
     /** Enable/disable keep-alive functionality. */
     setKeepAlive(keepAlive?: boolean): void;
+    /** Sets the given options of the socket, leaving the others as they are.
+     *
+     * ```ts
+     * const conn = await Deno.connect({ hostname: "example.com", port: 80 });
+     * conn.setSocketOptions({ noDelay: true, linger: 0, userTimeout: 10_000 });
+     * ```
+     */
+    setSocketOptions(options: TcpSocketOptions): void;
+    /** Returns the current options of the socket. Options that are not
+     * supported on this platform or for the address family of the socket are
+     * `undefined`. */
+    getSocketOptions(): TcpSocketOptions;
+  }
+
+  /** Options of a TCP socket, which can be set with
+   * {@linkcode TcpConn.setSocketOptions}.
+   *
+   * @category Network */
+  export interface TcpSocketOptions {
+    /** Whether Nagle's algorithm is disabled (`TCP_NODELAY`). */
+    noDelay?: boolean;
+    /** Whether keep-alive probes are sent (`SO_KEEPALIVE`). */
+    keepAlive?: boolean;
+    /** How many seconds closing the connection waits for unsent data to be
+     * sent (`SO_LINGER`). With 0, the connection is reset when closed. `null`
+     * turns lingering off. */
+    linger?: number | null;
+    /** The size of the receive buffer in bytes (`SO_RCVBUF`). The operating
+     * system may adjust it, e.g. Linux doubles it. */
+    recvBufferSize?: number;
+    /** The size of the send buffer in bytes (`SO_SNDBUF`). The operating
+     * system may adjust it, e.g. Linux doubles it. */
+    sendBufferSize?: number;
+    /** How many milliseconds sent data may remain unacknowledged before the
+     * connection is closed (`TCP_USER_TIMEOUT`). 0 uses the default of the
+     * system. Only supported on Linux. */
+    userTimeout?: number;
+    /** The type-of-service field of the IP packets (`IP_TOS`), or the traffic
+     * class of IPv6 packets (`IPV6_TCLASS`), e.g. a DSCP value shifted left
+     * by 2. Not supported on IPv6 connections on Windows. */
+    tos?: number;
   }
 
   /** @category Network */
@@ -350,6 +578,36 @@ declare namespace Deno {
   // deno-lint-ignore adjacent-overload-signatures
   export function connect(options: UnixConnectOptions): Promise<UnixConn>;
 
+  /** @category Network */
+  export interface VsockConnectOptions {
+    transport: "vsock";
+    /** The context ID of the virtual machine or host to connect to, e.g. `2`
+     * (`VMADDR_CID_HOST`) to reach the host from inside a virtual machine. */
+    cid: number;
+    /** The port to connect to. */
+    port: number;
+  }
+
+  /** @category Network */
+  export interface VsockConn extends Conn<VsockAddr> {}
+
+  /** Connects to a vsock address, and resolves to the connection.
+   *
+   * ```ts
+   * const conn = await Deno.connect({ cid: 2, port: 5000, transport: "vsock" });
+   * ```
+   *
+   * Only supported on Linux and macOS.
+   *
+   * Requires `allow-net` permission for the vsock address, like
+   * `--allow-net=vsock:<cid>:<port>`.
+   *
+   * @tags allow-net
+   * @category Network
+   */
+  // deno-lint-ignore adjacent-overload-signatures
+  export function connect(options: VsockConnectOptions): Promise<VsockConn>;
+
   /** @category Network */
   export interface ConnectTlsOptions {
     /** The port to connect to. */
@@ -368,6 +626,20 @@ declare namespace Deno {
      * TLS handshake.
      */
     alpnProtocols?: string[];
+    /** When the host resolves to several addresses, how long to wait, in
+     * milliseconds, for a connection to one of them before also trying the
+     * next one. The addresses alternate between IPv6 and IPv4, so a broken
+     * network of either family only delays connecting by this much.
+     *
+     * @default {250} */
+    happyEyeballsDelay?: number;
+    /** The local IP address to connect from, on hosts with several
+     * addresses. Only the addresses of the host of the same family are
+     * connected to. */
+    localAddress?: string;
+    /** The name of the network interface to connect through, e.g. `"eth1"`,
+     * on hosts with several interfaces. Only supported on Linux. */
+    interface?: string;
   }
 
   /** Establishes a secure connection over TLS (transport layer security) using
@@ -380,6 +652,10 @@ declare namespace Deno {
    * Mutual TLS (mTLS or client certificates) are supported by providing a
    * `key` and `cert` in the options as PEM-encoded strings.
    *
+   * Connections without `caCerts` or a client certificate resume the TLS
+   * session of the last connection to the same server when possible, which
+   * saves a round trip of the handshake.
+   *
    * ```ts
    * const caCert = await Deno.readTextFile("./certs/my_custom_root_CA.pem");
    * const conn1 = await Deno.connectTls({ port: 80 });
@@ -1,10 +1,14 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+mod dns_tls;
+pub mod happy_eyeballs;
 pub mod io;
 pub mod ops;
 pub mod ops_tls;
 #[cfg(unix)]
 pub mod ops_unix;
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+pub mod ops_vsock;
 pub mod raw;
 pub mod resolve_addr;
 mod tcp;
@@ -26,6 +30,12 @@ pub trait NetPermissions {
     host: &(T, Option<u16>),
     api_name: &str,
   ) -> Result<(), AnyError>;
+  fn check_vsock(
+    &mut self,
+    cid: u32,
+    port: u32,
+    api_name: &str,
+  ) -> Result<(), AnyError>;
   #[must_use = "the resolved return value to mitigate time-of-check to time-of-use issues"]
   fn check_read(
     &mut self,
@@ -56,6 +66,18 @@ impl NetPermissions for deno_permissions::PermissionsContainer {
     deno_permissions::PermissionsContainer::check_net(self, host, api_name)
   }
 
+  #[inline(always)]
+  fn check_vsock(
+    &mut self,
+    cid: u32,
+    port: u32,
+    api_name: &str,
+  ) -> Result<(), AnyError> {
+    deno_permissions::PermissionsContainer::check_net_vsock(
+      self, cid, port, api_name,
+    )
+  }
+
   #[inline(always)]
   fn check_read(
     &mut self,
@@ -134,16 +156,26 @@ deno_core::extension!(deno_net,
     ops::op_net_leave_multi_v6_udp,
     ops::op_net_set_multi_loopback_udp,
     ops::op_net_set_multi_ttl_udp,
+    ops::op_net_set_ttl_udp,
+    ops::op_net_set_broadcast_udp,
+    ops::op_net_connect_udp<P>,
     ops::op_dns_resolve<P>,
     ops::op_set_nodelay,
     ops::op_set_keepalive,
+    ops::op_net_set_tcp_socket_options,
+    ops::op_net_get_tcp_socket_options,
+    ops::op_net_set_tcp_listener_socket_options,
+    ops::op_net_get_tcp_listener_socket_options,
 
     ops_tls::op_tls_key_null,
     ops_tls::op_tls_key_static,
+    ops_tls::op_tls_key_pkcs12,
     ops_tls::op_tls_cert_resolver_create,
     ops_tls::op_tls_cert_resolver_poll,
     ops_tls::op_tls_cert_resolver_resolve,
     ops_tls::op_tls_cert_resolver_resolve_error,
+    ops_tls::op_tls_listener_config_create,
+    ops_tls::op_tls_listener_config_set_keys,
     ops_tls::op_tls_start<P>,
     ops_tls::op_net_connect_tls<P>,
     ops_tls::op_net_listen_tls<P>,
@@ -157,6 +189,10 @@ deno_core::extension!(deno_net,
     ops_unix::op_node_unstable_net_listen_unixpacket<P>,
     ops_unix::op_net_recv_unixpacket,
     ops_unix::op_net_send_unixpacket<P>,
+
+    ops_vsock::op_net_accept_vsock,
+    ops_vsock::op_net_connect_vsock<P>,
+    ops_vsock::op_net_listen_vsock<P>,
   ],
   esm = [ "01_net.js", "02_tls.js" ],
   options = {
@@ -173,46 +209,66 @@ deno_core::extension!(deno_net,
   },
 );
 
+/// Defines an op that fails with `Unsupported` on platforms lacking the
+/// underlying socket type.
+#[allow(unused_macros)]
+macro_rules! stub_op {
+  ($platforms:literal, $name:ident) => {
+    #[op2(fast)]
+    pub fn $name() -> Result<(), std::io::Error> {
+      let error_msg = format!(
+        "Operation `{:?}` not supported on {} platforms.",
+        stringify!($name),
+        $platforms
+      );
+      Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        error_msg,
+      ))
+    }
+  };
+  ($platforms:literal, $name:ident<P>) => {
+    #[op2(fast)]
+    pub fn $name<P: NetPermissions>() -> Result<(), std::io::Error> {
+      let error_msg = format!(
+        "Operation `{:?}` not supported on {} platforms.",
+        stringify!($name),
+        $platforms
+      );
+      Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        error_msg,
+      ))
+    }
+  };
+}
+
 /// Stub ops for non-unix platforms.
 #[cfg(not(unix))]
 mod ops_unix {
   use crate::NetPermissions;
   use deno_core::op2;
 
-  macro_rules! stub_op {
-    ($name:ident) => {
-      #[op2(fast)]
-      pub fn $name() -> Result<(), std::io::Error> {
-        let error_msg = format!(
-          "Operation `{:?}` not supported on non-unix platforms.",
-          stringify!($name)
-        );
-        Err(std::io::Error::new(
-          std::io::ErrorKind::Unsupported,
-          error_msg,
-        ))
-      }
-    };
-    ($name:ident<P>) => {
-      #[op2(fast)]
-      pub fn $name<P: NetPermissions>() -> Result<(), std::io::Error> {
-        let error_msg = format!(
-          "Operation `{:?}` not supported on non-unix platforms.",
-          stringify!($name)
-        );
-        Err(std::io::Error::new(
-          std::io::ErrorKind::Unsupported,
-          error_msg,
-        ))
-      }
-    };
-  }
+  stub_op!("non-unix", op_net_accept_unix);
+  stub_op!("non-unix", op_net_connect_unix<P>);
+  stub_op!("non-unix", op_net_listen_unix<P>);
+  stub_op!("non-unix", op_net_listen_unixpacket<P>);
+  stub_op!("non-unix", op_node_unstable_net_listen_unixpacket<P>);
+  stub_op!("non-unix", op_net_recv_unixpacket);
+  stub_op!("non-unix", op_net_send_unixpacket<P>);
+}
+
+/// Stub ops for platforms without vsock support.
+#[cfg(not(any(
+  target_os = "linux",
+  target_os = "android",
+  target_os = "macos"
+)))]
+mod ops_vsock {
+  use crate::NetPermissions;
+  use deno_core::op2;
 
-  stub_op!(op_net_accept_unix);
-  stub_op!(op_net_connect_unix<P>);
-  stub_op!(op_net_listen_unix<P>);
-  stub_op!(op_net_listen_unixpacket<P>);
-  stub_op!(op_node_unstable_net_listen_unixpacket<P>);
-  stub_op!(op_net_recv_unixpacket);
-  stub_op!(op_net_send_unixpacket<P>);
+  stub_op!("non-Linux and non-macOS", op_net_accept_vsock);
+  stub_op!("non-Linux and non-macOS", op_net_connect_vsock<P>);
+  stub_op!("non-Linux and non-macOS", op_net_listen_vsock<P>);
 }
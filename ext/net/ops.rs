@@ -1,18 +1,28 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use crate::dns_tls;
+use crate::happy_eyeballs;
+use crate::happy_eyeballs::LocalBinding;
 use crate::io::TcpStreamResource;
+use crate::ops_tls::TlsListener;
+use crate::ops_tls::TlsStreamResource;
 use crate::raw::NetworkListenerResource;
 use crate::resolve_addr::resolve_addr;
 use crate::resolve_addr::resolve_addr_sync;
 use crate::tcp::TcpListener;
+use crate::DefaultTlsOptions;
 use crate::NetPermissions;
+use crate::UnsafelyIgnoreCertificateErrors;
 use deno_core::error::bad_resource;
 use deno_core::error::custom_error;
 use deno_core::error::generic_error;
+use deno_core::error::type_error;
 use deno_core::error::AnyError;
 use deno_core::op2;
 use deno_core::CancelFuture;
 
+use deno_core::url::Host;
+use deno_core::url::Url;
 use deno_core::AsyncRefCell;
 use deno_core::ByteString;
 use deno_core::CancelHandle;
@@ -26,6 +36,7 @@ use serde::Deserialize;
 use serde::Serialize;
 use socket2::Domain;
 use socket2::Protocol;
+use socket2::SockRef;
 use socket2::Socket;
 use socket2::Type;
 use std::borrow::Cow;
@@ -35,7 +46,7 @@ use std::net::Ipv6Addr;
 use std::net::SocketAddr;
 use std::rc::Rc;
 use std::str::FromStr;
-use tokio::net::TcpStream;
+use std::time::Duration;
 use tokio::net::UdpSocket;
 use trust_dns_proto::rr::rdata::caa::Value;
 use trust_dns_proto::rr::record_data::RData;
@@ -133,9 +144,53 @@ pub async fn op_net_recv_udp(
 pub async fn op_net_send_udp<NP>(
   state: Rc<RefCell<OpState>>,
   #[smi] rid: ResourceId,
-  #[serde] addr: IpAddr,
+  #[serde] addr: Option<IpAddr>,
   #[buffer] zero_copy: JsBuffer,
 ) -> Result<usize, AnyError>
+where
+  NP: NetPermissions + 'static,
+{
+  // Without an address, the datagram is sent to the peer the socket is
+  // connected to, which was checked by `op_net_connect_udp`.
+  let addr = match addr {
+    Some(addr) => {
+      {
+        let mut s = state.borrow_mut();
+        s.borrow_mut::<NP>().check_net(
+          &(&addr.hostname, Some(addr.port)),
+          "Deno.DatagramConn.send()",
+        )?;
+      }
+      let addr = resolve_addr(&addr.hostname, addr.port)
+        .await?
+        .next()
+        .ok_or_else(|| generic_error("No resolved address found"))?;
+      Some(addr)
+    }
+    None => None,
+  };
+
+  let resource = state
+    .borrow_mut()
+    .resource_table
+    .get::<UdpSocketResource>(rid)
+    .map_err(|_| bad_resource("Socket has been closed"))?;
+  let socket = RcRef::map(&resource, |r| &r.socket).borrow().await;
+  let nwritten = match addr {
+    Some(addr) => socket.send_to(&zero_copy, &addr).await?,
+    None => socket.send(&zero_copy).await?,
+  };
+
+  Ok(nwritten)
+}
+
+#[op2(async)]
+#[serde]
+pub async fn op_net_connect_udp<NP>(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+  #[serde] addr: IpAddr,
+) -> Result<IpAddr, AnyError>
 where
   NP: NetPermissions + 'static,
 {
@@ -143,7 +198,7 @@ where
     let mut s = state.borrow_mut();
     s.borrow_mut::<NP>().check_net(
       &(&addr.hostname, Some(addr.port)),
-      "Deno.DatagramConn.send()",
+      "Deno.DatagramConn.connect()",
     )?;
   }
   let addr = resolve_addr(&addr.hostname, addr.port)
@@ -157,9 +212,9 @@ where
     .get::<UdpSocketResource>(rid)
     .map_err(|_| bad_resource("Socket has been closed"))?;
   let socket = RcRef::map(&resource, |r| &r.socket).borrow().await;
-  let nwritten = socket.send_to(&zero_copy, &addr).await?;
+  socket.connect(addr).await?;
 
-  Ok(nwritten)
+  Ok(IpAddr::from(socket.peer_addr()?))
 }
 
 #[op2(async)]
@@ -275,6 +330,7 @@ pub async fn op_net_set_multi_loopback_udp(
 pub async fn op_net_set_multi_ttl_udp(
   state: Rc<RefCell<OpState>>,
   #[smi] rid: ResourceId,
+  is_v4_membership: bool,
   #[smi] ttl: u32,
 ) -> Result<(), AnyError> {
   let resource = state
@@ -284,7 +340,51 @@ pub async fn op_net_set_multi_ttl_udp(
     .map_err(|_| bad_resource("Socket has been closed"))?;
   let socket = RcRef::map(&resource, |r| &r.socket).borrow().await;
 
-  socket.set_multicast_ttl_v4(ttl)?;
+  if is_v4_membership {
+    socket.set_multicast_ttl_v4(ttl)?;
+  } else {
+    SockRef::from(&*socket).set_multicast_hops_v6(ttl)?;
+  }
+
+  Ok(())
+}
+
+#[op2(async)]
+pub async fn op_net_set_ttl_udp(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+  #[smi] ttl: u32,
+) -> Result<(), AnyError> {
+  let resource = state
+    .borrow_mut()
+    .resource_table
+    .get::<UdpSocketResource>(rid)
+    .map_err(|_| bad_resource("Socket has been closed"))?;
+  let socket = RcRef::map(&resource, |r| &r.socket).borrow().await;
+
+  if socket.local_addr()?.is_ipv4() {
+    socket.set_ttl(ttl)?;
+  } else {
+    SockRef::from(&*socket).set_unicast_hops_v6(ttl)?;
+  }
+
+  Ok(())
+}
+
+#[op2(async)]
+pub async fn op_net_set_broadcast_udp(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+  broadcast: bool,
+) -> Result<(), AnyError> {
+  let resource = state
+    .borrow_mut()
+    .resource_table
+    .get::<UdpSocketResource>(rid)
+    .map_err(|_| bad_resource("Socket has been closed"))?;
+  let socket = RcRef::map(&resource, |r| &r.socket).borrow().await;
+
+  socket.set_broadcast(broadcast)?;
 
   Ok(())
 }
@@ -294,17 +394,27 @@ pub async fn op_net_set_multi_ttl_udp(
 pub async fn op_net_connect_tcp<NP>(
   state: Rc<RefCell<OpState>>,
   #[serde] addr: IpAddr,
+  #[serde] happy_eyeballs_delay: Option<u64>,
+  #[serde] local_binding: LocalBinding,
 ) -> Result<(ResourceId, IpAddr, IpAddr), AnyError>
 where
   NP: NetPermissions + 'static,
 {
-  op_net_connect_tcp_inner::<NP>(state, addr).await
+  op_net_connect_tcp_inner::<NP>(
+    state,
+    addr,
+    happy_eyeballs_delay,
+    local_binding,
+  )
+  .await
 }
 
 #[inline]
 pub async fn op_net_connect_tcp_inner<NP>(
   state: Rc<RefCell<OpState>>,
   addr: IpAddr,
+  happy_eyeballs_delay: Option<u64>,
+  local_binding: LocalBinding,
 ) -> Result<(ResourceId, IpAddr, IpAddr), AnyError>
 where
   NP: NetPermissions + 'static,
@@ -316,11 +426,15 @@ where
       .check_net(&(&addr.hostname, Some(addr.port)), "Deno.connect()")?;
   }
 
-  let addr = resolve_addr(&addr.hostname, addr.port)
-    .await?
-    .next()
-    .ok_or_else(|| generic_error("No resolved address found"))?;
-  let tcp_stream = TcpStream::connect(&addr).await?;
+  let addrs = resolve_addr(&addr.hostname, addr.port).await?;
+  let tcp_stream = happy_eyeballs::connect_from(
+    addrs,
+    happy_eyeballs_delay
+      .map(Duration::from_millis)
+      .unwrap_or(happy_eyeballs::DEFAULT_CONNECTION_ATTEMPT_DELAY),
+    &local_binding,
+  )
+  .await?;
   let local_addr = tcp_stream.local_addr()?;
   let remote_addr = tcp_stream.peer_addr()?;
 
@@ -374,17 +488,29 @@ where
     TcpListener::bind_direct(addr, reuse_port)
   }?;
   let local_addr = listener.local_addr()?;
-  let listener_resource = NetworkListenerResource::new(listener);
+  let socket = listener.try_clone_socket()?;
+  let listener_resource =
+    NetworkListenerResource::with_socket(listener, socket);
   let rid = state.resource_table.add(listener_resource);
 
   Ok((rid, IpAddr::from(local_addr)))
 }
 
+/// The sizes of the buffers of a UDP socket, which are left to the system
+/// when `None`.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UdpBufferSizes {
+  recv_buffer_size: Option<usize>,
+  send_buffer_size: Option<usize>,
+}
+
 fn net_listen_udp<NP>(
   state: &mut OpState,
   addr: IpAddr,
   reuse_address: bool,
   loopback: bool,
+  buffer_sizes: UdpBufferSizes,
 ) -> Result<(ResourceId, IpAddr), AnyError>
 where
   NP: NetPermissions + 'static,
@@ -421,6 +547,14 @@ where
     #[cfg(all(unix, not(target_os = "linux")))]
     socket_tmp.set_reuse_port(true)?;
   }
+  // Set before binding, so that no datagram is dropped for lack of space
+  // before the buffer is enlarged.
+  if let Some(size) = buffer_sizes.recv_buffer_size {
+    socket_tmp.set_recv_buffer_size(size)?;
+  }
+  if let Some(size) = buffer_sizes.send_buffer_size {
+    socket_tmp.set_send_buffer_size(size)?;
+  }
   let socket_addr = socket2::SockAddr::from(addr);
   socket_tmp.bind(&socket_addr)?;
   socket_tmp.set_nonblocking(true)?;
@@ -454,12 +588,13 @@ pub fn op_net_listen_udp<NP>(
   #[serde] addr: IpAddr,
   reuse_address: bool,
   loopback: bool,
+  #[serde] buffer_sizes: UdpBufferSizes,
 ) -> Result<(ResourceId, IpAddr), AnyError>
 where
   NP: NetPermissions + 'static,
 {
   super::check_unstable(state, "Deno.listenDatagram");
-  net_listen_udp::<NP>(state, addr, reuse_address, loopback)
+  net_listen_udp::<NP>(state, addr, reuse_address, loopback, buffer_sizes)
 }
 
 #[op2]
@@ -473,7 +608,13 @@ pub fn op_node_unstable_net_listen_udp<NP>(
 where
   NP: NetPermissions + 'static,
 {
-  net_listen_udp::<NP>(state, addr, reuse_address, loopback)
+  net_listen_udp::<NP>(
+    state,
+    addr,
+    reuse_address,
+    loopback,
+    UdpBufferSizes::default(),
+  )
 }
 
 #[derive(Serialize, Eq, PartialEq, Debug)]
@@ -533,6 +674,7 @@ pub struct ResolveAddrArgs {
 #[serde(rename_all = "camelCase")]
 pub struct ResolveDnsOption {
   name_server: Option<NameServer>,
+  encrypted_name_server: Option<EncryptedNameServer>,
 }
 
 fn default_port() -> u16 {
@@ -547,6 +689,83 @@ pub struct NameServer {
   port: u16,
 }
 
+/// A name server queried over DNS-over-TLS (`tls://host:853`) or
+/// DNS-over-HTTPS (`https://host/dns-query`).
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedNameServer {
+  url: String,
+  /// The name the certificate of the server is verified for, which is also
+  /// sent as SNI. Defaults to the host of the URL.
+  server_name: Option<String>,
+  /// The IP addresses of the server, so that its host name doesn't need to be
+  /// resolved with the system resolver. Required unless the host of the URL
+  /// is an IP address.
+  #[serde(default)]
+  bootstrap_addresses: Vec<String>,
+}
+
+impl EncryptedNameServer {
+  fn into_name_servers(self) -> Result<NameServerConfigGroup, AnyError> {
+    let invalid_url =
+      || type_error(format!("Invalid name server URL: {}", self.url));
+    let url = Url::parse(&self.url).map_err(|_| invalid_url())?;
+    let host = match url.host().ok_or_else(invalid_url)? {
+      Host::Domain(domain) => domain.to_string(),
+      Host::Ipv4(ip) => ip.to_string(),
+      Host::Ipv6(ip) => ip.to_string(),
+    };
+    let mut ips = self
+      .bootstrap_addresses
+      .iter()
+      .map(|addr| {
+        addr
+          .parse::<std::net::IpAddr>()
+          .map_err(|_| type_error(format!("Invalid bootstrap address: {addr}")))
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+    if ips.is_empty() {
+      match host.parse() {
+        Ok(ip) => ips.push(ip),
+        Err(_) => {
+          return Err(type_error(
+            "'bootstrapAddresses' are required when the name server URL has a host name",
+          ))
+        }
+      }
+    }
+    let server_name = self.server_name.unwrap_or(host);
+
+    match url.scheme() {
+      "tls" => Ok(NameServerConfigGroup::from_ips_tls(
+        &ips,
+        url.port().unwrap_or(853),
+        server_name,
+        true,
+      )),
+      "https" => {
+        // The resolver always queries the path of RFC 8484
+        let path = url.path();
+        if !matches!(path, "" | "/" | "/dns-query") || url.query().is_some() {
+          return Err(custom_error(
+            "NotSupported",
+            "DNS-over-HTTPS is only supported with the path '/dns-query'",
+          ));
+        }
+        Ok(NameServerConfigGroup::from_ips_https(
+          &ips,
+          url.port().unwrap_or(443),
+          server_name,
+          true,
+        ))
+      }
+      scheme => Err(type_error(format!(
+        "Unsupported name server URL scheme '{scheme}:', expected 'tls:' or 'https:'"
+      ))),
+    }
+  }
+}
+
 #[op2(async)]
 #[serde]
 pub async fn op_dns_resolve<NP>(
@@ -563,20 +782,46 @@ where
     cancel_rid,
   } = args;
 
-  let (config, opts) = if let Some(name_server) =
-    options.as_ref().and_then(|o| o.name_server.as_ref())
-  {
-    let group = NameServerConfigGroup::from_ips_clear(
-      &[name_server.ip_addr.parse()?],
-      name_server.port,
-      true,
-    );
-    (
-      ResolverConfig::from_parts(None, vec![], group),
-      ResolverOpts::default(),
-    )
-  } else {
-    system_conf::read_system_conf()?
+  let (name_server, encrypted_name_server) = match options {
+    Some(options) => (options.name_server, options.encrypted_name_server),
+    None => (None, None),
+  };
+  let (config, opts) = match (name_server, encrypted_name_server) {
+    (Some(_), Some(_)) => {
+      return Err(type_error(
+        "'nameServer' and 'encryptedNameServer' are mutually exclusive",
+      ))
+    }
+    (Some(name_server), None) => {
+      let group = NameServerConfigGroup::from_ips_clear(
+        &[name_server.ip_addr.parse()?],
+        name_server.port,
+        true,
+      );
+      (
+        ResolverConfig::from_parts(None, vec![], group),
+        ResolverOpts::default(),
+      )
+    }
+    (None, Some(encrypted_name_server)) => {
+      let client_config = {
+        let state = state.borrow();
+        dns_tls::create_client_config(
+          state.borrow::<DefaultTlsOptions>().root_cert_store()?,
+          state
+            .try_borrow::<UnsafelyIgnoreCertificateErrors>()
+            .and_then(|it| it.0.clone()),
+        )
+      };
+      let group = encrypted_name_server
+        .into_name_servers()?
+        .with_client_config(client_config);
+      (
+        ResolverConfig::from_parts(None, vec![], group),
+        ResolverOpts::default(),
+      )
+    }
+    (None, None) => system_conf::read_system_conf()?,
   };
 
   {
@@ -677,6 +922,284 @@ pub fn op_set_keepalive_inner(
   resource.set_keepalive(keepalive)
 }
 
+/// The options of a TCP socket, each of which is left as is when `None`.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TcpSocketOptions {
+  /// `TCP_NODELAY`
+  pub no_delay: Option<bool>,
+  /// `SO_KEEPALIVE`
+  pub keep_alive: Option<bool>,
+  /// `SO_LINGER`, in seconds. `Some(None)` turns it off.
+  #[serde(default, deserialize_with = "deserialize_some")]
+  pub linger: Option<Option<u64>>,
+  /// `SO_RCVBUF`, in bytes.
+  pub recv_buffer_size: Option<usize>,
+  /// `SO_SNDBUF`, in bytes.
+  pub send_buffer_size: Option<usize>,
+  /// `TCP_USER_TIMEOUT`, in milliseconds. Only supported on Linux.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub user_timeout: Option<u64>,
+  /// `IP_TOS`, or `IPV6_TCLASS` for IPv6 sockets.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub tos: Option<u32>,
+}
+
+/// Distinguishes `null` (`Some(None)`) from a missing field (`None`).
+fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+  T: Deserialize<'de>,
+  D: serde::Deserializer<'de>,
+{
+  T::deserialize(deserializer).map(Some)
+}
+
+impl TcpSocketOptions {
+  fn apply(&self, socket: &SockRef) -> Result<(), AnyError> {
+    if let Some(no_delay) = self.no_delay {
+      socket.set_nodelay(no_delay)?;
+    }
+    if let Some(keep_alive) = self.keep_alive {
+      socket.set_keepalive(keep_alive)?;
+    }
+    if let Some(linger) = self.linger {
+      socket.set_linger(linger.map(Duration::from_secs))?;
+    }
+    if let Some(size) = self.recv_buffer_size {
+      socket.set_recv_buffer_size(size)?;
+    }
+    if let Some(size) = self.send_buffer_size {
+      socket.set_send_buffer_size(size)?;
+    }
+    if let Some(user_timeout) = self.user_timeout {
+      #[cfg(any(target_os = "android", target_os = "linux"))]
+      socket.set_tcp_user_timeout(Some(Duration::from_millis(user_timeout)))?;
+      #[cfg(not(any(target_os = "android", target_os = "linux")))]
+      {
+        let _ = user_timeout;
+        return Err(custom_error(
+          "NotSupported",
+          "The userTimeout socket option is only supported on Linux",
+        ));
+      }
+    }
+    if let Some(tos) = self.tos {
+      set_tos(socket, tos)?;
+    }
+    Ok(())
+  }
+
+  fn read(socket: &SockRef) -> Result<Self, AnyError> {
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    let user_timeout = Some(
+      socket
+        .tcp_user_timeout()?
+        .map_or(0, |timeout| timeout.as_millis() as u64),
+    );
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    let user_timeout = None;
+    Ok(Self {
+      no_delay: Some(socket.nodelay()?),
+      keep_alive: Some(socket.keepalive()?),
+      linger: Some(socket.linger()?.map(|linger| linger.as_secs())),
+      recv_buffer_size: Some(socket.recv_buffer_size()?),
+      send_buffer_size: Some(socket.send_buffer_size()?),
+      user_timeout,
+      tos: tos(socket)?,
+    })
+  }
+}
+
+/// The options of a TCP listener, which the connections it accepts inherit,
+/// each of which is left as is when `None`.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TcpListenerSocketOptions {
+  /// `SO_REUSEPORT`, which is only read, as it's set when binding.
+  #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+  pub reuse_port: Option<bool>,
+  /// `SO_RCVBUF`, in bytes.
+  pub recv_buffer_size: Option<usize>,
+  /// `SO_SNDBUF`, in bytes.
+  pub send_buffer_size: Option<usize>,
+  /// `IP_TOS`, or `IPV6_TCLASS` for IPv6 sockets.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub tos: Option<u32>,
+}
+
+impl TcpListenerSocketOptions {
+  fn apply(&self, socket: &SockRef) -> Result<(), AnyError> {
+    if let Some(size) = self.recv_buffer_size {
+      socket.set_recv_buffer_size(size)?;
+    }
+    if let Some(size) = self.send_buffer_size {
+      socket.set_send_buffer_size(size)?;
+    }
+    if let Some(tos) = self.tos {
+      set_tos(socket, tos)?;
+    }
+    Ok(())
+  }
+
+  fn read(socket: &SockRef) -> Result<Self, AnyError> {
+    #[cfg(not(windows))]
+    let reuse_port = Some(socket.reuse_port()?);
+    #[cfg(windows)]
+    let reuse_port = None;
+    Ok(Self {
+      reuse_port,
+      recv_buffer_size: Some(socket.recv_buffer_size()?),
+      send_buffer_size: Some(socket.send_buffer_size()?),
+      tos: tos(socket)?,
+    })
+  }
+}
+
+fn set_tos(socket: &SockRef, tos: u32) -> Result<(), AnyError> {
+  if !socket.local_addr()?.is_ipv6() {
+    socket.set_tos(tos)?;
+    return Ok(());
+  }
+  #[cfg(unix)]
+  {
+    set_tclass_v6(socket, tos)?;
+    Ok(())
+  }
+  #[cfg(not(unix))]
+  Err(custom_error(
+    "NotSupported",
+    "The tos socket option is not supported on IPv6 sockets on Windows",
+  ))
+}
+
+fn tos(socket: &SockRef) -> Result<Option<u32>, AnyError> {
+  if !socket.local_addr()?.is_ipv6() {
+    return Ok(Some(socket.tos()?));
+  }
+  #[cfg(unix)]
+  return Ok(Some(tclass_v6(socket)?));
+  #[cfg(not(unix))]
+  Ok(None)
+}
+
+/// socket2 doesn't provide `IPV6_TCLASS` on all platforms.
+#[cfg(unix)]
+fn set_tclass_v6(socket: &SockRef, tclass: u32) -> std::io::Result<()> {
+  use std::os::fd::AsRawFd;
+  let value = tclass as libc::c_int;
+  // SAFETY: the value is a `c_int`, which is the type of the option.
+  let ret = unsafe {
+    libc::setsockopt(
+      socket.as_raw_fd(),
+      libc::IPPROTO_IPV6,
+      libc::IPV6_TCLASS,
+      &value as *const libc::c_int as *const libc::c_void,
+      std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+    )
+  };
+  if ret == -1 {
+    return Err(std::io::Error::last_os_error());
+  }
+  Ok(())
+}
+
+#[cfg(unix)]
+fn tclass_v6(socket: &SockRef) -> std::io::Result<u32> {
+  use std::os::fd::AsRawFd;
+  let mut value: libc::c_int = 0;
+  let mut len = std::mem::size_of::<libc::c_int>() as libc::socklen_t;
+  // SAFETY: the value is a `c_int`, which is the type of the option.
+  let ret = unsafe {
+    libc::getsockopt(
+      socket.as_raw_fd(),
+      libc::IPPROTO_IPV6,
+      libc::IPV6_TCLASS,
+      &mut value as *mut libc::c_int as *mut libc::c_void,
+      &mut len,
+    )
+  };
+  if ret == -1 {
+    return Err(std::io::Error::last_os_error());
+  }
+  Ok(value as u32)
+}
+
+/// Maps the socket of a TCP or TLS connection.
+fn map_conn_socket<R>(
+  state: &OpState,
+  rid: ResourceId,
+  map: impl FnOnce(SockRef) -> Result<R, AnyError>,
+) -> Result<R, AnyError> {
+  if let Ok(resource) = state.resource_table.get::<TlsStreamResource>(rid) {
+    return resource.map_socket(map);
+  }
+  let resource = state.resource_table.get::<TcpStreamResource>(rid)?;
+  resource.map_socket(map)
+}
+
+/// Maps the socket of a TCP or TLS listener.
+fn map_listener_socket<R>(
+  state: &OpState,
+  rid: ResourceId,
+  map: impl FnOnce(SockRef) -> Result<R, AnyError>,
+) -> Result<R, AnyError> {
+  fn sock_ref(socket: &Option<Socket>) -> Result<SockRef, AnyError> {
+    socket
+      .as_ref()
+      .map(SockRef::from)
+      .ok_or_else(|| generic_error("Unable to get resources"))
+  }
+
+  if let Ok(resource) = state
+    .resource_table
+    .get::<NetworkListenerResource<TlsListener>>(rid)
+  {
+    return map(sock_ref(&resource.socket)?);
+  }
+  let resource = state
+    .resource_table
+    .get::<NetworkListenerResource<TcpListener>>(rid)?;
+  map(sock_ref(&resource.socket)?)
+}
+
+#[op2]
+pub fn op_net_set_tcp_socket_options(
+  state: &mut OpState,
+  #[smi] rid: ResourceId,
+  #[serde] options: TcpSocketOptions,
+) -> Result<(), AnyError> {
+  map_conn_socket(state, rid, |socket| options.apply(&socket))
+}
+
+#[op2]
+#[serde]
+pub fn op_net_get_tcp_socket_options(
+  state: &mut OpState,
+  #[smi] rid: ResourceId,
+) -> Result<TcpSocketOptions, AnyError> {
+  map_conn_socket(state, rid, |socket| TcpSocketOptions::read(&socket))
+}
+
+#[op2]
+pub fn op_net_set_tcp_listener_socket_options(
+  state: &mut OpState,
+  #[smi] rid: ResourceId,
+  #[serde] options: TcpListenerSocketOptions,
+) -> Result<(), AnyError> {
+  map_listener_socket(state, rid, |socket| options.apply(&socket))
+}
+
+#[op2]
+#[serde]
+pub fn op_net_get_tcp_listener_socket_options(
+  state: &mut OpState,
+  #[smi] rid: ResourceId,
+) -> Result<TcpListenerSocketOptions, AnyError> {
+  map_listener_socket(state, rid, |socket| {
+    TcpListenerSocketOptions::read(&socket)
+  })
+}
+
 fn rdata_to_return_record(
   ty: RecordType,
 ) -> impl Fn(&RData) -> Result<Option<DnsReturnRecord>, AnyError> {
@@ -803,6 +1326,40 @@ mod tests {
   use trust_dns_proto::rr::record_data::RData;
   use trust_dns_proto::rr::Name;
 
+  #[test]
+  fn encrypted_name_servers() {
+    let name_servers = |url: &str, bootstrap_addresses: &[&str]| {
+      EncryptedNameServer {
+        url: url.to_string(),
+        server_name: None,
+        bootstrap_addresses: bootstrap_addresses
+          .iter()
+          .map(|addr| addr.to_string())
+          .collect(),
+      }
+      .into_name_servers()
+    };
+
+    let group = name_servers("tls://1.1.1.1", &[]).unwrap();
+    assert_eq!(group.len(), 1);
+    assert_eq!(group[0].socket_addr, "1.1.1.1:853".parse().unwrap());
+    assert_eq!(group[0].tls_dns_name.as_deref(), Some("1.1.1.1"));
+
+    let group = name_servers(
+      "https://dns.example/dns-query",
+      &["192.0.2.1", "2001:db8::1"],
+    )
+    .unwrap();
+    assert_eq!(group.len(), 2);
+    assert_eq!(group[1].socket_addr, "[2001:db8::1]:443".parse().unwrap());
+    assert_eq!(group[0].tls_dns_name.as_deref(), Some("dns.example"));
+
+    // A host name can't be resolved without the system resolver
+    assert!(name_servers("tls://dns.example", &[]).is_err());
+    assert!(name_servers("https://1.1.1.1/resolve", &[]).is_err());
+    assert!(name_servers("udp://1.1.1.1", &[]).is_err());
+  }
+
   #[test]
   fn rdata_to_return_record_a() {
     let func = rdata_to_return_record(RecordType::A);
@@ -990,6 +1547,15 @@ mod tests {
       Ok(())
     }
 
+    fn check_vsock(
+      &mut self,
+      _cid: u32,
+      _port: u32,
+      _api_name: &str,
+    ) -> Result<(), AnyError> {
+      Ok(())
+    }
+
     fn check_read(
       &mut self,
       p: &str,
@@ -1039,6 +1605,32 @@ mod tests {
     check_sockopt(String::from("127.0.0.1:4146"), set_keepalive, test_fn).await;
   }
 
+  #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+  async fn tcp_set_socket_options() {
+    let set_options = Box::new(|state: &mut OpState, rid| {
+      let options = TcpSocketOptions {
+        keep_alive: Some(true),
+        linger: Some(Some(5)),
+        recv_buffer_size: Some(65536),
+        tos: Some(0x10),
+        ..Default::default()
+      };
+      op_net_set_tcp_socket_options(state, rid, options).unwrap();
+      let options = op_net_get_tcp_socket_options(state, rid).unwrap();
+      assert_eq!(options.no_delay, Some(false));
+      assert_eq!(options.keep_alive, Some(true));
+      assert_eq!(options.linger, Some(Some(5)));
+      assert_eq!(options.tos, Some(0x10));
+    });
+    let test_fn = Box::new(|socket: SockRef| {
+      assert!(socket.keepalive().unwrap());
+      assert_eq!(socket.linger().unwrap(), Some(Duration::from_secs(5)));
+      // The kernel may round the size, e.g. Linux doubles it
+      assert!(socket.recv_buffer_size().unwrap() >= 65536);
+    });
+    check_sockopt(String::from("127.0.0.1:4147"), set_options, test_fn).await;
+  }
+
   #[allow(clippy::type_complexity)]
   async fn check_sockopt(
     addr: String,
@@ -1077,9 +1669,13 @@ mod tests {
       port: server_addr[1].parse().unwrap(),
     };
 
-    let mut connect_fut =
-      op_net_connect_tcp_inner::<TestPermission>(conn_state, ip_addr)
-        .boxed_local();
+    let mut connect_fut = op_net_connect_tcp_inner::<TestPermission>(
+      conn_state,
+      ip_addr,
+      None,
+      Default::default(),
+    )
+    .boxed_local();
     let mut rid = None;
 
     tokio::select! {
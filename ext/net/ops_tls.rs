@@ -1,5 +1,7 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use crate::happy_eyeballs;
+use crate::happy_eyeballs::LocalBinding;
 use crate::io::TcpStreamResource;
 use crate::ops::IpAddr;
 use crate::ops::TlsHandshakeInfo;
@@ -23,18 +25,26 @@ use deno_core::AsyncRefCell;
 use deno_core::AsyncResult;
 use deno_core::CancelHandle;
 use deno_core::CancelTryFuture;
+use deno_core::JsBuffer;
 use deno_core::OpState;
 use deno_core::RcRef;
 use deno_core::Resource;
 use deno_core::ResourceId;
 use deno_tls::create_client_config;
 use deno_tls::load_certs;
+use deno_tls::load_pkcs12;
 use deno_tls::load_private_keys;
 use deno_tls::new_resolver;
+use deno_tls::rustls::client::ClientSessionMemoryCache;
+use deno_tls::rustls::client::ClientSessionStore;
+use deno_tls::rustls::client::Resumption;
 use deno_tls::rustls::pki_types::ServerName;
 use deno_tls::rustls::ClientConnection;
 use deno_tls::rustls::ServerConfig;
+use deno_tls::RevocationCheck;
 use deno_tls::ServerConfigProvider;
+use deno_tls::SniCertResolver;
+use deno_tls::SniCertificate;
 use deno_tls::SocketUse;
 use deno_tls::TlsKey;
 use deno_tls::TlsKeyLookup;
@@ -54,6 +64,7 @@ use std::net::SocketAddr;
 use std::num::NonZeroUsize;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::io::AsyncReadExt;
 use tokio::io::AsyncWriteExt;
 use tokio::net::TcpStream;
@@ -65,27 +76,37 @@ pub(crate) const TLS_BUFFER_SIZE: Option<NonZeroUsize> =
 
 pub struct TlsListener {
   pub(crate) tcp_listener: TcpListener,
-  pub(crate) tls_config: Option<Arc<ServerConfig>>,
-  pub(crate) server_config_provider: Option<ServerConfigProvider>,
+  pub(crate) config: Rc<RefCell<TlsListenerConfig>>,
 }
 
 impl TlsListener {
   pub async fn accept(&self) -> std::io::Result<(TlsStream, SocketAddr)> {
     let (tcp, addr) = self.tcp_listener.accept().await?;
-    let tls = if let Some(provider) = &self.server_config_provider {
-      TlsStream::new_server_side_acceptor(
-        tcp,
-        provider.clone(),
-        TLS_BUFFER_SIZE,
-      )
-    } else {
-      TlsStream::new_server_side(
-        tcp,
-        self.tls_config.clone().unwrap(),
-        TLS_BUFFER_SIZE,
-      )
-    };
-    Ok((tls, addr))
+    Ok((self.server_side(tcp), addr))
+  }
+
+  /// Like [`TlsListener::accept`], also returning a handle to the socket of
+  /// the connection to set and read its options.
+  pub async fn accept_with_socket(
+    &self,
+  ) -> std::io::Result<(TlsStream, socket2::Socket, SocketAddr)> {
+    let (tcp, addr) = self.tcp_listener.accept().await?;
+    let socket = socket2::SockRef::from(&tcp).try_clone()?;
+    Ok((self.server_side(tcp), socket, addr))
+  }
+
+  fn server_side(&self, tcp: TcpStream) -> TlsStream {
+    // The config is read for each connection, as it may have been replaced
+    // while waiting for this one.
+    let config = self.config.borrow().clone();
+    match config {
+      TlsListenerConfig::Static(tls_config) => {
+        TlsStream::new_server_side(tcp, tls_config, TLS_BUFFER_SIZE)
+      }
+      TlsListenerConfig::Provider(provider) => {
+        TlsStream::new_server_side_acceptor(tcp, provider, TLS_BUFFER_SIZE)
+      }
+    }
   }
   pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
     self.tcp_listener.local_addr()
@@ -99,6 +120,9 @@ pub struct TlsStreamResource {
   // `None` when a TLS handshake hasn't been done.
   handshake_info: RefCell<Option<TlsHandshakeInfo>>,
   cancel_handle: CancelHandle, // Only read and handshake ops get canceled.
+  /// A handle to the TCP socket to set and read its options, which the TLS
+  /// stream doesn't give access to.
+  socket: Option<socket2::Socket>,
 }
 
 impl TlsStreamResource {
@@ -108,6 +132,24 @@ impl TlsStreamResource {
       wr: wr.into(),
       handshake_info: RefCell::new(None),
       cancel_handle: Default::default(),
+      socket: None,
+    }
+  }
+
+  pub fn with_socket(self, socket: socket2::Socket) -> Self {
+    Self {
+      socket: Some(socket),
+      ..self
+    }
+  }
+
+  pub fn map_socket<R>(
+    &self,
+    map: impl FnOnce(socket2::SockRef) -> Result<R, AnyError>,
+  ) -> Result<R, AnyError> {
+    match &self.socket {
+      Some(socket) => map(socket2::SockRef::from(socket)),
+      None => Err(generic_error("Unable to get resources")),
     }
   }
 
@@ -172,6 +214,24 @@ impl Resource for TlsStreamResource {
   }
 }
 
+/// The TLS sessions of the connections made with the default trust settings,
+/// shared so that reconnecting to a server resumes its last session. Other
+/// connections don't share theirs, as resuming skips verifying the server and
+/// authenticating the client again.
+struct TlsSessionCache(Arc<dyn ClientSessionStore>);
+
+/// The number of servers whose sessions are kept by [`TlsSessionCache`].
+const TLS_SESSION_CACHE_SIZE: usize = 256;
+
+fn shared_resumption(state: &mut OpState) -> Resumption {
+  if !state.has::<TlsSessionCache>() {
+    state.put(TlsSessionCache(Arc::new(ClientSessionMemoryCache::new(
+      TLS_SESSION_CACHE_SIZE,
+    ))));
+  }
+  Resumption::store(state.borrow::<TlsSessionCache>().0.clone())
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConnectTlsArgs {
@@ -179,6 +239,10 @@ pub struct ConnectTlsArgs {
   ca_certs: Vec<String>,
   alpn_protocols: Option<Vec<String>>,
   server_name: Option<String>,
+  happy_eyeballs_delay: Option<u64>,
+  revocation: Option<RevocationCheck>,
+  local_address: Option<std::net::IpAddr>,
+  interface: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -210,6 +274,16 @@ pub fn op_tls_key_static(
   Ok(TlsKeysHolder::from(TlsKeys::Static(TlsKey(cert, key))))
 }
 
+#[op2]
+#[cppgc]
+pub fn op_tls_key_pkcs12(
+  #[buffer] pkcs12: &[u8],
+  #[string] password: &str,
+) -> Result<TlsKeysHolder, AnyError> {
+  let key = load_pkcs12(pkcs12, password)?;
+  Ok(TlsKeysHolder::from(TlsKeys::Static(key)))
+}
+
 #[op2]
 pub fn op_tls_cert_resolver_create<'s>(
   scope: &mut v8::HandleScope<'s>,
@@ -308,13 +382,18 @@ where
   let local_addr = tcp_stream.local_addr()?;
   let remote_addr = tcp_stream.peer_addr()?;
 
+  let resumable = ca_certs.is_empty();
   let mut tls_config = create_client_config(
     root_cert_store,
     ca_certs,
     unsafely_ignore_certificate_errors,
     TlsKeys::Null,
     SocketUse::GeneralSsl,
+    None,
   )?;
+  if resumable {
+    tls_config.resumption = shared_resumption(&mut state.borrow_mut());
+  }
 
   if let Some(alpn_protocols) = args.alpn_protocols {
     tls_config.alpn_protocols =
@@ -322,6 +401,7 @@ where
   }
 
   let tls_config = Arc::new(tls_config);
+  let socket = socket2::SockRef::from(&tcp_stream).try_clone()?;
   let tls_stream = TlsStream::new_client_side(
     tcp_stream,
     ClientConnection::new(tls_config, hostname_dns)?,
@@ -332,7 +412,7 @@ where
     let mut state_ = state.borrow_mut();
     state_
       .resource_table
-      .add(TlsStreamResource::new(tls_stream.into_split()))
+      .add(TlsStreamResource::new(tls_stream.into_split()).with_socket(socket))
   };
 
   Ok((rid, IpAddr::from(local_addr), IpAddr::from(remote_addr)))
@@ -389,21 +469,38 @@ where
     ServerName::try_from(addr.hostname.clone())
   }
   .map_err(|_| invalid_hostname(&addr.hostname))?;
-  let connect_addr = resolve_addr(&addr.hostname, addr.port)
-    .await?
-    .next()
-    .ok_or_else(|| generic_error("No resolved address found"))?;
-  let tcp_stream = TcpStream::connect(connect_addr).await?;
+  let connect_addrs = resolve_addr(&addr.hostname, addr.port).await?;
+  let local_binding = LocalBinding {
+    local_address: args.local_address,
+    interface: args.interface,
+  };
+  let tcp_stream = happy_eyeballs::connect_from(
+    connect_addrs,
+    args
+      .happy_eyeballs_delay
+      .map(Duration::from_millis)
+      .unwrap_or(happy_eyeballs::DEFAULT_CONNECTION_ATTEMPT_DELAY),
+    &local_binding,
+  )
+  .await?;
   let local_addr = tcp_stream.local_addr()?;
   let remote_addr = tcp_stream.peer_addr()?;
 
+  let key_pair = key_pair.take();
+  let resumable = ca_certs.is_empty()
+    && matches!(key_pair, TlsKeys::Null)
+    && args.revocation.is_none();
   let mut tls_config = create_client_config(
     root_cert_store,
     ca_certs,
     unsafely_ignore_certificate_errors,
-    key_pair.take(),
+    key_pair,
     SocketUse::GeneralSsl,
+    args.revocation,
   )?;
+  if resumable {
+    tls_config.resumption = shared_resumption(&mut state.borrow_mut());
+  }
 
   if let Some(alpn_protocols) = args.alpn_protocols {
     tls_config.alpn_protocols =
@@ -412,6 +509,7 @@ where
 
   let tls_config = Arc::new(tls_config);
 
+  let socket = socket2::SockRef::from(&tcp_stream).try_clone()?;
   let tls_stream = TlsStream::new_client_side(
     tcp_stream,
     ClientConnection::new(tls_config, hostname_dns)?,
@@ -422,7 +520,7 @@ where
     let mut state_ = state.borrow_mut();
     state_
       .resource_table
-      .add(TlsStreamResource::new(tls_stream.into_split()))
+      .add(TlsStreamResource::new(tls_stream.into_split()).with_socket(socket))
   };
 
   Ok((rid, IpAddr::from(local_addr), IpAddr::from(remote_addr)))
@@ -430,8 +528,127 @@ where
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct ListenTlsArgs {
+pub struct TlsListenerConfigArgs {
   alpn_protocols: Option<Vec<String>>,
+  /// A DER-encoded OCSP response for the certificate, stapled to handshakes.
+  ocsp_response: Option<JsBuffer>,
+  /// The certificates served instead for some server names.
+  #[serde(default)]
+  sni_certificates: Vec<SniCertificate>,
+}
+
+/// The TLS configuration that a [`TlsListener`] accepts connections with. It
+/// is shared with the JS listener, which can replace the certificate while
+/// the listener accepts connections. Connections that are already established
+/// keep using the certificate they were made with.
+pub struct TlsListenerConfigHolder {
+  alpn: Vec<Vec<u8>>,
+  config: Rc<RefCell<TlsListenerConfig>>,
+}
+
+impl deno_core::GarbageCollected for TlsListenerConfigHolder {}
+
+#[derive(Clone)]
+pub(crate) enum TlsListenerConfig {
+  Static(Arc<ServerConfig>),
+  Provider(ServerConfigProvider),
+}
+
+impl TlsListenerConfig {
+  fn new(
+    keys: TlsKeys,
+    alpn: Vec<Vec<u8>>,
+    ocsp_response: Option<JsBuffer>,
+    sni_certificates: Vec<SniCertificate>,
+  ) -> Result<Self, AnyError> {
+    match keys {
+      TlsKeys::Null => Err(anyhow!("Deno.listenTls requires a key")),
+      TlsKeys::Static(key) if !sni_certificates.is_empty() => {
+        let resolver = SniCertResolver::new(
+          key,
+          ocsp_response.map(|ocsp| ocsp.to_vec()),
+          sni_certificates,
+        )?;
+        let mut tls_config = ServerConfig::builder()
+          .with_no_client_auth()
+          .with_cert_resolver(Arc::new(resolver));
+        tls_config.alpn_protocols = alpn;
+        Ok(Self::Static(tls_config.into()))
+      }
+      TlsKeys::Static(TlsKey(cert, key)) => {
+        let tls_config = ServerConfig::builder().with_no_client_auth();
+        let mut tls_config = match ocsp_response {
+          Some(ocsp) => {
+            tls_config.with_single_cert_with_ocsp(cert, key, ocsp.to_vec())
+          }
+          None => tls_config.with_single_cert(cert, key),
+        }
+        .map_err(|e| anyhow!(e))?;
+        tls_config.alpn_protocols = alpn;
+        Ok(Self::Static(tls_config.into()))
+      }
+      TlsKeys::Resolver(_) if ocsp_response.is_some() => Err(anyhow!(
+        "ocspResponse can't be used with a certificate resolver"
+      )),
+      TlsKeys::Resolver(_) if !sni_certificates.is_empty() => Err(anyhow!(
+        "sniCertificates can't be used with a certificate resolver"
+      )),
+      TlsKeys::Resolver(resolver) => {
+        Ok(Self::Provider(resolver.into_server_config_provider(alpn)))
+      }
+    }
+    .map_err(|e| {
+      custom_error("InvalidData", "Error creating TLS certificate").context(e)
+    })
+  }
+}
+
+#[op2]
+#[cppgc]
+pub fn op_tls_listener_config_create(
+  #[cppgc] keys: &TlsKeysHolder,
+  #[serde] args: TlsListenerConfigArgs,
+) -> Result<TlsListenerConfigHolder, AnyError> {
+  let alpn: Vec<Vec<u8>> = args
+    .alpn_protocols
+    .unwrap_or_default()
+    .into_iter()
+    .map(|s| s.into_bytes())
+    .collect();
+  let config = TlsListenerConfig::new(
+    keys.take(),
+    alpn.clone(),
+    args.ocsp_response,
+    args.sni_certificates,
+  )?;
+  Ok(TlsListenerConfigHolder {
+    alpn,
+    config: Rc::new(RefCell::new(config)),
+  })
+}
+
+/// Replaces the certificate of a listener, along with the certificates of its
+/// server names, for the connections it accepts from now on. The ALPN
+/// protocols of the listener are kept.
+#[op2]
+pub fn op_tls_listener_config_set_keys(
+  #[cppgc] holder: &TlsListenerConfigHolder,
+  #[cppgc] keys: &TlsKeysHolder,
+  #[serde] args: TlsListenerConfigArgs,
+) -> Result<(), AnyError> {
+  let config = TlsListenerConfig::new(
+    keys.take(),
+    holder.alpn.clone(),
+    args.ocsp_response,
+    args.sni_certificates,
+  )?;
+  *holder.config.borrow_mut() = config;
+  Ok(())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListenTlsArgs {
   reuse_port: bool,
   #[serde(default)]
   load_balanced: bool,
@@ -443,7 +660,7 @@ pub fn op_net_listen_tls<NP>(
   state: &mut OpState,
   #[serde] addr: IpAddr,
   #[serde] args: ListenTlsArgs,
-  #[cppgc] keys: &TlsKeysHolder,
+  #[cppgc] config: &TlsListenerConfigHolder,
 ) -> Result<(ResourceId, IpAddr), AnyError>
 where
   NP: NetPermissions + 'static,
@@ -468,37 +685,14 @@ where
     TcpListener::bind_direct(bind_addr, args.reuse_port)
   }?;
   let local_addr = tcp_listener.local_addr()?;
-  let alpn = args
-    .alpn_protocols
-    .unwrap_or_default()
-    .into_iter()
-    .map(|s| s.into_bytes())
-    .collect();
-  let listener = match keys.take() {
-    TlsKeys::Null => Err(anyhow!("Deno.listenTls requires a key")),
-    TlsKeys::Static(TlsKey(cert, key)) => {
-      let mut tls_config = ServerConfig::builder()
-        .with_no_client_auth()
-        .with_single_cert(cert, key)
-        .map_err(|e| anyhow!(e))?;
-      tls_config.alpn_protocols = alpn;
-      Ok(TlsListener {
-        tcp_listener,
-        tls_config: Some(tls_config.into()),
-        server_config_provider: None,
-      })
-    }
-    TlsKeys::Resolver(resolver) => Ok(TlsListener {
-      tcp_listener,
-      tls_config: None,
-      server_config_provider: Some(resolver.into_server_config_provider(alpn)),
-    }),
-  }
-  .map_err(|e| {
-    custom_error("InvalidData", "Error creating TLS certificate").context(e)
-  })?;
+  let socket = tcp_listener.try_clone_socket()?;
+  let listener = TlsListener {
+    tcp_listener,
+    config: config.config.clone(),
+  };
 
-  let tls_listener_resource = NetworkListenerResource::new(listener);
+  let tls_listener_resource =
+    NetworkListenerResource::with_socket(listener, socket);
 
   let rid = state.resource_table.add(tls_listener_resource);
 
@@ -522,22 +716,25 @@ pub async fn op_net_accept_tls(
     .try_borrow_mut()
     .ok_or_else(|| custom_error("Busy", "Another accept task is ongoing"))?;
 
-  let (tls_stream, remote_addr) =
-    match listener.accept().try_or_cancel(&cancel_handle).await {
-      Ok(tuple) => tuple,
-      Err(err) if err.kind() == ErrorKind::Interrupted => {
-        // FIXME(bartlomieju): compatibility with current JS implementation.
-        return Err(bad_resource("Listener has been closed"));
-      }
-      Err(err) => return Err(err.into()),
-    };
+  let (tls_stream, socket, remote_addr) = match listener
+    .accept_with_socket()
+    .try_or_cancel(&cancel_handle)
+    .await
+  {
+    Ok(tuple) => tuple,
+    Err(err) if err.kind() == ErrorKind::Interrupted => {
+      // FIXME(bartlomieju): compatibility with current JS implementation.
+      return Err(bad_resource("Listener has been closed"));
+    }
+    Err(err) => return Err(err.into()),
+  };
 
   let local_addr = tls_stream.local_addr()?;
   let rid = {
     let mut state_ = state.borrow_mut();
     state_
       .resource_table
-      .add(TlsStreamResource::new(tls_stream.into_split()))
+      .add(TlsStreamResource::new(tls_stream.into_split()).with_socket(socket))
   };
 
   Ok((rid, IpAddr::from(local_addr), IpAddr::from(remote_addr)))
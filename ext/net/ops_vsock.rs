@@ -0,0 +1,359 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Sockets of the `AF_VSOCK` address family, which connect virtual machines
+//! (e.g. Firecracker microVMs and Nitro enclaves) with their host. An address
+//! is a context ID (CID), which identifies the VM or host, and a port.
+//!
+//! They are available on Linux, and on macOS inside of virtual machines of the
+//! Virtualization framework.
+
+use crate::io::FullDuplexResource;
+use crate::NetPermissions;
+use deno_core::error::bad_resource;
+use deno_core::error::custom_error;
+use deno_core::error::AnyError;
+use deno_core::op2;
+use deno_core::AsyncRefCell;
+use deno_core::AsyncResult;
+use deno_core::CancelHandle;
+use deno_core::CancelTryFuture;
+use deno_core::OpState;
+use deno_core::RcRef;
+use deno_core::Resource;
+use deno_core::ResourceId;
+use serde::Deserialize;
+use serde::Serialize;
+use socket2::SockAddr;
+use socket2::Socket;
+use socket2::Type;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+use tokio::io::unix::AsyncFd;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::io::ReadBuf;
+use tokio::io::ReadHalf;
+use tokio::io::WriteHalf;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct VsockAddr {
+  pub cid: u32,
+  pub port: u32,
+}
+
+impl VsockAddr {
+  fn from_sock_addr(addr: SockAddr) -> io::Result<Self> {
+    let (cid, port) = sys::vsock_address(&addr).ok_or_else(|| {
+      io::Error::new(io::ErrorKind::InvalidData, "Not a vsock address")
+    })?;
+    Ok(Self { cid, port })
+  }
+
+  fn to_sock_addr(self) -> SockAddr {
+    sys::sock_addr(self.cid, self.port)
+  }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod sys {
+  use socket2::Domain;
+  use socket2::SockAddr;
+
+  pub fn domain() -> Domain {
+    Domain::VSOCK
+  }
+
+  pub fn sock_addr(cid: u32, port: u32) -> SockAddr {
+    SockAddr::vsock(cid, port)
+  }
+
+  pub fn vsock_address(addr: &SockAddr) -> Option<(u32, u32)> {
+    addr.as_vsock_address()
+  }
+}
+
+/// socket2 and libc only know about vsock on Linux, so the address is built
+/// after `<sys/vsock.h>` of macOS.
+#[cfg(target_os = "macos")]
+mod sys {
+  use socket2::Domain;
+  use socket2::SockAddr;
+
+  const AF_VSOCK: libc::c_int = 40;
+
+  pub fn domain() -> Domain {
+    Domain::from(AF_VSOCK)
+  }
+
+  #[allow(dead_code)]
+  #[repr(C)]
+  struct SockAddrVm {
+    svm_len: u8,
+    svm_family: libc::sa_family_t,
+    svm_reserved1: u16,
+    svm_port: u32,
+    svm_cid: u32,
+  }
+
+  pub fn sock_addr(cid: u32, port: u32) -> SockAddr {
+    let len = std::mem::size_of::<SockAddrVm>();
+    // SAFETY: a zeroed `sockaddr_storage` is valid, and it's large enough and
+    // aligned for a `sockaddr_vm`, which is initialized before it's used.
+    unsafe {
+      let mut storage: libc::sockaddr_storage = std::mem::zeroed();
+      let addr = &mut *(&mut storage as *mut _ as *mut SockAddrVm);
+      addr.svm_len = len as u8;
+      addr.svm_family = AF_VSOCK as libc::sa_family_t;
+      addr.svm_port = port;
+      addr.svm_cid = cid;
+      SockAddr::new(storage, len as libc::socklen_t)
+    }
+  }
+
+  pub fn vsock_address(addr: &SockAddr) -> Option<(u32, u32)> {
+    if addr.family() != AF_VSOCK as libc::sa_family_t
+      || (addr.len() as usize) < std::mem::size_of::<SockAddrVm>()
+    {
+      return None;
+    }
+    // SAFETY: the address is a `sockaddr_vm`, as checked above.
+    let addr = unsafe { &*(addr.as_ptr() as *const SockAddrVm) };
+    Some((addr.svm_cid, addr.svm_port))
+  }
+}
+
+fn new_socket() -> io::Result<Socket> {
+  let socket = Socket::new(sys::domain(), Type::STREAM, None)?;
+  socket.set_cloexec(true)?;
+  socket.set_nonblocking(true)?;
+  Ok(socket)
+}
+
+/// A connected vsock stream, driven by the tokio reactor.
+pub struct VsockStream(AsyncFd<Socket>);
+
+impl VsockStream {
+  /// Connects without blocking, so dropping the future cancels the connect
+  /// by closing the socket.
+  pub async fn connect(addr: VsockAddr) -> io::Result<Self> {
+    let socket = new_socket()?;
+    match socket.connect(&addr.to_sock_addr()) {
+      Ok(()) => {}
+      Err(err) if err.raw_os_error() == Some(libc::EINPROGRESS) => {}
+      Err(err) => return Err(err),
+    }
+    let socket = AsyncFd::new(socket)?;
+    // The socket becomes writable once connecting succeeded or failed, with
+    // the error pending on the socket.
+    drop(socket.writable().await?);
+    if let Some(err) = socket.get_ref().take_error()? {
+      return Err(err);
+    }
+    Ok(Self(socket))
+  }
+
+  pub fn local_addr(&self) -> io::Result<VsockAddr> {
+    VsockAddr::from_sock_addr(self.0.get_ref().local_addr()?)
+  }
+
+  pub fn peer_addr(&self) -> io::Result<VsockAddr> {
+    VsockAddr::from_sock_addr(self.0.get_ref().peer_addr()?)
+  }
+}
+
+impl AsyncRead for VsockStream {
+  fn poll_read(
+    self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &mut ReadBuf<'_>,
+  ) -> Poll<io::Result<()>> {
+    loop {
+      let mut guard = ready!(self.0.poll_read_ready(cx))?;
+      let unfilled = buf.initialize_unfilled();
+      match guard.try_io(|socket| socket.get_ref().read(unfilled)) {
+        Ok(Ok(nread)) => {
+          buf.advance(nread);
+          return Poll::Ready(Ok(()));
+        }
+        Ok(Err(err)) => return Poll::Ready(Err(err)),
+        Err(_would_block) => continue,
+      }
+    }
+  }
+}
+
+impl AsyncWrite for VsockStream {
+  fn poll_write(
+    self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &[u8],
+  ) -> Poll<io::Result<usize>> {
+    loop {
+      let mut guard = ready!(self.0.poll_write_ready(cx))?;
+      match guard.try_io(|socket| socket.get_ref().write(buf)) {
+        Ok(result) => return Poll::Ready(result),
+        Err(_would_block) => continue,
+      }
+    }
+  }
+
+  fn poll_flush(
+    self: Pin<&mut Self>,
+    _cx: &mut Context<'_>,
+  ) -> Poll<io::Result<()>> {
+    Poll::Ready(Ok(()))
+  }
+
+  fn poll_shutdown(
+    self: Pin<&mut Self>,
+    _cx: &mut Context<'_>,
+  ) -> Poll<io::Result<()>> {
+    Poll::Ready(self.0.get_ref().shutdown(std::net::Shutdown::Write))
+  }
+}
+
+/// A listening vsock socket, driven by the tokio reactor.
+pub struct VsockListener(AsyncFd<Socket>);
+
+impl VsockListener {
+  pub fn bind(addr: VsockAddr) -> io::Result<Self> {
+    let socket = new_socket()?;
+    socket.bind(&addr.to_sock_addr())?;
+    socket.listen(128)?;
+    Ok(Self(AsyncFd::new(socket)?))
+  }
+
+  pub async fn accept(&self) -> io::Result<(VsockStream, VsockAddr)> {
+    loop {
+      let mut guard = self.0.readable().await?;
+      match guard.try_io(|socket| socket.get_ref().accept()) {
+        Ok(Ok((socket, addr))) => {
+          socket.set_nonblocking(true)?;
+          let stream = VsockStream(AsyncFd::new(socket)?);
+          return Ok((stream, VsockAddr::from_sock_addr(addr)?));
+        }
+        Ok(Err(err)) => return Err(err),
+        Err(_would_block) => continue,
+      }
+    }
+  }
+
+  pub fn local_addr(&self) -> io::Result<VsockAddr> {
+    VsockAddr::from_sock_addr(self.0.get_ref().local_addr()?)
+  }
+}
+
+pub type VsockStreamResource =
+  FullDuplexResource<ReadHalf<VsockStream>, WriteHalf<VsockStream>>;
+
+impl Resource for VsockStreamResource {
+  deno_core::impl_readable_byob!();
+  deno_core::impl_writable!();
+
+  fn name(&self) -> Cow<str> {
+    "vsockStream".into()
+  }
+
+  fn shutdown(self: Rc<Self>) -> AsyncResult<()> {
+    Box::pin(self.shutdown())
+  }
+
+  fn close(self: Rc<Self>) {
+    self.cancel_read_ops();
+  }
+}
+
+pub struct VsockListenerResource {
+  listener: AsyncRefCell<VsockListener>,
+  cancel: CancelHandle,
+}
+
+impl Resource for VsockListenerResource {
+  fn name(&self) -> Cow<str> {
+    "vsockListener".into()
+  }
+
+  fn close(self: Rc<Self>) {
+    self.cancel.cancel();
+  }
+}
+
+#[op2(async)]
+#[serde]
+pub async fn op_net_connect_vsock<NP>(
+  state: Rc<RefCell<OpState>>,
+  #[serde] addr: VsockAddr,
+) -> Result<(ResourceId, VsockAddr, VsockAddr), AnyError>
+where
+  NP: NetPermissions + 'static,
+{
+  state.borrow_mut().borrow_mut::<NP>().check_vsock(
+    addr.cid,
+    addr.port,
+    "Deno.connect()",
+  )?;
+
+  let stream = VsockStream::connect(addr).await?;
+  let local_addr = stream.local_addr()?;
+  let remote_addr = stream.peer_addr()?;
+  let resource = VsockStreamResource::new(tokio::io::split(stream));
+  let rid = state.borrow_mut().resource_table.add(resource);
+  Ok((rid, local_addr, remote_addr))
+}
+
+#[op2]
+#[serde]
+pub fn op_net_listen_vsock<NP>(
+  state: &mut OpState,
+  #[serde] addr: VsockAddr,
+) -> Result<(ResourceId, VsockAddr), AnyError>
+where
+  NP: NetPermissions + 'static,
+{
+  state
+    .borrow_mut::<NP>()
+    .check_vsock(addr.cid, addr.port, "Deno.listen()")?;
+
+  let listener = VsockListener::bind(addr)?;
+  let local_addr = listener.local_addr()?;
+  let rid = state.resource_table.add(VsockListenerResource {
+    listener: AsyncRefCell::new(listener),
+    cancel: Default::default(),
+  });
+  Ok((rid, local_addr))
+}
+
+#[op2(async)]
+#[serde]
+pub async fn op_net_accept_vsock(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+) -> Result<(ResourceId, VsockAddr, VsockAddr), AnyError> {
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<VsockListenerResource>(rid)
+    .map_err(|_| bad_resource("Listener has been closed"))?;
+  let listener = RcRef::map(&resource, |r| &r.listener)
+    .try_borrow_mut()
+    .ok_or_else(|| custom_error("Busy", "Listener already in use"))?;
+  let cancel = RcRef::map(&resource, |r| &r.cancel);
+  let (stream, remote_addr) = listener
+    .accept()
+    .try_or_cancel(cancel)
+    .await
+    .map_err(crate::ops::accept_err)?;
+
+  let local_addr = stream.local_addr()?;
+  let resource = VsockStreamResource::new(tokio::io::split(stream));
+  let rid = state.borrow_mut().resource_table.add(resource);
+  Ok((rid, local_addr, remote_addr))
+}
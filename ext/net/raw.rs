@@ -39,6 +39,9 @@ pub struct NetworkListenerResource<T: NetworkStreamListenerTrait> {
   /// Associated data for this resource. Not required.
   #[allow(unused)]
   pub data: T::ResourceData,
+  /// A handle to the socket of TCP listeners to set and read its options,
+  /// which can be used while the listener is borrowed to accept.
+  pub socket: Option<socket2::Socket>,
   pub cancel: CancelHandle,
 }
 
@@ -59,10 +62,18 @@ impl<T: NetworkStreamListenerTrait + 'static> NetworkListenerResource<T> {
     Self {
       listener: AsyncRefCell::new(t),
       data: Default::default(),
+      socket: None,
       cancel: Default::default(),
     }
   }
 
+  pub fn with_socket(t: T, socket: socket2::Socket) -> Self {
+    Self {
+      socket: Some(socket),
+      ..Self::new(t)
+    }
+  }
+
   /// Returns a [`NetworkStreamListener`] from this resource if it is not in use elsewhere.
   fn take(
     resource_table: &mut ResourceTable,
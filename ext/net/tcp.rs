@@ -129,6 +129,11 @@ impl TcpListener {
   pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
     self.listener.as_ref().unwrap().local_addr()
   }
+
+  /// Duplicates the handle of the socket, to set and read its options.
+  pub fn try_clone_socket(&self) -> std::io::Result<socket2::Socket> {
+    socket2::SockRef::from(self.listener.as_ref().unwrap()).try_clone()
+  }
 }
 
 impl Drop for TcpListener {
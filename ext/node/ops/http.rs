@@ -65,7 +65,7 @@ where
 {
   let client = if let Some(rid) = client_rid {
     let r = state.resource_table.get::<HttpClientResource>(rid)?;
-    r.client.clone()
+    r.client()
   } else {
     get_or_create_client_from_state(state)?
   };
@@ -152,6 +152,7 @@ where
   Ok(FetchReturn {
     request_rid,
     cancel_handle_rid: Some(cancel_handle_rid),
+    upload_progress_rid: None,
   })
 }
 
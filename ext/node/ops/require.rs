@@ -14,7 +14,6 @@ use deno_fs::FileSystemRc;
 use deno_package_json::PackageJsonRc;
 use node_resolver::NodeModuleKind;
 use node_resolver::NodeResolutionMode;
-use node_resolver::REQUIRE_CONDITIONS;
 use std::cell::RefCell;
 use std::path::Path;
 use std::path::PathBuf;
@@ -420,7 +419,7 @@ where
       exports,
       Some(&referrer),
       NodeModuleKind::Cjs,
-      REQUIRE_CONDITIONS,
+      &node_resolver.require_conditions(),
       NodeResolutionMode::Execution,
     )?;
     Ok(Some(if r.scheme() == "file" {
@@ -508,7 +507,7 @@ where
     exports,
     Some(&referrer),
     NodeModuleKind::Cjs,
-    REQUIRE_CONDITIONS,
+    &node_resolver.require_conditions(),
     NodeResolutionMode::Execution,
   )?;
   Ok(Some(if r.scheme() == "file" {
@@ -583,7 +582,7 @@ where
       Some(&referrer_url),
       NodeModuleKind::Cjs,
       Some(&pkg),
-      REQUIRE_CONDITIONS,
+      &node_resolver.require_conditions(),
       NodeResolutionMode::Execution,
     )?;
     Ok(Some(url_to_file_path_string(&url)?))
@@ -59,6 +59,26 @@ static TYPES_ONLY_CONDITIONS: &[&str] = &["types"];
 
 pub type NodeModuleKind = deno_package_json::NodeModuleKind;
 
+/// Gets the conditions with the custom ones, whose order doesn't matter
+/// because the first key of the exports or imports that's in the conditions
+/// is the one that matches.
+fn with_custom_conditions<'a>(
+  conditions: &'static [&'static str],
+  custom_conditions: &'a [String],
+) -> Cow<'a, [&'a str]> {
+  if custom_conditions.is_empty() {
+    Cow::Borrowed(conditions)
+  } else {
+    Cow::Owned(
+      custom_conditions
+        .iter()
+        .map(String::as_str)
+        .chain(conditions.iter().copied())
+        .collect(),
+    )
+  }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NodeResolutionMode {
   Execution,
@@ -137,11 +157,33 @@ pub type NodeResolverRc<TEnv> = crate::sync::MaybeArc<NodeResolver<TEnv>>;
 pub struct NodeResolver<TEnv: NodeResolverEnv> {
   env: TEnv,
   npm_resolver: NpmResolverRc,
+  /// Custom conditions, like the ones of the `--conditions` flag of Node,
+  /// which are matched along with the default conditions.
+  conditions: Vec<String>,
 }
 
 impl<TEnv: NodeResolverEnv> NodeResolver<TEnv> {
-  pub fn new(env: TEnv, npm_resolver: NpmResolverRc) -> Self {
-    Self { env, npm_resolver }
+  pub fn new(
+    env: TEnv,
+    npm_resolver: NpmResolverRc,
+    conditions: Vec<String>,
+  ) -> Self {
+    Self {
+      env,
+      npm_resolver,
+      conditions,
+    }
+  }
+
+  /// The conditions of the resolution of ES modules, which prefer the "deno"
+  /// condition and fall back to the "node" and "import" conditions.
+  pub fn esm_conditions(&self) -> Cow<[&str]> {
+    with_custom_conditions(DEFAULT_CONDITIONS, &self.conditions)
+  }
+
+  /// The conditions of the resolution of `require` calls.
+  pub fn require_conditions(&self) -> Cow<[&str]> {
+    with_custom_conditions(REQUIRE_CONDITIONS, &self.conditions)
   }
 
   pub fn in_npm_package(&self, specifier: &Url) -> bool {
@@ -200,7 +242,7 @@ impl<TEnv: NodeResolverEnv> NodeResolver<TEnv> {
       referrer,
       referrer_kind,
       // even though the referrer may be CJS, if we're here that means we're doing ESM resolution
-      DEFAULT_CONDITIONS,
+      &self.esm_conditions(),
       mode,
     )?;
 
@@ -342,7 +384,7 @@ impl<TEnv: NodeResolverEnv> NodeResolver<TEnv> {
       &package_subpath,
       maybe_referrer,
       node_module_kind,
-      DEFAULT_CONDITIONS,
+      &self.esm_conditions(),
       mode,
     )?;
     let resolve_response = self.url_to_node_resolution(resolved_url)?;
@@ -498,9 +540,9 @@ impl<TEnv: NodeResolverEnv> NodeResolver<TEnv> {
         /* sub path */ ".",
         maybe_referrer,
         referrer_kind,
-        match referrer_kind {
-          NodeModuleKind::Esm => DEFAULT_CONDITIONS,
-          NodeModuleKind::Cjs => REQUIRE_CONDITIONS,
+        &match referrer_kind {
+          NodeModuleKind::Esm => self.esm_conditions(),
+          NodeModuleKind::Cjs => self.require_conditions(),
         },
         NodeResolutionMode::Types,
       );
@@ -2020,4 +2062,20 @@ mod tests {
       "@types/@scoped__package"
     );
   }
+
+  #[test]
+  fn test_with_custom_conditions() {
+    assert_eq!(
+      with_custom_conditions(DEFAULT_CONDITIONS, &[]),
+      Cow::Borrowed(DEFAULT_CONDITIONS)
+    );
+    assert_eq!(
+      with_custom_conditions(
+        REQUIRE_CONDITIONS,
+        &["development".to_string(), "worker".to_string()]
+      )
+      .as_ref(),
+      &["development", "worker", "require", "node"]
+    );
+  }
 }
@@ -2,6 +2,7 @@
 pub use deno_native_certs;
 pub use rustls;
 use rustls::pki_types::CertificateDer;
+use rustls::pki_types::CertificateRevocationListDer;
 use rustls::pki_types::PrivateKeyDer;
 use rustls::pki_types::ServerName;
 pub use rustls_pemfile;
@@ -22,6 +23,7 @@ use rustls::DigitallySignedStruct;
 use rustls::Error;
 use rustls::RootCertStore;
 use rustls_pemfile::certs;
+use rustls_pemfile::crls;
 use rustls_pemfile::ec_private_keys;
 use rustls_pemfile::pkcs8_private_keys;
 use rustls_pemfile::rsa_private_keys;
@@ -32,7 +34,12 @@ use std::io::Cursor;
 use std::net::IpAddr;
 use std::sync::Arc;
 
+mod pkcs12;
+mod sni;
 mod tls_key;
+pub use pkcs12::load_pkcs12;
+pub use sni::SniCertResolver;
+pub use sni::SniCertificate;
 pub use tls_key::*;
 
 /// Lazily resolves the root cert store.
@@ -144,6 +151,8 @@ impl ServerCertVerifier for NoCertificateVerification {
 pub struct Proxy {
   pub url: String,
   pub basic_auth: Option<BasicAuth>,
+  /// Hosts which are connected to directly, in the format of `NO_PROXY`.
+  pub no_proxy: Vec<String>,
 }
 
 #[derive(Deserialize, Default, Debug, Clone)]
@@ -172,12 +181,34 @@ pub enum SocketUse {
   Http2Only,
 }
 
+/// Checks the certificates of servers against certificate revocation lists.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RevocationCheck {
+  /// PEM-encoded certificate revocation lists.
+  pub crls: Vec<String>,
+  #[serde(default)]
+  pub mode: RevocationMode,
+}
+
+#[derive(Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RevocationMode {
+  /// Reject certificates whose status is not known, because none of the CRLs
+  /// is from their issuer.
+  #[default]
+  HardFail,
+  /// Only reject certificates that are listed as revoked.
+  SoftFail,
+}
+
 pub fn create_client_config(
   root_cert_store: Option<RootCertStore>,
   ca_certs: Vec<Vec<u8>>,
   unsafely_ignore_certificate_errors: Option<Vec<String>>,
   maybe_cert_chain_and_key: TlsKeys,
   socket_use: SocketUse,
+  revocation: Option<RevocationCheck>,
 ) -> Result<ClientConfig, AnyError> {
   if let Some(ic_allowlist) = unsafely_ignore_certificate_errors {
     let client_config = ClientConfig::builder()
@@ -223,8 +254,21 @@ pub fn create_client_config(
     }
   }
 
-  let client_config =
-    ClientConfig::builder().with_root_certificates(root_cert_store);
+  let client_config = match revocation {
+    Some(revocation) => {
+      let mut crls = vec![];
+      for crl in revocation.crls {
+        crls.extend(load_crls(&mut BufReader::new(crl.as_bytes()))?);
+      }
+      let mut verifier =
+        WebPkiServerVerifier::builder(root_cert_store.into()).with_crls(crls);
+      if revocation.mode == RevocationMode::SoftFail {
+        verifier = verifier.allow_unknown_revocation_status();
+      }
+      ClientConfig::builder().with_webpki_verifier(verifier.build()?)
+    }
+    None => ClientConfig::builder().with_root_certificates(root_cert_store),
+  };
 
   let mut client = match maybe_cert_chain_and_key {
     TlsKeys::Static(TlsKey(cert_chain, private_key)) => client_config
@@ -268,6 +312,28 @@ pub fn load_certs(
   Ok(certs)
 }
 
+pub fn load_crls(
+  reader: &mut dyn BufRead,
+) -> Result<Vec<CertificateRevocationListDer<'static>>, AnyError> {
+  let crls: Result<Vec<_>, _> = crls(reader).collect();
+
+  let crls = crls.map_err(|_| {
+    custom_error(
+      "InvalidData",
+      "Unable to decode certificate revocation list",
+    )
+  })?;
+
+  if crls.is_empty() {
+    return Err(custom_error(
+      "InvalidData",
+      "No certificate revocation lists found in CRL data",
+    ));
+  }
+
+  Ok(crls)
+}
+
 fn key_decode_err() -> AnyError {
   custom_error("InvalidData", "Unable to decode key")
 }
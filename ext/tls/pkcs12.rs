@@ -0,0 +1,92 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Loading of a private key and its certificate chain from a PKCS#12 archive
+//! (RFC 7292), also known as `.p12` or `.pfx` files.
+//!
+//! Both archives protected with PBES2, which is what OpenSSL 3 writes by
+//! default, and with the legacy 3DES and RC2 encryption schemes are supported.
+
+use crate::TlsKey;
+use deno_core::error::custom_error;
+use deno_core::error::AnyError;
+use p12_keystore::KeyStore;
+use p12_keystore::KeyStoreEntry;
+use webpki::types::CertificateDer;
+use webpki::types::PrivateKeyDer;
+
+fn pkcs12_err(msg: impl std::fmt::Display) -> AnyError {
+  custom_error("InvalidData", format!("Invalid PKCS#12 archive: {msg}"))
+}
+
+/// Loads the private key of a PKCS#12 archive and its certificate chain,
+/// which starts with the certificate of the key.
+pub fn load_pkcs12(der: &[u8], password: &str) -> Result<TlsKey, AnyError> {
+  let keystore = KeyStore::from_pkcs12(der, password).map_err(pkcs12_err)?;
+  let Some(chain) = keystore.entries().find_map(|(_, entry)| match entry {
+    KeyStoreEntry::PrivateKeyChain(chain) => Some(chain),
+    _ => None,
+  }) else {
+    return Err(pkcs12_err("no private key found"));
+  };
+  if chain.chain().is_empty() {
+    return Err(pkcs12_err("no certificate found"));
+  }
+  let certs = chain
+    .chain()
+    .iter()
+    .map(|cert| CertificateDer::from(cert.as_der().to_vec()))
+    .collect();
+  Ok(TlsKey(
+    certs,
+    PrivateKeyDer::Pkcs8(chain.key().to_vec().into()),
+  ))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  static EXAMPLE_CRT: &[u8] = include_bytes!("testdata/example1_cert.der");
+  static EXAMPLE_KEY: &[u8] = include_bytes!("testdata/example1_prikey.der");
+  static EXAMPLE_P12: &[u8] = include_bytes!("testdata/example1.p12");
+  static EXAMPLE_LEGACY_P12: &[u8] =
+    include_bytes!("testdata/example1_legacy.p12");
+  static EXAMPLE_LEGACY_RC2_P12: &[u8] =
+    include_bytes!("testdata/example1_legacy_rc2.p12");
+
+  fn assert_example_key(TlsKey(certs, key): TlsKey) {
+    assert_eq!(certs, vec![CertificateDer::from(EXAMPLE_CRT)]);
+    // The archive holds the RSA key of the fixture wrapped in PKCS#8.
+    assert!(matches!(key, PrivateKeyDer::Pkcs8(_)));
+    assert!(key.secret_der().ends_with(EXAMPLE_KEY));
+  }
+
+  #[test]
+  fn test_load_pkcs12() {
+    assert_example_key(load_pkcs12(EXAMPLE_P12, "deno").unwrap());
+  }
+
+  #[test]
+  fn test_load_pkcs12_invalid_password() {
+    let err = load_pkcs12(EXAMPLE_P12, "node").unwrap_err();
+    assert!(
+      err.to_string().starts_with("Invalid PKCS#12 archive: "),
+      "{err}"
+    );
+  }
+
+  #[test]
+  fn test_load_pkcs12_legacy() {
+    assert_example_key(load_pkcs12(EXAMPLE_LEGACY_P12, "deno").unwrap());
+    assert_example_key(load_pkcs12(EXAMPLE_LEGACY_RC2_P12, "deno").unwrap());
+  }
+
+  #[test]
+  fn test_load_pkcs12_invalid() {
+    let err = load_pkcs12(EXAMPLE_CRT, "deno").unwrap_err();
+    assert!(
+      err.to_string().starts_with("Invalid PKCS#12 archive: "),
+      "{err}"
+    );
+  }
+}
@@ -0,0 +1,143 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! Selection of the certificate of a server by the server name that a client
+//! asks for in its handshake (SNI, RFC 6066 Section 3), so that one listener
+//! can serve several domains.
+
+use std::collections::HashMap;
+use std::io::BufReader;
+use std::sync::Arc;
+
+use deno_core::anyhow::anyhow;
+use deno_core::error::AnyError;
+use rustls::server::ClientHello;
+use rustls::server::ResolvesServerCert;
+use rustls::sign::CertifiedKey;
+use serde::Deserialize;
+
+use crate::load_certs;
+use crate::load_private_keys;
+use crate::TlsKey;
+
+/// The PEM-encoded certificate and key served to the clients that ask for
+/// `server_name`, which may start with a `*.` wildcard label.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SniCertificate {
+  pub server_name: String,
+  pub cert: String,
+  pub key: String,
+}
+
+/// Serves the certificate of the server name of a handshake, or the default
+/// certificate to clients asking for another name or none.
+#[derive(Debug)]
+pub struct SniCertResolver {
+  default: Arc<CertifiedKey>,
+  by_name: HashMap<String, Arc<CertifiedKey>>,
+}
+
+impl SniCertificate {
+  fn load(self) -> Result<(String, TlsKey), AnyError> {
+    let certs = load_certs(&mut BufReader::new(self.cert.as_bytes()))?;
+    let key = load_private_keys(self.key.as_bytes())?
+      .into_iter()
+      .next()
+      .unwrap();
+    Ok((self.server_name, TlsKey(certs, key)))
+  }
+}
+
+impl SniCertResolver {
+  pub fn new(
+    default: TlsKey,
+    ocsp_response: Option<Vec<u8>>,
+    certificates: Vec<SniCertificate>,
+  ) -> Result<Self, AnyError> {
+    let certificates = certificates
+      .into_iter()
+      .map(SniCertificate::load)
+      .collect::<Result<_, _>>()?;
+    Self::from_keys(default, ocsp_response, certificates)
+  }
+
+  fn from_keys(
+    default: TlsKey,
+    ocsp_response: Option<Vec<u8>>,
+    keys: Vec<(String, TlsKey)>,
+  ) -> Result<Self, AnyError> {
+    let mut default = certified_key(default)?;
+    default.ocsp = ocsp_response;
+    let mut by_name = HashMap::with_capacity(keys.len());
+    for (server_name, key) in keys {
+      by_name.insert(
+        server_name.to_ascii_lowercase(),
+        Arc::new(certified_key(key)?),
+      );
+    }
+    Ok(Self {
+      default: Arc::new(default),
+      by_name,
+    })
+  }
+
+  fn lookup(&self, server_name: &str) -> Option<&Arc<CertifiedKey>> {
+    let server_name = server_name.to_ascii_lowercase();
+    self.by_name.get(&server_name).or_else(|| {
+      let (_, parent) = server_name.split_once('.')?;
+      self.by_name.get(&format!("*.{parent}"))
+    })
+  }
+}
+
+impl ResolvesServerCert for SniCertResolver {
+  fn resolve(&self, client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+    let key = client_hello
+      .server_name()
+      .and_then(|server_name| self.lookup(server_name))
+      .unwrap_or(&self.default);
+    Some(key.clone())
+  }
+}
+
+fn certified_key(TlsKey(certs, key): TlsKey) -> Result<CertifiedKey, AnyError> {
+  let key = rustls::crypto::ring::sign::any_supported_type(&key)
+    .map_err(|e| anyhow!(e))?;
+  Ok(CertifiedKey::new(certs, key))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use webpki::types::CertificateDer;
+  use webpki::types::PrivateKeyDer;
+
+  static EXAMPLE_CRT: &[u8] = include_bytes!("testdata/example1_cert.der");
+  static EXAMPLE_KEY: &[u8] = include_bytes!("testdata/example1_prikey.der");
+
+  fn example_key() -> TlsKey {
+    TlsKey(
+      vec![CertificateDer::from(EXAMPLE_CRT)],
+      PrivateKeyDer::try_from(EXAMPLE_KEY).unwrap().clone_key(),
+    )
+  }
+
+  #[test]
+  fn lookup_by_server_name() {
+    let resolver = SniCertResolver::from_keys(
+      example_key(),
+      None,
+      vec![
+        ("Example.com".to_string(), example_key()),
+        ("*.deno.land".to_string(), example_key()),
+      ],
+    )
+    .unwrap();
+    assert!(resolver.lookup("example.com").is_some());
+    assert!(resolver.lookup("EXAMPLE.COM").is_some());
+    assert!(resolver.lookup("www.example.com").is_none());
+    assert!(resolver.lookup("api.deno.land").is_some());
+    assert!(resolver.lookup("deno.land").is_none());
+    assert!(resolver.lookup("a.b.deno.land").is_none());
+  }
+}
@@ -16,10 +16,11 @@ import {
   op_ws_get_buffered_amount,
   op_ws_get_error,
   op_ws_next_event,
+  op_ws_ping,
   op_ws_send_binary,
   op_ws_send_binary_ab,
-  op_ws_send_ping,
   op_ws_send_text,
+  op_ws_set_idle_timeout,
 } from "ext:core/ops";
 const {
   ArrayBufferIsView,
@@ -43,6 +44,7 @@ const {
   SymbolFor,
   SymbolIterator,
   TypedArrayPrototypeGetByteLength,
+  Uint8Array,
 } = primordials;
 
 import { URL } from "ext:deno_url/00_url.js";
@@ -50,7 +52,6 @@ import * as webidl from "ext:deno_webidl/00_webidl.js";
 import { createFilteredInspectProxy } from "ext:deno_console/01_console.js";
 import { HTTP_TOKEN_CODE_POINT_RE } from "ext:deno_web/00_infra.js";
 import { DOMException } from "ext:deno_web/01_dom_exception.js";
-import { clearTimeout, setTimeout } from "ext:deno_web/02_timers.js";
 import {
   CloseEvent,
   defineEventHandler,
@@ -63,6 +64,11 @@ import {
 } from "ext:deno_web/02_event.js";
 import { Blob, BlobPrototype } from "ext:deno_web/09_file.js";
 import { getLocationHref } from "ext:deno_web/12_location.js";
+import {
+  fillHeaders,
+  headerListFromHeaders,
+  headersFromHeaderList,
+} from "ext:deno_fetch/20_headers.js";
 
 webidl.converters["sequence<DOMString> or DOMString"] = (
   V,
@@ -79,6 +85,90 @@ webidl.converters["sequence<DOMString> or DOMString"] = (
   return webidl.converters.DOMString(V, prefix, context, opts);
 };
 
+webidl.converters.WebSocketCompressionOptions = webidl
+  .createDictionaryConverter(
+    "WebSocketCompressionOptions",
+    [
+      {
+        key: "serverNoContextTakeover",
+        converter: webidl.converters.boolean,
+      },
+      {
+        key: "clientNoContextTakeover",
+        converter: webidl.converters.boolean,
+      },
+      {
+        key: "serverMaxWindowBits",
+        converter: webidl.converters.octet,
+      },
+      {
+        key: "clientMaxWindowBits",
+        converter: webidl.converters.octet,
+      },
+    ],
+  );
+webidl.converters["boolean or WebSocketCompressionOptions"] = (
+  V,
+  prefix,
+  context,
+  opts,
+) => {
+  if (webidl.type(V) === "Object") {
+    return webidl.converters.WebSocketCompressionOptions(
+      V,
+      prefix,
+      context,
+      opts,
+    );
+  }
+  return webidl.converters.boolean(V, prefix, context, opts);
+};
+
+webidl.converters.WebSocketOptions = webidl.createDictionaryConverter(
+  "WebSocketOptions",
+  [
+    {
+      key: "protocols",
+      converter: webidl.converters["sequence<DOMString> or DOMString"],
+      get defaultValue() {
+        return [];
+      },
+    },
+    {
+      key: "headers",
+      converter: webidl.converters.HeadersInit,
+    },
+    {
+      key: "compression",
+      converter: webidl.converters["boolean or WebSocketCompressionOptions"],
+    },
+  ],
+);
+
+webidl.converters["sequence<DOMString> or DOMString or WebSocketOptions"] = (
+  V,
+  prefix,
+  context,
+  opts,
+) => {
+  // The options are a Deno extension, which can be told apart from a sequence
+  // of protocols as they aren't iterable.
+  if (
+    webidl.type(V) === "Object" && V !== null &&
+    V[SymbolIterator] === undefined
+  ) {
+    return webidl.converters.WebSocketOptions(V, prefix, context, opts);
+  }
+  return {
+    protocols: webidl.converters["sequence<DOMString> or DOMString"](
+      V,
+      prefix,
+      context,
+      opts,
+    ),
+  };
+};
+
 webidl.converters["WebSocketSend"] = (V, prefix, context, opts) => {
   // Union for (Blob or ArrayBufferView or ArrayBuffer or USVString)
   if (ObjectPrototypeIsPrototypeOf(BlobPrototype, V)) {
@@ -118,8 +208,6 @@ const _queueSend = Symbol("[[queueSend]]");
 
 const _server = Symbol("[[server]]");
 const _idleTimeoutDuration = Symbol("[[idleTimeout]]");
-const _idleTimeoutTimeout = Symbol("[[idleTimeoutTimeout]]");
-const _serverHandleIdleTimeout = Symbol("[[serverHandleIdleTimeout]]");
 
 class WebSocket extends EventTarget {
   constructor(url, protocols = []) {
@@ -133,17 +221,19 @@ class WebSocket extends EventTarget {
     this[_url] = "";
     this[_binaryType] = "blob";
     this[_idleTimeoutDuration] = 0;
-    this[_idleTimeoutTimeout] = undefined;
     this[_sendQueue] = [];
 
     const prefix = "Failed to construct 'WebSocket'";
     webidl.requiredArguments(arguments.length, 1, prefix);
     url = webidl.converters.USVString(url, prefix, "Argument 1");
-    protocols = webidl.converters["sequence<DOMString> or DOMString"](
+    const options = webidl.converters[
+      "sequence<DOMString> or DOMString or WebSocketOptions"
+    ](
       protocols,
       prefix,
       "Argument 2",
     );
+    protocols = options.protocols;
 
     let wsURL;
 
@@ -213,11 +303,21 @@ class WebSocket extends EventTarget {
       );
     }
 
+    const headers = headersFromHeaderList([], "request");
+    if (options.headers !== undefined) {
+      fillHeaders(headers, options.headers);
+    }
+
     PromisePrototypeThen(
       op_ws_create(
         "new WebSocket()",
         wsURL.href,
         ArrayPrototypeJoin(protocols, ", "),
+        undefined,
+        headerListFromHeaders(headers),
+        options.compression === true
+          ? {}
+          : options.compression || undefined,
       ),
       (create) => {
         this[_rid] = create.rid;
@@ -422,6 +522,9 @@ class WebSocket extends EventTarget {
 
   async [_eventLoop]() {
     const rid = this[_rid];
+    if (this[_idleTimeoutDuration]) {
+      op_ws_set_idle_timeout(rid, this[_idleTimeoutDuration]);
+    }
     while (this[_readyState] !== CLOSED) {
       const kind = await op_ws_next_event(rid);
       /* close the connection if read was cancelled, and we didn't get a close frame */
@@ -445,7 +548,6 @@ class WebSocket extends EventTarget {
             break;
           }
 
-          this[_serverHandleIdleTimeout]();
           const event = new MessageEvent("message", {
             data,
             origin: this[_url],
@@ -461,7 +563,6 @@ class WebSocket extends EventTarget {
             break;
           }
 
-          this[_serverHandleIdleTimeout]();
           // deno-lint-ignore prefer-primordials
           const buffer = d.buffer;
           let data;
@@ -481,7 +582,6 @@ class WebSocket extends EventTarget {
         }
         case 2: {
           /* pong */
-          this[_serverHandleIdleTimeout]();
           break;
         }
         case 3: {
@@ -498,13 +598,31 @@ class WebSocket extends EventTarget {
           core.tryClose(rid);
           break;
         }
+        case 4: {
+          /* idle timeout, the close frame was already sent */
+          this[_readyState] = CLOSED;
+          const reason = "No response from ping frame.";
+
+          const errorEv = new ErrorEvent("error", {
+            message: reason,
+          });
+          this.dispatchEvent(errorEv);
+
+          const closeEv = new CloseEvent("close", {
+            wasClean: false,
+            code: 1001,
+            reason,
+          });
+          this.dispatchEvent(closeEv);
+          core.tryClose(rid);
+          break;
+        }
         default: {
           /* close */
           const code = kind;
           const reason = code == 1005 ? "" : op_ws_get_error(rid);
           const prevState = this[_readyState];
           this[_readyState] = CLOSED;
-          clearTimeout(this[_idleTimeoutTimeout]);
 
           if (prevState === OPEN) {
             try {
@@ -563,45 +681,6 @@ class WebSocket extends EventTarget {
     }
   }
 
-  [_serverHandleIdleTimeout]() {
-    if (this[_idleTimeoutDuration]) {
-      clearTimeout(this[_idleTimeoutTimeout]);
-      this[_idleTimeoutTimeout] = setTimeout(async () => {
-        if (this[_readyState] === OPEN) {
-          await PromisePrototypeCatch(op_ws_send_ping(this[_rid]), () => {});
-          this[_idleTimeoutTimeout] = setTimeout(async () => {
-            if (this[_readyState] === OPEN) {
-              this[_readyState] = CLOSING;
-              const reason = "No response from ping frame.";
-              await PromisePrototypeCatch(
-                op_ws_close(this[_rid], 1001, reason),
-                () => {},
-              );
-              this[_readyState] = CLOSED;
-
-              const errEvent = new ErrorEvent("error", {
-                message: reason,
-              });
-              this.dispatchEvent(errEvent);
-
-              const event = new CloseEvent("close", {
-                wasClean: false,
-                code: 1001,
-                reason,
-              });
-              this.dispatchEvent(event);
-              core.tryClose(this[_rid]);
-            } else {
-              clearTimeout(this[_idleTimeoutTimeout]);
-            }
-          }, (this[_idleTimeoutDuration] / 2) * 1000);
-        } else {
-          clearTimeout(this[_idleTimeoutTimeout]);
-        }
-      }, (this[_idleTimeoutDuration] / 2) * 1000);
-    }
-  }
-
   [SymbolFor("Deno.privateCustomInspect")](inspect, inspectOptions) {
     return inspect(
       createFilteredInspectProxy({
@@ -652,8 +731,36 @@ defineEventHandler(WebSocket.prototype, "open");
 webidl.configureInterface(WebSocket);
 const WebSocketPrototype = WebSocket.prototype;
 
-function createWebSocketBranded() {
-  const socket = webidl.createBranded(WebSocket);
+// The sockets returned by `Deno.upgradeWebSocket`, which can also ping the
+// client. This class is not exposed, so `WebSocket.prototype` stays as the
+// spec defines it.
+class ServerWebSocket extends WebSocket {
+  constructor() {
+    webidl.illegalConstructor();
+  }
+
+  ping(data = undefined) {
+    webidl.assertBranded(this, WebSocketPrototype);
+    const prefix = "Failed to execute 'ping' on 'WebSocket'";
+
+    let payload;
+    if (typeof data === "string") {
+      payload = core.encode(data);
+    } else if (data !== undefined) {
+      data = webidl.converters.BufferSource(data, prefix, "Argument 1");
+      payload = ArrayBufferIsView(data) ? data : new Uint8Array(data);
+    }
+
+    if (this[_readyState] !== OPEN) {
+      throw new DOMException("'readyState' not OPEN", "InvalidStateError");
+    }
+
+    return op_ws_ping(this[_rid], payload);
+  }
+}
+
+function createWebSocketBranded(Type = WebSocket) {
+  const socket = webidl.createBranded(Type);
   socket[_rid] = undefined;
   socket[_role] = undefined;
   socket[_readyState] = CONNECTING;
@@ -666,22 +773,21 @@ function createWebSocketBranded() {
   // https://github.com/denoland/deno/issues/15340#issuecomment-1872353134
   socket[_binaryType] = "arraybuffer";
   socket[_idleTimeoutDuration] = 0;
-  socket[_idleTimeoutTimeout] = undefined;
   socket[_sendQueue] = [];
   return socket;
 }
 
 export {
   _eventLoop,
+  _extensions,
   _idleTimeoutDuration,
-  _idleTimeoutTimeout,
   _protocol,
   _readyState,
   _rid,
   _role,
   _server,
-  _serverHandleIdleTimeout,
   createWebSocketBranded,
   SERVER,
+  ServerWebSocket,
   WebSocket,
 };
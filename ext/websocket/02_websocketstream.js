@@ -61,6 +61,10 @@ webidl.converters.WebSocketStreamOptions = webidl.createDictionaryConverter(
       key: "headers",
       converter: webidl.converters.HeadersInit,
     },
+    {
+      key: "compression",
+      converter: webidl.converters["boolean or WebSocketCompressionOptions"],
+    },
   ],
 );
 webidl.converters.WebSocketCloseInfo = webidl.createDictionaryConverter(
@@ -173,6 +177,9 @@ class WebSocketStream {
           options.protocols ? ArrayPrototypeJoin(options.protocols, ", ") : "",
           cancelRid,
           headerListFromHeaders(headers),
+          options.compression === true
+            ? {}
+            : options.compression || undefined,
         ),
         (create) => {
           options.signal?.[remove](abort);
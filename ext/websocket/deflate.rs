@@ -0,0 +1,669 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+//! The `permessage-deflate` extension (RFC 7692).
+//!
+//! fastwebsockets refuses frames with reserved bits set, so the frames passing
+//! through the [`WebSocketStream`](crate::stream::WebSocketStream) of a
+//! compressed connection are rewritten by its [`DeflateFrames`]: compressed
+//! messages are read as binary messages without RSV1, and RSV1 is set on the
+//! messages that are written. Their payloads are (de)compressed by the
+//! [`PerMessageDeflate`] of the socket.
+
+use deno_core::anyhow::bail;
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use fastwebsockets::Frame;
+use fastwebsockets::OpCode;
+use fastwebsockets::Role;
+use flate2::Compress;
+use flate2::Compression;
+use flate2::Decompress;
+use flate2::FlushCompress;
+use flate2::FlushDecompress;
+use flate2::Status;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::rc::Rc;
+
+const PERMESSAGE_DEFLATE: &str = "permessage-deflate";
+
+const RSV1: u8 = 0x40;
+const OPCODE: u8 = 0x0f;
+
+/// Every compressed message ends with the trailer of a sync flush, which is
+/// left out of its payload.
+const TRAILER: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+/// The largest message that is decompressed, matching the largest frame
+/// fastwebsockets accepts.
+const MAX_MESSAGE_SIZE: usize = 64 << 20;
+
+/// zlib can't compress with a 256 byte window, so this is the smallest window
+/// we accept, even though RFC 7692 allows 8 bits.
+const MIN_WINDOW_BITS: u8 = 9;
+const MAX_WINDOW_BITS: u8 = 15;
+
+/// The `compression` option of `Deno.upgradeWebSocket()` and
+/// `WebSocketStream`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeflateOptions {
+  pub server_no_context_takeover: bool,
+  pub client_no_context_takeover: bool,
+  pub server_max_window_bits: Option<u8>,
+  pub client_max_window_bits: Option<u8>,
+}
+
+impl DeflateOptions {
+  fn validate(&self) -> Result<(), AnyError> {
+    for (name, bits) in [
+      ("serverMaxWindowBits", self.server_max_window_bits),
+      ("clientMaxWindowBits", self.client_max_window_bits),
+    ] {
+      match bits {
+        Some(bits) if !(MIN_WINDOW_BITS..=MAX_WINDOW_BITS).contains(&bits) => {
+          return Err(type_error(format!(
+            "Invalid '{name}': must be between {MIN_WINDOW_BITS} and {MAX_WINDOW_BITS}, received {bits}"
+          )));
+        }
+        _ => {}
+      }
+    }
+    Ok(())
+  }
+
+  /// The `Sec-WebSocket-Extensions` header of a client offering compression.
+  pub fn offer(&self) -> Result<String, AnyError> {
+    self.validate()?;
+    let params = DeflateParams {
+      server_no_context_takeover: self.server_no_context_takeover,
+      client_no_context_takeover: self.client_no_context_takeover,
+      server_max_window_bits: self.server_max_window_bits,
+      // Always let the server limit our window, as we can compress with any.
+      client_max_window_bits: Some(self.client_max_window_bits),
+    };
+    Ok(params.to_string())
+  }
+
+  /// Accepts the first of the `permessage-deflate` offers of a client that
+  /// can be, returning the `Sec-WebSocket-Extensions` header of the response.
+  pub fn accept(&self, offers: &str) -> Result<Option<String>, AnyError> {
+    self.validate()?;
+    let offers = extensions(offers)
+      .filter(|(name, _)| name.eq_ignore_ascii_case(PERMESSAGE_DEFLATE))
+      .filter_map(|(_, params)| DeflateParams::parse(params));
+    for offer in offers {
+      let server_max_window_bits = self
+        .server_max_window_bits
+        .unwrap_or(MAX_WINDOW_BITS)
+        .min(offer.server_max_window_bits.unwrap_or(MAX_WINDOW_BITS));
+      if server_max_window_bits < MIN_WINDOW_BITS {
+        continue;
+      }
+      // The window of the client can only be limited if it allows it.
+      let client_max_window_bits =
+        match (offer.client_max_window_bits, self.client_max_window_bits) {
+          (Some(offered), Some(bits)) => {
+            Some(Some(offered.map_or(bits, |offered| offered.min(bits))))
+          }
+          _ => None,
+        };
+      let response = DeflateParams {
+        server_no_context_takeover: self.server_no_context_takeover
+          || offer.server_no_context_takeover,
+        client_no_context_takeover: self.client_no_context_takeover
+          || offer.client_no_context_takeover,
+        server_max_window_bits: (server_max_window_bits < MAX_WINDOW_BITS)
+          .then_some(server_max_window_bits),
+        client_max_window_bits,
+      };
+      return Ok(Some(response.to_string()));
+    }
+    Ok(None)
+  }
+}
+
+/// Splits a `Sec-WebSocket-Extensions` header into the names and parameters
+/// of its extensions.
+fn extensions(header: &str) -> impl Iterator<Item = (&str, &str)> {
+  header
+    .split(',')
+    .map(|extension| {
+      let (name, params) = extension.split_once(';').unwrap_or((extension, ""));
+      (name.trim(), params)
+    })
+    .filter(|(name, _)| !name.is_empty())
+}
+
+/// The parameters of a `permessage-deflate` offer or response. A
+/// `client_max_window_bits` parameter without a value is `Some(None)`.
+#[derive(Debug, Default, PartialEq)]
+struct DeflateParams {
+  server_no_context_takeover: bool,
+  client_no_context_takeover: bool,
+  server_max_window_bits: Option<u8>,
+  client_max_window_bits: Option<Option<u8>>,
+}
+
+impl DeflateParams {
+  /// Parses the parameters of an extension, returning `None` if any is
+  /// unknown, repeated or invalid.
+  fn parse(params: &str) -> Option<Self> {
+    let mut parsed = Self::default();
+    for param in params.split(';').map(str::trim) {
+      if param.is_empty() {
+        continue;
+      }
+      let (name, value) = match param.split_once('=') {
+        Some((name, value)) => {
+          (name.trim(), Some(value.trim().trim_matches('"')))
+        }
+        None => (param, None),
+      };
+      match (name, value) {
+        ("server_no_context_takeover", None)
+          if !parsed.server_no_context_takeover =>
+        {
+          parsed.server_no_context_takeover = true;
+        }
+        ("client_no_context_takeover", None)
+          if !parsed.client_no_context_takeover =>
+        {
+          parsed.client_no_context_takeover = true;
+        }
+        ("server_max_window_bits", Some(value))
+          if parsed.server_max_window_bits.is_none() =>
+        {
+          parsed.server_max_window_bits = Some(parse_window_bits(value)?);
+        }
+        ("client_max_window_bits", value)
+          if parsed.client_max_window_bits.is_none() =>
+        {
+          parsed.client_max_window_bits = Some(match value {
+            Some(value) => Some(parse_window_bits(value)?),
+            None => None,
+          });
+        }
+        _ => return None,
+      }
+    }
+    Some(parsed)
+  }
+}
+
+fn parse_window_bits(value: &str) -> Option<u8> {
+  if !value.bytes().all(|b| b.is_ascii_digit()) || value.starts_with('0') {
+    return None;
+  }
+  value
+    .parse()
+    .ok()
+    .filter(|bits| (8..=MAX_WINDOW_BITS).contains(bits))
+}
+
+impl fmt::Display for DeflateParams {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(PERMESSAGE_DEFLATE)?;
+    if self.server_no_context_takeover {
+      f.write_str("; server_no_context_takeover")?;
+    }
+    if self.client_no_context_takeover {
+      f.write_str("; client_no_context_takeover")?;
+    }
+    if let Some(bits) = self.server_max_window_bits {
+      write!(f, "; server_max_window_bits={bits}")?;
+    }
+    match self.client_max_window_bits {
+      Some(Some(bits)) => write!(f, "; client_max_window_bits={bits}"),
+      Some(None) => f.write_str("; client_max_window_bits"),
+      None => Ok(()),
+    }
+  }
+}
+
+/// Compresses the messages sent over a socket and decompresses the ones it
+/// receives.
+pub(crate) struct PerMessageDeflate {
+  /// `None` if the messages we send can't be compressed with the window we
+  /// were left, in which case they're sent uncompressed.
+  compress: Option<RefCell<Compress>>,
+  no_context_takeover: bool,
+  decompress: RefCell<Decompress>,
+  /// For each message read, the opcode it had if it was compressed.
+  incoming: Rc<RefCell<VecDeque<Option<OpCode>>>>,
+}
+
+impl PerMessageDeflate {
+  /// Enables compression if `extensions`, the `Sec-WebSocket-Extensions`
+  /// header of the handshake response, negotiated it. Our window is limited
+  /// to `max_window_bits`, if the response doesn't limit it further.
+  pub fn negotiated(
+    extensions: &str,
+    role: Role,
+    max_window_bits: Option<u8>,
+  ) -> Result<Option<Self>, AnyError> {
+    let mut negotiated = None;
+    for (name, params) in self::extensions(extensions) {
+      if !name.eq_ignore_ascii_case(PERMESSAGE_DEFLATE) {
+        bail!("Unsupported extension '{name}'");
+      }
+      if negotiated.is_some() {
+        bail!("Extension '{PERMESSAGE_DEFLATE}' negotiated more than once");
+      }
+      let Some(params) = DeflateParams::parse(params) else {
+        bail!("Invalid '{PERMESSAGE_DEFLATE}' parameters: '{params}'");
+      };
+      negotiated = Some(params);
+    }
+    let Some(params) = negotiated else {
+      return Ok(None);
+    };
+
+    let (no_context_takeover, window_bits) = match role {
+      Role::Server => (
+        params.server_no_context_takeover,
+        params.server_max_window_bits,
+      ),
+      Role::Client => (
+        params.client_no_context_takeover,
+        params.client_max_window_bits.flatten(),
+      ),
+    };
+    let window_bits = window_bits
+      .unwrap_or(MAX_WINDOW_BITS)
+      .min(max_window_bits.unwrap_or(MAX_WINDOW_BITS));
+    let compress = (window_bits >= MIN_WINDOW_BITS).then(|| {
+      RefCell::new(Compress::new_with_window_bits(
+        Compression::default(),
+        false,
+        window_bits,
+      ))
+    });
+    Ok(Some(Self {
+      compress,
+      no_context_takeover,
+      // A full window can decompress messages compressed with any window.
+      decompress: RefCell::new(Decompress::new(false)),
+      incoming: Default::default(),
+    }))
+  }
+
+  /// The rewriting of the frames of the stream this socket uses.
+  pub fn frames(&self) -> DeflateFrames {
+    DeflateFrames {
+      reading: FrameScanner::default(),
+      writing: self.compress.is_some().then(FrameScanner::default),
+      incoming: self.incoming.clone(),
+    }
+  }
+
+  /// Compresses a message that is about to be sent. Its frame must not be
+  /// fragmented.
+  pub fn compress<'f>(&self, frame: Frame<'f>) -> Result<Frame<'f>, AnyError> {
+    let Some(compress) = &self.compress else {
+      return Ok(frame);
+    };
+    if !matches!(frame.opcode, OpCode::Text | OpCode::Binary) {
+      return Ok(frame);
+    }
+
+    let mut compress = compress.borrow_mut();
+    let input = &frame.payload[..];
+    let mut output = Vec::with_capacity(input.len() / 2 + 64);
+    let start = compress.total_in();
+    loop {
+      let consumed = (compress.total_in() - start) as usize;
+      compress.compress_vec(
+        &input[consumed..],
+        &mut output,
+        FlushCompress::Sync,
+      )?;
+      // The flush is complete once deflate has consumed everything without
+      // running out of room.
+      if compress.total_in() - start == input.len() as u64
+        && output.len() < output.capacity()
+      {
+        break;
+      }
+      output.reserve(output.capacity());
+    }
+    if output.ends_with(&TRAILER) {
+      output.truncate(output.len() - TRAILER.len());
+    }
+    if self.no_context_takeover {
+      compress.reset();
+    }
+    Ok(Frame::new(frame.fin, frame.opcode, None, output.into()))
+  }
+
+  /// Decompresses a message that was read, if it was compressed, returning
+  /// its original opcode and payload.
+  pub fn decompress(
+    &self,
+    frame: &Frame,
+  ) -> Result<Option<(OpCode, Vec<u8>)>, AnyError> {
+    if !matches!(frame.opcode, OpCode::Text | OpCode::Binary) {
+      return Ok(None);
+    }
+    let Some(opcode) = self.incoming.borrow_mut().pop_front().flatten() else {
+      return Ok(None);
+    };
+
+    let mut decompress = self.decompress.borrow_mut();
+    let mut output = Vec::with_capacity(frame.payload.len() * 2 + 64);
+    'inputs: for input in [&frame.payload[..], &TRAILER] {
+      let start = decompress.total_in();
+      loop {
+        let consumed = (decompress.total_in() - start) as usize;
+        let status = decompress
+          .decompress_vec(
+            &input[consumed..],
+            &mut output,
+            FlushDecompress::Sync,
+          )
+          .map_err(|_| type_error("Invalid compressed message"))?;
+        if output.len() > MAX_MESSAGE_SIZE {
+          return Err(type_error("Compressed message too large"));
+        }
+        // The message ended with a final block, after which nothing else may
+        // be decompressed with this context.
+        if status == Status::StreamEnd {
+          decompress.reset(false);
+          break 'inputs;
+        }
+        if output.len() < output.capacity() {
+          if decompress.total_in() - start != input.len() as u64 {
+            return Err(type_error("Invalid compressed message"));
+          }
+          break;
+        }
+        output.reserve(output.capacity());
+      }
+    }
+    Ok(Some((opcode, output)))
+  }
+}
+
+/// Rewrites the heads of the frames of a compressed connection for
+/// fastwebsockets.
+pub(crate) struct DeflateFrames {
+  reading: FrameScanner,
+  /// `None` if the messages sent aren't compressed.
+  writing: Option<FrameScanner>,
+  incoming: Rc<RefCell<VecDeque<Option<OpCode>>>>,
+}
+
+impl DeflateFrames {
+  /// Rewrites the frames among the `bytes` that were just read. Compressed
+  /// messages become binary messages without RSV1, and their original opcode
+  /// is kept for [`PerMessageDeflate::decompress`].
+  pub fn read(&mut self, bytes: &mut [u8]) {
+    let incoming = &self.incoming;
+    self.reading.scan(bytes, |head| {
+      let opcode = match *head & OPCODE {
+        0x1 => OpCode::Text,
+        0x2 => OpCode::Binary,
+        // RSV1 is only valid on the first frame of a message, so anywhere else
+        // it is left for fastwebsockets to refuse.
+        _ => return,
+      };
+      if *head & RSV1 == 0 {
+        incoming.borrow_mut().push_back(None);
+      } else {
+        *head = (*head & !(RSV1 | OPCODE)) | OpCode::Binary as u8;
+        incoming.borrow_mut().push_back(Some(opcode));
+      }
+    });
+  }
+
+  /// Whether messages written need their frames rewritten with [`Self::mark`].
+  pub fn compresses(&self) -> bool {
+    self.writing.is_some()
+  }
+
+  /// Sets RSV1 on the frames of the messages among the `bytes` that are about
+  /// to be written. Once they are, the returned scanner must be passed to
+  /// [`Self::written`].
+  pub fn mark(&self, bytes: &mut [u8]) -> FrameScanner {
+    let mut scanner = self.writing.unwrap_or_default();
+    scanner.scan(bytes, |head| {
+      if matches!(*head & OPCODE, 0x1 | 0x2) {
+        *head |= RSV1;
+      }
+    });
+    scanner
+  }
+
+  /// Records that the first `n` of the `bytes` returned by [`Self::mark`]
+  /// were written.
+  pub fn written(&mut self, scanner: FrameScanner, bytes: &mut [u8], n: usize) {
+    let Some(writing) = &mut self.writing else {
+      return;
+    };
+    if n == bytes.len() {
+      *writing = scanner;
+    } else {
+      writing.scan(&mut bytes[..n], |_| {});
+    }
+  }
+}
+
+/// Finds the frames in the bytes of a websocket stream.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct FrameScanner {
+  /// The start of the current frame's head, up to its payload length.
+  head: [u8; 10],
+  head_len: usize,
+  payload_left: u64,
+}
+
+impl FrameScanner {
+  /// Scans the next `bytes` of the stream, calling `on_head` with the first
+  /// byte of each frame, which holds its flags and opcode.
+  fn scan(&mut self, bytes: &mut [u8], mut on_head: impl FnMut(&mut u8)) {
+    let mut i = 0;
+    while i < bytes.len() {
+      if self.payload_left > 0 {
+        let n = self.payload_left.min((bytes.len() - i) as u64);
+        self.payload_left -= n;
+        i += n as usize;
+        continue;
+      }
+      if self.head_len == 0 {
+        on_head(&mut bytes[i]);
+      }
+      if self.head_len < self.head.len() {
+        self.head[self.head_len] = bytes[i];
+      }
+      self.head_len += 1;
+      i += 1;
+      if self.head_len < 2 {
+        continue;
+      }
+      let length = self.head[1] & 0x7f;
+      let extra = match length {
+        126 => 2,
+        127 => 8,
+        _ => 0,
+      };
+      let masked = self.head[1] & 0x80 != 0;
+      if self.head_len == 2 + extra + masked as usize * 4 {
+        self.payload_left = match extra {
+          0 => length as u64,
+          2 => u16::from_be_bytes([self.head[2], self.head[3]]) as u64,
+          _ => u64::from_be_bytes(self.head[2..10].try_into().unwrap()),
+        };
+        self.head_len = 0;
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::stream::WebSocketStream;
+  use crate::stream::WsStreamKind;
+  use deno_net::raw::NetworkStream;
+  use fastwebsockets::FragmentCollector;
+  use fastwebsockets::WebSocket;
+  use tokio::net::TcpListener;
+  use tokio::net::TcpStream;
+
+  #[test]
+  fn test_accept() {
+    let options = DeflateOptions::default();
+    assert_eq!(
+      options
+        .accept("permessage-deflate; client_max_window_bits")
+        .unwrap()
+        .as_deref(),
+      Some("permessage-deflate")
+    );
+    assert_eq!(options.accept("x-webkit-deflate-frame").unwrap(), None);
+    // Invalid and unsatisfiable offers are skipped.
+    assert_eq!(
+      options
+        .accept(
+          "permessage-deflate; foo, permessage-deflate; server_max_window_bits=8, permessage-deflate; server_max_window_bits=10"
+        )
+        .unwrap()
+        .as_deref(),
+      Some("permessage-deflate; server_max_window_bits=10")
+    );
+
+    let options = DeflateOptions {
+      server_no_context_takeover: true,
+      client_max_window_bits: Some(10),
+      ..Default::default()
+    };
+    assert_eq!(
+      options
+        .accept("permessage-deflate; client_max_window_bits=12; client_no_context_takeover")
+        .unwrap()
+        .as_deref(),
+      Some("permessage-deflate; server_no_context_takeover; client_no_context_takeover; client_max_window_bits=10")
+    );
+    assert_eq!(
+      options.accept("permessage-deflate").unwrap().as_deref(),
+      Some("permessage-deflate; server_no_context_takeover")
+    );
+
+    let options = DeflateOptions {
+      server_max_window_bits: Some(8),
+      ..Default::default()
+    };
+    assert!(options.accept("permessage-deflate").is_err());
+  }
+
+  #[test]
+  fn test_offer() {
+    assert_eq!(
+      DeflateOptions::default().offer().unwrap(),
+      "permessage-deflate; client_max_window_bits"
+    );
+    let options = DeflateOptions {
+      client_no_context_takeover: true,
+      server_max_window_bits: Some(12),
+      client_max_window_bits: Some(9),
+      ..Default::default()
+    };
+    assert_eq!(
+      options.offer().unwrap(),
+      "permessage-deflate; client_no_context_takeover; server_max_window_bits=12; client_max_window_bits=9"
+    );
+  }
+
+  #[test]
+  fn test_negotiated() {
+    assert!(PerMessageDeflate::negotiated("", Role::Client, None)
+      .unwrap()
+      .is_none());
+    assert!(PerMessageDeflate::negotiated("foo", Role::Client, None).is_err());
+    assert!(PerMessageDeflate::negotiated(
+      "permessage-deflate; client_max_window_bits=16",
+      Role::Client,
+      None
+    )
+    .is_err());
+    assert!(PerMessageDeflate::negotiated(
+      "permessage-deflate, permessage-deflate",
+      Role::Client,
+      None
+    )
+    .is_err());
+    let deflate = PerMessageDeflate::negotiated(
+      "permessage-deflate; client_max_window_bits=8",
+      Role::Client,
+      None,
+    )
+    .unwrap()
+    .unwrap();
+    assert!(!deflate.frames().compresses());
+  }
+
+  #[tokio::test]
+  async fn test_round_trip() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (client, server) =
+      tokio::join!(TcpStream::connect(addr), listener.accept());
+    let header =
+      "permessage-deflate; server_no_context_takeover; client_max_window_bits=9";
+    let socket = |stream, role| {
+      let deflate = PerMessageDeflate::negotiated(header, role, None)
+        .unwrap()
+        .unwrap();
+      let mut stream =
+        WebSocketStream::new(WsStreamKind::Network(stream), None);
+      stream.set_deflate(deflate.frames());
+      let mut ws = WebSocket::after_handshake(stream, role);
+      ws.set_writev(false);
+      ws.set_auto_pong(false);
+      (FragmentCollector::new(ws), deflate)
+    };
+    let (mut client, client_deflate) =
+      socket(NetworkStream::Tcp(client.unwrap()), Role::Client);
+    let (mut server, server_deflate) =
+      socket(NetworkStream::Tcp(server.unwrap().0), Role::Server);
+
+    let text = "hello ".repeat(10_000);
+    let binary = vec![7u8; 100_000];
+    for _ in 0..2 {
+      for frame in [
+        Frame::text(text.as_bytes().into()),
+        Frame::new(true, OpCode::Ping, None, b"ping"[..].into()),
+        Frame::binary(binary[..].into()),
+      ] {
+        let frame = client_deflate.compress(frame).unwrap();
+        client.write_frame(frame).await.unwrap();
+      }
+      let frame = server.read_frame().await.unwrap();
+      assert!(frame.payload.len() < 1000);
+      assert_eq!(
+        server_deflate.decompress(&frame).unwrap(),
+        Some((OpCode::Text, text.as_bytes().to_vec()))
+      );
+      let frame = server.read_frame().await.unwrap();
+      assert_eq!(frame.opcode, OpCode::Ping);
+      assert_eq!(server_deflate.decompress(&frame).unwrap(), None);
+      let frame = server.read_frame().await.unwrap();
+      assert_eq!(
+        server_deflate.decompress(&frame).unwrap(),
+        Some((OpCode::Binary, binary.clone()))
+      );
+
+      let frame = server_deflate
+        .compress(Frame::text(text.as_bytes().into()))
+        .unwrap();
+      server.write_frame(frame).await.unwrap();
+      let frame = client.read_frame().await.unwrap();
+      assert_eq!(
+        client_deflate.decompress(&frame).unwrap(),
+        Some((OpCode::Text, text.as_bytes().to_vec()))
+      );
+    }
+  }
+}
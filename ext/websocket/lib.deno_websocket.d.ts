@@ -119,10 +119,26 @@ declare interface WebSocket extends EventTarget {
   ): void;
 }
 
+/**
+ * Options which can be set when connecting a {@linkcode WebSocket}, in place
+ * of its protocols.
+ *
+ * @category WebSockets
+ */
+declare interface WebSocketOptions {
+  /** The subprotocols to request, one of which the server may select. */
+  protocols?: string | string[];
+  /** Headers to send with the handshake request, such as an `Authorization`
+   * header or cookies. The headers of the handshake itself, such as
+   * `Sec-WebSocket-Protocol`, can't be set. */
+  headers?: HeadersInit;
+}
+
 /** @category WebSockets */
 declare var WebSocket: {
   readonly prototype: WebSocket;
   new (url: string | URL, protocols?: string | string[]): WebSocket;
+  new (url: string | URL, options?: WebSocketOptions): WebSocket;
   readonly CLOSED: number;
   readonly CLOSING: number;
   readonly CONNECTING: number;
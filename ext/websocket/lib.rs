@@ -1,10 +1,13 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+use crate::deflate::DeflateOptions;
+use crate::deflate::PerMessageDeflate;
 use crate::stream::WebSocketStream;
 use bytes::Bytes;
 use deno_core::anyhow::bail;
 use deno_core::error::invalid_hostname;
 use deno_core::error::type_error;
 use deno_core::error::AnyError;
+use deno_core::futures::channel::oneshot;
 use deno_core::futures::TryFutureExt;
 use deno_core::op2;
 use deno_core::unsync::spawn;
@@ -12,6 +15,7 @@ use deno_core::url;
 use deno_core::AsyncMutFuture;
 use deno_core::AsyncRefCell;
 use deno_core::ByteString;
+use deno_core::CancelFuture;
 use deno_core::CancelHandle;
 use deno_core::CancelTryFuture;
 use deno_core::JsBuffer;
@@ -43,12 +47,16 @@ use serde::Serialize;
 use std::borrow::Cow;
 use std::cell::Cell;
 use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::fmt;
 use std::future::Future;
 use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::rc::Weak;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 use tokio::io::AsyncRead;
 use tokio::io::AsyncWrite;
 use tokio::io::ReadHalf;
@@ -63,6 +71,7 @@ use fastwebsockets::Role;
 use fastwebsockets::WebSocket;
 use fastwebsockets::WebSocketWrite;
 
+mod deflate;
 mod stream;
 
 static USE_WRITEV: Lazy<bool> = Lazy::new(|| {
@@ -167,6 +176,7 @@ async fn handshake_websocket(
   state: &Rc<RefCell<OpState>>,
   uri: &Uri,
   protocols: &str,
+  extensions: &str,
   headers: Option<Vec<(ByteString, ByteString)>>,
 ) -> Result<(WebSocket<WebSocketStream>, http::HeaderMap), AnyError> {
   let mut request = Request::builder().method(Method::GET).uri(
@@ -191,8 +201,13 @@ async fn handshake_websocket(
     );
 
   let user_agent = state.borrow().borrow::<WsUserAgent>().0.clone();
-  request =
-    populate_common_request_headers(request, &user_agent, protocols, &headers)?;
+  request = populate_common_request_headers(
+    request,
+    &user_agent,
+    protocols,
+    extensions,
+    &headers,
+  )?;
 
   let request = request.body(http_body_util::Empty::new())?;
   let domain = &uri.host().unwrap().to_string();
@@ -215,6 +230,7 @@ async fn handshake_websocket(
             authority,
             &user_agent,
             protocols,
+            extensions,
             domain,
             &headers,
             &addr,
@@ -263,6 +279,7 @@ async fn handshake_http2_wss(
   authority: &str,
   user_agent: &str,
   protocols: &str,
+  extensions: &str,
   domain: &str,
   headers: &Option<Vec<(ByteString, ByteString)>>,
   addr: &str,
@@ -292,8 +309,9 @@ async fn handshake_http2_wss(
     .scheme("https")
     .build()?;
   request = request.uri(uri);
-  request =
-    populate_common_request_headers(request, user_agent, protocols, headers)?;
+  request = populate_common_request_headers(
+    request, user_agent, protocols, extensions, headers,
+  )?;
   request = request.extension(h2::ext::Protocol::from("websocket"));
   let (resp, send) = send.send_request(request.body(())?, false)?;
   let resp = resp.await?;
@@ -348,6 +366,7 @@ pub fn create_ws_client_config(
     unsafely_ignore_certificate_errors,
     TlsKeys::Null,
     socket_use,
+    None,
   )
 }
 
@@ -356,6 +375,7 @@ fn populate_common_request_headers(
   mut request: http::request::Builder,
   user_agent: &str,
   protocols: &str,
+  extensions: &str,
   headers: &Option<Vec<(ByteString, ByteString)>>,
 ) -> Result<http::request::Builder, AnyError> {
   request = request
@@ -366,6 +386,10 @@ fn populate_common_request_headers(
     request = request.header("Sec-WebSocket-Protocol", protocols);
   }
 
+  if !extensions.is_empty() {
+    request = request.header("Sec-WebSocket-Extensions", extensions);
+  }
+
   if let Some(headers) = headers {
     for (key, value) in headers {
       let name = HeaderName::from_bytes(key)
@@ -401,6 +425,7 @@ pub async fn op_ws_create<WP>(
   #[string] protocols: String,
   #[smi] cancel_handle: Option<ResourceId>,
   #[serde] headers: Option<Vec<(ByteString, ByteString)>>,
+  #[serde] compression: Option<DeflateOptions>,
 ) -> Result<CreateResponse, AnyError>
 where
   WP: WebSocketPermissions + 'static,
@@ -425,14 +450,15 @@ where
   };
 
   let uri: Uri = url.parse()?;
+  let offer = match &compression {
+    Some(compression) => compression.offer()?,
+    None => String::new(),
+  };
 
-  let handshake = handshake_websocket(&state, &uri, &protocols, headers)
-    .map_err(|err| {
-      AnyError::from(DomExceptionNetworkError::new(&format!(
-        "failed to connect to WebSocket: {err}"
-      )))
-    });
-  let (stream, response) = match cancel_resource {
+  let handshake =
+    handshake_websocket(&state, &uri, &protocols, &offer, headers)
+      .map_err(handshake_error);
+  let (mut stream, response) = match cancel_resource {
     Some(rc) => handshake.try_or_cancel(rc).await,
     None => handshake.await,
   }?;
@@ -443,18 +469,50 @@ where
     }
   }
 
-  let mut state = state.borrow_mut();
-  let rid = state.resource_table.add(ServerWebSocket::new(stream));
-
   let protocol = match response.get("Sec-WebSocket-Protocol") {
-    Some(header) => header.to_str().unwrap(),
+    Some(header) => header.to_str().map_err(handshake_error)?,
     None => "",
   };
+  // The server may only pick one of the protocols that were requested.
+  if !protocol.is_empty()
+    && !protocols
+      .split(',')
+      .any(|requested| requested.trim() == protocol)
+  {
+    return Err(handshake_error(format!(
+      "server selected protocol '{protocol}', which was not requested"
+    )));
+  }
   let extensions = response
     .get_all("Sec-WebSocket-Extensions")
     .iter()
-    .map(|header| header.to_str().unwrap())
-    .collect::<String>();
+    .map(|header| header.to_str())
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(handshake_error)?
+    .join(", ");
+
+  // Extensions are only negotiated if compression was offered.
+  let deflate = match compression {
+    Some(compression) => PerMessageDeflate::negotiated(
+      &extensions,
+      Role::Client,
+      compression.client_max_window_bits,
+    )
+    .map_err(handshake_error)?,
+    None => None,
+  };
+  if let Some(deflate) = &deflate {
+    let mut inner = stream.into_inner();
+    inner.set_deflate(deflate.frames());
+    stream = WebSocket::after_handshake(inner, Role::Client);
+    stream.set_writev(false);
+  }
+
+  let mut state = state.borrow_mut();
+  let rid = state
+    .resource_table
+    .add(ServerWebSocket::new(stream, deflate));
+
   Ok(CreateResponse {
     rid,
     protocol: protocol.to_string(),
@@ -462,15 +520,36 @@ where
   })
 }
 
+fn handshake_error(err: impl fmt::Display) -> AnyError {
+  AnyError::from(DomExceptionNetworkError::new(&format!(
+    "failed to connect to WebSocket: {err}"
+  )))
+}
+
 #[repr(u16)]
 pub enum MessageKind {
   Text = 0,
   Binary = 1,
   Pong = 2,
   Error = 3,
+  IdleTimeout = 4,
   ClosedDefault = 1005,
 }
 
+/// The reason of the close frame sent to a peer that timed out.
+const IDLE_TIMEOUT_REASON: &str = "No response from ping frame.";
+
+/// The largest payload of a control frame.
+const MAX_PING_PAYLOAD: usize = 125;
+
+/// A ping sent by [`ServerWebSocket::ping`], which is answered by the pong
+/// with the same payload.
+struct PendingPing {
+  payload: Vec<u8>,
+  sent: Instant,
+  reply: Option<oneshot::Sender<Duration>>,
+}
+
 /// To avoid locks, we keep as much as we can inside of [`Cell`]s.
 pub struct ServerWebSocket {
   buffered: Cell<usize>,
@@ -481,10 +560,25 @@ pub struct ServerWebSocket {
   string: Cell<Option<String>>,
   ws_read: AsyncRefCell<FragmentCollectorRead<ReadHalf<WebSocketStream>>>,
   ws_write: AsyncRefCell<WebSocketWrite<WriteHalf<WebSocketStream>>>,
+  deflate: Option<PerMessageDeflate>,
+  /// When the last frame was read, to detect idle connections.
+  last_read: Cell<Instant>,
+  idle_timeout: Cell<Option<Duration>>,
+  /// Bumped whenever the idle timeout changes, which stops the watchdog of
+  /// the previous one.
+  idle_generation: Cell<u32>,
+  /// Cancels the pending read once the connection timed out.
+  idle_cancel: CancelHandle,
+  /// The pings awaiting a pong, oldest first, with their payload and the
+  /// sender of their latency if it was requested.
+  pings: RefCell<VecDeque<PendingPing>>,
 }
 
 impl ServerWebSocket {
-  fn new(ws: WebSocket<WebSocketStream>) -> Self {
+  fn new(
+    ws: WebSocket<WebSocketStream>,
+    deflate: Option<PerMessageDeflate>,
+  ) -> Self {
     let (ws_read, ws_write) = ws.split(tokio::io::split);
     Self {
       buffered: Cell::new(0),
@@ -495,6 +589,12 @@ impl ServerWebSocket {
       string: Cell::new(None),
       ws_read: AsyncRefCell::new(FragmentCollectorRead::new(ws_read)),
       ws_write: AsyncRefCell::new(ws_write),
+      deflate,
+      last_read: Cell::new(Instant::now()),
+      idle_timeout: Cell::new(None),
+      idle_generation: Cell::new(0),
+      idle_cancel: CancelHandle::new(),
+      pings: RefCell::new(VecDeque::new()),
     }
   }
 
@@ -525,11 +625,106 @@ impl ServerWebSocket {
     if ws.is_closed() {
       return Ok(());
     }
+    // Messages are compressed once their turn comes, as their order matters
+    // when the compression context is kept between them.
+    let frame = match &self.deflate {
+      Some(deflate) => deflate.compress(frame)?,
+      None => frame,
+    };
     ws.write_frame(frame)
       .await
       .map_err(|err| type_error(err.to_string()))?;
     Ok(())
   }
+
+  /// Sends a ping. Its latency is sent to `reply` once the matching pong is
+  /// read, and `reply` is dropped if the socket closes before that.
+  async fn ping(
+    self: &Rc<Self>,
+    payload: Vec<u8>,
+    reply: Option<oneshot::Sender<Duration>>,
+  ) -> Result<(), AnyError> {
+    let mut ws = self.reserve_lock().await;
+    if ws.is_closed() {
+      return Ok(());
+    }
+    self.pings.borrow_mut().push_back(PendingPing {
+      payload: payload.clone(),
+      sent: Instant::now(),
+      reply,
+    });
+    ws.write_frame(Frame::new(true, OpCode::Ping, None, payload.into()))
+      .await
+      .map_err(|err| type_error(err.to_string()))?;
+    Ok(())
+  }
+
+  /// Resolves the oldest pending ping with the payload of a pong. Pongs that
+  /// answer no ping, which peers may send unsolicited, are ignored.
+  fn pong(&self, payload: &[u8]) {
+    let mut pings = self.pings.borrow_mut();
+    let Some(index) = pings.iter().position(|ping| ping.payload == payload)
+    else {
+      return;
+    };
+    if let Some(PendingPing {
+      sent,
+      reply: Some(reply),
+      ..
+    }) = pings.remove(index)
+    {
+      _ = reply.send(sent.elapsed());
+    }
+  }
+}
+
+/// Pings the peer of a socket that has been idle for half of its idle
+/// timeout, and closes the socket if nothing is read during the other half.
+/// Only a weak reference is kept, so that the watchdog stops with the socket.
+async fn watch_idle_timeout(
+  socket: Weak<ServerWebSocket>,
+  generation: u32,
+  timeout: Duration,
+) {
+  let half = timeout / 2;
+  loop {
+    let idle_since = match socket.upgrade() {
+      Some(socket) if socket.idle_generation.get() == generation => {
+        socket.last_read.get()
+      }
+      _ => return,
+    };
+    if idle_since.elapsed() < half {
+      tokio::time::sleep_until((idle_since + half).into()).await;
+      continue;
+    }
+
+    match socket.upgrade() {
+      Some(socket) => {
+        if socket.ping(Vec::new(), None).await.is_err() {
+          return;
+        }
+      }
+      None => return,
+    }
+    tokio::time::sleep(half).await;
+
+    let Some(socket) = socket.upgrade() else {
+      return;
+    };
+    if socket.idle_generation.get() != generation {
+      return;
+    }
+    if socket.last_read.get() == idle_since {
+      let lock = socket.reserve_lock();
+      _ = socket
+        .write_frame(lock, Frame::close(1001, IDLE_TIMEOUT_REASON.as_bytes()))
+        .await;
+      socket.closed.set(true);
+      socket.idle_cancel.cancel();
+      return;
+    }
+  }
 }
 
 impl Resource for ServerWebSocket {
@@ -538,26 +733,48 @@ impl Resource for ServerWebSocket {
   }
 }
 
+/// Creates the websocket of a connection upgraded by a server. `extensions`
+/// is the `Sec-WebSocket-Extensions` header of the handshake response, if
+/// any.
 pub fn ws_create_server_stream(
   state: &mut OpState,
   transport: NetworkStream,
   read_buf: Bytes,
+  extensions: Option<&str>,
 ) -> Result<ResourceId, AnyError> {
-  let mut ws = WebSocket::after_handshake(
-    WebSocketStream::new(
-      stream::WsStreamKind::Network(transport),
-      Some(read_buf),
-    ),
-    Role::Server,
+  let deflate = match extensions {
+    Some(extensions) => {
+      PerMessageDeflate::negotiated(extensions, Role::Server, None)?
+    }
+    None => None,
+  };
+  let mut stream = WebSocketStream::new(
+    stream::WsStreamKind::Network(transport),
+    Some(read_buf),
   );
-  ws.set_writev(*USE_WRITEV);
+  if let Some(deflate) = &deflate {
+    stream.set_deflate(deflate.frames());
+  }
+  let mut ws = WebSocket::after_handshake(stream, Role::Server);
+  ws.set_writev(*USE_WRITEV && deflate.is_none());
   ws.set_auto_close(true);
   ws.set_auto_pong(true);
 
-  let rid = state.resource_table.add(ServerWebSocket::new(ws));
+  let rid = state.resource_table.add(ServerWebSocket::new(ws, deflate));
   Ok(rid)
 }
 
+/// Picks the `permessage-deflate` offer a server accepts, returning the
+/// `Sec-WebSocket-Extensions` header of its response.
+#[op2]
+#[string]
+pub fn op_ws_negotiate_deflate(
+  #[string] offers: &str,
+  #[serde] options: DeflateOptions,
+) -> Result<Option<String>, AnyError> {
+  options.accept(offers)
+}
+
 fn send_binary(state: &mut OpState, rid: ResourceId, data: &[u8]) {
   let resource = state.resource_table.get::<ServerWebSocket>(rid).unwrap();
   let data = data.to_vec();
@@ -657,8 +874,6 @@ pub async fn op_ws_send_text_async(
     .await
 }
 
-const EMPTY_PAYLOAD: &[u8] = &[];
-
 #[op2(fast)]
 #[smi]
 pub fn op_ws_get_buffered_amount(
@@ -682,13 +897,60 @@ pub async fn op_ws_send_ping(
     .borrow_mut()
     .resource_table
     .get::<ServerWebSocket>(rid)?;
-  let lock = resource.reserve_lock();
-  resource
-    .write_frame(
-      lock,
-      Frame::new(true, OpCode::Ping, None, EMPTY_PAYLOAD.into()),
-    )
-    .await
+  resource.ping(Vec::new(), None).await
+}
+
+/// Sends a ping and resolves with the time until its pong, in milliseconds.
+#[op2(async)]
+pub async fn op_ws_ping(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+  #[buffer] data: Option<JsBuffer>,
+) -> Result<f64, AnyError> {
+  let resource = state
+    .borrow_mut()
+    .resource_table
+    .get::<ServerWebSocket>(rid)?;
+  let payload = data.map(|data| data.to_vec()).unwrap_or_default();
+  if payload.len() > MAX_PING_PAYLOAD {
+    return Err(type_error(format!(
+      "Ping payload must be at most {MAX_PING_PAYLOAD} bytes, received {}",
+      payload.len()
+    )));
+  }
+  let (tx, rx) = oneshot::channel();
+  resource.ping(payload, Some(tx)).await?;
+  // The pending ping must not keep the socket alive, or it would never be
+  // rejected once the socket is closed.
+  drop(resource);
+  let latency = rx.await.map_err(|_| {
+    DomExceptionNetworkError::new("WebSocket closed before receiving a pong")
+  })?;
+  Ok(latency.as_secs_f64() * 1000.0)
+}
+
+/// Closes the socket once its peer stops responding for `seconds`, pinging
+/// it halfway through. A timeout of 0 disables this.
+#[op2(fast)]
+pub fn op_ws_set_idle_timeout(
+  state: &mut OpState,
+  #[smi] rid: ResourceId,
+  seconds: f64,
+) -> Result<(), AnyError> {
+  let resource = state.resource_table.get::<ServerWebSocket>(rid)?;
+  let timeout = (seconds.is_finite() && seconds > 0.0)
+    .then(|| Duration::from_secs_f64(seconds));
+  let generation = resource.idle_generation.get().wrapping_add(1);
+  resource.idle_generation.set(generation);
+  resource.idle_timeout.set(timeout);
+  if let Some(timeout) = timeout {
+    spawn(watch_idle_timeout(
+      Rc::downgrade(&resource),
+      generation,
+      timeout,
+    ));
+  }
+  Ok(())
 }
 
 #[op2(async(lazy))]
@@ -776,9 +1038,19 @@ pub async fn op_ws_next_event(
     async move { writer.borrow_mut().await.write_frame(frame).await }
   };
   loop {
-    let res = ws.read_frame(&mut sender).await;
+    let Ok(res) = ws
+      .read_frame(&mut sender)
+      .or_cancel(RcRef::map(&resource, |r| &r.idle_cancel))
+      .await
+    else {
+      // The watchdog already sent the close frame.
+      return MessageKind::IdleTimeout as u16;
+    };
     let val = match res {
-      Ok(val) => val,
+      Ok(val) => {
+        resource.last_read.set(Instant::now());
+        val
+      }
       Err(err) => {
         // No message was received, socket closed while we waited.
         // Report closed status to JavaScript.
@@ -791,8 +1063,23 @@ pub async fn op_ws_next_event(
       }
     };
 
-    break match val.opcode {
-      OpCode::Text => match String::from_utf8(val.payload.to_vec()) {
+    let decompressed = match &resource.deflate {
+      Some(deflate) => match deflate.decompress(&val) {
+        Ok(decompressed) => decompressed,
+        Err(err) => {
+          resource.set_error(Some(err.to_string()));
+          return MessageKind::Error as u16;
+        }
+      },
+      None => None,
+    };
+    let (opcode, payload) = match &decompressed {
+      Some((opcode, payload)) => (*opcode, &payload[..]),
+      None => (val.opcode, &val.payload[..]),
+    };
+
+    break match opcode {
+      OpCode::Text => match String::from_utf8(payload.to_vec()) {
         Ok(s) => {
           resource.string.set(Some(s));
           MessageKind::Text as u16
@@ -803,7 +1090,7 @@ pub async fn op_ws_next_event(
         }
       },
       OpCode::Binary => {
-        resource.buffer.set(Some(val.payload.to_vec()));
+        resource.buffer.set(Some(payload.to_vec()));
         MessageKind::Binary as u16
       }
       OpCode::Close => {
@@ -821,7 +1108,10 @@ pub async fn op_ws_next_event(
           close_code.into()
         }
       }
-      OpCode::Pong => MessageKind::Pong as u16,
+      OpCode::Pong => {
+        resource.pong(payload);
+        MessageKind::Pong as u16
+      }
       OpCode::Continuation | OpCode::Ping => {
         continue;
       }
@@ -846,7 +1136,10 @@ deno_core::extension!(deno_websocket,
     op_ws_send_binary_async,
     op_ws_send_text_async,
     op_ws_send_ping,
+    op_ws_ping,
+    op_ws_set_idle_timeout,
     op_ws_get_buffered_amount,
+    op_ws_negotiate_deflate,
   ],
   esm = [ "01_websocket.js", "02_websocketstream.js" ],
   options = {
@@ -1,4 +1,5 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+use crate::deflate::DeflateFrames;
 use bytes::Buf;
 use bytes::Bytes;
 use deno_net::raw::NetworkStream;
@@ -24,6 +25,7 @@ pub(crate) enum WsStreamKind {
 pub(crate) struct WebSocketStream {
   stream: WsStreamKind,
   pre: Option<Bytes>,
+  deflate: Option<DeflateFrames>,
 }
 
 impl WebSocketStream {
@@ -31,13 +33,18 @@ impl WebSocketStream {
     Self {
       stream,
       pre: buffer,
+      deflate: None,
     }
   }
-}
 
-impl AsyncRead for WebSocketStream {
+  /// Rewrites the frames passing through the stream for `permessage-deflate`.
+  /// Vectored writes must be disabled then.
+  pub fn set_deflate(&mut self, deflate: DeflateFrames) {
+    self.deflate = Some(deflate);
+  }
+
   // From hyper's Rewind (https://github.com/hyperium/hyper), MIT License, Copyright (c) Sean McArthur
-  fn poll_read(
+  fn poll_read_inner(
     mut self: Pin<&mut Self>,
     cx: &mut std::task::Context<'_>,
     buf: &mut ReadBuf<'_>,
@@ -83,11 +90,9 @@ impl AsyncRead for WebSocketStream {
       }
     }
   }
-}
 
-impl AsyncWrite for WebSocketStream {
-  fn poll_write(
-    mut self: Pin<&mut Self>,
+  fn poll_write_inner(
+    &mut self,
     cx: &mut std::task::Context<'_>,
     buf: &[u8],
   ) -> std::task::Poll<Result<usize, std::io::Error>> {
@@ -120,6 +125,41 @@ impl AsyncWrite for WebSocketStream {
       }
     }
   }
+}
+
+impl AsyncRead for WebSocketStream {
+  fn poll_read(
+    mut self: Pin<&mut Self>,
+    cx: &mut std::task::Context<'_>,
+    buf: &mut ReadBuf<'_>,
+  ) -> Poll<std::io::Result<()>> {
+    let filled = buf.filled().len();
+    ready!(self.as_mut().poll_read_inner(cx, buf))?;
+    if let Some(deflate) = &mut self.deflate {
+      deflate.read(&mut buf.filled_mut()[filled..]);
+    }
+    Poll::Ready(Ok(()))
+  }
+}
+
+impl AsyncWrite for WebSocketStream {
+  fn poll_write(
+    self: Pin<&mut Self>,
+    cx: &mut std::task::Context<'_>,
+    buf: &[u8],
+  ) -> std::task::Poll<Result<usize, std::io::Error>> {
+    let this = self.get_mut();
+    let Some(deflate) = this.deflate.as_ref().filter(|d| d.compresses()) else {
+      return this.poll_write_inner(cx, buf);
+    };
+    let mut marked = buf.to_vec();
+    let scanner = deflate.mark(&mut marked);
+    let n = ready!(this.poll_write_inner(cx, &marked))?;
+    if let Some(deflate) = &mut this.deflate {
+      deflate.written(scanner, &mut marked, n);
+    }
+    Poll::Ready(Ok(n))
+  }
 
   fn poll_flush(
     mut self: Pin<&mut Self>,
@@ -150,6 +190,9 @@ impl AsyncWrite for WebSocketStream {
   }
 
   fn is_write_vectored(&self) -> bool {
+    if self.deflate.is_some() {
+      return false;
+    }
     match &self.stream {
       WsStreamKind::Network(stream) => stream.is_write_vectored(),
       WsStreamKind::Upgraded(stream) => stream.is_write_vectored(),
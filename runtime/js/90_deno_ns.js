@@ -16,6 +16,7 @@ import * as tls from "ext:deno_net/02_tls.js";
 import * as serve from "ext:deno_http/00_serve.ts";
 import * as http from "ext:deno_http/01_http.js";
 import * as websocket from "ext:deno_http/02_websocket.ts";
+import * as sse from "ext:deno_http/03_sse.ts";
 import * as errors from "ext:runtime/01_errors.js";
 import * as version from "ext:runtime/01_version.ts";
 import * as permissions from "ext:runtime/10_permissions.js";
@@ -109,6 +110,7 @@ const denoNs = {
   serve: serve.serve,
   resolveDns: net.resolveDns,
   upgradeWebSocket: websocket.upgradeWebSocket,
+  createServerSentEventStream: sse.createServerSentEventStream,
   utime: fs.utime,
   utimeSync: fs.utimeSync,
   kill: process.kill,
@@ -134,6 +136,7 @@ const denoNs = {
   umask: fs.umask,
   httpClient: httpClient.httpClient,
   createHttpClient: httpClient.createHttpClient,
+  upgradeHttp: serve.upgradeHttp,
 };
 
 // NOTE(bartlomieju): keep IDs in sync with `cli/main.rs`
@@ -1,6 +1,7 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 use super::utils::into_string;
+use crate::worker::DeferredExit;
 use crate::worker::ExitCode;
 use deno_core::error::type_error;
 use deno_core::error::AnyError;
@@ -172,6 +173,10 @@ fn op_get_exit_code(state: &mut OpState) -> i32 {
 
 #[op2(fast)]
 fn op_exit(state: &mut OpState) {
+  if let Some(deferred_exit) = state.try_borrow::<DeferredExit>() {
+    deferred_exit.request();
+    return;
+  }
   let code = state.borrow::<ExitCode>().get();
   std::process::exit(code)
 }
@@ -789,6 +789,8 @@ pub struct WriteDescriptor(pub PathBuf);
 pub enum Host {
   Fqdn(FQDN),
   Ip(IpAddr),
+  /// The context ID (CID) of a vsock address.
+  Vsock(u32),
 }
 
 impl Host {
@@ -835,7 +837,7 @@ impl Host {
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
-pub struct NetDescriptor(pub Host, pub Option<u16>);
+pub struct NetDescriptor(pub Host, pub Option<u32>);
 
 impl QueryDescriptor for NetDescriptor {
   type AllowDesc = NetDescriptor;
@@ -894,6 +896,25 @@ impl QueryDescriptor for NetDescriptor {
 // TODO(bartlomieju): rewrite to not use `AnyError` but a specific error implementations
 impl NetDescriptor {
   pub fn parse(hostname: &str) -> Result<Self, AnyError> {
+    // A vsock address is a CID with an optional port, which are both u32.
+    if let Some(address) = hostname.strip_prefix("vsock:") {
+      let (cid, port) = match address.split_once(':') {
+        Some((cid, port)) => (cid, Some(port)),
+        None => (address, None),
+      };
+      let cid = cid.parse::<u32>().map_err(|_| {
+        uri_error(format!("invalid vsock CID in '{hostname}': '{cid}'"))
+      })?;
+      let port = port
+        .map(|port| {
+          port.parse::<u32>().map_err(|_| {
+            uri_error(format!("invalid port in '{hostname}': '{port}'"))
+          })
+        })
+        .transpose()?;
+      return Ok(NetDescriptor(Host::Vsock(cid), port));
+    }
+
     // If this is a IPv6 address enclosed in square brackets, parse it as such.
     if hostname.starts_with('[') {
       if let Some((ip, after)) = hostname.split_once(']') {
@@ -904,7 +925,7 @@ impl NetDescriptor {
           let port = port.parse::<u16>().map_err(|_| {
             uri_error(format!("invalid port in '{hostname}': '{port}'"))
           })?;
-          Some(port)
+          Some(port.into())
         } else if after.is_empty() {
           None
         } else {
@@ -941,7 +962,7 @@ impl NetDescriptor {
           uri_error(format!("invalid port in '{hostname}': '{port}'"))
         }
       })?;
-      Some(port)
+      Some(port.into())
     };
 
     Ok(NetDescriptor(host, port))
@@ -954,6 +975,7 @@ impl fmt::Display for NetDescriptor {
       Host::Fqdn(fqdn) => write!(f, "{fqdn}"),
       Host::Ip(IpAddr::V4(ip)) => write!(f, "{ip}"),
       Host::Ip(IpAddr::V6(ip)) => write!(f, "[{ip}]"),
+      Host::Vsock(cid) => write!(f, "vsock:{cid}"),
     }?;
     if let Some(port) = self.1 {
       write!(f, ":{}", port)?;
@@ -1525,7 +1547,7 @@ impl UnaryPermission<NetDescriptor> {
       .host_str()
       .ok_or_else(|| type_error(format!("Missing host in url: '{}'", url)))?;
     let host = Host::parse(host)?;
-    let port = url.port_or_known_default();
+    let port = url.port_or_known_default().map(u32::from);
     let descriptor = NetDescriptor(host, port);
     self.check_desc(Some(&descriptor), false, api_name)
   }
@@ -2384,7 +2406,21 @@ impl PermissionsContainer {
     let inner = &mut inner.net;
     skip_check_if_is_permission_fully_granted!(inner);
     let hostname = Host::parse(host.0.as_ref())?;
-    let descriptor = NetDescriptor(hostname, host.1);
+    let descriptor = NetDescriptor(hostname, host.1.map(u32::from));
+    inner.check(&descriptor, Some(api_name))
+  }
+
+  #[inline(always)]
+  pub fn check_net_vsock(
+    &mut self,
+    cid: u32,
+    port: u32,
+    api_name: &str,
+  ) -> Result<(), AnyError> {
+    let mut inner = self.inner.lock();
+    let inner = &mut inner.net;
+    skip_check_if_is_permission_fully_granted!(inner);
+    let descriptor = NetDescriptor(Host::Vsock(cid), Some(port));
     inner.check(&descriptor, Some(api_name))
   }
 
@@ -4228,6 +4264,15 @@ mod tests {
       ),
       ("", None),
       ("deno.land..", None),
+      ("vsock:3", Some(NetDescriptor(Host::Vsock(3), None))),
+      (
+        "vsock:4294967295:70000",
+        Some(NetDescriptor(Host::Vsock(u32::MAX), Some(70000))),
+      ),
+      ("vsock:", None),
+      ("vsock:a", None),
+      ("vsock:3:", None),
+      ("vsock:3:a", None),
     ];
 
     for (input, expected) in cases {
@@ -123,6 +123,15 @@ impl deno_net::NetPermissions for Permissions {
     unreachable!("snapshotting!")
   }
 
+  fn check_vsock(
+    &mut self,
+    _cid: u32,
+    _port: u32,
+    _api_name: &str,
+  ) -> Result<(), deno_core::error::AnyError> {
+    unreachable!("snapshotting!")
+  }
+
   fn check_read(
     &mut self,
     _p: &str,
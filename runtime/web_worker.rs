@@ -354,6 +354,7 @@ pub struct WebWorker {
   bootstrap_fn_global: Option<v8::Global<v8::Function>>,
   // Consumed when `bootstrap_fn` is called
   maybe_worker_metadata: Option<WorkerMetadata>,
+  should_wait_for_inspector_session: bool,
 }
 
 pub struct WebWorkerOptions {
@@ -371,6 +372,9 @@ pub struct WebWorkerOptions {
   pub format_js_error_fn: Option<Arc<FormatJsErrorFn>>,
   pub worker_type: WebWorkerType,
   pub maybe_inspector_server: Option<Arc<InspectorServer>>,
+  // If true, the worker will wait for inspector session before executing
+  // its main module, without breaking on its first statement.
+  pub should_wait_for_inspector_session: bool,
   pub get_error_class_fn: Option<GetErrorClassFn>,
   pub blob_store: Arc<BlobStore>,
   pub broadcast_channel: InMemoryBroadcastChannel,
@@ -578,11 +582,14 @@ impl WebWorker {
       js_runtime.op_state().borrow_mut().put(op_summary_metrics);
     }
 
+    let should_wait_for_inspector_session =
+      options.maybe_inspector_server.is_some()
+        && options.should_wait_for_inspector_session;
     if let Some(server) = options.maybe_inspector_server.clone() {
       server.register_inspector(
         main_module.to_string(),
         &mut js_runtime,
-        false,
+        should_wait_for_inspector_session,
       );
 
       // Put inspector handle into the op state so we can put a breakpoint when
@@ -638,6 +645,7 @@ impl WebWorker {
         close_on_idle: options.close_on_idle,
         has_executed_main_module: false,
         maybe_worker_metadata: options.maybe_worker_metadata,
+        should_wait_for_inspector_session,
       },
       external_handle,
     )
@@ -768,6 +776,9 @@ impl WebWorker {
     &mut self,
     id: ModuleId,
   ) -> Result<(), AnyError> {
+    if self.should_wait_for_inspector_session {
+      self.js_runtime.inspector().borrow_mut().wait_for_session();
+    }
     let mut receiver = self.js_runtime.mod_evaluate(id);
     let poll_options = PollEventLoopOptions::default();
 
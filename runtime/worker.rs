@@ -107,6 +107,34 @@ impl ExitCode {
   }
 }
 
+/// Makes `Deno.exit()` terminate the execution of JavaScript instead of
+/// exiting the process, so the embedder can finish up first, like writing
+/// profiles, and then exit with the exit code.
+#[derive(Clone)]
+pub struct DeferredExit {
+  isolate: v8::IsolateHandle,
+  requested: Arc<AtomicBool>,
+}
+
+impl DeferredExit {
+  pub fn new(isolate: v8::IsolateHandle) -> Self {
+    Self {
+      isolate,
+      requested: Default::default(),
+    }
+  }
+
+  /// Whether `Deno.exit()` was called.
+  pub fn is_requested(&self) -> bool {
+    self.requested.load(Relaxed)
+  }
+
+  pub(crate) fn request(&self) {
+    self.requested.store(true, Relaxed);
+    self.isolate.terminate_execution();
+  }
+}
+
 /// This worker is created and used by almost all
 /// subcommands in Deno executable.
 ///
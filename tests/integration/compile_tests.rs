@@ -1298,3 +1298,53 @@ fn standalone_jsr_dynamic_import() {
   output.assert_exit_code(0);
   output.assert_matches_text("Hello world\n");
 }
+
+#[test]
+fn standalone_code_cache() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let dir = context.temp_dir();
+  dir.write("main.ts", "console.log('Hello World');");
+  let exe = if cfg!(windows) {
+    dir.path().join("code_cache.exe")
+  } else {
+    dir.path().join("code_cache")
+  };
+  let compile = |extra_args: &[&str]| {
+    let mut args = vec!["compile", "-Ldebug"];
+    args.extend(extra_args);
+    let output = exe.to_string_lossy();
+    args.extend(["--output", &output, "main.ts"]);
+    context
+      .new_command()
+      .args_vec(args)
+      .run()
+      .skip_output_check()
+      .assert_exit_code(0);
+  };
+  compile(&[]);
+
+  // the first run compiles the module and stores its code cache
+  let output = context.new_command().name(&exe).split_output().run();
+  output
+    .assert_stdout_matches_text("Hello World\n")
+    .assert_stderr_matches_text(
+      "[WILDCARD]Updating V8 code cache for ES module: file:///[WILDCARD]main.ts[WILDCARD]",
+    );
+  assert_not_contains!(output.stderr(), "V8 code cache hit");
+
+  // later runs use it
+  let output = context.new_command().name(&exe).split_output().run();
+  output
+    .assert_stdout_matches_text("Hello World\n")
+    .assert_stderr_matches_text(
+      "[WILDCARD]V8 code cache hit for ES module: file:///[WILDCARD]main.ts[WILDCARD]",
+    );
+  assert_not_contains!(output.stderr(), "Updating V8 code cache");
+
+  compile(&["--no-code-cache"]);
+  let output = context.new_command().name(&exe).split_output().run();
+  output
+    .assert_stdout_matches_text("Hello World\n")
+    .skip_stderr_check();
+  assert_not_contains!(output.stderr(), "V8 code cache");
+}
@@ -1449,3 +1449,49 @@ async fn inspector_wait() {
   assert!(temp_dir.path().join("hello.txt").exists());
   tester.child.kill().unwrap();
 }
+
+#[tokio::test]
+async fn inspector_wait_worker() {
+  let script = util::testdata_path().join("inspector/inspect_wait_worker.js");
+  let test_context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = test_context.temp_dir();
+
+  let child = test_context
+    .new_command()
+    .args_vec([
+      "run",
+      "-A",
+      &inspect_flag_with_unique_port("--inspect-wait"),
+      &script.to_string_lossy(),
+    ])
+    .spawn_with_piped_output();
+
+  let mut tester = InspectorTester::create(child, ignore_script_parsed).await;
+
+  tester.assert_stderr_for_inspect_brk();
+  tester
+    .send_many(&[
+      json!({"id":1,"method":"Runtime.enable"}),
+      json!({"id":2,"method":"Runtime.runIfWaitingForDebugger"}),
+    ])
+    .await;
+  tester
+    .assert_received_messages(
+      &[r#"{"id":1,"result":{}}"#, r#"{"id":2,"result":{}}"#],
+      &[
+        r#"{"method":"Runtime.executionContextCreated","params":{"context":{"id":1,"#,
+      ],
+    )
+    .await;
+  assert_eq!(&tester.stderr_line(), "Debugger session started.");
+  assert_eq!(&tester.stderr_line(), "main did run");
+
+  // the worker waits for a debugger to connect to it as well
+  assert!(tester
+    .stderr_line()
+    .starts_with("Debugger listening on ws://"));
+  tester.assert_stderr_for_inspect_brk();
+  tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+  assert!(!temp_dir.path().join("hello.txt").exists());
+  tester.child.kill().unwrap();
+}
@@ -48,14 +48,16 @@ fn install_basic() {
   assert_eq!(content.chars().last().unwrap(), '\n');
 
   if cfg!(windows) {
+    assert_contains!(content, r#""run" "--check" "--no-config" "--lock" ""#);
     assert_contains!(
       content,
-      r#""run" "--check" "--no-config" "http://localhost:4545/echo.ts""#
+      r#".echo_test.lock.json" "http://localhost:4545/echo.ts""#
     );
   } else {
+    assert_contains!(content, "run --check --no-config --lock ");
     assert_contains!(
       content,
-      r#"run --check --no-config 'http://localhost:4545/echo.ts'"#
+      "/.echo_test.lock.json 'http://localhost:4545/echo.ts'"
     );
   }
 
@@ -76,6 +78,7 @@ fn install_basic() {
   assert!(!temp_dir.path().join("deno.lock").exists());
   // ensure uninstall occurred
   assert!(!file_path.exists());
+  assert!(!file_path.with_file_name(".echo_test.lock.json").exists());
 }
 
 #[test]
@@ -125,14 +128,16 @@ fn install_basic_global() {
   assert_eq!(content.chars().last().unwrap(), '\n');
 
   if cfg!(windows) {
+    assert_contains!(content, r#""run" "--check" "--no-config" "--lock" ""#);
     assert_contains!(
       content,
-      r#""run" "--check" "--no-config" "http://localhost:4545/echo.ts""#
+      r#".echo_test.lock.json" "http://localhost:4545/echo.ts""#
     );
   } else {
+    assert_contains!(content, "run --check --no-config --lock ");
     assert_contains!(
       content,
-      r#"run --check --no-config 'http://localhost:4545/echo.ts'"#
+      "/.echo_test.lock.json 'http://localhost:4545/echo.ts'"
     );
   }
 
@@ -153,6 +158,7 @@ fn install_basic_global() {
   assert!(!temp_dir.path().join("deno.lock").exists());
   // ensure uninstall occurred
   assert!(!file_path.exists());
+  assert!(!file_path.with_file_name(".echo_test.lock.json").exists());
 }
 
 #[test]
@@ -183,14 +189,16 @@ fn install_custom_dir_env_var() {
 
   let content = file_path.read_to_string();
   if cfg!(windows) {
+    assert_contains!(content, r#""run" "--check" "--no-config" "--lock" ""#);
     assert_contains!(
       content,
-      r#""run" "--check" "--no-config" "http://localhost:4545/echo.ts""#
+      r#".echo_test.lock.json" "http://localhost:4545/echo.ts""#
     );
   } else {
+    assert_contains!(content, "run --check --no-config --lock ");
     assert_contains!(
       content,
-      r#"run --check --no-config 'http://localhost:4545/echo.ts'"#
+      "/.echo_test.lock.json 'http://localhost:4545/echo.ts'"
     );
   }
 }
@@ -135,6 +135,10 @@ fn js_unit_test(test: String) {
     deno = deno.arg("--unstable-cron");
   }
 
+  if test == "serve_test" {
+    deno = deno.arg("--unstable-http");
+  }
+
   if test.contains("kv_") {
     deno = deno.arg("--unstable-kv");
   }
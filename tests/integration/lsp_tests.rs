@@ -1056,6 +1056,85 @@ fn lsp_workspace_enable_paths_no_workspace_configuration() {
   client.shutdown();
 }
 
+#[test]
+fn lsp_workspace_folders_settings() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.create_dir_all("a/src");
+  temp_dir.create_dir_all("b");
+  temp_dir.write("a/main.ts", "Date.now()");
+  temp_dir.write("a/src/main.ts", "Date.now()");
+  temp_dir.write("b/main.ts", "Date.now()");
+  let folder_a = temp_dir.path().join("a");
+  let folder_b = temp_dir.path().join("b");
+
+  let mut client = context.new_lsp_command().build();
+  client.initialize(|builder| {
+    builder.set_workspace_folders(vec![
+      lsp::WorkspaceFolder {
+        uri: folder_a.uri_dir(),
+        name: "a".to_string(),
+      },
+      lsp::WorkspaceFolder {
+        uri: folder_b.uri_dir(),
+        name: "b".to_string(),
+      },
+    ]);
+    // relative to each workspace folder
+    builder.set_enable_paths(vec!["./src".to_string()]);
+  });
+  client.change_folder_configuration(
+    &folder_b.url_dir(),
+    json!({ "deno": { "enable": true } }),
+  );
+
+  let mut hover = |url: String| {
+    client.did_open(json!({
+      "textDocument": {
+        "uri": url,
+        "languageId": "typescript",
+        "version": 1,
+        "text": "Date.now()",
+      }
+    }));
+    client.write_request(
+      "textDocument/hover",
+      json!({
+        "textDocument": { "uri": url },
+        "position": { "line": 0, "character": 5 }
+      }),
+    )
+  };
+  let enabled_hover = json!({
+    "contents": [
+      {
+        "language": "typescript",
+        "value": "(method) DateConstructor.now(): number",
+      },
+      "Returns the number of milliseconds elapsed since midnight, January 1, 1970 Universal Coordinated Time (UTC)."
+    ],
+    "range": {
+      "start": { "line": 0, "character": 5, },
+      "end": { "line": 0, "character": 8, }
+    }
+  });
+  assert_eq!(
+    hover(folder_a.join("main.ts").url_file().to_string()),
+    json!(null)
+  );
+  assert_eq!(
+    hover(folder_a.join("src/main.ts").url_file().to_string()),
+    enabled_hover
+  );
+  // enabled by the settings of its own folder
+  assert_eq!(
+    hover(folder_b.join("main.ts").url_file().to_string()),
+    enabled_hover
+  );
+
+  client.shutdown();
+}
+
 #[test]
 fn lsp_did_change_deno_configuration_notification() {
   let context = TestContextBuilder::new().use_temp_cwd().build();
@@ -1184,7 +1263,11 @@ fn lsp_deno_task() {
     "deno.jsonc",
     r#"{
     "tasks": {
-      "build": "deno test"
+      "build": "deno test",
+      "check": {
+        "command": "deno check main.ts",
+        "dependsOn": ["build"]
+      }
     }
   }"#,
   );
@@ -1203,6 +1286,11 @@ fn lsp_deno_task() {
         "name": "build",
         "detail": "deno test",
         "sourceUri": temp_dir.url().join("deno.jsonc").unwrap(),
+      },
+      {
+        "name": "check",
+        "detail": "deno check main.ts",
+        "sourceUri": temp_dir.url().join("deno.jsonc").unwrap(),
       }
     ])
   );
@@ -1781,6 +1869,71 @@ fn lsp_inlay_hints_not_enabled() {
   client.shutdown();
 }
 
+#[test]
+fn lsp_inlay_hints_parameter_names_literals() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let mut client = context.new_lsp_command().build();
+  client.initialize_with_config(
+    |_| {},
+    json!({
+      "deno": { "enable": true },
+      "typescript": {
+        "inlayHints": {
+          "parameterNames": { "enabled": "literals" },
+        },
+      },
+    }),
+  );
+  client.did_open(json!({
+    "textDocument": {
+      "uri": "file:///a/file.ts",
+      "languageId": "typescript",
+      "version": 1,
+      "text": r#"function a(b: string, c: number) {
+  return b;
+}
+declare const b: string;
+a(b, 1);
+a("foo", 2);
+"#
+    }
+  }));
+  let res = client.write_request(
+    "textDocument/inlayHint",
+    json!({
+      "textDocument": {
+        "uri": "file:///a/file.ts",
+      },
+      "range": {
+        "start": { "line": 0, "character": 0 },
+        "end": { "line": 6, "character": 0, }
+      }
+    }),
+  );
+  assert_eq!(
+    res,
+    json!([
+      {
+        "position": { "line": 4, "character": 5 },
+        "label": "c:",
+        "kind": 2,
+        "paddingRight": true
+      }, {
+        "position": { "line": 5, "character": 2 },
+        "label": "b:",
+        "kind": 2,
+        "paddingRight": true
+      }, {
+        "position": { "line": 5, "character": 9 },
+        "label": "c:",
+        "kind": 2,
+        "paddingRight": true
+      }
+    ])
+  );
+  client.shutdown();
+}
+
 #[test]
 fn lsp_workspace_disable_enable_paths() {
   fn run_test(use_trailing_slash: bool) {
@@ -3093,6 +3246,153 @@ fn lsp_call_hierarchy() {
   client.shutdown();
 }
 
+#[test]
+fn lsp_call_hierarchy_npm() {
+  let context = TestContextBuilder::new()
+    .use_http_server()
+    .use_temp_cwd()
+    .build();
+  let temp_dir = context.temp_dir();
+  let file = source_file(
+    temp_dir.path().join("main.ts"),
+    r#"import { add } from "npm:@denotest/add@1";
+
+export function sum() {
+  return add(1, 2);
+}
+"#,
+  );
+  let mut client = context.new_lsp_command().build();
+  client.initialize(|builder| {
+    builder.with_capabilities(|capabilities| {
+      capabilities.workspace.as_mut().unwrap().configuration = Some(false);
+    });
+    builder.set_workspace_folders(vec![lsp::WorkspaceFolder {
+      uri: temp_dir.uri(),
+      name: "project".to_string(),
+    }]);
+    builder.set_root_uri(temp_dir.url());
+    builder.set_enable_paths(vec!["./main.ts".to_string()]);
+  });
+  client.did_open_file(&file);
+  client.write_request(
+    "workspace/executeCommand",
+    json!({
+      "command": "deno.cache",
+      "arguments": [[], file.url()],
+    }),
+  );
+  let res = client.write_request(
+    "textDocument/prepareCallHierarchy",
+    json!({
+      "textDocument": file.identifier(),
+      "position": { "line": 3, "character": 9 },
+    }),
+  );
+  let item = res.as_array().unwrap().first().unwrap();
+  assert_eq!(item["name"], "add");
+  assert!(item["uri"]
+    .as_str()
+    .unwrap()
+    .ends_with("/@denotest/add/1.0.0/index.d.ts"));
+  // the npm package lives outside of the enabled paths, but is still enabled
+  // as a dependency of `main.ts`
+  let res = client
+    .write_request("callHierarchy/incomingCalls", json!({ "item": item }));
+  assert_eq!(
+    res,
+    json!([{
+      "from": {
+        "name": "sum",
+        "kind": 12,
+        "detail": "",
+        "uri": file.url(),
+        "range": {
+          "start": { "line": 2, "character": 0 },
+          "end": { "line": 4, "character": 1 },
+        },
+        "selectionRange": file.range_of("sum"),
+      },
+      "fromRanges": [{
+        "start": { "line": 3, "character": 9 },
+        "end": { "line": 3, "character": 12 },
+      }],
+    }])
+  );
+  client.shutdown();
+}
+
+#[test]
+fn lsp_type_hierarchy() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  let file = source_file(
+    temp_dir.path().join("file.ts"),
+    r#"interface Named {
+  name: string;
+}
+
+class Animal implements Named {
+  name = "";
+}
+
+class Dog extends Animal {}
+"#,
+  );
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  client.did_open_file(&file);
+  let res = client.write_request(
+    "textDocument/prepareTypeHierarchy",
+    json!({
+      "textDocument": file.identifier(),
+      "position": file.range_of("Animal").start,
+    }),
+  );
+  assert_eq!(
+    res,
+    json!([{
+      "name": "Animal",
+      "kind": 5,
+      "uri": file.url(),
+      "range": {
+        "start": { "line": 4, "character": 0 },
+        "end": { "line": 6, "character": 1 },
+      },
+      "selectionRange": file.range_of("Animal"),
+    }]),
+  );
+  let item = res.as_array().unwrap().first().unwrap();
+  let res =
+    client.write_request("typeHierarchy/supertypes", json!({ "item": item }));
+  assert_eq!(
+    res,
+    json!([{
+      "name": "Named",
+      "kind": 11,
+      "uri": file.url(),
+      "range": {
+        "start": { "line": 0, "character": 0 },
+        "end": { "line": 2, "character": 1 },
+      },
+      "selectionRange": file.range_of("Named"),
+    }]),
+  );
+  let res =
+    client.write_request("typeHierarchy/subtypes", json!({ "item": item }));
+  assert_eq!(
+    res,
+    json!([{
+      "name": "Dog",
+      "kind": 5,
+      "uri": file.url(),
+      "range": file.range_of("class Dog extends Animal {}"),
+      "selectionRange": file.range_of("Dog"),
+    }]),
+  );
+  client.shutdown();
+}
+
 #[test]
 fn lsp_large_doc_changes() {
   let context = TestContextBuilder::new().use_temp_cwd().build();
@@ -3442,6 +3742,50 @@ fn lsp_folding_range() {
   client.shutdown();
 }
 
+#[test]
+fn lsp_linked_editing_range() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  client.did_open(json!({
+    "textDocument": {
+      "uri": "file:///a/file.tsx",
+      "languageId": "typescriptreact",
+      "version": 1,
+      "text": "const a = <div>hi</div>;\n",
+    },
+  }));
+  let res = client.write_request(
+    "textDocument/linkedEditingRange",
+    json!({
+      "textDocument": { "uri": "file:///a/file.tsx" },
+      "position": { "line": 0, "character": 12 },
+    }),
+  );
+  assert_eq!(
+    res,
+    json!({
+      "ranges": [{
+        "start": { "line": 0, "character": 11 },
+        "end": { "line": 0, "character": 14 },
+      }, {
+        "start": { "line": 0, "character": 19 },
+        "end": { "line": 0, "character": 22 },
+      }],
+      "wordPattern": "[a-zA-Z0-9:\\-\\._$]*",
+    }),
+  );
+  let res = client.write_request(
+    "textDocument/linkedEditingRange",
+    json!({
+      "textDocument": { "uri": "file:///a/file.tsx" },
+      "position": { "line": 0, "character": 5 },
+    }),
+  );
+  assert_eq!(res, json!(null));
+  client.shutdown();
+}
+
 #[test]
 fn lsp_rename() {
   let context = TestContextBuilder::new().use_temp_cwd().build();
@@ -3599,6 +3943,7 @@ fn lsp_semantic_tokens() {
   assert_eq!(
     res,
     json!({
+      "resultId": "1",
       "data": [
         0, 5, 6, 1, 1, 0, 9, 6, 8, 9, 0, 8, 6, 8, 9, 2, 15, 3, 10, 5, 0, 4, 1,
         6, 1, 0, 12, 7, 2, 16, 1, 8, 1, 7, 41, 0, 4, 1, 6, 0, 0, 2, 5, 11, 16,
@@ -3634,46 +3979,131 @@ fn lsp_semantic_tokens() {
 }
 
 #[test]
-fn lsp_code_lens_references() {
+fn lsp_semantic_tokens_full_delta() {
   let context = TestContextBuilder::new().use_temp_cwd().build();
   let mut client = context.new_lsp_command().build();
   client.initialize_default();
-  client.change_configuration(json!({
-    "deno": {
-      "enable": true,
-      "codeLens": {
-        "references": true,
-      }
-    },
-  }));
   client.did_open(json!({
     "textDocument": {
       "uri": "file:///a/file.ts",
       "languageId": "typescript",
       "version": 1,
-      "text": concat!(
-        "class A {\n",
-        "  a = \"a\";\n",
-        "\n",
-        "  b() {\n",
-        "    console.log(this.a);\n",
-        "  }\n",
-        "\n",
-        "  c() {\n",
-        "    this.a = \"c\";\n",
-        "  }\n",
-        "}\n",
-        "\n",
-        "const a = new A();\n",
-        "a.b();\n",
-        "const b = 2;\n",
-        "const c = 3;\n",
-        "c; c;",
-      ),
-    }
+      "text": "const a = 1;\nconst b = a;\n",
+    },
   }));
   let res = client.write_request(
-    "textDocument/codeLens",
+    "textDocument/semanticTokens/full",
+    json!({
+      "textDocument": { "uri": "file:///a/file.ts" },
+    }),
+  );
+  assert_eq!(
+    res,
+    json!({
+      "resultId": "1",
+      "data": [0, 6, 1, 7, 9, 1, 6, 1, 7, 9, 0, 4, 1, 7, 8],
+    }),
+  );
+  client.write_notification(
+    "textDocument/didChange",
+    json!({
+      "textDocument": {
+        "uri": "file:///a/file.ts",
+        "version": 2,
+      },
+      "contentChanges": [
+        {
+          "range": {
+            "start": { "line": 2, "character": 0 },
+            "end": { "line": 2, "character": 0 },
+          },
+          "text": "const c = b;\n",
+        },
+      ],
+    }),
+  );
+  let res = client.write_request(
+    "textDocument/semanticTokens/full/delta",
+    json!({
+      "textDocument": { "uri": "file:///a/file.ts" },
+      "previousResultId": "1",
+    }),
+  );
+  assert_eq!(
+    res,
+    json!({
+      "resultId": "2",
+      "edits": [
+        {
+          "start": 15,
+          "deleteCount": 0,
+          "data": [1, 6, 1, 7, 9, 0, 4, 1, 7, 8],
+        },
+      ],
+    }),
+  );
+  // an unknown result id gets all of the tokens
+  let res = client.write_request(
+    "textDocument/semanticTokens/full/delta",
+    json!({
+      "textDocument": { "uri": "file:///a/file.ts" },
+      "previousResultId": "1",
+    }),
+  );
+  assert_eq!(
+    res,
+    json!({
+      "resultId": "3",
+      "data": [
+        0, 6, 1, 7, 9, 1, 6, 1, 7, 9, 0, 4, 1, 7, 8, 1, 6, 1, 7, 9, 0, 4, 1, 7,
+        8,
+      ],
+    }),
+  );
+  client.shutdown();
+}
+
+#[test]
+fn lsp_code_lens_references() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  client.change_configuration(json!({
+    "deno": {
+      "enable": true,
+      "codeLens": {
+        "references": true,
+      }
+    },
+  }));
+  client.did_open(json!({
+    "textDocument": {
+      "uri": "file:///a/file.ts",
+      "languageId": "typescript",
+      "version": 1,
+      "text": concat!(
+        "class A {\n",
+        "  a = \"a\";\n",
+        "\n",
+        "  b() {\n",
+        "    console.log(this.a);\n",
+        "  }\n",
+        "\n",
+        "  c() {\n",
+        "    this.a = \"c\";\n",
+        "  }\n",
+        "}\n",
+        "\n",
+        "const a = new A();\n",
+        "a.b();\n",
+        "const b = 2;\n",
+        "const c = 3;\n",
+        "c; c;",
+      ),
+    }
+  }));
+  let res = client.write_request(
+    "textDocument/codeLens",
     json!({
       "textDocument": {
         "uri": "file:///a/file.ts"
@@ -3834,6 +4264,111 @@ fn lsp_code_lens_references() {
   client.shutdown();
 }
 
+#[test]
+fn lsp_code_lens_references_exported_functions() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  client.change_configuration(json!({
+    "deno": {
+      "enable": true,
+      "codeLens": {
+        "references": true,
+      }
+    },
+  }));
+  client.did_open(json!({
+    "textDocument": {
+      "uri": "file:///a/file.ts",
+      "languageId": "typescript",
+      "version": 1,
+      "text": concat!(
+        "export function a() {}\n",
+        "function b() {}\n",
+        "a();\n",
+        "b();\n",
+      ),
+    }
+  }));
+  let res = client.write_request(
+    "textDocument/codeLens",
+    json!({
+      "textDocument": {
+        "uri": "file:///a/file.ts"
+      }
+    }),
+  );
+  assert_eq!(
+    res,
+    json!([{
+      "range": {
+        "start": { "line": 0, "character": 16 },
+        "end": { "line": 0, "character": 17 }
+      },
+      "data": {
+        "specifier": "file:///a/file.ts",
+        "source": "references"
+      }
+    }])
+  );
+  let res = client.write_request(
+    "codeLens/resolve",
+    json!({
+      "range": {
+        "start": { "line": 0, "character": 16 },
+        "end": { "line": 0, "character": 17 }
+      },
+      "data": {
+        "specifier": "file:///a/file.ts",
+        "source": "references"
+      }
+    }),
+  );
+  assert_eq!(
+    res,
+    json!({
+      "range": {
+        "start": { "line": 0, "character": 16 },
+        "end": { "line": 0, "character": 17 }
+      },
+      "command": {
+        "title": "1 reference",
+        "command": "deno.client.showReferences",
+        "arguments": [
+          "file:///a/file.ts",
+          { "line": 0, "character": 16 },
+          [{
+            "uri": "file:///a/file.ts",
+            "range": {
+              "start": { "line": 2, "character": 0 },
+              "end": { "line": 2, "character": 1 }
+            }
+          }]
+        ]
+      }
+    })
+  );
+  client.change_configuration(json!({
+    "deno": {
+      "enable": true,
+      "codeLens": {
+        "references": true,
+        "referencesExportedFunctions": false,
+      }
+    },
+  }));
+  let res = client.write_request(
+    "textDocument/codeLens",
+    json!({
+      "textDocument": {
+        "uri": "file:///a/file.ts"
+      }
+    }),
+  );
+  assert_eq!(res, json!(null));
+  client.shutdown();
+}
+
 #[test]
 fn lsp_code_lens_implementations() {
   let context = TestContextBuilder::new().use_temp_cwd().build();
@@ -4046,7 +4581,7 @@ fn lsp_code_lens_test() {
         "arguments": [
           "file:///a/file.ts",
           "test a",
-          { "inspect": false }
+          { "inspect": false, "filter": "/^test a$/" }
         ]
       }
     }, {
@@ -4060,7 +4595,7 @@ fn lsp_code_lens_test() {
         "arguments": [
           "file:///a/file.ts",
           "test a",
-          { "inspect": true }
+          { "inspect": true, "filter": "/^test a$/" }
         ]
       }
     }, {
@@ -4074,7 +4609,7 @@ fn lsp_code_lens_test() {
         "arguments": [
           "file:///a/file.ts",
           "test b",
-          { "inspect": false }
+          { "inspect": false, "filter": "/^test b$/" }
         ]
       }
     }, {
@@ -4088,7 +4623,7 @@ fn lsp_code_lens_test() {
         "arguments": [
           "file:///a/file.ts",
           "test b",
-          { "inspect": true }
+          { "inspect": true, "filter": "/^test b$/" }
         ]
       }
     }, {
@@ -4102,7 +4637,7 @@ fn lsp_code_lens_test() {
         "arguments": [
           "file:///a/file.ts",
           "test c",
-          { "inspect": false }
+          { "inspect": false, "filter": "/^test c$/" }
         ]
       }
     }, {
@@ -4116,7 +4651,7 @@ fn lsp_code_lens_test() {
         "arguments": [
           "file:///a/file.ts",
           "test c",
-          { "inspect": true }
+          { "inspect": true, "filter": "/^test c$/" }
         ]
       }
     }, {
@@ -4130,7 +4665,7 @@ fn lsp_code_lens_test() {
         "arguments": [
           "file:///a/file.ts",
           "test d",
-          { "inspect": false }
+          { "inspect": false, "filter": "/^test d$/" }
         ]
       }
     }, {
@@ -4144,7 +4679,7 @@ fn lsp_code_lens_test() {
         "arguments": [
           "file:///a/file.ts",
           "test d",
-          { "inspect": true }
+          { "inspect": true, "filter": "/^test d$/" }
         ]
       }
     }, {
@@ -4158,7 +4693,7 @@ fn lsp_code_lens_test() {
         "arguments": [
           "file:///a/file.ts",
           "test e",
-          { "inspect": false }
+          { "inspect": false, "filter": "/^test e$/" }
         ]
       }
     }, {
@@ -4172,7 +4707,7 @@ fn lsp_code_lens_test() {
         "arguments": [
           "file:///a/file.ts",
           "test e",
-          { "inspect": true }
+          { "inspect": true, "filter": "/^test e$/" }
         ]
       }
     }, {
@@ -4186,7 +4721,7 @@ fn lsp_code_lens_test() {
         "arguments": [
           "file:///a/file.ts",
           "test f",
-          { "inspect": false }
+          { "inspect": false, "filter": "/^test f$/" }
         ]
       }
     }, {
@@ -4200,7 +4735,7 @@ fn lsp_code_lens_test() {
         "arguments": [
           "file:///a/file.ts",
           "test f",
-          { "inspect": true }
+          { "inspect": true, "filter": "/^test f$/" }
         ]
       }
     }, {
@@ -4214,7 +4749,7 @@ fn lsp_code_lens_test() {
         "arguments": [
           "file:///a/file.ts",
           "test g",
-          { "inspect": false }
+          { "inspect": false, "filter": "/^test g$/" }
         ]
       }
     }, {
@@ -4228,7 +4763,7 @@ fn lsp_code_lens_test() {
         "arguments": [
           "file:///a/file.ts",
           "test g",
-          { "inspect": true }
+          { "inspect": true, "filter": "/^test g$/" }
         ]
       }
     }, {
@@ -4242,7 +4777,7 @@ fn lsp_code_lens_test() {
         "arguments": [
           "file:///a/file.ts",
           "test h",
-          { "inspect": false }
+          { "inspect": false, "filter": "/^test h$/" }
         ]
       }
     }, {
@@ -4256,7 +4791,7 @@ fn lsp_code_lens_test() {
         "arguments": [
           "file:///a/file.ts",
           "test h",
-          { "inspect": true }
+          { "inspect": true, "filter": "/^test h$/" }
         ]
       }
     }])
@@ -6039,6 +6574,58 @@ fn lsp_cache_then_definition() {
   client.shutdown();
 }
 
+#[test]
+fn lsp_cache_work_done_progress() {
+  let context = TestContextBuilder::new()
+    .use_http_server()
+    .use_temp_cwd()
+    .build();
+  let temp_dir = context.temp_dir();
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  client.did_open(json!({
+    "textDocument": {
+      "uri": temp_dir.url().join("file.ts").unwrap(),
+      "languageId": "typescript",
+      "version": 1,
+      "text": r#"import "http://localhost:4545/run/002_hello.ts";"#,
+    },
+  }));
+  client.write_request(
+    "workspace/executeCommand",
+    json!({
+      "command": "deno.cache",
+      "arguments": [
+        ["http://localhost:4545/run/002_hello.ts"],
+        temp_dir.url().join("file.ts").unwrap(),
+      ],
+      "workDoneToken": "cache-progress",
+    }),
+  );
+  let begin = client.read_notification_with_method::<Value>("$/progress");
+  assert_eq!(
+    begin,
+    Some(json!({
+      "token": "cache-progress",
+      "value": {
+        "kind": "begin",
+        "title": "Caching dependencies",
+        "cancellable": false,
+        "message": "http://localhost:4545/run/002_hello.ts",
+      },
+    })),
+  );
+  let end = client.read_notification_with_method::<Value>("$/progress");
+  assert_eq!(
+    end,
+    Some(json!({
+      "token": "cache-progress",
+      "value": { "kind": "end" },
+    })),
+  );
+  client.shutdown();
+}
+
 #[test]
 fn lsp_code_actions_imports() {
   let context = TestContextBuilder::new().use_temp_cwd().build();
@@ -6563,44 +7150,273 @@ fn lsp_code_actions_refactor() {
 }
 
 #[test]
-fn lsp_code_actions_imports_respects_fmt_config() {
+fn lsp_code_actions_organize_imports() {
   let context = TestContextBuilder::new().use_temp_cwd().build();
-  let temp_dir = context.temp_dir();
-  temp_dir.write(
-    "./deno.jsonc",
-    json!({
-      "fmt": {
-        "semiColons": false,
-        "singleQuote": true,
-      }
-    })
-    .to_string(),
-  );
-  temp_dir.write(
-    "file00.ts",
-    r#"
-    export interface MallardDuckConfigOptions extends DuckConfigOptions {
-      kind: "mallard";
-    }
-  "#,
-  );
-  temp_dir.write(
-    "file01.ts",
-    r#"
-    export interface DuckConfigOptions {
-      kind: string;
-      quacks: boolean;
-    }
-  "#,
-  );
   let mut client = context.new_lsp_command().build();
   client.initialize_default();
   client.did_open(json!({
     "textDocument": {
-      "uri": temp_dir.url().join("file00.ts").unwrap(),
+      "uri": "file:///a/file.ts",
       "languageId": "typescript",
       "version": 1,
-      "text": temp_dir.read_to_string("file00.ts"),
+      "text": r#"import { join } from "jsr:@std/path@1";
+import { z, a } from "./b.ts";
+import { unused } from "./c.ts";
+import { assert } from "node:assert";
+import { y } from "./b.ts";
+
+console.log(join, a, z, y, assert);
+"#,
+    }
+  }));
+  let res = client.write_request(
+    "textDocument/codeAction",
+    json!({
+      "textDocument": {
+        "uri": "file:///a/file.ts"
+      },
+      "range": {
+        "start": { "line": 0, "character": 0 },
+        "end": { "line": 0, "character": 0 }
+      },
+      "context": {
+        "diagnostics": [],
+        "only": ["source.organizeImports"]
+      }
+    }),
+  );
+  assert_eq!(
+    res,
+    json!([{
+      "title": "Organize Imports",
+      "kind": "source.organizeImports",
+      "data": { "specifier": "file:///a/file.ts" }
+    }])
+  );
+  let res = client.write_request(
+    "codeAction/resolve",
+    json!({
+      "title": "Organize Imports",
+      "kind": "source.organizeImports",
+      "data": { "specifier": "file:///a/file.ts" }
+    }),
+  );
+  // unused imports are removed, duplicates are merged and the declarations
+  // are grouped and sorted like `deno fmt` does
+  assert_eq!(
+    res,
+    json!({
+      "title": "Organize Imports",
+      "kind": "source.organizeImports",
+      "edit": {
+        "documentChanges": [{
+          "textDocument": {
+            "uri": "file:///a/file.ts",
+            "version": 1
+          },
+          "edits": [{
+            "range": {
+              "start": { "line": 1, "character": 0 },
+              "end": { "line": 3, "character": 0 }
+            },
+            "newText": ""
+          }, {
+            "range": {
+              "start": { "line": 4, "character": 0 },
+              "end": { "line": 4, "character": 0 }
+            },
+            "newText": "\n"
+          }, {
+            "range": {
+              "start": { "line": 4, "character": 9 },
+              "end": { "line": 4, "character": 10 }
+            },
+            "newText": "a, y, z"
+          }]
+        }]
+      },
+      "data": { "specifier": "file:///a/file.ts" }
+    })
+  );
+  client.shutdown();
+}
+
+#[test]
+fn lsp_code_actions_refactor_rename_command() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let mut client = context.new_lsp_command().build();
+  client.initialize(|builder| {
+    builder.with_capabilities(|c| {
+      let experimental = c.experimental.as_mut().unwrap();
+      experimental["commands"] = json!({ "commands": ["deno.client.rename"] });
+    });
+  });
+  client.did_open(json!({
+    "textDocument": {
+      "uri": "file:///a/file.ts",
+      "languageId": "typescript",
+      "version": 1,
+      "text": "console.log(1 + 2);\n",
+    },
+  }));
+  let res = client.write_request(
+    "codeAction/resolve",
+    json!({
+      "title": "Extract to constant in enclosing scope",
+      "kind": "refactor.extract.constant",
+      "data": {
+        "specifier": "file:///a/file.ts",
+        "range": {
+          "start": { "line": 0, "character": 12 },
+          "end": { "line": 0, "character": 17 },
+        },
+        "refactorName": "Extract Symbol",
+        "actionName": "constant_scope_0",
+      },
+    }),
+  );
+  // the position of the reference to the constant after the edits
+  assert_eq!(
+    res["command"],
+    json!({
+      "title": "Rename",
+      "command": "deno.client.rename",
+      "arguments": [
+        "file:///a/file.ts",
+        { "line": 1, "character": 12 },
+      ],
+    }),
+  );
+  client.shutdown();
+}
+
+#[test]
+fn lsp_code_actions_refactor_inline_function() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  client.did_open(json!({
+    "textDocument": {
+      "uri": "file:///a/a.ts",
+      "languageId": "typescript",
+      "version": 1,
+      "text": "export function add(a: number, b: number) {\n  return a + b;\n}\n",
+    },
+  }));
+  client.did_open(json!({
+    "textDocument": {
+      "uri": "file:///a/b.ts",
+      "languageId": "typescript",
+      "version": 1,
+      "text": "import { add } from \"./a.ts\";\n\nconsole.log(add(1, 2) * 3);\n",
+    },
+  }));
+  let res = client.write_request(
+    "textDocument/codeAction",
+    json!({
+      "textDocument": { "uri": "file:///a/a.ts" },
+      "range": {
+        "start": { "line": 0, "character": 16 },
+        "end": { "line": 0, "character": 16 },
+      },
+      "context": {
+        "diagnostics": [],
+        "only": ["refactor.inline"],
+        "triggerKind": 1,
+      },
+    }),
+  );
+  assert_eq!(
+    res,
+    json!([{
+      "title": "Inline function",
+      "kind": "refactor.inline.function",
+      "isPreferred": false,
+      "data": {
+        "specifier": "file:///a/a.ts",
+        "range": {
+          "start": { "line": 0, "character": 16 },
+          "end": { "line": 0, "character": 16 },
+        },
+        "refactorName": "Inline function",
+        "actionName": "Inline function",
+      },
+    }]),
+  );
+  let res = client.write_request("codeAction/resolve", res[0].clone());
+  // the call in the importing module is inlined and its import is removed
+  assert_eq!(
+    res["edit"],
+    json!({
+      "documentChanges": [{
+        "textDocument": { "uri": "file:///a/b.ts", "version": 1 },
+        "edits": [{
+          "range": {
+            "start": { "line": 0, "character": 0 },
+            "end": { "line": 1, "character": 0 },
+          },
+          "newText": "",
+        }, {
+          "range": {
+            "start": { "line": 2, "character": 12 },
+            "end": { "line": 2, "character": 21 },
+          },
+          "newText": "(1 + 2)",
+        }],
+      }, {
+        "textDocument": { "uri": "file:///a/a.ts", "version": 1 },
+        "edits": [{
+          "range": {
+            "start": { "line": 0, "character": 0 },
+            "end": { "line": 3, "character": 0 },
+          },
+          "newText": "",
+        }],
+      }],
+    }),
+  );
+  client.shutdown();
+}
+
+#[test]
+fn lsp_code_actions_imports_respects_fmt_config() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "./deno.jsonc",
+    json!({
+      "fmt": {
+        "semiColons": false,
+        "singleQuote": true,
+      }
+    })
+    .to_string(),
+  );
+  temp_dir.write(
+    "file00.ts",
+    r#"
+    export interface MallardDuckConfigOptions extends DuckConfigOptions {
+      kind: "mallard";
+    }
+  "#,
+  );
+  temp_dir.write(
+    "file01.ts",
+    r#"
+    export interface DuckConfigOptions {
+      kind: string;
+      quacks: boolean;
+    }
+  "#,
+  );
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  client.did_open(json!({
+    "textDocument": {
+      "uri": temp_dir.url().join("file00.ts").unwrap(),
+      "languageId": "typescript",
+      "version": 1,
+      "text": temp_dir.read_to_string("file00.ts"),
     }
   }));
   client.did_open(json!({
@@ -7541,9 +8357,28 @@ fn lsp_npm_completions_auto_import_and_quick_fix_no_import_map() {
 }
 
 #[test]
-fn lsp_completions_node_specifier() {
-  let context = TestContextBuilder::new().use_temp_cwd().build();
+fn lsp_npm_completions_auto_import_and_quick_fix_package_json() {
+  let context = TestContextBuilder::new()
+    .use_http_server()
+    .use_temp_cwd()
+    .build();
   let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    json!({
+      "nodeModulesDir": false,
+    })
+    .to_string(),
+  );
+  temp_dir.write(
+    "package.json",
+    json!({
+      "dependencies": {
+        "@denotest/types-exports-subpaths": "1",
+      },
+    })
+    .to_string(),
+  );
   let mut client = context.new_lsp_command().build();
   client.initialize_default();
   client.did_open(json!({
@@ -7551,22 +8386,160 @@ fn lsp_completions_node_specifier() {
       "uri": temp_dir.url().join("file.ts").unwrap(),
       "languageId": "typescript",
       "version": 1,
-      "text": "import fs from \"node:as\";\n",
-    },
+      "text": "import {getClient} from 'npm:@denotest/types-exports-subpaths@1/client';\n",
+    }
   }));
-  let list = client.get_completion_list(
-    temp_dir.url().join("file.ts").unwrap(),
-    (0, 23),
+  client.write_request(
+    "workspace/executeCommand",
     json!({
-      "triggerKind": 2,
-      "triggerCharacter": ".",
+      "command": "deno.cache",
+      "arguments": [
+        ["npm:@denotest/types-exports-subpaths@1/client"],
+        temp_dir.url().join("file.ts").unwrap(),
+      ],
     }),
   );
+
+  // try auto-import
+  client.did_open(json!({
+    "textDocument": {
+      "uri": temp_dir.url().join("a.ts").unwrap(),
+      "languageId": "typescript",
+      "version": 1,
+      "text": "getClie",
+    }
+  }));
+  let list = client.get_completion_list(
+    temp_dir.url().join("a.ts").unwrap(),
+    (0, 7),
+    json!({ "triggerKind": 1 }),
+  );
   assert!(!list.is_incomplete);
+  let item = list
+    .items
+    .iter()
+    .find(|item| item.label == "getClient")
+    .unwrap();
+  let mut res = client.write_request("completionItem/resolve", item);
+  let obj = res.as_object_mut().unwrap();
+  obj.remove("detail"); // not worth testing these
+  obj.remove("documentation");
   assert_eq!(
-    list
-      .items
-      .iter()
+    res,
+    json!({
+      "label": "getClient",
+      "labelDetails": {
+        "description": "@denotest/types-exports-subpaths/client",
+      },
+      "kind": 3,
+      "sortText": "￿16_0",
+      "additionalTextEdits": [
+        {
+          "range": {
+            "start": { "line": 0, "character": 0 },
+            "end": { "line": 0, "character": 0 }
+          },
+          "newText": "import { getClient } from \"@denotest/types-exports-subpaths/client\";\n\n"
+        }
+      ]
+    })
+  );
+
+  // try quick fix
+  let diagnostics = client.did_open(json!({
+    "textDocument": {
+      "uri": temp_dir.url().join("b.ts").unwrap(),
+      "languageId": "typescript",
+      "version": 1,
+      "text": "getClient",
+    }
+  }));
+  let diagnostics = diagnostics
+    .messages_with_file_and_source(
+      temp_dir.url().join("b.ts").unwrap().as_str(),
+      "deno-ts",
+    )
+    .diagnostics;
+  let res = client.write_request(
+    "textDocument/codeAction",
+    json!({
+      "textDocument": {
+        "uri": temp_dir.url().join("b.ts").unwrap(),
+      },
+      "range": {
+        "start": { "line": 0, "character": 0 },
+        "end": { "line": 0, "character": 9 }
+      },
+      "context": {
+        "diagnostics": diagnostics,
+        "only": ["quickfix"]
+      }
+    }),
+  );
+  assert_eq!(
+    res,
+    json!([{
+      "title": "Add import from \"@denotest/types-exports-subpaths/client\"",
+      "kind": "quickfix",
+      "diagnostics": [
+        {
+          "range": {
+            "start": { "line": 0, "character": 0 },
+            "end": { "line": 0, "character": 9 }
+          },
+          "severity": 1,
+          "code": 2304,
+          "source": "deno-ts",
+          "message": "Cannot find name 'getClient'.",
+        }
+      ],
+      "edit": {
+        "documentChanges": [{
+          "textDocument": {
+            "uri": temp_dir.url().join("b.ts").unwrap(),
+            "version": 1,
+          },
+          "edits": [{
+            "range": {
+              "start": { "line": 0, "character": 0 },
+              "end": { "line": 0, "character": 0 }
+            },
+            "newText": "import { getClient } from \"@denotest/types-exports-subpaths/client\";\n\n"
+          }]
+        }]
+      }
+    }])
+  );
+  client.shutdown();
+}
+
+#[test]
+fn lsp_completions_node_specifier() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  client.did_open(json!({
+    "textDocument": {
+      "uri": temp_dir.url().join("file.ts").unwrap(),
+      "languageId": "typescript",
+      "version": 1,
+      "text": "import fs from \"node:as\";\n",
+    },
+  }));
+  let list = client.get_completion_list(
+    temp_dir.url().join("file.ts").unwrap(),
+    (0, 23),
+    json!({
+      "triggerKind": 2,
+      "triggerCharacter": ".",
+    }),
+  );
+  assert!(!list.is_incomplete);
+  assert_eq!(
+    list
+      .items
+      .iter()
       .map(|i| i.label.as_str())
       .collect::<Vec<_>>(),
     vec![
@@ -7930,6 +8903,7 @@ fn lsp_semantic_tokens_for_disabled_module() {
   assert_eq!(
     res,
     json!({
+      "resultId": "1",
       "data": [0, 6, 9, 7, 9, 0, 15, 9, 7, 8],
     })
   );
@@ -9961,6 +10935,134 @@ fn lsp_ts_diagnostics_refresh_on_lsp_version_reset() {
   client.shutdown();
 }
 
+#[test]
+fn lsp_pull_diagnostics() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  let mut client = context.new_lsp_command().build();
+  client.initialize(|builder| {
+    builder.with_capabilities(|c| {
+      c.text_document.as_mut().unwrap().diagnostic =
+        Some(lsp::DiagnosticClientCapabilities::default());
+      c.experimental.as_mut().unwrap()["pullDiagnostics"] = json!(true);
+    });
+  });
+  client.did_open_raw(json!({
+    "textDocument": {
+      "uri": temp_dir.url().join("file.ts").unwrap(),
+      "languageId": "typescript",
+      "version": 1,
+      "text": "export const a: string = 1;\n",
+    },
+  }));
+  let res = client.write_request(
+    "textDocument/diagnostic",
+    json!({
+      "textDocument": { "uri": temp_dir.url().join("file.ts").unwrap() },
+    }),
+  );
+  let result_id = res.get("resultId").unwrap().as_str().unwrap().to_string();
+  assert_eq!(
+    res,
+    json!({
+      "kind": "full",
+      "resultId": result_id,
+      "items": [
+        {
+          "range": {
+            "start": { "line": 0, "character": 13 },
+            "end": { "line": 0, "character": 14 },
+          },
+          "severity": 1,
+          "code": 2322,
+          "source": "deno-ts",
+          "message": "Type 'number' is not assignable to type 'string'.",
+        },
+      ],
+    }),
+  );
+  let res = client.write_request(
+    "textDocument/diagnostic",
+    json!({
+      "textDocument": { "uri": temp_dir.url().join("file.ts").unwrap() },
+      "previousResultId": result_id,
+    }),
+  );
+  assert_eq!(res, json!({ "kind": "unchanged", "resultId": result_id }));
+  let res = client.write_request(
+    "workspace/diagnostic",
+    json!({
+      "previousResultIds": [{
+        "uri": temp_dir.url().join("file.ts").unwrap(),
+        "value": result_id,
+      }],
+    }),
+  );
+  assert_eq!(
+    res,
+    json!({
+      "items": [{
+        "kind": "unchanged",
+        "uri": temp_dir.url().join("file.ts").unwrap(),
+        "version": 1,
+        "resultId": result_id,
+      }],
+    }),
+  );
+  client.write_notification(
+    "textDocument/didChange",
+    json!({
+      "textDocument": {
+        "uri": temp_dir.url().join("file.ts").unwrap(),
+        "version": 2,
+      },
+      "contentChanges": [{ "text": "export const a: string = \"\";\n" }],
+    }),
+  );
+  let res = client.write_request(
+    "textDocument/diagnostic",
+    json!({
+      "textDocument": { "uri": temp_dir.url().join("file.ts").unwrap() },
+      "previousResultId": result_id,
+    }),
+  );
+  assert_eq!(res.get("kind").unwrap(), "full");
+  assert_ne!(res.get("resultId").unwrap(), &json!(result_id));
+  assert_eq!(res.get("items").unwrap(), &json!([]));
+  client.shutdown();
+}
+
+#[test]
+fn lsp_pull_diagnostics_not_negotiated() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  let mut client = context.new_lsp_command().build();
+  // supporting pull diagnostics isn't enough to stop publishing them
+  client.initialize(|builder| {
+    builder.with_capabilities(|c| {
+      c.text_document.as_mut().unwrap().diagnostic =
+        Some(lsp::DiagnosticClientCapabilities::default());
+    });
+  });
+  let diagnostics = client.did_open(json!({
+    "textDocument": {
+      "uri": temp_dir.url().join("file.ts").unwrap(),
+      "languageId": "typescript",
+      "version": 1,
+      "text": "export const a: string = 1;\n",
+    },
+  }));
+  assert_eq!(
+    diagnostics
+      .all()
+      .iter()
+      .map(|d| d.message.as_str())
+      .collect::<Vec<_>>(),
+    vec!["Type 'number' is not assignable to type 'string'."],
+  );
+  client.shutdown();
+}
+
 #[test]
 fn lsp_diagnostics_none_for_resolving_types() {
   let context = TestContextBuilder::for_npm().use_temp_cwd().build();
@@ -10151,6 +11253,58 @@ fn lsp_performance() {
   client.shutdown();
 }
 
+#[test]
+fn lsp_profile() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  let profile_path = temp_dir.path().join("lsp_profile.json");
+  let mut client = context.new_lsp_command().profile(&profile_path).build();
+  client.initialize_default();
+  client.did_open(json!({
+    "textDocument": {
+      "uri": "file:///a/file.ts",
+      "languageId": "typescript",
+      "version": 1,
+      "text": "console.log(Deno.args);\n"
+    }
+  }));
+  client.write_request(
+    "textDocument/hover",
+    json!({
+      "textDocument": {
+        "uri": "file:///a/file.ts"
+      },
+      "position": { "line": 0, "character": 19 }
+    }),
+  );
+  let res = client.write_request("deno/profile", json!(null));
+  let hover = res["requests"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .find(|r| r["name"] == "lsp.hover")
+    .unwrap();
+  assert_eq!(hover["count"], 1);
+  assert!(res["tsc"]["roundTrips"].as_u64().unwrap() > 0);
+  assert!(res["tsc"]["requests"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .any(|r| r["name"] == "tsc.request.getQuickInfoAtPosition"));
+  let source_files = &res["caches"]["tscSourceFiles"];
+  assert!(source_files["misses"].as_u64().unwrap() > 0);
+  assert!(source_files["hitRate"].is_f64());
+  assert!(!profile_path.exists());
+  client.shutdown();
+  let profile: Value =
+    serde_json::from_str(&profile_path.read_to_string()).unwrap();
+  assert!(profile["requests"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .any(|r| r["name"] == "lsp.hover"));
+}
+
 #[test]
 fn lsp_format_no_changes() {
   let context = TestContextBuilder::new().use_temp_cwd().build();
@@ -13513,23 +14667,426 @@ fn lsp_deno_json_scopes_file_rename_import_edits() {
 }
 
 #[test]
-fn lsp_deno_json_scopes_goto_implementations() {
+fn lsp_diagnostics_unused_import_map_entries() {
   let context = TestContextBuilder::new().use_temp_cwd().build();
   let temp_dir = context.temp_dir();
-  temp_dir.create_dir_all("project1");
-  temp_dir.create_dir_all("project2");
-  temp_dir.write("project1/deno.json", json!({}).to_string());
-  temp_dir.write("project2/deno.json", json!({}).to_string());
-  let file1 = source_file(
-    temp_dir.path().join("project1/file.ts"),
-    "export interface Foo {}\n",
-  );
-  let file2 = source_file(
-    temp_dir.path().join("project2/file.ts"),
-    r#"
-      import type { Foo } from "../project1/file.ts";
-      export class SomeFoo implements Foo {}
-    "#,
+  temp_dir.write(
+    "deno.json",
+    json!({
+      "imports": {
+        "unused": "./unused.ts",
+        "mod": "./mod.ts",
+      },
+    })
+    .to_string(),
+  );
+  temp_dir.write("mod.ts", "export const a = 1;\n");
+  temp_dir.write("main.ts", "import { a } from \"mod\";\nconsole.log(a);\n");
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  client.did_open(json!({
+    "textDocument": {
+      "uri": temp_dir.url().join("main.ts").unwrap(),
+      "languageId": "typescript",
+      "version": 1,
+      "text": temp_dir.read_to_string("main.ts"),
+    },
+  }));
+  let deno_json_text = r#"{
+  "imports": {
+    "unused": "./unused.ts",
+    "mod": "./mod.ts",
+    "mod": "./mod.ts"
+  }
+}
+"#;
+  client.did_open_raw(json!({
+    "textDocument": {
+      "uri": temp_dir.url().join("deno.json").unwrap(),
+      "languageId": "json",
+      "version": 1,
+      "text": deno_json_text,
+    },
+  }));
+  let diagnostics = client.read_diagnostics();
+  let diagnostics =
+    diagnostics.for_file(&temp_dir.url().join("deno.json").unwrap());
+  assert_eq!(
+    json!(diagnostics),
+    json!([
+      {
+        "range": {
+          "start": { "line": 2, "character": 4 },
+          "end": { "line": 2, "character": 27 },
+        },
+        "severity": 4,
+        "code": "unused-import-map-entry",
+        "source": "deno",
+        "message": "The import map entry \"unused\" is not used by any module of the workspace.",
+        "tags": [1],
+        "data": {
+          "key": "unused",
+          "removalRange": {
+            "start": { "line": 2, "character": 4 },
+            "end": { "line": 3, "character": 4 },
+          },
+        },
+      },
+      {
+        "range": {
+          "start": { "line": 3, "character": 4 },
+          "end": { "line": 3, "character": 21 },
+        },
+        "severity": 2,
+        "code": "duplicate-import-map-entry",
+        "source": "deno",
+        "message": "The import map entry \"mod\" is ignored because it is defined again later.",
+        "data": {
+          "key": "mod",
+          "removalRange": {
+            "start": { "line": 3, "character": 4 },
+            "end": { "line": 4, "character": 4 },
+          },
+        },
+      },
+    ]),
+  );
+  let res = client.write_request(
+    "textDocument/codeAction",
+    json!({
+      "textDocument": {
+        "uri": temp_dir.url().join("deno.json").unwrap(),
+      },
+      "range": {
+        "start": { "line": 2, "character": 4 },
+        "end": { "line": 2, "character": 27 },
+      },
+      "context": {
+        "diagnostics": [diagnostics[0]],
+        "only": ["quickfix"],
+      },
+    }),
+  );
+  assert_eq!(
+    res,
+    json!([{
+      "title": "Remove unused entry \"unused\".",
+      "kind": "quickfix",
+      "diagnostics": [diagnostics[0]],
+      "edit": {
+        "changes": {
+          temp_dir.url().join("deno.json").unwrap(): [{
+            "range": {
+              "start": { "line": 2, "character": 4 },
+              "end": { "line": 3, "character": 4 },
+            },
+            "newText": "",
+          }],
+        },
+      },
+    }]),
+  );
+  client.shutdown();
+}
+
+#[test]
+fn lsp_diagnostics_unused_import_map_entries_follow_module_graph() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    json!({
+      "exports": "./mod.ts",
+      "imports": {
+        "util": "./util.ts",
+        "unused": "./unused.ts",
+      },
+    })
+    .to_string(),
+  );
+  temp_dir.write("mod.ts", "export * from \"./lib.ts\";\n");
+  temp_dir.write("lib.ts", "export * from \"util\";\n");
+  temp_dir.write("util.ts", "export const a = 1;\n");
+  let mut client = context.new_lsp_command().build();
+  // none of the modules are loaded before they're found in the module graph
+  client.initialize(|builder| {
+    builder.set_preload_limit(0);
+  });
+  client.did_open_raw(json!({
+    "textDocument": {
+      "uri": temp_dir.url().join("deno.json").unwrap(),
+      "languageId": "json",
+      "version": 1,
+      "text": temp_dir.read_to_string("deno.json"),
+    },
+  }));
+  let diagnostics = client.read_diagnostics();
+  let diagnostics =
+    diagnostics.for_file(&temp_dir.url().join("deno.json").unwrap());
+  let unused_keys = diagnostics
+    .iter()
+    .filter_map(|d| d.data.as_ref()?.get("key")?.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(unused_keys, vec!["unused"]);
+  client.shutdown();
+}
+
+#[test]
+fn lsp_embedded_scripts_plugin() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    json!({
+      "plugins": {
+        "lsp": ["./vue_plugin.js"],
+      },
+    })
+    .to_string(),
+  );
+  temp_dir.write(
+    "vue_plugin.js",
+    r#"export default {
+  name: "vue",
+  extensions: [".vue"],
+  extract(source, specifier) {
+    const match = /(<script[^>]*>)([\s\S]*?)<\/script>/.exec(source);
+    if (match == null) {
+      return [];
+    }
+    const start = match.index + match[1].length;
+    return [{ start, end: start + match[2].length, lang: "ts" }];
+  },
+};
+"#,
+  );
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  let diagnostics = client.did_open(json!({
+    "textDocument": {
+      "uri": temp_dir.url().join("App.vue").unwrap(),
+      "languageId": "vue",
+      "version": 1,
+      "text": "<template>\n  <p>{{ msg }}</p>\n</template>\n<script lang=\"ts\">\nconst greeting = \"hello\";\nconst msg: string = greeting.length;\nconsole.log(msg);\n</script>\n",
+    },
+  }));
+  assert_eq!(
+    json!(diagnostics.all()),
+    json!([{
+      "range": {
+        "start": { "line": 5, "character": 6 },
+        "end": { "line": 5, "character": 9 },
+      },
+      "severity": 1,
+      "code": 2322,
+      "source": "deno-ts",
+      "message": "Type 'number' is not assignable to type 'string'.",
+    }]),
+  );
+  let res = client.write_request(
+    "textDocument/definition",
+    json!({
+      "textDocument": { "uri": temp_dir.url().join("App.vue").unwrap() },
+      "position": { "line": 5, "character": 22 },
+    }),
+  );
+  assert_eq!(
+    res,
+    json!([{
+      "targetUri": temp_dir.url().join("App.vue").unwrap(),
+      "targetRange": {
+        "start": { "line": 4, "character": 0 },
+        "end": { "line": 4, "character": 25 },
+      },
+      "targetSelectionRange": {
+        "start": { "line": 4, "character": 6 },
+        "end": { "line": 4, "character": 14 },
+      },
+    }]),
+  );
+  client.shutdown();
+}
+
+#[test]
+fn lsp_config_file_schema() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  let deno_json_text = r#"{
+  "fmt": {
+    "proseWrap": "sometimes"
+  }
+}
+"#;
+  temp_dir.write("deno.json", deno_json_text);
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  client.did_open_raw(json!({
+    "textDocument": {
+      "uri": temp_dir.url().join("deno.json").unwrap(),
+      "languageId": "json",
+      "version": 1,
+      "text": deno_json_text,
+    },
+  }));
+  let diagnostics = client.read_diagnostics();
+  assert_eq!(
+    json!(diagnostics.for_file(&temp_dir.url().join("deno.json").unwrap())),
+    json!([{
+      "range": {
+        "start": { "line": 2, "character": 17 },
+        "end": { "line": 2, "character": 28 },
+      },
+      "severity": 2,
+      "code": "invalid-config-value",
+      "source": "deno",
+      "message": "Value is not accepted. Valid values: \"always\", \"never\", \"preserve\".",
+    }]),
+  );
+  let res = client.write_request(
+    "textDocument/completion",
+    json!({
+      "textDocument": {
+        "uri": temp_dir.url().join("deno.json").unwrap(),
+      },
+      "position": { "line": 2, "character": 18 },
+    }),
+  );
+  let to_item = |value: &str| {
+    json!({
+      "label": value,
+      "kind": 12,
+      "documentation": {
+        "kind": "markdown",
+        "value": "Define how prose should be wrapped in Markdown files.",
+      },
+      "filterText": format!("\"{value}\""),
+      "textEdit": {
+        "range": {
+          "start": { "line": 2, "character": 17 },
+          "end": { "line": 2, "character": 28 },
+        },
+        "newText": format!("\"{value}\""),
+      },
+    })
+  };
+  assert_eq!(
+    res,
+    json!({
+      "isIncomplete": false,
+      "items": [to_item("always"), to_item("never"), to_item("preserve")],
+    }),
+  );
+  let res = client.write_request(
+    "textDocument/hover",
+    json!({
+      "textDocument": {
+        "uri": temp_dir.url().join("deno.json").unwrap(),
+      },
+      "position": { "line": 1, "character": 3 },
+    }),
+  );
+  assert_eq!(
+    res,
+    json!({
+      "contents": {
+        "kind": "markdown",
+        "value": "Configuration for formatter",
+      },
+      "range": {
+        "start": { "line": 1, "character": 2 },
+        "end": { "line": 1, "character": 7 },
+      },
+    }),
+  );
+  client.shutdown();
+}
+
+#[test]
+fn lsp_file_rename_config_file_edits() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  let deno_json = source_file(
+    temp_dir.path().join("deno.json"),
+    r#"{
+  "imports": {
+    "@/util": "./util.ts"
+  },
+  "exports": "./util.ts"
+}
+"#,
+  );
+  let util = source_file(temp_dir.path().join("util.ts"), "");
+  let main =
+    source_file(temp_dir.path().join("main.ts"), "import \"./util.ts\";\n");
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  let res = client.write_request(
+    "workspace/willRenameFiles",
+    json!({
+      "files": [
+        {
+          "oldUri": util.url(),
+          "newUri": util.url().join("lib/util.ts").unwrap(),
+        },
+      ],
+    }),
+  );
+  assert_eq!(
+    res,
+    json!({
+      "documentChanges": [
+        {
+          "textDocument": {
+            "uri": main.url(),
+            "version": null,
+          },
+          "edits": [
+            {
+              "range": main.range_of("./util.ts"),
+              "newText": "./lib/util.ts",
+            },
+          ],
+        },
+        {
+          "textDocument": {
+            "uri": deno_json.url(),
+            "version": null,
+          },
+          "edits": [
+            {
+              "range": deno_json.range_of_nth(0, "\"./util.ts\""),
+              "newText": "\"./lib/util.ts\"",
+            },
+            {
+              "range": deno_json.range_of_nth(1, "\"./util.ts\""),
+              "newText": "\"./lib/util.ts\"",
+            },
+          ],
+        },
+      ],
+    }),
+  );
+  client.shutdown();
+}
+
+#[test]
+fn lsp_deno_json_scopes_goto_implementations() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.create_dir_all("project1");
+  temp_dir.create_dir_all("project2");
+  temp_dir.write("project1/deno.json", json!({}).to_string());
+  temp_dir.write("project2/deno.json", json!({}).to_string());
+  let file1 = source_file(
+    temp_dir.path().join("project1/file.ts"),
+    "export interface Foo {}\n",
+  );
+  let file2 = source_file(
+    temp_dir.path().join("project2/file.ts"),
+    r#"
+      import type { Foo } from "../project1/file.ts";
+      export class SomeFoo implements Foo {}
+    "#,
   );
   let mut client = context.new_lsp_command().build();
   client.initialize_default();
@@ -14044,6 +15601,80 @@ fn lsp_deno_json_workspace_lint_config() {
   client.shutdown();
 }
 
+#[test]
+fn lsp_deno_json_workspace_member_config_change() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    json!({ "workspace": ["project1", "project2"] }).to_string(),
+  );
+  temp_dir.create_dir_all("project1");
+  temp_dir.write(
+    "project1/deno.json",
+    json!({
+      "name": "@denotest/project1",
+      "version": "1.0.0",
+      "exports": "./mod1.ts",
+      "imports": {
+        "foo": "./foo1.ts",
+      },
+    })
+    .to_string(),
+  );
+  temp_dir.write("project1/mod1.ts", "export const a = 1;");
+  temp_dir.write("project1/mod2.ts", "export const b = 2;");
+  temp_dir.write("project1/foo1.ts", "export const foo1 = 1;");
+  temp_dir.write("project1/foo2.ts", "export const foo2 = 2;");
+  temp_dir.create_dir_all("project2");
+  temp_dir.write("project2/deno.json", json!({}).to_string());
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  let file1 = temp_dir.url().join("project1/main.ts").unwrap();
+  let file2 = temp_dir.url().join("project2/main.ts").unwrap();
+  client.did_open(json!({
+    "textDocument": {
+      "uri": file1,
+      "languageId": "typescript",
+      "version": 1,
+      "text": "import { foo2 } from \"foo\";\nconsole.log(foo2);\n",
+    },
+  }));
+  let diagnostics = client.did_open(json!({
+    "textDocument": {
+      "uri": file2,
+      "languageId": "typescript",
+      "version": 1,
+      "text": "import { b } from \"@denotest/project1\";\nconsole.log(b);\n",
+    },
+  }));
+  assert_eq!(diagnostics.for_file(&file1).len(), 1);
+  assert_eq!(diagnostics.for_file(&file2).len(), 1);
+
+  // The import map of the member changes how its own modules resolve, and its
+  // exports how the other members resolve it.
+  temp_dir.write(
+    "project1/deno.json",
+    json!({
+      "name": "@denotest/project1",
+      "version": "1.0.0",
+      "exports": "./mod2.ts",
+      "imports": {
+        "foo": "./foo2.ts",
+      },
+    })
+    .to_string(),
+  );
+  client.did_change_watched_files(json!({
+    "changes": [{
+      "uri": temp_dir.url().join("project1/deno.json").unwrap(),
+      "type": 2,
+    }],
+  }));
+  assert_eq!(json!(client.read_diagnostics().all()), json!([]));
+  client.shutdown();
+}
+
 #[test]
 fn lsp_deno_json_workspace_import_map() {
   let context = TestContextBuilder::new().use_temp_cwd().build();
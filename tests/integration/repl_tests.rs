@@ -42,6 +42,24 @@ fn pty_multiline() {
   });
 }
 
+#[test]
+fn pty_multiline_continuation_prompt() {
+  util::with_pty(&["repl"], |mut console| {
+    console.write_line_raw("function add(a, b) {");
+    console.expect("... ");
+    console.write_line_raw("return a + b;");
+    console.expect("... return a + b;");
+    console.write_line("}");
+    console.write_line("add(1, 2)");
+    console.expect("3");
+    // continuation prompts are not part of the code, even in templates
+    console.write_line_raw("`a");
+    console.expect("... ");
+    console.write_line_raw("b`");
+    console.expect("\"a\\nb\"");
+  });
+}
+
 #[test]
 fn pty_null() {
   util::with_pty(&["repl"], |mut console| {
@@ -771,6 +789,37 @@ fn eval_file_flag_multiple_files() {
   assert_contains!(err, "Download");
 }
 
+#[test]
+fn eval_file_flag_repl_api() {
+  let (out, err) = util::run_and_collect_output_with_args(
+    true,
+    vec![
+      "repl",
+      "--eval-file=./repl/console_context.js",
+      "--eval-file=./repl/console_commands.js",
+    ],
+    Some(vec![".greet Deno", "answer * 2", ".help"]),
+    None,
+    false,
+  );
+  assert_contains!(out, "Hello, Deno!");
+  assert_contains!(out, "84");
+  assert_contains!(out, ".greet  Greet someone");
+  assert_contains!(out, ".help   Print the available REPL commands");
+  assert!(err.is_empty());
+}
+
+#[test]
+fn pty_set_prompt() {
+  util::with_pty(&["repl"], |mut console| {
+    console.write_line("repl.setPrompt('app' + '> ')");
+    console.expect("app> ");
+    console.write_line("1 + 2");
+    console.expect("3");
+    console.expect("app> ");
+  });
+}
+
 #[flaky_test::flaky_test]
 fn pty_clear_function() {
   util::with_pty(&["repl"], |mut console| {
@@ -3873,6 +3873,7 @@ async fn test_resolve_dns() {
   use trust_dns_server::ServerFuture;
 
   const DNS_PORT: u16 = 4553;
+  const DNS_OVER_TLS_PORT: u16 = 4563;
 
   // Setup DNS server for testing
   async fn run_dns_server(tx: oneshot::Sender<()>) {
@@ -3900,6 +3901,30 @@ async fn test_resolve_dns() {
     let udp_socket = UdpSocket::bind(socket_addr).await.unwrap();
     server_fut.register_socket(udp_socket);
     server_fut.register_listener(tcp_listener, Duration::from_secs(2));
+    let cert_chain = rustls_pemfile::certs(
+      &mut &include_bytes!("../testdata/tls/localhost.crt")[..],
+    )
+    .map(|cert| rustls_v021::Certificate(cert.unwrap().to_vec()))
+    .collect();
+    let key = rustls_pemfile::private_key(
+      &mut &include_bytes!("../testdata/tls/localhost.key")[..],
+    )
+    .unwrap()
+    .unwrap();
+    let tls_listener =
+      TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], DNS_OVER_TLS_PORT)))
+        .await
+        .unwrap();
+    server_fut
+      .register_tls_listener(
+        tls_listener,
+        Duration::from_secs(2),
+        (
+          cert_chain,
+          rustls_v021::PrivateKey(key.secret_der().to_vec()),
+        ),
+      )
+      .unwrap();
 
     // Notifies that the DNS server is ready
     tx.send(()).unwrap();
@@ -4013,6 +4038,53 @@ async fn test_resolve_dns() {
     assert!(out.is_empty());
   }
 
+  // DNS-over-TLS, with the certificate of the name server trusted through
+  // `--cert` or ignored with `--unsafely-ignore-certificate-errors`
+  for tls_arg in [
+    "--cert=tls/RootCA.pem",
+    "--unsafely-ignore-certificate-errors=localhost",
+  ] {
+    let output = util::deno_cmd()
+      .current_dir(util::testdata_path())
+      .env("NO_COLOR", "1")
+      .arg("run")
+      .arg("--allow-net=127.0.0.1:4563")
+      .arg(tls_arg)
+      .arg("run/resolve_dns_over_tls.ts")
+      .piped_output()
+      .spawn()
+      .unwrap()
+      .wait_with_output()
+      .unwrap();
+    let err = String::from_utf8_lossy(&output.stderr);
+    let out = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+      eprintln!("stderr: {err}");
+    }
+    assert!(output.status.success());
+    assert_eq!(out, "[ \"1.2.3.4\", \"5.6.7.8\" ]\n");
+  }
+
+  // DNS-over-TLS fails when the certificate of the name server isn't trusted
+  {
+    let output = util::deno_cmd()
+      .current_dir(util::testdata_path())
+      .env("NO_COLOR", "1")
+      .arg("run")
+      .arg("--allow-net=127.0.0.1:4563")
+      .arg("run/resolve_dns_over_tls.ts")
+      .piped_output()
+      .spawn()
+      .unwrap()
+      .wait_with_output()
+      .unwrap();
+    let err = String::from_utf8_lossy(&output.stderr);
+    let out = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success());
+    assert!(err.contains("error: Uncaught (in promise)"), "{err}");
+    assert!(out.is_empty());
+  }
+
   handle.abort();
 }
 
@@ -648,6 +648,44 @@ async fn run_watch_external_watch_files() {
   check_alive_then_kill(child);
 }
 
+#[flaky_test(tokio)]
+async fn run_watch_external_watch_glob() {
+  let t = TempDir::new();
+  let file_to_watch = t.path().join("file_to_watch.js");
+  file_to_watch.write("console.log('Hello world');");
+  let assets = t.path().join("assets");
+  assets.create_dir_all();
+  let ignored_file = assets.join("notes.md");
+  ignored_file.write("notes");
+  let watched_file = assets.join("styles.css");
+  watched_file.write("body {}");
+
+  let mut child = util::deno_cmd()
+    .current_dir(t.path())
+    .arg("run")
+    .arg("--watch=assets/*.css")
+    .arg("--watch-debounce=50")
+    .arg("-L")
+    .arg("debug")
+    .arg(&file_to_watch)
+    .env("NO_COLOR", "1")
+    .piped_output()
+    .spawn()
+    .unwrap();
+  let (mut stdout_lines, mut stderr_lines) = child_lines(&mut child);
+  wait_contains("Process started", &mut stderr_lines).await;
+  wait_contains("Hello world", &mut stdout_lines).await;
+  wait_contains("Process finished", &mut stderr_lines).await;
+
+  // files in the directory that don't match the glob don't restart the process
+  ignored_file.write("more notes");
+  watched_file.write("body { color: red; }");
+  wait_contains("Restarting", &mut stderr_lines).await;
+  wait_contains("Process finished", &mut stderr_lines).await;
+
+  check_alive_then_kill(child);
+}
+
 #[flaky_test(tokio)]
 async fn run_watch_load_unload_events() {
   let t = TempDir::new();
@@ -1459,6 +1497,71 @@ async fn test_watch_serve() {
   check_alive_then_kill(child);
 }
 
+/// Ensures that `deno serve --watch` lets the requests in flight finish
+/// before restarting the server.
+#[flaky_test(tokio)]
+async fn serve_watch_graceful_reload() {
+  let t = TempDir::new();
+  let file_to_watch = t.path().join("main.ts");
+  let file_content = |message: &str| {
+    format!(
+      r#"
+      export default {{
+        async fetch() {{
+          await new Promise((resolve) => setTimeout(resolve, 1000));
+          return new Response("{message}");
+        }},
+      }};
+    "#
+    )
+  };
+  file_to_watch.write(file_content("first"));
+
+  let mut child = util::deno_cmd()
+    .current_dir(t.path())
+    .arg("serve")
+    .arg("--watch")
+    .arg("--port")
+    .arg("4602")
+    .arg("-L")
+    .arg("debug")
+    .arg(&file_to_watch)
+    .env("NO_COLOR", "1")
+    .piped_output()
+    .spawn()
+    .unwrap();
+  let (mut _stdout_lines, mut stderr_lines) = child_lines(&mut child);
+
+  wait_contains("Listening on", &mut stderr_lines).await;
+  wait_contains(r#"Watching paths: [""#, &mut stderr_lines).await;
+
+  let request = tokio::spawn(async {
+    reqwest::get("http://127.0.0.1:4602/")
+      .await
+      .unwrap()
+      .text()
+      .await
+      .unwrap()
+  });
+  tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+  file_to_watch.write(file_content("second"));
+
+  // the request made before the change is answered by the previous server
+  assert_eq!(request.await.unwrap(), "first");
+  wait_contains("File change detected", &mut stderr_lines).await;
+  wait_contains("Listening on", &mut stderr_lines).await;
+
+  let response = reqwest::get("http://127.0.0.1:4602/")
+    .await
+    .unwrap()
+    .text()
+    .await
+    .unwrap();
+  assert_eq!(response, "second");
+
+  check_alive_then_kill(child);
+}
+
 #[flaky_test(tokio)]
 async fn run_watch_dynamic_imports() {
   let t = TempDir::new();
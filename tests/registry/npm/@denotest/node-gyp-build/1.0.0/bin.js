@@ -0,0 +1 @@
+console.log("node-gyp-build ran");
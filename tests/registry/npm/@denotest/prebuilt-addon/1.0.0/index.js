@@ -0,0 +1,2 @@
+// the addon would be loaded from prebuilds/ by node-gyp-build
+module.exports.hello = () => "hello";
@@ -0,0 +1,3 @@
+const code = Deno.readTextFileSync("dist/out.js");
+console.log("add:", code.includes("function add("));
+console.log("unused:", code.includes("function unused("));
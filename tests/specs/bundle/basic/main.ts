@@ -0,0 +1,5 @@
+import { add } from "./math.ts";
+import data from "./data.json" with { type: "json" };
+
+console.log(add(1, 2));
+console.log(data.name);
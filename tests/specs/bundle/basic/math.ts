@@ -0,0 +1,7 @@
+export function add(a: number, b: number): number {
+  return a + b;
+}
+
+export function unused(): string {
+  return "removed from the bundle";
+}
@@ -0,0 +1,12 @@
+// greet.ts is imported by both bundles, so it's only in the common one
+const names = [...Deno.readDirSync("dist")].map((entry) => entry.name).sort();
+for (const name of names) {
+  const code = Deno.readTextFileSync(`dist/${name}`);
+  console.log(
+    name.replace(/-[0-9a-f]{8}\.js$/, ".js"),
+    "greet:",
+    code.includes("function greet("),
+    "imports common:",
+    /from ["']\.\/chunk-[0-9a-f]{8}\.js["']/.test(code),
+  );
+}
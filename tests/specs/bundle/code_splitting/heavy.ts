@@ -0,0 +1,5 @@
+import { greet } from "./greet.ts";
+
+export function heavy(): string {
+  return greet("dynamic");
+}
@@ -0,0 +1,5 @@
+import { greet } from "./greet.ts";
+
+console.log(greet("static"));
+const { heavy } = await import("./heavy.ts");
+console.log(heavy());
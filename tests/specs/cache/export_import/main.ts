@@ -0,0 +1,6 @@
+import { printHello } from "http://localhost:4545/subdir/print_hello.ts";
+import { getValue, setValue } from "npm:@denotest/esm-basic";
+
+printHello();
+setValue(5);
+console.log(getValue());
@@ -0,0 +1,3 @@
+import { Class1 } from "npm:@denotest/check-error";
+
+console.log(Class1);
@@ -0,0 +1,4 @@
+import { greet } from "./mod.ts";
+
+const data = Deno.readTextFileSync(new URL("./data.txt", import.meta.url));
+console.log(greet(data.trim()));
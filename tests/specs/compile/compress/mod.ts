@@ -0,0 +1,3 @@
+export function greet(message: string): string {
+  return `> ${message}`;
+}
@@ -0,0 +1,6 @@
+// the files are read from the executable's virtual file system, which is
+// rooted at a directory that doesn't exist on the real file system
+const dir = import.meta.dirname!;
+console.log(Array.from(Deno.readDirSync(`${dir}/templates`), (e) => e.name));
+console.log(Deno.readTextFileSync(`${dir}/templates/hello.txt`).trim());
+console.log((await Deno.readTextFile(`${dir}/data.txt`)).trim());
@@ -0,0 +1,2 @@
+# stands in for a signing tool, checking that it gets the executable's path
+test -x "$1" && echo "signed" > signed.txt
@@ -0,0 +1,10 @@
+Deno.test("adds numbers", () => {});
+
+Deno.test({
+  name: "parses config",
+  fn() {},
+});
+
+Deno.test(async function readsFiles() {});
+
+Deno.test.ignore('skipped on ci', () => {});
@@ -0,0 +1,9 @@
+import { add } from "./generated/add.js";
+import { noop } from "./vendored/util.js";
+
+Deno.test("add", () => {
+  noop();
+  if (add(1, 2) !== 3) {
+    throw new Error("fail");
+  }
+});
@@ -0,0 +1,11 @@
+/**
+ * Adds two numbers, see {@link Calculator} and {@linkcode Math.max}.
+ *
+ * Unlike {@link subtract}, this links to a symbol that doesn't exist.
+ */
+export function add(a: number, b: number): number {
+  return a + b;
+}
+
+/** A calculator. */
+export class Calculator {}
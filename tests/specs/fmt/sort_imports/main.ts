@@ -0,0 +1,5 @@
+import { b } from "./b.ts";
+// a comes first
+import { a } from "./a.ts";
+
+console.log(a, b);
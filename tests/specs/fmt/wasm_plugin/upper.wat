@@ -0,0 +1,46 @@
+;; Source of upper.wasm, a dprint plugin (schema version 4) that formats
+;; `.upper` files by converting ASCII letters to upper case.
+(module
+  (memory (export "memory") 2)
+  ;; length of the shared bytes, which start at 4096
+  (global $len (mut i32) (i32.const 0))
+  (data (i32.const 0) "{\"name\":\"upper\",\"version\":\"0.1.0\",\"configKey\":\"upper\",\"helpUrl\":\"\",\"configSchemaUrl\":\"\",\"updateUrl\":null}")
+  (data (i32.const 1024) "{\"fileExtensions\":[\"upper\"],\"fileNames\":[]}")
+  (data (i32.const 2048) "[]")
+
+  (func (export "dprint_plugin_version_4") (result i32) (i32.const 4))
+  (func (export "get_shared_bytes_ptr") (result i32) (i32.const 4096))
+  (func (export "clear_shared_bytes") (param $size i32) (result i32)
+    (global.set $len (local.get $size))
+    (i32.const 4096))
+  (func (export "get_plugin_info") (result i32)
+    (call $share (i32.const 0) (i32.const 105)))
+  (func (export "register_config") (param $id i32))
+  (func (export "get_config_diagnostics") (param $id i32) (result i32)
+    (call $share (i32.const 2048) (i32.const 2)))
+  (func (export "get_config_file_matching") (param $id i32) (result i32)
+    (call $share (i32.const 1024) (i32.const 43)))
+  (func (export "set_file_path"))
+  (func (export "set_override_config"))
+  (func (export "format") (param $id i32) (result i32)
+    (local $i i32) (local $changed i32) (local $byte i32)
+    (block $done
+      (loop $next
+        (br_if $done (i32.ge_u (local.get $i) (global.get $len)))
+        (local.set $byte (i32.load8_u offset=4096 (local.get $i)))
+        (if (i32.lt_u (i32.sub (local.get $byte) (i32.const 97)) (i32.const 26))
+          (then
+            (i32.store8 offset=4096
+              (local.get $i)
+              (i32.sub (local.get $byte) (i32.const 32)))
+            (local.set $changed (i32.const 1))))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $next)))
+    (local.get $changed))
+  (func (export "get_formatted_text") (result i32) (global.get $len))
+  (func (export "get_error_text") (result i32) (i32.const 0))
+
+  (func $share (param $ptr i32) (param $size i32) (result i32)
+    (memory.copy (i32.const 4096) (local.get $ptr) (local.get $size))
+    (global.set $len (local.get $size))
+    (local.get $size)))
@@ -0,0 +1,3 @@
+import "npm:@denotest/different-nested-dep-child@2.0.0";
+
+export const value = 1;
@@ -0,0 +1,4 @@
+import { value } from "./lib.ts";
+import "npm:@denotest/different-nested-dep@1.0.0";
+
+console.log(value);
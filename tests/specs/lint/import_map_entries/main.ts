@@ -0,0 +1,4 @@
+import chalk from "chalk";
+import "./a.ts";
+
+console.log(chalk);
@@ -0,0 +1,4 @@
+import { Type } from "./test.ts";
+export type MyType = Type;
+console.log(window.value);
+window.fetch;
@@ -0,0 +1,28 @@
+interface Context {
+  ast: { body: { type: string; span: { start: number; end: number } }[] };
+  report(diagnostic: {
+    span: { start: number; end: number };
+    message: string;
+    hint?: string;
+  }): void;
+}
+
+export default {
+  name: "my-plugin",
+  rules: {
+    "no-debugger": {
+      docsUrl: "https://example.com/rules/no-debugger",
+      lint(context: Context) {
+        for (const item of context.ast.body) {
+          if (item.type === "DebuggerStatement") {
+            context.report({
+              span: item.span,
+              message: "No debugger",
+              hint: "Remove the debugger statement",
+            });
+          }
+        }
+      },
+    },
+  },
+};
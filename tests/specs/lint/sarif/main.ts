@@ -0,0 +1,4 @@
+function foo() {
+  return true;
+}
+if (foo()) {}
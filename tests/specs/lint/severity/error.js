@@ -0,0 +1,2 @@
+// deno-lint-ignore
+export const a = 1;
@@ -0,0 +1 @@
+import "http://localhost:4545/welcome.ts";
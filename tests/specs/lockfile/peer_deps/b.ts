@@ -0,0 +1,5 @@
+import version2 from "npm:@denotest/peer-dep-test-child@2";
+import version1 from "npm:@denotest/peer-dep-test-child@1";
+
+console.log(version2);
+console.log(version1);
@@ -0,0 +1,10 @@
+// imported one after the other, so they're resolved separately
+for (
+  const specifier of [
+    "npm:@denotest/peer-dep-test-grandchild@1",
+    "npm:@denotest/peer-dep-test-peer@1",
+  ]
+) {
+  const { default: value } = await import(specifier);
+  console.log(value);
+}
@@ -0,0 +1,3 @@
+import value from "npm:@denotest/custom-conditions";
+
+console.log(value);
@@ -0,0 +1,3 @@
+import { getValue, patched } from "npm:@denotest/esm-basic";
+
+console.log(getValue(), patched);
@@ -0,0 +1,3 @@
+import { hello } from "npm:@denotest/prebuilt-addon";
+
+console.log(hello());
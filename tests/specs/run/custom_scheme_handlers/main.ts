@@ -0,0 +1,3 @@
+import { printHello } from "corp://subdir/print_hello.ts";
+
+printHello();
@@ -0,0 +1,3 @@
+console.log(Deno.env.get("FOO"));
+console.log(Deno.env.get("BAR"));
+console.log(Deno.env.get("MULTILINE"));
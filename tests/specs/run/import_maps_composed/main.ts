@@ -0,0 +1,4 @@
+import "log";
+import { greet } from "greet";
+
+greet();
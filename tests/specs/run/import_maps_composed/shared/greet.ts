@@ -0,0 +1,3 @@
+export function greet() {
+  console.log("hello from shared");
+}
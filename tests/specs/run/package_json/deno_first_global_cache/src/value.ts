@@ -0,0 +1,5 @@
+import { getValue, setValue } from "esm-basic";
+
+setValue(5);
+
+export const value = getValue();
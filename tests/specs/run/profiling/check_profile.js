@@ -0,0 +1,6 @@
+const [path, property] = Deno.args;
+const profile = JSON.parse(Deno.readTextFileSync(path));
+if (profile[property] == null) {
+  throw new Error(`Missing ${property} in ${path}`);
+}
+console.log("valid profile");
@@ -0,0 +1,3 @@
+import "./main.js";
+
+throw new Error("boom");
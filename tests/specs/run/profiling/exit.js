@@ -0,0 +1,4 @@
+import "./main.js";
+
+setTimeout(() => console.log("not printed"), 1000);
+Deno.exit(3);
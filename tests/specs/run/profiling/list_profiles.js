@@ -0,0 +1,3 @@
+for (const entry of Deno.readDirSync(Deno.args[0])) {
+  console.log(entry.name);
+}
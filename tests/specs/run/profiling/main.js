@@ -0,0 +1,9 @@
+function fib(n) {
+  return n < 2 ? n : fib(n - 1) + fib(n - 2);
+}
+
+const values = [];
+for (let i = 0; i < 1000; i++) {
+  values.push({ i, text: "value " + i });
+}
+console.log(fib(20), values.length);
@@ -0,0 +1,9 @@
+Deno.test("fib", () => {
+  let [a, b] = [0, 1];
+  for (let i = 0; i < 20; i++) {
+    [a, b] = [b, a + b];
+  }
+  if (a !== 6765) {
+    throw new Error("unexpected value");
+  }
+});
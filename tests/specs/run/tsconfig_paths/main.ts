@@ -0,0 +1,10 @@
+import { add } from "@utils/math";
+import { Button } from "@components";
+import { VERSION } from "config";
+
+console.log(add(1, 2));
+console.log(Button);
+console.log(VERSION);
+
+const { sub } = await import("@utils/math.ts");
+console.log(sub(3, 1));
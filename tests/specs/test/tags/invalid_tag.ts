@@ -0,0 +1 @@
+Deno.test({ name: "invalid", tags: ["a b"], fn() {} });
@@ -0,0 +1,12 @@
+Deno.test({ name: "unit", tags: ["unit"], fn() {} });
+Deno.test({
+  name: "slow integration",
+  tags: ["integration", "slow"],
+  fn() {},
+});
+Deno.test({
+  name: "windows integration",
+  tags: ["integration", "windows"],
+  fn() {},
+});
+Deno.test("untagged", () => {});
@@ -0,0 +1,4 @@
+console.error("main did run");
+new Worker(import.meta.resolve("./inspect_wait_worker_module.js"), {
+  type: "module",
+});
@@ -0,0 +1,3 @@
+Deno.writeTextFileSync("./hello.txt", "hello world");
+console.error("worker did run");
+self.close();
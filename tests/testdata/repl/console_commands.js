@@ -0,0 +1,7 @@
+repl.setPrompt("app> ");
+repl.defineCommand("greet", {
+  help: "Greet someone",
+  action(name) {
+    console.log(`Hello, ${name}!`);
+  },
+});
@@ -0,0 +1 @@
+repl.defineContext({ answer: 42 });
@@ -91,3 +91,20 @@ try {
 } catch (e) {
   console.log((e as Error).name);
 }
+
+try {
+  await Deno.resolveDns("www.example.com", "A", {
+    encryptedNameServer: { url: "tls://dns.example" },
+  });
+} catch (e) {
+  console.log((e as Error).message);
+}
+
+try {
+  await Deno.resolveDns("www.example.com", "A", {
+    ...nameServer,
+    encryptedNameServer: { url: "https://127.0.0.1/dns-query" },
+  });
+} catch (e) {
+  console.log((e as Error).message);
+}
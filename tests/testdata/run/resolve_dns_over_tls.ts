@@ -0,0 +1,7 @@
+const records = await Deno.resolveDns("www.example.com", "A", {
+  encryptedNameServer: {
+    url: "tls://127.0.0.1:4563",
+    serverName: "localhost",
+  },
+});
+console.log(records);
@@ -1175,6 +1175,111 @@ Deno.test(
   },
 );
 
+Deno.test(
+  { permissions: { net: true } },
+  async function createHttpClientDnsHosts() {
+    const client = Deno.createHttpClient({
+      dns: { hosts: { "deno.test": ["127.0.0.1"] } },
+    });
+    const response = await fetch("http://deno.test:4545/assets/fixture.json", {
+      client,
+    });
+    assertEquals(response.status, 200);
+    await response.text();
+    client.close();
+
+    assertThrows(
+      () => Deno.createHttpClient({ dns: { hosts: { "deno.test": ["x"] } } }),
+      TypeError,
+      "Invalid IP address for host 'deno.test': received 'x'",
+    );
+    assertThrows(
+      () => Deno.createHttpClient({ dns: { cacheSize: 8 } }),
+      TypeError,
+      "'cacheSize' is only supported by the \"builtin\" resolver",
+    );
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function createHttpClientLocalAddress() {
+    // Only the IPv4 addresses of "localhost" are connected to
+    const client = Deno.createHttpClient({ localAddress: "127.0.0.1" });
+    const response = await fetch("http://localhost:4545/assets/fixture.json", {
+      client,
+    });
+    assertEquals(response.status, 200);
+    await response.text();
+    client.close();
+
+    const ipv6Client = Deno.createHttpClient({ localAddress: "::1" });
+    await assertRejects(
+      () =>
+        fetch("http://127.0.0.1:4545/assets/fixture.json", {
+          client: ipv6Client,
+        }),
+      TypeError,
+      "No resolved address found of the family of the local address",
+    );
+    ipv6Client.close();
+  },
+);
+
+Deno.test(
+  { permissions: { net: true }, ignore: Deno.build.os !== "linux" },
+  async function createHttpClientInterface() {
+    const client = Deno.createHttpClient({ interface: "lo" });
+    const response = await fetch("http://127.0.0.1:4545/assets/fixture.json", {
+      client,
+    });
+    assertEquals(response.status, 200);
+    await response.text();
+    client.close();
+  },
+);
+
+Deno.test(
+  { permissions: { net: true }, ignore: Deno.build.os === "linux" },
+  async function createHttpClientInterfaceNotSupported() {
+    const client = Deno.createHttpClient({ interface: "lo0" });
+    await assertRejects(
+      () => fetch("http://127.0.0.1:4545/assets/fixture.json", { client }),
+      TypeError,
+      "Binding to a network interface is only supported on Linux",
+    );
+    client.close();
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function createHttpClientMaxConnections() {
+    assertThrows(
+      () => Deno.createHttpClient({ maxConnections: 0 }),
+      TypeError,
+      "'maxConnections' must be greater than 0",
+    );
+    const client = Deno.createHttpClient({ maxConnections: 1 });
+    const responses = await Promise.all(
+      [1, 2, 3].map(() =>
+        fetch("http://localhost:4545/assets/fixture.json", { client })
+      ),
+    );
+    for (const response of responses) {
+      assertEquals(response.status, 200);
+      await response.text();
+    }
+    client.closeIdleConnections();
+    const response = await fetch("http://localhost:4545/assets/fixture.json", {
+      client,
+    });
+    assertEquals(response.status, 200);
+    await response.text();
+    client.close();
+  },
+);
+
 Deno.test(
   { permissions: { net: true } },
   async function fetchCustomClientUserAgent(): Promise<
@@ -1387,6 +1492,113 @@ Deno.test(
   },
 );
 
+Deno.test(
+  { permissions: { read: true, net: true } },
+  async function fetchCustomClientPkcs12(): Promise<void> {
+    const caCert = await Deno.readTextFile("tests/testdata/tls/RootCA.crt");
+    const client = Deno.createHttpClient({
+      keyFormat: "pkcs12",
+      pkcs12: await Deno.readFile("tests/testdata/tls/localhost.p12"),
+      password: "deno",
+      caCerts: [caCert],
+    });
+    const response = await fetch("https://localhost:5552/echo_server", {
+      client,
+      method: "POST",
+      body: "Hello World",
+    });
+    assertEquals(await response.text(), "Hello World");
+    client.close();
+  },
+);
+
+Deno.test(
+  { permissions: { read: true } },
+  async function fetchClientPkcs12WrongPassword(): Promise<void> {
+    const pkcs12 = await Deno.readFile("tests/testdata/tls/localhost.p12");
+    assertThrows(
+      () =>
+        Deno.createHttpClient({ keyFormat: "pkcs12", pkcs12, password: "x" }),
+      Deno.errors.InvalidData,
+      "Invalid password for PKCS#12 archive",
+    );
+    assertThrows(
+      () =>
+        Deno.createHttpClient({
+          keyFormat: "pkcs12",
+          pkcs12: new Uint8Array([1, 2, 3]),
+        }),
+      Deno.errors.InvalidData,
+      "Invalid PKCS#12 archive",
+    );
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function fetchStreamingBodyWithContentLength(): Promise<void> {
+    const server = Deno.serve({ port: listenPort }, async (req) => {
+      const body = await req.text();
+      return new Response(
+        `${req.headers.get("content-length")} ${
+          req.headers.get("transfer-encoding")
+        } ${body}`,
+      );
+    });
+    const progress: Deno.UploadProgress[] = [];
+    const response = await fetch(`http://localhost:${listenPort}/`, {
+      method: "POST",
+      headers: { "content-length": "6" },
+      body: ReadableStream.from(["foo", "bar"]).pipeThrough(
+        new TextEncoderStream(),
+      ),
+      onUploadProgress: (p) => progress.push(p),
+    });
+    assertEquals(await response.text(), "6 null foobar");
+    assertEquals(progress.at(-1), { loaded: 6, total: 6 });
+
+    await assertRejects(
+      () =>
+        fetch(`http://localhost:${listenPort}/`, {
+          method: "POST",
+          headers: { "content-length": "4" },
+          body: ReadableStream.from([new Uint8Array(8)]),
+        }),
+      TypeError,
+    );
+    await server.shutdown();
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function fetchUploadProgressThrows(): Promise<void> {
+    const server = Deno.serve({ port: listenPort }, async (req) => {
+      await req.text().catch(() => {});
+      return new Response("ok");
+    });
+    // The body never ends, so the request only settles once it is aborted
+    const body = new ReadableStream({
+      start(controller) {
+        controller.enqueue(new Uint8Array([1, 2, 3, 4]));
+      },
+    });
+    await assertRejects(
+      () =>
+        fetch(`http://localhost:${listenPort}/`, {
+          method: "POST",
+          body,
+          onUploadProgress: () => {
+            throw new Error("progress failed");
+          },
+        }),
+      Error,
+      "progress failed",
+    );
+    await server.shutdown();
+  },
+);
+
 Deno.test(
   { permissions: { net: true } },
   async function fetchAbortWhileUploadStreaming(): Promise<void> {
@@ -1582,11 +1794,46 @@ Deno.test(
     const client = Deno.createHttpClient({ caCerts: [caCert] });
     const res = await fetch("https://localhost:5545/http_version", { client });
     assert(res.ok);
+    assertEquals(res.protocol, "h2");
     assertEquals(await res.text(), "HTTP/2.0");
     client.close();
   },
 );
 
+Deno.test(
+  { permissions: { net: true, read: true } },
+  async function fetchHttp2PriorKnowledge() {
+    const client = Deno.createHttpClient({
+      http2PriorKnowledge: true,
+      http2MaxConcurrentStreams: 4,
+      http2InitialStreamWindowSize: 1024 * 1024,
+      http2InitialConnectionWindowSize: 4 * 1024 * 1024,
+    });
+    const responses = await Promise.all(
+      Array.from(
+        { length: 8 },
+        () => fetch("http://localhost:5549/http_version", { client }),
+      ),
+    );
+    for (const res of responses) {
+      assertEquals(res.protocol, "h2");
+      assertEquals(await res.text(), "HTTP/2.0");
+    }
+    client.close();
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  function fetchHttp2MaxConcurrentStreamsRequiresHttp2() {
+    assertThrows(
+      () => Deno.createHttpClient({ http2MaxConcurrentStreams: 4 }),
+      TypeError,
+      "requires HTTP/2",
+    );
+  },
+);
+
 Deno.test(
   { permissions: { net: true, read: true } },
   async function createHttpClientAllowHost() {
@@ -46,6 +46,98 @@ Deno.test(
   },
 );
 
+Deno.test(
+  { permissions: { net: true } },
+  async function netTcpSetSocketOptions() {
+    const listener = Deno.listen({ hostname: "127.0.0.1", port: listenPort });
+    const acceptPromise = listener.accept();
+    const conn = await Deno.connect({
+      hostname: "127.0.0.1",
+      port: listenPort,
+    });
+
+    conn.setSocketOptions({ noDelay: true, linger: 0, tos: 0x10 });
+    let options = conn.getSocketOptions();
+    assertEquals(options.noDelay, true);
+    assertEquals(options.keepAlive, false);
+    assertEquals(options.linger, 0);
+    assertEquals(options.tos, 0x10);
+    assert(options.recvBufferSize! > 0);
+    assert(options.sendBufferSize! > 0);
+
+    conn.setSocketOptions({ linger: null });
+    options = conn.getSocketOptions();
+    assertEquals(options.noDelay, true);
+    assertEquals(options.linger, null);
+
+    if (Deno.build.os === "linux") {
+      conn.setSocketOptions({ userTimeout: 5000 });
+      assertEquals(conn.getSocketOptions().userTimeout, 5000);
+    } else {
+      assertThrows(
+        () => conn.setSocketOptions({ userTimeout: 5000 }),
+        Deno.errors.NotSupported,
+      );
+    }
+
+    conn.close();
+    (await acceptPromise).close();
+    listener.close();
+  },
+);
+
+Deno.test(
+  {
+    // Windows has no traffic class option for IPv6 sockets
+    ignore: Deno.build.os === "windows",
+    permissions: { net: true },
+  },
+  async function netTcpSetSocketOptionsIpv6() {
+    const listener = Deno.listen({ hostname: "::1", port: 0 });
+    const acceptPromise = listener.accept();
+    const conn = await Deno.connect({
+      hostname: "::1",
+      port: listener.addr.port,
+    });
+
+    conn.setSocketOptions({ tos: 0x10 });
+    assertEquals(conn.getSocketOptions().tos, 0x10);
+
+    conn.close();
+    (await acceptPromise).close();
+    listener.close();
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  function netTcpListenerSetSocketOptions() {
+    const listener = Deno.listen({ hostname: "127.0.0.1", port: 0 });
+    let options = listener.getSocketOptions();
+    if (Deno.build.os !== "windows") {
+      assertEquals(options.reusePort, false);
+    }
+    assert(options.recvBufferSize! > 0);
+
+    listener.setSocketOptions({ recvBufferSize: 65536, tos: 0x10 });
+    options = listener.getSocketOptions();
+    assert(options.recvBufferSize! >= 65536);
+    assertEquals(options.tos, 0x10);
+    listener.close();
+
+    // Other platforms ignore `reusePort`, as they don't balance connections
+    if (Deno.build.os === "linux") {
+      const reusing = Deno.listen({
+        hostname: "127.0.0.1",
+        port: 0,
+        reusePort: true,
+      });
+      assertEquals(reusing.getSocketOptions().reusePort, true);
+      reusing.close();
+    }
+  },
+);
+
 Deno.test(
   {
     ignore: Deno.build.os === "windows",
@@ -118,6 +210,64 @@ Deno.test(
   },
 );
 
+const isVsockSupported = Deno.build.os === "linux" ||
+  Deno.build.os === "darwin";
+
+// Listening and connecting on the same machine requires the local CID 1, like
+// the `vsock_loopback` kernel module of Linux provides.
+function isVsockLoopbackAvailable() {
+  if (!isVsockSupported) {
+    return false;
+  }
+  try {
+    Deno.listen({ cid: 1, port: 4151, transport: "vsock" }).close();
+    return true;
+  } catch {
+    return false;
+  }
+}
+
+Deno.test(
+  {
+    ignore: !isVsockSupported,
+    permissions: { net: ["vsock:1:4150"] },
+  },
+  async function netVsockNetPermission() {
+    assertThrows(() => {
+      Deno.listen({ cid: 1, port: 4151, transport: "vsock" });
+    }, Deno.errors.NotCapable);
+    await assertRejects(
+      () => Deno.connect({ cid: 2, port: 4150, transport: "vsock" }),
+      Deno.errors.NotCapable,
+    );
+  },
+);
+
+Deno.test(
+  {
+    permissions: { net: ["vsock:1:4150"] },
+    ignore: !isVsockLoopbackAvailable(),
+  },
+  async function netVsockDialListen() {
+    const listener = Deno.listen({ cid: 1, port: 4150, transport: "vsock" });
+    assertEquals(listener.addr, { transport: "vsock", cid: 1, port: 4150 });
+    const acceptPromise = listener.accept();
+    const conn = await Deno.connect({ cid: 1, port: 4150, transport: "vsock" });
+    const serverConn = await acceptPromise;
+    assertEquals(conn.remoteAddr, { transport: "vsock", cid: 1, port: 4150 });
+    assertEquals(serverConn.localAddr.transport, "vsock");
+
+    await conn.write(new Uint8Array([1, 2, 3]));
+    const buf = new Uint8Array(3);
+    assertEquals(await serverConn.read(buf), 3);
+    assertEquals(buf, new Uint8Array([1, 2, 3]));
+
+    conn.close();
+    serverConn.close();
+    listener.close();
+  },
+);
+
 Deno.test(
   {
     permissions: { net: true },
@@ -241,6 +391,55 @@ Deno.test({ permissions: { net: true } }, async function netTcpDialListen() {
   conn.close();
 });
 
+Deno.test(
+  { permissions: { net: true } },
+  async function netTcpConnectFallsBackToOtherAddresses() {
+    // "localhost" may resolve to "::1" first, where nothing listens.
+    const listener = Deno.listen({ hostname: "127.0.0.1", port: listenPort });
+    const accepted = listener.accept();
+    const conn = await Deno.connect({
+      hostname: "localhost",
+      port: listenPort,
+      happyEyeballsDelay: 50,
+    });
+    assertEquals(conn.remoteAddr.hostname, "127.0.0.1");
+    conn.close();
+    (await accepted).close();
+    listener.close();
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function netTcpConnectFromLocalAddress() {
+    const listener = Deno.listen({ hostname: "127.0.0.1", port: listenPort });
+    const accepted = listener.accept();
+    // Only the IPv4 addresses of "localhost" are connected to
+    const conn = await Deno.connect({
+      hostname: "localhost",
+      port: listenPort,
+      localAddress: "127.0.0.1",
+    });
+    assertEquals(conn.localAddr.hostname, "127.0.0.1");
+    const serverConn = await accepted;
+    assertEquals(serverConn.remoteAddr, conn.localAddr);
+    conn.close();
+    serverConn.close();
+    listener.close();
+
+    await assertRejects(
+      () =>
+        Deno.connect({
+          hostname: "127.0.0.1",
+          port: listenPort,
+          localAddress: "::1",
+        }),
+      Error,
+      "No resolved address found of the family of the local address",
+    );
+  },
+);
+
 Deno.test({ permissions: { net: true } }, async function netTcpSetNoDelay() {
   const listener = Deno.listen({ port: listenPort });
   listener.accept().then(
@@ -349,6 +548,42 @@ Deno.test(
   },
 );
 
+Deno.test(
+  { permissions: { net: true } },
+  async function netUdpConnected() {
+    const alice = Deno.listenDatagram({
+      port: listenPort,
+      transport: "udp",
+      recvBufferSize: 1 << 16,
+      sendBufferSize: 1 << 16,
+    });
+    const bob = Deno.listenDatagram({ port: listenPort2, transport: "udp" });
+    assertEquals(alice.remoteAddr, null);
+
+    await alice.connect(bob.addr as Deno.NetAddr);
+    assertEquals(alice.remoteAddr, {
+      transport: "udp",
+      hostname: "127.0.0.1",
+      port: listenPort2,
+    });
+    await alice.setTTL(32);
+    await alice.setBroadcast(false);
+
+    const byteLength = await alice.send(new Uint8Array([1, 2, 3]));
+    assertEquals(byteLength, 3);
+    const [recvd, remote] = await bob.receive();
+    assertEquals(recvd, new Uint8Array([1, 2, 3]));
+    assertEquals((remote as Deno.NetAddr).port, listenPort);
+
+    await bob.send(new Uint8Array([4]), alice.addr);
+    const [reply] = await alice.receive();
+    assertEquals(reply, new Uint8Array([4]));
+
+    alice.close();
+    bob.close();
+  },
+);
+
 Deno.test(
   { permissions: { net: true } },
   async function netUdpSendReceive() {
@@ -521,6 +756,7 @@ Deno.test(
 
     membership.setLoopback(true);
     membership.setLoopback(false);
+    membership.setTTL(2);
     membership.leave();
     listener.close();
   },
@@ -8,6 +8,7 @@ import { TextProtoReader } from "../testdata/run/textproto.ts";
 import {
   assert,
   assertEquals,
+  assertStrictEquals,
   assertStringIncludes,
   assertThrows,
   curlRequest,
@@ -55,7 +56,7 @@ async function makeServer(
   {
     finished: Promise<void>;
     abort: () => void;
-    shutdown: () => Promise<void>;
+    shutdown: (options?: Deno.ServeShutdownOptions) => Promise<void>;
     [Symbol.asyncDispose](): PromiseLike<void>;
   }
 > {
@@ -75,8 +76,8 @@ async function makeServer(
     abort() {
       ac.abort();
     },
-    async shutdown() {
-      await server.shutdown();
+    async shutdown(options?: Deno.ServeShutdownOptions) {
+      await server.shutdown(options);
     },
     [Symbol.asyncDispose]() {
       return server[Symbol.asyncDispose]();
@@ -290,6 +291,381 @@ Deno.test(
   },
 );
 
+// When the grace period of a graceful shutdown elapses, the connections that are still open are
+// aborted.
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerShutdownGracePeriodHttp11() {
+    const input = Promise.withResolvers<string>();
+    const out = Promise.withResolvers<void>();
+    const encoder = new TextEncoder();
+    const decoder = new TextDecoder();
+    const { finished, shutdown } = await makeServer((_req) => {
+      return new Response(
+        new ReadableStream({
+          async start(controller) {
+            controller.enqueue(new Uint8Array([46]));
+            out.resolve();
+            controller.enqueue(encoder.encode(await input.promise));
+            controller.close();
+          },
+        }),
+      );
+    });
+    const conn = await Deno.connect({ port: servePort });
+    const w = conn.writable.getWriter();
+    const r = conn.readable.getReader();
+
+    await w.write(encoder.encode(`GET / HTTP/1.1\nConnection: keep-alive\n\n`));
+    await out.promise;
+
+    // This is half served, so wait for the chunk that has the first '.'
+    let text = "";
+    while (!text.includes("1\r\n.\r\n")) {
+      text += decoder.decode((await r.read()).value);
+    }
+
+    // The shutdown completes although the response never does
+    await shutdown({ gracePeriodMs: 100 });
+    assert((await r.read()).done);
+
+    input.resolve("!");
+    try {
+      conn.close();
+    } catch (_) {
+      // Ignore
+    }
+    await finished;
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerShutdownInvalidGracePeriod() {
+    const { finished, shutdown } = await makeServer((_req) =>
+      new Response("ok")
+    );
+    await assertRejects(
+      () => shutdown({ gracePeriodMs: -1 }),
+      TypeError,
+      "'gracePeriodMs' must be a non-negative finite number, received -1",
+    );
+    await shutdown();
+    await finished;
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerSendsEarlyHints() {
+    const { promise, resolve } = Promise.withResolvers<void>();
+    const { finished, abort } = await makeServer(async (_req, info) => {
+      assert(
+        info.sendInformational(103, {
+          "Link": "</style.css>; rel=preload; as=style",
+        }),
+      );
+      assertThrows(
+        () => info.sendInformational(101),
+        RangeError,
+        "The informational status provided (101) must be an integer in the range [102, 199]",
+      );
+      await promise;
+      return new Response("ok");
+    });
+    const conn = await Deno.connect({ port: servePort });
+    const encoder = new TextEncoder();
+    const decoder = new TextDecoder();
+    const w = conn.writable.getWriter();
+    const r = conn.readable.getReader();
+    await w.write(
+      encoder.encode(`GET / HTTP/1.1\r\nHost: localhost\r\n\r\n`),
+    );
+
+    // The early hints are sent before the final response is ready
+    let text = "";
+    while (!text.includes("\r\n\r\n")) {
+      text += decoder.decode((await r.read()).value);
+    }
+    assertEquals(
+      text,
+      "HTTP/1.1 103 Early Hints\r\nlink: </style.css>; rel=preload; as=style\r\n\r\n",
+    );
+    resolve();
+    while (!text.endsWith("ok")) {
+      text += decoder.decode((await r.read()).value);
+    }
+    assert(text.includes("\r\n\r\nHTTP/1.1 200 OK\r\n"));
+    conn.close();
+    abort();
+    await finished;
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerUpgradeHttp() {
+    const { promise: upgraded, resolve } = Promise.withResolvers<void>();
+    const { finished, abort } = await makeServer((req) => {
+      const { conn, response } = Deno.upgradeHttp(req);
+      conn.then(async (conn) => {
+        // Echo the first five bytes of the custom protocol back
+        const buf = new Uint8Array(5);
+        let n = 0;
+        while (n < buf.length) {
+          n += (await conn.read(buf.subarray(n)))!;
+        }
+        await conn.write(buf);
+        conn.close();
+        resolve();
+      });
+      return response;
+    });
+    const conn = await Deno.connect({ port: servePort });
+    const encoder = new TextEncoder();
+    const decoder = new TextDecoder();
+    const w = conn.writable.getWriter();
+    const r = conn.readable.getReader();
+    // The client may send the new protocol right after its request
+    await w.write(
+      encoder.encode(
+        "GET / HTTP/1.1\r\nHost: localhost\r\nConnection: Upgrade\r\nUpgrade: echo\r\n\r\nhello",
+      ),
+    );
+
+    let text = "";
+    while (!text.endsWith("\r\n\r\nhello")) {
+      text += decoder.decode((await r.read()).value);
+    }
+    assert(text.startsWith("HTTP/1.1 101 Switching Protocols\r\n"));
+    assert(text.includes("\r\nupgrade: echo\r\n"));
+    await upgraded;
+    conn.close();
+    abort();
+    await finished;
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerUpgradeHttpInvalid() {
+    const { finished, abort } = await makeServer((req) => {
+      assertThrows(
+        () => Deno.upgradeHttp(req),
+        TypeError,
+        "Invalid Header: 'upgrade' header must be set",
+      );
+      return new Response("ok");
+    });
+    const resp = await fetch(`http://localhost:${servePort}/`);
+    assertEquals(await resp.text(), "ok");
+    abort();
+    await finished;
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerUpgradeHttpInvalidProtocol() {
+    const { finished, abort } = await makeServer((req) => {
+      assertThrows(
+        () => Deno.upgradeHttp(req, { protocol: "echo\r\nx-injected: 1" }),
+        TypeError,
+        "Invalid header value",
+      );
+      return new Response("ok");
+    });
+    const resp = await fetch(`http://localhost:${servePort}/`, {
+      headers: { "connection": "Upgrade", "upgrade": "echo" },
+    });
+    assertEquals(resp.headers.get("x-injected"), null);
+    assertEquals(await resp.text(), "ok");
+    abort();
+    await finished;
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerMetricsAndConnectionHooks() {
+    const ac = new AbortController();
+    const { promise: listening, resolve: onListenResolve } = Promise
+      .withResolvers<void>();
+    const { promise: connected, resolve: onConnect } = Promise.withResolvers<
+      Deno.ServeConnectionInfo
+    >();
+    const { promise: closed, resolve: onClose } = Promise.withResolvers<
+      Deno.ServeConnectionInfo
+    >();
+    const server = Deno.serve({
+      port: servePort,
+      signal: ac.signal,
+      onListen: onListen(onListenResolve),
+      onConnect,
+      onClose,
+      handler: () => new Response("ok"),
+    });
+    await listening;
+
+    const conn = await Deno.connect({ port: servePort });
+    await conn.write(
+      new TextEncoder().encode(
+        "GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+      ),
+    );
+    const response = await new Response(conn.readable).text();
+    assert(response.startsWith("HTTP/1.1 200 OK\r\n"));
+
+    const info = await connected;
+    assertEquals(info.remoteAddr.transport, "tcp");
+    assertEquals((info.remoteAddr as Deno.NetAddr).hostname, "127.0.0.1");
+    assertStrictEquals(await closed, info);
+
+    const metrics = server.metrics();
+    assertEquals(metrics.activeConnections, 0);
+    assertEquals(metrics.totalConnections, 1);
+    assertEquals(metrics.inFlightRequests, 0);
+    assertEquals(metrics.totalRequests, 1);
+    assertEquals(metrics.handshakeErrors, 0);
+    assert(metrics.bytesRead > 0);
+    assertEquals(metrics.bytesWritten, response.length);
+
+    ac.abort();
+    await server.finished;
+    // The metrics remain available once the server is closed
+    assertEquals(server.metrics().totalRequests, 1);
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  function httpServerInvalidConnectionHooks() {
+    assertThrows(
+      () =>
+        Deno.serve({
+          port: servePort,
+          // deno-lint-ignore no-explicit-any
+          onConnect: "hello" as any,
+        }, () => new Response()),
+      TypeError,
+      "'onConnect' must be a function, received string",
+    );
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerRequestAndResponseTrailers() {
+    const { finished, abort } = await makeServer(async (req, info) => {
+      assertEquals(info.requestTrailers, null);
+      assertEquals(await req.text(), "hello");
+      assertEquals(info.requestTrailers?.get("x-checksum"), "abc");
+      info.setResponseTrailers({ "grpc-status": "0" });
+      return new Response("ok");
+    });
+    const conn = await Deno.connect({ port: servePort });
+    const encoder = new TextEncoder();
+    const decoder = new TextDecoder();
+    const w = conn.writable.getWriter();
+    const r = conn.readable.getReader();
+    await w.write(
+      encoder.encode(
+        "POST / HTTP/1.1\r\nHost: localhost\r\nTE: trailers\r\nTrailer: x-checksum\r\n" +
+          "Transfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n0\r\nx-checksum: abc\r\n\r\n",
+      ),
+    );
+
+    // The trailers are declared, and sent after the chunked body
+    let text = "";
+    while (!text.endsWith("\r\n0\r\ngrpc-status: 0\r\n\r\n")) {
+      text += decoder.decode((await r.read()).value);
+    }
+    assert(text.includes("\r\ntrailer: grpc-status\r\n"));
+    assert(text.includes("\r\ntransfer-encoding: chunked\r\n"));
+    conn.close();
+    abort();
+    await finished;
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerTrailersWithFetch() {
+    const { finished, abort } = await makeServer((_req, info) => {
+      info.setResponseTrailers({ "grpc-status": "0" });
+      return new Response("ok");
+    });
+
+    const res = await fetch(`http://localhost:${servePort}/`, {
+      headers: { "te": "trailers" },
+    });
+    assertEquals(await res.text(), "ok");
+    assertEquals((await res.trailers).get("grpc-status"), "0");
+
+    // Trailers are only sent to clients that accept them
+    const res2 = await fetch(`http://localhost:${servePort}/`);
+    assertEquals(await res2.text(), "ok");
+    assertEquals([...(await res2.trailers)], []);
+
+    abort();
+    await finished;
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerHeaderReadTimeout() {
+    const { promise, resolve } = Promise.withResolvers<void>();
+    const server = Deno.serve({
+      handler: (_req) => new Response("ok"),
+      port: servePort,
+      headerReadTimeoutMs: 100,
+      onListen: onListen(resolve),
+    });
+    await promise;
+
+    const conn = await Deno.connect({ port: servePort });
+    const w = conn.writable.getWriter();
+    const r = conn.readable.getReader();
+    // A client that never finishes its headers is disconnected
+    await w.write(new TextEncoder().encode("GET / HTTP/1.1\r\nHost: "));
+    while (!(await r.read()).done) {
+      // Drain the error response, if any
+    }
+    try {
+      conn.close();
+    } catch (_) {
+      // Ignore
+    }
+    await server.shutdown();
+    await server.finished;
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  function httpServerInvalidTimeout() {
+    for (
+      const name of [
+        "headerReadTimeoutMs",
+        "requestBodyIdleTimeoutMs",
+        "responseWriteTimeoutMs",
+      ]
+    ) {
+      assertThrows(
+        () =>
+          Deno.serve({
+            handler: (_req) => new Response("ok"),
+            port: servePort,
+            [name]: 0,
+          }),
+        TypeError,
+        `'${name}' must be a positive finite number, received 0`,
+      );
+    }
+  },
+);
+
 // Ensure that resources don't leak during a graceful shutdown
 Deno.test(
   { permissions: { net: true, write: true, read: true } },
@@ -332,6 +708,24 @@ Deno.test(
   },
 );
 
+Deno.test(
+  { permissions: { net: true, write: true, read: true } },
+  async function httpServerFileBodyContentLength() {
+    const { finished, shutdown } = await makeServer(async (_req) => {
+      const file = await makeTempFile(1024 * 1024);
+      await file.seek(1024, Deno.SeekMode.Start);
+      return new Response(file.readable);
+    });
+
+    const response = await fetch(`http://localhost:${servePort}`);
+    assertEquals(response.headers.get("content-length"), "1047552");
+    assertEquals(response.headers.get("transfer-encoding"), null);
+    assertEquals((await response.arrayBuffer()).byteLength, 1047552);
+    await shutdown();
+    await finished;
+  },
+);
+
 Deno.test(
   { permissions: { net: true, write: true, read: true } },
   async function httpServerExplicitResourceManagement() {
@@ -2856,6 +3250,30 @@ const compressionTestCases = [
     out: { "Content-Type": "text/plain", "Cache-Control": "no-transform" },
     expect: null,
   },
+  {
+    name: "Disabled",
+    length: 1024,
+    in: { "Accept-Encoding": "gzip" },
+    out: { "Content-Type": "text/plain" },
+    serve: { compression: false },
+    expect: null,
+  },
+  {
+    name: "AllowedContentType",
+    length: 1024,
+    in: { "Accept-Encoding": "gzip" },
+    out: { "Content-Type": "text/fake" },
+    serve: { compression: { contentTypes: ["text/*"], gzipLevel: 9 } },
+    expect: "gzip",
+  },
+  {
+    name: "DisallowedContentType",
+    length: 1024,
+    in: { "Accept-Encoding": "gzip" },
+    out: { "Content-Type": "text/plain" },
+    serve: { compression: { contentTypes: ["application/json"] } },
+    expect: null,
+  },
 ];
 
 for (const testCase of compressionTestCases) {
@@ -2868,6 +3286,7 @@ for (const testCase of compressionTestCases) {
         const listeningDeferred = Promise.withResolvers<void>();
         const ac = new AbortController();
         const server = Deno.serve({
+          ...testCase.serve,
           handler: async (_request) => {
             const f = await makeTempFile(testCase.length);
             deferred.resolve();
@@ -2916,6 +3335,39 @@ for (const testCase of compressionTestCases) {
   );
 }
 
+Deno.test(
+  { permissions: { net: true } },
+  function httpServerInvalidCompression() {
+    const serve = (compression: Deno.ServeCompressionOptions) =>
+      Deno.serve({
+        handler: (_req) => new Response("ok"),
+        port: servePort,
+        compression,
+      });
+    assertThrows(
+      () => serve({ gzipLevel: 10 }),
+      TypeError,
+      "'compression.gzipLevel' must be an integer between 0 and 9, received 10",
+    );
+    assertThrows(
+      () => serve({ brotliQuality: 1.5 }),
+      TypeError,
+      "'compression.brotliQuality' must be an integer between 0 and 11, received 1.5",
+    );
+    assertThrows(
+      () => serve({ minSize: -1 }),
+      TypeError,
+      "'compression.minSize' must be a non-negative integer, received -1",
+    );
+    assertThrows(
+      // deno-lint-ignore no-explicit-any
+      () => serve({ contentTypes: [1] as any }),
+      TypeError,
+      "'compression.contentTypes' must be an array of strings",
+    );
+  },
+);
+
 Deno.test(
   { permissions: { net: true, write: true, read: true } },
   async function httpServerPostFile() {
@@ -3179,6 +3631,155 @@ Deno.test(
   },
 );
 
+Deno.test(
+  { permissions: { read: true, net: true } },
+  async function httpServerSetCertificate() {
+    const ac = new AbortController();
+    const { promise, resolve } = Promise.withResolvers<void>();
+
+    const server = Deno.serve({
+      handler: () => new Response("Hello World"),
+      hostname: "127.0.0.1",
+      port: servePort,
+      signal: ac.signal,
+      onListen: onListen(resolve),
+      onError: createOnErrorCb(ac),
+      cert: Deno.readTextFileSync("tests/testdata/tls/localhost.crt"),
+      key: Deno.readTextFileSync("tests/testdata/tls/localhost.key"),
+    });
+
+    await promise;
+    const caCert = Deno.readTextFileSync("tests/testdata/tls/RootCA.pem");
+    const client = Deno.createHttpClient({
+      caCerts: [caCert],
+      poolMaxIdlePerHost: 0,
+    });
+    const request = () =>
+      fetch(`https://localhost:${servePort}/`, { client }).then((resp) =>
+        resp.text()
+      );
+
+    assertEquals(await request(), "Hello World");
+    // The CA certificate is not valid for "localhost"
+    server.setCertificate({
+      cert: Deno.readTextFileSync("tests/testdata/tls/RootCA.crt"),
+      key: Deno.readTextFileSync("tests/testdata/tls/RootCA.key"),
+    });
+    await assertRejects(request, TypeError);
+    server.setCertificate({
+      cert: Deno.readTextFileSync("tests/testdata/tls/localhost_ecc.crt"),
+      key: Deno.readTextFileSync("tests/testdata/tls/localhost_ecc.key"),
+    });
+    assertEquals(await request(), "Hello World");
+
+    client.close();
+    ac.abort();
+    await server.finished;
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, net: true } },
+  async function httpServerSniCertificates() {
+    const ac = new AbortController();
+    const { promise, resolve } = Promise.withResolvers<void>();
+
+    const server = Deno.serve({
+      handler: () => new Response("Hello World"),
+      hostname: "127.0.0.1",
+      port: servePort,
+      signal: ac.signal,
+      onListen: onListen(resolve),
+      onError: createOnErrorCb(ac),
+      // The CA certificate is not valid for "localhost"
+      cert: Deno.readTextFileSync("tests/testdata/tls/RootCA.crt"),
+      key: Deno.readTextFileSync("tests/testdata/tls/RootCA.key"),
+      sniCertificates: {
+        localhost: {
+          cert: Deno.readTextFileSync("tests/testdata/tls/localhost.crt"),
+          key: Deno.readTextFileSync("tests/testdata/tls/localhost.key"),
+        },
+      },
+    });
+
+    await promise;
+    const caCert = Deno.readTextFileSync("tests/testdata/tls/RootCA.pem");
+    const client = Deno.createHttpClient({
+      caCerts: [caCert],
+      poolMaxIdlePerHost: 0,
+    });
+    const resp = await fetch(`https://localhost:${servePort}/`, { client });
+    assertEquals(await resp.text(), "Hello World");
+
+    client.close();
+    ac.abort();
+    await server.finished;
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, net: true } },
+  async function httpServerWithHttp3AdvertisesAltSvc() {
+    const ac = new AbortController();
+    const { promise, resolve } = Promise.withResolvers<void>();
+    const hostname = "127.0.0.1";
+
+    const server = Deno.serve({
+      handler: () => new Response("Hello World"),
+      hostname,
+      port: servePort,
+      signal: ac.signal,
+      onListen: onListen(resolve),
+      onError: createOnErrorCb(ac),
+      cert: Deno.readTextFileSync("tests/testdata/tls/localhost.crt"),
+      key: Deno.readTextFileSync("tests/testdata/tls/localhost.key"),
+      http3: true,
+    });
+
+    await promise;
+    const caCert = Deno.readTextFileSync("tests/testdata/tls/RootCA.pem");
+    const client = Deno.createHttpClient({ caCerts: [caCert] });
+    const resp = await fetch(`https://localhost:${servePort}/`, {
+      client,
+      headers: { "connection": "close" },
+    });
+
+    assertEquals(resp.headers.get("alt-svc"), `h3=":${servePort}"; ma=86400`);
+    assertEquals(await resp.text(), "Hello World");
+    // Replaces the certificate of the HTTP/3 endpoint too
+    server.setCertificate({
+      cert: Deno.readTextFileSync("tests/testdata/tls/localhost_ecc.crt"),
+      key: Deno.readTextFileSync("tests/testdata/tls/localhost_ecc.key"),
+      sniCertificates: {
+        localhost: {
+          cert: Deno.readTextFileSync("tests/testdata/tls/localhost.crt"),
+          key: Deno.readTextFileSync("tests/testdata/tls/localhost.key"),
+        },
+      },
+    });
+
+    client.close();
+    ac.abort();
+    await server.finished;
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  function httpServerHttp3RequiresCertAndKey() {
+    assertThrows(
+      () =>
+        Deno.serve({
+          handler: () => new Response(),
+          port: servePort,
+          http3: true,
+        }),
+      TypeError,
+      "Both 'cert' and 'key' must be provided to enable HTTP/3",
+    );
+  },
+);
+
 Deno.test(
   { permissions: { net: true, write: true, read: true } },
   async function httpServerRequestCLTE() {
@@ -3974,6 +4575,77 @@ Deno.test(
   },
 );
 
+Deno.test(
+  {
+    permissions: { net: true, run: true },
+    ignore: Deno.build.os === "windows",
+  },
+  async function httpServeCurlH2CUpgrade() {
+    const writeOut = ["-s", "-w", " %{http_version}"];
+    const ac = new AbortController();
+    let server = Deno.serve(
+      { port: servePort, signal: ac.signal },
+      () => new Response("hello"),
+    );
+
+    // Servers don't upgrade to h2c unless asked to
+    assertEquals(
+      "hello 1.1",
+      await curlRequest([
+        `http://localhost:${servePort}/path`,
+        "--http2",
+        ...writeOut,
+      ]),
+    );
+    ac.abort();
+    await server.finished;
+
+    const ac2 = new AbortController();
+    server = Deno.serve(
+      { port: servePort, signal: ac2.signal, h2c: true },
+      async (req) => new Response(`${req.method} ${await req.text()}`),
+    );
+
+    // The request which asks to upgrade is answered over HTTP/2
+    assertEquals(
+      "GET  2",
+      await curlRequest([
+        `http://localhost:${servePort}/path`,
+        "--http2",
+        ...writeOut,
+      ]),
+    );
+    // A request with a body isn't upgraded
+    assertEquals(
+      "POST body 1.1",
+      await curlRequest([
+        `http://localhost:${servePort}/path`,
+        "--http2",
+        "-d",
+        "body",
+        ...writeOut,
+      ]),
+    );
+
+    ac2.abort();
+    await server.finished;
+  },
+);
+
+Deno.test(function httpServeH2CWithTls() {
+  assertThrows(
+    () =>
+      Deno.serve({
+        port: servePort,
+        cert: "",
+        key: "",
+        h2c: true,
+      }, () => new Response()),
+    TypeError,
+    "Unsupported 'h2c' option provided",
+  );
+});
+
 // TODO(mmastrac): This test should eventually use fetch, when we support trailers there.
 // This test is ignored because it's flaky and relies on cURL's verbose output.
 Deno.test(
@@ -4270,3 +4942,111 @@ Deno.test({
   assertEquals(hostname, "0.0.0.0");
   await server.shutdown();
 });
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerServerSentEvents() {
+    const ac = new AbortController();
+    const listeningDeferred = Promise.withResolvers<void>();
+    let writer: Deno.ServerSentEventWriter | undefined;
+
+    const server = Deno.serve({
+      handler: () => {
+        const stream = Deno.createServerSentEventStream({
+          heartbeatInterval: 0,
+          headers: { "x-stream": "events" },
+        });
+        writer = stream.writer;
+        return stream.response;
+      },
+      port: servePort,
+      signal: ac.signal,
+      onListen: () => listeningDeferred.resolve(),
+      onError: createOnErrorCb(ac),
+    });
+
+    await listeningDeferred.promise;
+    const resp = await fetch(`http://127.0.0.1:${servePort}/`, {
+      headers: { "accept-encoding": "gzip" },
+    });
+    assertEquals(resp.headers.get("content-type"), "text/event-stream");
+    assertEquals(resp.headers.get("content-encoding"), null);
+    assertEquals(resp.headers.get("x-stream"), "events");
+
+    await writer!.send({ event: "greeting", id: "1", data: "hello\nworld" });
+    await writer!.send({ comment: "keep going", data: "" });
+    await assertRejects(() => writer!.send({ event: "a\nb" }), TypeError);
+    writer!.close();
+    await writer!.finished;
+
+    assertEquals(
+      await resp.text(),
+      "event: greeting\nid: 1\ndata: hello\ndata: world\n\n" +
+        ": keep going\ndata: \n\n",
+    );
+    ac.abort();
+    await server.finished;
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerServerSentEventsDisconnect() {
+    const ac = new AbortController();
+    const listeningDeferred = Promise.withResolvers<void>();
+    const writerDeferred = Promise.withResolvers<Deno.ServerSentEventWriter>();
+
+    const server = Deno.serve({
+      handler: () => {
+        const { response, writer } = Deno.createServerSentEventStream({
+          heartbeatInterval: 10,
+          highWaterMark: 1,
+        });
+        writerDeferred.resolve(writer);
+        return response;
+      },
+      port: servePort,
+      signal: ac.signal,
+      onListen: () => listeningDeferred.resolve(),
+      onError: createOnErrorCb(ac),
+    });
+
+    await listeningDeferred.promise;
+    const clientAc = new AbortController();
+    const resp = await fetch(`http://127.0.0.1:${servePort}/`, {
+      signal: clientAc.signal,
+    });
+    const reader = resp.body!.getReader();
+    // With no events sent, a heartbeat comment arrives
+    const { value } = await reader.read();
+    assertEquals(new TextDecoder().decode(value), ":\n\n");
+
+    const writer = await writerDeferred.promise;
+    clientAc.abort();
+    await writer.finished;
+    await assertRejects(
+      () => writer.send({ data: "too late" }),
+      Deno.errors.BrokenPipe,
+    );
+    ac.abort();
+    await server.finished;
+  },
+);
+
+Deno.test(async function serverSentEventStreamLargeOptions() {
+  // Values beyond what the stream can use are clamped rather than rejected
+  const { response, writer } = Deno.createServerSentEventStream({
+    heartbeatInterval: 0xFFFFFFFF,
+    highWaterMark: Number.MAX_SAFE_INTEGER,
+  });
+  await writer.send({ data: "hello" });
+  writer.close();
+  assertEquals(await response.text(), "data: hello\n\n");
+  await writer.finished;
+
+  assertThrows(
+    () => Deno.createServerSentEventStream({ highWaterMark: -1 }),
+    RangeError,
+    "Invalid highWaterMark: -1",
+  );
+});
@@ -23,7 +23,11 @@ async function sleep(msec: number) {
 }
 
 function listenTls(
-  options?: { alpnProtocols?: string[]; reusePort?: boolean },
+  options?: {
+    alpnProtocols?: string[];
+    reusePort?: boolean;
+    ocspResponse?: Uint8Array;
+  },
 ): { listener: Deno.TlsListener; port: number; hostname: string } {
   const tlsOptions = { port: 0, hostname: "localhost", cert, key, ...options };
   const listener = Deno.listenTls(tlsOptions);
@@ -1296,6 +1300,30 @@ Deno.test(
   },
 );
 
+Deno.test(
+  { permissions: { net: true } },
+  async function tlsSetSocketOptions() {
+    const { listener, port, hostname } = listenTls();
+    listener.setSocketOptions({ recvBufferSize: 65536 });
+    assert(listener.getSocketOptions().recvBufferSize! >= 65536);
+
+    const [serverConn, clientConn] = await Promise.all([
+      listener.accept(),
+      Deno.connectTls({ hostname, port, caCerts }),
+    ]);
+    clientConn.setSocketOptions({ noDelay: true, keepAlive: true });
+    const options = clientConn.getSocketOptions();
+    assertEquals(options.noDelay, true);
+    assertEquals(options.keepAlive, true);
+    serverConn.setSocketOptions({ linger: 0 });
+    assertEquals(serverConn.getSocketOptions().linger, 0);
+
+    clientConn.close();
+    serverConn.close();
+    listener.close();
+  },
+);
+
 Deno.test({
   ignore: Deno.build.os !== "linux",
   permissions: { net: true },
@@ -1404,3 +1432,178 @@ Deno.test(
     listener.close();
   },
 );
+
+Deno.test(
+  { permissions: { net: true, read: true } },
+  async function connectTLSRevocation() {
+    const { listener, hostname, port } = listenTls();
+    const serverPromise = (async () => {
+      for await (const conn of listener) {
+        await conn.handshake().catch(() => {});
+        conn.close();
+      }
+    })();
+
+    const handshake = async (revocation: Deno.RevocationCheckOptions) => {
+      const conn = await Deno.connectTls({
+        hostname,
+        port,
+        caCerts,
+        revocation,
+      });
+      try {
+        await conn.handshake();
+      } finally {
+        conn.close();
+      }
+    };
+    const crl = (name: string) =>
+      Deno.readTextFileSync(`tests/testdata/tls/${name}`);
+
+    await handshake({ crls: [crl("RootCA.crl")] });
+    await assertRejects(
+      () => handshake({ crls: [crl("localhost_revoked.crl")] }),
+      Deno.errors.InvalidData,
+    );
+    await assertRejects(
+      () =>
+        handshake({
+          crls: [crl("localhost_revoked.crl")],
+          mode: "soft-fail",
+        }),
+      Deno.errors.InvalidData,
+    );
+
+    listener.close();
+    await serverPromise;
+  },
+);
+
+Deno.test(
+  { permissions: { net: true, read: true } },
+  async function listenTLSOcspResponse() {
+    // The response is stapled as is, so it doesn't need to be a real one.
+    const { listener, hostname, port } = listenTls({
+      ocspResponse: new Uint8Array([0x30, 0x03, 0x0a, 0x01, 0x00]),
+    });
+    const acceptPromise = listener.accept();
+    const conn = await Deno.connectTls({ hostname, port, caCerts });
+    const serverConn = await acceptPromise;
+    await Promise.all([conn.handshake(), serverConn.handshake()]);
+    conn.close();
+    serverConn.close();
+    listener.close();
+  },
+);
+
+Deno.test(
+  { permissions: { net: true, read: true } },
+  async function listenTLSSniCertificates() {
+    const listener = Deno.listenTls({
+      port: 0,
+      hostname: "localhost",
+      // The CA certificate is not valid for "localhost"
+      cert: Deno.readTextFileSync("tests/testdata/tls/RootCA.crt"),
+      key: Deno.readTextFileSync("tests/testdata/tls/RootCA.key"),
+      sniCertificates: { "LOCALHOST": { cert, key } },
+    });
+    const { port } = listener.addr;
+    const serverPromise = (async () => {
+      for await (const conn of listener) {
+        try {
+          await conn.handshake();
+          await conn.write(new Uint8Array([1]));
+        } catch {
+          // The client rejected the certificate
+        }
+        conn.close();
+      }
+    })();
+
+    const handshake = async () => {
+      const conn = await Deno.connectTls({
+        hostname: "localhost",
+        port,
+        caCerts,
+      });
+      try {
+        await conn.handshake();
+        assertEquals(await conn.read(new Uint8Array(1)), 1);
+      } finally {
+        conn.close();
+      }
+    };
+
+    await handshake();
+    // Replacing the certificate replaces the ones of the server names too
+    listener.setCertificate({
+      cert: Deno.readTextFileSync("tests/testdata/tls/RootCA.crt"),
+      key: Deno.readTextFileSync("tests/testdata/tls/RootCA.key"),
+    });
+    await assertRejects(handshake, Deno.errors.InvalidData);
+    listener.setCertificate({
+      cert: Deno.readTextFileSync("tests/testdata/tls/RootCA.crt"),
+      key: Deno.readTextFileSync("tests/testdata/tls/RootCA.key"),
+      sniCertificates: {
+        "*.localhost": { cert, key },
+        localhost: { cert, key },
+      },
+    });
+    await handshake();
+
+    listener.close();
+    await serverPromise;
+  },
+);
+
+Deno.test(
+  { permissions: { net: true, read: true } },
+  async function listenTLSSetCertificate() {
+    const { listener, hostname, port } = listenTls();
+    const serverPromise = (async () => {
+      for await (const conn of listener) {
+        try {
+          await conn.handshake();
+          await conn.write(new Uint8Array([1]));
+        } catch {
+          // The client rejected the certificate
+        }
+        conn.close();
+      }
+    })();
+
+    const handshake = async () => {
+      const conn = await Deno.connectTls({ hostname, port, caCerts });
+      try {
+        await conn.handshake();
+        assertEquals(await conn.read(new Uint8Array(1)), 1);
+      } finally {
+        conn.close();
+      }
+    };
+
+    await handshake();
+    // The CA certificate is not valid for "localhost"
+    listener.setCertificate({
+      cert: Deno.readTextFileSync("tests/testdata/tls/RootCA.crt"),
+      key: Deno.readTextFileSync("tests/testdata/tls/RootCA.key"),
+    });
+    await assertRejects(handshake, Deno.errors.InvalidData);
+    listener.setCertificate({
+      cert: Deno.readTextFileSync("tests/testdata/tls/localhost_ecc.crt"),
+      key: Deno.readTextFileSync("tests/testdata/tls/localhost_ecc.key"),
+    });
+    await handshake();
+    assertThrows(
+      () =>
+        listener.setCertificate({
+          cert: Deno.readTextFileSync("tests/testdata/tls/invalid.crt"),
+          key,
+        }),
+      Deno.errors.InvalidData,
+    );
+
+    listener.close();
+    await serverPromise;
+  },
+);
@@ -1,5 +1,11 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
-import { assert, assertEquals, assertThrows, fail } from "./test_util.ts";
+import {
+  assert,
+  assertEquals,
+  assertRejects,
+  assertThrows,
+  fail,
+} from "./test_util.ts";
 
 const servePort = 4248;
 const serveUrl = `ws://localhost:${servePort}/`;
@@ -436,6 +442,49 @@ Deno.test(
   },
 );
 
+Deno.test(async function websocketServerPing() {
+  const ac = new AbortController();
+  const listeningDeferred = Promise.withResolvers<void>();
+  const latencyDeferred = Promise.withResolvers<number>();
+
+  const server = Deno.serve({
+    handler: (req) => {
+      const { socket, response } = Deno.upgradeWebSocket(req);
+      socket.onopen = async () => {
+        await assertRejects(
+          () => socket.ping(new Uint8Array(126)),
+          TypeError,
+          "Ping payload must be at most 125 bytes",
+        );
+        assert(socket instanceof WebSocket);
+        // Each ping is resolved by the pong echoing its payload
+        const [latency] = await Promise.all([
+          socket.ping("hello"),
+          socket.ping(new Uint8Array([1, 2, 3])),
+        ]);
+        latencyDeferred.resolve(latency);
+        socket.close();
+      };
+      socket.onclose = () => ac.abort();
+      return response;
+    },
+    signal: ac.signal,
+    onListen: () => listeningDeferred.resolve(),
+    hostname: "localhost",
+    port: servePort,
+  });
+
+  await listeningDeferred.promise;
+  const ws = new WebSocket(serveUrl);
+  const latency = await latencyDeferred.promise;
+  assert(latency >= 0);
+  // `ping` is not part of the standard `WebSocket` interface
+  assert(!("ping" in ws));
+  assert(!("ping" in WebSocket.prototype));
+  ws.onclose = () => {};
+  await server.finished;
+});
+
 Deno.test("invalid scheme", () => {
   assertThrows(() => new WebSocket("foo://localhost:4242"));
 });
@@ -805,3 +854,179 @@ Deno.test("Close connection", async () => {
   await server.finished;
   conn.close();
 });
+
+Deno.test(
+  { sanitizeOps: false, sanitizeResources: false },
+  async function websocketPermessageDeflate() {
+    const ac = new AbortController();
+    const listeningDeferred = Promise.withResolvers<void>();
+    const message = "Hello ".repeat(10_000);
+
+    const server = Deno.serve({
+      handler: (req) => {
+        const { socket, response } = Deno.upgradeWebSocket(req, {
+          compression: {
+            serverNoContextTakeover: true,
+            clientMaxWindowBits: 12,
+          },
+        });
+        socket.onmessage = (e) => socket.send(e.data);
+        socket.onclose = () => ac.abort();
+        socket.onerror = () => fail();
+        assertEquals(
+          socket.extensions,
+          "permessage-deflate; server_no_context_takeover; client_max_window_bits=10",
+        );
+        return response;
+      },
+      signal: ac.signal,
+      onListen: () => listeningDeferred.resolve(),
+      hostname: "localhost",
+      port: servePort,
+    });
+
+    await listeningDeferred.promise;
+    const ws = new WebSocketStream(serveUrl, {
+      compression: { clientMaxWindowBits: 10 },
+    });
+    const { readable, writable, extensions } = await ws.opened;
+    assertEquals(
+      extensions,
+      "permessage-deflate; server_no_context_takeover; client_max_window_bits=10",
+    );
+    const writer = writable.getWriter();
+    const reader = readable.getReader();
+    for (const data of [message, new TextEncoder().encode(message)]) {
+      await writer.write(data);
+      const { value } = await reader.read();
+      assertEquals(value, data);
+    }
+    ws.close();
+    await server.finished;
+  },
+);
+
+Deno.test(
+  { sanitizeOps: false, sanitizeResources: false },
+  async function websocketClientPermessageDeflate() {
+    const ac = new AbortController();
+    const listeningDeferred = Promise.withResolvers<void>();
+    const message = "Hello ".repeat(10_000);
+
+    const server = Deno.serve({
+      handler: (req) => {
+        const { socket, response } = Deno.upgradeWebSocket(req, {
+          compression: true,
+        });
+        socket.onmessage = (e) => socket.send(e.data);
+        socket.onclose = () => ac.abort();
+        socket.onerror = () => fail();
+        return response;
+      },
+      signal: ac.signal,
+      onListen: () => listeningDeferred.resolve(),
+      hostname: "localhost",
+      port: servePort,
+    });
+
+    await listeningDeferred.promise;
+    const ws = new WebSocket(serveUrl, { compression: true });
+    const messageDeferred = Promise.withResolvers<string>();
+    ws.onopen = () => ws.send(message);
+    ws.onmessage = (e) => messageDeferred.resolve(e.data);
+    ws.onerror = () => fail();
+    assertEquals(await messageDeferred.promise, message);
+    assert(ws.extensions.startsWith("permessage-deflate"), ws.extensions);
+    ws.close();
+    await server.finished;
+  },
+);
+
+Deno.test(function websocketPermessageDeflateInvalidOptions() {
+  const request = new Request(serveUrl, {
+    headers: {
+      connection: "Upgrade",
+      upgrade: "websocket",
+      "sec-websocket-key": "dGhlIHNhbXBsZSBub25jZQ==",
+      "sec-websocket-extensions": "permessage-deflate",
+    },
+  });
+  assertThrows(
+    () =>
+      Deno.upgradeWebSocket(request, {
+        compression: { serverMaxWindowBits: 16 },
+      }),
+    TypeError,
+    "Invalid 'serverMaxWindowBits': must be between 9 and 15, received 16",
+  );
+});
+
+Deno.test(
+  { sanitizeOps: false, sanitizeResources: false },
+  async function websocketOptionsHeadersAndProtocols() {
+    const ac = new AbortController();
+    const listeningDeferred = Promise.withResolvers<void>();
+
+    const server = Deno.serve({
+      handler: (req) => {
+        assertEquals(req.headers.get("authorization"), "Bearer token");
+        assertEquals(req.headers.get("cookie"), "a=b");
+        assertEquals(req.headers.get("sec-websocket-protocol"), "foo, bar");
+        const { socket, response } = Deno.upgradeWebSocket(req, {
+          protocol: "bar",
+        });
+        socket.onopen = () => socket.close();
+        socket.onclose = () => ac.abort();
+        return response;
+      },
+      signal: ac.signal,
+      onListen: () => listeningDeferred.resolve(),
+      hostname: "localhost",
+      port: servePort,
+    });
+
+    await listeningDeferred.promise;
+    const deferred = Promise.withResolvers<void>();
+    const ws = new WebSocket(serveUrl, {
+      protocols: ["foo", "bar"],
+      headers: { authorization: "Bearer token", cookie: "a=b" },
+    });
+    ws.onerror = () => fail();
+    ws.onopen = () => {
+      assertEquals(ws.protocol, "bar");
+      assertEquals(ws.extensions, "");
+    };
+    ws.onclose = () => deferred.resolve();
+    await Promise.all([deferred.promise, server.finished]);
+  },
+);
+
+Deno.test(
+  { sanitizeOps: false, sanitizeResources: false },
+  async function websocketUnrequestedProtocol() {
+    const listener = Deno.listen({ hostname: "localhost", port: servePort });
+    (async () => {
+      const conn = await listener.accept();
+      await conn.read(new Uint8Array(4096));
+      await conn.write(
+        new TextEncoder().encode(
+          "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\nSec-WebSocket-Protocol: baz\r\n\r\n",
+        ),
+      );
+    })();
+
+    const deferred = Promise.withResolvers<void>();
+    const ws = new WebSocket(serveUrl, ["foo"]);
+    ws.onopen = () => fail();
+    ws.onerror = (e) => {
+      assert(e instanceof ErrorEvent);
+      assertEquals(
+        e.message,
+        "NetworkError: failed to connect to WebSocket: server selected protocol 'baz', which was not requested",
+      );
+      deferred.resolve();
+    };
+    await deferred.promise;
+    listener.close();
+  },
+);
@@ -473,6 +473,7 @@ pub struct LspClientBuilder {
   deno_dir: TempDir,
   envs: HashMap<OsString, OsString>,
   collect_perf: bool,
+  profile_path: Option<PathRef>,
 }
 
 impl LspClientBuilder {
@@ -492,6 +493,7 @@ impl LspClientBuilder {
       deno_dir,
       envs: Default::default(),
       collect_perf: false,
+      profile_path: None,
     }
   }
 
@@ -527,6 +529,12 @@ impl LspClientBuilder {
     self
   }
 
+  /// Write a profile of the server to this file on shutdown.
+  pub fn profile(mut self, path: impl AsRef<Path>) -> Self {
+    self.profile_path = Some(PathRef::new(path));
+    self
+  }
+
   /// Whether to use the synchronization messages to better sync diagnostics
   /// between the test client and server.
   pub fn use_diagnostic_sync(mut self, value: bool) -> Self {
@@ -561,6 +569,9 @@ impl LspClientBuilder {
     if self.log_debug {
       args.push("--log-level=debug".to_string());
     }
+    if let Some(profile_path) = &self.profile_path {
+      args.push(format!("--profile={profile_path}"));
+    }
     command
       .env("DENO_DIR", deno_dir.path())
       .env("NPM_CONFIG_REGISTRY", npm_registry_url())
@@ -643,6 +654,7 @@ impl LspClientBuilder {
       deno_dir,
       stderr_lines_rx,
       config: json!("{}"),
+      folder_configs: HashMap::new(),
       supports_workspace_configuration: false,
       perf: perf_rx.map(Perf::new),
     })
@@ -742,6 +754,9 @@ pub struct LspClient {
   root_dir: PathRef,
   stderr_lines_rx: Option<mpsc::Receiver<String>>,
   config: serde_json::Value,
+  /// The settings of workspace folders, keyed by their uri, which are
+  /// returned for configuration items scoped to them instead of `config`.
+  folder_configs: HashMap<String, serde_json::Value>,
   supports_workspace_configuration: bool,
   perf: Option<Perf>,
 }
@@ -837,6 +852,7 @@ impl LspClient {
           "implementations": false,
           "references": false,
           "referencesAllFunctions": false,
+          "referencesExportedFunctions": true,
           "test": true,
         },
         "config": null,
@@ -940,15 +956,32 @@ impl LspClient {
     }
   }
 
+  /// Changes the settings of a workspace folder, while the other folders
+  /// keep using the unscoped settings.
+  pub fn change_folder_configuration(
+    &mut self,
+    folder_uri: &Url,
+    config: Value,
+  ) {
+    self.folder_configs.insert(folder_uri.to_string(), config);
+    self.change_configuration(self.config.clone());
+  }
+
   pub fn handle_configuration_request(&mut self) {
     let (id, method, args) = self.read_request::<Value>();
     assert_eq!(method, "workspace/configuration");
     let params = args.as_ref().unwrap().as_object().unwrap();
     let items = params.get("items").unwrap().as_array().unwrap();
-    let config_object = self.config.as_object().unwrap();
     let mut result = vec![];
     for item in items {
       let item = item.as_object().unwrap();
+      let config_object = item
+        .get("scopeUri")
+        .and_then(|uri| uri.as_str())
+        .and_then(|uri| self.folder_configs.get(uri))
+        .unwrap_or(&self.config)
+        .as_object()
+        .unwrap();
       let section = item.get("section").unwrap().as_str().unwrap();
       result.push(config_object.get(section).cloned().unwrap_or_default());
     }
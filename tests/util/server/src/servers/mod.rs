@@ -88,6 +88,8 @@ const H2_GRPC_PORT: u16 = 4246;
 const H2S_GRPC_PORT: u16 = 4247;
 const JSR_REGISTRY_SERVER_PORT: u16 = 4250;
 const PROVENANCE_MOCK_SERVER_PORT: u16 = 4251;
+// a registry other than the default one, for the `"registry"` config option
+const CUSTOM_JSR_REGISTRY_SERVER_PORT: u16 = 4252;
 pub(crate) const PUBLIC_NPM_REGISTRY_PORT: u16 = 4260;
 pub(crate) const PRIVATE_NPM_REGISTRY_1_PORT: u16 = 4261;
 pub(crate) const PRIVATE_NPM_REGISTRY_2_PORT: u16 = 4262;
@@ -136,6 +138,8 @@ pub async fn run_all_servers() {
     jsr_registry::registry_server(JSR_REGISTRY_SERVER_PORT);
   let provenance_mock_server_fut =
     jsr_registry::provenance_mock_server(PROVENANCE_MOCK_SERVER_PORT);
+  let custom_registry_server_fut =
+    jsr_registry::registry_server(CUSTOM_JSR_REGISTRY_SERVER_PORT);
 
   let npm_registry_server_futs =
     npm_registry::public_npm_registry(PUBLIC_NPM_REGISTRY_PORT);
@@ -169,6 +173,7 @@ pub async fn run_all_servers() {
     h2_grpc_server_fut.boxed_local(),
     registry_server_fut.boxed_local(),
     provenance_mock_server_fut.boxed_local(),
+    custom_registry_server_fut.boxed_local(),
   ];
   futures.extend(npm_registry_server_futs);
   futures.extend(private_npm_registry_1_server_futs);